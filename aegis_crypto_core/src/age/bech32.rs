@@ -0,0 +1,114 @@
+//! Bech32 (BIP 173) as used by age for recipients and identities.
+//!
+//! age lifts the 90-character limit, which post-quantum keys far exceed.
+
+use super::AgeError;
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+fn polymod(values: impl Iterator<Item = u8>) -> u32 {
+    let mut checksum = 1u32;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x1ffffff) << 5) ^ (value as u32);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = hrp.iter().map(|c| c >> 5).collect();
+    out.push(0);
+    out.extend(hrp.iter().map(|c| c & 31));
+    out
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0u32;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    let max = (1u32 << to) - 1;
+    for &value in data {
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+/// Encode `data` under the lowercase `hrp`.
+pub(crate) fn encode(hrp: &str, data: &[u8]) -> String {
+    let values = convert_bits(data, 8, 5, true).expect("padding always succeeds");
+    let checksum_input = hrp_expand(hrp.as_bytes())
+        .into_iter()
+        .chain(values.iter().copied())
+        .chain([0u8; 6]);
+    let checksum = polymod(checksum_input) ^ 1;
+    let mut out = String::with_capacity(hrp.len() + 1 + values.len() + 6);
+    out.push_str(hrp);
+    out.push('1');
+    for value in values {
+        out.push(CHARSET[value as usize] as char);
+    }
+    for i in 0..6 {
+        out.push(CHARSET[((checksum >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    out
+}
+
+/// Decode a string, returning its lowercase HRP and data. Mixed case is
+/// rejected.
+pub(crate) fn decode(s: &str) -> Result<(String, Vec<u8>), AgeError> {
+    let malformed = |msg: &str| AgeError::Malformed(format!("bech32: {}", msg));
+    let has_lower = s.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.bytes().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(malformed("mixed case"));
+    }
+    let s = s.to_ascii_lowercase();
+    let separator = s.rfind('1').ok_or_else(|| malformed("missing separator"))?;
+    let (hrp, data) = (&s[..separator], &s[separator + 1..]);
+    if hrp.is_empty() || data.len() < 6 || !hrp.bytes().all(|c| (33..=126).contains(&c)) {
+        return Err(malformed("invalid length or prefix"));
+    }
+    let values = data
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&x| x == c).map(|v| v as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| malformed("invalid character"))?;
+    if polymod(hrp_expand(hrp.as_bytes()).into_iter().chain(values.iter().copied())) != 1 {
+        return Err(malformed("invalid checksum"));
+    }
+    let bytes = convert_bits(&values[..values.len() - 6], 5, 8, false).ok_or_else(|| malformed("invalid padding"))?;
+    Ok((hrp.to_string(), bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bip173_vectors() {
+        assert_eq!(decode("A12UEL5L").unwrap(), ("a".to_string(), vec![]));
+        let (hrp, data) = decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").unwrap();
+        assert_eq!(hrp, "abcdef");
+        assert_eq!(encode(&hrp, &data), "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw");
+        assert!(decode("A1G7SGD8").is_err());
+        assert!(decode("abcdef1Qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").is_err());
+    }
+}
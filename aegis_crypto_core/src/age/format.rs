@@ -0,0 +1,179 @@
+//! The age v1 header: version line, recipient stanzas and header MAC.
+
+use std::io::{ BufRead, Read };
+
+use base64::{ engine::general_purpose::STANDARD_NO_PAD, Engine };
+
+use super::AgeError;
+
+pub(crate) const VERSION_LINE: &str = "age-encryption.org/v1";
+const STANZA_PREFIX: &str = "-> ";
+const MAC_PREFIX: &str = "---";
+const COLUMNS: usize = 64;
+const MAC_LEN: usize = 32;
+/// Upper bound on a header line, well above any stanza body line.
+const MAX_LINE_LEN: usize = 16 * 1024;
+const MAX_STANZAS: usize = 1024;
+
+/// A recipient stanza: a type, its arguments and a binary body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stanza {
+    pub tag: String,
+    pub args: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+impl Stanza {
+    pub fn new(tag: &str, args: Vec<String>, body: Vec<u8>) -> Self {
+        Stanza { tag: tag.to_string(), args, body }
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(STANZA_PREFIX.as_bytes());
+        out.extend_from_slice(self.tag.as_bytes());
+        for arg in &self.args {
+            out.push(b' ');
+            out.extend_from_slice(arg.as_bytes());
+        }
+        out.push(b'\n');
+        let body = STANDARD_NO_PAD.encode(&self.body);
+        let mut lines = body.as_bytes().chunks(COLUMNS).peekable();
+        // The final line is always shorter than a full column, even if empty.
+        while let Some(line) = lines.next() {
+            out.extend_from_slice(line);
+            out.push(b'\n');
+            if lines.peek().is_none() && line.len() == COLUMNS {
+                out.push(b'\n');
+            }
+        }
+        if body.is_empty() {
+            out.push(b'\n');
+        }
+    }
+}
+
+/// Decode unpadded canonical base64, as age requires.
+pub(crate) fn decode_base64(s: &str) -> Result<Vec<u8>, AgeError> {
+    STANDARD_NO_PAD.decode(s).map_err(|_| AgeError::Malformed("invalid base64".into()))
+}
+
+pub(crate) fn encode_base64(data: &[u8]) -> String {
+    STANDARD_NO_PAD.encode(data)
+}
+
+fn is_argument(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| (0x21..=0x7e).contains(&c))
+}
+
+/// A parsed header.
+pub(crate) struct Header {
+    pub stanzas: Vec<Stanza>,
+    /// The header bytes the MAC covers, through `---`.
+    pub mac_input: Vec<u8>,
+    pub mac: [u8; MAC_LEN],
+}
+
+/// Serialize the header up to `---`, ready to be MACed.
+pub(crate) fn write_header(stanzas: &[Stanza]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(VERSION_LINE.as_bytes());
+    out.push(b'\n');
+    for stanza in stanzas {
+        stanza.write(&mut out);
+    }
+    out.extend_from_slice(MAC_PREFIX.as_bytes());
+    out
+}
+
+/// Append the MAC line to a header produced by [`write_header`].
+pub(crate) fn finish_header(header: &mut Vec<u8>, mac: &[u8; MAC_LEN]) {
+    header.push(b' ');
+    header.extend_from_slice(encode_base64(mac).as_bytes());
+    header.push(b'\n');
+}
+
+fn read_line<R: BufRead>(reader: &mut R, raw: &mut Vec<u8>) -> Result<String, AgeError> {
+    let mut line = Vec::new();
+    reader.by_ref()
+        .take(MAX_LINE_LEN as u64)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| AgeError::Io(e.to_string()))?;
+    if line.last() != Some(&b'\n') {
+        return Err(AgeError::Malformed("unterminated header line".into()));
+    }
+    raw.extend_from_slice(&line);
+    line.pop();
+    String::from_utf8(line).map_err(|_| AgeError::Malformed("header is not ASCII".into()))
+}
+
+/// Read a header, leaving `reader` positioned at the payload nonce.
+pub(crate) fn read_header<R: BufRead>(reader: &mut R) -> Result<Header, AgeError> {
+    let mut raw = Vec::new();
+    let version = read_line(reader, &mut raw)?;
+    if version != VERSION_LINE {
+        return Err(
+            if version.starts_with("age-encryption.org/") {
+                AgeError::UnsupportedVersion(version)
+            } else {
+                AgeError::Malformed("not an age file".into())
+            }
+        );
+    }
+
+    let mut stanzas = Vec::new();
+    loop {
+        let start = raw.len();
+        let line = read_line(reader, &mut raw)?;
+        if let Some(rest) = line.strip_prefix(STANZA_PREFIX) {
+            if stanzas.len() == MAX_STANZAS {
+                return Err(AgeError::Malformed("too many stanzas".into()));
+            }
+            let mut args: Vec<String> = rest.split(' ').map(str::to_string).collect();
+            if !args.iter().all(|arg| is_argument(arg)) {
+                return Err(AgeError::Malformed("invalid stanza argument".into()));
+            }
+            let tag = args.remove(0);
+            let mut body = String::new();
+            loop {
+                let line = read_line(reader, &mut raw)?;
+                if line.len() > COLUMNS {
+                    return Err(AgeError::Malformed("stanza body line too long".into()));
+                }
+                body.push_str(&line);
+                if line.len() < COLUMNS {
+                    break;
+                }
+            }
+            stanzas.push(Stanza { tag, args, body: decode_base64(&body)? });
+        } else if let Some(encoded) = line.strip_prefix("--- ") {
+            if stanzas.is_empty() {
+                return Err(AgeError::Malformed("no recipient stanzas".into()));
+            }
+            let mac: [u8; MAC_LEN] = decode_base64(encoded)?
+                .try_into()
+                .map_err(|_| AgeError::Malformed("invalid header MAC".into()))?;
+            raw.truncate(start + MAC_PREFIX.len());
+            return Ok(Header { stanzas, mac_input: raw, mac });
+        } else {
+            return Err(AgeError::Malformed("unexpected header line".into()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_body_wrapping() {
+        for len in [0, 1, 47, 48, 49, 96] {
+            let stanza = Stanza::new("test", vec!["a".into()], vec![7u8; len]);
+            let mut header = write_header(std::slice::from_ref(&stanza));
+            finish_header(&mut header, &[1u8; 32]);
+            let parsed = read_header(&mut header.as_slice()).unwrap();
+            assert_eq!(parsed.stanzas, vec![stanza]);
+            assert_eq!(parsed.mac_input, header[..parsed.mac_input.len()]);
+            assert!(parsed.mac_input.ends_with(b"\n---"));
+        }
+    }
+}
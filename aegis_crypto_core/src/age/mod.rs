@@ -0,0 +1,336 @@
+//! age v1 file encryption (<https://age-encryption.org/v1>).
+//!
+//! Files start with a text header: one stanza per recipient, each wrapping
+//! a random 16-byte file key, followed by an HMAC-SHA256 over the header.
+//! The payload is a 16-byte nonce and 64 KiB ChaCha20-Poly1305 STREAM
+//! chunks (see [`crate::stream`]) under a key derived from the file key.
+//!
+//! Besides age's native X25519 recipients, [`MlKemRecipient`] and
+//! [`HybridRecipient`] add ML-KEM-768 and X25519+ML-KEM-768 stanzas, so a
+//! file can be encrypted to classical and post-quantum recipients at once.
+//! Their Bech32 encodings follow age's plugin conventions; the `age` CLI
+//! would need a matching `age-plugin-aegismlkem` or
+//! `age-plugin-aegishybrid` binary to use them. ASCII armor and scrypt
+//! passphrase stanzas are not supported.
+
+mod bech32;
+mod format;
+mod recipients;
+
+pub use format::Stanza;
+pub use recipients::{
+    HybridIdentity,
+    HybridRecipient,
+    MlKemIdentity,
+    MlKemRecipient,
+    X25519Identity,
+    X25519Recipient,
+};
+
+use std::io::{ self, BufRead, Read, Write };
+
+use chacha20poly1305::{ ChaCha20Poly1305, KeyInit };
+use hkdf::Hkdf;
+use hmac::{ Hmac, Mac };
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::registry::AlgorithmError;
+use crate::stream::{ ChunkOpener, ChunkSealer, StreamError, DEFAULT_CHUNK_SIZE };
+
+/// Length of the file key wrapped in each stanza.
+pub const FILE_KEY_LEN: usize = 16;
+const NONCE_LEN: usize = 16;
+
+/// The symmetric key a file is encrypted under.
+pub type FileKey = [u8; FILE_KEY_LEN];
+
+/// age error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgeError {
+    Malformed(String),
+    UnsupportedVersion(String),
+    InvalidRecipient(String),
+    InvalidIdentity(String),
+    NoRecipients,
+    /// None of the identities could unwrap a stanza.
+    NoMatchingIdentity,
+    /// The header MAC did not verify under the unwrapped file key.
+    HeaderMac,
+    Stream(StreamError),
+    Io(String),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for AgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgeError::Malformed(msg) => write!(f, "Malformed age file: {}", msg),
+            AgeError::UnsupportedVersion(v) => write!(f, "Unsupported age version {}", v),
+            AgeError::InvalidRecipient(msg) => write!(f, "Invalid recipient: {}", msg),
+            AgeError::InvalidIdentity(msg) => write!(f, "Invalid identity: {}", msg),
+            AgeError::NoRecipients => write!(f, "No recipients given"),
+            AgeError::NoMatchingIdentity => write!(f, "No identity matched any recipient"),
+            AgeError::HeaderMac => write!(f, "Header MAC is invalid"),
+            AgeError::Stream(e) => write!(f, "{}", e),
+            AgeError::Io(msg) => write!(f, "I/O error: {}", msg),
+            AgeError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AgeError {}
+
+impl From<AlgorithmError> for AgeError {
+    fn from(e: AlgorithmError) -> Self {
+        AgeError::Algorithm(e)
+    }
+}
+
+impl From<StreamError> for AgeError {
+    fn from(e: StreamError) -> Self {
+        AgeError::Stream(e)
+    }
+}
+
+impl From<io::Error> for AgeError {
+    fn from(e: io::Error) -> Self {
+        AgeError::Io(e.to_string())
+    }
+}
+
+impl From<AgeError> for io::Error {
+    fn from(e: AgeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+/// Something a file key can be wrapped to.
+pub trait Recipient {
+    fn wrap_file_key(&self, file_key: &FileKey) -> Result<Vec<Stanza>, AgeError>;
+}
+
+/// Something that can unwrap file keys.
+pub trait Identity {
+    /// `None` if the stanza is not addressed to this identity.
+    fn unwrap_stanza(&self, stanza: &Stanza) -> Option<Result<FileKey, AgeError>>;
+}
+
+fn hkdf(salt: &[u8], file_key: &FileKey, info: &[u8]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>
+        ::new(Some(salt), file_key)
+        .expand(info, &mut key)
+        .expect("32 is a valid HKDF-SHA256 output length");
+    key
+}
+
+fn header_mac(file_key: &FileKey) -> Hmac<Sha256> {
+    let mut key = hkdf(&[], file_key, b"header");
+    let mac = <Hmac<Sha256> as Mac>::new_from_slice(&key).expect("HMAC accepts any key length");
+    key.zeroize();
+    mac
+}
+
+fn payload_cipher(file_key: &FileKey, nonce: &[u8; NONCE_LEN]) -> ChaCha20Poly1305 {
+    let mut key = hkdf(nonce, file_key, b"payload");
+    let cipher = ChaCha20Poly1305::new_from_slice(&key).expect("32-byte key");
+    key.zeroize();
+    cipher
+}
+
+/// Build the header and payload sealer for a new file.
+fn seal_header(recipients: &[&dyn Recipient]) -> Result<(Vec<u8>, ChunkSealer), AgeError> {
+    if recipients.is_empty() {
+        return Err(AgeError::NoRecipients);
+    }
+    let mut file_key: FileKey = [0u8; FILE_KEY_LEN];
+    getrandom::fill(&mut file_key).expect("RNG failed");
+    let mut stanzas = Vec::new();
+    for recipient in recipients {
+        stanzas.extend(recipient.wrap_file_key(&file_key)?);
+    }
+
+    let mut header = format::write_header(&stanzas);
+    let mut mac = header_mac(&file_key);
+    mac.update(&header);
+    format::finish_header(&mut header, &mac.finalize().into_bytes().into());
+
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).expect("RNG failed");
+    header.extend_from_slice(&nonce);
+    let sealer = ChunkSealer::new(payload_cipher(&file_key, &nonce), DEFAULT_CHUNK_SIZE);
+    file_key.zeroize();
+    Ok((header, sealer))
+}
+
+/// Read the header and nonce, unwrap the file key and check the MAC.
+fn open_header<R: BufRead>(reader: &mut R, identities: &[&dyn Identity]) -> Result<ChunkOpener, AgeError> {
+    let header = format::read_header(reader)?;
+    let mut file_key = identities
+        .iter()
+        .find_map(|identity| header.stanzas.iter().find_map(|stanza| identity.unwrap_stanza(stanza)))
+        .ok_or(AgeError::NoMatchingIdentity)??;
+
+    let mut mac = header_mac(&file_key);
+    mac.update(&header.mac_input);
+    if mac.verify_slice(&header.mac).is_err() {
+        file_key.zeroize();
+        return Err(AgeError::HeaderMac);
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    let read = reader.read_exact(&mut nonce).map_err(|_| AgeError::Stream(StreamError::Truncated));
+    let opener = read.map(|_| ChunkOpener::new(payload_cipher(&file_key, &nonce), DEFAULT_CHUNK_SIZE));
+    file_key.zeroize();
+    opener
+}
+
+/// Encrypt `plaintext` to every recipient.
+pub fn encrypt(recipients: &[&dyn Recipient], plaintext: &[u8]) -> Result<Vec<u8>, AgeError> {
+    let (mut out, mut sealer) = seal_header(recipients)?;
+    sealer.update(plaintext, &mut out)?;
+    sealer.finalize(&mut out)?;
+    Ok(out)
+}
+
+/// Decrypt a complete age file with the first identity that matches.
+pub fn decrypt(identities: &[&dyn Identity], file: &[u8]) -> Result<Vec<u8>, AgeError> {
+    let mut reader = file;
+    let mut opener = open_header(&mut reader, identities)?;
+    let mut out = Vec::new();
+    opener.update(reader, &mut out)?;
+    opener.finalize(&mut out)?;
+    Ok(out)
+}
+
+/// Parse an identity file: one identity per line, with `#` comments and
+/// blank lines ignored.
+pub fn parse_identity_file(contents: &str) -> Result<Vec<Box<dyn Identity>>, AgeError> {
+    let mut identities: Vec<Box<dyn Identity>> = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let upper = line.to_ascii_uppercase();
+        let identity: Box<dyn Identity> = if upper.starts_with("AGE-SECRET-KEY-1") {
+            Box::new(line.parse::<X25519Identity>()?)
+        } else if upper.starts_with("AGE-PLUGIN-AEGISMLKEM-1") {
+            Box::new(line.parse::<MlKemIdentity>()?)
+        } else if upper.starts_with("AGE-PLUGIN-AEGISHYBRID-1") {
+            Box::new(line.parse::<HybridIdentity>()?)
+        } else {
+            return Err(AgeError::InvalidIdentity("unrecognized identity type".into()));
+        };
+        identities.push(identity);
+    }
+    if identities.is_empty() {
+        return Err(AgeError::InvalidIdentity("no identities found".into()));
+    }
+    Ok(identities)
+}
+
+/// Parse a Bech32 recipient of any supported type.
+pub fn parse_recipient(s: &str) -> Result<Box<dyn Recipient>, AgeError> {
+    let s = s.trim();
+    if s.starts_with("age1aegismlkem1") {
+        Ok(Box::new(s.parse::<MlKemRecipient>()?))
+    } else if s.starts_with("age1aegishybrid1") {
+        Ok(Box::new(s.parse::<HybridRecipient>()?))
+    } else {
+        Ok(Box::new(s.parse::<X25519Recipient>()?))
+    }
+}
+
+/// `std::io::Write` adapter producing an age file.
+///
+/// [`EncryptWriter::finish`] must be called to write the final chunk.
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    sealer: ChunkSealer,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    /// Write the header to `inner` and start the payload.
+    pub fn new(mut inner: W, recipients: &[&dyn Recipient]) -> Result<Self, AgeError> {
+        let (header, sealer) = seal_header(recipients)?;
+        inner.write_all(&header)?;
+        Ok(EncryptWriter { inner, sealer })
+    }
+
+    /// Write the final chunk and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut out = Vec::new();
+        self.sealer.finalize(&mut out)?;
+        self.inner.write_all(&out)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut out = Vec::new();
+        self.sealer.update(buf, &mut out)?;
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `std::io::Read` adapter over an age file.
+pub struct DecryptReader<R: BufRead> {
+    inner: R,
+    opener: ChunkOpener,
+    plaintext: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: BufRead> DecryptReader<R> {
+    /// Read the header from `inner` and unwrap the file key.
+    pub fn new(mut inner: R, identities: &[&dyn Identity]) -> Result<Self, AgeError> {
+        let opener = open_header(&mut inner, identities)?;
+        Ok(DecryptReader { inner, opener, plaintext: Vec::new(), position: 0, done: false })
+    }
+}
+
+impl<R: BufRead> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() && !self.done {
+            self.plaintext.clear();
+            self.position = 0;
+            let input = self.inner.fill_buf()?;
+            if input.is_empty() {
+                self.done = true;
+                self.opener.finalize(&mut self.plaintext)?;
+            } else {
+                let n = input.len();
+                self.opener.update(input, &mut self.plaintext)?;
+                self.inner.consume(n);
+            }
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_recipient_round_trip() {
+        let x25519 = X25519Identity::generate();
+        let mlkem = MlKemIdentity::generate().unwrap();
+        let file = encrypt(&[&x25519.to_public(), &mlkem.to_public()], b"shared secret").unwrap();
+        assert_eq!(decrypt(&[&x25519], &file).unwrap(), b"shared secret");
+        assert_eq!(decrypt(&[&mlkem], &file).unwrap(), b"shared secret");
+        let stranger = HybridIdentity::generate().unwrap();
+        assert_eq!(decrypt(&[&stranger], &file), Err(AgeError::NoMatchingIdentity));
+    }
+}
@@ -0,0 +1,412 @@
+//! age recipient types: native X25519 plus ML-KEM-768 and hybrid
+//! X25519+ML-KEM-768 stanzas.
+//!
+//! The post-quantum types use plugin-style encodings (`age1aegismlkem1…`,
+//! `AGE-PLUGIN-AEGISMLKEM-1…`) and `aegis-` stanza tags, so they never
+//! collide with types defined by age itself.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chacha20poly1305::{ aead::{ Aead, KeyInit }, ChaCha20Poly1305, Nonce };
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{ PublicKey, StaticSecret };
+use zeroize::Zeroize;
+
+use super::format::{ decode_base64, encode_base64, Stanza };
+use super::{ bech32, AgeError, FileKey, Identity, Recipient, FILE_KEY_LEN };
+use crate::registry::{ self, AlgorithmId };
+
+const X25519_TAG: &str = "X25519";
+const X25519_INFO: &[u8] = b"age-encryption.org/v1/X25519";
+const X25519_RECIPIENT_HRP: &str = "age";
+const X25519_IDENTITY_HRP: &str = "age-secret-key-";
+
+const MLKEM_TAG: &str = "aegis-mlkem768";
+const MLKEM_INFO: &[u8] = b"aegis/age/mlkem768";
+const MLKEM_RECIPIENT_HRP: &str = "age1aegismlkem";
+const MLKEM_IDENTITY_HRP: &str = "age-plugin-aegismlkem-";
+
+const HYBRID_TAG: &str = "aegis-mlkem768x25519";
+const HYBRID_INFO: &[u8] = b"aegis/age/mlkem768x25519";
+const HYBRID_RECIPIENT_HRP: &str = "age1aegishybrid";
+const HYBRID_IDENTITY_HRP: &str = "age-plugin-aegishybrid-";
+
+const KEM: AlgorithmId = AlgorithmId::MlKem768;
+const WRAPPED_LEN: usize = FILE_KEY_LEN + 16;
+
+fn wrap_key(salt: &[u8], shared_secret: &[u8], info: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>
+        ::new(Some(salt), shared_secret)
+        .expand(info, &mut key)
+        .expect("32 is a valid HKDF-SHA256 output length");
+    let cipher = ChaCha20Poly1305::new_from_slice(&key).expect("32-byte key");
+    key.zeroize();
+    cipher
+}
+
+/// Wrap the file key under a zero nonce; every wrap key is used once.
+fn wrap(cipher: &ChaCha20Poly1305, file_key: &FileKey) -> Vec<u8> {
+    cipher.encrypt(Nonce::from_slice(&[0u8; 12]), file_key.as_slice()).expect("ChaCha20-Poly1305 cannot fail")
+}
+
+/// Unwrap a stanza body. A body that fails to authenticate was meant for
+/// a different identity, so it yields `None` rather than an error.
+fn unwrap(cipher: &ChaCha20Poly1305, body: &[u8]) -> Option<Result<FileKey, AgeError>> {
+    if body.len() != WRAPPED_LEN {
+        return Some(Err(AgeError::Malformed("invalid stanza body length".into())));
+    }
+    let mut plaintext = cipher.decrypt(Nonce::from_slice(&[0u8; 12]), body).ok()?;
+    let file_key: FileKey = plaintext.as_slice().try_into().expect("length checked");
+    plaintext.zeroize();
+    Some(Ok(file_key))
+}
+
+fn decode_key<const N: usize>(arg: &str) -> Result<[u8; N], AgeError> {
+    decode_base64(arg)?.try_into().map_err(|_| AgeError::Malformed("invalid stanza argument length".into()))
+}
+
+fn parse_bech32(s: &str, hrp: &str, len: usize, invalid: fn(String) -> AgeError) -> Result<Vec<u8>, AgeError> {
+    let (found, data) = bech32::decode(s).map_err(|e| invalid(e.to_string()))?;
+    if found != hrp {
+        return Err(invalid(format!("unexpected prefix {}", found)));
+    }
+    if data.len() != len {
+        return Err(invalid("unexpected key length".into()));
+    }
+    Ok(data)
+}
+
+/// X25519 exchange that rejects low-order points, as age requires.
+fn x25519(secret: &[u8; 32], public: &[u8; 32]) -> Result<[u8; 32], AgeError> {
+    let shared = StaticSecret::from(*secret).diffie_hellman(&PublicKey::from(*public));
+    if !shared.was_contributory() {
+        return Err(AgeError::Malformed("low-order X25519 share".into()));
+    }
+    Ok(shared.to_bytes())
+}
+
+fn x25519_public(secret: &[u8; 32]) -> [u8; 32] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+/// Generate an X25519 key pair as `(secret, public)`.
+fn x25519_keypair() -> ([u8; 32], [u8; 32]) {
+    let mut secret = [0u8; 32];
+    getrandom::fill(&mut secret).expect("RNG failed");
+    (secret, x25519_public(&secret))
+}
+
+/// An age X25519 recipient (`age1…`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct X25519Recipient {
+    public_key: [u8; 32],
+}
+
+impl X25519Recipient {
+    pub fn public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+}
+
+impl fmt::Display for X25519Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32::encode(X25519_RECIPIENT_HRP, &self.public_key))
+    }
+}
+
+impl FromStr for X25519Recipient {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let data = parse_bech32(s, X25519_RECIPIENT_HRP, 32, AgeError::InvalidRecipient)?;
+        Ok(X25519Recipient { public_key: data.try_into().unwrap() })
+    }
+}
+
+impl Recipient for X25519Recipient {
+    fn wrap_file_key(&self, file_key: &FileKey) -> Result<Vec<Stanza>, AgeError> {
+        let (mut ephemeral, share) = x25519_keypair();
+        let shared = x25519(&ephemeral, &self.public_key);
+        ephemeral.zeroize();
+        let mut shared = shared?;
+        let salt = [share, self.public_key].concat();
+        let body = wrap(&wrap_key(&salt, &shared, X25519_INFO), file_key);
+        shared.zeroize();
+        Ok(vec![Stanza::new(X25519_TAG, vec![encode_base64(&share)], body)])
+    }
+}
+
+/// An age X25519 identity (`AGE-SECRET-KEY-1…`).
+pub struct X25519Identity {
+    secret_key: [u8; 32],
+}
+
+impl X25519Identity {
+    pub fn generate() -> Self {
+        X25519Identity { secret_key: x25519_keypair().0 }
+    }
+
+    pub fn to_public(&self) -> X25519Recipient {
+        X25519Recipient { public_key: x25519_public(&self.secret_key) }
+    }
+}
+
+impl fmt::Display for X25519Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32::encode(X25519_IDENTITY_HRP, &self.secret_key).to_ascii_uppercase())
+    }
+}
+
+impl FromStr for X25519Identity {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let data = parse_bech32(s, X25519_IDENTITY_HRP, 32, AgeError::InvalidIdentity)?;
+        Ok(X25519Identity { secret_key: data.try_into().unwrap() })
+    }
+}
+
+impl Identity for X25519Identity {
+    fn unwrap_stanza(&self, stanza: &Stanza) -> Option<Result<FileKey, AgeError>> {
+        if stanza.tag != X25519_TAG {
+            return None;
+        }
+        let result = (|| {
+            let [share] = stanza.args.as_slice() else {
+                return Err(AgeError::Malformed("X25519 stanza takes one argument".into()));
+            };
+            let share: [u8; 32] = decode_key(share)?;
+            let mut shared = x25519(&self.secret_key, &share)?;
+            let salt = [share, self.to_public().public_key].concat();
+            let cipher = wrap_key(&salt, &shared, X25519_INFO);
+            shared.zeroize();
+            Ok(cipher)
+        })();
+        match result {
+            Ok(cipher) => unwrap(&cipher, &stanza.body),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl Drop for X25519Identity {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// An ML-KEM-768 recipient (`age1aegismlkem1…`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MlKemRecipient {
+    public_key: Vec<u8>,
+}
+
+impl MlKemRecipient {
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl fmt::Display for MlKemRecipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&bech32::encode(MLKEM_RECIPIENT_HRP, &self.public_key))
+    }
+}
+
+impl FromStr for MlKemRecipient {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let len = KEM.public_key_len().expect("fixed size");
+        let public_key = parse_bech32(s, MLKEM_RECIPIENT_HRP, len, AgeError::InvalidRecipient)?;
+        Ok(MlKemRecipient { public_key })
+    }
+}
+
+impl Recipient for MlKemRecipient {
+    fn wrap_file_key(&self, file_key: &FileKey) -> Result<Vec<Stanza>, AgeError> {
+        let (ciphertext, mut shared) = registry::encapsulate(KEM, &self.public_key)?;
+        let body = wrap(&wrap_key(&ciphertext, &shared, MLKEM_INFO), file_key);
+        shared.zeroize();
+        Ok(vec![Stanza::new(MLKEM_TAG, vec![encode_base64(&ciphertext)], body)])
+    }
+}
+
+/// An ML-KEM-768 identity (`AGE-PLUGIN-AEGISMLKEM-1…`).
+pub struct MlKemIdentity {
+    public_key: Vec<u8>,
+    secret_key: Vec<u8>,
+}
+
+impl MlKemIdentity {
+    pub fn generate() -> Result<Self, AgeError> {
+        let (public_key, secret_key) = registry::keygen(KEM)?;
+        Ok(MlKemIdentity { public_key, secret_key })
+    }
+
+    pub fn to_public(&self) -> MlKemRecipient {
+        MlKemRecipient { public_key: self.public_key.clone() }
+    }
+}
+
+impl fmt::Display for MlKemIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = [self.secret_key.as_slice(), &self.public_key].concat();
+        f.write_str(&bech32::encode(MLKEM_IDENTITY_HRP, &data).to_ascii_uppercase())
+    }
+}
+
+impl FromStr for MlKemIdentity {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let sk_len = KEM.secret_key_len().expect("fixed size");
+        let pk_len = KEM.public_key_len().expect("fixed size");
+        let mut data = parse_bech32(s, MLKEM_IDENTITY_HRP, sk_len + pk_len, AgeError::InvalidIdentity)?;
+        let public_key = data.split_off(sk_len);
+        Ok(MlKemIdentity { public_key, secret_key: data })
+    }
+}
+
+impl Identity for MlKemIdentity {
+    fn unwrap_stanza(&self, stanza: &Stanza) -> Option<Result<FileKey, AgeError>> {
+        if stanza.tag != MLKEM_TAG {
+            return None;
+        }
+        let result = (|| {
+            let [ciphertext] = stanza.args.as_slice() else {
+                return Err(AgeError::Malformed("ML-KEM stanza takes one argument".into()));
+            };
+            let ciphertext = decode_base64(ciphertext)?;
+            let mut shared = registry::decapsulate(KEM, &self.secret_key, &ciphertext)?;
+            let cipher = wrap_key(&ciphertext, &shared, MLKEM_INFO);
+            shared.zeroize();
+            Ok(cipher)
+        })();
+        match result {
+            Ok(cipher) => unwrap(&cipher, &stanza.body),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl Drop for MlKemIdentity {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// A hybrid X25519+ML-KEM-768 recipient (`age1aegishybrid1…`). The file
+/// key stays confidential unless both exchanges are broken.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HybridRecipient {
+    x25519: [u8; 32],
+    mlkem: Vec<u8>,
+}
+
+fn hybrid_salt(share: &[u8; 32], recipient: &[u8; 32], ciphertext: &[u8]) -> Vec<u8> {
+    [share.as_slice(), recipient, ciphertext].concat()
+}
+
+impl fmt::Display for HybridRecipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = [self.x25519.as_slice(), &self.mlkem].concat();
+        f.write_str(&bech32::encode(HYBRID_RECIPIENT_HRP, &data))
+    }
+}
+
+impl FromStr for HybridRecipient {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let len = 32 + KEM.public_key_len().expect("fixed size");
+        let mut data = parse_bech32(s, HYBRID_RECIPIENT_HRP, len, AgeError::InvalidRecipient)?;
+        let mlkem = data.split_off(32);
+        Ok(HybridRecipient { x25519: data.try_into().unwrap(), mlkem })
+    }
+}
+
+impl Recipient for HybridRecipient {
+    fn wrap_file_key(&self, file_key: &FileKey) -> Result<Vec<Stanza>, AgeError> {
+        let (mut ephemeral, share) = x25519_keypair();
+        let dh = x25519(&ephemeral, &self.x25519);
+        ephemeral.zeroize();
+        let mut dh = dh?;
+        let (ciphertext, kem) = registry::encapsulate(KEM, &self.mlkem)?;
+        let mut shared = [kem.as_slice(), &dh].concat();
+        let salt = hybrid_salt(&share, &self.x25519, &ciphertext);
+        let body = wrap(&wrap_key(&salt, &shared, HYBRID_INFO), file_key);
+        shared.zeroize();
+        dh.zeroize();
+        Ok(vec![Stanza::new(HYBRID_TAG, vec![encode_base64(&share), encode_base64(&ciphertext)], body)])
+    }
+}
+
+/// A hybrid X25519+ML-KEM-768 identity (`AGE-PLUGIN-AEGISHYBRID-1…`).
+pub struct HybridIdentity {
+    x25519: X25519Identity,
+    mlkem: MlKemIdentity,
+}
+
+impl HybridIdentity {
+    pub fn generate() -> Result<Self, AgeError> {
+        Ok(HybridIdentity { x25519: X25519Identity::generate(), mlkem: MlKemIdentity::generate()? })
+    }
+
+    pub fn to_public(&self) -> HybridRecipient {
+        HybridRecipient { x25519: self.x25519.to_public().public_key, mlkem: self.mlkem.public_key.clone() }
+    }
+}
+
+impl fmt::Display for HybridIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data = [self.x25519.secret_key.as_slice(), &self.mlkem.secret_key, &self.mlkem.public_key].concat();
+        f.write_str(&bech32::encode(HYBRID_IDENTITY_HRP, &data).to_ascii_uppercase())
+    }
+}
+
+impl FromStr for HybridIdentity {
+    type Err = AgeError;
+
+    fn from_str(s: &str) -> Result<Self, AgeError> {
+        let sk_len = KEM.secret_key_len().expect("fixed size");
+        let pk_len = KEM.public_key_len().expect("fixed size");
+        let mut data = parse_bech32(s, HYBRID_IDENTITY_HRP, 32 + sk_len + pk_len, AgeError::InvalidIdentity)?;
+        let public_key = data.split_off(32 + sk_len);
+        let secret_key = data.split_off(32);
+        Ok(HybridIdentity {
+            x25519: X25519Identity { secret_key: data.as_slice().try_into().unwrap() },
+            mlkem: MlKemIdentity { public_key, secret_key },
+        })
+    }
+}
+
+impl Identity for HybridIdentity {
+    fn unwrap_stanza(&self, stanza: &Stanza) -> Option<Result<FileKey, AgeError>> {
+        if stanza.tag != HYBRID_TAG {
+            return None;
+        }
+        let result = (|| {
+            let [share, ciphertext] = stanza.args.as_slice() else {
+                return Err(AgeError::Malformed("hybrid stanza takes two arguments".into()));
+            };
+            let share: [u8; 32] = decode_key(share)?;
+            let ciphertext = decode_base64(ciphertext)?;
+            let mut dh = x25519(&self.x25519.secret_key, &share)?;
+            let kem = registry::decapsulate(KEM, &self.mlkem.secret_key, &ciphertext)?;
+            let mut shared = [kem.as_slice(), &dh].concat();
+            let salt = hybrid_salt(&share, &self.x25519.to_public().public_key, &ciphertext);
+            let cipher = wrap_key(&salt, &shared, HYBRID_INFO);
+            shared.zeroize();
+            dh.zeroize();
+            Ok(cipher)
+        })();
+        match result {
+            Ok(cipher) => unwrap(&cipher, &stanza.body),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -0,0 +1,629 @@
+//! Tamper-evident audit log: a SHA3-256 hash chain with signed checkpoints.
+//!
+//! Each [`AuditRecord`] carries the hash of the record before it, so
+//! changing, inserting or removing a record breaks every later link. Every
+//! `interval` records, and on demand, the log signs a [`Checkpoint`] over
+//! the current head with a registry signature key, normally ML-DSA. An
+//! auditor who holds a checkpoint can detect any change to the records it
+//! covers, including truncation below it.
+//!
+//! A log may be kept in memory or in an append-only file:
+//!
+//! ```text
+//! file       := "AEAL" version:u8 entry*
+//! entry      := tag:u8 length:u32be body
+//! record     := sequence:u64be timestamp:u64be prev_hash[32] event      (tag 1)
+//! checkpoint := size:u64be timestamp:u64be head[32] algorithm:u16be
+//!               signature                                               (tag 2)
+//! hash       := SHA3-256("aegis-audit-record-v1" sequence:u64be
+//!               timestamp:u64be prev_hash[32] event)
+//! signed     := "aegis-audit-checkpoint-v1" size:u64be timestamp:u64be head[32]
+//! ```
+//!
+//! The first record's `prev_hash` is all zeros, and so is the head of an
+//! empty log. [`AuditProof`] exports a run of records with the checkpoint
+//! that covers them, for auditors who do not have the whole log.
+
+use std::fs::{ File, OpenOptions };
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use sha3::{ Digest, Sha3_256 };
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use crate::kms;
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+const FILE_MAGIC: &[u8; 4] = b"AEAL";
+const PROOF_MAGIC: &[u8; 4] = b"AEAP";
+const VERSION: u8 = 1;
+const RECORD_TAG: u8 = 1;
+const CHECKPOINT_TAG: u8 = 2;
+const RECORD_LABEL: &[u8] = b"aegis-audit-record-v1";
+const CHECKPOINT_LABEL: &[u8] = b"aegis-audit-checkpoint-v1";
+const RECORD_HEADER_LEN: usize = 8 + 8 + HASH_LEN;
+const CHECKPOINT_HEADER_LEN: usize = 8 + 8 + HASH_LEN + 2;
+
+pub const HASH_LEN: usize = 32;
+
+pub type Hash = [u8; HASH_LEN];
+
+/// Audit log error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditError {
+    Malformed(String),
+    /// The record does not link to the one before it, or is out of
+    /// sequence: a record was modified, inserted or removed.
+    ChainBroken {
+        sequence: u64,
+    },
+    /// A checkpoint does not match the records it covers.
+    CheckpointMismatch {
+        size: u64,
+    },
+    /// A checkpoint signature does not verify under the given key.
+    BadSignature {
+        size: u64,
+    },
+    /// The log is shorter than a checkpoint the caller trusts, or ends in
+    /// a partial entry.
+    Truncated {
+        expected: u64,
+        found: u64,
+    },
+    /// No checkpoint covers the requested records yet.
+    NotCheckpointed,
+    Io(String),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for AuditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditError::Malformed(msg) => write!(f, "Malformed audit log: {}", msg),
+            AuditError::ChainBroken { sequence } => write!(f, "Audit chain broken at record {}", sequence),
+            AuditError::CheckpointMismatch { size } => {
+                write!(f, "Checkpoint at {} records does not match the log", size)
+            }
+            AuditError::BadSignature { size } => write!(f, "Bad signature on checkpoint at {} records", size),
+            AuditError::Truncated { expected, found } => {
+                write!(f, "Audit log truncated: expected {} records, found {}", expected, found)
+            }
+            AuditError::NotCheckpointed => write!(f, "Records are not covered by a checkpoint"),
+            AuditError::Io(msg) => write!(f, "Audit log I/O error: {}", msg),
+            AuditError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+impl From<AlgorithmError> for AuditError {
+    fn from(e: AlgorithmError) -> Self {
+        AuditError::Algorithm(e)
+    }
+}
+
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> AuditError + '_ {
+    move |e| AuditError::Io(format!("{}: {}", path.display(), e))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// One logged event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Position in the log, from 0.
+    pub sequence: u64,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub prev_hash: Hash,
+    /// Caller-defined event encoding.
+    pub event: Vec<u8>,
+}
+
+impl AuditRecord {
+    pub fn hash(&self) -> Hash {
+        let mut hasher = Sha3_256::new();
+        hasher.update(RECORD_LABEL);
+        hasher.update(self.sequence.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hasher.update(self.prev_hash);
+        hasher.update(&self.event);
+        hasher.finalize().into()
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.sequence.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.prev_hash);
+        out.extend_from_slice(&self.event);
+    }
+
+    fn decode(body: &[u8]) -> Result<Self, AuditError> {
+        if body.len() < RECORD_HEADER_LEN {
+            return Err(AuditError::Malformed("short record".into()));
+        }
+        Ok(AuditRecord {
+            sequence: u64::from_be_bytes(body[..8].try_into().unwrap()),
+            timestamp: u64::from_be_bytes(body[8..16].try_into().unwrap()),
+            prev_hash: body[16..RECORD_HEADER_LEN].try_into().unwrap(),
+            event: body[RECORD_HEADER_LEN..].to_vec(),
+        })
+    }
+}
+
+/// A signed statement that the first `size` records hash to `head`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub size: u64,
+    pub timestamp: u64,
+    /// Hash of record `size - 1`, or zeros for an empty log.
+    pub head: Hash,
+    pub algorithm: AlgorithmId,
+    pub signature: Vec<u8>,
+}
+
+impl Checkpoint {
+    fn message(size: u64, timestamp: u64, head: &Hash) -> Vec<u8> {
+        let mut message = CHECKPOINT_LABEL.to_vec();
+        message.extend_from_slice(&size.to_be_bytes());
+        message.extend_from_slice(&timestamp.to_be_bytes());
+        message.extend_from_slice(head);
+        message
+    }
+
+    pub fn verify(&self, public_key: &[u8]) -> bool {
+        let message = Checkpoint::message(self.size, self.timestamp, &self.head);
+        registry::verify(self.algorithm, public_key, &message, &self.signature)
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.size.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.head);
+        out.extend_from_slice(&self.algorithm.code().to_be_bytes());
+        out.extend_from_slice(&self.signature);
+    }
+
+    fn decode(body: &[u8]) -> Result<Self, AuditError> {
+        if body.len() < CHECKPOINT_HEADER_LEN {
+            return Err(AuditError::Malformed("short checkpoint".into()));
+        }
+        let code = u16::from_be_bytes([body[48], body[49]]);
+        Ok(Checkpoint {
+            size: u64::from_be_bytes(body[..8].try_into().unwrap()),
+            timestamp: u64::from_be_bytes(body[8..16].try_into().unwrap()),
+            head: body[16..48].try_into().unwrap(),
+            algorithm: AlgorithmId::from_code(code)?,
+            signature: body[CHECKPOINT_HEADER_LEN..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(CHECKPOINT_HEADER_LEN + self.signature.len());
+        self.encode(&mut out);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, AuditError> {
+        Checkpoint::decode(data)
+    }
+}
+
+/// The key a log signs its checkpoints with.
+pub struct CheckpointKey {
+    algorithm: AlgorithmId,
+    public_key: Vec<u8>,
+    secret_key: Zeroizing<Vec<u8>>,
+}
+
+impl CheckpointKey {
+    pub fn new(algorithm: AlgorithmId, public_key: &[u8], secret_key: &[u8]) -> Result<Self, AuditError> {
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        Ok(CheckpointKey {
+            algorithm,
+            public_key: public_key.to_vec(),
+            secret_key: Zeroizing::new(secret_key.to_vec()),
+        })
+    }
+
+    pub fn generate(algorithm: AlgorithmId) -> Result<Self, AuditError> {
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        let (public_key, secret_key) = registry::keygen(algorithm)?;
+        Ok(CheckpointKey { algorithm, public_key, secret_key: Zeroizing::new(secret_key) })
+    }
+
+    pub fn algorithm(&self) -> AlgorithmId {
+        self.algorithm
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+/// The state of a log that passed [`verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedLog {
+    pub records: Vec<AuditRecord>,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+impl VerifiedLog {
+    /// Records after the last checkpoint. They are chained but not yet
+    /// signed, so truncating them cannot be detected from the file alone.
+    pub fn unsigned_records(&self) -> &[AuditRecord] {
+        let signed = self.checkpoints.last().map_or(0, |c| c.size as usize);
+        &self.records[signed..]
+    }
+}
+
+/// Check a serialized log: the hash chain, every checkpoint and its
+/// signature under `public_key`. If the caller holds a `trusted`
+/// checkpoint from an earlier look at the log, the log must still
+/// contain the records it covers, unchanged.
+pub fn verify(data: &[u8], public_key: &[u8], trusted: Option<&Checkpoint>) -> Result<VerifiedLog, AuditError> {
+    if data.len() < 5 || &data[..4] != FILE_MAGIC {
+        return Err(AuditError::Malformed("not an audit log".into()));
+    }
+    if data[4] != VERSION {
+        return Err(AuditError::Malformed(format!("unsupported version {}", data[4])));
+    }
+    let mut chain = Chain::default();
+    let mut rest = &data[5..];
+    while !rest.is_empty() {
+        if rest.len() < 5 {
+            return Err(chain.truncated());
+        }
+        let length = u32::from_be_bytes(rest[1..5].try_into().unwrap()) as usize;
+        if rest.len() - 5 < length {
+            return Err(chain.truncated());
+        }
+        let body = &rest[5..5 + length];
+        match rest[0] {
+            RECORD_TAG => chain.push(AuditRecord::decode(body)?)?,
+            CHECKPOINT_TAG => chain.check(Checkpoint::decode(body)?, public_key)?,
+            tag => {
+                return Err(AuditError::Malformed(format!("unknown entry 0x{:02x}", tag)));
+            }
+        }
+        rest = &rest[5 + length..];
+    }
+    if let Some(trusted) = trusted {
+        if !trusted.verify(public_key) {
+            return Err(AuditError::BadSignature { size: trusted.size });
+        }
+        chain.check_head(trusted)?;
+    }
+    Ok(VerifiedLog { records: chain.records, checkpoints: chain.checkpoints })
+}
+
+/// Records and checkpoints checked as they are added.
+#[derive(Default)]
+struct Chain {
+    records: Vec<AuditRecord>,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl Chain {
+    fn head(&self) -> Hash {
+        self.records.last().map_or([0u8; HASH_LEN], AuditRecord::hash)
+    }
+
+    fn push(&mut self, record: AuditRecord) -> Result<(), AuditError> {
+        let sequence = self.records.len() as u64;
+        if record.sequence != sequence || !bool::from(record.prev_hash.ct_eq(&self.head())) {
+            return Err(AuditError::ChainBroken { sequence });
+        }
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// Check a checkpoint that claims to cover the records so far.
+    fn check(&mut self, checkpoint: Checkpoint, public_key: &[u8]) -> Result<(), AuditError> {
+        if checkpoint.size != self.records.len() as u64 || checkpoint.head != self.head() {
+            return Err(AuditError::CheckpointMismatch { size: checkpoint.size });
+        }
+        if !checkpoint.verify(public_key) {
+            return Err(AuditError::BadSignature { size: checkpoint.size });
+        }
+        self.checkpoints.push(checkpoint);
+        Ok(())
+    }
+
+    /// Check that an earlier checkpoint still matches a prefix of the log.
+    fn check_head(&self, checkpoint: &Checkpoint) -> Result<(), AuditError> {
+        let found = self.records.len() as u64;
+        if checkpoint.size > found {
+            return Err(AuditError::Truncated { expected: checkpoint.size, found });
+        }
+        let head = match checkpoint.size {
+            0 => [0u8; HASH_LEN],
+            size => self.records[size as usize - 1].hash(),
+        };
+        if head != checkpoint.head {
+            return Err(AuditError::CheckpointMismatch { size: checkpoint.size });
+        }
+        Ok(())
+    }
+
+    fn truncated(&self) -> AuditError {
+        let found = self.records.len() as u64;
+        AuditError::Truncated { expected: found + 1, found }
+    }
+}
+
+fn encode_entry(tag: u8, body: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(5 + body.len());
+    entry.push(tag);
+    entry.extend_from_slice(&(body.len() as u32).to_be_bytes());
+    entry.extend_from_slice(body);
+    entry
+}
+
+/// An append-only, hash-chained audit log.
+pub struct AuditLog {
+    chain: Chain,
+    key: CheckpointKey,
+    interval: u64,
+    file: Option<(PathBuf, File)>,
+}
+
+impl AuditLog {
+    /// An in-memory log that signs a checkpoint every `interval` records,
+    /// or only on [`AuditLog::checkpoint`] if `interval` is 0.
+    pub fn new(key: CheckpointKey, interval: u64) -> Self {
+        AuditLog { chain: Chain::default(), key, interval, file: None }
+    }
+
+    /// Create a log file. Fails if `path` exists.
+    pub fn create(path: impl AsRef<Path>, key: CheckpointKey, interval: u64) -> Result<Self, AuditError> {
+        let path = path.as_ref();
+        let mut file = OpenOptions::new().append(true).create_new(true).open(path).map_err(io_error(path))?;
+        file.write_all(FILE_MAGIC).map_err(io_error(path))?;
+        file.write_all(&[VERSION]).map_err(io_error(path))?;
+        file.sync_all().map_err(io_error(path))?;
+        let mut log = AuditLog::new(key, interval);
+        log.file = Some((path.to_path_buf(), file));
+        Ok(log)
+    }
+
+    /// Open a log file to append to it. The whole log is verified under
+    /// the key's public key first.
+    pub fn open(path: impl AsRef<Path>, key: CheckpointKey, interval: u64) -> Result<Self, AuditError> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).map_err(io_error(path))?;
+        let verified = verify(&data, key.public_key(), None)?;
+        let file = OpenOptions::new().append(true).open(path).map_err(io_error(path))?;
+        Ok(AuditLog {
+            chain: Chain { records: verified.records, checkpoints: verified.checkpoints },
+            key,
+            interval,
+            file: Some((path.to_path_buf(), file)),
+        })
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    pub fn len(&self) -> u64 {
+        self.chain.records.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chain.records.is_empty()
+    }
+
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.chain.records
+    }
+
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.chain.checkpoints
+    }
+
+    /// Hash of the last record, or zeros for an empty log.
+    pub fn head(&self) -> Hash {
+        self.chain.head()
+    }
+
+    /// Append an event and return its sequence number. Signs a checkpoint
+    /// if the record completes an interval.
+    pub fn append(&mut self, event: &[u8]) -> Result<u64, AuditError> {
+        let record = AuditRecord { sequence: self.len(), timestamp: now(), prev_hash: self.head(), event: event.to_vec() };
+        let mut body = Vec::with_capacity(RECORD_HEADER_LEN + event.len());
+        record.encode(&mut body);
+        self.write(&encode_entry(RECORD_TAG, &body), false)?;
+        self.chain.push(record)?;
+        if self.interval != 0 && self.len().is_multiple_of(self.interval) {
+            self.checkpoint()?;
+        }
+        Ok(self.len() - 1)
+    }
+
+    /// Sign a checkpoint over every record so far. Returns the latest
+    /// checkpoint unchanged if nothing was appended since.
+    pub fn checkpoint(&mut self) -> Result<Checkpoint, AuditError> {
+        if let Some(last) = self.chain.checkpoints.last() {
+            if last.size == self.len() {
+                return Ok(last.clone());
+            }
+        }
+        let (size, timestamp, head) = (self.len(), now(), self.head());
+        let message = Checkpoint::message(size, timestamp, &head);
+        let signature = registry::sign(self.key.algorithm, &self.key.secret_key, &message)?;
+        let checkpoint = Checkpoint { size, timestamp, head, algorithm: self.key.algorithm, signature };
+        self.write(&encode_entry(CHECKPOINT_TAG, &checkpoint.to_bytes()), true)?;
+        self.chain.checkpoints.push(checkpoint.clone());
+        Ok(checkpoint)
+    }
+
+    /// The log in its file encoding.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = FILE_MAGIC.to_vec();
+        out.push(VERSION);
+        let mut checkpoints = self.chain.checkpoints.iter().peekable();
+        let mut body = Vec::new();
+        for position in 0..=self.chain.records.len() {
+            while let Some(checkpoint) = checkpoints.next_if(|c| c.size as usize == position) {
+                out.extend_from_slice(&encode_entry(CHECKPOINT_TAG, &checkpoint.to_bytes()));
+            }
+            if let Some(record) = self.chain.records.get(position) {
+                body.clear();
+                record.encode(&mut body);
+                out.extend_from_slice(&encode_entry(RECORD_TAG, &body));
+            }
+        }
+        out
+    }
+
+    /// Export records `from..to` with the first checkpoint that covers
+    /// them, for an auditor who holds only the public key.
+    pub fn proof(&self, from: u64, to: u64) -> Result<AuditProof, AuditError> {
+        if from >= to || to > self.len() {
+            return Err(AuditError::Malformed(format!("invalid record range {}..{}", from, to)));
+        }
+        let checkpoint = self.chain.checkpoints
+            .iter()
+            .find(|c| c.size >= to)
+            .ok_or(AuditError::NotCheckpointed)?;
+        Ok(AuditProof {
+            records: self.chain.records[from as usize..checkpoint.size as usize].to_vec(),
+            checkpoint: checkpoint.clone(),
+        })
+    }
+
+    fn write(&mut self, entry: &[u8], sync: bool) -> Result<(), AuditError> {
+        if let Some((path, file)) = &mut self.file {
+            file.write_all(entry).map_err(io_error(path))?;
+            if sync {
+                file.sync_data().map_err(io_error(path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A run of records and a checkpoint whose chain runs through them.
+///
+/// ```text
+/// proof := "AEAP" version:u8 count:u32be (length:u32be record)*
+///          checkpoint
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditProof {
+    /// Consecutive records ending with the last one the checkpoint covers.
+    pub records: Vec<AuditRecord>,
+    pub checkpoint: Checkpoint,
+}
+
+impl AuditProof {
+    /// Check the checkpoint signature and the chain from the first record
+    /// to the checkpoint head.
+    pub fn verify(&self, public_key: &[u8]) -> Result<(), AuditError> {
+        let first = self.records.first().ok_or_else(|| AuditError::Malformed("empty proof".into()))?;
+        if first.sequence + (self.records.len() as u64) != self.checkpoint.size {
+            return Err(AuditError::CheckpointMismatch { size: self.checkpoint.size });
+        }
+        for pair in self.records.windows(2) {
+            if pair[1].sequence != pair[0].sequence + 1 || pair[1].prev_hash != pair[0].hash() {
+                return Err(AuditError::ChainBroken { sequence: pair[1].sequence });
+            }
+        }
+        if self.records.last().map(AuditRecord::hash) != Some(self.checkpoint.head) {
+            return Err(AuditError::CheckpointMismatch { size: self.checkpoint.size });
+        }
+        if !self.checkpoint.verify(public_key) {
+            return Err(AuditError::BadSignature { size: self.checkpoint.size });
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = PROOF_MAGIC.to_vec();
+        out.push(VERSION);
+        out.extend_from_slice(&(self.records.len() as u32).to_be_bytes());
+        let mut body = Vec::new();
+        for record in &self.records {
+            body.clear();
+            record.encode(&mut body);
+            out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            out.extend_from_slice(&body);
+        }
+        self.checkpoint.encode(&mut out);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, AuditError> {
+        if data.len() < 9 || &data[..4] != PROOF_MAGIC {
+            return Err(AuditError::Malformed("not an audit proof".into()));
+        }
+        if data[4] != VERSION {
+            return Err(AuditError::Malformed(format!("unsupported version {}", data[4])));
+        }
+        let count = u32::from_be_bytes(data[5..9].try_into().unwrap()) as usize;
+        let mut rest = &data[9..];
+        let mut records = Vec::with_capacity(count.min(rest.len() / (4 + RECORD_HEADER_LEN)));
+        for _ in 0..count {
+            if rest.len() < 4 {
+                return Err(AuditError::Malformed("truncated".into()));
+            }
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            if rest.len() - 4 < length {
+                return Err(AuditError::Malformed("truncated".into()));
+            }
+            records.push(AuditRecord::decode(&rest[4..4 + length])?);
+            rest = &rest[4 + length..];
+        }
+        Ok(AuditProof { records, checkpoint: Checkpoint::decode(rest)? })
+    }
+}
+
+/// Records KMS calls as text events:
+/// `<operation> keys=<id>,... context=<k>=<v>;... result=<ok|error>`.
+impl kms::AuditSink for AuditLog {
+    fn record(&mut self, record: kms::AuditRecord) -> Result<(), kms::KmsError> {
+        let keys: Vec<_> = record.keys.iter().map(|key| key.to_string()).collect();
+        let context: Vec<_> = record.context.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        let result = record.error.map_or_else(|| "ok".to_string(), |e| e.to_string());
+        let event = format!(
+            "{} keys={} context={} result={}",
+            record.operation.name(),
+            keys.join(","),
+            context.join(";"),
+            result
+        );
+        self.append(event.as_bytes()).map_err(|e| kms::KmsError::Audit(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_log_round_trip() {
+        let key = CheckpointKey::generate(AlgorithmId::MlDsa44).unwrap();
+        let public_key = key.public_key().to_vec();
+        let mut log = AuditLog::new(key, 0);
+        let empty = log.checkpoint().unwrap();
+        assert_eq!((empty.size, empty.head), (0, [0u8; HASH_LEN]));
+        let verified = verify(&log.to_bytes(), &public_key, Some(&empty)).unwrap();
+        assert!(verified.records.is_empty());
+        assert_eq!(Checkpoint::from_bytes(&empty.to_bytes()).unwrap(), empty);
+    }
+}
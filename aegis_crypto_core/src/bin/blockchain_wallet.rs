@@ -1,8 +1,14 @@
 use aegis_crypto_core::{
-    kyber::{ kyber_keygen, kyber_encapsulate },
-    dilithium::{ dilithium_keygen, dilithium_sign, dilithium_verify },
-    falcon::{ falcon_keygen, falcon_sign, falcon_verify },
+    kyber::kyber_encapsulate,
+    dilithium::{ dilithium_sign, dilithium_verify },
+    falcon::{ falcon_sign, falcon_verify },
     hash::sha3_256_hash,
+    hd::{ DerivationPath, ExtendedKey, Mnemonic },
+    keystore::{ KeyOptions, KeyStore, MemoryKeyStore },
+    merkle::{ MerkleHash, MerkleLog },
+    multisig::{ MultisigPolicy, PolicyKey, SignatureCollector },
+    policy::{ CryptoPolicy, Purpose },
+    registry::{ self, AlgorithmId },
     utils::bytes_to_hex,
 };
 use std::collections::HashMap;
@@ -29,10 +35,13 @@ enum WalletSecurityLevel {
     Maximum, // Triple PQC algorithms
 }
 
-/// Wallet keypair with multiple PQC algorithms
+/// Wallet keypair with multiple PQC algorithms, derived from the wallet's
+/// recovery phrase
 #[derive(Debug)]
 struct WalletKeypair {
     _wallet_id: String,
+    mnemonic: Mnemonic,
+    path: DerivationPath,
     kyber_keys: (Vec<u8>, Vec<u8>), // (public_key, secret_key)
     dilithium_keys: (Vec<u8>, Vec<u8>), // (public_key, secret_key)
     falcon_keys: (Vec<u8>, Vec<u8>), // (public_key, secret_key)
@@ -108,33 +117,32 @@ impl BlockchainWalletSystem {
         println!("   💰 Currency: {}", currency);
         println!("   🛡️  Security Level: {:?}", security_level);
 
-        // Generate PQC keypairs based on security level
-        let kyber_keys = kyber_keygen();
+        // Derive every PQC keypair from one recovery phrase
+        let mnemonic = Mnemonic::generate(24).map_err(|e| e.to_string())?;
+        let path = DerivationPath::bip44(coin_type(&currency), 0, 0).map_err(|e| e.to_string())?;
+        let (kyber_keys, dilithium_keys, falcon_keys) = derive_wallet_keys(&mnemonic, &path)?;
+        println!("   📝 Recovery phrase generated ({} words), keys derived at {}", mnemonic.word_count(), path);
         println!(
-            "   ✅ Kyber KEM keys generated (Public: {} bytes, Secret: {} bytes)",
-            kyber_keys.public_key().len(),
-            kyber_keys.secret_key().len()
+            "   ✅ Kyber KEM keys derived (Public: {} bytes, Secret: {} bytes)",
+            kyber_keys.0.len(),
+            kyber_keys.1.len()
         );
-
-        let dilithium_keys = dilithium_keygen();
         println!(
-            "   ✅ Dilithium signature keys generated (Public: {} bytes, Secret: {} bytes)",
-            dilithium_keys.public_key().len(),
-            dilithium_keys.secret_key().len()
+            "   ✅ Dilithium signature keys derived (Public: {} bytes, Secret: {} bytes)",
+            dilithium_keys.0.len(),
+            dilithium_keys.1.len()
         );
-
-        let falcon_keys = falcon_keygen();
         println!(
-            "   ✅ Falcon signature keys generated (Public: {} bytes, Secret: {} bytes)",
-            falcon_keys.public_key().len(),
-            falcon_keys.secret_key().len()
+            "   ✅ Falcon signature keys derived (Public: {} bytes, Secret: {} bytes)",
+            falcon_keys.0.len(),
+            falcon_keys.1.len()
         );
 
         // Create wallet
         let wallet = CryptoWallet {
             _wallet_id: wallet_id.clone(),
             owner_name: owner_name.clone(),
-            public_address: self.generate_public_address(&kyber_keys.public_key()),
+            public_address: self.generate_public_address(&kyber_keys.0),
             balance: 0.0,
             currency,
             _created_at: now,
@@ -145,9 +153,11 @@ impl BlockchainWalletSystem {
         // Create keypair
         let keypair = WalletKeypair {
             _wallet_id: wallet_id.clone(),
-            kyber_keys: (kyber_keys.public_key(), kyber_keys.secret_key()),
-            dilithium_keys: (dilithium_keys.public_key(), dilithium_keys.secret_key()),
-            falcon_keys: (falcon_keys.public_key(), falcon_keys.secret_key()),
+            mnemonic,
+            path,
+            kyber_keys,
+            dilithium_keys,
+            falcon_keys,
         };
 
         self.wallets.insert(wallet_id.clone(), wallet);
@@ -394,6 +404,27 @@ impl BlockchainWalletSystem {
         format!("WALLET_{}", bytes_to_hex(&hash[..8]))
     }
 
+    /// Restore a wallet's keys from its recovery phrase alone
+    fn demo_wallet_recovery(&self, wallet_id: &str) -> Result<(), String> {
+        let wallet = self.wallets.get(wallet_id).ok_or("Wallet not found")?;
+        let keypair = self.keypairs.get(wallet_id).ok_or("Keypair not found")?;
+        println!("\n🧩 Restoring {}'s wallet from the recovery phrase...", wallet.owner_name);
+
+        // The phrase is all a user has to write down
+        let phrase = keypair.mnemonic.phrase();
+        let restored = Mnemonic::parse(&phrase).map_err(|e| e.to_string())?;
+        let (kyber_keys, dilithium_keys, falcon_keys) = derive_wallet_keys(&restored, &keypair.path)?;
+        if kyber_keys.0 != keypair.kyber_keys.0
+            || dilithium_keys.0 != keypair.dilithium_keys.0
+            || falcon_keys.0 != keypair.falcon_keys.0
+        {
+            return Err("restored keys do not match".into());
+        }
+        println!("   📍 Restored address: {}", self.generate_public_address(&kyber_keys.0));
+        println!("   ✅ Kyber, Dilithium and Falcon keys at {} match the originals", keypair.path);
+        Ok(())
+    }
+
     /// Generate a public address from public key
     fn generate_public_address(&self, public_key: &[u8]) -> String {
         let hash = sha3_256_hash(public_key);
@@ -467,6 +498,106 @@ impl BlockchainWalletSystem {
     }
 }
 
+/// SLIP-44 coin type of a currency
+fn coin_type(currency: &str) -> u32 {
+    match currency {
+        "ETH" => 60,
+        _ => 0,
+    }
+}
+
+type KeyPair = (Vec<u8>, Vec<u8>);
+
+/// Derive the Kyber, Dilithium and Falcon keypairs at `path`
+fn derive_wallet_keys(
+    mnemonic: &Mnemonic,
+    path: &DerivationPath
+) -> Result<(KeyPair, KeyPair, KeyPair), String> {
+    let node = ExtendedKey::from_mnemonic(mnemonic, "").derive(path).map_err(|e| e.to_string())?;
+    let derive = |algorithm| node.keypair(algorithm).map_err(|e| e.to_string());
+    Ok((derive(AlgorithmId::MlKem768)?, derive(AlgorithmId::MlDsa87)?, derive(AlgorithmId::Falcon512)?))
+}
+
+/// Approve a treasury withdrawal with a 2-of-3 policy over mixed PQC keys
+fn demo_treasury_multisig() -> Result<(), String> {
+    println!("\n🏦 Treasury withdrawal with 2-of-3 PQC multisig...");
+    let algorithms = [AlgorithmId::MlDsa65, AlgorithmId::Falcon512, AlgorithmId::MlDsa87];
+    let mut officers = Vec::new();
+    for algorithm in algorithms {
+        let (public_key, secret_key) = registry::keygen(algorithm).map_err(|e| e.to_string())?;
+        officers.push((algorithm, public_key, secret_key));
+    }
+    let keys = officers
+        .iter()
+        .map(|(algorithm, public_key, _)| PolicyKey::new(*algorithm, public_key))
+        .collect();
+    let policy = MultisigPolicy::new(2, keys).map_err(|e| e.to_string())?;
+    println!("   📜 Policy hash: {}", bytes_to_hex(&policy.policy_hash()[..8]));
+
+    let withdrawal = b"withdraw:treasury:cold-storage:25.0:BTC";
+    let mut collector = SignatureCollector::new(&policy, withdrawal);
+    for (algorithm, public_key, secret_key) in &officers[..2] {
+        let partial = policy
+            .sign(*algorithm, public_key, secret_key, withdrawal)
+            .map_err(|e| e.to_string())?;
+        collector.add(partial).map_err(|e| e.to_string())?;
+        println!("   🖊️  {} officer signed ({} of {})", algorithm.name(), collector.count(), policy.threshold());
+    }
+    let bundle = collector.finish().map_err(|e| e.to_string())?;
+    policy.verify(withdrawal, &bundle).map_err(|e| e.to_string())?;
+    println!("   ✅ Multisig bundle verified ({} bytes)", bundle.to_bytes().len());
+    Ok(())
+}
+
+fn demo_block_commitment(tx_ids: &[&String]) -> Result<(), String> {
+    println!("\n🌳 Committing the block's transactions to a Merkle root...");
+    let log = MerkleLog::from_leaves(MerkleHash::Keccak256, tx_ids.iter().map(|id| id.as_bytes()));
+    let root = log.root();
+    println!("   🌱 Transactions root: {}", bytes_to_hex(&root));
+
+    let (public_key, secret_key) = registry::keygen(AlgorithmId::MlDsa65).map_err(|e| e.to_string())?;
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    let head = log.sign_tree_head(AlgorithmId::MlDsa65, &secret_key, timestamp).map_err(|e| e.to_string())?;
+    if !head.verify(&public_key) {
+        return Err("signed tree head failed to verify".into());
+    }
+    println!("   🖊️  Block producer signed the tree head ({} bytes)", head.to_bytes().len());
+
+    let proof = log.inclusion_proof(1, log.len()).map_err(|e| e.to_string())?;
+    if !proof.verify(log.hash(), tx_ids[1].as_bytes(), &head.root) {
+        return Err("inclusion proof failed to verify".into());
+    }
+    println!("   ✅ Light client verified {} with {} proof hashes", tx_ids[1], proof.path.len());
+    Ok(())
+}
+
+/// Move a validator key from Falcon to ML-DSA without invalidating old signatures
+fn demo_key_rotation() -> Result<(), String> {
+    println!("\n🔄 Rotating the validator key from Falcon-512 to ML-DSA...");
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let mut store = MemoryKeyStore::new();
+    let old_key = store
+        .generate(AlgorithmId::Falcon512, KeyOptions::new().with_label("validator"))
+        .map_err(|e| e.to_string())?;
+    let block = b"block:1024:state-root";
+    let old_signature = store.sign(&old_key.id, block).map_err(|e| e.to_string())?;
+    let old_public_key = store.public_key(&old_key.id).map_err(|e| e.to_string())?;
+
+    let policy = CryptoPolicy::recommended().deprecate(AlgorithmId::Falcon512, now);
+    for event in policy.rotate(&mut store, Purpose::Sign, now).map_err(|e| e.to_string())? {
+        println!("   📣 {:?}: {} -> {} (key {})", event.reason, event.from.name(), event.to.name(), event.key);
+    }
+
+    let keys = store.list().map_err(|e| e.to_string())?;
+    let current = policy.select_key(Purpose::Sign, &keys, now).ok_or("no signing key")?;
+    println!("   🔑 New blocks are signed with {} (key {})", current.algorithm.name(), current.id);
+    let valid = policy
+        .verify(AlgorithmId::Falcon512, &old_public_key, block, &old_signature, now)
+        .map_err(|e| e.to_string())?;
+    println!("   ✅ Blocks signed before the rotation still verify: {}", valid);
+    Ok(())
+}
+
 fn main() {
     println!("🚀 AEGIS BLOCKCHAIN WALLET SECURITY DEMO");
     println!("🔗 Post-Quantum Cryptography for Cryptocurrency");
@@ -520,6 +651,11 @@ fn main() {
     system.verify_transaction(&tx2);
     system.verify_transaction(&tx3);
 
+    demo_block_commitment(&[&tx1, &tx2, &tx3]).unwrap();
+    demo_treasury_multisig().unwrap();
+    demo_key_rotation().unwrap();
+    system.demo_wallet_recovery(&alice_wallet).unwrap();
+
     // Display final status
     system.display_status();
 
@@ -527,5 +663,9 @@ fn main() {
     println!("🔗 Blockchain wallet system using post-quantum cryptography!");
     println!("✅ Multi-algorithm PQC security");
     println!("✅ Transaction signing and verification");
+    println!("✅ Merkle block commitments with signed tree heads");
+    println!("✅ k-of-n multisig over heterogeneous PQC keys");
+    println!("✅ Policy-driven key rotation");
+    println!("✅ Recovery phrases with hierarchical key derivation");
     println!("✅ Blockchain state management");
 }
@@ -1,8 +1,12 @@
 use aegis_crypto_core::{
+    audit::{ self, AuditLog, CheckpointKey },
+    did::{ self, DidKey },
     dilithium::{ dilithium_keygen, dilithium_sign, dilithium_verify },
     falcon::{ falcon_keygen, falcon_sign, falcon_verify },
     sphincsplus::{ sphincsplus_keygen, sphincsplus_sign, sphincsplus_verify },
     hash::sha3_256_hash,
+    pake::{ Pake, PakeConfig, PakeError },
+    registry::AlgorithmId,
     utils::bytes_to_hex,
 };
 use std::collections::HashMap;
@@ -12,6 +16,8 @@ use std::time::{ SystemTime, UNIX_EPOCH };
 #[derive(Debug, Clone)]
 struct DigitalIdentity {
     id: String,
+    /// `did:key` identifier derived from the ML-DSA-87 public key
+    did: DidKey,
     username: String,
     email: String,
     full_name: String,
@@ -71,6 +77,9 @@ struct IdentityManagementSystem {
     credentials: HashMap<String, AuthCredentials>,
     active_sessions: HashMap<String, AuthSession>,
     audit_log: Vec<AuditEvent>,
+    /// Hash-chained copy of `audit_log`, checkpointed with ML-DSA.
+    audit_chain: AuditLog,
+    audit_public_key: Vec<u8>,
 }
 
 /// Audit event for security monitoring
@@ -87,11 +96,15 @@ struct AuditEvent {
 
 impl IdentityManagementSystem {
     fn new() -> Self {
+        let audit_key = CheckpointKey::generate(AlgorithmId::MlDsa44).expect("audit key generation failed");
+        let audit_public_key = audit_key.public_key().to_vec();
         Self {
             identities: HashMap::new(),
             credentials: HashMap::new(),
             active_sessions: HashMap::new(),
             audit_log: Vec::new(),
+            audit_chain: AuditLog::new(audit_key, 8),
+            audit_public_key,
         }
     }
 
@@ -139,9 +152,22 @@ impl IdentityManagementSystem {
             sphincsplus_keys.secret_key().len()
         );
 
+        // Issue a did:key identifier for the ML-DSA-87 key
+        let did = DidKey::new(AlgorithmId::MlDsa87, &dilithium_keys.public_key()).map_err(|e|
+            e.to_string()
+        )?;
+        let document = did.document();
+        println!("   🌐 DID: {}", abbreviate_did(&did.did()));
+        println!(
+            "   📄 DID document: {} verification method(s), {} bytes of JSON",
+            document.verification_method.len(),
+            document.to_json().len()
+        );
+
         // Create identity
         let identity = DigitalIdentity {
             id: identity_id.clone(),
+            did,
             username: username.clone(),
             email,
             full_name,
@@ -205,10 +231,10 @@ impl IdentityManagementSystem {
         let identity = self.identities.get(&identity_id).unwrap();
         println!("\n🔐 Authenticating user: {}", identity.full_name);
 
-        // Step 1: Verify password
-        println!("   🔑 Verifying password...");
-        let password_hash = self.hash_password(password);
-        if password_hash != credentials.password_hash {
+        // Step 1: Verify password with a PAKE
+        println!("   🔑 Verifying password (PAKE)...");
+        if let Err(e) = self.password_exchange(username, password, &credentials.password_hash) {
+            println!("   ❌ PAKE failed: {}", e);
             self.log_audit_event(
                 "AUTH_FAILED",
                 &identity_id,
@@ -450,7 +476,15 @@ impl IdentityManagementSystem {
             let valid = match signature_type.as_str() {
                 "Dilithium" => {
                     let challenge_hash = sha3_256_hash(&signature.document_hash);
-                    dilithium_verify(&credentials.dilithium_keypair.0, &challenge_hash)
+                    // Resolve the verification key from the signer's DID document
+                    match
+                        did
+                            ::resolve(&identity.did.did())
+                            .and_then(|document| document.verification_method[0].public_key())
+                    {
+                        Ok((_, public_key)) => dilithium_verify(&public_key, &challenge_hash),
+                        Err(_) => false,
+                    }
                 }
                 "Falcon" => {
                     falcon_verify(
@@ -485,6 +519,49 @@ impl IdentityManagementSystem {
         all_valid
     }
 
+    /// Run the PAKE login between the user's client and the server. The
+    /// client only knows the typed password and the server only its stored
+    /// hash; the three messages below are all that pass between them.
+    fn password_exchange(
+        &self,
+        username: &str,
+        password: &str,
+        password_hash: &[u8]
+    ) -> Result<(), PakeError> {
+        let login_id = self.generate_salt();
+        let mut client = Pake::initiator(
+            PakeConfig::new(&self.hash_password(password))
+                .with_session_id(&login_id)
+                .with_identities(username.as_bytes(), b"aegis-idm")
+        );
+        let mut server = Pake::responder(
+            PakeConfig::new(password_hash)
+                .with_session_id(&login_id)
+                .with_identities(username.as_bytes(), b"aegis-idm")
+        );
+
+        let hello = client.write_message()?;
+        println!("   📤 Client -> server: {} bytes (CPace share, ML-KEM public key)", hello.len());
+        server.read_message(&hello)?;
+
+        let response = server.write_message()?;
+        println!("   📥 Server -> client: {} bytes (CPace share, ML-KEM ciphertext, confirmation)", response.len());
+        client.read_message(&response)?;
+
+        let confirm = client.write_message()?;
+        println!("   📤 Client -> server: {} bytes (confirmation)", confirm.len());
+        server.read_message(&confirm)?;
+
+        let client_key = client.into_key()?;
+        let server_key = server.into_key()?;
+        println!(
+            "   🔐 Both sides derived the session key (transcript {}...) without sending a password or hash",
+            bytes_to_hex(&client_key.transcript_hash()[..8])
+        );
+        debug_assert_eq!(client_key.as_bytes(), server_key.as_bytes());
+        Ok(())
+    }
+
     /// Generate a unique identity ID
     fn generate_identity_id(&self, username: &str) -> String {
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
@@ -559,6 +636,15 @@ impl IdentityManagementSystem {
             ip_address: ip_address.to_string(),
             success,
         };
+        let record = format!(
+            "{}\t{}\t{}\t{}\t{}",
+            event.event_type,
+            event.identity_id,
+            event.details,
+            event.ip_address,
+            event.success
+        );
+        self.audit_chain.append(record.as_bytes()).expect("audit log append failed");
         self.audit_log.push(event);
     }
 
@@ -571,6 +657,11 @@ impl IdentityManagementSystem {
         println!("👥 Identities: {}", self.identities.len());
         println!("🔐 Active Sessions: {}", self.active_sessions.len());
         println!("📝 Audit Events: {}", self.audit_log.len());
+        let chain_status = match audit::verify(&self.audit_chain.to_bytes(), &self.audit_public_key, None) {
+            Ok(log) => format!("✅ intact, {} ML-DSA checkpoints", log.checkpoints.len()),
+            Err(e) => format!("❌ {}", e),
+        };
+        println!("🔗 Audit Chain: {}", chain_status);
 
         if !self.identities.is_empty() {
             println!("\n👤 Registered Identities:");
@@ -589,6 +680,7 @@ impl IdentityManagementSystem {
                     identity.organization,
                     identity.login_count
                 );
+                println!("     🌐 {}", abbreviate_did(&identity.did.did()));
             }
         }
 
@@ -641,6 +733,14 @@ struct DocumentSignature {
     security_level: SecurityLevel,
 }
 
+/// Shorten a did:key identifier for display; ML-DSA keys make them long.
+fn abbreviate_did(did: &str) -> String {
+    if did.len() <= 48 {
+        return did.to_string();
+    }
+    format!("{}…{}", &did[..32], &did[did.len() - 12..])
+}
+
 fn main() {
     println!("🚀 AEGIS DIGITAL IDENTITY & AUTHENTICATION SYSTEM DEMO");
     println!("🆔 Post-Quantum Cryptography for Identity Management");
@@ -689,6 +789,8 @@ fn main() {
 
     // Authenticate users
     println!("\n🔐 Authenticating users...");
+    let rejected = system.authenticate("bob.smith", "guessed_password", b"challenge_response");
+    assert!(rejected.is_err(), "a wrong password must fail the PAKE");
     let _alice_session = system
         .authenticate("alice.johnson", "default_password123", b"challenge_response")
         .unwrap();
@@ -1,6 +1,8 @@
 use aegis_crypto_core::{
-    falcon::{ falcon_keygen, falcon_sign, falcon_verify },
+    falcon::falcon_verify,
     hash::sha3_256_hash,
+    keystore::{ KeyId, KeyOptions, KeyStore, MemoryKeyStore },
+    registry::AlgorithmId,
     utils::bytes_to_hex,
 };
 use std::collections::HashMap;
@@ -37,12 +39,13 @@ struct User {
     email: String,
     role: String,
     falcon_public_key: Vec<u8>,
-    falcon_secret_key: Vec<u8>,
+    falcon_key: KeyId,
     _created_at: u64,
 }
 
 /// Document signing and verification system
 struct DocumentSigningSystem {
+    keystore: MemoryKeyStore,
     users: HashMap<String, User>,
     documents: HashMap<String, Document>,
     signatures: HashMap<String, DocumentSignature>,
@@ -51,6 +54,7 @@ struct DocumentSigningSystem {
 impl DocumentSigningSystem {
     fn new() -> Self {
         Self {
+            keystore: MemoryKeyStore::new(),
             users: HashMap::new(),
             documents: HashMap::new(),
             signatures: HashMap::new(),
@@ -67,12 +71,15 @@ impl DocumentSigningSystem {
     ) -> Result<(), String> {
         println!("🔐 Creating PQC-secured user: {}", name);
 
-        // Generate Falcon signature keypair
-        let falcon_keys = falcon_keygen();
+        // Generate a Falcon signature key; the secret key stays in the keystore
+        let falcon_key = self.keystore
+            .generate(AlgorithmId::Falcon512, KeyOptions::new().with_label(&id))
+            .map_err(|e| e.to_string())?;
+        let falcon_public_key = self.keystore.public_key(&falcon_key.id).map_err(|e| e.to_string())?;
         println!(
-            "   ✅ Falcon signature keys generated (Public: {} bytes, Secret: {} bytes)",
-            falcon_keys.public_key().len(),
-            falcon_keys.secret_key().len()
+            "   ✅ Falcon signature key generated (Public: {} bytes, Key ID: {})",
+            falcon_public_key.len(),
+            falcon_key.id
         );
 
         let user = User {
@@ -80,8 +87,8 @@ impl DocumentSigningSystem {
             name,
             email,
             role,
-            falcon_public_key: falcon_keys.public_key(),
-            falcon_secret_key: falcon_keys.secret_key(),
+            falcon_public_key,
+            falcon_key: falcon_key.id,
             _created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         };
 
@@ -142,7 +149,7 @@ impl DocumentSigningSystem {
         println!("   🖊️  Document hash created: {}", bytes_to_hex(&document_hash));
 
         // Sign with Falcon
-        let signature = falcon_sign(&signer.falcon_secret_key, &document_hash);
+        let signature = self.keystore.sign(&signer.falcon_key, &document_hash).map_err(|e| e.to_string())?;
         println!("   ✅ Falcon signature created ({} bytes)", signature.len());
 
         // Create signature record
@@ -1,7 +1,9 @@
 use aegis_crypto_core::{
-    kyber::{ kyber_keygen, kyber_encapsulate, kyber_decapsulate },
+    kyber::kyber_keygen,
     falcon::{ falcon_keygen, falcon_sign, falcon_verify },
     hash::sha3_256_hash,
+    registry::AlgorithmId,
+    stream,
     utils::bytes_to_hex,
 };
 use std::collections::HashMap;
@@ -56,7 +58,8 @@ struct SecureTransaction {
     transaction_type: TransactionType,
     description: String,
     falcon_signature: Vec<u8>,
-    kem_ciphertext: Vec<u8>,
+    /// Signed transaction record, encrypted to the system's Kyber key.
+    encrypted_record: Vec<u8>,
     timestamp: u64,
     risk_score: f64,
 }
@@ -208,35 +211,33 @@ impl FinancialSecuritySystem {
         println!("   💰 Amount: {} {}", amount, from_account.currency);
         println!("   📝 Description: {}", description);
 
-        // Step 1: Perform Kyber KEM encapsulation
-        println!("   🔑 Performing Kyber KEM encapsulation...");
-        let encaps_result = kyber_encapsulate(&self.system_kyber_keys.0).map_err(|e|
-            format!("KEM encapsulation failed: {:?}", e)
-        )?;
-        let shared_secret = encaps_result.shared_secret();
-        let kem_ciphertext = encaps_result.ciphertext();
-        println!("   ✅ KEM encapsulation complete (Shared secret: {} bytes)", shared_secret.len());
-
-        // Step 2: Create transaction data for signing
+        // Step 1: Create transaction record for signing
         println!("   🖊️  Creating transaction hash for digital signature...");
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
         let transaction_data = format!(
-            "{}:{}:{}:{}:{}:{}:{}",
+            "{}:{}:{}:{}:{}:{}",
             from_account_id,
             to_account_id,
             amount,
             from_account.currency,
             description,
-            SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
-            shared_secret.len()
+            timestamp
         );
         let transaction_hash = sha3_256_hash(transaction_data.as_bytes());
         println!("   ✅ Transaction hash created: {}", bytes_to_hex(&transaction_hash));
 
-        // Step 3: Sign transaction with Falcon
+        // Step 2: Sign transaction with Falcon
         println!("   ✍️  Signing transaction with Falcon...");
         let falcon_signature = falcon_sign(&from_account.falcon_secret_key, &transaction_hash);
         println!("   ✅ Falcon signature created ({} bytes)", falcon_signature.len());
 
+        // Step 3: Encrypt the record to the system key (Kyber KEM + chunked AEAD)
+        println!("   🔒 Encrypting transaction record...");
+        let encrypted_record = stream
+            ::encrypt(AlgorithmId::MlKem768, &self.system_kyber_keys.0, transaction_data.as_bytes())
+            .map_err(|e| format!("Record encryption failed: {}", e))?;
+        println!("   ✅ Transaction record encrypted ({} bytes)", encrypted_record.len());
+
         // Step 4: Calculate risk score
         let risk_score = self.calculate_risk_score(from_account_id, amount);
         println!("   ⚠️  Risk score calculated: {:.2}", risk_score);
@@ -252,8 +253,8 @@ impl FinancialSecuritySystem {
             transaction_type: TransactionType::Transfer,
             description: description.to_string(),
             falcon_signature,
-            kem_ciphertext,
-            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
+            encrypted_record,
+            timestamp,
             risk_score,
         };
 
@@ -292,27 +293,28 @@ impl FinancialSecuritySystem {
         println!("   📤 From: {} ({})", from_account.owner_name, transaction.from_account);
         println!("   💰 Amount: {} {}", transaction.amount, transaction.currency);
 
-        // Step 1: Decapsulate shared secret using Kyber
-        println!("   🔑 Performing Kyber KEM decapsulation...");
-        let shared_secret = kyber_decapsulate(
-            &self.system_kyber_keys.1,
-            &transaction.kem_ciphertext
-        ).map_err(|e| format!("KEM decapsulation failed: {:?}", e))?;
-        println!("   ✅ KEM decapsulation complete (Shared secret: {} bytes)", shared_secret.len());
+        // Step 1: Decrypt the signed record; tampering or truncation fails here
+        println!("   🔓 Decrypting transaction record...");
+        let transaction_data = stream
+            ::decrypt(&self.system_kyber_keys.1, &transaction.encrypted_record)
+            .map_err(|e| format!("Record decryption failed: {}", e))?;
+        println!("   ✅ Transaction record decrypted ({} bytes)", transaction_data.len());
 
-        // Step 2: Recreate transaction hash
+        // Step 2: Check the record against the stored transaction and hash it
         println!("   🖊️  Recreating transaction hash...");
-        let transaction_data = format!(
-            "{}:{}:{}:{}:{}:{}:{}",
+        let expected_data = format!(
+            "{}:{}:{}:{}:{}:{}",
             transaction.from_account,
             transaction.to_account,
             transaction.amount,
             transaction.currency,
             transaction.description,
-            transaction.timestamp,
-            shared_secret.len()
+            transaction.timestamp
         );
-        let transaction_hash = sha3_256_hash(transaction_data.as_bytes());
+        if transaction_data != expected_data.as_bytes() {
+            return Err("Transaction record does not match the ledger entry".to_string());
+        }
+        let transaction_hash = sha3_256_hash(&transaction_data);
         println!("   ✅ Transaction hash recreated: {}", bytes_to_hex(&transaction_hash));
 
         // Step 3: Verify Falcon signature
@@ -1,7 +1,9 @@
 use aegis_crypto_core::{
-    kyber::{ kyber_keygen, kyber_encapsulate, kyber_decapsulate },
+    kyber::kyber_keygen,
     falcon::{ falcon_keygen, falcon_sign, falcon_verify },
     hash::sha3_256_hash,
+    registry::AlgorithmId,
+    stream,
     utils::bytes_to_hex,
 };
 use std::collections::HashMap;
@@ -43,7 +45,6 @@ struct SecureMessage {
     message_type: MessageType,
     payload: Vec<u8>,
     falcon_signature: Vec<u8>,
-    kem_ciphertext: Vec<u8>,
     timestamp: u64,
 }
 
@@ -142,25 +143,18 @@ impl IoTSecurityGateway {
         println!("   📡 Message type: {:?}", message_type);
         println!("   📝 Payload: {} characters", payload.len());
 
-        // Step 1: Perform Kyber KEM encapsulation
-        println!("   🔑 Performing Kyber KEM encapsulation...");
-        let encaps_result = kyber_encapsulate(&self.gateway_kyber_keys.0).map_err(|e|
-            format!("KEM encapsulation failed: {:?}", e)
-        )?;
-        let shared_secret = encaps_result.shared_secret();
-        let kem_ciphertext = encaps_result.ciphertext();
-        println!("   ✅ KEM encapsulation complete (Shared secret: {} bytes)", shared_secret.len());
-
-        // Step 2: Encrypt payload using shared secret
-        println!("   🔒 Encrypting message payload...");
-        let encrypted_payload = self.simulate_aes_encryption(payload.as_bytes(), &shared_secret);
+        // Step 1: Encrypt payload as a stream keyed from a Kyber KEM encapsulation
+        println!("   🔒 Encrypting message payload (Kyber KEM + chunked AEAD)...");
+        let encrypted_payload = stream
+            ::encrypt(AlgorithmId::MlKem768, &self.gateway_kyber_keys.0, payload.as_bytes())
+            .map_err(|e| format!("Payload encryption failed: {}", e))?;
         println!(
             "   ✅ Payload encrypted ({} bytes -> {} bytes)",
             payload.len(),
             encrypted_payload.len()
         );
 
-        // Step 3: Create message hash for signing
+        // Step 2: Create message hash for signing
         println!("   🖊️  Creating message hash for digital signature...");
         let message_data = format!(
             "{}:{}:{:?}:{}:{}",
@@ -173,7 +167,7 @@ impl IoTSecurityGateway {
         let message_hash = sha3_256_hash(message_data.as_bytes());
         println!("   ✅ Message hash created: {}", bytes_to_hex(&message_hash));
 
-        // Step 4: Sign message with Falcon
+        // Step 3: Sign message with Falcon
         println!("   ✍️  Signing message with Falcon...");
         let falcon_signature = falcon_sign(&device.falcon_secret_key, &message_hash);
         println!("   ✅ Falcon signature created ({} bytes)", falcon_signature.len());
@@ -185,7 +179,6 @@ impl IoTSecurityGateway {
             message_type,
             payload: encrypted_payload,
             falcon_signature,
-            kem_ciphertext,
             timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         };
 
@@ -202,24 +195,18 @@ impl IoTSecurityGateway {
         println!("📥 Processing secure message from {}...", device.name);
         println!("   📡 Message type: {:?}", message.message_type);
 
-        // Step 1: Decapsulate shared secret using Kyber
-        println!("   🔑 Performing Kyber KEM decapsulation...");
-        let shared_secret = kyber_decapsulate(
-            &self.gateway_kyber_keys.1,
-            &message.kem_ciphertext
-        ).map_err(|e| format!("KEM decapsulation failed: {:?}", e))?;
-        println!("   ✅ KEM decapsulation complete (Shared secret: {} bytes)", shared_secret.len());
-
-        // Step 2: Decrypt payload
+        // Step 1: Decrypt payload; tampering or truncation fails here
         println!("   🔓 Decrypting message payload...");
-        let decrypted_payload = self.simulate_aes_decryption(&message.payload, &shared_secret);
+        let decrypted_payload = stream
+            ::decrypt(&self.gateway_kyber_keys.1, &message.payload)
+            .map_err(|e| format!("Payload decryption failed: {}", e))?;
         println!(
             "   ✅ Payload decrypted ({} bytes -> {} bytes)",
             message.payload.len(),
             decrypted_payload.len()
         );
 
-        // Step 3: Verify message hash
+        // Step 2: Verify message hash
         println!("   🖊️  Verifying message hash...");
         let message_data = format!(
             "{}:{}:{:?}:{}:{}",
@@ -232,7 +219,7 @@ impl IoTSecurityGateway {
         let message_hash = sha3_256_hash(message_data.as_bytes());
         println!("   ✅ Message hash verified: {}", bytes_to_hex(&message_hash));
 
-        // Step 4: Verify Falcon signature
+        // Step 3: Verify Falcon signature
         println!("   ✍️  Verifying Falcon signature...");
         let falcon_valid = falcon_verify(
             &device.falcon_public_key,
@@ -259,26 +246,6 @@ impl IoTSecurityGateway {
         Ok(String::from_utf8_lossy(&decrypted_payload).to_string())
     }
 
-    /// Simulate AES encryption (in real implementation, use proper AES-GCM)
-    fn simulate_aes_encryption(&self, plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-        let mut encrypted = Vec::new();
-        for (i, &byte) in plaintext.iter().enumerate() {
-            let key_byte = key[i % key.len()];
-            encrypted.push(byte ^ key_byte);
-        }
-        encrypted
-    }
-
-    /// Simulate AES decryption (in real implementation, use proper AES-GCM)
-    fn simulate_aes_decryption(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
-        let mut decrypted = Vec::new();
-        for (i, &byte) in ciphertext.iter().enumerate() {
-            let key_byte = key[i % key.len()];
-            decrypted.push(byte ^ key_byte);
-        }
-        decrypted
-    }
-
     /// Display system status
     fn display_status(&self) {
         let separator = "=".repeat(60);
@@ -4,6 +4,7 @@ use aegis_crypto_core::{
     kyber::{ kyber_keygen, kyber_encapsulate },
     falcon::{ falcon_keygen, falcon_sign },
     hash::sha3_256_hash,
+    serialization::hex,
     utils::bytes_to_hex,
 };
 use axum::{
@@ -25,8 +26,10 @@ struct User {
     name: String,
     email: String,
     role: String,
-    kyber_public_key: String,
-    falcon_public_key: String,
+    #[serde(with = "hex")]
+    kyber_public_key: Vec<u8>,
+    #[serde(with = "hex")]
+    falcon_public_key: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -34,11 +37,16 @@ struct SecureMessage {
     sender_id: String,
     recipient_id: String,
     timestamp: u64,
-    encrypted_content: String,
-    falcon_signature: String,
-    kem_ciphertext: String,
-    nonce: String,
-    auth_tag: String,
+    #[serde(with = "hex")]
+    encrypted_content: Vec<u8>,
+    #[serde(with = "hex")]
+    falcon_signature: Vec<u8>,
+    #[serde(with = "hex")]
+    kem_ciphertext: Vec<u8>,
+    #[serde(with = "hex")]
+    nonce: Vec<u8>,
+    #[serde(with = "hex")]
+    auth_tag: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,7 +92,8 @@ struct VerificationRequest {
     message_id: String,
     sender_id: String,
     content: String,
-    signature: String,
+    #[serde(with = "hex")]
+    signature: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -166,8 +175,8 @@ async fn initialize_default_users(state: &Arc<AppState>) {
         name: "Alice Johnson".to_string(),
         email: "alice.johnson@company.com".to_string(),
         role: "Senior Developer".to_string(),
-        kyber_public_key: bytes_to_hex(&alice_kyber.public_key()),
-        falcon_public_key: bytes_to_hex(&alice_keys.public_key()),
+        kyber_public_key: alice_kyber.public_key(),
+        falcon_public_key: alice_keys.public_key(),
     });
 
     // Create Bob
@@ -178,8 +187,8 @@ async fn initialize_default_users(state: &Arc<AppState>) {
         name: "Bob Smith".to_string(),
         email: "bob.smith@company.com".to_string(),
         role: "Project Manager".to_string(),
-        kyber_public_key: bytes_to_hex(&bob_kyber.public_key()),
-        falcon_public_key: bytes_to_hex(&bob_keys.public_key()),
+        kyber_public_key: bob_kyber.public_key(),
+        falcon_public_key: bob_keys.public_key(),
     });
 
     // Create Charlie
@@ -190,8 +199,8 @@ async fn initialize_default_users(state: &Arc<AppState>) {
         name: "Charlie Brown".to_string(),
         email: "charlie.brown@company.com".to_string(),
         role: "Security Officer".to_string(),
-        kyber_public_key: bytes_to_hex(&charlie_kyber.public_key()),
-        falcon_public_key: bytes_to_hex(&charlie_keys.public_key()),
+        kyber_public_key: charlie_kyber.public_key(),
+        falcon_public_key: charlie_keys.public_key(),
     });
 
     println!("   👥 Created {} default users with PQC keypairs", users.len());
@@ -220,8 +229,8 @@ async fn create_user(
         name: payload.name,
         email: payload.email,
         role: payload.role,
-        kyber_public_key: bytes_to_hex(&kyber_keys.public_key()),
-        falcon_public_key: bytes_to_hex(&falcon_keys.public_key()),
+        kyber_public_key: kyber_keys.public_key(),
+        falcon_public_key: falcon_keys.public_key(),
     };
 
     // Store user
@@ -268,7 +277,7 @@ async fn send_message(
 
     // Step 2: Perform Kyber KEM encapsulation
     let step_start = std::time::Instant::now();
-    let encaps_result = match kyber_encapsulate(&hex::decode(&sender.kyber_public_key).unwrap()) {
+    let encaps_result = match kyber_encapsulate(&sender.kyber_public_key) {
         Ok(result) => result,
         Err(_) => {
             return JsonResponse(SendMessageResponse {
@@ -314,7 +323,7 @@ async fn send_message(
 
     // Step 5: Simulate encryption (in real app, use AES with shared secret)
     let step_start = std::time::Instant::now();
-    let encrypted_content = bytes_to_hex(&message_hash); // Simplified for demo
+    let encrypted_content = message_hash.to_vec(); // Simplified for demo
 
     crypto_steps.push(CryptoStep {
         step: "Message Encryption".to_string(),
@@ -333,10 +342,10 @@ async fn send_message(
             .unwrap()
             .as_secs(),
         encrypted_content,
-        falcon_signature: bytes_to_hex(&signature),
-        kem_ciphertext: bytes_to_hex(&kem_ciphertext),
-        nonce: bytes_to_hex(&[0u8; 12]), // Simplified for demo
-        auth_tag: bytes_to_hex(&[0u8; 16]), // Simplified for demo
+        falcon_signature: signature,
+        kem_ciphertext,
+        nonce: vec![0u8; 12], // Simplified for demo
+        auth_tag: vec![0u8; 16], // Simplified for demo
     };
 
     // Store message
@@ -388,21 +397,9 @@ async fn verify_message(Json(
 
     // Step 2: Verify signature (simplified - in real app, get actual public key)
     let step_start = std::time::Instant::now();
-    let signature_bytes = match hex::decode(&payload.signature) {
-        Ok(bytes) => bytes,
-        Err(_) => {
-            return JsonResponse(VerificationResponse {
-                success: false,
-                message: "Invalid signature format".to_string(),
-                signature_valid: false,
-                verification_steps,
-            });
-        }
-    };
-
     // For demo purposes, we'll simulate verification
     // In real app, this would use the actual sender's public key
-    let signature_valid = !signature_bytes.is_empty();
+    let signature_valid = !payload.signature.is_empty();
 
     verification_steps.push(CryptoStep {
         step: "Falcon Signature Verification".to_string(),
@@ -1,14 +1,17 @@
 //! Blockchain integration utilities for post-quantum cryptography.
 
 #[cfg(all(feature = "kyber", feature = "dilithium"))]
-use crate::{
-    kyber_keygen,
-    kyber_encapsulate,
-    kyber_decapsulate,
-    dilithium_keygen,
-    dilithium_sign,
-    dilithium_verify,
-};
+use crate::{ kyber_keygen, dilithium_keygen };
+use crate::container::{ self, Container };
+use crate::registry::{ self, AlgorithmId };
+
+/// The KEM of [`generate_blockchain_keypair`] keys.
+pub const KEM_ALGORITHM: AlgorithmId = AlgorithmId::MlKem768;
+/// The signature algorithm of [`generate_blockchain_keypair`] keys.
+pub const SIGNATURE_ALGORITHM: AlgorithmId = AlgorithmId::MlDsa87;
+
+const TRANSACTION_LABEL: &[u8] = b"aegis-blockchain-tx-v1";
+const SIGNING_LABEL: &[u8] = b"aegis-blockchain-tx-sign-v1";
 
 /// Blockchain-specific key pair for hybrid encryption
 #[derive(Debug, Clone)]
@@ -20,13 +23,42 @@ pub struct BlockchainKeyPair {
     pub address: String,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    BlockchainKeyPair {
+        pqc_public_key as pqc_public_key,
+        pqc_secret_key as pqc_secret_key,
+        signature_public_key as signature_public_key,
+        signature_secret_key as signature_secret_key;
+        address: String
+    },
+    // Zero when an algorithm is compiled out, so no key deserializes.
+    [
+        [
+            KEM_ALGORITHM.public_key_len().unwrap_or(0),
+            KEM_ALGORITHM.secret_key_len().unwrap_or(0),
+            SIGNATURE_ALGORITHM.public_key_len().unwrap_or(0),
+            SIGNATURE_ALGORITHM.secret_key_len().unwrap_or(0),
+        ],
+    ]
+);
+
 /// Blockchain transaction with PQC encryption
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BlockchainTransaction {
+    /// The chain the transaction is valid on.
+    pub chain_id: u64,
+    /// The sender's transaction count, for ordering and replay checks
+    /// within a chain.
+    pub nonce: u64,
     pub from_address: String,
     pub to_address: String,
     pub amount: u64,
+    pub fee: u64,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))]
     pub encrypted_data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))]
     pub signature: Vec<u8>,
     pub timestamp: u64,
 }
@@ -69,113 +101,300 @@ pub fn generate_address(public_key: &[u8]) -> String {
     format!("0x{}", hex::encode(address_bytes))
 }
 
-/// Encrypt data for blockchain transaction
-#[cfg(feature = "kyber")]
-pub fn encrypt_for_blockchain(recipient_public_key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
-    // Use Kyber for key encapsulation
-    let encapsulated = kyber_encapsulate(recipient_public_key).map_err(|e|
-        format!("Encapsulation failed: {:?}", e)
-    )?;
-
-    // Combine encapsulated key and encrypted data
-    let mut result = encapsulated.ciphertext().to_vec();
-    result.extend_from_slice(data); // In real implementation, encrypt data with shared key
-
-    Ok(result)
-}
-
-/// Decrypt blockchain transaction data
-#[cfg(feature = "kyber")]
-pub fn decrypt_blockchain_data(
-    secret_key: &[u8],
-    encrypted_data: &[u8]
+/// Encrypt `data` to a recipient's KEM public key.
+///
+/// The data is sealed with AES-256-GCM under a key derived from the KEM
+/// shared secret, in a sealed-envelope [`Container`] that records the KEM.
+/// Decryption therefore works for any KEM in the registry.
+pub fn encrypt_for_blockchain(
+    algorithm: AlgorithmId,
+    recipient_public_key: &[u8],
+    data: &[u8]
 ) -> Result<Vec<u8>, String> {
-    // Extract ciphertext (first part)
-    let ciphertext_len = 1088; // Kyber ciphertext size
-    if encrypted_data.len() < ciphertext_len {
-        return Err("Invalid encrypted data length".to_string());
-    }
-
-    let ciphertext = &encrypted_data[..ciphertext_len];
-    let data = &encrypted_data[ciphertext_len..];
-
-    // Decapsulate the shared key
-    let _shared_key = kyber_decapsulate(secret_key, ciphertext).map_err(|e|
-        format!("Decapsulation failed: {:?}", e)
-    )?;
+    container
+        ::seal(algorithm, recipient_public_key, data)
+        .map(|sealed| sealed.to_bytes())
+        .map_err(|e| format!("Encryption failed: {}", e))
+}
 
-    // In real implementation, decrypt data with shared key
-    Ok(data.to_vec())
+/// Decrypt data from [`encrypt_for_blockchain`].
+pub fn decrypt_blockchain_data(secret_key: &[u8], encrypted_data: &[u8]) -> Result<Vec<u8>, String> {
+    let sealed = Container::from_bytes(encrypted_data).map_err(|e| format!("Invalid encrypted data: {}", e))?;
+    container::open(&sealed, secret_key).map_err(|e| format!("Decryption failed: {}", e))
 }
 
-/// Sign a blockchain transaction
+/// Produce a detached signature over `transaction_data`.
 #[cfg(feature = "dilithium")]
 pub fn sign_transaction(secret_key: &[u8], transaction_data: &[u8]) -> Result<Vec<u8>, String> {
-    Ok(dilithium_sign(secret_key, transaction_data))
+    registry::sign(SIGNATURE_ALGORITHM, secret_key, transaction_data).map_err(|e| e.to_string())
 }
 
-/// Verify a blockchain transaction signature
+/// Verify a detached signature over `transaction_data`.
 #[cfg(feature = "dilithium")]
 pub fn verify_transaction_signature(
     public_key: &[u8],
-    _transaction_data: &[u8],
+    transaction_data: &[u8],
     signature: &[u8]
 ) -> bool {
-    dilithium_verify(public_key, signature)
+    registry::verify(SIGNATURE_ALGORITHM, public_key, transaction_data, signature)
+}
+
+/// Chain, nonce and fee of a new transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionOptions {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub fee: u64,
+}
+
+impl TransactionOptions {
+    /// Options for the first transaction of an account, with no fee.
+    pub fn new(chain_id: u64) -> Self {
+        TransactionOptions { chain_id, nonce: 0, fee: 0 }
+    }
+
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    pub fn with_fee(mut self, fee: u64) -> Self {
+        self.fee = fee;
+        self
+    }
+}
+
+impl BlockchainTransaction {
+    /// Append the RLP items of every field except the signature.
+    fn encode_fields(&self, out: &mut Vec<u8>) {
+        rlp::encode_uint(out, self.chain_id);
+        rlp::encode_uint(out, self.nonce);
+        rlp::encode_bytes(out, self.from_address.as_bytes());
+        rlp::encode_bytes(out, self.to_address.as_bytes());
+        rlp::encode_uint(out, self.amount);
+        rlp::encode_uint(out, self.fee);
+        rlp::encode_uint(out, self.timestamp);
+        rlp::encode_bytes(out, &self.encrypted_data);
+    }
+
+    /// The canonical encoding: an RLP list of
+    ///
+    /// ```text
+    /// [chain_id, nonce, from_address, to_address, amount, fee, timestamp,
+    ///  encrypted_data, signature]
+    /// ```
+    ///
+    /// Integers are minimal big-endian byte strings, as in Ethereum.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut fields = Vec::with_capacity(128 + self.encrypted_data.len() + self.signature.len());
+        self.encode_fields(&mut fields);
+        rlp::encode_bytes(&mut fields, &self.signature);
+        let mut out = Vec::with_capacity(fields.len() + 9);
+        rlp::encode_list(&mut out, &fields);
+        out
+    }
+
+    /// Decode [`BlockchainTransaction::to_bytes`]. Non-canonical encodings
+    /// are rejected, so every transaction has exactly one encoding.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let items = rlp::decode_list(data)?;
+        let [chain_id, nonce, from, to, amount, fee, timestamp, encrypted_data, signature] = items[..] else {
+            return Err(format!("Expected 9 transaction fields, found {}", items.len()));
+        };
+        let text = |item: &[u8]| String::from_utf8(item.to_vec()).map_err(|_| "Address is not UTF-8".to_string());
+        Ok(BlockchainTransaction {
+            chain_id: rlp::decode_uint(chain_id)?,
+            nonce: rlp::decode_uint(nonce)?,
+            from_address: text(from)?,
+            to_address: text(to)?,
+            amount: rlp::decode_uint(amount)?,
+            fee: rlp::decode_uint(fee)?,
+            encrypted_data: encrypted_data.to_vec(),
+            signature: signature.to_vec(),
+            timestamp: rlp::decode_uint(timestamp)?,
+        })
+    }
+
+    /// The digest a sender signs:
+    /// `SHA3-256("aegis-blockchain-tx-sign-v1" ‖ rlp([fields except signature]))`.
+    /// It includes the chain ID, so a signature is only valid on one chain.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut fields = Vec::with_capacity(128 + self.encrypted_data.len());
+        self.encode_fields(&mut fields);
+        let mut unsigned = Vec::with_capacity(fields.len() + 9);
+        rlp::encode_list(&mut unsigned, &fields);
+        domain_hash(SIGNING_LABEL, &unsigned)
+    }
+
+    /// The transaction ID: `SHA3-256("aegis-blockchain-tx-v1" ‖ to_bytes())`.
+    pub fn hash(&self) -> [u8; 32] {
+        domain_hash(TRANSACTION_LABEL, &self.to_bytes())
+    }
+}
+
+fn domain_hash(label: &[u8], data: &[u8]) -> [u8; 32] {
+    use sha3::{ Digest, Sha3_256 };
+
+    let mut hasher = Sha3_256::new();
+    hasher.update(label);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Recursive length prefix encoding, restricted to what transactions use:
+/// byte strings and one flat list of them.
+mod rlp {
+    const SHORT_LIMIT: usize = 55;
+
+    fn encode_header(out: &mut Vec<u8>, short_base: u8, len: usize) {
+        if len <= SHORT_LIMIT {
+            out.push(short_base + (len as u8));
+        } else {
+            let len_bytes = (len as u64).to_be_bytes();
+            let skip = len_bytes.iter().take_while(|&&b| b == 0).count();
+            out.push(short_base + (SHORT_LIMIT as u8) + ((8 - skip) as u8));
+            out.extend_from_slice(&len_bytes[skip..]);
+        }
+    }
+
+    pub fn encode_bytes(out: &mut Vec<u8>, data: &[u8]) {
+        if data.len() == 1 && data[0] < 0x80 {
+            out.push(data[0]);
+        } else {
+            encode_header(out, 0x80, data.len());
+            out.extend_from_slice(data);
+        }
+    }
+
+    pub fn encode_uint(out: &mut Vec<u8>, value: u64) {
+        let bytes = value.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        encode_bytes(out, &bytes[skip..]);
+    }
+
+    pub fn encode_list(out: &mut Vec<u8>, payload: &[u8]) {
+        encode_header(out, 0xc0, payload.len());
+        out.extend_from_slice(payload);
+    }
+
+    /// Split off one item: whether it is a list, its payload and the rest.
+    fn decode_item(data: &[u8]) -> Result<(bool, &[u8], &[u8]), String> {
+        let (&prefix, rest) = data.split_first().ok_or("Unexpected end of RLP data")?;
+        let (is_list, base) = match prefix {
+            0x00..=0x7f => {
+                return Ok((false, &data[..1], rest));
+            }
+            0x80..=0xbf => (false, 0x80u8),
+            _ => (true, 0xc0u8),
+        };
+        let short = (prefix - base) as usize;
+        let (len, rest) = if short <= SHORT_LIMIT {
+            (short, rest)
+        } else {
+            let len_len = short - SHORT_LIMIT;
+            if rest.len() < len_len || rest[0] == 0 {
+                return Err("Non-canonical RLP length".to_string());
+            }
+            let len = rest[..len_len].iter().try_fold(0usize, |acc, &b| {
+                acc.checked_mul(256).map(|acc| acc + (b as usize))
+            });
+            match len {
+                Some(len) if len > SHORT_LIMIT => (len, &rest[len_len..]),
+                _ => {
+                    return Err("Non-canonical RLP length".to_string());
+                }
+            }
+        };
+        if rest.len() < len {
+            return Err("Truncated RLP item".to_string());
+        }
+        let (payload, rest) = rest.split_at(len);
+        if !is_list && len == 1 && payload[0] < 0x80 {
+            return Err("Non-canonical RLP single byte".to_string());
+        }
+        Ok((is_list, payload, rest))
+    }
+
+    /// Decode a list of byte strings that fills `data` exactly.
+    pub fn decode_list(data: &[u8]) -> Result<Vec<&[u8]>, String> {
+        let (is_list, mut payload, rest) = decode_item(data)?;
+        if !is_list || !rest.is_empty() {
+            return Err("Expected one RLP list".to_string());
+        }
+        let mut items = Vec::new();
+        while !payload.is_empty() {
+            let (is_list, item, rest) = decode_item(payload)?;
+            if is_list {
+                return Err("Unexpected nested RLP list".to_string());
+            }
+            items.push(item);
+            payload = rest;
+        }
+        Ok(items)
+    }
+
+    pub fn decode_uint(item: &[u8]) -> Result<u64, String> {
+        if item.len() > 8 || item.first() == Some(&0) {
+            return Err("Non-canonical RLP integer".to_string());
+        }
+        Ok(item.iter().fold(0u64, |acc, &b| (acc << 8) | (b as u64)))
+    }
 }
 
-/// Create a blockchain transaction
+/// Create a signed transaction carrying `data` encrypted to the
+/// recipient's KEM public key.
 #[cfg(all(feature = "kyber", feature = "dilithium"))]
 pub fn create_transaction(
     from_keypair: &BlockchainKeyPair,
     to_address: &str,
+    recipient_public_key: &[u8],
     amount: u64,
-    data: &[u8]
+    data: &[u8],
+    options: TransactionOptions
 ) -> Result<BlockchainTransaction, String> {
-    // Encrypt data for recipient
-    let encrypted_data = encrypt_for_blockchain(&from_keypair.pqc_public_key, data)?;
-
-    // Create transaction data
-    let tx_data = format!("{}:{}:{}", from_keypair.address, to_address, amount).into_bytes();
-
-    // Sign transaction
-    let signature = sign_transaction(&from_keypair.signature_secret_key, &tx_data)?;
-
-    Ok(BlockchainTransaction {
+    let mut transaction = BlockchainTransaction {
+        chain_id: options.chain_id,
+        nonce: options.nonce,
         from_address: from_keypair.address.clone(),
         to_address: to_address.to_string(),
         amount,
-        encrypted_data,
-        signature,
+        fee: options.fee,
+        encrypted_data: encrypt_for_blockchain(KEM_ALGORITHM, recipient_public_key, data)?,
+        signature: Vec::new(),
         timestamp: std::time::SystemTime
             ::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs(),
-    })
+    };
+    transaction.signature = sign_transaction(&from_keypair.signature_secret_key, &transaction.signing_hash())?;
+    Ok(transaction)
 }
 
-/// Verify a blockchain transaction
+/// Verify a transaction for `chain_id`: `sender_public_key` must hash to
+/// the sender address and have signed every field of the transaction.
+/// Transactions for other chains are rejected. Checking the nonce against
+/// the sender's account is left to the ledger.
 #[cfg(feature = "dilithium")]
-pub fn verify_transaction(transaction: &BlockchainTransaction, sender_public_key: &[u8]) -> bool {
-    let tx_data = format!(
-        "{}:{}:{}",
-        transaction.from_address,
-        transaction.to_address,
-        transaction.amount
-    ).into_bytes();
-    verify_transaction_signature(sender_public_key, &tx_data, &transaction.signature)
+pub fn verify_transaction(transaction: &BlockchainTransaction, sender_public_key: &[u8], chain_id: u64) -> bool {
+    transaction.chain_id == chain_id &&
+        generate_address(sender_public_key) == transaction.from_address &&
+        verify_transaction_signature(sender_public_key, &transaction.signing_hash(), &transaction.signature)
 }
 
 /// Smart contract integration for PQC operations
 pub mod smart_contract {
     use super::*;
+    use crate::multisig::{ MultisigBundle, MultisigPolicy };
 
     /// Smart contract state with PQC keys
     #[derive(Debug, Clone)]
     pub struct SmartContractState {
         pub contract_address: String,
         pub admin_keypair: BlockchainKeyPair,
+        /// When set, state changes require a threshold of admin signatures
+        /// through [`execute_admin_function`] instead of a single caller.
+        pub admin_policy: Option<MultisigPolicy>,
         pub encrypted_state: Vec<u8>,
         pub state_signature: Vec<u8>,
     }
@@ -188,11 +407,17 @@ pub mod smart_contract {
         SmartContractState {
             contract_address,
             admin_keypair,
+            admin_policy: None,
             encrypted_state: Vec::new(),
             state_signature: Vec::new(),
         }
     }
 
+    /// Canonical call data signed by callers and admins.
+    pub fn call_data(function_name: &str, parameters: &[u8]) -> Vec<u8> {
+        format!("{}:{}", function_name, hex::encode(parameters)).into_bytes()
+    }
+
     /// Execute a smart contract function with PQC verification
     #[cfg(feature = "dilithium")]
     pub fn execute_contract_function(
@@ -203,11 +428,38 @@ pub mod smart_contract {
         caller_public_key: &[u8]
     ) -> Result<Vec<u8>, String> {
         // Verify caller signature
-        let call_data = format!("{}:{}", function_name, hex::encode(parameters)).into_bytes();
+        let call_data = call_data(function_name, parameters);
         if !verify_transaction_signature(caller_public_key, &call_data, caller_signature) {
             return Err("Invalid caller signature".to_string());
         }
+        if contract.admin_policy.is_some() && function_name != "getState" {
+            return Err("Function requires admin multisig".to_string());
+        }
+        apply_function(contract, function_name, parameters)
+    }
+
+    /// Execute a function authorized by a threshold of the contract's
+    /// admin policy, each admin having signed [`call_data`].
+    #[cfg(feature = "dilithium")]
+    pub fn execute_admin_function(
+        contract: &mut SmartContractState,
+        function_name: &str,
+        parameters: &[u8],
+        bundle: &MultisigBundle
+    ) -> Result<Vec<u8>, String> {
+        let policy = contract.admin_policy.as_ref().ok_or("Contract has no admin policy")?;
+        policy
+            .verify(&call_data(function_name, parameters), bundle)
+            .map_err(|e| format!("Admin authorization failed: {}", e))?;
+        apply_function(contract, function_name, parameters)
+    }
 
+    #[cfg(feature = "dilithium")]
+    fn apply_function(
+        contract: &mut SmartContractState,
+        function_name: &str,
+        parameters: &[u8]
+    ) -> Result<Vec<u8>, String> {
         // Execute function (simplified)
         match function_name {
             "setState" => {
@@ -246,17 +498,71 @@ mod tests {
         let transaction = create_transaction(
             &sender,
             &recipient.address,
+            &recipient.pqc_public_key,
             1000,
-            b"Test transaction data"
+            b"Test transaction data",
+            TransactionOptions::new(1).with_nonce(7).with_fee(21)
         ).unwrap();
 
         assert_eq!(transaction.from_address, sender.address);
         assert_eq!(transaction.to_address, recipient.address);
         assert_eq!(transaction.amount, 1000);
+        assert_eq!((transaction.chain_id, transaction.nonce, transaction.fee), (1, 7, 21));
+        assert_eq!(
+            decrypt_blockchain_data(&recipient.pqc_secret_key, &transaction.encrypted_data).unwrap(),
+            b"Test transaction data"
+        );
+        assert!(decrypt_blockchain_data(&sender.pqc_secret_key, &transaction.encrypted_data).is_err());
 
         // Verify transaction
-        let is_valid = verify_transaction(&transaction, &sender.signature_public_key);
+        let is_valid = verify_transaction(&transaction, &sender.signature_public_key, 1);
         assert!(is_valid);
+        assert!(!verify_transaction(&transaction, &recipient.signature_public_key, 1));
+
+        // The signature covers every field, and only this transaction.
+        let mut altered = transaction.clone();
+        altered.amount = 1001;
+        assert!(!verify_transaction(&altered, &sender.signature_public_key, 1));
+        let options = TransactionOptions::new(1);
+        let other = create_transaction(&sender, &recipient.address, &recipient.pqc_public_key, 5, b"", options).unwrap();
+        altered = transaction.clone();
+        altered.signature = other.signature;
+        assert!(!verify_transaction(&altered, &sender.signature_public_key, 1));
+    }
+
+    #[test]
+    #[cfg(all(feature = "kyber", feature = "dilithium"))]
+    fn test_canonical_encoding_and_replay_protection() {
+        let sender = generate_blockchain_keypair();
+        let recipient = generate_blockchain_keypair();
+        let options = TransactionOptions::new(1).with_nonce(300).with_fee(0x80);
+        let transaction = create_transaction(&sender, &recipient.address, &recipient.pqc_public_key, 0, b"", options)
+            .unwrap();
+
+        let bytes = transaction.to_bytes();
+        assert_eq!(&bytes[..3], [0xf9, ((bytes.len() - 3) >> 8) as u8, (bytes.len() - 3) as u8]);
+        // chain_id 1, nonce 300, then the 42-character sender address.
+        assert_eq!(&bytes[3..8], [0x01, 0x82, 0x01, 0x2c, 0x80 + 42]);
+        let decoded = BlockchainTransaction::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.to_bytes(), bytes);
+        assert_eq!(decoded.hash(), transaction.hash());
+        assert_ne!(transaction.hash(), transaction.signing_hash());
+
+        // The same signed transaction is rejected on another chain, and
+        // moving it there breaks the signature.
+        assert!(verify_transaction(&decoded, &sender.signature_public_key, 1));
+        assert!(!verify_transaction(&decoded, &sender.signature_public_key, 2));
+        let mut replayed = decoded.clone();
+        replayed.chain_id = 2;
+        assert!(!verify_transaction(&replayed, &sender.signature_public_key, 2));
+
+        // A non-minimal integer or trailing byte does not decode.
+        let mut padded = bytes.clone();
+        padded.push(0);
+        assert!(BlockchainTransaction::from_bytes(&padded).is_err());
+        assert!(rlp::decode_uint(&[0x00, 0x01]).is_err());
+        assert!(rlp::decode_list(&[0xc2, 0x81, 0x05]).is_err());
+        assert_eq!(rlp::decode_list(&[0xc2, 0x81, 0x80]).unwrap(), [&[0x80u8][..]]);
     }
 
     #[test]
@@ -267,7 +573,11 @@ mod tests {
 
         let caller = generate_blockchain_keypair();
         let call_data = b"test state data";
-        let signature = sign_transaction(&caller.signature_secret_key, call_data).unwrap();
+        let sign = |function, parameters| {
+            let data = smart_contract::call_data(function, parameters);
+            sign_transaction(&caller.signature_secret_key, &data).unwrap()
+        };
+        let signature = sign("setState", call_data);
 
         // Set state
         let result = smart_contract
@@ -282,17 +592,49 @@ mod tests {
 
         assert_eq!(result, b"State updated");
 
+        // A signature is only good for the call it was made for.
+        assert!(
+            smart_contract
+                ::execute_contract_function(&mut contract, "getState", &[], &signature, &caller.signature_public_key)
+                .is_err()
+        );
+
         // Get state
         let state = smart_contract
             ::execute_contract_function(
                 &mut contract,
                 "getState",
                 &[],
-                &signature,
+                &sign("getState", &[]),
                 &caller.signature_public_key
             )
             .unwrap();
 
         assert_eq!(state, call_data);
     }
+
+    #[test]
+    #[cfg(all(feature = "kyber", feature = "dilithium"))]
+    fn test_smart_contract_admin_multisig() {
+        use crate::multisig::{ MultisigPolicy, PolicyKey, SignatureCollector };
+        use crate::registry::{ self, AlgorithmId };
+
+        let admins: Vec<_> = (0..3).map(|_| registry::keygen(AlgorithmId::MlDsa65).unwrap()).collect();
+        let keys = admins.iter().map(|(pk, _)| PolicyKey::new(AlgorithmId::MlDsa65, pk)).collect();
+        let mut contract = smart_contract::deploy_contract(generate_blockchain_keypair());
+        contract.admin_policy = Some(MultisigPolicy::new(2, keys).unwrap());
+        let policy = contract.admin_policy.clone().unwrap();
+
+        let call_data = smart_contract::call_data("setState", b"v2");
+        let mut collector = SignatureCollector::new(&policy, &call_data);
+        for (pk, sk) in &admins[..2] {
+            collector.add(policy.sign(AlgorithmId::MlDsa65, pk, sk, &call_data).unwrap()).unwrap();
+        }
+        let bundle = collector.finish().unwrap();
+
+        assert!(smart_contract::execute_admin_function(&mut contract, "setState", b"v3", &bundle).is_err());
+        let result = smart_contract::execute_admin_function(&mut contract, "setState", b"v2", &bundle).unwrap();
+        assert_eq!(result, b"State updated");
+        assert_eq!(contract.encrypted_state, b"v2");
+    }
 }
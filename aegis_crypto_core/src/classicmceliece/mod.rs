@@ -31,6 +31,16 @@ pub struct ClassicMcElieceKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    ClassicMcElieceKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_classicmceliece::mceliece348864,
+        pqcrypto_classicmceliece::mceliece460896,
+        pqcrypto_classicmceliece::mceliece6688128
+    )
+);
+
 #[wasm_bindgen]
 impl ClassicMcElieceKeyPair {
     #[wasm_bindgen(getter)]
@@ -49,6 +59,16 @@ pub struct ClassicMcElieceEncapsulated {
     shared_secret: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    ClassicMcElieceEncapsulated { ciphertext as ciphertext, shared_secret as shared_secret },
+    crate::serialization::encapsulated_lengths!(
+        pqcrypto_classicmceliece::mceliece348864,
+        pqcrypto_classicmceliece::mceliece460896,
+        pqcrypto_classicmceliece::mceliece6688128
+    )
+);
+
 #[wasm_bindgen]
 impl ClassicMcElieceEncapsulated {
     #[wasm_bindgen(getter)]
@@ -0,0 +1,594 @@
+//! Self-describing, versioned binary container for Aegis artifacts.
+//!
+//! A container wraps a key, KEM ciphertext, signature or sealed envelope
+//! together with the algorithm that produced it, so that a decoder never
+//! has to guess sizes or assume a default parameter set. The format is
+//! documented in `docs/container_format.md`:
+//!
+//! ```text
+//! container := "AEGC" version:u8 kind:u8 field*
+//! field     := tag:u8 length:u32be value
+//! ```
+//!
+//! Fields appear in strictly increasing tag order, each at most once, and
+//! the payload (tag 0x7f) is always last. Unknown tags below 0x40 are
+//! optional: they are carried through unchanged so that newer writers can
+//! add metadata. Unknown tags from 0x40 upwards are critical and cause the
+//! container to be rejected.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use aes_gcm::{ aead::{ Aead, KeyInit, Payload }, Aes256Gcm, Nonce };
+use hkdf::Hkdf;
+use sha2::Sha256;
+use sha3::{ Digest, Sha3_256 };
+use zeroize::Zeroize;
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// Container magic bytes.
+pub const MAGIC: &[u8; 4] = b"AEGC";
+/// Current container format version.
+pub const VERSION: u8 = 1;
+/// Length of a key identifier in bytes.
+pub const KEY_ID_LEN: usize = 16;
+
+const TAG_ALGORITHM: u8 = 0x01;
+const TAG_PARAMETER_SET: u8 = 0x02;
+const TAG_KEY_ID: u8 = 0x03;
+const TAG_CREATED: u8 = 0x04;
+const TAG_KEM_CIPHERTEXT: u8 = 0x05;
+const TAG_NONCE: u8 = 0x06;
+const TAG_PAYLOAD: u8 = 0x7f;
+const TAG_FIRST_CRITICAL: u8 = 0x40;
+
+const ENVELOPE_INFO: &[u8] = b"aegis-container-v1 sealed envelope";
+const NONCE_LEN: usize = 12;
+
+/// The kind of artifact carried by a container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ContainerKind {
+    PublicKey = 1,
+    SecretKey = 2,
+    Ciphertext = 3,
+    Signature = 4,
+    SealedEnvelope = 5,
+}
+
+impl ContainerKind {
+    pub fn from_u8(value: u8) -> Result<Self, ContainerError> {
+        match value {
+            1 => Ok(ContainerKind::PublicKey),
+            2 => Ok(ContainerKind::SecretKey),
+            3 => Ok(ContainerKind::Ciphertext),
+            4 => Ok(ContainerKind::Signature),
+            5 => Ok(ContainerKind::SealedEnvelope),
+            other => Err(ContainerError::Malformed(format!("unknown container kind {}", other))),
+        }
+    }
+}
+
+/// Container-specific error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerError {
+    Malformed(String),
+    UnsupportedVersion(u8),
+    Algorithm(AlgorithmError),
+    WrongKind(ContainerKind),
+    DecryptionFailed,
+}
+
+impl std::fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerError::Malformed(msg) => write!(f, "Malformed container: {}", msg),
+            ContainerError::UnsupportedVersion(v) => write!(f, "Unsupported container version {}", v),
+            ContainerError::Algorithm(e) => write!(f, "{}", e),
+            ContainerError::WrongKind(kind) => write!(f, "Unexpected container kind {:?}", kind),
+            ContainerError::DecryptionFailed => write!(f, "Decryption failed"),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+impl From<AlgorithmError> for ContainerError {
+    fn from(e: AlgorithmError) -> Self {
+        ContainerError::Algorithm(e)
+    }
+}
+
+/// Derive the key identifier of a public key: the first 16 bytes of
+/// SHA3-256 over the algorithm code and the key bytes.
+pub fn key_id(algorithm: AlgorithmId, public_key: &[u8]) -> [u8; KEY_ID_LEN] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(algorithm.code().to_be_bytes());
+    hasher.update(public_key);
+    let digest = hasher.finalize();
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&digest[..KEY_ID_LEN]);
+    id
+}
+
+/// A decoded container.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    kind: ContainerKind,
+    algorithm: AlgorithmId,
+    key_id: Vec<u8>,
+    created: u64,
+    kem_ciphertext: Vec<u8>,
+    nonce: Vec<u8>,
+    /// Unknown optional fields, preserved so re-encoding is lossless.
+    extensions: Vec<(u8, Vec<u8>)>,
+    payload: Vec<u8>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Container {
+    /// Kind code (1 = public key, 2 = secret key, 3 = ciphertext,
+    /// 4 = signature, 5 = sealed envelope).
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn kind_code(&self) -> u8 {
+        self.kind as u8
+    }
+
+    /// Stable algorithm code from the registry.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn algorithm_code(&self) -> u16 {
+        self.algorithm.code()
+    }
+
+    /// Parameter-set name, e.g. `ML-KEM-768`.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn parameter_set(&self) -> String {
+        self.algorithm.name().to_string()
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn key_id(&self) -> Vec<u8> {
+        self.key_id.clone()
+    }
+
+    /// Creation time in seconds since the Unix epoch.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn created(&self) -> u64 {
+        self.created
+    }
+
+    #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
+    pub fn payload(&self) -> Vec<u8> {
+        self.payload.clone()
+    }
+
+    /// Serialize the container.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = toBytes))]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.header_bytes();
+        write_field(&mut out, TAG_PAYLOAD, &self.payload);
+        out
+    }
+}
+
+impl Container {
+    fn new(kind: ContainerKind, algorithm: AlgorithmId, key_id: Vec<u8>, payload: Vec<u8>) -> Self {
+        Container {
+            kind,
+            algorithm,
+            key_id,
+            created: now(),
+            kem_ciphertext: Vec::new(),
+            nonce: Vec::new(),
+            extensions: Vec::new(),
+            payload,
+        }
+    }
+
+    /// Wrap a public key. The key ID is derived from the key itself.
+    pub fn public_key(algorithm: AlgorithmId, public_key: &[u8]) -> Self {
+        let id = key_id(algorithm, public_key).to_vec();
+        Container::new(ContainerKind::PublicKey, algorithm, id, public_key.to_vec())
+    }
+
+    /// Wrap a secret key under the key ID of its public half.
+    pub fn secret_key(algorithm: AlgorithmId, public_key: &[u8], secret_key: &[u8]) -> Self {
+        let id = key_id(algorithm, public_key).to_vec();
+        Container::new(ContainerKind::SecretKey, algorithm, id, secret_key.to_vec())
+    }
+
+    /// Wrap a raw KEM ciphertext addressed to `recipient_key_id`.
+    pub fn ciphertext(algorithm: AlgorithmId, recipient_key_id: &[u8], ciphertext: &[u8]) -> Self {
+        Container::new(
+            ContainerKind::Ciphertext,
+            algorithm,
+            recipient_key_id.to_vec(),
+            ciphertext.to_vec()
+        )
+    }
+
+    /// Wrap a detached signature made by the key `signer_key_id`.
+    pub fn signature(algorithm: AlgorithmId, signer_key_id: &[u8], signature: &[u8]) -> Self {
+        Container::new(ContainerKind::Signature, algorithm, signer_key_id.to_vec(), signature.to_vec())
+    }
+
+    pub fn kind(&self) -> ContainerKind {
+        self.kind
+    }
+
+    pub fn algorithm(&self) -> AlgorithmId {
+        self.algorithm
+    }
+
+    pub fn payload_bytes(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Everything except the payload field; used as AEAD associated data.
+    fn header_bytes(&self) -> Vec<u8> {
+        let code = self.algorithm.code().to_be_bytes();
+        let created = self.created.to_be_bytes();
+        let mut fields: Vec<(u8, &[u8])> = vec![
+            (TAG_ALGORITHM, &code),
+            (TAG_PARAMETER_SET, self.algorithm.name().as_bytes()),
+            (TAG_KEY_ID, &self.key_id),
+            (TAG_CREATED, &created),
+            (TAG_KEM_CIPHERTEXT, &self.kem_ciphertext),
+            (TAG_NONCE, &self.nonce)
+        ];
+        fields.extend(self.extensions.iter().map(|(tag, value)| (*tag, value.as_slice())));
+        fields.sort_by_key(|(tag, _)| *tag);
+
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        out.push(self.kind as u8);
+        for (tag, value) in fields {
+            if !(is_optional(tag) && value.is_empty()) {
+                write_field(&mut out, tag, value);
+            }
+        }
+        out
+    }
+
+    /// Parse a container.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ContainerError> {
+        if data.len() < 6 || &data[..4] != MAGIC {
+            return Err(ContainerError::Malformed("bad magic".into()));
+        }
+        if data[4] != VERSION {
+            return Err(ContainerError::UnsupportedVersion(data[4]));
+        }
+        let kind = ContainerKind::from_u8(data[5])?;
+
+        let mut algorithm = None;
+        let mut parameter_set = None;
+        let mut key_id = Vec::new();
+        let mut created = None;
+        let mut kem_ciphertext = Vec::new();
+        let mut nonce = Vec::new();
+        let mut extensions = Vec::new();
+        let mut payload = None;
+        let mut last_tag = 0u8;
+        let mut rest = &data[6..];
+        while !rest.is_empty() {
+            if payload.is_some() {
+                return Err(ContainerError::Malformed("data after payload".into()));
+            }
+            if rest.len() < 5 {
+                return Err(ContainerError::Malformed("truncated field header".into()));
+            }
+            let tag = rest[0];
+            let len = u32::from_be_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+            let end = 5usize
+                .checked_add(len)
+                .ok_or_else(|| ContainerError::Malformed("field length overflows".into()))?;
+            let value = rest
+                .get(5..end)
+                .ok_or_else(|| ContainerError::Malformed("truncated field".into()))?;
+            rest = &rest[end..];
+            if tag <= last_tag {
+                return Err(ContainerError::Malformed("fields out of order".into()));
+            }
+            last_tag = tag;
+            if is_optional(tag) && value.is_empty() {
+                return Err(ContainerError::Malformed(format!("empty field 0x{:02x}", tag)));
+            }
+            match tag {
+                TAG_ALGORITHM => {
+                    let code: [u8; 2] = value
+                        .try_into()
+                        .map_err(|_| ContainerError::Malformed("bad algorithm field".into()))?;
+                    algorithm = Some(AlgorithmId::from_code(u16::from_be_bytes(code))?);
+                }
+                TAG_PARAMETER_SET => {
+                    parameter_set = Some(value.to_vec());
+                }
+                TAG_KEY_ID => {
+                    key_id = value.to_vec();
+                }
+                TAG_CREATED => {
+                    let bytes: [u8; 8] = value
+                        .try_into()
+                        .map_err(|_| ContainerError::Malformed("bad creation time".into()))?;
+                    created = Some(u64::from_be_bytes(bytes));
+                }
+                TAG_KEM_CIPHERTEXT => {
+                    kem_ciphertext = value.to_vec();
+                }
+                TAG_NONCE => {
+                    nonce = value.to_vec();
+                }
+                TAG_PAYLOAD => {
+                    payload = Some(value.to_vec());
+                }
+                t if t >= TAG_FIRST_CRITICAL => {
+                    return Err(ContainerError::Malformed(format!("unknown critical field 0x{:02x}", t)));
+                }
+                t => extensions.push((t, value.to_vec())),
+            }
+        }
+
+        let algorithm = algorithm.ok_or_else(|| ContainerError::Malformed("missing algorithm".into()))?;
+        if parameter_set.as_deref() != Some(algorithm.name().as_bytes()) {
+            return Err(ContainerError::Malformed("parameter set does not match algorithm".into()));
+        }
+        Ok(Container {
+            kind,
+            algorithm,
+            key_id,
+            created: created.ok_or_else(|| ContainerError::Malformed("missing creation time".into()))?,
+            kem_ciphertext,
+            nonce,
+            extensions,
+            payload: payload.ok_or_else(|| ContainerError::Malformed("missing payload".into()))?,
+        })
+    }
+
+    /// Verify a signature container over `message` with `public_key`.
+    /// The algorithm is taken from the container, not from the caller.
+    pub fn verify_signature(&self, public_key: &[u8], message: &[u8]) -> Result<bool, ContainerError> {
+        if self.kind != ContainerKind::Signature {
+            return Err(ContainerError::WrongKind(self.kind));
+        }
+        if !self.key_id.is_empty() && self.key_id != key_id(self.algorithm, public_key) {
+            return Ok(false);
+        }
+        Ok(registry::verify(self.algorithm, public_key, message, &self.payload))
+    }
+}
+
+/// Known fields that are omitted when empty. Present but empty, they
+/// would not survive re-encoding, so the parser rejects them.
+fn is_optional(tag: u8) -> bool {
+    matches!(tag, TAG_KEY_ID | TAG_KEM_CIPHERTEXT | TAG_NONCE)
+}
+
+fn write_field(out: &mut Vec<u8>, tag: u8, value: &[u8]) {
+    out.push(tag);
+    out.extend_from_slice(&(value.len() as u32).to_be_bytes());
+    out.extend_from_slice(value);
+}
+
+fn envelope_cipher(algorithm: AlgorithmId, shared_secret: &[u8]) -> Aes256Gcm {
+    let mut info = ENVELOPE_INFO.to_vec();
+    info.extend_from_slice(&algorithm.code().to_be_bytes());
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>
+        ::new(None, shared_secret)
+        .expand(&info, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    let cipher = Aes256Gcm::new_from_slice(&key).expect("32-byte key");
+    key.zeroize();
+    cipher
+}
+
+/// Seal `plaintext` to a KEM public key. The container records the KEM so
+/// that it can be opened after the default algorithm has been rotated.
+pub fn seal(
+    algorithm: AlgorithmId,
+    recipient_public_key: &[u8],
+    plaintext: &[u8]
+) -> Result<Container, ContainerError> {
+    if algorithm.kind() != AlgorithmKind::Kem {
+        return Err(AlgorithmError::Unsupported(algorithm).into());
+    }
+    let (kem_ciphertext, mut shared_secret) = registry::encapsulate(algorithm, recipient_public_key)?;
+    let mut nonce = vec![0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).expect("RNG failed");
+
+    let mut container = Container::new(
+        ContainerKind::SealedEnvelope,
+        algorithm,
+        key_id(algorithm, recipient_public_key).to_vec(),
+        Vec::new()
+    );
+    container.kem_ciphertext = kem_ciphertext;
+    container.nonce = nonce;
+    let aad = container.header_bytes();
+    let cipher = envelope_cipher(algorithm, &shared_secret);
+    shared_secret.zeroize();
+    container.payload = cipher
+        .encrypt(Nonce::from_slice(&container.nonce), Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| ContainerError::Malformed("encryption failed".into()))?;
+    Ok(container)
+}
+
+/// Open a sealed envelope with the recipient's secret key.
+pub fn open(container: &Container, secret_key: &[u8]) -> Result<Vec<u8>, ContainerError> {
+    if container.kind != ContainerKind::SealedEnvelope {
+        return Err(ContainerError::WrongKind(container.kind));
+    }
+    if container.nonce.len() != NONCE_LEN {
+        return Err(ContainerError::Malformed("bad nonce length".into()));
+    }
+    let mut shared_secret = registry::decapsulate(
+        container.algorithm,
+        secret_key,
+        &container.kem_ciphertext
+    )?;
+    let cipher = envelope_cipher(container.algorithm, &shared_secret);
+    shared_secret.zeroize();
+    cipher
+        .decrypt(Nonce::from_slice(&container.nonce), Payload {
+            msg: &container.payload,
+            aad: &container.header_bytes(),
+        })
+        .map_err(|_| ContainerError::DecryptionFailed)
+}
+
+/// Seal `plaintext` to a KEM public key and return the encoded container.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn container_seal(
+    algorithm_code: u16,
+    recipient_public_key: &[u8],
+    plaintext: &[u8]
+) -> Result<Vec<u8>, String> {
+    let algorithm = AlgorithmId::from_code(algorithm_code).map_err(|e| e.to_string())?;
+    seal(algorithm, recipient_public_key, plaintext)
+        .map(|c| c.to_bytes())
+        .map_err(|e| e.to_string())
+}
+
+/// Open an encoded sealed-envelope container.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn container_open(secret_key: &[u8], container: &[u8]) -> Result<Vec<u8>, String> {
+    let container = Container::from_bytes(container).map_err(|e| e.to_string())?;
+    open(&container, secret_key).map_err(|e| e.to_string())
+}
+
+/// Emit a key, ciphertext or signature container.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn container_encode(
+    kind: u8,
+    algorithm_code: u16,
+    key_id: &[u8],
+    payload: &[u8]
+) -> Result<Vec<u8>, String> {
+    let kind = ContainerKind::from_u8(kind).map_err(|e| e.to_string())?;
+    if kind == ContainerKind::SealedEnvelope {
+        return Err("use container_seal to create sealed envelopes".to_string());
+    }
+    let algorithm = AlgorithmId::from_code(algorithm_code).map_err(|e| e.to_string())?;
+    Ok(Container::new(kind, algorithm, key_id.to_vec(), payload.to_vec()).to_bytes())
+}
+
+/// Parse an encoded container.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn container_decode(data: &[u8]) -> Result<Container, String> {
+    Container::from_bytes(data).map_err(|e| e.to_string())
+}
+
+fn now() -> u64 {
+    std::time::SystemTime
+        ::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Containers serialize as their encoded bytes. Secret key containers
+/// require [`ExposeSecrets`](crate::serialization::ExposeSecrets).
+#[cfg(feature = "serde")]
+impl serde::Serialize for Container {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.kind == ContainerKind::SecretKey {
+            return crate::serialization::refuse_secret::<S>("Container");
+        }
+        crate::serialization::bytes::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl crate::serialization::SerializeSecret for Container {
+    fn serialize_secret<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serialization::bytes::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Container {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::serialization::bytes::deserialize(deserializer)?;
+        Container::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "kyber")]
+    fn test_sealed_envelope_survives_algorithm_rotation() {
+        // Data sealed under the old default must open regardless of the
+        // algorithm a newer writer prefers.
+        for alg in [AlgorithmId::MlKem768, AlgorithmId::MlKem1024] {
+            let (pk, sk) = registry::keygen(alg).unwrap();
+            let bytes = seal(alg, &pk, b"secret payload").unwrap().to_bytes();
+            let parsed = Container::from_bytes(&bytes).unwrap();
+            assert_eq!(parsed.algorithm(), alg);
+            assert_eq!(parsed.key_id(), key_id(alg, &pk).to_vec());
+            assert_eq!(open(&parsed, &sk).unwrap(), b"secret payload");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "kyber")]
+    fn test_tampered_header_is_rejected() {
+        let (pk, sk) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+        let mut container = seal(AlgorithmId::MlKem768, &pk, b"data").unwrap();
+        container.created += 1;
+        assert_eq!(open(&container, &sk), Err(ContainerError::DecryptionFailed));
+    }
+
+    #[test]
+    #[cfg(feature = "dilithium")]
+    fn test_signature_container_roundtrip() {
+        let (pk, sk) = registry::keygen(AlgorithmId::MlDsa44).unwrap();
+        let sig = registry::sign(AlgorithmId::MlDsa44, &sk, b"msg").unwrap();
+        let container = Container::signature(AlgorithmId::MlDsa44, &key_id(AlgorithmId::MlDsa44, &pk), &sig);
+        let parsed = Container::from_bytes(&container.to_bytes()).unwrap();
+        assert_eq!(parsed, container);
+        assert!(parsed.verify_signature(&pk, b"msg").unwrap());
+        assert!(!parsed.verify_signature(&pk, b"other").unwrap());
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let container = Container::new(ContainerKind::PublicKey, AlgorithmId::Falcon512, vec![1; 16], vec![2; 4]);
+        let bytes = container.to_bytes();
+        let payload_at = bytes.len() - (5 + 4);
+
+        // An unknown optional field is accepted and preserved.
+        let mut optional = bytes[..payload_at].to_vec();
+        write_field(&mut optional, 0x10, b"future");
+        optional.extend_from_slice(&bytes[payload_at..]);
+        let parsed = Container::from_bytes(&optional).unwrap();
+        assert_eq!(parsed.payload_bytes(), container.payload_bytes());
+        assert_eq!(parsed.to_bytes(), optional);
+
+        // An unknown critical field is rejected.
+        let mut critical = bytes[..payload_at].to_vec();
+        write_field(&mut critical, 0x50, b"must-understand");
+        critical.extend_from_slice(&bytes[payload_at..]);
+        assert!(Container::from_bytes(&critical).is_err());
+
+        // Nothing may follow the payload.
+        let mut trailing = bytes.clone();
+        write_field(&mut trailing, 0x10, b"late");
+        assert!(Container::from_bytes(&trailing).is_err());
+
+        // A length running past the end is rejected, not sliced.
+        let mut oversized = bytes[..payload_at].to_vec();
+        oversized.extend_from_slice(&[0x10, 0xff, 0xff, 0xff, 0xff]);
+        assert!(matches!(Container::from_bytes(&oversized), Err(ContainerError::Malformed(_))));
+
+        // An empty optional field would be dropped from the re-encoded
+        // header, so it is rejected rather than accepted as the same AAD.
+        let mut empty = bytes[..payload_at].to_vec();
+        write_field(&mut empty, TAG_NONCE, b"");
+        empty.extend_from_slice(&bytes[payload_at..]);
+        assert_eq!(Container::from_bytes(&empty), Err(ContainerError::Malformed("empty field 0x06".into())));
+    }
+}
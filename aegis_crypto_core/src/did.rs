@@ -0,0 +1,339 @@
+//! Decentralized identifiers using the `did:key` method.
+//!
+//! A `did:key` identifier embeds a multicodec public key encoded as
+//! base58btc multibase: `did:key:z<varint(codec) || public key>`.
+//! Resolution is a pure function of the identifier, so no network lookup
+//! is involved. Documents use the `Multikey` verification method type.
+//!
+//! Signature keys are listed under authentication, assertion and capability
+//! relationships; KEM keys only under `keyAgreement`.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+use crate::multiformats::{
+    decode_public_key,
+    encode_public_key,
+    multibase_decode,
+    multibase_encode,
+    Multibase,
+    MultiformatError,
+};
+use crate::registry::{ AlgorithmId, AlgorithmKind };
+
+/// DID core JSON-LD context.
+pub const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+/// Multikey JSON-LD context.
+pub const MULTIKEY_CONTEXT: &str = "https://w3id.org/security/multikey/v1";
+
+const DID_KEY_PREFIX: &str = "did:key:";
+
+/// DID error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DidError {
+    InvalidDid(String),
+    UnsupportedMethod(String),
+    Multiformat(MultiformatError),
+}
+
+impl std::fmt::Display for DidError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DidError::InvalidDid(msg) => write!(f, "Invalid DID: {}", msg),
+            DidError::UnsupportedMethod(method) => write!(f, "Unsupported DID method: {}", method),
+            DidError::Multiformat(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DidError {}
+
+impl From<MultiformatError> for DidError {
+    fn from(e: MultiformatError) -> Self {
+        DidError::Multiformat(e)
+    }
+}
+
+/// A `did:key` identifier for a post-quantum public key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DidKey {
+    algorithm: AlgorithmId,
+    public_key: Vec<u8>,
+    multibase: String,
+}
+
+impl DidKey {
+    pub fn new(algorithm: AlgorithmId, public_key: &[u8]) -> Result<Self, DidError> {
+        let encoded = encode_public_key(algorithm, public_key)?;
+        Ok(DidKey {
+            algorithm,
+            public_key: public_key.to_vec(),
+            multibase: multibase_encode(Multibase::Base58Btc, &encoded),
+        })
+    }
+
+    /// Parse a `did:key` identifier. DID URLs with a path, query or
+    /// fragment are rejected; use [`resolve_verification_method`] for those.
+    pub fn parse(did: &str) -> Result<Self, DidError> {
+        let method_specific = match did.strip_prefix(DID_KEY_PREFIX) {
+            Some(rest) => rest,
+            None if did.starts_with("did:") => {
+                let method = did[4..].split(':').next().unwrap_or_default();
+                return Err(DidError::UnsupportedMethod(method.to_string()));
+            }
+            None => {
+                return Err(DidError::InvalidDid("missing did: scheme".into()));
+            }
+        };
+        if method_specific.contains(['#', '/', '?', ':']) {
+            return Err(DidError::InvalidDid("expected a bare did:key identifier".into()));
+        }
+        let (base, data) = multibase_decode(method_specific)?;
+        if base != Multibase::Base58Btc {
+            return Err(DidError::InvalidDid("did:key must use base58btc".into()));
+        }
+        let (algorithm, public_key) = decode_public_key(&data)?;
+        Ok(DidKey { algorithm, public_key, multibase: method_specific.to_string() })
+    }
+
+    pub fn algorithm(&self) -> AlgorithmId {
+        self.algorithm
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+
+    /// The `publicKeyMultibase` value (also the method-specific identifier).
+    pub fn public_key_multibase(&self) -> &str {
+        &self.multibase
+    }
+
+    pub fn did(&self) -> String {
+        format!("{}{}", DID_KEY_PREFIX, self.multibase)
+    }
+
+    /// The DID URL of the key's verification method.
+    pub fn verification_method_id(&self) -> String {
+        format!("{}#{}", self.did(), self.multibase)
+    }
+
+    pub fn verification_method(&self) -> VerificationMethod {
+        VerificationMethod {
+            id: self.verification_method_id(),
+            method_type: "Multikey".to_string(),
+            controller: self.did(),
+            public_key_multibase: self.multibase.clone(),
+        }
+    }
+
+    /// Build the DID document this identifier resolves to.
+    pub fn document(&self) -> DidDocument {
+        let method = self.verification_method();
+        let reference = vec![method.id.clone()];
+        let mut document = DidDocument {
+            context: vec![DID_CONTEXT.to_string(), MULTIKEY_CONTEXT.to_string()],
+            id: self.did(),
+            verification_method: vec![method],
+            authentication: Vec::new(),
+            assertion_method: Vec::new(),
+            capability_invocation: Vec::new(),
+            capability_delegation: Vec::new(),
+            key_agreement: Vec::new(),
+        };
+        match self.algorithm.kind() {
+            AlgorithmKind::Signature => {
+                document.authentication = reference.clone();
+                document.assertion_method = reference.clone();
+                document.capability_invocation = reference.clone();
+                document.capability_delegation = reference;
+            }
+            AlgorithmKind::Kem => {
+                document.key_agreement = reference;
+            }
+        }
+        document
+    }
+}
+
+impl std::fmt::Display for DidKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", DID_KEY_PREFIX, self.multibase)
+    }
+}
+
+/// A verification method entry in a DID document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct VerificationMethod {
+    pub id: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub method_type: String,
+    pub controller: String,
+    pub public_key_multibase: String,
+}
+
+impl VerificationMethod {
+    /// Decode the method's public key.
+    pub fn public_key(&self) -> Result<(AlgorithmId, Vec<u8>), DidError> {
+        let (_, data) = multibase_decode(&self.public_key_multibase)?;
+        Ok(decode_public_key(&data)?)
+    }
+}
+
+/// A DID document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct DidDocument {
+    #[cfg_attr(feature = "serde", serde(rename = "@context"))]
+    pub context: Vec<String>,
+    pub id: String,
+    pub verification_method: Vec<VerificationMethod>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub authentication: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub assertion_method: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub capability_invocation: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub capability_delegation: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty"))]
+    pub key_agreement: Vec<String>,
+}
+
+impl DidDocument {
+    /// Look up a verification method by its full DID URL or by fragment.
+    pub fn verification_method(&self, id: &str) -> Option<&VerificationMethod> {
+        self.verification_method
+            .iter()
+            .find(|vm| vm.id == id || vm.id.strip_prefix(&self.id) == Some(id))
+    }
+
+    /// Serialize the document as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n");
+        out.push_str(&format!("  \"@context\": {},\n", json_array(&self.context, 2)));
+        out.push_str(&format!("  \"id\": {},\n", json_string(&self.id)));
+        out.push_str("  \"verificationMethod\": [\n");
+        for (i, vm) in self.verification_method.iter().enumerate() {
+            out.push_str("    {\n");
+            out.push_str(&format!("      \"id\": {},\n", json_string(&vm.id)));
+            out.push_str(&format!("      \"type\": {},\n", json_string(&vm.method_type)));
+            out.push_str(&format!("      \"controller\": {},\n", json_string(&vm.controller)));
+            out.push_str(
+                &format!("      \"publicKeyMultibase\": {}\n", json_string(&vm.public_key_multibase))
+            );
+            out.push_str(if i + 1 < self.verification_method.len() { "    },\n" } else { "    }\n" });
+        }
+        out.push_str("  ]");
+        let relationships = [
+            ("authentication", &self.authentication),
+            ("assertionMethod", &self.assertion_method),
+            ("capabilityInvocation", &self.capability_invocation),
+            ("capabilityDelegation", &self.capability_delegation),
+            ("keyAgreement", &self.key_agreement),
+        ];
+        for (name, ids) in relationships {
+            if !ids.is_empty() {
+                out.push_str(&format!(",\n  \"{}\": {}", name, json_array(ids, 2)));
+            }
+        }
+        out.push_str("\n}");
+        out
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_array(values: &[String], indent: usize) -> String {
+    let pad = " ".repeat(indent + 2);
+    let items: Vec<String> = values
+        .iter()
+        .map(|v| format!("{}{}", pad, json_string(v)))
+        .collect();
+    format!("[\n{}\n{}]", items.join(",\n"), " ".repeat(indent))
+}
+
+/// Resolve a `did:key` identifier to its DID document.
+pub fn resolve(did: &str) -> Result<DidDocument, DidError> {
+    Ok(DidKey::parse(did)?.document())
+}
+
+/// Resolve a DID URL such as `did:key:z...#z...` to its verification method.
+pub fn resolve_verification_method(did_url: &str) -> Result<VerificationMethod, DidError> {
+    let (did, fragment) = did_url
+        .split_once('#')
+        .ok_or_else(|| DidError::InvalidDid("missing fragment".into()))?;
+    let key = DidKey::parse(did)?;
+    if fragment != key.public_key_multibase() {
+        return Err(DidError::InvalidDid("unknown verification method".into()));
+    }
+    Ok(key.verification_method())
+}
+
+/// Create a `did:key` identifier for a public key.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn did_key_create(algorithm_code: u16, public_key: &[u8]) -> Result<String, String> {
+    let algorithm = AlgorithmId::from_code(algorithm_code).map_err(|e| e.to_string())?;
+    DidKey::new(algorithm, public_key)
+        .map(|key| key.did())
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve a `did:key` identifier to its DID document as JSON.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn did_key_resolve(did: &str) -> Result<String, String> {
+    resolve(did)
+        .map(|document| document.to_json())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_did_key_roundtrip() {
+        let public_key = vec![7u8; AlgorithmId::MlDsa65.public_key_len().unwrap_or(1952)];
+        let key = DidKey::new(AlgorithmId::MlDsa65, &public_key).unwrap();
+        let did = key.did();
+        assert!(did.starts_with("did:key:z"));
+
+        let parsed = DidKey::parse(&did).unwrap();
+        assert_eq!(parsed, key);
+        assert_eq!(parsed.public_key(), &public_key[..]);
+
+        let document = resolve(&did).unwrap();
+        assert_eq!(document.assertion_method, vec![key.verification_method_id()]);
+        assert!(document.key_agreement.is_empty());
+        let vm = document.verification_method(&format!("#{}", key.public_key_multibase())).unwrap();
+        assert_eq!(vm.public_key().unwrap(), (AlgorithmId::MlDsa65, public_key));
+        assert_eq!(resolve_verification_method(&key.verification_method_id()).unwrap(), *vm);
+    }
+
+    #[test]
+    fn test_invalid_identifiers() {
+        assert!(matches!(DidKey::parse("did:web:example.com"), Err(DidError::UnsupportedMethod(m)) if m == "web"));
+        assert!(DidKey::parse("did:key:uAAAA").is_err());
+        assert!(DidKey::parse("did:key:z6Mk#frag").is_err());
+        assert!(resolve_verification_method("did:key:z6Mk").is_err());
+    }
+}
@@ -5,6 +5,9 @@ use pqcrypto_mldsa::mldsa44::{
     keypair as keypair44,
     open as open44,
     SignedMessage as SignedMessage44,
+    DetachedSignature as DetachedSignature44,
+    detached_sign as detached_sign44,
+    verify_detached_signature as verify_detached_signature44,
 };
 use pqcrypto_mldsa::mldsa65::{
     PublicKey as PublicKey65,
@@ -13,6 +16,9 @@ use pqcrypto_mldsa::mldsa65::{
     keypair as keypair65,
     open as open65,
     SignedMessage as SignedMessage65,
+    DetachedSignature as DetachedSignature65,
+    detached_sign as detached_sign65,
+    verify_detached_signature as verify_detached_signature65,
 };
 use pqcrypto_mldsa::mldsa87::{
     PublicKey as PublicKey87,
@@ -21,8 +27,16 @@ use pqcrypto_mldsa::mldsa87::{
     keypair as keypair87,
     open as open87,
     SignedMessage as SignedMessage87,
+    DetachedSignature as DetachedSignature87,
+    detached_sign as detached_sign87,
+    verify_detached_signature as verify_detached_signature87,
+};
+use pqcrypto_traits::sign::{
+    PublicKey as _,
+    SecretKey as _,
+    SignedMessage as _,
+    DetachedSignature as _,
 };
-use pqcrypto_traits::sign::{ PublicKey as _, SecretKey as _, SignedMessage as _ };
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
@@ -32,6 +46,16 @@ pub struct DilithiumKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    DilithiumKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_mldsa::mldsa44,
+        pqcrypto_mldsa::mldsa65,
+        pqcrypto_mldsa::mldsa87
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl DilithiumKeyPair {
     #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
@@ -79,6 +103,30 @@ pub fn dilithium44_verify(public_key: &[u8], signed_message: &[u8]) -> bool {
     open44(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium44_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKey44::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detached_sign44(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium44_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKey44::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignature44::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verify_detached_signature44(&sig, message, &pk).is_ok()
+}
+
 // ML-DSA-65 Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn dilithium65_keygen() -> DilithiumKeyPair {
@@ -113,6 +161,30 @@ pub fn dilithium65_verify(public_key: &[u8], signed_message: &[u8]) -> bool {
     open65(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium65_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKey65::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detached_sign65(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium65_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKey65::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignature65::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verify_detached_signature65(&sig, message, &pk).is_ok()
+}
+
 // ML-DSA-87 Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn dilithium87_keygen() -> DilithiumKeyPair {
@@ -147,6 +219,30 @@ pub fn dilithium87_verify(public_key: &[u8], signed_message: &[u8]) -> bool {
     open87(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium87_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKey87::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detached_sign87(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn dilithium87_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKey87::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignature87::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verify_detached_signature87(&sig, message, &pk).is_ok()
+}
+
 // Legacy functions (for backward compatibility - default to ML-DSA-87)
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn dilithium_keygen() -> DilithiumKeyPair {
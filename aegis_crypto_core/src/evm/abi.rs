@@ -0,0 +1,114 @@
+//! Solidity ABI encoding of static words and dynamic byte strings.
+//!
+//! Only the types the verifier and ERC-4337 hashing need are supported:
+//! 32-byte words (`uint256`, `bytes32`, `address`) and `bytes`.
+
+use super::{ keccak256, Address, EvmError };
+
+/// Signature of the verifier entry point.
+pub const VERIFY_SIGNATURE: &str = "verify(bytes,bytes,bytes)";
+
+/// One ABI word.
+pub type Word = [u8; 32];
+
+/// A value to encode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// A static 32-byte value.
+    Word(Word),
+    /// A dynamic `bytes` value.
+    Bytes(&'a [u8]),
+}
+
+/// First four bytes of the Keccak-256 of a function signature.
+pub fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature.as_bytes())[..4].try_into().unwrap()
+}
+
+/// A `uint256` word.
+pub fn uint(value: u128) -> Word {
+    let mut word = [0u8; 32];
+    word[16..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// An `address` word.
+pub fn address(address: &Address) -> Word {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// `abi.encode(tokens...)`: static heads, then the tails of `bytes` values
+/// padded to whole words.
+pub fn encode(tokens: &[Token]) -> Vec<u8> {
+    let mut head = Vec::with_capacity(tokens.len() * 32);
+    let mut tail = Vec::new();
+    for token in tokens {
+        match token {
+            Token::Word(word) => head.extend_from_slice(word),
+            Token::Bytes(data) => {
+                head.extend_from_slice(&uint((tokens.len() * 32 + tail.len()) as u128));
+                tail.extend_from_slice(&uint(data.len() as u128));
+                tail.extend_from_slice(data);
+                tail.resize(tail.len().next_multiple_of(32), 0);
+            }
+        }
+    }
+    head.extend_from_slice(&tail);
+    head
+}
+
+/// Calldata for `signature` with `tokens` as arguments.
+pub fn encode_call(signature: &str, tokens: &[Token]) -> Vec<u8> {
+    let mut out = selector(signature).to_vec();
+    out.extend_from_slice(&encode(tokens));
+    out
+}
+
+/// Decode `count` `bytes` arguments. Offsets must point into the data
+/// and padding must be zero.
+pub fn decode_bytes(data: &[u8], count: usize) -> Result<Vec<Vec<u8>>, EvmError> {
+    let word = |at: usize| -> Result<usize, EvmError> {
+        let bytes = at
+            .checked_add(32)
+            .and_then(|end| data.get(at..end))
+            .ok_or_else(|| EvmError::Malformed("truncated calldata".into()))?;
+        if bytes[..24].iter().any(|&b| b != 0) {
+            return Err(EvmError::Malformed("offset or length out of range".into()));
+        }
+        Ok(u64::from_be_bytes(bytes[24..].try_into().unwrap()) as usize)
+    };
+    (0..count)
+        .map(|i| {
+            let offset = word(i * 32)?;
+            let len = word(offset)?;
+            let start = offset + 32;
+            let end = start
+                .checked_add(len)
+                .filter(|&end| end.next_multiple_of(32) <= data.len())
+                .ok_or_else(|| EvmError::Malformed("truncated calldata".into()))?;
+            if data[end..end.next_multiple_of(32)].iter().any(|&b| b != 0) {
+                return Err(EvmError::Malformed("non-zero padding".into()));
+            }
+            Ok(data[start..end].to_vec())
+        })
+        .collect()
+}
+
+/// Calldata for `verify(publicKey, message, signature)`.
+pub fn encode_verify_call(public_key: &[u8], message: &[u8], signature: &[u8]) -> Vec<u8> {
+    encode_call(VERIFY_SIGNATURE, &[Token::Bytes(public_key), Token::Bytes(message), Token::Bytes(signature)])
+}
+
+/// Public key, message and signature of a `verify` call.
+pub type VerifyArgs = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+/// Split `verify` calldata into its arguments.
+pub fn decode_verify_call(calldata: &[u8]) -> Result<VerifyArgs, EvmError> {
+    if calldata.len() < 4 || calldata[..4] != selector(VERIFY_SIGNATURE) {
+        return Err(EvmError::Malformed("not a verify call".into()));
+    }
+    let mut args = decode_bytes(&calldata[4..], 3)?.into_iter();
+    Ok((args.next().unwrap(), args.next().unwrap(), args.next().unwrap()))
+}
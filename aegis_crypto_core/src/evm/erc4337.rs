@@ -0,0 +1,87 @@
+//! ERC-4337 user operations signed with post-quantum keys.
+//!
+//! [`UserOperation`] is EntryPoint v0.7's `PackedUserOperation` and
+//! [`UserOperation::hash`] is the EntryPoint's `getUserOpHash`. The
+//! signature field carries a raw registry signature over the 32-byte hash,
+//! which is what [`super::solidity::falcon512_account`] checks.
+
+use crate::registry::{ self, AlgorithmId };
+
+use super::abi::{ self, Token, Word };
+use super::{ keccak256, Address, EvmError };
+
+/// Address of the canonical EntryPoint v0.7 deployment.
+pub const ENTRY_POINT_V07: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+
+/// An EntryPoint v0.7 packed user operation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserOperation {
+    pub sender: Address,
+    /// 192-bit key and 64-bit sequence, as one `uint256`.
+    pub nonce: Word,
+    pub init_code: Vec<u8>,
+    pub call_data: Vec<u8>,
+    pub verification_gas_limit: u128,
+    pub call_gas_limit: u128,
+    pub pre_verification_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    pub max_fee_per_gas: u128,
+    pub paymaster_and_data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+fn pack(high: u128, low: u128) -> Word {
+    let mut word = [0u8; 32];
+    word[..16].copy_from_slice(&high.to_be_bytes());
+    word[16..].copy_from_slice(&low.to_be_bytes());
+    word
+}
+
+impl UserOperation {
+    /// `accountGasLimits`: verification gas limit, then call gas limit.
+    pub fn account_gas_limits(&self) -> Word {
+        pack(self.verification_gas_limit, self.call_gas_limit)
+    }
+
+    /// `gasFees`: max priority fee, then max fee.
+    pub fn gas_fees(&self) -> Word {
+        pack(self.max_priority_fee_per_gas, self.max_fee_per_gas)
+    }
+
+    /// The user operation hash for `entry_point` on `chain_id`. The
+    /// signature is not covered.
+    pub fn hash(&self, entry_point: &Address, chain_id: u64) -> [u8; 32] {
+        let packed = abi::encode(&[
+            Token::Word(abi::address(&self.sender)),
+            Token::Word(self.nonce),
+            Token::Word(keccak256(&self.init_code)),
+            Token::Word(keccak256(&self.call_data)),
+            Token::Word(self.account_gas_limits()),
+            Token::Word(abi::uint(self.pre_verification_gas)),
+            Token::Word(self.gas_fees()),
+            Token::Word(keccak256(&self.paymaster_and_data)),
+        ]);
+        keccak256(&abi::encode(&[
+            Token::Word(keccak256(&packed)),
+            Token::Word(abi::address(entry_point)),
+            Token::Word(abi::uint(u128::from(chain_id))),
+        ]))
+    }
+
+    /// Sign the hash with a registry signature key and store the signature.
+    pub fn sign(
+        &mut self,
+        entry_point: &Address,
+        chain_id: u64,
+        algorithm: AlgorithmId,
+        secret_key: &[u8]
+    ) -> Result<(), EvmError> {
+        self.signature = registry::sign(algorithm, secret_key, &self.hash(entry_point, chain_id))?;
+        Ok(())
+    }
+
+    /// Check the stored signature.
+    pub fn verify(&self, entry_point: &Address, chain_id: u64, algorithm: AlgorithmId, public_key: &[u8]) -> bool {
+        registry::verify(algorithm, public_key, &self.hash(entry_point, chain_id), &self.signature)
+    }
+}
@@ -0,0 +1,318 @@
+//! Falcon-512 verification as the generated Solidity contract performs it.
+//!
+//! The steps and constant tables match [`super::solidity`] one for one:
+//! the public key and compressed signature are decoded as in the Falcon
+//! reference code, the salted message is hashed to a point with a
+//! SHAKE256 built on the same Keccak-f tables the contract embeds, and
+//! `s2 * h` is computed with the contract's number-theoretic transform.
+//! Agreement with the crate's Falcon implementation therefore checks the
+//! contract's algorithm and tables.
+
+/// Falcon modulus.
+pub const Q: u32 = 12289;
+/// Polynomial degree of Falcon-512.
+pub const N: usize = 512;
+/// Bound on the squared norm of `(s1, s2)`.
+pub const L2_BOUND: u64 = 34_034_726;
+pub const PUBLIC_KEY_LEN: usize = 897;
+pub const SALT_LEN: usize = 40;
+/// Length of a padded Falcon-512 signature, whose compressed part may be
+/// followed by zero bytes.
+pub const PADDED_SIGNATURE_LEN: usize = 666;
+
+const PUBLIC_KEY_HEADER: u8 = 0x09;
+const SIGNATURE_HEADER: u8 = 0x39;
+const SHAKE256_RATE: usize = 136;
+
+/// Keccak-f[1600] round constants.
+pub const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Keccak-f rotation offsets, indexed by lane `x + 5 * y`.
+pub const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+fn pow_mod(mut base: u32, mut exponent: u32) -> u32 {
+    let mut result = 1u32;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % Q;
+        }
+        base = base * base % Q;
+        exponent >>= 1;
+    }
+    result
+}
+
+/// `N^-1 mod Q`, applied after the inverse transform.
+pub fn n_inverse() -> u32 {
+    pow_mod(N as u32, Q - 2)
+}
+
+/// Twiddle factors: `zetas[k]` is `psi` to the 9-bit reversal of `k`,
+/// where `psi` is the smallest primitive 1024th root of unity mod `Q`.
+pub fn zetas() -> Vec<u32> {
+    let psi = (2..Q)
+        .find(|&g| pow_mod(g, N as u32) == Q - 1)
+        .expect("Q has primitive 1024th roots of unity");
+    (0..N as u32).map(|k| pow_mod(psi, k.reverse_bits() >> (32 - 9))).collect()
+}
+
+/// Forward transform, in place, to bit-reversed order.
+pub fn ntt(a: &mut [u32], zetas: &[u32]) {
+    let mut k = 0;
+    let mut len = N / 2;
+    while len >= 1 {
+        for start in (0..N).step_by(2 * len) {
+            k += 1;
+            let zeta = zetas[k];
+            for j in start..start + len {
+                let t = zeta * a[j + len] % Q;
+                a[j + len] = (a[j] + Q - t) % Q;
+                a[j] = (a[j] + t) % Q;
+            }
+        }
+        len /= 2;
+    }
+}
+
+/// Inverse of [`ntt`], including the division by `N`.
+pub fn inverse_ntt(a: &mut [u32], zetas: &[u32]) {
+    let mut k = N;
+    let mut len = 1;
+    while len < N {
+        for start in (0..N).step_by(2 * len) {
+            k -= 1;
+            let zeta = Q - zetas[k];
+            for j in start..start + len {
+                let t = a[j];
+                a[j] = (t + a[j + len]) % Q;
+                a[j + len] = (t + Q - a[j + len]) % Q * zeta % Q;
+            }
+        }
+        len *= 2;
+    }
+    let n_inverse = n_inverse();
+    a.iter_mut().for_each(|x| *x = *x * n_inverse % Q);
+}
+
+fn keccak_f(state: &mut [u64; 25]) {
+    for round_constant in ROUND_CONSTANTS {
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = state[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+        for x in 0..5 {
+            for y in 0..5 {
+                state[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        state[0] ^= round_constant;
+    }
+}
+
+/// SHAKE256 of `input`, `output_len` bytes long.
+pub fn shake256(input: &[u8], output_len: usize) -> Vec<u8> {
+    let mut state = [0u64; 25];
+    let mut padded = input.to_vec();
+    padded.push(0x1f);
+    padded.resize(padded.len().next_multiple_of(SHAKE256_RATE), 0);
+    *padded.last_mut().unwrap() |= 0x80;
+    for block in padded.chunks(SHAKE256_RATE) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        keccak_f(&mut state);
+    }
+    let mut output = Vec::with_capacity(output_len.next_multiple_of(SHAKE256_RATE));
+    loop {
+        for lane in &state[..SHAKE256_RATE / 8] {
+            output.extend_from_slice(&lane.to_le_bytes());
+        }
+        if output.len() >= output_len {
+            output.truncate(output_len);
+            return output;
+        }
+        keccak_f(&mut state);
+    }
+}
+
+/// Hash the salt and message to a polynomial with coefficients mod `Q`.
+/// 16-bit big-endian samples below `5 * Q` are kept and reduced.
+pub fn hash_to_point(salt: &[u8], message: &[u8]) -> Vec<u32> {
+    let input = [salt, message].concat();
+    // Nine SHAKE256 blocks hold 612 samples; about 480 are needed.
+    let mut blocks = 9;
+    loop {
+        let stream = shake256(&input, blocks * SHAKE256_RATE);
+        let point: Vec<u32> = stream
+            .chunks(2)
+            .map(|pair| u32::from(u16::from_be_bytes([pair[0], pair[1]])))
+            .filter(|&w| w < 5 * Q)
+            .map(|w| w % Q)
+            .take(N)
+            .collect();
+        if point.len() == N {
+            return point;
+        }
+        blocks += 1;
+    }
+}
+
+/// Decode a public key into `h`.
+pub fn decode_public_key(public_key: &[u8]) -> Option<Vec<u32>> {
+    if public_key.len() != PUBLIC_KEY_LEN || public_key[0] != PUBLIC_KEY_HEADER {
+        return None;
+    }
+    let mut h = Vec::with_capacity(N);
+    let (mut acc, mut acc_len) = (0u32, 0);
+    for &byte in &public_key[1..] {
+        acc = (acc << 8) | u32::from(byte);
+        acc_len += 8;
+        if acc_len >= 14 {
+            acc_len -= 14;
+            let w = (acc >> acc_len) & 0x3fff;
+            if w >= Q {
+                return None;
+            }
+            h.push(w);
+        }
+    }
+    Some(h)
+}
+
+/// Decode a compressed signature into `s2` mod `Q` and its squared norm.
+pub fn decode_signature(compressed: &[u8]) -> Option<(Vec<u32>, u64)> {
+    let mut s2 = Vec::with_capacity(N);
+    let mut norm = 0u64;
+    let (mut acc, mut acc_len, mut v) = (0u32, 0u32, 0usize);
+    for _ in 0..N {
+        acc = (acc << 8) | u32::from(*compressed.get(v)?);
+        v += 1;
+        let b = acc >> acc_len;
+        let negative = b & 128 != 0;
+        let mut m = b & 127;
+        loop {
+            if acc_len == 0 {
+                acc = (acc << 8) | u32::from(*compressed.get(v)?);
+                v += 1;
+                acc_len = 8;
+            }
+            acc_len -= 1;
+            if (acc >> acc_len) & 1 != 0 {
+                break;
+            }
+            m += 128;
+            if m > 2047 {
+                return None;
+            }
+        }
+        if negative && m == 0 {
+            return None;
+        }
+        norm += u64::from(m * m);
+        s2.push(if negative { Q - m } else { m });
+    }
+    if acc & ((1 << acc_len) - 1) != 0 {
+        return None;
+    }
+    // A padded signature may end in zero bytes.
+    let rest = &compressed[v..];
+    if !rest.is_empty() && (compressed.len() != PADDED_SIGNATURE_LEN - 1 - SALT_LEN || rest.iter().any(|&b| b != 0)) {
+        return None;
+    }
+    Some((s2, norm))
+}
+
+/// Verify a detached Falcon-512 signature.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    if signature.len() < 1 + SALT_LEN || signature[0] != SIGNATURE_HEADER {
+        return false;
+    }
+    let (Some(mut h), Some((mut s2, mut norm))) = (
+        decode_public_key(public_key),
+        decode_signature(&signature[1 + SALT_LEN..]),
+    ) else {
+        return false;
+    };
+    let c = hash_to_point(&signature[1..1 + SALT_LEN], message);
+
+    // -s1 = s2 * h - c
+    let zetas = zetas();
+    ntt(&mut h, &zetas);
+    ntt(&mut s2, &zetas);
+    let mut t: Vec<u32> = s2.iter().zip(&h).map(|(a, b)| a * b % Q).collect();
+    inverse_ntt(&mut t, &zetas);
+    for (x, c) in t.iter().zip(&c) {
+        let w = (x + Q - c) % Q;
+        let centered = if w > Q / 2 { Q - w } else { w };
+        norm += u64::from(centered * centered);
+    }
+    norm <= L2_BOUND
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ntt_multiplies_negacyclically() {
+        let a: Vec<u32> = (0..N as u32).map(|i| (i * 7919 + 13) % Q).collect();
+        let b: Vec<u32> = (0..N as u32).map(|i| (i * i + 5) % Q).collect();
+        let mut expected = vec![0u32; N];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                let product = x * y % Q;
+                let k = (i + j) % N;
+                expected[k] = if i + j < N { (expected[k] + product) % Q } else { (expected[k] + Q - product) % Q };
+            }
+        }
+        let zetas = zetas();
+        let (mut a_hat, mut b_hat) = (a.clone(), b.clone());
+        ntt(&mut a_hat, &zetas);
+        ntt(&mut b_hat, &zetas);
+        let mut product: Vec<u32> = a_hat.iter().zip(&b_hat).map(|(x, y)| x * y % Q).collect();
+        inverse_ntt(&mut product, &zetas);
+        assert_eq!(product, expected);
+
+        inverse_ntt(&mut a_hat, &zetas);
+        assert_eq!(a_hat, a);
+    }
+}
@@ -0,0 +1,123 @@
+//! Gas estimates for `verify(bytes,bytes,bytes)` calls.
+//!
+//! Calldata and memory costs follow from key and signature sizes and hold
+//! for any verifier taking the [`super::abi`] encoding. Execution cost is
+//! only modelled for the generated Falcon-512 contract, from its operation
+//! counts, and is a rough upper estimate rather than a measurement.
+
+use crate::registry::{ AlgorithmError, AlgorithmId, AlgorithmKind };
+
+use super::falcon::N;
+use super::EvmError;
+
+/// Base cost of every transaction.
+pub const TRANSACTION_GAS: u64 = 21_000;
+/// Calldata cost of a zero byte (EIP-2028).
+pub const ZERO_BYTE_GAS: u64 = 4;
+/// Calldata cost of a non-zero byte (EIP-2028).
+pub const NONZERO_BYTE_GAS: u64 = 16;
+
+/// Approximate cost of one NTT butterfly in the reference contract.
+const BUTTERFLY_GAS: u64 = 250;
+/// Approximate cost of one Keccak-f permutation in Solidity.
+const KECCAK_F_GAS: u64 = 250_000;
+/// Approximate cost of per-coefficient work (decoding, pointwise product,
+/// norm) in one pass over the polynomial.
+const COEFFICIENT_GAS: u64 = 400;
+/// Passes over the polynomial outside the transforms.
+const COEFFICIENT_PASSES: u64 = 5;
+/// SHAKE256 rate in bytes.
+const RATE: u64 = 136;
+/// Squeeze permutations `hashToPoint` needs in the typical case.
+const SQUEEZE_PERMUTATIONS: u64 = 8;
+
+/// Breakdown of the gas of one verification transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    pub intrinsic: u64,
+    pub calldata: u64,
+    pub memory: u64,
+    /// Contract execution, if modelled for the algorithm.
+    pub execution: Option<u64>,
+}
+
+impl GasEstimate {
+    /// Total gas, if execution is modelled.
+    pub fn total(&self) -> Option<u64> {
+        self.execution.map(|execution| self.intrinsic + self.calldata + self.memory + execution)
+    }
+}
+
+/// Exact calldata cost of `calldata`.
+pub fn calldata_gas(calldata: &[u8]) -> u64 {
+    calldata
+        .iter()
+        .map(|&b| if b == 0 { ZERO_BYTE_GAS } else { NONZERO_BYTE_GAS })
+        .sum()
+}
+
+/// Cost of expanding memory to `bytes`.
+pub fn memory_gas(bytes: usize) -> u64 {
+    let words = bytes.div_ceil(32) as u64;
+    3 * words + words * words / 512
+}
+
+/// Estimate the gas of verifying a `message_len`-byte message under
+/// `algorithm`. Key and signature bytes are priced as non-zero.
+pub fn estimate_verify_gas(algorithm: AlgorithmId, message_len: usize) -> Result<GasEstimate, EvmError> {
+    if algorithm.kind() != AlgorithmKind::Signature {
+        return Err(AlgorithmError::Unsupported(algorithm).into());
+    }
+    let (Some(public_key_len), Some(signature_len)) = (algorithm.public_key_len(), algorithm.output_len()) else {
+        return Err(AlgorithmError::Unsupported(algorithm).into());
+    };
+
+    // Selector, three offsets and three lengths; each word has at most two
+    // non-zero bytes.
+    let header = 4 * NONZERO_BYTE_GAS + 6 * (2 * NONZERO_BYTE_GAS + 30 * ZERO_BYTE_GAS);
+    let data = [public_key_len, message_len, signature_len];
+    let payload: u64 = data
+        .iter()
+        .map(|&len| len as u64 * NONZERO_BYTE_GAS + (len.next_multiple_of(32) - len) as u64 * ZERO_BYTE_GAS)
+        .sum();
+    let copied: usize = data.iter().map(|len| 32 + len.next_multiple_of(32)).sum();
+
+    let (memory, execution) = match algorithm {
+        AlgorithmId::Falcon512 => {
+            // h, s2, c and the hash input.
+            let working = 3 * (32 + 32 * N) + 40 + message_len;
+            let n = N as u64;
+            let butterflies = 3 * (n / 2) * 9;
+            let permutations = (40 + message_len as u64) / RATE + 1 + SQUEEZE_PERMUTATIONS;
+            let execution = butterflies * BUTTERFLY_GAS
+                + permutations * KECCAK_F_GAS
+                + COEFFICIENT_PASSES * n * COEFFICIENT_GAS;
+            (memory_gas(copied + working), Some(execution))
+        }
+        _ => (memory_gas(copied), None),
+    };
+
+    Ok(GasEstimate {
+        intrinsic: TRANSACTION_GAS,
+        calldata: header + payload,
+        memory,
+        execution,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evm::abi::encode_verify_call;
+
+    #[test]
+    fn test_calldata_estimate_bounds_actual_cost() {
+        let public_key = vec![0xaa; 897];
+        let message = vec![0x55; 32];
+        let signature = vec![0x11; AlgorithmId::Falcon512.output_len().unwrap()];
+        let estimate = estimate_verify_gas(AlgorithmId::Falcon512, message.len()).unwrap();
+        let actual = calldata_gas(&encode_verify_call(&public_key, &message, &signature));
+        assert!(estimate.calldata >= actual && estimate.calldata < actual + 100);
+        assert!(estimate.total().unwrap() > estimate.calldata);
+    }
+}
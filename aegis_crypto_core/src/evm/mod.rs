@@ -0,0 +1,61 @@
+//! Artifacts for checking post-quantum signatures on EVM chains.
+//!
+//! - [`abi`] encodes public keys, messages and signatures as calldata for
+//!   `verify(bytes,bytes,bytes)`.
+//! - [`solidity`] generates a reference Falcon-512 verifier contract and
+//!   an ERC-4337 account that uses it. [`falcon`] is the same algorithm in
+//!   Rust, step for step and over the same constant tables, so the
+//!   contract's arithmetic can be checked against the Falcon
+//!   implementation without an EVM.
+//! - [`gas`] estimates the gas of a verification call from key and
+//!   signature sizes.
+//! - [`erc4337`] hashes and signs ERC-4337 user operations with registry
+//!   signature keys.
+
+pub mod abi;
+pub mod erc4337;
+pub mod falcon;
+pub mod gas;
+pub mod solidity;
+
+use sha3::{ Digest, Keccak256 };
+
+use crate::registry::AlgorithmError;
+
+/// EVM artifact error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvmError {
+    Malformed(String),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for EvmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EvmError::Malformed(msg) => write!(f, "Malformed EVM data: {}", msg),
+            EvmError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for EvmError {}
+
+impl From<AlgorithmError> for EvmError {
+    fn from(e: AlgorithmError) -> Self {
+        EvmError::Algorithm(e)
+    }
+}
+
+/// An EVM address.
+pub type Address = [u8; 20];
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Parse a `0x`-prefixed hex address. Checksum casing is not enforced.
+pub fn parse_address(text: &str) -> Result<Address, EvmError> {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    let bytes = hex::decode(digits).map_err(|_| EvmError::Malformed(format!("invalid address {}", text)))?;
+    bytes.try_into().map_err(|_| EvmError::Malformed(format!("invalid address length {}", text)))
+}
@@ -0,0 +1,333 @@
+//! Generated Solidity sources.
+//!
+//! [`falcon512_verifier`] returns `Falcon512.sol`: a `Falcon512` library
+//! and a `Falcon512Verifier` contract whose `verify(bytes,bytes,bytes)`
+//! takes the calldata from [`super::abi::encode_verify_call`].
+//! [`falcon512_account`] returns an ERC-4337 (EntryPoint v0.7) account
+//! that imports it. The library is written for clarity rather than gas and
+//! follows [`super::falcon`] line for line; its constant tables are
+//! generated from the same Rust definitions.
+
+use super::falcon::{ n_inverse, zetas, ROTATIONS, ROUND_CONSTANTS };
+
+const VERIFIER_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT OR Apache-2.0
+// Generated by aegis_crypto_core::evm::solidity. Do not edit.
+pragma solidity ^0.8.24;
+
+/// @notice Reference Falcon-512 signature verification.
+/// @dev Mirrors aegis_crypto_core::evm::falcon step for step.
+library Falcon512 {
+    uint256 internal constant Q = 12289;
+    uint256 internal constant N = 512;
+    uint256 internal constant L2_BOUND = 34034726;
+    uint256 internal constant N_INVERSE = {{N_INVERSE}};
+    uint256 internal constant PUBLIC_KEY_LENGTH = 897;
+    uint256 internal constant SALT_LENGTH = 40;
+    uint256 internal constant PADDED_SIGNATURE_LENGTH = 666;
+    uint256 private constant RATE = 136;
+
+    /// zetas[k] = psi^bitreverse9(k) mod Q, two bytes each.
+    bytes private constant ZETAS = hex"{{ZETAS}}";
+    /// Keccak-f round constants, eight bytes each.
+    bytes private constant ROUND_CONSTANTS = hex"{{ROUND_CONSTANTS}}";
+    /// Keccak-f rotation offsets by lane x + 5 * y.
+    bytes private constant ROTATIONS = hex"{{ROTATIONS}}";
+
+    function verify(bytes memory publicKey, bytes memory message, bytes memory signature)
+        internal
+        pure
+        returns (bool)
+    {
+        if (signature.length < 1 + SALT_LENGTH || uint8(signature[0]) != 0x39) return false;
+        (bool ok, uint256[] memory h) = decodePublicKey(publicKey);
+        if (!ok) return false;
+        uint256[] memory s2;
+        uint256 norm;
+        (ok, s2, norm) = decodeSignature(signature);
+        if (!ok) return false;
+        uint256[] memory c = hashToPoint(signature, message);
+
+        // -s1 = s2 * h - c
+        ntt(h);
+        ntt(s2);
+        for (uint256 i = 0; i < N; i++) s2[i] = mulmod(s2[i], h[i], Q);
+        inverseNtt(s2);
+        for (uint256 i = 0; i < N; i++) {
+            uint256 w = addmod(s2[i], Q - c[i], Q);
+            uint256 centered = w > Q / 2 ? Q - w : w;
+            norm += centered * centered;
+        }
+        return norm <= L2_BOUND;
+    }
+
+    function decodePublicKey(bytes memory publicKey) private pure returns (bool, uint256[] memory h) {
+        h = new uint256[](N);
+        if (publicKey.length != PUBLIC_KEY_LENGTH || uint8(publicKey[0]) != 0x09) return (false, h);
+        uint256 acc;
+        uint256 accLength;
+        uint256 u;
+        for (uint256 i = 1; i < PUBLIC_KEY_LENGTH; i++) {
+            acc = (acc << 8) | uint8(publicKey[i]);
+            accLength += 8;
+            if (accLength >= 14) {
+                accLength -= 14;
+                uint256 w = (acc >> accLength) & 0x3fff;
+                if (w >= Q) return (false, h);
+                h[u++] = w;
+            }
+        }
+        return (true, h);
+    }
+
+    /// Decode the compressed s2 into residues mod Q and its squared norm.
+    function decodeSignature(bytes memory signature)
+        private
+        pure
+        returns (bool, uint256[] memory s2, uint256 norm)
+    {
+        s2 = new uint256[](N);
+        uint256 length = signature.length;
+        uint256 v = 1 + SALT_LENGTH;
+        uint256 acc;
+        uint256 accLength;
+        for (uint256 u = 0; u < N; u++) {
+            if (v >= length) return (false, s2, 0);
+            acc = (acc << 8) | uint8(signature[v++]);
+            uint256 b = acc >> accLength;
+            bool negative = (b & 128) != 0;
+            uint256 m = b & 127;
+            while (true) {
+                if (accLength == 0) {
+                    if (v >= length) return (false, s2, 0);
+                    acc = (acc << 8) | uint8(signature[v++]);
+                    accLength = 8;
+                }
+                accLength -= 1;
+                if (((acc >> accLength) & 1) != 0) break;
+                m += 128;
+                if (m > 2047) return (false, s2, 0);
+            }
+            if (negative && m == 0) return (false, s2, 0);
+            norm += m * m;
+            s2[u] = negative ? Q - m : m;
+        }
+        if ((acc & ((1 << accLength) - 1)) != 0) return (false, s2, 0);
+        // A padded signature may end in zero bytes.
+        if (v != length) {
+            if (length != PADDED_SIGNATURE_LENGTH) return (false, s2, 0);
+            for (; v < length; v++) {
+                if (uint8(signature[v]) != 0) return (false, s2, 0);
+            }
+        }
+        return (true, s2, norm);
+    }
+
+    /// SHAKE256(salt || message) read as 16-bit big-endian samples; those
+    /// below 5 * Q are kept and reduced.
+    function hashToPoint(bytes memory signature, bytes memory message) private pure returns (uint256[] memory c) {
+        bytes memory input = new bytes(SALT_LENGTH + message.length);
+        for (uint256 i = 0; i < SALT_LENGTH; i++) input[i] = signature[1 + i];
+        for (uint256 i = 0; i < message.length; i++) input[SALT_LENGTH + i] = message[i];
+        uint64[25] memory state = absorb(input);
+        c = new uint256[](N);
+        uint256 u;
+        while (true) {
+            for (uint256 lane = 0; lane < RATE / 8; lane++) {
+                uint64 word = state[lane];
+                for (uint256 j = 0; j < 8; j += 2) {
+                    uint256 w = (uint256(uint8(word >> (8 * j))) << 8) | uint8(word >> (8 * j + 8));
+                    if (w < 5 * Q) {
+                        c[u++] = w % Q;
+                        if (u == N) return c;
+                    }
+                }
+            }
+            keccakF(state);
+        }
+    }
+
+    /// Absorb with SHAKE padding; the state is ready to squeeze.
+    function absorb(bytes memory input) private pure returns (uint64[25] memory state) {
+        uint256 blocks = input.length / RATE + 1;
+        for (uint256 block_ = 0; block_ < blocks; block_++) {
+            for (uint256 i = 0; i < RATE; i++) {
+                uint256 at = block_ * RATE + i;
+                uint64 value;
+                if (at < input.length) value = uint8(input[at]);
+                else if (at == input.length) value = 0x1f;
+                if (i == RATE - 1 && block_ == blocks - 1) value |= 0x80;
+                state[i / 8] ^= value << (8 * (i % 8));
+            }
+            keccakF(state);
+        }
+    }
+
+    function keccakF(uint64[25] memory a) private pure {
+        bytes memory rotations = ROTATIONS;
+        bytes memory roundConstants = ROUND_CONSTANTS;
+        uint64[5] memory c;
+        uint64[25] memory b;
+        for (uint256 round = 0; round < 24; round++) {
+            for (uint256 x = 0; x < 5; x++) {
+                c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+            }
+            for (uint256 x = 0; x < 5; x++) {
+                uint64 d = c[(x + 4) % 5] ^ rotl(c[(x + 1) % 5], 1);
+                for (uint256 y = 0; y < 25; y += 5) a[x + y] ^= d;
+            }
+            for (uint256 x = 0; x < 5; x++) {
+                for (uint256 y = 0; y < 5; y++) {
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(a[x + 5 * y], uint8(rotations[x + 5 * y]));
+                }
+            }
+            for (uint256 x = 0; x < 5; x++) {
+                for (uint256 y = 0; y < 25; y += 5) {
+                    a[x + y] = b[x + y] ^ (~b[((x + 1) % 5) + y] & b[((x + 2) % 5) + y]);
+                }
+            }
+            uint64 roundConstant;
+            for (uint256 i = 0; i < 8; i++) {
+                roundConstant = (roundConstant << 8) | uint8(roundConstants[8 * round + i]);
+            }
+            a[0] ^= roundConstant;
+        }
+    }
+
+    function rotl(uint64 x, uint256 n) private pure returns (uint64) {
+        return n == 0 ? x : (x << n) | (x >> (64 - n));
+    }
+
+    function ntt(uint256[] memory a) private pure {
+        bytes memory zetas = ZETAS;
+        uint256 k;
+        for (uint256 len = N / 2; len >= 1; len /= 2) {
+            for (uint256 start = 0; start < N; start += 2 * len) {
+                k++;
+                uint256 zeta = zetaAt(zetas, k);
+                for (uint256 j = start; j < start + len; j++) {
+                    uint256 t = mulmod(zeta, a[j + len], Q);
+                    a[j + len] = addmod(a[j], Q - t, Q);
+                    a[j] = addmod(a[j], t, Q);
+                }
+            }
+        }
+    }
+
+    function inverseNtt(uint256[] memory a) private pure {
+        bytes memory zetas = ZETAS;
+        uint256 k = N;
+        for (uint256 len = 1; len < N; len *= 2) {
+            for (uint256 start = 0; start < N; start += 2 * len) {
+                k--;
+                uint256 zeta = Q - zetaAt(zetas, k);
+                for (uint256 j = start; j < start + len; j++) {
+                    uint256 t = a[j];
+                    a[j] = addmod(t, a[j + len], Q);
+                    a[j + len] = mulmod(addmod(t, Q - a[j + len], Q), zeta, Q);
+                }
+            }
+        }
+        for (uint256 i = 0; i < N; i++) a[i] = mulmod(a[i], N_INVERSE, Q);
+    }
+
+    function zetaAt(bytes memory zetas, uint256 k) private pure returns (uint256) {
+        return (uint256(uint8(zetas[2 * k])) << 8) | uint8(zetas[2 * k + 1]);
+    }
+}
+
+/// @notice Verifies Falcon-512 signatures for callers and off-chain tools.
+contract Falcon512Verifier {
+    function verify(bytes calldata publicKey, bytes calldata message, bytes calldata signature)
+        external
+        pure
+        returns (bool)
+    {
+        return Falcon512.verify(publicKey, message, signature);
+    }
+}
+"#;
+
+const ACCOUNT_TEMPLATE: &str = r#"// SPDX-License-Identifier: MIT OR Apache-2.0
+// Generated by aegis_crypto_core::evm::solidity. Do not edit.
+pragma solidity ^0.8.24;
+
+import "./Falcon512.sol";
+
+/// @dev EntryPoint v0.7 user operation.
+struct PackedUserOperation {
+    address sender;
+    uint256 nonce;
+    bytes initCode;
+    bytes callData;
+    bytes32 accountGasLimits;
+    uint256 preVerificationGas;
+    bytes32 gasFees;
+    bytes paymasterAndData;
+    bytes signature;
+}
+
+/// @notice ERC-4337 account controlled by a Falcon-512 key. The signature
+/// of a user operation is a Falcon-512 signature over its 32-byte hash.
+contract Falcon512Account {
+    uint256 internal constant SIG_VALIDATION_FAILED = 1;
+
+    address public immutable entryPoint;
+    bytes public publicKey;
+
+    constructor(address entryPoint_, bytes memory publicKey_) {
+        require(publicKey_.length == Falcon512.PUBLIC_KEY_LENGTH, "bad public key");
+        entryPoint = entryPoint_;
+        publicKey = publicKey_;
+    }
+
+    modifier onlyEntryPoint() {
+        require(msg.sender == entryPoint, "not from EntryPoint");
+        _;
+    }
+
+    function validateUserOp(PackedUserOperation calldata userOp, bytes32 userOpHash, uint256 missingAccountFunds)
+        external
+        onlyEntryPoint
+        returns (uint256 validationData)
+    {
+        if (!Falcon512.verify(publicKey, abi.encodePacked(userOpHash), userOp.signature)) {
+            validationData = SIG_VALIDATION_FAILED;
+        }
+        if (missingAccountFunds > 0) {
+            (bool paid, ) = payable(msg.sender).call{ value: missingAccountFunds }("");
+            (paid);
+        }
+    }
+
+    function execute(address target, uint256 value, bytes calldata data) external onlyEntryPoint {
+        (bool ok, bytes memory result) = target.call{ value: value }(data);
+        if (!ok) {
+            assembly {
+                revert(add(result, 32), mload(result))
+            }
+        }
+    }
+
+    receive() external payable {}
+}
+"#;
+
+/// `Falcon512.sol`: the verifier library and contract.
+pub fn falcon512_verifier() -> String {
+    let zetas: Vec<u8> = zetas()
+        .iter()
+        .flat_map(|&z| (z as u16).to_be_bytes())
+        .collect();
+    let round_constants: Vec<u8> = ROUND_CONSTANTS.iter().flat_map(|c| c.to_be_bytes()).collect();
+    let rotations: Vec<u8> = ROTATIONS.iter().map(|&r| r as u8).collect();
+    VERIFIER_TEMPLATE
+        .replace("{{N_INVERSE}}", &n_inverse().to_string())
+        .replace("{{ZETAS}}", &hex::encode(zetas))
+        .replace("{{ROUND_CONSTANTS}}", &hex::encode(round_constants))
+        .replace("{{ROTATIONS}}", &hex::encode(rotations))
+}
+
+/// `Falcon512Account.sol`: an ERC-4337 account that imports `Falcon512.sol`.
+pub fn falcon512_account() -> String {
+    ACCOUNT_TEMPLATE.to_string()
+}
@@ -31,6 +31,15 @@ pub struct FalconKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    FalconKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_falcon::falcon512,
+        pqcrypto_falcon::falcon1024
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl FalconKeyPair {
     /// Returns the public key component of the Falcon key pair.
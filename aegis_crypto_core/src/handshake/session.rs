@@ -0,0 +1,104 @@
+//! Established channel with one AEAD key per direction.
+
+use aes_gcm::{ aead::{ Aead, KeyInit, Payload }, Aes256Gcm, Nonce };
+use zeroize::Zeroize;
+
+use super::transcript::{ KeySchedule, HASH_LEN };
+use super::{ HandshakeError, PeerIdentity, Role };
+
+/// AES-256-GCM key for one direction. The nonce is the static IV XORed
+/// with a 64-bit record sequence number.
+pub(crate) struct TrafficKey {
+    cipher: Aes256Gcm,
+    iv: [u8; 12],
+    seq: u64,
+}
+
+impl TrafficKey {
+    pub(crate) fn derive(schedule: &KeySchedule, label: &str, context: &[u8]) -> Self {
+        let mut okm = [0u8; 44];
+        schedule.expand(label, context, &mut okm);
+        let cipher = Aes256Gcm::new_from_slice(&okm[..32]).expect("32-byte key");
+        let mut iv = [0u8; 12];
+        iv.copy_from_slice(&okm[32..]);
+        okm.zeroize();
+        TrafficKey { cipher, iv, seq: 0 }
+    }
+
+    fn next_nonce(&mut self) -> Result<[u8; 12], HandshakeError> {
+        let seq = self.seq;
+        self.seq = seq.checked_add(1).ok_or(HandshakeError::SequenceExhausted)?;
+        let mut nonce = self.iv;
+        for (n, s) in nonce[4..].iter_mut().zip(seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        Ok(nonce)
+    }
+
+    pub(crate) fn seal(&mut self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, HandshakeError> {
+        let nonce = self.next_nonce()?;
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| HandshakeError::DecryptionFailed)
+    }
+
+    pub(crate) fn open(&mut self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, HandshakeError> {
+        let nonce = self.next_nonce()?;
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: ciphertext, aad })
+            .map_err(|_| HandshakeError::DecryptionFailed)
+    }
+}
+
+/// A mutually authenticated channel produced by a completed handshake.
+///
+/// Records must be opened in the order they were sealed; a dropped,
+/// replayed or reordered record fails to decrypt.
+pub struct Session {
+    role: Role,
+    send: TrafficKey,
+    recv: TrafficKey,
+    handshake_hash: [u8; HASH_LEN],
+    peer: PeerIdentity,
+}
+
+impl Session {
+    pub(crate) fn new(
+        role: Role,
+        schedule: &KeySchedule,
+        handshake_hash: [u8; HASH_LEN],
+        peer: PeerIdentity
+    ) -> Self {
+        let initiator = TrafficKey::derive(schedule, "i ap traffic", &handshake_hash);
+        let responder = TrafficKey::derive(schedule, "r ap traffic", &handshake_hash);
+        let (send, recv) = match role {
+            Role::Initiator => (initiator, responder),
+            Role::Responder => (responder, initiator),
+        };
+        Session { role, send, recv, handshake_hash, peer }
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// The authenticated identity of the other party.
+    pub fn peer(&self) -> &PeerIdentity {
+        &self.peer
+    }
+
+    /// Hash of the full handshake transcript, usable for channel binding.
+    pub fn handshake_hash(&self) -> &[u8; HASH_LEN] {
+        &self.handshake_hash
+    }
+
+    /// Encrypt a record for the peer.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, HandshakeError> {
+        self.send.seal(&[], plaintext)
+    }
+
+    /// Decrypt the next record from the peer.
+    pub fn open(&mut self, record: &[u8]) -> Result<Vec<u8>, HandshakeError> {
+        self.recv.open(&[], record)
+    }
+}
@@ -0,0 +1,313 @@
+//! Sans-IO handshake state machine.
+
+use zeroize::{ Zeroize, Zeroizing };
+
+use super::session::{ Session, TrafficKey };
+use super::transcript::{ finished_mac, put_bytes, put_u16, verify_finished, KeySchedule, Reader, Transcript, HASH_LEN };
+use super::{ HandshakeConfig, HandshakeError, PeerIdentity, Role };
+use crate::registry::{ self, AlgorithmId, AlgorithmKind };
+
+const VERSION: u8 = 1;
+
+const MSG_HELLO: u8 = 1;
+const MSG_RESPONSE: u8 = 2;
+const MSG_AUTH: u8 = 3;
+
+const NONCE_LEN: usize = 32;
+
+// Transcript item types.
+const ITEM_HELLO: u8 = 1;
+const ITEM_RESPONSE: u8 = 2;
+const ITEM_IDENTITY: u8 = 3;
+const ITEM_SIGNATURE: u8 = 4;
+const ITEM_FINISHED: u8 = 5;
+
+enum State {
+    InitiatorStart,
+    InitiatorAwaitResponse {
+        kem_secret_key: Zeroizing<Vec<u8>>,
+    },
+    InitiatorSendAuth {
+        schedule: KeySchedule,
+        peer: PeerIdentity,
+    },
+    ResponderAwaitHello,
+    ResponderSendResponse {
+        ciphertext: Vec<u8>,
+        shared_secret: Zeroizing<Vec<u8>>,
+    },
+    ResponderAwaitAuth {
+        schedule: KeySchedule,
+    },
+    Complete(Box<Session>),
+    Failed,
+}
+
+/// One side of a handshake.
+///
+/// The handshake is three messages: the initiator's hello (ephemeral KEM
+/// public key), the responder's reply (KEM ciphertext plus its encrypted
+/// identity, signature and Finished MAC) and the initiator's encrypted
+/// identity, signature and Finished MAC. Drive it by calling
+/// [`write_message`](Self::write_message) whenever
+/// [`wants_write`](Self::wants_write) is true and passing every message
+/// received from the peer to [`read_message`](Self::read_message). Any
+/// error is fatal: the handshake cannot be resumed.
+pub struct Handshake {
+    role: Role,
+    config: HandshakeConfig,
+    transcript: Transcript,
+    state: State,
+}
+
+impl Handshake {
+    pub fn initiator(config: HandshakeConfig) -> Self {
+        Handshake { role: Role::Initiator, config, transcript: Transcript::new(), state: State::InitiatorStart }
+    }
+
+    pub fn responder(config: HandshakeConfig) -> Self {
+        Handshake {
+            role: Role::Responder,
+            config,
+            transcript: Transcript::new(),
+            state: State::ResponderAwaitHello,
+        }
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Whether the next step is to send a message.
+    pub fn wants_write(&self) -> bool {
+        matches!(
+            self.state,
+            State::InitiatorStart | State::InitiatorSendAuth { .. } | State::ResponderSendResponse { .. }
+        )
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, State::Complete(_))
+    }
+
+    /// The established session, once the handshake is complete.
+    pub fn into_session(mut self) -> Result<Session, HandshakeError> {
+        match std::mem::replace(&mut self.state, State::Failed) {
+            State::Complete(session) => Ok(*session),
+            _ => Err(HandshakeError::NotComplete),
+        }
+    }
+
+    /// Produce the next outgoing message.
+    pub fn write_message(&mut self) -> Result<Vec<u8>, HandshakeError> {
+        let (message, next) = match std::mem::replace(&mut self.state, State::Failed) {
+            State::InitiatorStart => {
+                let (message, kem_secret_key) = self.write_hello()?;
+                (message, State::InitiatorAwaitResponse { kem_secret_key })
+            }
+            State::ResponderSendResponse { ciphertext, shared_secret } => {
+                let (message, schedule) = self.write_response(&ciphertext, &shared_secret)?;
+                (message, State::ResponderAwaitAuth { schedule })
+            }
+            State::InitiatorSendAuth { schedule, peer } => {
+                let plaintext = self.write_auth(&schedule)?;
+                let header = [VERSION, MSG_AUTH];
+                let sealed = TrafficKey::derive(&schedule, "i hs traffic", &[]).seal(&header, &plaintext)?;
+                let mut message = header.to_vec();
+                put_bytes(&mut message, &sealed);
+                let session = Session::new(Role::Initiator, &schedule, self.transcript.hash(), peer);
+                (message, State::Complete(Box::new(session)))
+            }
+            _ => {
+                return Err(HandshakeError::UnexpectedMessage);
+            }
+        };
+        self.state = next;
+        Ok(message)
+    }
+
+    /// Process a message received from the peer.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<(), HandshakeError> {
+        self.state = match std::mem::replace(&mut self.state, State::Failed) {
+            State::ResponderAwaitHello => {
+                let (ciphertext, shared_secret) = self.read_hello(message)?;
+                State::ResponderSendResponse { ciphertext, shared_secret: Zeroizing::new(shared_secret) }
+            }
+            State::InitiatorAwaitResponse { kem_secret_key } => {
+                let (schedule, peer) = self.read_response(message, &kem_secret_key)?;
+                State::InitiatorSendAuth { schedule, peer }
+            }
+            State::ResponderAwaitAuth { schedule } => {
+                let mut reader = Reader::new(message);
+                check_header(&mut reader, MSG_AUTH)?;
+                let sealed = reader.bytes()?;
+                reader.finish()?;
+                let plaintext = TrafficKey::derive(&schedule, "i hs traffic", &[]).open(&message[..2], sealed)?;
+                let peer = self.read_auth(&schedule, Role::Initiator, &plaintext)?;
+                State::Complete(Box::new(Session::new(Role::Responder, &schedule, self.transcript.hash(), peer)))
+            }
+            _ => {
+                return Err(HandshakeError::UnexpectedMessage);
+            }
+        };
+        Ok(())
+    }
+
+    fn write_hello(&mut self) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), HandshakeError> {
+        let (public_key, secret_key) = registry::keygen(self.config.kem)?;
+        let mut message = vec![VERSION, MSG_HELLO];
+        put_u16(&mut message, self.config.kem.code());
+        message.extend_from_slice(&random_nonce());
+        put_bytes(&mut message, &public_key);
+        self.transcript.absorb(ITEM_HELLO, &message);
+        Ok((message, Zeroizing::new(secret_key)))
+    }
+
+    fn read_hello(&mut self, message: &[u8]) -> Result<(Vec<u8>, Vec<u8>), HandshakeError> {
+        let mut reader = Reader::new(message);
+        check_header(&mut reader, MSG_HELLO)?;
+        let kem = AlgorithmId::from_code(reader.u16()?)?;
+        if kem != self.config.kem {
+            return Err(HandshakeError::UnsupportedAlgorithm(kem));
+        }
+        reader.take(NONCE_LEN)?;
+        let public_key = reader.bytes()?;
+        reader.finish()?;
+        self.transcript.absorb(ITEM_HELLO, message);
+        Ok(registry::encapsulate(kem, public_key)?)
+    }
+
+    fn write_response(
+        &mut self,
+        ciphertext: &[u8],
+        shared_secret: &[u8]
+    ) -> Result<(Vec<u8>, KeySchedule), HandshakeError> {
+        let mut message = vec![VERSION, MSG_RESPONSE];
+        message.extend_from_slice(&random_nonce());
+        put_bytes(&mut message, ciphertext);
+        self.transcript.absorb(ITEM_RESPONSE, &message);
+
+        let schedule = KeySchedule::new(shared_secret, &self.transcript.hash());
+        let plaintext = self.write_auth(&schedule)?;
+        let sealed = TrafficKey::derive(&schedule, "r hs traffic", &[]).seal(&message, &plaintext)?;
+        put_bytes(&mut message, &sealed);
+        Ok((message, schedule))
+    }
+
+    fn read_response(
+        &mut self,
+        message: &[u8],
+        kem_secret_key: &[u8]
+    ) -> Result<(KeySchedule, PeerIdentity), HandshakeError> {
+        let mut reader = Reader::new(message);
+        check_header(&mut reader, MSG_RESPONSE)?;
+        reader.take(NONCE_LEN)?;
+        let ciphertext = reader.bytes()?;
+        let sealed = reader.bytes()?;
+        reader.finish()?;
+        let hello = &message[..message.len() - 4 - sealed.len()];
+        self.transcript.absorb(ITEM_RESPONSE, hello);
+
+        let mut shared_secret = registry::decapsulate(self.config.kem, kem_secret_key, ciphertext)?;
+        let schedule = KeySchedule::new(&shared_secret, &self.transcript.hash());
+        shared_secret.zeroize();
+        let plaintext = TrafficKey::derive(&schedule, "r hs traffic", &[]).open(hello, sealed)?;
+        let peer = self.read_auth(&schedule, Role::Responder, &plaintext)?;
+        Ok((schedule, peer))
+    }
+
+    /// Identity, transcript signature and Finished MAC of this side.
+    fn write_auth(&mut self, schedule: &KeySchedule) -> Result<Vec<u8>, HandshakeError> {
+        let identity = &self.config.identity;
+        let mut plaintext = Vec::new();
+        put_u16(&mut plaintext, identity.algorithm().code());
+        put_bytes(&mut plaintext, identity.public_key());
+        self.transcript.absorb(ITEM_IDENTITY, &plaintext);
+
+        let signature = registry::sign(
+            identity.algorithm(),
+            identity.secret_key(),
+            &signature_input(self.role, &self.transcript.hash())
+        )?;
+        self.transcript.absorb(ITEM_SIGNATURE, &signature);
+        put_bytes(&mut plaintext, &signature);
+
+        let finished = finished_mac(&finished_key(schedule, self.role), &self.transcript.hash());
+        self.transcript.absorb(ITEM_FINISHED, &finished);
+        plaintext.extend_from_slice(&finished);
+        Ok(plaintext)
+    }
+
+    fn read_auth(
+        &mut self,
+        schedule: &KeySchedule,
+        peer_role: Role,
+        plaintext: &[u8]
+    ) -> Result<PeerIdentity, HandshakeError> {
+        let mut reader = Reader::new(plaintext);
+        let algorithm = AlgorithmId::from_code(reader.u16()?)?;
+        let public_key = reader.bytes()?;
+        let signature = reader.bytes()?;
+        let finished = reader.take(HASH_LEN)?;
+        reader.finish()?;
+
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(HandshakeError::UnsupportedAlgorithm(algorithm));
+        }
+        let peer = PeerIdentity { algorithm, public_key: public_key.to_vec() };
+        if let Some(expected) = &self.config.expected_peer {
+            if *expected != peer {
+                return Err(HandshakeError::UnknownPeer);
+            }
+        }
+
+        self.transcript.absorb(ITEM_IDENTITY, &plaintext[..2 + 4 + public_key.len()]);
+        let input = signature_input(peer_role, &self.transcript.hash());
+        if !registry::verify(algorithm, public_key, &input, signature) {
+            return Err(HandshakeError::BadSignature);
+        }
+        self.transcript.absorb(ITEM_SIGNATURE, signature);
+        verify_finished(&finished_key(schedule, peer_role), &self.transcript.hash(), finished)?;
+        self.transcript.absorb(ITEM_FINISHED, finished);
+        Ok(peer)
+    }
+}
+
+fn check_header(reader: &mut Reader<'_>, message_type: u8) -> Result<(), HandshakeError> {
+    if reader.u8()? != VERSION {
+        return Err(HandshakeError::Malformed("unsupported version".into()));
+    }
+    if reader.u8()? != message_type {
+        return Err(HandshakeError::UnexpectedMessage);
+    }
+    Ok(())
+}
+
+/// Signatures cover a role-specific context string and the transcript
+/// hash, so a signature from one role can never be replayed as the other.
+fn signature_input(role: Role, transcript_hash: &[u8; HASH_LEN]) -> Vec<u8> {
+    let context: &[u8] = match role {
+        Role::Initiator => b"aegis-handshake-v1 initiator signature",
+        Role::Responder => b"aegis-handshake-v1 responder signature",
+    };
+    let mut input = context.to_vec();
+    input.push(0);
+    input.extend_from_slice(transcript_hash);
+    input
+}
+
+fn finished_key(schedule: &KeySchedule, role: Role) -> [u8; HASH_LEN] {
+    let label = match role {
+        Role::Initiator => "i finished",
+        Role::Responder => "r finished",
+    };
+    let mut key = [0u8; HASH_LEN];
+    schedule.expand(label, &[], &mut key);
+    key
+}
+
+fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).expect("RNG failed");
+    nonce
+}
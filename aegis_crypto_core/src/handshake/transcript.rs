@@ -0,0 +1,131 @@
+//! Transcript hashing, key schedule and wire encoding helpers.
+
+use hkdf::Hkdf;
+use hmac::{ Hmac, Mac };
+use sha2::{ Digest, Sha256 };
+
+use super::HandshakeError;
+
+/// Protocol label absorbed first into every transcript and prefixed to
+/// every HKDF label.
+pub(crate) const PROTOCOL_LABEL: &[u8] = b"aegis-handshake-v1";
+
+pub(crate) const HASH_LEN: usize = 32;
+
+/// Running SHA-256 over every handshake message, framed with its type and
+/// length so message boundaries cannot shift.
+#[derive(Clone)]
+pub(crate) struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    pub(crate) fn new() -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(PROTOCOL_LABEL);
+        Transcript { hasher }
+    }
+
+    pub(crate) fn absorb(&mut self, item: u8, data: &[u8]) {
+        self.hasher.update([item]);
+        self.hasher.update((data.len() as u32).to_be_bytes());
+        self.hasher.update(data);
+    }
+
+    /// Hash of everything absorbed so far.
+    pub(crate) fn hash(&self) -> [u8; HASH_LEN] {
+        self.hasher.clone().finalize().into()
+    }
+}
+
+/// Secret derived from the KEM shared secret and the hello transcript.
+pub(crate) struct KeySchedule {
+    prk: Hkdf<Sha256>,
+}
+
+impl KeySchedule {
+    pub(crate) fn new(shared_secret: &[u8], hello_hash: &[u8; HASH_LEN]) -> Self {
+        KeySchedule { prk: Hkdf::<Sha256>::new(Some(hello_hash), shared_secret) }
+    }
+
+    /// HKDF-Expand with info `PROTOCOL_LABEL || " " || label || context`.
+    pub(crate) fn expand(&self, label: &str, context: &[u8], out: &mut [u8]) {
+        let mut info = Vec::with_capacity(PROTOCOL_LABEL.len() + 1 + label.len() + context.len());
+        info.extend_from_slice(PROTOCOL_LABEL);
+        info.push(b' ');
+        info.extend_from_slice(label.as_bytes());
+        info.extend_from_slice(context);
+        self.prk.expand(&info, out).expect("output length is valid for HKDF-SHA256");
+    }
+}
+
+/// HMAC-SHA256 over a transcript hash, used for Finished messages.
+pub(crate) fn finished_mac(key: &[u8], transcript_hash: &[u8; HASH_LEN]) -> [u8; HASH_LEN] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(transcript_hash);
+    mac.finalize().into_bytes().into()
+}
+
+pub(crate) fn verify_finished(
+    key: &[u8],
+    transcript_hash: &[u8; HASH_LEN],
+    tag: &[u8]
+) -> Result<(), HandshakeError> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(transcript_hash);
+    mac.verify_slice(tag).map_err(|_| HandshakeError::BadFinished)
+}
+
+pub(crate) fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Append a `u32` length-prefixed byte string.
+pub(crate) fn put_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Cursor over a received message.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], HandshakeError> {
+        if self.data.len() < len {
+            return Err(HandshakeError::Malformed("truncated message".into()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, HandshakeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, HandshakeError> {
+        let b = self.take(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn bytes(&mut self) -> Result<&'a [u8], HandshakeError> {
+        let b = self.take(4)?;
+        let len = u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize;
+        self.take(len)
+    }
+
+    /// Fail unless the whole message has been consumed.
+    pub(crate) fn finish(self) -> Result<(), HandshakeError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(HandshakeError::Malformed("trailing data".into()))
+        }
+    }
+}
@@ -0,0 +1,575 @@
+//! Leighton-Micali signatures (RFC 8554) and the SP 800-208 additions.
+//!
+//! An LMS key is a Merkle tree of height `h` over `2^h` LM-OTS one-time
+//! keys. HSS stacks up to eight LMS trees: each tree signs the public key
+//! of the tree below it, and the bottom tree signs messages. Supported
+//! type codes cover SHA-256, SHA-256/192, SHAKE256/256 and SHAKE256/192,
+//! with heights 5 to 25 and Winternitz widths 1, 2, 4 and 8.
+//!
+//! One-time keys, lower-level trees and the randomizers used when a tree
+//! signs its child are derived from a 32-byte seed as in RFC 8554
+//! Appendix A. Message randomizers are fresh random bytes. A signer keeps
+//! one Merkle tree per level in memory, `2^(h+1)` nodes each, so choose
+//! HSS with several small trees over a single tall one.
+
+use zeroize::Zeroizing;
+
+use super::sealed::{ KeyState, Token };
+use super::{ u32_at, HashFamily, HbsError, Reader, StatefulKey };
+
+pub const LMS_SHA256_M32_H5: u32 = 0x05;
+pub const LMS_SHA256_M32_H10: u32 = 0x06;
+pub const LMS_SHA256_M32_H15: u32 = 0x07;
+pub const LMS_SHA256_M32_H20: u32 = 0x08;
+pub const LMS_SHA256_M32_H25: u32 = 0x09;
+pub const LMS_SHA256_M24_H5: u32 = 0x0a;
+pub const LMS_SHA256_M24_H10: u32 = 0x0b;
+pub const LMS_SHA256_M24_H15: u32 = 0x0c;
+pub const LMS_SHA256_M24_H20: u32 = 0x0d;
+pub const LMS_SHA256_M24_H25: u32 = 0x0e;
+pub const LMS_SHAKE_M32_H5: u32 = 0x0f;
+pub const LMS_SHAKE_M32_H10: u32 = 0x10;
+pub const LMS_SHAKE_M32_H15: u32 = 0x11;
+pub const LMS_SHAKE_M32_H20: u32 = 0x12;
+pub const LMS_SHAKE_M32_H25: u32 = 0x13;
+pub const LMS_SHAKE_M24_H5: u32 = 0x14;
+pub const LMS_SHAKE_M24_H10: u32 = 0x15;
+pub const LMS_SHAKE_M24_H15: u32 = 0x16;
+pub const LMS_SHAKE_M24_H20: u32 = 0x17;
+pub const LMS_SHAKE_M24_H25: u32 = 0x18;
+
+pub const LMOTS_SHA256_N32_W1: u32 = 0x01;
+pub const LMOTS_SHA256_N32_W2: u32 = 0x02;
+pub const LMOTS_SHA256_N32_W4: u32 = 0x03;
+pub const LMOTS_SHA256_N32_W8: u32 = 0x04;
+pub const LMOTS_SHA256_N24_W1: u32 = 0x05;
+pub const LMOTS_SHA256_N24_W2: u32 = 0x06;
+pub const LMOTS_SHA256_N24_W4: u32 = 0x07;
+pub const LMOTS_SHA256_N24_W8: u32 = 0x08;
+pub const LMOTS_SHAKE_N32_W1: u32 = 0x09;
+pub const LMOTS_SHAKE_N32_W2: u32 = 0x0a;
+pub const LMOTS_SHAKE_N32_W4: u32 = 0x0b;
+pub const LMOTS_SHAKE_N32_W8: u32 = 0x0c;
+pub const LMOTS_SHAKE_N24_W1: u32 = 0x0d;
+pub const LMOTS_SHAKE_N24_W2: u32 = 0x0e;
+pub const LMOTS_SHAKE_N24_W4: u32 = 0x0f;
+pub const LMOTS_SHAKE_N24_W8: u32 = 0x10;
+
+/// Maximum number of HSS levels.
+pub const MAX_LEVELS: usize = 8;
+
+const ID_LEN: usize = 16;
+const D_PBLC: [u8; 2] = [0x80, 0x80];
+const D_MESG: [u8; 2] = [0x81, 0x81];
+const D_LEAF: [u8; 2] = [0x82, 0x82];
+const D_INTR: [u8; 2] = [0x83, 0x83];
+// Derivation indices for values that are not Winternitz chains.
+const DERIVE_C: [u8; 2] = [0xff, 0xfd];
+const DERIVE_SEED: [u8; 2] = [0xff, 0xfe];
+const DERIVE_ID: [u8; 2] = [0xff, 0xff];
+
+const MAGIC: &[u8; 4] = b"AHSS";
+const VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LmsParams {
+    code: u32,
+    family: HashFamily,
+    m: usize,
+    h: usize,
+}
+
+impl LmsParams {
+    fn from_code(code: u32) -> Result<Self, HbsError> {
+        let (family, m, base) = match code {
+            0x05..=0x09 => (HashFamily::Sha256, 32, 0x05),
+            0x0a..=0x0e => (HashFamily::Sha256, 24, 0x0a),
+            0x0f..=0x13 => (HashFamily::Shake256, 32, 0x0f),
+            0x14..=0x18 => (HashFamily::Shake256, 24, 0x14),
+            _ => {
+                return Err(HbsError::UnknownType(code));
+            }
+        };
+        Ok(LmsParams { code, family, m, h: 5 * (1 + (code - base) as usize) })
+    }
+
+    fn public_key_len(&self) -> usize {
+        8 + ID_LEN + self.m
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OtsParams {
+    code: u32,
+    family: HashFamily,
+    n: usize,
+    w: usize,
+    p: usize,
+    ls: u32,
+}
+
+impl OtsParams {
+    fn from_code(code: u32) -> Result<Self, HbsError> {
+        let (family, n, base): (_, usize, _) = match code {
+            0x01..=0x04 => (HashFamily::Sha256, 32, 0x01),
+            0x05..=0x08 => (HashFamily::Sha256, 24, 0x05),
+            0x09..=0x0c => (HashFamily::Shake256, 32, 0x09),
+            0x0d..=0x10 => (HashFamily::Shake256, 24, 0x0d),
+            _ => {
+                return Err(HbsError::UnknownType(code));
+            }
+        };
+        let w = 1usize << (code - base);
+        // RFC 8554 Appendix B.
+        let u = (8 * n).div_ceil(w);
+        let max_sum = ((1usize << w) - 1) * u;
+        let v = (max_sum.ilog2() as usize + 1).div_ceil(w);
+        let ls = 16 - (v * w) as u32;
+        Ok(OtsParams { code, family, n, w, p: u + v, ls })
+    }
+
+    fn signature_len(&self) -> usize {
+        4 + self.n * (self.p + 1)
+    }
+
+    fn hash(&self, parts: &[&[u8]]) -> [u8; 32] {
+        self.family.hash(self.n, parts)
+    }
+
+    /// Digit `i` of `s` in base `2^w`.
+    fn coef(&self, s: &[u8], i: usize) -> usize {
+        let per_byte = 8 / self.w;
+        let shift = 8 - self.w * (i % per_byte + 1);
+        ((s[i / per_byte] >> shift) as usize) & ((1 << self.w) - 1)
+    }
+
+    /// The `p` chain lengths signed for message hash `q`.
+    fn digits(&self, q: &[u8]) -> Vec<usize> {
+        let max = (1 << self.w) - 1;
+        let sum: usize = (0..(8 * self.n) / self.w).map(|i| max - self.coef(q, i)).sum();
+        let checksum = ((sum as u16) << self.ls).to_be_bytes();
+        let extended = [q, &checksum].concat();
+        (0..self.p).map(|i| self.coef(&extended, i)).collect()
+    }
+
+    /// Advance chain `i` of key `q` from step `start` to step `end`.
+    fn chain(&self, id: &[u8], q: u32, i: usize, start: usize, end: usize, value: &[u8]) -> [u8; 32] {
+        let mut tmp = [0u8; 32];
+        tmp[..self.n].copy_from_slice(&value[..self.n]);
+        let q = q.to_be_bytes();
+        let i = (i as u16).to_be_bytes();
+        for j in start..end {
+            tmp = self.hash(&[id, &q, &i, &[j as u8], &tmp[..self.n]]);
+        }
+        tmp
+    }
+
+    fn private_element(&self, id: &[u8], q: u32, i: usize, seed: &[u8]) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.hash(&[id, &q.to_be_bytes(), &(i as u16).to_be_bytes(), &[0xff], seed]))
+    }
+
+    fn public_key_hash(&self, id: &[u8], q: u32, seed: &[u8]) -> [u8; 32] {
+        let end = (1 << self.w) - 1;
+        let mut ys = Vec::with_capacity(self.p * self.n);
+        for i in 0..self.p {
+            let x = self.private_element(id, q, i, seed);
+            ys.extend_from_slice(&self.chain(id, q, i, 0, end, &x[..self.n])[..self.n]);
+        }
+        self.hash(&[id, &q.to_be_bytes(), &D_PBLC, &ys])
+    }
+
+    fn sign(&self, id: &[u8], q: u32, seed: &[u8], c: &[u8], message: &[u8]) -> Vec<u8> {
+        let hash = self.hash(&[id, &q.to_be_bytes(), &D_MESG, c, message]);
+        let mut out = Vec::with_capacity(self.signature_len());
+        out.extend_from_slice(&self.code.to_be_bytes());
+        out.extend_from_slice(c);
+        for (i, digit) in self.digits(&hash[..self.n]).into_iter().enumerate() {
+            let x = self.private_element(id, q, i, seed);
+            out.extend_from_slice(&self.chain(id, q, i, 0, digit, &x[..self.n])[..self.n]);
+        }
+        out
+    }
+
+    /// The public key hash a signature implies (RFC 8554 Algorithm 4b).
+    fn candidate(&self, id: &[u8], q: u32, signature: &[u8], message: &[u8]) -> [u8; 32] {
+        let (c, ys) = signature[4..].split_at(self.n);
+        let hash = self.hash(&[id, &q.to_be_bytes(), &D_MESG, c, message]);
+        let end = (1 << self.w) - 1;
+        let mut zs = Vec::with_capacity(self.p * self.n);
+        for (i, digit) in self.digits(&hash[..self.n]).into_iter().enumerate() {
+            let y = &ys[i * self.n..(i + 1) * self.n];
+            zs.extend_from_slice(&self.chain(id, q, i, digit, end, y)[..self.n]);
+        }
+        self.hash(&[id, &q.to_be_bytes(), &D_PBLC, &zs])
+    }
+}
+
+fn check_pair(lms: LmsParams, ots: OtsParams) -> Result<(), HbsError> {
+    if lms.family != ots.family || lms.m != ots.n {
+        return Err(HbsError::InvalidParameters("LMS and LM-OTS types use different hashes".into()));
+    }
+    Ok(())
+}
+
+/// One LMS tree with all of its nodes.
+struct LmsTree {
+    lms: LmsParams,
+    ots: OtsParams,
+    id: [u8; ID_LEN],
+    seed: Zeroizing<[u8; 32]>,
+    /// Node `r` (1-based, root first) at `nodes[r]`.
+    nodes: Vec<[u8; 32]>,
+}
+
+impl LmsTree {
+    fn build(lms: LmsParams, ots: OtsParams, id: [u8; ID_LEN], seed: Zeroizing<[u8; 32]>) -> Self {
+        let leaves = 1usize << lms.h;
+        let mut nodes = vec![[0u8; 32]; 2 * leaves];
+        for q in 0..leaves {
+            let r = leaves + q;
+            let k = ots.public_key_hash(&id, q as u32, &seed[..ots.n]);
+            nodes[r] = lms.family.hash(lms.m, &[&id, &(r as u32).to_be_bytes(), &D_LEAF, &k[..ots.n]]);
+        }
+        for r in (1..leaves).rev() {
+            let (left, right) = (&nodes[2 * r][..lms.m], &nodes[2 * r + 1][..lms.m]);
+            nodes[r] = lms.family.hash(lms.m, &[&id, &(r as u32).to_be_bytes(), &D_INTR, left, right]);
+        }
+        LmsTree { lms, ots, id, seed, nodes }
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.lms.public_key_len());
+        out.extend_from_slice(&self.lms.code.to_be_bytes());
+        out.extend_from_slice(&self.ots.code.to_be_bytes());
+        out.extend_from_slice(&self.id);
+        out.extend_from_slice(&self.nodes[1][..self.lms.m]);
+        out
+    }
+
+    /// Pseudorandom value bound to leaf `q` (RFC 8554 Appendix A).
+    fn derive(&self, q: u32, index: [u8; 2]) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.ots.hash(&[&self.id, &q.to_be_bytes(), &index, &[0xff], &self.seed[..self.ots.n]]))
+    }
+
+    fn sign(&self, q: u32, message: &[u8], c: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&q.to_be_bytes());
+        out.extend_from_slice(&self.ots.sign(&self.id, q, &self.seed[..self.ots.n], c, message));
+        out.extend_from_slice(&self.lms.code.to_be_bytes());
+        let mut r = (1usize << self.lms.h) + q as usize;
+        while r > 1 {
+            out.extend_from_slice(&self.nodes[r ^ 1][..self.lms.m]);
+            r >>= 1;
+        }
+        out
+    }
+
+    /// The tree the leaf `q` signs in an HSS hierarchy.
+    fn child(&self, q: u32, lms: LmsParams, ots: OtsParams) -> LmsTree {
+        let mut id = [0u8; ID_LEN];
+        id.copy_from_slice(&self.derive(q, DERIVE_ID)[..ID_LEN]);
+        LmsTree::build(lms, ots, id, self.derive(q, DERIVE_SEED))
+    }
+}
+
+/// Length of the LMS signature at the start of `data`, from its embedded
+/// type codes.
+fn lms_signature_len(data: &[u8]) -> Result<usize, HbsError> {
+    let truncated = || HbsError::Malformed("truncated signature".into());
+    let ots = OtsParams::from_code(u32_at(data, 4).ok_or_else(truncated)?)?;
+    let lms_offset = 4 + ots.signature_len();
+    let lms = LmsParams::from_code(u32_at(data, lms_offset).ok_or_else(truncated)?)?;
+    Ok(lms_offset + 4 + lms.h * lms.m)
+}
+
+fn lms_public_key_len(data: &[u8]) -> Result<usize, HbsError> {
+    let code = u32_at(data, 0).ok_or_else(|| HbsError::Malformed("truncated public key".into()))?;
+    Ok(LmsParams::from_code(code)?.public_key_len())
+}
+
+fn verify_lms(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool, HbsError> {
+    let truncated = || HbsError::Malformed("truncated public key".into());
+    let lms = LmsParams::from_code(u32_at(public_key, 0).ok_or_else(truncated)?)?;
+    let ots = OtsParams::from_code(u32_at(public_key, 4).ok_or_else(truncated)?)?;
+    check_pair(lms, ots)?;
+    if public_key.len() != lms.public_key_len() || signature.len() != lms_signature_len(signature)? {
+        return Ok(false);
+    }
+    let (id, root) = public_key[8..].split_at(ID_LEN);
+    let q = u32_at(signature, 0).expect("length checked");
+    let ots_signature = &signature[4..4 + ots.signature_len()];
+    let lms_code = u32_at(signature, 4 + ots.signature_len()).expect("length checked");
+    if u32_at(ots_signature, 0) != Some(ots.code) || lms_code != lms.code || (q as usize) >> lms.h != 0 {
+        return Ok(false);
+    }
+    let path = &signature[8 + ots.signature_len()..];
+
+    let k = ots.candidate(id, q, ots_signature, message);
+    let mut r = (1u32 << lms.h) + q;
+    let mut node = lms.family.hash(lms.m, &[id, &r.to_be_bytes(), &D_LEAF, &k[..ots.n]]);
+    for sibling in path.chunks(lms.m) {
+        let parent = (r / 2).to_be_bytes();
+        node = if r % 2 == 1 {
+            lms.family.hash(lms.m, &[id, &parent, &D_INTR, sibling, &node[..lms.m]])
+        } else {
+            lms.family.hash(lms.m, &[id, &parent, &D_INTR, &node[..lms.m], sibling])
+        };
+        r /= 2;
+    }
+    Ok(bool::from(subtle::ConstantTimeEq::ct_eq(&node[..lms.m], root)))
+}
+
+/// Verify a single-tree LMS signature against an LMS public key.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    verify_lms(public_key, message, signature).unwrap_or(false)
+}
+
+/// Verify an HSS signature against an HSS public key (`L ‖ LMS key`).
+pub fn verify_hss(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let check = || -> Result<bool, HbsError> {
+        let mut key_reader = Reader::new(public_key);
+        let levels = key_reader.u32()? as usize;
+        if levels == 0 || levels > MAX_LEVELS {
+            return Ok(false);
+        }
+        let mut key = key_reader.take(lms_public_key_len(key_reader.rest())?)?;
+        key_reader.finish()?;
+
+        let mut reader = Reader::new(signature);
+        if reader.u32()? as usize != levels - 1 {
+            return Ok(false);
+        }
+        for _ in 1..levels {
+            let sig = reader.take(lms_signature_len(reader.rest())?)?;
+            let child = reader.take(lms_public_key_len(reader.rest())?)?;
+            if !verify_lms(key, child, sig)? {
+                return Ok(false);
+            }
+            key = child;
+        }
+        let sig = reader.take(lms_signature_len(reader.rest())?)?;
+        reader.finish()?;
+        verify_lms(key, message, sig)
+    };
+    check().unwrap_or(false)
+}
+
+/// An HSS private key with its cached trees. Use it through
+/// [`super::StatefulSigner`].
+pub struct HssPrivateKey {
+    levels: Vec<(LmsParams, OtsParams)>,
+    /// One tree per level; below the top, the tree serving the most
+    /// recent index along with the parent's signature over its key.
+    trees: Vec<(u64, LmsTree, Vec<u8>)>,
+}
+
+impl HssPrivateKey {
+    /// Generate a key from `(LMS type, LM-OTS type)` pairs, top level
+    /// first. All levels must use the same hash function and output size.
+    pub fn generate(levels: &[(u32, u32)]) -> Result<Self, HbsError> {
+        let mut id = [0u8; ID_LEN];
+        let mut seed = Zeroizing::new([0u8; 32]);
+        getrandom::fill(&mut id).expect("RNG failed");
+        getrandom::fill(&mut seed[..]).expect("RNG failed");
+        HssPrivateKey::from_parts(levels, id, seed)
+    }
+
+    fn from_parts(levels: &[(u32, u32)], id: [u8; ID_LEN], seed: Zeroizing<[u8; 32]>) -> Result<Self, HbsError> {
+        if levels.is_empty() || levels.len() > MAX_LEVELS {
+            return Err(HbsError::InvalidParameters(format!("HSS needs 1 to {} levels", MAX_LEVELS)));
+        }
+        let levels = levels
+            .iter()
+            .map(|&(lms, ots)| {
+                let pair = (LmsParams::from_code(lms)?, OtsParams::from_code(ots)?);
+                check_pair(pair.0, pair.1)?;
+                Ok(pair)
+            })
+            .collect::<Result<Vec<_>, HbsError>>()?;
+        if levels.iter().any(|(lms, _)| lms.family != levels[0].0.family || lms.m != levels[0].0.m) {
+            return Err(HbsError::InvalidParameters("all HSS levels must use the same hash".into()));
+        }
+        if levels.iter().map(|(lms, _)| lms.h).sum::<usize>() > 63 {
+            return Err(HbsError::InvalidParameters("total height must not exceed 63".into()));
+        }
+        let (lms, ots) = levels[0];
+        let root = LmsTree::build(lms, ots, id, seed);
+        Ok(HssPrivateKey { levels, trees: vec![(0, root, Vec::new())] })
+    }
+
+    /// Height of the levels below `level`.
+    fn height_below(&self, level: usize) -> usize {
+        self.levels[level + 1..].iter().map(|(lms, _)| lms.h).sum()
+    }
+}
+
+impl StatefulKey for HssPrivateKey {
+    fn capacity(&self) -> u64 {
+        1u64 << self.height_below(0) << self.levels[0].0.h
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        let mut out = (self.levels.len() as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(&self.trees[0].1.public_key());
+        out
+    }
+}
+
+impl KeyState for HssPrivateKey {
+    fn encode(&self, _: Token, next_index: u64) -> Zeroizing<Vec<u8>> {
+        let root = &self.trees[0].1;
+        let mut out = Zeroizing::new(Vec::new());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(self.levels.len() as u8);
+        for (lms, ots) in &self.levels {
+            out.extend_from_slice(&lms.code.to_be_bytes());
+            out.extend_from_slice(&ots.code.to_be_bytes());
+        }
+        out.extend_from_slice(&root.id);
+        out.extend_from_slice(&root.seed[..]);
+        out.extend_from_slice(&next_index.to_be_bytes());
+        out
+    }
+
+    fn decode(_: Token, state: &[u8]) -> Result<(Self, u64), HbsError> {
+        let mut reader = Reader::new(state);
+        if reader.take(4)? != MAGIC || reader.u8()? != VERSION {
+            return Err(HbsError::Malformed("not an HSS private key".into()));
+        }
+        let count = reader.u8()? as usize;
+        let levels = (0..count)
+            .map(|_| Ok((reader.u32()?, reader.u32()?)))
+            .collect::<Result<Vec<_>, HbsError>>()?;
+        let id = reader.take(ID_LEN)?.try_into().expect("length checked");
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(reader.take(32)?);
+        let next = reader.u64()?;
+        reader.finish()?;
+        let key = HssPrivateKey::from_parts(&levels, id, seed)?;
+        if next > key.capacity() {
+            return Err(HbsError::Malformed("index beyond capacity".into()));
+        }
+        Ok((key, next))
+    }
+
+    fn sign_at(&mut self, _: Token, index: u64, message: &[u8]) -> Vec<u8> {
+        let leaf = |key: &Self, level: usize| {
+            let below = key.height_below(level);
+            ((index >> below) & ((1u64 << key.levels[level].0.h) - 1)) as u32
+        };
+        for level in 1..self.levels.len() {
+            let tree_index = index >> self.height_below(level) >> self.levels[level].0.h;
+            if self.trees.get(level).is_some_and(|(cached, _, _)| *cached == tree_index) {
+                continue;
+            }
+            self.trees.truncate(level);
+            let (lms, ots) = self.levels[level];
+            let parent = &self.trees[level - 1].1;
+            let q = leaf(self, level - 1);
+            let child = parent.child(q, lms, ots);
+            let c = parent.derive(q, DERIVE_C);
+            let signature = parent.sign(q, &child.public_key(), &c[..ots.n]);
+            self.trees.push((tree_index, child, signature));
+        }
+
+        let bottom = self.levels.len() - 1;
+        let mut out = (bottom as u32).to_be_bytes().to_vec();
+        for (_, tree, signature) in &self.trees[1..] {
+            out.extend_from_slice(signature);
+            out.extend_from_slice(&tree.public_key());
+        }
+        let tree = &self.trees[bottom].1;
+        let mut c = [0u8; 32];
+        getrandom::fill(&mut c[..tree.ots.n]).expect("RNG failed");
+        out.extend_from_slice(&tree.sign(leaf(self, bottom), message, &c[..tree.ots.n]));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_table() {
+        // RFC 8554 Table 1 and SP 800-208 Table 2.
+        let expected = [
+            (LMOTS_SHA256_N32_W1, 265, 7),
+            (LMOTS_SHA256_N32_W2, 133, 6),
+            (LMOTS_SHA256_N32_W4, 67, 4),
+            (LMOTS_SHA256_N32_W8, 34, 0),
+            (LMOTS_SHA256_N24_W1, 200, 8),
+            (LMOTS_SHA256_N24_W2, 101, 6),
+            (LMOTS_SHA256_N24_W4, 51, 4),
+            (LMOTS_SHA256_N24_W8, 26, 0),
+        ];
+        for (code, p, ls) in expected {
+            let ots = OtsParams::from_code(code).unwrap();
+            assert_eq!((ots.p, ots.ls), (p, ls));
+        }
+        assert_eq!(LmsParams::from_code(LMS_SHAKE_M24_H25).unwrap().h, 25);
+    }
+
+    #[test]
+    fn test_lms_sign_verify() {
+        let mut key = HssPrivateKey::generate(&[(LMS_SHA256_M32_H5, LMOTS_SHA256_N32_W8)]).unwrap();
+        let public_key = key.public_key();
+        let signature = key.sign_at(Token(()), 7, b"firmware");
+        // RFC 8554 Section 6.4: the one-level HSS signature is Nspk = 0
+        // followed by an LMS signature of 4 + 1124 + 4 + 5 * 32 bytes.
+        assert_eq!(signature.len(), 4 + 1292);
+        assert!(verify_hss(&public_key, b"firmware", &signature));
+        assert!(verify(&public_key[4..], b"firmware", &signature[4..]));
+        assert!(!verify_hss(&public_key, b"firmwarf", &signature));
+    }
+
+    #[test]
+    fn test_rfc8554_test_case_2() {
+        // RFC 8554 Appendix F, Test Case 2. The published second-level I
+        // and SEED are what the top tree derives for its leaf 3, so the
+        // top-level values determine the whole key.
+        let id = hex::decode("d08fabd4a2091ff0a8cb4ed834e74534").unwrap().try_into().unwrap();
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(&hex::decode("558b8966c48ae9cb898b423c83443aae014a72f1b1ab5cc85cf1d892903b5439").unwrap());
+        let levels = [(LMS_SHA256_M32_H10, LMOTS_SHA256_N32_W4), (LMS_SHA256_M32_H5, LMOTS_SHA256_N32_W8)];
+        let mut key = HssPrivateKey::from_parts(&levels, id, seed).unwrap();
+        let public_key = key.public_key();
+        assert_eq!(
+            hex::encode(&public_key),
+            concat!(
+                "00000002",
+                "00000006",
+                "00000003",
+                "d08fabd4a2091ff0a8cb4ed834e74534",
+                "32a58885cd9ba0431235466bff9651c6c92124404d45fa53cf161c28f1ad5a8e"
+            )
+        );
+        let child_key = concat!(
+            "00000005",
+            "00000004",
+            "215f83b7ccb9acbcd08db97b0d04dc2b",
+            "a1cd035833e0e90059603f26e07ad2aad152338e7a5e5984bcd5f7bb4eba40b7"
+        );
+        let (lms, ots) = key.levels[1];
+        let child = key.trees[0].1.child(3, lms, ots);
+        assert_eq!(hex::encode(child.public_key()), child_key);
+        // The published message signature is at leaf 4 with this randomizer.
+        assert_eq!(
+            hex::encode(&child.derive(4, DERIVE_C)[..]),
+            "0eb1ed54a2460d512388cad533138d240534e97b1e82d33bd927d201dfc24ebb"
+        );
+
+        // Signing at that leaf embeds the published child key.
+        let signature = key.sign_at(Token(()), 3 * 32 + 4, b"test case 2");
+        let top_len = 4 + ots_len(LMOTS_SHA256_N32_W4) + 4 + 10 * 32;
+        assert_eq!(&signature[4..8], &3u32.to_be_bytes());
+        assert_eq!(hex::encode(&signature[4 + top_len..4 + top_len + 56]), child_key);
+        assert!(verify_hss(&public_key, b"test case 2", &signature));
+        assert!(!verify_hss(&public_key, b"test case 3", &signature));
+    }
+
+    fn ots_len(code: u32) -> usize {
+        OtsParams::from_code(code).unwrap().signature_len()
+    }
+}
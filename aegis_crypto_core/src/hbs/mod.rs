@@ -0,0 +1,320 @@
+//! Stateful hash-based signatures: LMS/HSS (RFC 8554) and XMSS/XMSS^MT
+//! (RFC 8391), with the parameter sets approved in NIST SP 800-208.
+//!
+//! Each signature consumes a one-time key, and signing two messages with
+//! the same one-time key breaks security. The private state therefore
+//! lives behind [`StatefulSigner`], which writes the advanced index
+//! counter to a [`StateStore`] before any signature is released:
+//!
+//! 1. If the index is not already reserved, reserve it and persist the
+//!    new counter. A persist failure aborts without signing.
+//! 2. Sign with the reserved index.
+//!
+//! A crash between the steps loses reserved indices but never reuses
+//! them. [`StatefulSigner::reserve`] reserves a batch with one write, for
+//! signers where a durable write per signature is too slow. There is no
+//! API to move the counter backwards. Restoring an old backup of the state
+//! would reuse indices, so keep a single live copy of it.
+//!
+//! Verification is stateless: [`lms::verify`], [`lms::verify_hss`],
+//! [`xmss::verify`] and [`xmss::verify_mt`].
+//!
+//! Private key formats are specific to this crate. Public keys and
+//! signatures use the RFC encodings.
+
+pub mod lms;
+pub mod xmss;
+
+use std::fs::{ self, File };
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+
+use sha2::{ Digest, Sha256 };
+use sha3::{ digest::{ ExtendableOutput, Update, XofReader }, Shake256 };
+use zeroize::Zeroizing;
+
+use sealed::Token;
+
+/// Stateful signature error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HbsError {
+    Malformed(String),
+    InvalidParameters(String),
+    /// An LMS, LM-OTS or XMSS type code this crate does not know.
+    UnknownType(u32),
+    /// Every one-time key has been used.
+    Exhausted,
+    /// The state store failed; no signature was produced.
+    State(String),
+}
+
+impl std::fmt::Display for HbsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HbsError::Malformed(msg) => write!(f, "Malformed key or signature: {}", msg),
+            HbsError::InvalidParameters(msg) => write!(f, "Invalid parameters: {}", msg),
+            HbsError::UnknownType(code) => write!(f, "Unknown type code 0x{:08x}", code),
+            HbsError::Exhausted => write!(f, "All one-time keys have been used"),
+            HbsError::State(msg) => write!(f, "State storage failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for HbsError {}
+
+/// The hash function underlying a parameter set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashFamily {
+    Sha256,
+    Shake256,
+}
+
+impl HashFamily {
+    /// Hash the concatenation of `parts`, truncated or extended to `n`
+    /// bytes (`n` is 24 or 32).
+    pub(crate) fn hash(self, n: usize, parts: &[&[u8]]) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        match self {
+            HashFamily::Sha256 => {
+                let mut hasher = Sha256::new();
+                for part in parts {
+                    Digest::update(&mut hasher, part);
+                }
+                out.copy_from_slice(&hasher.finalize());
+                out[n..].fill(0);
+            }
+            HashFamily::Shake256 => {
+                let mut hasher = Shake256::default();
+                for part in parts {
+                    hasher.update(part);
+                }
+                hasher.finalize_xof().read(&mut out[..n]);
+            }
+        }
+        out
+    }
+}
+
+/// Durable storage for a stateful private key.
+pub trait StateStore {
+    /// Replace the stored state. Return only once the new state would
+    /// survive a crash or power loss.
+    fn store(&mut self, state: &[u8]) -> Result<(), HbsError>;
+
+    /// The stored state, if any.
+    fn load(&mut self) -> Result<Option<Zeroizing<Vec<u8>>>, HbsError>;
+}
+
+/// State kept in memory, for tests and short-lived keys.
+#[derive(Default)]
+pub struct MemoryStateStore {
+    state: Option<Zeroizing<Vec<u8>>>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        MemoryStateStore::default()
+    }
+}
+
+impl StateStore for MemoryStateStore {
+    fn store(&mut self, state: &[u8]) -> Result<(), HbsError> {
+        self.state = Some(Zeroizing::new(state.to_vec()));
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Option<Zeroizing<Vec<u8>>>, HbsError> {
+        Ok(self.state.clone())
+    }
+}
+
+/// State kept in a file, replaced atomically: the new state is written to
+/// a temporary file, synced, renamed over the old one and the directory is
+/// synced.
+pub struct FileStateStore {
+    path: PathBuf,
+}
+
+impl FileStateStore {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        FileStateStore { path: path.as_ref().to_path_buf() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn store(&mut self, state: &[u8]) -> Result<(), HbsError> {
+        let io = |e: std::io::Error| HbsError::State(format!("{}: {}", self.path.display(), e));
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        let temporary = PathBuf::from(temporary);
+        {
+            let mut file = File::create(&temporary).map_err(io)?;
+            file.write_all(state).map_err(io)?;
+            file.sync_all().map_err(io)?;
+        }
+        fs::rename(&temporary, &self.path).map_err(io)?;
+        #[cfg(unix)]
+        {
+            let parent = self.path
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            File::open(parent)
+                .and_then(|dir| dir.sync_all())
+                .map_err(io)?;
+        }
+        Ok(())
+    }
+
+    fn load(&mut self) -> Result<Option<Zeroizing<Vec<u8>>>, HbsError> {
+        match fs::read(&self.path) {
+            Ok(state) => Ok(Some(Zeroizing::new(state))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(HbsError::State(format!("{}: {}", self.path.display(), e))),
+        }
+    }
+}
+
+mod sealed {
+    use super::HbsError;
+    use zeroize::Zeroizing;
+
+    /// Only this module can create a token, so the methods below cannot be
+    /// called from outside the crate, even through a `StatefulKey` bound.
+    pub struct Token(pub(in crate::hbs) ());
+
+    /// Operations only [`super::StatefulSigner`] may perform.
+    pub trait KeyState: Sized {
+        fn encode(&self, token: Token, next_index: u64) -> Zeroizing<Vec<u8>>;
+        fn decode(token: Token, state: &[u8]) -> Result<(Self, u64), HbsError>;
+        fn sign_at(&mut self, token: Token, index: u64, message: &[u8]) -> Vec<u8>;
+    }
+}
+
+/// A stateful private key: [`lms::HssPrivateKey`] or
+/// [`xmss::XmssPrivateKey`].
+pub trait StatefulKey: sealed::KeyState {
+    /// Total number of signatures the key can produce.
+    fn capacity(&self) -> u64;
+
+    /// The public key in its RFC encoding.
+    fn public_key(&self) -> Vec<u8>;
+}
+
+/// A private key bound to the store that tracks its index counter.
+pub struct StatefulSigner<K: StatefulKey, S: StateStore> {
+    key: K,
+    store: S,
+    next: u64,
+    /// Indices below this value are persisted as used.
+    reserved: u64,
+}
+
+impl<K: StatefulKey, S: StateStore> StatefulSigner<K, S> {
+    /// Take ownership of a freshly generated key and persist it.
+    pub fn new(key: K, mut store: S) -> Result<Self, HbsError> {
+        store.store(&key.encode(Token(()), 0))?;
+        Ok(StatefulSigner { key, store, next: 0, reserved: 0 })
+    }
+
+    /// Resume from stored state. Indices reserved but not used before the
+    /// state was last written are skipped.
+    pub fn load(mut store: S) -> Result<Self, HbsError> {
+        let state = store.load()?.ok_or_else(|| HbsError::State("no stored key".into()))?;
+        let (key, next) = K::decode(Token(()), &state)?;
+        Ok(StatefulSigner { key, store, next, reserved: next })
+    }
+
+    pub fn public_key(&self) -> Vec<u8> {
+        self.key.public_key()
+    }
+
+    /// Signatures left, including reserved indices.
+    pub fn remaining(&self) -> u64 {
+        self.key.capacity() - self.next
+    }
+
+    /// Index the next signature will use.
+    pub fn next_index(&self) -> u64 {
+        self.next
+    }
+
+    /// Persist `count` indices as used so the following signatures need
+    /// no further writes. Unused reserved indices are lost on restart.
+    pub fn reserve(&mut self, count: u64) -> Result<(), HbsError> {
+        let start = self.reserved.max(self.next);
+        let reserved = start.saturating_add(count).min(self.key.capacity());
+        if reserved > self.reserved {
+            self.store.store(&self.key.encode(Token(()), reserved))?;
+            self.reserved = reserved;
+        }
+        Ok(())
+    }
+
+    /// Sign `message`, persisting the counter first if needed.
+    pub fn sign(&mut self, message: &[u8]) -> Result<Vec<u8>, HbsError> {
+        if self.next >= self.key.capacity() {
+            return Err(HbsError::Exhausted);
+        }
+        if self.next >= self.reserved {
+            self.reserve(1)?;
+        }
+        let index = self.next;
+        self.next += 1;
+        Ok(self.key.sign_at(Token(()), index, message))
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+}
+
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], HbsError> {
+        if self.data.len() < len {
+            return Err(HbsError::Malformed("truncated".into()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    /// The unread bytes, without consuming them.
+    pub(crate) fn rest(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, HbsError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, HbsError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, HbsError> {
+        let b = self.take(8)?;
+        Ok(u64::from_be_bytes(b.try_into().expect("length checked")))
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), HbsError> {
+        if self.data.is_empty() { Ok(()) } else { Err(HbsError::Malformed("trailing data".into())) }
+    }
+}
@@ -0,0 +1,609 @@
+//! XMSS and XMSS^MT (RFC 8391) with the SP 800-208 parameter sets.
+//!
+//! Supported: SHA-256 with n = 32 and n = 24, and SHAKE256 with n = 32
+//! and n = 24, all with Winternitz parameter w = 16. WOTS+ secret keys
+//! are derived with `PRF_keygen` as required by SP 800-208. A signer keeps
+//! the current tree of every layer in memory (`2^(h/d+1)` nodes each) and
+//! rebuilds a layer's tree when the index moves past it.
+
+use zeroize::Zeroizing;
+
+use super::sealed::{ KeyState, Token };
+use super::{ u32_at, HashFamily, HbsError, Reader, StatefulKey };
+
+pub const XMSS_SHA2_10_256: u32 = 0x01;
+pub const XMSS_SHA2_16_256: u32 = 0x02;
+pub const XMSS_SHA2_20_256: u32 = 0x03;
+pub const XMSS_SHA2_10_192: u32 = 0x0d;
+pub const XMSS_SHA2_16_192: u32 = 0x0e;
+pub const XMSS_SHA2_20_192: u32 = 0x0f;
+pub const XMSS_SHAKE256_10_256: u32 = 0x10;
+pub const XMSS_SHAKE256_16_256: u32 = 0x11;
+pub const XMSS_SHAKE256_20_256: u32 = 0x12;
+pub const XMSS_SHAKE256_10_192: u32 = 0x13;
+pub const XMSS_SHAKE256_16_192: u32 = 0x14;
+pub const XMSS_SHAKE256_20_192: u32 = 0x15;
+
+pub const XMSSMT_SHA2_20_2_256: u32 = 0x01;
+pub const XMSSMT_SHA2_20_4_256: u32 = 0x02;
+pub const XMSSMT_SHA2_40_2_256: u32 = 0x03;
+pub const XMSSMT_SHA2_40_4_256: u32 = 0x04;
+pub const XMSSMT_SHA2_40_8_256: u32 = 0x05;
+pub const XMSSMT_SHA2_60_3_256: u32 = 0x06;
+pub const XMSSMT_SHA2_60_6_256: u32 = 0x07;
+pub const XMSSMT_SHA2_60_12_256: u32 = 0x08;
+pub const XMSSMT_SHA2_20_2_192: u32 = 0x21;
+pub const XMSSMT_SHA2_20_4_192: u32 = 0x22;
+pub const XMSSMT_SHA2_40_2_192: u32 = 0x23;
+pub const XMSSMT_SHA2_40_4_192: u32 = 0x24;
+pub const XMSSMT_SHA2_40_8_192: u32 = 0x25;
+pub const XMSSMT_SHA2_60_3_192: u32 = 0x26;
+pub const XMSSMT_SHA2_60_6_192: u32 = 0x27;
+pub const XMSSMT_SHA2_60_12_192: u32 = 0x28;
+pub const XMSSMT_SHAKE256_20_2_256: u32 = 0x29;
+pub const XMSSMT_SHAKE256_20_4_256: u32 = 0x2a;
+pub const XMSSMT_SHAKE256_40_2_256: u32 = 0x2b;
+pub const XMSSMT_SHAKE256_40_4_256: u32 = 0x2c;
+pub const XMSSMT_SHAKE256_40_8_256: u32 = 0x2d;
+pub const XMSSMT_SHAKE256_60_3_256: u32 = 0x2e;
+pub const XMSSMT_SHAKE256_60_6_256: u32 = 0x2f;
+pub const XMSSMT_SHAKE256_60_12_256: u32 = 0x30;
+pub const XMSSMT_SHAKE256_20_2_192: u32 = 0x31;
+pub const XMSSMT_SHAKE256_20_4_192: u32 = 0x32;
+pub const XMSSMT_SHAKE256_40_2_192: u32 = 0x33;
+pub const XMSSMT_SHAKE256_40_4_192: u32 = 0x34;
+pub const XMSSMT_SHAKE256_40_8_192: u32 = 0x35;
+pub const XMSSMT_SHAKE256_60_3_192: u32 = 0x36;
+pub const XMSSMT_SHAKE256_60_6_192: u32 = 0x37;
+pub const XMSSMT_SHAKE256_60_12_192: u32 = 0x38;
+
+const W: usize = 16;
+const LEN2: usize = 3;
+
+const MAGIC: &[u8; 4] = b"AXMS";
+const VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Params {
+    oid: u32,
+    multi_tree: bool,
+    family: HashFamily,
+    n: usize,
+    /// Total tree height.
+    h: usize,
+    /// Number of layers.
+    d: usize,
+}
+
+impl Params {
+    fn xmss(oid: u32) -> Result<Self, HbsError> {
+        let (family, n, base) = match oid {
+            0x01..=0x03 => (HashFamily::Sha256, 32, 0x01),
+            0x0d..=0x0f => (HashFamily::Sha256, 24, 0x0d),
+            0x10..=0x12 => (HashFamily::Shake256, 32, 0x10),
+            0x13..=0x15 => (HashFamily::Shake256, 24, 0x13),
+            _ => {
+                return Err(HbsError::UnknownType(oid));
+            }
+        };
+        let h = [10, 16, 20][(oid - base) as usize];
+        Ok(Params { oid, multi_tree: false, family, n, h, d: 1 })
+    }
+
+    fn xmss_mt(oid: u32) -> Result<Self, HbsError> {
+        let (family, n, base) = match oid {
+            0x01..=0x08 => (HashFamily::Sha256, 32, 0x01),
+            0x21..=0x28 => (HashFamily::Sha256, 24, 0x21),
+            0x29..=0x30 => (HashFamily::Shake256, 32, 0x29),
+            0x31..=0x38 => (HashFamily::Shake256, 24, 0x31),
+            _ => {
+                return Err(HbsError::UnknownType(oid));
+            }
+        };
+        let (h, d) = [(20, 2), (20, 4), (40, 2), (40, 4), (40, 8), (60, 3), (60, 6), (60, 12)][
+            (oid - base) as usize
+        ];
+        Ok(Params { oid, multi_tree: true, family, n, h, d })
+    }
+
+    fn tree_height(&self) -> usize {
+        self.h / self.d
+    }
+
+    fn len1(&self) -> usize {
+        2 * self.n
+    }
+
+    fn len(&self) -> usize {
+        self.len1() + LEN2
+    }
+
+    fn index_len(&self) -> usize {
+        if self.multi_tree { self.h.div_ceil(8) } else { 4 }
+    }
+
+    fn public_key_len(&self) -> usize {
+        4 + 2 * self.n
+    }
+
+    fn signature_len(&self) -> usize {
+        self.index_len() + self.n + self.d * (self.len() + self.tree_height()) * self.n
+    }
+
+    /// `toByte(prefix, pad) ‖ key ‖ data`, where SP 800-208 shortens the
+    /// prefix to four bytes for the n = 24 sets.
+    fn keyed(&self, prefix: u8, key: &[u8], data: &[&[u8]]) -> [u8; 32] {
+        let pad = if self.n == 32 { 32 } else { 4 };
+        let mut encoded = [0u8; 32];
+        encoded[pad - 1] = prefix;
+        let mut parts = vec![&encoded[..pad], key];
+        parts.extend_from_slice(data);
+        self.family.hash(self.n, &parts)
+    }
+
+    fn prf(&self, seed: &[u8], adrs: &Adrs) -> [u8; 32] {
+        self.keyed(3, seed, &[&adrs.to_bytes()])
+    }
+
+    fn prf_keygen(&self, sk_seed: &[u8], pub_seed: &[u8], adrs: &Adrs) -> Zeroizing<[u8; 32]> {
+        Zeroizing::new(self.keyed(4, sk_seed, &[pub_seed, &adrs.to_bytes()]))
+    }
+
+    fn hash_message(&self, r: &[u8], root: &[u8], index: u64, message: &[u8]) -> [u8; 32] {
+        let index = to_byte(index, self.n);
+        self.keyed(2, &[r, root, &index].concat(), &[message])
+    }
+
+    fn chain(&self, x: &[u8], start: usize, steps: usize, pub_seed: &[u8], adrs: &mut Adrs) -> [u8; 32] {
+        let n = self.n;
+        let mut tmp = [0u8; 32];
+        tmp[..n].copy_from_slice(&x[..n]);
+        for j in start..start + steps {
+            adrs.set_hash(j as u32);
+            adrs.set_key_and_mask(0);
+            let key = self.prf(pub_seed, adrs);
+            adrs.set_key_and_mask(1);
+            let mask = self.prf(pub_seed, adrs);
+            for (t, m) in tmp[..n].iter_mut().zip(&mask[..n]) {
+                *t ^= m;
+            }
+            tmp = self.keyed(0, &key[..n], &[&tmp[..n]]);
+        }
+        tmp
+    }
+
+    /// Chain lengths signed for an n-byte message digest (RFC 8391
+    /// Algorithm 5).
+    fn digits(&self, digest: &[u8]) -> Vec<usize> {
+        let mut digits: Vec<usize> = digest[..self.n]
+            .iter()
+            .flat_map(|b| [(b >> 4) as usize, (b & 0x0f) as usize])
+            .collect();
+        let checksum = digits.iter().map(|d| W - 1 - d).sum::<usize>() << 4;
+        digits.extend((0..LEN2).map(|i| (checksum >> (12 - 4 * i)) & 0x0f));
+        digits
+    }
+
+    fn wots_secret(&self, keys: &SecretSeeds, pub_seed: &[u8], adrs: &mut Adrs, i: usize) -> Zeroizing<[u8; 32]> {
+        adrs.set_chain(i as u32);
+        adrs.set_hash(0);
+        adrs.set_key_and_mask(0);
+        self.prf_keygen(&keys.sk_seed[..self.n], pub_seed, adrs)
+    }
+
+    fn wots_public(&self, keys: &SecretSeeds, pub_seed: &[u8], mut adrs: Adrs) -> Vec<[u8; 32]> {
+        (0..self.len())
+            .map(|i| {
+                let sk = self.wots_secret(keys, pub_seed, &mut adrs, i);
+                self.chain(&sk[..], 0, W - 1, pub_seed, &mut adrs)
+            })
+            .collect()
+    }
+
+    fn wots_sign(&self, keys: &SecretSeeds, pub_seed: &[u8], mut adrs: Adrs, digest: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.len() * self.n);
+        for (i, digit) in self.digits(digest).into_iter().enumerate() {
+            let sk = self.wots_secret(keys, pub_seed, &mut adrs, i);
+            out.extend_from_slice(&self.chain(&sk[..], 0, digit, pub_seed, &mut adrs)[..self.n]);
+        }
+        out
+    }
+
+    fn wots_public_from_signature(&self, signature: &[u8], digest: &[u8], pub_seed: &[u8], mut adrs: Adrs) -> Vec<[u8; 32]> {
+        self.digits(digest)
+            .into_iter()
+            .enumerate()
+            .map(|(i, digit)| {
+                adrs.set_chain(i as u32);
+                let y = &signature[i * self.n..(i + 1) * self.n];
+                self.chain(y, digit, W - 1 - digit, pub_seed, &mut adrs)
+            })
+            .collect()
+    }
+
+    fn rand_hash(&self, left: &[u8], right: &[u8], pub_seed: &[u8], adrs: &mut Adrs) -> [u8; 32] {
+        let n = self.n;
+        adrs.set_key_and_mask(0);
+        let key = self.prf(pub_seed, adrs);
+        adrs.set_key_and_mask(1);
+        let mask_left = self.prf(pub_seed, adrs);
+        adrs.set_key_and_mask(2);
+        let mask_right = self.prf(pub_seed, adrs);
+        let mut data = [0u8; 64];
+        for i in 0..n {
+            data[i] = left[i] ^ mask_left[i];
+            data[n + i] = right[i] ^ mask_right[i];
+        }
+        self.keyed(1, &key[..n], &[&data[..2 * n]])
+    }
+
+    fn l_tree(&self, mut nodes: Vec<[u8; 32]>, pub_seed: &[u8], adrs: &mut Adrs) -> [u8; 32] {
+        let mut height = 0;
+        while nodes.len() > 1 {
+            adrs.set_tree_height(height);
+            let mut next = Vec::with_capacity(nodes.len().div_ceil(2));
+            for (i, pair) in nodes.chunks(2).enumerate() {
+                if let [left, right] = pair {
+                    adrs.set_tree_index(i as u32);
+                    next.push(self.rand_hash(left, right, pub_seed, adrs));
+                } else {
+                    next.push(pair[0]);
+                }
+            }
+            nodes = next;
+            height += 1;
+        }
+        nodes[0]
+    }
+
+    fn leaf(&self, wots_public: Vec<[u8; 32]>, pub_seed: &[u8], layer: u32, tree: u64, index: u32) -> [u8; 32] {
+        let mut adrs = Adrs::new(layer, tree, Adrs::L_TREE);
+        adrs.set_word(4, index);
+        self.l_tree(wots_public, pub_seed, &mut adrs)
+    }
+
+    fn parent(&self, left: &[u8], right: &[u8], pub_seed: &[u8], mut adrs: Adrs) -> [u8; 32] {
+        self.rand_hash(left, right, pub_seed, &mut adrs)
+    }
+}
+
+fn to_byte(value: u64, len: usize) -> Vec<u8> {
+    let mut out = vec![0u8; len];
+    let bytes = value.to_be_bytes();
+    let take = len.min(8);
+    out[len - take..].copy_from_slice(&bytes[8 - take..]);
+    out
+}
+
+/// An RFC 8391 hash address.
+#[derive(Clone, Copy)]
+struct Adrs([u32; 8]);
+
+impl Adrs {
+    const OTS: u32 = 0;
+    const L_TREE: u32 = 1;
+    const HASH_TREE: u32 = 2;
+
+    fn new(layer: u32, tree: u64, kind: u32) -> Self {
+        Adrs([layer, (tree >> 32) as u32, tree as u32, kind, 0, 0, 0, 0])
+    }
+
+    fn ots(layer: u32, tree: u64, index: u32) -> Self {
+        let mut adrs = Adrs::new(layer, tree, Adrs::OTS);
+        adrs.set_word(4, index);
+        adrs
+    }
+
+    /// Address of the node at `(height + 1, index)` of a hash tree.
+    fn node(layer: u32, tree: u64, height: usize, index: usize) -> Self {
+        let mut adrs = Adrs::new(layer, tree, Adrs::HASH_TREE);
+        adrs.set_tree_height(height as u32);
+        adrs.set_tree_index(index as u32);
+        adrs
+    }
+
+    fn set_word(&mut self, word: usize, value: u32) {
+        self.0[word] = value;
+    }
+
+    fn set_chain(&mut self, chain: u32) {
+        self.0[5] = chain;
+    }
+
+    fn set_hash(&mut self, hash: u32) {
+        self.0[6] = hash;
+    }
+
+    fn set_tree_height(&mut self, height: u32) {
+        self.0[5] = height;
+    }
+
+    fn set_tree_index(&mut self, index: u32) {
+        self.0[6] = index;
+    }
+
+    fn set_key_and_mask(&mut self, value: u32) {
+        self.0[7] = value;
+    }
+
+    fn to_bytes(self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (chunk, word) in out.chunks_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+}
+
+struct SecretSeeds {
+    sk_seed: Zeroizing<[u8; 32]>,
+    sk_prf: Zeroizing<[u8; 32]>,
+}
+
+/// The nodes of one XMSS tree, `levels[height][index]`.
+struct CachedTree {
+    tree: u64,
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl CachedTree {
+    fn root(&self) -> &[u8; 32] {
+        &self.levels[self.levels.len() - 1][0]
+    }
+}
+
+/// Root of the tree a signature implies, from a WOTS+ signature over
+/// `digest` and its authentication path.
+fn root_from_signature(
+    params: &Params,
+    part: &[u8],
+    digest: &[u8],
+    pub_seed: &[u8],
+    layer: u32,
+    tree: u64,
+    leaf: u32
+) -> [u8; 32] {
+    let (wots, auth) = part.split_at(params.len() * params.n);
+    let wots_public = params.wots_public_from_signature(wots, digest, pub_seed, Adrs::ots(layer, tree, leaf));
+    let mut node = params.leaf(wots_public, pub_seed, layer, tree, leaf);
+    let mut index = leaf as usize;
+    for (height, sibling) in auth.chunks(params.n).enumerate() {
+        let adrs = Adrs::node(layer, tree, height, index / 2);
+        node = if index.is_multiple_of(2) {
+            params.parent(&node, sibling, pub_seed, adrs)
+        } else {
+            params.parent(sibling, &node, pub_seed, adrs)
+        };
+        index /= 2;
+    }
+    node
+}
+
+fn verify_with(params: Params, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    if public_key.len() != params.public_key_len() || signature.len() != params.signature_len() {
+        return false;
+    }
+    let n = params.n;
+    let (root, pub_seed) = public_key[4..].split_at(n);
+    let (index_bytes, rest) = signature.split_at(params.index_len());
+    let (r, mut rest) = rest.split_at(n);
+    let index = index_bytes.iter().fold(0u64, |acc, &b| (acc << 8) | (b as u64));
+    if params.h < 64 && index >> params.h != 0 {
+        return false;
+    }
+
+    let height = params.tree_height();
+    let part_len = (params.len() + height) * n;
+    let mut node = params.hash_message(r, root, index, message);
+    let mut tree = index;
+    for layer in 0..params.d {
+        let leaf = (tree & ((1 << height) - 1)) as u32;
+        tree >>= height;
+        let (part, tail) = rest.split_at(part_len);
+        node = root_from_signature(&params, part, &node[..n], pub_seed, layer as u32, tree, leaf);
+        rest = tail;
+    }
+    bool::from(subtle::ConstantTimeEq::ct_eq(&node[..n], root))
+}
+
+/// Verify an XMSS signature.
+pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    match u32_at(public_key, 0).map(Params::xmss) {
+        Some(Ok(params)) => verify_with(params, public_key, message, signature),
+        _ => false,
+    }
+}
+
+/// Verify an XMSS^MT signature.
+pub fn verify_mt(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    match u32_at(public_key, 0).map(Params::xmss_mt) {
+        Some(Ok(params)) => verify_with(params, public_key, message, signature),
+        _ => false,
+    }
+}
+
+/// An XMSS or XMSS^MT private key. Use it through
+/// [`super::StatefulSigner`].
+pub struct XmssPrivateKey {
+    params: Params,
+    seeds: SecretSeeds,
+    pub_seed: [u8; 32],
+    root: [u8; 32],
+    /// Current tree of each layer, bottom layer first.
+    trees: Vec<Option<CachedTree>>,
+}
+
+impl XmssPrivateKey {
+    /// Generate an XMSS key for one of the `XMSS_*` identifiers.
+    pub fn generate(oid: u32) -> Result<Self, HbsError> {
+        XmssPrivateKey::generate_with(Params::xmss(oid)?)
+    }
+
+    /// Generate an XMSS^MT key for one of the `XMSSMT_*` identifiers.
+    pub fn generate_mt(oid: u32) -> Result<Self, HbsError> {
+        XmssPrivateKey::generate_with(Params::xmss_mt(oid)?)
+    }
+
+    fn generate_with(params: Params) -> Result<Self, HbsError> {
+        let mut seeds = SecretSeeds {
+            sk_seed: Zeroizing::new([0u8; 32]),
+            sk_prf: Zeroizing::new([0u8; 32]),
+        };
+        let mut pub_seed = [0u8; 32];
+        getrandom::fill(&mut seeds.sk_seed[..params.n]).expect("RNG failed");
+        getrandom::fill(&mut seeds.sk_prf[..params.n]).expect("RNG failed");
+        getrandom::fill(&mut pub_seed[..params.n]).expect("RNG failed");
+        let mut key = XmssPrivateKey {
+            params,
+            seeds,
+            pub_seed,
+            root: [0u8; 32],
+            trees: (0..params.d).map(|_| None).collect(),
+        };
+        let top = params.d - 1;
+        key.root = *key.tree(top, 0).root();
+        Ok(key)
+    }
+
+    /// The tree at `(layer, tree)`, building it if it is not cached.
+    fn tree(&mut self, layer: usize, tree: u64) -> &CachedTree {
+        if self.trees[layer].as_ref().is_none_or(|cached| cached.tree != tree) {
+            let params = self.params;
+            let pub_seed = &self.pub_seed[..params.n];
+            let leaves = (0..1u32 << params.tree_height())
+                .map(|i| {
+                    let wots = params.wots_public(&self.seeds, pub_seed, Adrs::ots(layer as u32, tree, i));
+                    params.leaf(wots, pub_seed, layer as u32, tree, i)
+                })
+                .collect::<Vec<_>>();
+            let mut levels = vec![leaves];
+            for height in 0..params.tree_height() {
+                let next = levels[height]
+                    .chunks(2)
+                    .enumerate()
+                    .map(|(i, pair)| {
+                        let adrs = Adrs::node(layer as u32, tree, height, i);
+                        params.parent(&pair[0], &pair[1], pub_seed, adrs)
+                    })
+                    .collect();
+                levels.push(next);
+            }
+            self.trees[layer] = Some(CachedTree { tree, levels });
+        }
+        self.trees[layer].as_ref().expect("tree cached")
+    }
+}
+
+impl StatefulKey for XmssPrivateKey {
+    fn capacity(&self) -> u64 {
+        1u64 << self.params.h
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        let n = self.params.n;
+        let mut out = self.params.oid.to_be_bytes().to_vec();
+        out.extend_from_slice(&self.root[..n]);
+        out.extend_from_slice(&self.pub_seed[..n]);
+        out
+    }
+}
+
+impl KeyState for XmssPrivateKey {
+    fn encode(&self, _: Token, next_index: u64) -> Zeroizing<Vec<u8>> {
+        let n = self.params.n;
+        let mut out = Zeroizing::new(Vec::new());
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(self.params.multi_tree as u8);
+        out.extend_from_slice(&self.params.oid.to_be_bytes());
+        out.extend_from_slice(&self.seeds.sk_seed[..n]);
+        out.extend_from_slice(&self.seeds.sk_prf[..n]);
+        out.extend_from_slice(&self.pub_seed[..n]);
+        out.extend_from_slice(&self.root[..n]);
+        out.extend_from_slice(&next_index.to_be_bytes());
+        out
+    }
+
+    fn decode(_: Token, state: &[u8]) -> Result<(Self, u64), HbsError> {
+        let mut reader = Reader::new(state);
+        if reader.take(4)? != MAGIC || reader.u8()? != VERSION {
+            return Err(HbsError::Malformed("not an XMSS private key".into()));
+        }
+        let params = match reader.u8()? {
+            0 => Params::xmss(reader.u32()?)?,
+            1 => Params::xmss_mt(reader.u32()?)?,
+            _ => {
+                return Err(HbsError::Malformed("bad key kind".into()));
+            }
+        };
+        let n = params.n;
+        let mut seeds = SecretSeeds {
+            sk_seed: Zeroizing::new([0u8; 32]),
+            sk_prf: Zeroizing::new([0u8; 32]),
+        };
+        let mut pub_seed = [0u8; 32];
+        let mut root = [0u8; 32];
+        seeds.sk_seed[..n].copy_from_slice(reader.take(n)?);
+        seeds.sk_prf[..n].copy_from_slice(reader.take(n)?);
+        pub_seed[..n].copy_from_slice(reader.take(n)?);
+        root[..n].copy_from_slice(reader.take(n)?);
+        let next = reader.u64()?;
+        reader.finish()?;
+        if next > 1u64 << params.h {
+            return Err(HbsError::Malformed("index beyond capacity".into()));
+        }
+        let trees = (0..params.d).map(|_| None).collect();
+        Ok((XmssPrivateKey { params, seeds, pub_seed, root, trees }, next))
+    }
+
+    fn sign_at(&mut self, _: Token, index: u64, message: &[u8]) -> Vec<u8> {
+        let params = self.params;
+        let n = params.n;
+        let height = params.tree_height();
+        let mut r = Zeroizing::new([0u8; 32]);
+        *r = params.keyed(3, &self.seeds.sk_prf[..n], &[&to_byte(index, 32)]);
+        let mut out = to_byte(index, params.index_len());
+        out.extend_from_slice(&r[..n]);
+
+        let mut digest = params.hash_message(&r[..n], &self.root[..n], index, message);
+        let mut tree = index;
+        for layer in 0..params.d {
+            let leaf = (tree & ((1 << height) - 1)) as usize;
+            tree >>= height;
+            let adrs = Adrs::ots(layer as u32, tree, leaf as u32);
+            out.extend_from_slice(&params.wots_sign(&self.seeds, &self.pub_seed[..n], adrs, &digest[..n]));
+            let cached = self.tree(layer, tree);
+            for h in 0..height {
+                out.extend_from_slice(&cached.levels[h][(leaf >> h) ^ 1][..n]);
+            }
+            digest = *cached.root();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_sizes() {
+        // RFC 8391 Sections 5.3 and 5.4.
+        assert_eq!(Params::xmss(XMSS_SHA2_10_256).unwrap().signature_len(), 2500);
+        assert_eq!(Params::xmss(XMSS_SHA2_20_256).unwrap().signature_len(), 2820);
+        assert_eq!(Params::xmss_mt(XMSSMT_SHA2_20_2_256).unwrap().signature_len(), 4963);
+        assert_eq!(Params::xmss_mt(XMSSMT_SHA2_60_12_256).unwrap().signature_len(), 27688);
+        assert_eq!(Params::xmss_mt(XMSSMT_SHA2_40_8_192).unwrap().len(), 51);
+        assert!(Params::xmss(0x04).is_err());
+    }
+
+    #[test]
+    fn test_xmss_mt_sign_verify() {
+        let mut key = XmssPrivateKey::generate_mt(XMSSMT_SHAKE256_20_4_192).unwrap();
+        let public_key = key.public_key();
+        let signature = key.sign_at(Token(()), 0x2f, b"release manifest");
+        assert!(verify_mt(&public_key, b"release manifest", &signature));
+        assert!(!verify_mt(&public_key, b"release manifesto", &signature));
+        assert!(!verify(&public_key, b"release manifest", &signature));
+    }
+}
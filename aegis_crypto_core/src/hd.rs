@@ -0,0 +1,291 @@
+//! Hierarchical deterministic post-quantum keys from a BIP-39 mnemonic.
+//!
+//! A [`Mnemonic`] encodes 128 to 256 bits of entropy as 12 to 24 English
+//! words and stretches them, with an optional passphrase, into a 64-byte
+//! seed. [`ExtendedKey::master`] turns the seed into a root node and
+//! [`ExtendedKey::derive`] walks paths such as `m/44'/0'/0'/0'`, in the
+//! manner of SLIP-10:
+//!
+//! ```text
+//! master  = HMAC-SHA512("aegis-hd-v1", seed)                = key || chain_code
+//! child i = HMAC-SHA512(chain_code, 0x00 || key || i:u32be) = key || chain_code
+//! ```
+//!
+//! Only hardened indices exist. Post-quantum public keys have no algebraic
+//! structure that would let a public parent derive public children, so
+//! unhardened derivation would offer nothing and is rejected.
+//!
+//! A node's key seeds any algorithm with deterministic key generation (see
+//! [`registry::keygen_from_seed`]):
+//!
+//! ```text
+//! seed = SHAKE256("aegis-hd-v1 keygen" || algorithm:u16be || key)
+//! ```
+//!
+//! The algorithm code separates the seeds, so one node can hold unrelated
+//! ML-DSA, ML-KEM and Falcon keys. Derivation is frozen: the same mnemonic,
+//! passphrase, path and algorithm always restore the same key pair.
+
+use std::fmt;
+use std::str::FromStr;
+
+use hmac::{ Hmac, Mac };
+use sha2::Sha512;
+use sha3::digest::{ ExtendableOutput, Update, XofReader };
+use sha3::Shake256;
+use zeroize::Zeroizing;
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId };
+
+/// Set on an index to mark it hardened.
+pub const HARDENED: u32 = 1 << 31;
+
+const MASTER_KEY: &[u8] = b"aegis-hd-v1";
+const KEYGEN_DOMAIN: &[u8] = b"aegis-hd-v1 keygen";
+
+/// HD wallet error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HdError {
+    /// The phrase is not a valid BIP-39 mnemonic.
+    InvalidMnemonic(String),
+    /// Mnemonics have 12, 15, 18, 21 or 24 words.
+    InvalidWordCount(usize),
+    /// Master seeds are 16 to 64 bytes long.
+    InvalidSeedLength(usize),
+    InvalidPath(String),
+    /// The index is not hardened.
+    NotHardened(u32),
+    Algorithm(AlgorithmError),
+}
+
+impl fmt::Display for HdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HdError::InvalidMnemonic(msg) => write!(f, "Invalid mnemonic: {}", msg),
+            HdError::InvalidWordCount(n) => write!(f, "Invalid mnemonic word count {}", n),
+            HdError::InvalidSeedLength(n) => write!(f, "Invalid seed length {}", n),
+            HdError::InvalidPath(msg) => write!(f, "Invalid derivation path: {}", msg),
+            HdError::NotHardened(index) => write!(f, "Index {} is not hardened", index),
+            HdError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for HdError {}
+
+impl From<AlgorithmError> for HdError {
+    fn from(e: AlgorithmError) -> Self {
+        HdError::Algorithm(e)
+    }
+}
+
+impl From<bip39::Error> for HdError {
+    fn from(e: bip39::Error) -> Self {
+        HdError::InvalidMnemonic(e.to_string())
+    }
+}
+
+/// A BIP-39 mnemonic over the English word list. Cleared on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Mnemonic(bip39::Mnemonic);
+
+impl Mnemonic {
+    /// A random mnemonic of `word_count` words.
+    pub fn generate(word_count: usize) -> Result<Self, HdError> {
+        if !(12..=24).contains(&word_count) || !word_count.is_multiple_of(3) {
+            return Err(HdError::InvalidWordCount(word_count));
+        }
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        let len = word_count * 4 / 3;
+        getrandom::fill(&mut entropy[..len]).expect("RNG failed");
+        Self::from_entropy(&entropy[..len])
+    }
+
+    /// The mnemonic encoding 16, 20, 24, 28 or 32 bytes of entropy.
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, HdError> {
+        Ok(Mnemonic(bip39::Mnemonic::from_entropy(entropy)?))
+    }
+
+    /// Parse a phrase, checking every word and the checksum. Case and
+    /// extra whitespace are ignored.
+    pub fn parse(phrase: &str) -> Result<Self, HdError> {
+        let normalized = Zeroizing::new(phrase.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase());
+        Ok(Mnemonic(bip39::Mnemonic::parse_normalized(&normalized)?))
+    }
+
+    /// The words, separated by single spaces.
+    pub fn phrase(&self) -> Zeroizing<String> {
+        Zeroizing::new(self.0.to_string())
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.0.word_count()
+    }
+
+    /// The BIP-39 seed for `passphrase`, which may be empty.
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; 64]> {
+        Zeroizing::new(self.0.to_seed(passphrase))
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mnemonic({} words)", self.word_count())
+    }
+}
+
+/// A path of hardened indices, written `m/44'/0'/0'/0'`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The path through `indices`, each below [`HARDENED`], hardened.
+    pub fn new(indices: &[u32]) -> Result<Self, HdError> {
+        indices
+            .iter()
+            .map(|&i| if i < HARDENED { Ok(i | HARDENED) } else { Err(HdError::InvalidPath(format!("index {} too large", i))) })
+            .collect::<Result<_, _>>()
+            .map(DerivationPath)
+    }
+
+    /// `m/44'/coin_type'/account'/index'`.
+    pub fn bip44(coin_type: u32, account: u32, index: u32) -> Result<Self, HdError> {
+        Self::new(&[44, coin_type, account, index])
+    }
+
+    /// The indices with [`HARDENED`] set.
+    pub fn indices(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdError;
+
+    /// Parse `m/a'/b'/...`. `h` may stand for `'`.
+    fn from_str(s: &str) -> Result<Self, HdError> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(HdError::InvalidPath(format!("{} does not start at m", s)));
+        }
+        let indices = parts
+            .map(|part| {
+                let digits = part.strip_suffix(['\'', 'h']);
+                let index: u32 = digits
+                    .unwrap_or(part)
+                    .parse()
+                    .map_err(|_| HdError::InvalidPath(format!("bad index {}", part)))?;
+                match digits {
+                    Some(_) => Ok(index),
+                    None => Err(HdError::NotHardened(index)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(&indices)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.0 {
+            write!(f, "/{}'", index & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+/// A node of the derivation tree: a key and a chain code.
+#[derive(Clone)]
+pub struct ExtendedKey {
+    key: Zeroizing<[u8; 32]>,
+    chain_code: Zeroizing<[u8; 32]>,
+    path: DerivationPath,
+}
+
+impl ExtendedKey {
+    fn from_hmac(key: &[u8], data: &[&[u8]], path: DerivationPath) -> Self {
+        let mut mac = <Hmac<Sha512> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+        for part in data {
+            Mac::update(&mut mac, part);
+        }
+        let output = Zeroizing::new(<[u8; 64]>::from(mac.finalize().into_bytes()));
+        let mut node = ExtendedKey { key: Zeroizing::new([0u8; 32]), chain_code: Zeroizing::new([0u8; 32]), path };
+        node.key.copy_from_slice(&output[..32]);
+        node.chain_code.copy_from_slice(&output[32..]);
+        node
+    }
+
+    /// The root node for a 16- to 64-byte seed.
+    pub fn master(seed: &[u8]) -> Result<Self, HdError> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(HdError::InvalidSeedLength(seed.len()));
+        }
+        Ok(Self::from_hmac(MASTER_KEY, &[seed], DerivationPath::default()))
+    }
+
+    /// The root node for a mnemonic and passphrase.
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str) -> Self {
+        Self::master(&mnemonic.to_seed(passphrase)[..]).expect("BIP-39 seeds are 64 bytes")
+    }
+
+    /// The child at a hardened `index`.
+    pub fn child(&self, index: u32) -> Result<Self, HdError> {
+        if index & HARDENED == 0 {
+            return Err(HdError::NotHardened(index));
+        }
+        let mut path = self.path.clone();
+        path.0.push(index);
+        Ok(Self::from_hmac(&self.chain_code[..], &[&[0], &self.key[..], &index.to_be_bytes()], path))
+    }
+
+    /// The descendant along `path`, taken relative to this node.
+    pub fn derive(&self, path: &DerivationPath) -> Result<Self, HdError> {
+        path.0.iter().try_fold(self.clone(), |node, &index| node.child(index))
+    }
+
+    /// Path of this node from the root.
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// The key generation seed for `algorithm` at this node.
+    pub fn algorithm_seed(&self, algorithm: AlgorithmId) -> Result<Zeroizing<Vec<u8>>, HdError> {
+        let len = algorithm.seed_len().ok_or(AlgorithmError::Unsupported(algorithm))?;
+        let mut shake = Shake256::default();
+        shake.update(KEYGEN_DOMAIN);
+        shake.update(&algorithm.code().to_be_bytes());
+        shake.update(&self.key[..]);
+        let mut seed = Zeroizing::new(vec![0u8; len]);
+        shake.finalize_xof().read(&mut seed);
+        Ok(seed)
+    }
+
+    /// The `(public_key, secret_key)` pair for `algorithm` at this node.
+    pub fn keypair(&self, algorithm: AlgorithmId) -> Result<(Vec<u8>, Vec<u8>), HdError> {
+        Ok(registry::keygen_from_seed(algorithm, &self.algorithm_seed(algorithm)?)?)
+    }
+}
+
+impl fmt::Debug for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ExtendedKey({})", self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_parsing() {
+        let path: DerivationPath = "m/44'/7h/0'/2147483647'".parse().unwrap();
+        assert_eq!(path.indices(), &[44 | HARDENED, 7 | HARDENED, HARDENED, u32::MAX]);
+        assert_eq!(path.to_string(), "m/44'/7'/0'/2147483647'");
+        assert_eq!("m".parse::<DerivationPath>().unwrap(), DerivationPath::default());
+        assert_eq!("m/44'/0".parse::<DerivationPath>(), Err(HdError::NotHardened(0)));
+        assert!("m/2147483648'".parse::<DerivationPath>().is_err());
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/x'".parse::<DerivationPath>().is_err());
+    }
+}
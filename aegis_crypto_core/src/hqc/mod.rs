@@ -38,6 +38,16 @@ pub struct HqcKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    HqcKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_hqc::hqc128,
+        pqcrypto_hqc::hqc192,
+        pqcrypto_hqc::hqc256
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl HqcKeyPair {
     /// Returns the public key component of the HQC key pair.
@@ -60,6 +70,16 @@ pub struct HqcEncapsulated {
     shared_secret: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    HqcEncapsulated { ciphertext as ciphertext, shared_secret as shared_secret },
+    crate::serialization::encapsulated_lengths!(
+        pqcrypto_hqc::hqc128,
+        pqcrypto_hqc::hqc192,
+        pqcrypto_hqc::hqc256
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl HqcEncapsulated {
     /// Returns the ciphertext generated during encapsulation.
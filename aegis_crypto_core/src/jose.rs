@@ -0,0 +1,190 @@
+//! JSON Web Signatures (RFC 7515) with post-quantum algorithms.
+//!
+//! Only the compact serialization is supported. The `alg` header value is
+//! the registry parameter-set name, e.g. `ML-DSA-65` or
+//! `SLH-DSA-SHA2-128f`, matching the names used by the IETF COSE/JOSE
+//! drafts for ML-DSA. Tokens with a `crit` header or an unencoded payload
+//! (`b64: false`) are rejected.
+
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine as _ };
+use serde_json::{ Map, Value };
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// JOSE error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JoseError {
+    Malformed(String),
+    UnsupportedAlgorithm(String),
+    AlgorithmMismatch {
+        expected: AlgorithmId,
+        actual: AlgorithmId,
+    },
+    BadSignature,
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for JoseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JoseError::Malformed(msg) => write!(f, "Malformed JWS: {}", msg),
+            JoseError::UnsupportedAlgorithm(alg) => write!(f, "Unsupported JWS algorithm: {}", alg),
+            JoseError::AlgorithmMismatch { expected, actual } =>
+                write!(f, "JWS algorithm {} does not match key algorithm {}", actual, expected),
+            JoseError::BadSignature => write!(f, "Bad JWS signature"),
+            JoseError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for JoseError {}
+
+impl From<AlgorithmError> for JoseError {
+    fn from(e: AlgorithmError) -> Self {
+        JoseError::Algorithm(e)
+    }
+}
+
+/// Map a JOSE `alg` value to a signature algorithm.
+pub fn algorithm_from_name(name: &str) -> Result<AlgorithmId, JoseError> {
+    match AlgorithmId::from_name(name) {
+        Ok(alg) if alg.kind() == AlgorithmKind::Signature && alg.name() == name => Ok(alg),
+        _ => Err(JoseError::UnsupportedAlgorithm(name.to_string())),
+    }
+}
+
+/// A decoded (not yet verified) compact JWS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jws {
+    pub header: Map<String, Value>,
+    pub payload: Vec<u8>,
+    signing_input: String,
+    signature: Vec<u8>,
+}
+
+impl Jws {
+    /// The algorithm named by the protected header.
+    pub fn algorithm(&self) -> Result<AlgorithmId, JoseError> {
+        let name = self.header
+            .get("alg")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JoseError::Malformed("missing alg".into()))?;
+        algorithm_from_name(name)
+    }
+
+    pub fn kid(&self) -> Option<&str> {
+        self.header.get("kid").and_then(Value::as_str)
+    }
+
+    pub fn typ(&self) -> Option<&str> {
+        self.header.get("typ").and_then(Value::as_str)
+    }
+
+    /// Verify the signature with a key of `algorithm`. The header's `alg`
+    /// must name the same algorithm.
+    pub fn verify(&self, algorithm: AlgorithmId, public_key: &[u8]) -> Result<(), JoseError> {
+        let actual = self.algorithm()?;
+        if actual != algorithm {
+            return Err(JoseError::AlgorithmMismatch { expected: algorithm, actual });
+        }
+        if registry::verify(algorithm, public_key, self.signing_input.as_bytes(), &self.signature) {
+            Ok(())
+        } else {
+            Err(JoseError::BadSignature)
+        }
+    }
+}
+
+/// Sign `payload` and return a compact JWS. `alg` is set from `algorithm`,
+/// overriding any value in `header`.
+pub fn sign_compact(
+    algorithm: AlgorithmId,
+    secret_key: &[u8],
+    mut header: Map<String, Value>,
+    payload: &[u8]
+) -> Result<String, JoseError> {
+    if algorithm.kind() != AlgorithmKind::Signature {
+        return Err(JoseError::UnsupportedAlgorithm(algorithm.name().to_string()));
+    }
+    header.insert("alg".into(), Value::String(algorithm.name().to_string()));
+    let header_json = serde_json::to_vec(&header).map_err(|e| JoseError::Malformed(e.to_string()))?;
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header_json),
+        URL_SAFE_NO_PAD.encode(payload)
+    );
+    let signature = registry::sign(algorithm, secret_key, signing_input.as_bytes())?;
+    Ok(format!("{}.{}", signing_input, URL_SAFE_NO_PAD.encode(signature)))
+}
+
+/// Parse a compact JWS without verifying it.
+pub fn decode_compact(token: &str) -> Result<Jws, JoseError> {
+    let mut parts = token.split('.');
+    let (header_b64, payload_b64, signature_b64) = match
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    {
+        (Some(h), Some(p), Some(s), None) => (h, p, s),
+        _ => {
+            return Err(JoseError::Malformed("expected three segments".into()));
+        }
+    };
+    let decode = |segment: &str| {
+        URL_SAFE_NO_PAD.decode(segment).map_err(|e| JoseError::Malformed(e.to_string()))
+    };
+    let header: Map<String, Value> = serde_json
+        ::from_slice(&decode(header_b64)?)
+        .map_err(|e| JoseError::Malformed(e.to_string()))?;
+    if header.contains_key("crit") {
+        return Err(JoseError::Malformed("unsupported critical header".into()));
+    }
+    if header.get("b64") == Some(&Value::Bool(false)) {
+        return Err(JoseError::Malformed("unencoded payloads are not supported".into()));
+    }
+    Ok(Jws {
+        header,
+        payload: decode(payload_b64)?,
+        signing_input: format!("{}.{}", header_b64, payload_b64),
+        signature: decode(signature_b64)?,
+    })
+}
+
+/// Parse and verify a compact JWS in one step.
+pub fn verify_compact(token: &str, algorithm: AlgorithmId, public_key: &[u8]) -> Result<Jws, JoseError> {
+    let jws = decode_compact(token)?;
+    jws.verify(algorithm, public_key)?;
+    Ok(jws)
+}
+
+#[cfg(all(test, feature = "dilithium"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify_compact() {
+        let keypair = crate::dilithium44_keygen();
+        let mut header = Map::new();
+        header.insert("typ".into(), Value::String("JWT".into()));
+        let token = sign_compact(AlgorithmId::MlDsa44, &keypair.secret_key(), header, b"{\"a\":1}").unwrap();
+
+        let jws = verify_compact(&token, AlgorithmId::MlDsa44, &keypair.public_key()).unwrap();
+        assert_eq!(jws.payload, b"{\"a\":1}");
+        assert_eq!(jws.typ(), Some("JWT"));
+        assert_eq!(jws.header["alg"], "ML-DSA-44");
+
+        let other = crate::dilithium65_keygen();
+        assert!(matches!(
+            verify_compact(&token, AlgorithmId::MlDsa65, &other.public_key()),
+            Err(JoseError::AlgorithmMismatch { .. })
+        ));
+
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let tampered = URL_SAFE_NO_PAD.encode(b"{\"a\":2}");
+        parts[1] = &tampered;
+        assert_eq!(
+            verify_compact(&parts.join("."), AlgorithmId::MlDsa44, &keypair.public_key()),
+            Err(JoseError::BadSignature)
+        );
+        assert!(algorithm_from_name("ML-KEM-768").is_err());
+        assert!(algorithm_from_name("ml-dsa-44").is_err());
+    }
+}
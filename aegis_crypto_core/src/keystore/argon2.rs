@@ -0,0 +1,269 @@
+//! Argon2id password hashing (RFC 9106, version 0x13).
+//!
+//! Lanes are filled one after another rather than in parallel threads;
+//! the output is identical, only slower for `parallelism > 1`.
+
+use blake2::digest::{ Update, VariableOutput };
+use blake2::{ Blake2b512, Blake2bVar, Digest };
+use zeroize::{ Zeroize, Zeroizing };
+
+use super::KeyStoreError;
+
+const VERSION: u32 = 0x13;
+const ARGON2ID: u32 = 2;
+const BLOCK_WORDS: usize = 128;
+const SYNC_POINTS: usize = 4;
+
+/// Cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory in KiB.
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Argon2Params {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> Result<Self, KeyStoreError> {
+        if parallelism == 0 || parallelism > 0xff_ffff || iterations == 0 || memory_kib < 8 * parallelism {
+            return Err(KeyStoreError::Malformed("invalid Argon2 parameters".into()));
+        }
+        Ok(Argon2Params { memory_kib, iterations, parallelism })
+    }
+}
+
+impl Default for Argon2Params {
+    /// The second recommended option of RFC 9106: 64 MiB, 3 passes, 4
+    /// lanes.
+    fn default() -> Self {
+        Argon2Params { memory_kib: 64 * 1024, iterations: 3, parallelism: 4 }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Block([u64; BLOCK_WORDS]);
+
+impl Block {
+    const ZERO: Block = Block([0; BLOCK_WORDS]);
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let mut block = Block::ZERO;
+        for (word, chunk) in block.0.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+        }
+        block
+    }
+
+    fn xor(&self, other: &Block) -> Block {
+        let mut out = *self;
+        for (a, b) in out.0.iter_mut().zip(&other.0) {
+            *a ^= b;
+        }
+        out
+    }
+}
+
+fn blamka(x: u64, y: u64) -> u64 {
+    x.wrapping_add(y).wrapping_add(2u64.wrapping_mul(x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff))
+}
+
+fn gb(v: &mut [u64; BLOCK_WORDS], a: usize, b: usize, c: usize, d: usize) {
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+/// The BLAKE2b-based permutation over the sixteen words at `i`.
+fn permute(v: &mut [u64; BLOCK_WORDS], i: [usize; 16]) {
+    gb(v, i[0], i[4], i[8], i[12]);
+    gb(v, i[1], i[5], i[9], i[13]);
+    gb(v, i[2], i[6], i[10], i[14]);
+    gb(v, i[3], i[7], i[11], i[15]);
+    gb(v, i[0], i[5], i[10], i[15]);
+    gb(v, i[1], i[6], i[11], i[12]);
+    gb(v, i[2], i[7], i[8], i[13]);
+    gb(v, i[3], i[4], i[9], i[14]);
+}
+
+/// The compression function `G(x, y)`.
+fn compress(x: &Block, y: &Block) -> Block {
+    let r = x.xor(y);
+    let mut q = r;
+    for row in 0..8 {
+        permute(&mut q.0, std::array::from_fn(|k| 16 * row + k));
+    }
+    for column in 0..8 {
+        permute(&mut q.0, std::array::from_fn(|k| 2 * column + (k % 2) + 16 * (k / 2)));
+    }
+    q.xor(&r)
+}
+
+/// The variable-length hash `H'`.
+fn hash_long(input: &[&[u8]], out: &mut [u8]) {
+    let len = (out.len() as u32).to_le_bytes();
+    if out.len() <= 64 {
+        let mut hasher = Blake2bVar::new(out.len()).expect("valid length");
+        hasher.update(&len);
+        input.iter().for_each(|part| hasher.update(part));
+        hasher.finalize_variable(out).expect("valid length");
+        return;
+    }
+    let mut hasher = Blake2b512::new();
+    Digest::update(&mut hasher, len);
+    input.iter().for_each(|part| Digest::update(&mut hasher, part));
+    let mut v: [u8; 64] = hasher.finalize().into();
+    let mut written = 0;
+    while out.len() - written > 64 {
+        out[written..written + 32].copy_from_slice(&v[..32]);
+        written += 32;
+        if out.len() - written > 64 {
+            v = Blake2b512::digest(v).into();
+        }
+    }
+    let rest = out.len() - written;
+    let mut hasher = Blake2bVar::new(rest).expect("valid length");
+    hasher.update(&v);
+    hasher.finalize_variable(&mut out[written..]).expect("valid length");
+    v.zeroize();
+}
+
+/// Derive `out.len()` bytes from `password` and `salt`.
+pub fn argon2id(password: &[u8], salt: &[u8], params: &Argon2Params, out: &mut [u8]) {
+    argon2id_keyed(password, salt, &[], &[], params, out)
+}
+
+/// Argon2id with the optional secret `key` and associated data.
+pub fn argon2id_keyed(
+    password: &[u8],
+    salt: &[u8],
+    key: &[u8],
+    associated_data: &[u8],
+    params: &Argon2Params,
+    out: &mut [u8]
+) {
+    assert!(out.len() >= 4 && salt.len() >= 8, "Argon2 needs a 4-byte tag and an 8-byte salt");
+    let lanes = params.parallelism as usize;
+    let passes = params.iterations as usize;
+    let segment_len = (params.memory_kib as usize / (SYNC_POINTS * lanes)).max(2);
+    let lane_len = segment_len * SYNC_POINTS;
+    let total = lane_len * lanes;
+
+    let mut h0 = Zeroizing::new([0u8; 72]);
+    let mut hasher = Blake2b512::new();
+    for value in [params.parallelism, out.len() as u32, params.memory_kib, params.iterations, VERSION, ARGON2ID] {
+        Digest::update(&mut hasher, value.to_le_bytes());
+    }
+    for field in [password, salt, key, associated_data] {
+        Digest::update(&mut hasher, (field.len() as u32).to_le_bytes());
+        Digest::update(&mut hasher, field);
+    }
+    h0[..64].copy_from_slice(&hasher.finalize());
+
+    let mut memory = Zeroizing::new(vec![[0u64; BLOCK_WORDS]; total]);
+    let mut bytes = Zeroizing::new([0u8; 1024]);
+    for lane in 0..lanes {
+        for column in 0..2u32 {
+            hash_long(&[&h0[..64], &column.to_le_bytes(), &(lane as u32).to_le_bytes()], &mut bytes[..]);
+            memory[lane * lane_len + column as usize] = Block::from_bytes(&bytes[..]).0;
+        }
+    }
+
+    for pass in 0..passes {
+        for slice in 0..SYNC_POINTS {
+            for lane in 0..lanes {
+                let independent = pass == 0 && slice < SYNC_POINTS / 2;
+                let mut input = Block::ZERO;
+                let mut addresses = Block::ZERO;
+                if independent {
+                    input.0[..6].copy_from_slice(&[
+                        pass as u64,
+                        lane as u64,
+                        slice as u64,
+                        total as u64,
+                        passes as u64,
+                        ARGON2ID as u64,
+                    ]);
+                }
+                let next_addresses = |input: &mut Block| {
+                    input.0[6] += 1;
+                    compress(&Block::ZERO, &compress(&Block::ZERO, input))
+                };
+                let start = if pass == 0 && slice == 0 {
+                    if independent {
+                        addresses = next_addresses(&mut input);
+                    }
+                    2
+                } else {
+                    0
+                };
+                for index in start..segment_len {
+                    let column = slice * segment_len + index;
+                    let current = lane * lane_len + column;
+                    let previous = if column == 0 { current + lane_len - 1 } else { current - 1 };
+
+                    let pseudo_random = if independent {
+                        if index % BLOCK_WORDS == 0 {
+                            addresses = next_addresses(&mut input);
+                        }
+                        addresses.0[index % BLOCK_WORDS]
+                    } else {
+                        memory[previous][0]
+                    };
+                    let ref_lane = if pass == 0 && slice == 0 { lane } else { (pseudo_random >> 32) as usize % lanes };
+                    let same_lane = ref_lane == lane;
+
+                    // Blocks the reference may point at (RFC 9106 Section 3.4.1.1).
+                    let finished = if pass == 0 { slice * segment_len } else { lane_len - segment_len };
+                    let area = if same_lane {
+                        finished + index - 1
+                    } else if index == 0 {
+                        finished - 1
+                    } else {
+                        finished
+                    };
+                    let j1 = pseudo_random & 0xffff_ffff;
+                    let x = (j1 * j1) >> 32;
+                    let y = ((area as u64) * x) >> 32;
+                    let relative = area - 1 - y as usize;
+                    let window_start = if pass == 0 || slice == SYNC_POINTS - 1 { 0 } else { (slice + 1) * segment_len };
+                    let reference = ref_lane * lane_len + (window_start + relative) % lane_len;
+
+                    let mut block = compress(&Block(memory[previous]), &Block(memory[reference]));
+                    if pass > 0 {
+                        block = block.xor(&Block(memory[current]));
+                    }
+                    memory[current] = block.0;
+                }
+            }
+        }
+    }
+
+    let mut last = Block(memory[lane_len - 1]);
+    for lane in 1..lanes {
+        last = last.xor(&Block(memory[lane * lane_len + lane_len - 1]));
+    }
+    for (chunk, word) in bytes.chunks_exact_mut(8).zip(last.0) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    last.0.zeroize();
+    hash_long(&[&bytes[..]], out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc9106_argon2id_vector() {
+        // RFC 9106 Section 5.3.
+        let params = Argon2Params::new(32, 3, 4).unwrap();
+        let mut tag = [0u8; 32];
+        argon2id_keyed(&[0x01; 32], &[0x02; 16], &[0x03; 8], &[0x04; 12], &params, &mut tag);
+        assert_eq!(hex::encode(tag), "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659");
+    }
+}
@@ -0,0 +1,232 @@
+//! Passphrase-encrypted keystore directory.
+//!
+//! ```text
+//! keystore.meta := "AEKS" version:u8 memory_kib:u32be iterations:u32be
+//!                  parallelism:u32be salt[16] nonce[24] check_tag[16]
+//! <key id>.key  := "AEKK" version:u8 nonce[24] ciphertext
+//! ```
+//!
+//! The storage key is Argon2id of the passphrase and salt. Each key file
+//! is the encoded entry sealed with XChaCha20-Poly1305 under a fresh
+//! random nonce, with the magic, version and key ID as associated data.
+//! `check_tag` seals the empty string over the metadata header so a wrong
+//! passphrase is reported before any key is read.
+
+use std::fs::{ self, File };
+use std::io::Write;
+use std::path::{ Path, PathBuf };
+
+use chacha20poly1305::aead::{ Aead, Payload };
+use chacha20poly1305::{ KeyInit, XChaCha20Poly1305, XNonce };
+use zeroize::Zeroizing;
+
+use super::argon2::{ self, Argon2Params };
+use super::sealed::{ Backend, KeyEntry, Token };
+use super::{ KeyId, KeyStoreError };
+
+const META_FILE: &str = "keystore.meta";
+const META_MAGIC: &[u8; 4] = b"AEKS";
+const KEY_MAGIC: &[u8; 4] = b"AEKK";
+const KEY_EXTENSION: &str = "key";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const META_HEADER_LEN: usize = 4 + 1 + 12 + SALT_LEN;
+
+/// Keys stored as encrypted files in a directory.
+pub struct DirectoryKeyStore {
+    dir: PathBuf,
+    key: Zeroizing<[u8; 32]>,
+}
+
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> KeyStoreError + '_ {
+    move |e| KeyStoreError::Io(format!("{}: {}", path.display(), e))
+}
+
+/// Replace `path` with `data`: write a temporary file, sync it, rename it
+/// into place and sync the directory.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), KeyStoreError> {
+    let mut temporary = path.to_path_buf().into_os_string();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    {
+        let mut file = File::create(&temporary).map_err(io_error(&temporary))?;
+        file.write_all(data).map_err(io_error(&temporary))?;
+        file.sync_all().map_err(io_error(&temporary))?;
+    }
+    fs::rename(&temporary, path).map_err(io_error(path))?;
+    sync_parent(path)
+}
+
+fn sync_parent(path: &Path) -> Result<(), KeyStoreError> {
+    #[cfg(unix)]
+    {
+        let parent = path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        File::open(parent)
+            .and_then(|dir| dir.sync_all())
+            .map_err(io_error(parent))?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).expect("RNG failed");
+    nonce
+}
+
+impl DirectoryKeyStore {
+    /// Create a keystore in `dir`, creating the directory if needed.
+    pub fn create(dir: impl AsRef<Path>, passphrase: &[u8], params: Argon2Params) -> Result<Self, KeyStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(io_error(&dir))?;
+        let meta_path = dir.join(META_FILE);
+        if meta_path.exists() {
+            return Err(KeyStoreError::Io(format!("{}: keystore already exists", dir.display())));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::fill(&mut salt).expect("RNG failed");
+
+        let mut meta = Vec::with_capacity(META_HEADER_LEN + NONCE_LEN + 16);
+        meta.extend_from_slice(META_MAGIC);
+        meta.push(VERSION);
+        meta.extend_from_slice(&params.memory_kib.to_be_bytes());
+        meta.extend_from_slice(&params.iterations.to_be_bytes());
+        meta.extend_from_slice(&params.parallelism.to_be_bytes());
+        meta.extend_from_slice(&salt);
+
+        let store = DirectoryKeyStore { key: derive_key(passphrase, &salt, &params), dir };
+        let nonce = random_nonce();
+        let check = store.seal(&nonce, &meta, b"")?;
+        meta.extend_from_slice(&nonce);
+        meta.extend_from_slice(&check);
+        write_atomic(&meta_path, &meta)?;
+        Ok(store)
+    }
+
+    /// Open an existing keystore.
+    pub fn open(dir: impl AsRef<Path>, passphrase: &[u8]) -> Result<Self, KeyStoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        let meta_path = dir.join(META_FILE);
+        let meta = fs::read(&meta_path).map_err(io_error(&meta_path))?;
+        if meta.len() != META_HEADER_LEN + NONCE_LEN + 16 || &meta[..4] != META_MAGIC {
+            return Err(KeyStoreError::Malformed("not a keystore metadata file".into()));
+        }
+        if meta[4] != VERSION {
+            return Err(KeyStoreError::Malformed(format!("unsupported version {}", meta[4])));
+        }
+        let word = |i: usize| u32::from_be_bytes(meta[5 + 4 * i..9 + 4 * i].try_into().unwrap());
+        let params = Argon2Params::new(word(0), word(1), word(2))?;
+        let (header, sealed) = meta.split_at(META_HEADER_LEN);
+        let salt = &header[META_HEADER_LEN - SALT_LEN..];
+
+        let store = DirectoryKeyStore { key: derive_key(passphrase, salt, &params), dir };
+        let (nonce, check) = sealed.split_at(NONCE_LEN);
+        store.open_sealed(nonce, header, check).map_err(|_| KeyStoreError::BadPassphrase)?;
+        Ok(store)
+    }
+
+    /// Whether `dir` holds a keystore.
+    pub fn exists(dir: impl AsRef<Path>) -> bool {
+        dir.as_ref().join(META_FILE).is_file()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.dir
+    }
+
+    fn key_path(&self, id: &KeyId) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, KEY_EXTENSION))
+    }
+
+    fn seal(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, KeyStoreError> {
+        XChaCha20Poly1305::new(self.key.as_ref().into())
+            .encrypt(XNonce::from_slice(nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| KeyStoreError::Malformed("encryption failed".into()))
+    }
+
+    fn open_sealed(&self, nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, KeyStoreError> {
+        XChaCha20Poly1305::new(self.key.as_ref().into())
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map(Zeroizing::new)
+            .map_err(|_| KeyStoreError::Malformed("key file failed authentication".into()))
+    }
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8], params: &Argon2Params) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2::argon2id(passphrase, salt, params, &mut key[..]);
+    key
+}
+
+fn key_aad(id: &KeyId) -> Vec<u8> {
+    [&KEY_MAGIC[..], &[VERSION], &id.0].concat()
+}
+
+impl Backend for DirectoryKeyStore {
+    fn insert(&mut self, _: Token, entry: KeyEntry) -> Result<(), KeyStoreError> {
+        let id = entry.metadata.id;
+        let path = self.key_path(&id);
+        if path.exists() {
+            return Err(KeyStoreError::AlreadyExists(id));
+        }
+        let nonce = random_nonce();
+        let ciphertext = self.seal(&nonce, &key_aad(&id), &entry.encode())?;
+        let mut file = Vec::with_capacity(5 + NONCE_LEN + ciphertext.len());
+        file.extend_from_slice(KEY_MAGIC);
+        file.push(VERSION);
+        file.extend_from_slice(&nonce);
+        file.extend_from_slice(&ciphertext);
+        write_atomic(&path, &file)
+    }
+
+    fn entry(&self, _: Token, id: &KeyId) -> Result<KeyEntry, KeyStoreError> {
+        let path = self.key_path(id);
+        let file = match fs::read(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(KeyStoreError::NotFound(*id));
+            }
+            Err(e) => {
+                return Err(io_error(&path)(e));
+            }
+        };
+        if file.len() < 5 + NONCE_LEN || &file[..4] != KEY_MAGIC || file[4] != VERSION {
+            return Err(KeyStoreError::Malformed(format!("{} is not a key file", path.display())));
+        }
+        let (nonce, ciphertext) = file[5..].split_at(NONCE_LEN);
+        let plaintext = self.open_sealed(nonce, &key_aad(id), ciphertext)?;
+        KeyEntry::decode(&plaintext)
+    }
+
+    fn remove(&mut self, _: Token, id: &KeyId) -> Result<(), KeyStoreError> {
+        let path = self.key_path(id);
+        match fs::remove_file(&path) {
+            Ok(()) => sync_parent(&path),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(KeyStoreError::NotFound(*id)),
+            Err(e) => Err(io_error(&path)(e)),
+        }
+    }
+
+    fn ids(&self, _: Token) -> Result<Vec<KeyId>, KeyStoreError> {
+        let mut ids = Vec::new();
+        for item in fs::read_dir(&self.dir).map_err(io_error(&self.dir))? {
+            let path = item.map_err(io_error(&self.dir))?.path();
+            if path.extension().is_some_and(|ext| ext == KEY_EXTENSION) {
+                if let Some(id) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse().ok())
+                {
+                    ids.push(id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+}
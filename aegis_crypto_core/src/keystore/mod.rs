@@ -0,0 +1,426 @@
+//! Key storage with pluggable backends.
+//!
+//! A [`KeyStore`] holds registry keys and performs private-key operations
+//! on the caller's behalf. Keys are addressed by a [`KeyId`], the
+//! [`container::key_id`] of the algorithm and public key. Secret key bytes
+//! never leave the store: there is no export for them, and the signing and
+//! decapsulation calls check each key's expiry and usage flags first.
+//!
+//! Backends:
+//!
+//! - [`MemoryKeyStore`] keeps keys in process memory, zeroized on drop.
+//! - [`DirectoryKeyStore`] keeps one encrypted file per key in a
+//!   directory, under a key derived from a passphrase with Argon2id.
+
+pub mod argon2;
+mod directory;
+
+pub use directory::DirectoryKeyStore;
+
+use std::collections::BTreeMap;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use zeroize::Zeroizing;
+
+use crate::container::{ self, KEY_ID_LEN };
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// Keystore error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyStoreError {
+    Malformed(String),
+    NotFound(KeyId),
+    AlreadyExists(KeyId),
+    Expired(KeyId),
+    /// The key's usage flags do not allow the operation.
+    UsageNotPermitted(KeyId),
+    /// The passphrase does not open the keystore.
+    BadPassphrase,
+    Io(String),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for KeyStoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyStoreError::Malformed(msg) => write!(f, "Malformed keystore data: {}", msg),
+            KeyStoreError::NotFound(id) => write!(f, "Key {} not found", id),
+            KeyStoreError::AlreadyExists(id) => write!(f, "Key {} already exists", id),
+            KeyStoreError::Expired(id) => write!(f, "Key {} has expired", id),
+            KeyStoreError::UsageNotPermitted(id) => write!(f, "Key {} is not allowed for this operation", id),
+            KeyStoreError::BadPassphrase => write!(f, "Wrong passphrase or corrupted keystore"),
+            KeyStoreError::Io(msg) => write!(f, "Keystore I/O error: {}", msg),
+            KeyStoreError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for KeyStoreError {}
+
+impl From<AlgorithmError> for KeyStoreError {
+    fn from(e: AlgorithmError) -> Self {
+        KeyStoreError::Algorithm(e)
+    }
+}
+
+/// Identifier of a stored key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyId(pub [u8; KEY_ID_LEN]);
+
+impl std::fmt::Display for KeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl std::str::FromStr for KeyId {
+    type Err = KeyStoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s).map_err(|e| KeyStoreError::Malformed(e.to_string()))?;
+        let id = bytes.try_into().map_err(|_| KeyStoreError::Malformed("key ID must be 16 bytes".into()))?;
+        Ok(KeyId(id))
+    }
+}
+
+/// Operations a key may be used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyUsage(u8);
+
+impl KeyUsage {
+    pub const SIGN: KeyUsage = KeyUsage(0x01);
+    pub const DECAPSULATE: KeyUsage = KeyUsage(0x02);
+
+    /// Every operation the algorithm supports.
+    pub fn for_algorithm(algorithm: AlgorithmId) -> Self {
+        match algorithm.kind() {
+            AlgorithmKind::Signature => KeyUsage::SIGN,
+            AlgorithmKind::Kem => KeyUsage::DECAPSULATE,
+        }
+    }
+
+    pub fn contains(self, other: KeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+}
+
+impl std::ops::BitOr for KeyUsage {
+    type Output = KeyUsage;
+
+    fn bitor(self, other: KeyUsage) -> KeyUsage {
+        KeyUsage(self.0 | other.0)
+    }
+}
+
+/// Options for a new or imported key.
+#[derive(Debug, Clone, Default)]
+pub struct KeyOptions {
+    pub expires_at: Option<u64>,
+    pub usage: Option<KeyUsage>,
+    pub labels: Vec<String>,
+}
+
+impl KeyOptions {
+    pub fn new() -> Self {
+        KeyOptions::default()
+    }
+
+    /// Expiry in seconds since the Unix epoch.
+    pub fn with_expiry(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Restrict the key to `usage`. Defaults to every operation the
+    /// algorithm supports.
+    pub fn with_usage(mut self, usage: KeyUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.labels.push(label.to_string());
+        self
+    }
+}
+
+/// Public information about a stored key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMetadata {
+    pub id: KeyId,
+    pub algorithm: AlgorithmId,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+    pub expires_at: Option<u64>,
+    pub usage: KeyUsage,
+    pub labels: Vec<String>,
+}
+
+impl KeyMetadata {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.iter().any(|l| l == label)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+mod sealed {
+    use super::{ KeyId, KeyMetadata, KeyStoreError };
+    use zeroize::Zeroizing;
+
+    /// A key as backends hold it.
+    #[derive(Clone)]
+    pub struct KeyEntry {
+        pub metadata: KeyMetadata,
+        pub public_key: Vec<u8>,
+        pub secret_key: Zeroizing<Vec<u8>>,
+    }
+
+    /// Only this module can create a token, so the methods below cannot be
+    /// called from outside the crate, even through a `KeyStore` bound.
+    pub struct Token(pub(in crate::keystore) ());
+
+    /// Storage operations only [`super::KeyStore`] may perform.
+    pub trait Backend {
+        /// Store a new entry; fails if the ID is taken.
+        fn insert(&mut self, token: Token, entry: KeyEntry) -> Result<(), KeyStoreError>;
+        fn entry(&self, token: Token, id: &KeyId) -> Result<KeyEntry, KeyStoreError>;
+        fn remove(&mut self, token: Token, id: &KeyId) -> Result<(), KeyStoreError>;
+        fn ids(&self, token: Token) -> Result<Vec<KeyId>, KeyStoreError>;
+    }
+}
+
+use sealed::{ KeyEntry, Token };
+
+impl KeyEntry {
+    fn encode(&self) -> Zeroizing<Vec<u8>> {
+        let m = &self.metadata;
+        let mut out = Zeroizing::new(Vec::new());
+        out.extend_from_slice(&m.id.0);
+        out.extend_from_slice(&m.algorithm.code().to_be_bytes());
+        out.extend_from_slice(&m.created_at.to_be_bytes());
+        out.extend_from_slice(&m.expires_at.unwrap_or(0).to_be_bytes());
+        out.push(m.usage.0);
+        out.extend_from_slice(&(m.labels.len() as u16).to_be_bytes());
+        for label in &m.labels {
+            out.extend_from_slice(&(label.len() as u16).to_be_bytes());
+            out.extend_from_slice(label.as_bytes());
+        }
+        for key in [&self.public_key[..], &self.secret_key[..]] {
+            out.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            out.extend_from_slice(key);
+        }
+        out
+    }
+
+    fn decode(data: &[u8]) -> Result<KeyEntry, KeyStoreError> {
+        let mut reader = Reader(data);
+        let id = KeyId(reader.take(KEY_ID_LEN)?.try_into().unwrap());
+        let algorithm = AlgorithmId::from_code(reader.u16()?)?;
+        let created_at = reader.u64()?;
+        let expires_at = Some(reader.u64()?).filter(|&t| t != 0);
+        let usage = KeyUsage(reader.take(1)?[0]);
+        let count = reader.u16()?;
+        let mut labels = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let len = reader.u16()? as usize;
+            let label = std::str::from_utf8(reader.take(len)?)
+                .map_err(|_| KeyStoreError::Malformed("label is not UTF-8".into()))?;
+            labels.push(label.to_string());
+        }
+        let public_len = reader.u32()? as usize;
+        let public_key = reader.take(public_len)?.to_vec();
+        let secret_len = reader.u32()? as usize;
+        let secret_key = Zeroizing::new(reader.take(secret_len)?.to_vec());
+        if !reader.0.is_empty() {
+            return Err(KeyStoreError::Malformed("trailing data".into()));
+        }
+        if container::key_id(algorithm, &public_key) != id.0 {
+            return Err(KeyStoreError::Malformed("key ID does not match the public key".into()));
+        }
+        let metadata = KeyMetadata { id, algorithm, created_at, expires_at, usage, labels };
+        Ok(KeyEntry { metadata, public_key, secret_key })
+    }
+
+    /// The entry, if it is unexpired and allowed `usage`.
+    fn check(self, usage: KeyUsage) -> Result<KeyEntry, KeyStoreError> {
+        let id = self.metadata.id;
+        if self.metadata.is_expired(now()) {
+            return Err(KeyStoreError::Expired(id));
+        }
+        if !self.metadata.usage.contains(usage) {
+            return Err(KeyStoreError::UsageNotPermitted(id));
+        }
+        Ok(self)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], KeyStoreError> {
+        if self.0.len() < len {
+            return Err(KeyStoreError::Malformed("truncated key entry".into()));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, KeyStoreError> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, KeyStoreError> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, KeyStoreError> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Check that `secret_key` belongs to `public_key` by running it once.
+fn check_pair(algorithm: AlgorithmId, public_key: &[u8], secret_key: &[u8]) -> Result<(), KeyStoreError> {
+    let matches = match algorithm.kind() {
+        AlgorithmKind::Signature => {
+            let probe = b"aegis-keystore import check";
+            registry::verify(algorithm, public_key, probe, &registry::sign(algorithm, secret_key, probe)?)
+        }
+        AlgorithmKind::Kem => {
+            let (ciphertext, shared) = registry::encapsulate(algorithm, public_key)?;
+            let decapsulated = Zeroizing::new(registry::decapsulate(algorithm, secret_key, &ciphertext)?);
+            bool::from(subtle::ConstantTimeEq::ct_eq(&shared[..], &decapsulated[..]))
+        }
+    };
+    if matches { Ok(()) } else { Err(AlgorithmError::InvalidKey.into()) }
+}
+
+/// Store a new key under its computed ID.
+fn store_key<S: sealed::Backend + ?Sized>(
+    store: &mut S,
+    algorithm: AlgorithmId,
+    public_key: Vec<u8>,
+    secret_key: Zeroizing<Vec<u8>>,
+    options: KeyOptions
+) -> Result<KeyMetadata, KeyStoreError> {
+    let supported = KeyUsage::for_algorithm(algorithm);
+    let usage = options.usage.unwrap_or(supported);
+    if !supported.contains(usage) {
+        return Err(AlgorithmError::Unsupported(algorithm).into());
+    }
+    let metadata = KeyMetadata {
+        id: KeyId(container::key_id(algorithm, &public_key)),
+        algorithm,
+        created_at: now(),
+        expires_at: options.expires_at,
+        usage,
+        labels: options.labels,
+    };
+    store.insert(Token(()), KeyEntry { metadata: metadata.clone(), public_key, secret_key })?;
+    Ok(metadata)
+}
+
+/// Key storage and private-key operations by key ID.
+pub trait KeyStore: sealed::Backend {
+    /// Generate and store a new key.
+    fn generate(&mut self, algorithm: AlgorithmId, options: KeyOptions) -> Result<KeyMetadata, KeyStoreError> {
+        let (public_key, secret_key) = registry::keygen(algorithm)?;
+        store_key(self, algorithm, public_key, Zeroizing::new(secret_key), options)
+    }
+
+    /// Store an existing key pair after checking the two halves match.
+    fn import(
+        &mut self,
+        algorithm: AlgorithmId,
+        public_key: &[u8],
+        secret_key: &[u8],
+        options: KeyOptions
+    ) -> Result<KeyMetadata, KeyStoreError> {
+        check_pair(algorithm, public_key, secret_key)?;
+        store_key(self, algorithm, public_key.to_vec(), Zeroizing::new(secret_key.to_vec()), options)
+    }
+
+    fn public_key(&self, id: &KeyId) -> Result<Vec<u8>, KeyStoreError> {
+        Ok(self.entry(Token(()), id)?.public_key)
+    }
+
+    fn metadata(&self, id: &KeyId) -> Result<KeyMetadata, KeyStoreError> {
+        Ok(self.entry(Token(()), id)?.metadata)
+    }
+
+    /// Produce a detached signature with a signing key.
+    fn sign(&self, id: &KeyId, message: &[u8]) -> Result<Vec<u8>, KeyStoreError> {
+        let entry = self.entry(Token(()), id)?.check(KeyUsage::SIGN)?;
+        Ok(registry::sign(entry.metadata.algorithm, &entry.secret_key, message)?)
+    }
+
+    /// Decapsulate a KEM ciphertext with a KEM key.
+    fn decapsulate(&self, id: &KeyId, ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>, KeyStoreError> {
+        let entry = self.entry(Token(()), id)?.check(KeyUsage::DECAPSULATE)?;
+        Ok(Zeroizing::new(registry::decapsulate(entry.metadata.algorithm, &entry.secret_key, ciphertext)?))
+    }
+
+    fn delete(&mut self, id: &KeyId) -> Result<(), KeyStoreError> {
+        self.remove(Token(()), id)
+    }
+
+    /// Metadata of every stored key, ordered by ID.
+    fn list(&self) -> Result<Vec<KeyMetadata>, KeyStoreError> {
+        let mut ids = self.ids(Token(()))?;
+        ids.sort();
+        ids.iter()
+            .map(|id| self.metadata(id))
+            .collect()
+    }
+}
+
+impl<T: sealed::Backend> KeyStore for T {}
+
+/// Keys held in process memory.
+#[derive(Default)]
+pub struct MemoryKeyStore {
+    entries: BTreeMap<KeyId, KeyEntry>,
+}
+
+impl MemoryKeyStore {
+    pub fn new() -> Self {
+        MemoryKeyStore::default()
+    }
+}
+
+impl sealed::Backend for MemoryKeyStore {
+    fn insert(&mut self, _: Token, entry: KeyEntry) -> Result<(), KeyStoreError> {
+        let id = entry.metadata.id;
+        if self.entries.contains_key(&id) {
+            return Err(KeyStoreError::AlreadyExists(id));
+        }
+        self.entries.insert(id, entry);
+        Ok(())
+    }
+
+    fn entry(&self, _: Token, id: &KeyId) -> Result<KeyEntry, KeyStoreError> {
+        self.entries.get(id).cloned().ok_or(KeyStoreError::NotFound(*id))
+    }
+
+    fn remove(&mut self, _: Token, id: &KeyId) -> Result<(), KeyStoreError> {
+        self.entries.remove(id).map(|_| ()).ok_or(KeyStoreError::NotFound(*id))
+    }
+
+    fn ids(&self, _: Token) -> Result<Vec<KeyId>, KeyStoreError> {
+        Ok(self.entries.keys().copied().collect())
+    }
+}
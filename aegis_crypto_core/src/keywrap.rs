@@ -0,0 +1,343 @@
+//! Key transport: one data-encryption key (DEK) wrapped to several KEM
+//! recipients.
+//!
+//! ```text
+//! bundle    := "AEKW" version:u8 key_len:u16be count:u16be recipient*
+//! recipient := algorithm:u16be key_id[16] ciphertext_len:u16be
+//!              kem_ciphertext wrapped_key
+//! ```
+//!
+//! Each recipient gets a fresh KEM encapsulation. The key-encryption key
+//! is HKDF-SHA256 of the shared secret, bound to the algorithm, the
+//! recipient's [`KeyId`] and the KEM ciphertext. The DEK is wrapped under
+//! it with AES-256 key wrap with padding (RFC 5649), so `wrapped_key` is
+//! `key_len` rounded up to a multiple of 8, plus 8 bytes.
+//!
+//! Recipients are identified by the same key ID as the
+//! [`keystore`](crate::keystore), so [`unwrap_key_with`] can find the right
+//! entry in a store directly.
+
+use aes_kw::KekAes256;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::container::{ self, KEY_ID_LEN };
+use crate::keystore::{ KeyId, KeyStore, KeyStoreError };
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+const MAGIC: &[u8; 4] = b"AEKW";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 4 + 1 + 2 + 2;
+const KEK_INFO: &[u8] = b"aegis-keywrap-v1";
+
+/// Largest data key that can be wrapped, in bytes.
+pub const MAX_KEY_LEN: usize = u16::MAX as usize;
+
+/// Key wrapping error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyWrapError {
+    Malformed(String),
+    /// The data key is empty, too long, or not the bundle's key length.
+    KeyLength(usize),
+    NoRecipients,
+    /// None of the bundle's entries is addressed to the given key.
+    NotRecipient,
+    /// The entry for the key did not authenticate.
+    UnwrapFailed,
+    Algorithm(AlgorithmError),
+    KeyStore(KeyStoreError),
+}
+
+impl std::fmt::Display for KeyWrapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeyWrapError::Malformed(msg) => write!(f, "Malformed key bundle: {}", msg),
+            KeyWrapError::KeyLength(len) => write!(f, "Invalid data key length {}", len),
+            KeyWrapError::NoRecipients => write!(f, "No recipients"),
+            KeyWrapError::NotRecipient => write!(f, "Key is not a recipient of this bundle"),
+            KeyWrapError::UnwrapFailed => write!(f, "Wrapped key failed authentication"),
+            KeyWrapError::Algorithm(e) => write!(f, "{}", e),
+            KeyWrapError::KeyStore(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for KeyWrapError {}
+
+impl From<AlgorithmError> for KeyWrapError {
+    fn from(e: AlgorithmError) -> Self {
+        KeyWrapError::Algorithm(e)
+    }
+}
+
+impl From<KeyStoreError> for KeyWrapError {
+    fn from(e: KeyStoreError) -> Self {
+        KeyWrapError::KeyStore(e)
+    }
+}
+
+/// A recipient's KEM public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKeyRef<'a> {
+    pub algorithm: AlgorithmId,
+    pub public_key: &'a [u8],
+}
+
+impl<'a> PublicKeyRef<'a> {
+    pub fn new(algorithm: AlgorithmId, public_key: &'a [u8]) -> Self {
+        PublicKeyRef { algorithm, public_key }
+    }
+
+    pub fn key_id(&self) -> KeyId {
+        KeyId(container::key_id(self.algorithm, self.public_key))
+    }
+}
+
+/// The data key wrapped to one recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    recipient: KeyId,
+    algorithm: AlgorithmId,
+    kem_ciphertext: Vec<u8>,
+    wrapped: Vec<u8>,
+}
+
+impl WrappedKey {
+    pub fn recipient(&self) -> KeyId {
+        self.recipient
+    }
+
+    pub fn algorithm(&self) -> AlgorithmId {
+        self.algorithm
+    }
+
+    fn unwrap(&self, shared_secret: &[u8], key_len: usize) -> Option<Zeroizing<Vec<u8>>> {
+        let kek = key_encryption_key(self.algorithm, &self.recipient, &self.kem_ciphertext, shared_secret);
+        let key = Zeroizing::new(kek.unwrap_with_padding_vec(&self.wrapped).ok()?);
+        (key.len() == key_len).then_some(key)
+    }
+}
+
+/// A data key wrapped to each of a list of recipients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKeyBundle {
+    key_len: usize,
+    recipients: Vec<WrappedKey>,
+}
+
+fn wrapped_len(key_len: usize) -> usize {
+    key_len.div_ceil(8) * 8 + 8
+}
+
+fn check_key_len(len: usize) -> Result<(), KeyWrapError> {
+    if (1..=MAX_KEY_LEN).contains(&len) { Ok(()) } else { Err(KeyWrapError::KeyLength(len)) }
+}
+
+fn key_encryption_key(
+    algorithm: AlgorithmId,
+    recipient: &KeyId,
+    kem_ciphertext: &[u8],
+    shared_secret: &[u8]
+) -> KekAes256 {
+    let mut info = Vec::with_capacity(KEK_INFO.len() + 2 + KEY_ID_LEN + kem_ciphertext.len());
+    info.extend_from_slice(KEK_INFO);
+    info.extend_from_slice(&algorithm.code().to_be_bytes());
+    info.extend_from_slice(&recipient.0);
+    info.extend_from_slice(kem_ciphertext);
+    let mut kek = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared_secret)
+        .expand(&info, &mut kek[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    KekAes256::from(*kek)
+}
+
+/// Wrap `key` to every recipient.
+pub fn wrap_key(key: &[u8], recipients: &[PublicKeyRef]) -> Result<WrappedKeyBundle, KeyWrapError> {
+    check_key_len(key.len())?;
+    if recipients.is_empty() {
+        return Err(KeyWrapError::NoRecipients);
+    }
+    let mut bundle = WrappedKeyBundle { key_len: key.len(), recipients: Vec::with_capacity(recipients.len()) };
+    for recipient in recipients {
+        bundle.add_recipient(key, *recipient)?;
+    }
+    Ok(bundle)
+}
+
+/// Recover the data key with a KEM secret key. A secret key does not name
+/// its key ID, so each entry of a matching algorithm is tried in turn.
+pub fn unwrap_key(secret_key: &[u8], bundle: &WrappedKeyBundle) -> Result<Zeroizing<Vec<u8>>, KeyWrapError> {
+    for entry in &bundle.recipients {
+        if entry.algorithm.secret_key_len() != Some(secret_key.len()) {
+            continue;
+        }
+        let Ok(shared_secret) = registry::decapsulate(entry.algorithm, secret_key, &entry.kem_ciphertext) else {
+            continue;
+        };
+        if let Some(key) = entry.unwrap(&Zeroizing::new(shared_secret), bundle.key_len) {
+            return Ok(key);
+        }
+    }
+    Err(KeyWrapError::NotRecipient)
+}
+
+/// Recover the data key with the first recipient key found in `store`.
+pub fn unwrap_key_with<S: KeyStore + ?Sized>(
+    store: &S,
+    bundle: &WrappedKeyBundle
+) -> Result<Zeroizing<Vec<u8>>, KeyWrapError> {
+    for entry in &bundle.recipients {
+        match store.metadata(&entry.recipient) {
+            Ok(_) => {}
+            Err(KeyStoreError::NotFound(_)) => {
+                continue;
+            }
+            Err(e) => {
+                return Err(e.into());
+            }
+        }
+        let shared_secret = store.decapsulate(&entry.recipient, &entry.kem_ciphertext)?;
+        return entry.unwrap(&shared_secret, bundle.key_len).ok_or(KeyWrapError::UnwrapFailed);
+    }
+    Err(KeyWrapError::NotRecipient)
+}
+
+impl WrappedKeyBundle {
+    pub fn key_len(&self) -> usize {
+        self.key_len
+    }
+
+    pub fn recipients(&self) -> &[WrappedKey] {
+        &self.recipients
+    }
+
+    pub fn contains(&self, id: &KeyId) -> bool {
+        self.recipients.iter().any(|entry| entry.recipient == *id)
+    }
+
+    /// Wrap `key` to one more recipient, for example to share an object
+    /// after [`unwrap_key`]. `key` must be the bundle's data key; only its
+    /// length can be checked here. Adding an existing recipient does
+    /// nothing.
+    pub fn add_recipient(&mut self, key: &[u8], recipient: PublicKeyRef) -> Result<(), KeyWrapError> {
+        if key.len() != self.key_len {
+            return Err(KeyWrapError::KeyLength(key.len()));
+        }
+        if recipient.algorithm.kind() != AlgorithmKind::Kem {
+            return Err(AlgorithmError::Unsupported(recipient.algorithm).into());
+        }
+        let id = recipient.key_id();
+        if self.contains(&id) {
+            return Ok(());
+        }
+        if self.recipients.len() == u16::MAX as usize {
+            return Err(KeyWrapError::Malformed("too many recipients".into()));
+        }
+        let (kem_ciphertext, shared_secret) = registry::encapsulate(recipient.algorithm, recipient.public_key)?;
+        let shared_secret = Zeroizing::new(shared_secret);
+        let wrapped = key_encryption_key(recipient.algorithm, &id, &kem_ciphertext, &shared_secret)
+            .wrap_with_padding_vec(key)
+            .expect("key length checked");
+        self.recipients.push(WrappedKey { recipient: id, algorithm: recipient.algorithm, kem_ciphertext, wrapped });
+        Ok(())
+    }
+
+    /// Drop a recipient's entry. This only stops future readers of the
+    /// bundle: a removed recipient may already have seen the data key.
+    pub fn remove_recipient(&mut self, id: &KeyId) -> bool {
+        let before = self.recipients.len();
+        self.recipients.retain(|entry| entry.recipient != *id);
+        self.recipients.len() != before
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN);
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&(self.key_len as u16).to_be_bytes());
+        out.extend_from_slice(&(self.recipients.len() as u16).to_be_bytes());
+        for entry in &self.recipients {
+            out.extend_from_slice(&entry.algorithm.code().to_be_bytes());
+            out.extend_from_slice(&entry.recipient.0);
+            out.extend_from_slice(&(entry.kem_ciphertext.len() as u16).to_be_bytes());
+            out.extend_from_slice(&entry.kem_ciphertext);
+            out.extend_from_slice(&entry.wrapped);
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, KeyWrapError> {
+        let mut rest = data;
+        let mut take = |len: usize| -> Result<&[u8], KeyWrapError> {
+            if rest.len() < len {
+                return Err(KeyWrapError::Malformed("truncated".into()));
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+        let u16_at = |bytes: &[u8]| u16::from_be_bytes([bytes[0], bytes[1]]);
+
+        if take(4)? != MAGIC {
+            return Err(KeyWrapError::Malformed("not a key bundle".into()));
+        }
+        let version = take(1)?[0];
+        if version != VERSION {
+            return Err(KeyWrapError::Malformed(format!("unsupported version {}", version)));
+        }
+        let key_len = u16_at(take(2)?) as usize;
+        check_key_len(key_len).map_err(|_| KeyWrapError::Malformed("empty data key".into()))?;
+        let count = u16_at(take(2)?) as usize;
+        let mut recipients = Vec::with_capacity(count.min(64));
+        for _ in 0..count {
+            let algorithm = AlgorithmId::from_code(u16_at(take(2)?))?;
+            if algorithm.kind() != AlgorithmKind::Kem {
+                return Err(KeyWrapError::Malformed(format!("{} is not a KEM", algorithm.name())));
+            }
+            let recipient = KeyId(take(KEY_ID_LEN)?.try_into().expect("fixed length"));
+            let ciphertext_len = u16_at(take(2)?) as usize;
+            let kem_ciphertext = take(ciphertext_len)?.to_vec();
+            let wrapped = take(wrapped_len(key_len))?.to_vec();
+            recipients.push(WrappedKey { recipient, algorithm, kem_ciphertext, wrapped });
+        }
+        if !rest.is_empty() {
+            return Err(KeyWrapError::Malformed("trailing bytes".into()));
+        }
+        Ok(WrappedKeyBundle { key_len, recipients })
+    }
+}
+
+/// Bundles serialize as their encoded bytes.
+#[cfg(feature = "serde")]
+impl serde::Serialize for WrappedKeyBundle {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serialization::bytes::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for WrappedKeyBundle {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::serialization::bytes::deserialize(deserializer)?;
+        WrappedKeyBundle::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "kyber")]
+    fn test_encoding_size() {
+        let (pk, sk) = registry::keygen(AlgorithmId::MlKem512).unwrap();
+        let bundle = wrap_key(&[7u8; 32], &[PublicKeyRef::new(AlgorithmId::MlKem512, &pk)]).unwrap();
+        let bytes = bundle.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 2 + KEY_ID_LEN + 2 + 768 + 40);
+        let decoded = WrappedKeyBundle::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, bundle);
+        assert_eq!(*unwrap_key(&sk, &decoded).unwrap(), [7u8; 32]);
+        assert!(WrappedKeyBundle::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
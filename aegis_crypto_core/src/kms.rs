@@ -0,0 +1,479 @@
+//! KMS-style key service over a [`KeyStore`].
+//!
+//! The [`Kms`] trait offers the operations of a cloud key management
+//! service: data keys, encryption and re-encryption under a stored KEM
+//! key, and signing with a stored signature key. [`LocalKms`] implements
+//! it in-process over any keystore, so services written against the trait
+//! can be tested without a cloud dependency. Every call is reported to an
+//! [`AuditSink`].
+//!
+//! Ciphertexts are self-contained blobs that name their key:
+//!
+//! ```text
+//! blob := "AEKM" version:u8 algorithm:u16be key_id[16] ciphertext_len:u16be
+//!         kem_ciphertext nonce[12] aes_gcm_ciphertext
+//! ```
+//!
+//! The AES-256-GCM key is HKDF-SHA256 of the KEM shared secret. The
+//! associated data is everything before the AEAD ciphertext followed by
+//! the encoded [`EncryptionContext`]. The context is not stored in the
+//! blob: decryption needs the same context the caller encrypted with.
+//!
+//! [`encrypt_envelope`] and [`decrypt_envelope`] build envelope encryption
+//! on top of any [`Kms`]: data is sealed under a fresh data key and stored
+//! with the data key's KMS ciphertext.
+
+use std::collections::BTreeMap;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use aes_gcm::aead::{ Aead, Payload };
+use aes_gcm::{ Aes256Gcm, KeyInit, Nonce };
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::container::KEY_ID_LEN;
+use crate::keystore::{ KeyId, KeyMetadata, KeyOptions, KeyStore, KeyStoreError, KeyUsage };
+use crate::registry::{ self, AlgorithmError, AlgorithmId };
+
+const BLOB_MAGIC: &[u8; 4] = b"AEKM";
+const ENVELOPE_MAGIC: &[u8; 4] = b"AEKE";
+const VERSION: u8 = 1;
+const NONCE_LEN: usize = 12;
+const KEY_INFO: &[u8] = b"aegis-kms-v1";
+
+/// Largest data key [`Kms::generate_data_key`] produces, in bytes.
+pub const MAX_DATA_KEY_LEN: usize = 1024;
+
+/// Non-secret key-value pairs bound to a ciphertext.
+pub type EncryptionContext = BTreeMap<String, String>;
+
+/// KMS error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KmsError {
+    Malformed(String),
+    /// Requested data key length outside `1..=MAX_DATA_KEY_LEN`.
+    DataKeyLength(usize),
+    /// Wrong key or encryption context, or a modified ciphertext.
+    DecryptionFailed,
+    KeyStore(KeyStoreError),
+    Algorithm(AlgorithmError),
+    /// The audit sink could not record the operation.
+    Audit(String),
+}
+
+impl std::fmt::Display for KmsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KmsError::Malformed(msg) => write!(f, "Malformed KMS ciphertext: {}", msg),
+            KmsError::DataKeyLength(len) => write!(f, "Invalid data key length {}", len),
+            KmsError::DecryptionFailed => write!(f, "Decryption failed"),
+            KmsError::KeyStore(e) => write!(f, "{}", e),
+            KmsError::Algorithm(e) => write!(f, "{}", e),
+            KmsError::Audit(msg) => write!(f, "Audit failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for KmsError {}
+
+impl From<KeyStoreError> for KmsError {
+    fn from(e: KeyStoreError) -> Self {
+        KmsError::KeyStore(e)
+    }
+}
+
+impl From<AlgorithmError> for KmsError {
+    fn from(e: AlgorithmError) -> Self {
+        KmsError::Algorithm(e)
+    }
+}
+
+/// A new data key, in plaintext and encrypted under a KMS key.
+pub struct DataKey {
+    pub plaintext: Zeroizing<Vec<u8>>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// A key management service.
+pub trait Kms {
+    /// Generate a random data key of `len` bytes and encrypt it under `key`.
+    fn generate_data_key(&mut self, key: &KeyId, len: usize, context: &EncryptionContext) -> Result<DataKey, KmsError>;
+
+    fn encrypt(&mut self, key: &KeyId, plaintext: &[u8], context: &EncryptionContext) -> Result<Vec<u8>, KmsError>;
+
+    /// Decrypt with the key named in the ciphertext.
+    fn decrypt(&mut self, ciphertext: &[u8], context: &EncryptionContext) -> Result<Zeroizing<Vec<u8>>, KmsError>;
+
+    /// Move a ciphertext to another key or context without returning the
+    /// plaintext to the caller.
+    fn re_encrypt(
+        &mut self,
+        ciphertext: &[u8],
+        context: &EncryptionContext,
+        destination: &KeyId,
+        destination_context: &EncryptionContext
+    ) -> Result<Vec<u8>, KmsError>;
+
+    fn sign(&mut self, key: &KeyId, message: &[u8]) -> Result<Vec<u8>, KmsError>;
+
+    fn verify(&mut self, key: &KeyId, message: &[u8], signature: &[u8]) -> Result<bool, KmsError>;
+}
+
+/// The operation an [`AuditRecord`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KmsOperation {
+    CreateKey,
+    GenerateDataKey,
+    Encrypt,
+    Decrypt,
+    ReEncrypt,
+    Sign,
+    Verify,
+}
+
+impl KmsOperation {
+    pub fn name(self) -> &'static str {
+        match self {
+            KmsOperation::CreateKey => "CreateKey",
+            KmsOperation::GenerateDataKey => "GenerateDataKey",
+            KmsOperation::Encrypt => "Encrypt",
+            KmsOperation::Decrypt => "Decrypt",
+            KmsOperation::ReEncrypt => "ReEncrypt",
+            KmsOperation::Sign => "Sign",
+            KmsOperation::Verify => "Verify",
+        }
+    }
+}
+
+/// One KMS call, successful or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// Seconds since the Unix epoch.
+    pub time: u64,
+    pub operation: KmsOperation,
+    /// The keys involved: for `ReEncrypt`, the source and then the
+    /// destination. Empty if a ciphertext could not be parsed.
+    pub keys: Vec<KeyId>,
+    pub context: EncryptionContext,
+    pub error: Option<KmsError>,
+}
+
+/// Where a [`LocalKms`] reports its calls. An operation fails if its
+/// record cannot be written.
+pub trait AuditSink {
+    fn record(&mut self, record: AuditRecord) -> Result<(), KmsError>;
+}
+
+impl AuditSink for Vec<AuditRecord> {
+    fn record(&mut self, record: AuditRecord) -> Result<(), KmsError> {
+        self.push(record);
+        Ok(())
+    }
+}
+
+/// An in-process [`Kms`] over a keystore.
+pub struct LocalKms<S: KeyStore, A: AuditSink = Vec<AuditRecord>> {
+    store: S,
+    audit: A,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Length-prefixed pairs in key order.
+fn encode_context(context: &EncryptionContext) -> Vec<u8> {
+    let mut out = (context.len() as u32).to_be_bytes().to_vec();
+    for (key, value) in context {
+        for text in [key, value] {
+            out.extend_from_slice(&(text.len() as u32).to_be_bytes());
+            out.extend_from_slice(text.as_bytes());
+        }
+    }
+    out
+}
+
+fn blob_cipher(shared_secret: &[u8]) -> Aes256Gcm {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Hkdf::<Sha256>::new(None, shared_secret)
+        .expand(KEY_INFO, &mut key[..])
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Aes256Gcm::new_from_slice(&key[..]).expect("32-byte key")
+}
+
+/// A parsed KMS ciphertext.
+struct Blob<'a> {
+    key: KeyId,
+    kem_ciphertext: &'a [u8],
+    nonce: &'a [u8],
+    header: &'a [u8],
+    sealed: &'a [u8],
+}
+
+impl<'a> Blob<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, KmsError> {
+        let fixed = 4 + 1 + 2 + KEY_ID_LEN + 2;
+        if data.len() < fixed || &data[..4] != BLOB_MAGIC {
+            return Err(KmsError::Malformed("not a KMS ciphertext".into()));
+        }
+        if data[4] != VERSION {
+            return Err(KmsError::Malformed(format!("unsupported version {}", data[4])));
+        }
+        let key = KeyId(data[7..7 + KEY_ID_LEN].try_into().expect("fixed length"));
+        let ciphertext_len = u16::from_be_bytes([data[fixed - 2], data[fixed - 1]]) as usize;
+        let header_len = fixed + ciphertext_len + NONCE_LEN;
+        if data.len() < header_len {
+            return Err(KmsError::Malformed("truncated".into()));
+        }
+        Ok(Blob {
+            key,
+            kem_ciphertext: &data[fixed..fixed + ciphertext_len],
+            nonce: &data[fixed + ciphertext_len..header_len],
+            header: &data[..header_len],
+            sealed: &data[header_len..],
+        })
+    }
+}
+
+impl<S: KeyStore> LocalKms<S> {
+    /// A KMS that keeps its audit trail in memory.
+    pub fn new(store: S) -> Self {
+        LocalKms { store, audit: Vec::new() }
+    }
+}
+
+impl<S: KeyStore, A: AuditSink> LocalKms<S, A> {
+    pub fn with_audit(store: S, audit: A) -> Self {
+        LocalKms { store, audit }
+    }
+
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn audit(&self) -> &A {
+        &self.audit
+    }
+
+    pub fn into_parts(self) -> (S, A) {
+        (self.store, self.audit)
+    }
+
+    /// Create a key in the store.
+    pub fn create_key(&mut self, algorithm: AlgorithmId, options: KeyOptions) -> Result<KeyMetadata, KmsError> {
+        let result = self.store.generate(algorithm, options).map_err(KmsError::from);
+        let keys = result.as_ref().map(|key| vec![key.id]).unwrap_or_default();
+        self.audited(KmsOperation::CreateKey, keys, &EncryptionContext::new(), result)
+    }
+
+    /// Report an operation, and fail it if the report cannot be written.
+    fn audited<T>(
+        &mut self,
+        operation: KmsOperation,
+        keys: Vec<KeyId>,
+        context: &EncryptionContext,
+        result: Result<T, KmsError>
+    ) -> Result<T, KmsError> {
+        self.audit.record(AuditRecord {
+            time: now(),
+            operation,
+            keys,
+            context: context.clone(),
+            error: result.as_ref().err().cloned(),
+        })?;
+        result
+    }
+
+    /// Check `key` exists, is unexpired and permits `usage`.
+    fn usable(&self, key: &KeyId, usage: KeyUsage) -> Result<KeyMetadata, KmsError> {
+        let metadata = self.store.metadata(key)?;
+        if metadata.is_expired(now()) {
+            return Err(KeyStoreError::Expired(*key).into());
+        }
+        if !metadata.usage.contains(usage) {
+            return Err(KeyStoreError::UsageNotPermitted(*key).into());
+        }
+        Ok(metadata)
+    }
+
+    fn seal(&self, key: &KeyId, plaintext: &[u8], context: &EncryptionContext) -> Result<Vec<u8>, KmsError> {
+        let algorithm = self.usable(key, KeyUsage::DECAPSULATE)?.algorithm;
+        let public_key = self.store.public_key(key)?;
+        let (kem_ciphertext, shared_secret) = registry::encapsulate(algorithm, &public_key)?;
+        let shared_secret = Zeroizing::new(shared_secret);
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::fill(&mut nonce).expect("RNG failed");
+
+        let mut blob = Vec::with_capacity(4 + 1 + 2 + KEY_ID_LEN + 2 + kem_ciphertext.len() + NONCE_LEN + plaintext.len() + 16);
+        blob.extend_from_slice(BLOB_MAGIC);
+        blob.push(VERSION);
+        blob.extend_from_slice(&algorithm.code().to_be_bytes());
+        blob.extend_from_slice(&key.0);
+        blob.extend_from_slice(&(kem_ciphertext.len() as u16).to_be_bytes());
+        blob.extend_from_slice(&kem_ciphertext);
+        blob.extend_from_slice(&nonce);
+        let aad = [&blob[..], &encode_context(context)].concat();
+        let sealed = blob_cipher(&shared_secret)
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+            .map_err(|_| KmsError::Malformed("encryption failed".into()))?;
+        blob.extend_from_slice(&sealed);
+        Ok(blob)
+    }
+
+    fn open(&self, blob: &Blob, context: &EncryptionContext) -> Result<Zeroizing<Vec<u8>>, KmsError> {
+        self.usable(&blob.key, KeyUsage::DECAPSULATE)?;
+        let shared_secret = self.store.decapsulate(&blob.key, blob.kem_ciphertext).map_err(|e| match e {
+            KeyStoreError::Algorithm(_) => KmsError::DecryptionFailed,
+            other => other.into(),
+        })?;
+        let aad = [blob.header, &encode_context(context)].concat();
+        blob_cipher(&shared_secret)
+            .decrypt(Nonce::from_slice(blob.nonce), Payload { msg: blob.sealed, aad: &aad })
+            .map(Zeroizing::new)
+            .map_err(|_| KmsError::DecryptionFailed)
+    }
+}
+
+impl<S: KeyStore, A: AuditSink> Kms for LocalKms<S, A> {
+    fn generate_data_key(&mut self, key: &KeyId, len: usize, context: &EncryptionContext) -> Result<DataKey, KmsError> {
+        let result = if (1..=MAX_DATA_KEY_LEN).contains(&len) {
+            let mut plaintext = Zeroizing::new(vec![0u8; len]);
+            getrandom::fill(&mut plaintext).expect("RNG failed");
+            self.seal(key, &plaintext, context).map(|ciphertext| DataKey { plaintext, ciphertext })
+        } else {
+            Err(KmsError::DataKeyLength(len))
+        };
+        self.audited(KmsOperation::GenerateDataKey, vec![*key], context, result)
+    }
+
+    fn encrypt(&mut self, key: &KeyId, plaintext: &[u8], context: &EncryptionContext) -> Result<Vec<u8>, KmsError> {
+        let result = self.seal(key, plaintext, context);
+        self.audited(KmsOperation::Encrypt, vec![*key], context, result)
+    }
+
+    fn decrypt(&mut self, ciphertext: &[u8], context: &EncryptionContext) -> Result<Zeroizing<Vec<u8>>, KmsError> {
+        let (keys, result) = match Blob::parse(ciphertext) {
+            Ok(blob) => (vec![blob.key], self.open(&blob, context)),
+            Err(e) => (Vec::new(), Err(e)),
+        };
+        self.audited(KmsOperation::Decrypt, keys, context, result)
+    }
+
+    fn re_encrypt(
+        &mut self,
+        ciphertext: &[u8],
+        context: &EncryptionContext,
+        destination: &KeyId,
+        destination_context: &EncryptionContext
+    ) -> Result<Vec<u8>, KmsError> {
+        let (keys, result) = match Blob::parse(ciphertext) {
+            Ok(blob) => {
+                let result = self
+                    .open(&blob, context)
+                    .and_then(|plaintext| self.seal(destination, &plaintext, destination_context));
+                (vec![blob.key, *destination], result)
+            }
+            Err(e) => (vec![*destination], Err(e)),
+        };
+        // Both contexts go on record, the destination's under "dest:".
+        let mut recorded = context.clone();
+        recorded.extend(destination_context.iter().map(|(k, v)| (format!("dest:{}", k), v.clone())));
+        self.audited(KmsOperation::ReEncrypt, keys, &recorded, result)
+    }
+
+    fn sign(&mut self, key: &KeyId, message: &[u8]) -> Result<Vec<u8>, KmsError> {
+        let result = self.store.sign(key, message).map_err(KmsError::from);
+        self.audited(KmsOperation::Sign, vec![*key], &EncryptionContext::new(), result)
+    }
+
+    fn verify(&mut self, key: &KeyId, message: &[u8], signature: &[u8]) -> Result<bool, KmsError> {
+        let result = self
+            .usable(key, KeyUsage::SIGN)
+            .and_then(|metadata| {
+                let public_key = self.store.public_key(key)?;
+                Ok(registry::verify(metadata.algorithm, &public_key, message, signature))
+            });
+        self.audited(KmsOperation::Verify, vec![*key], &EncryptionContext::new(), result)
+    }
+}
+
+/// Encrypt `plaintext` under a fresh 32-byte data key from `kms`.
+///
+/// ```text
+/// envelope := "AEKE" version:u8 key_len:u32be encrypted_data_key
+///             nonce[12] aes_gcm_ciphertext
+/// ```
+pub fn encrypt_envelope<K: Kms + ?Sized>(
+    kms: &mut K,
+    key: &KeyId,
+    plaintext: &[u8],
+    context: &EncryptionContext
+) -> Result<Vec<u8>, KmsError> {
+    let data_key = kms.generate_data_key(key, 32, context)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).expect("RNG failed");
+
+    let mut envelope = Vec::with_capacity(9 + data_key.ciphertext.len() + NONCE_LEN + plaintext.len() + 16);
+    envelope.extend_from_slice(ENVELOPE_MAGIC);
+    envelope.push(VERSION);
+    envelope.extend_from_slice(&(data_key.ciphertext.len() as u32).to_be_bytes());
+    envelope.extend_from_slice(&data_key.ciphertext);
+    envelope.extend_from_slice(&nonce);
+    let aad = [&envelope[..], &encode_context(context)].concat();
+    let sealed = Aes256Gcm::new_from_slice(&data_key.plaintext)
+        .expect("32-byte key")
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+        .map_err(|_| KmsError::Malformed("encryption failed".into()))?;
+    envelope.extend_from_slice(&sealed);
+    Ok(envelope)
+}
+
+/// Decrypt an envelope from [`encrypt_envelope`].
+pub fn decrypt_envelope<K: Kms + ?Sized>(
+    kms: &mut K,
+    envelope: &[u8],
+    context: &EncryptionContext
+) -> Result<Vec<u8>, KmsError> {
+    if envelope.len() < 9 || &envelope[..4] != ENVELOPE_MAGIC {
+        return Err(KmsError::Malformed("not a KMS envelope".into()));
+    }
+    if envelope[4] != VERSION {
+        return Err(KmsError::Malformed(format!("unsupported version {}", envelope[4])));
+    }
+    let key_len = u32::from_be_bytes(envelope[5..9].try_into().unwrap()) as usize;
+    let header_len = 9usize.saturating_add(key_len).saturating_add(NONCE_LEN);
+    if envelope.len() < header_len {
+        return Err(KmsError::Malformed("truncated".into()));
+    }
+    let data_key = kms.decrypt(&envelope[9..9 + key_len], context)?;
+    if data_key.len() != 32 {
+        return Err(KmsError::DecryptionFailed);
+    }
+    let aad = [&envelope[..header_len], &encode_context(context)].concat();
+    Aes256Gcm::new_from_slice(&data_key)
+        .expect("32-byte key")
+        .decrypt(Nonce::from_slice(&envelope[header_len - NONCE_LEN..header_len]), Payload {
+            msg: &envelope[header_len..],
+            aad: &aad,
+        })
+        .map_err(|_| KmsError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_encoding_is_unambiguous() {
+        let split = |pairs: &[(&str, &str)]| {
+            encode_context(&pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+        };
+        assert_ne!(split(&[("ab", "c")]), split(&[("a", "bc")]));
+        assert_ne!(split(&[("a", "")]), split(&[]));
+        assert_eq!(split(&[("x", "1"), ("a", "2")]), split(&[("a", "2"), ("x", "1")]));
+    }
+}
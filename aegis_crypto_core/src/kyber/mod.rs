@@ -42,6 +42,16 @@ pub struct KyberKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    KyberKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_mlkem::mlkem512,
+        pqcrypto_mlkem::mlkem768,
+        pqcrypto_mlkem::mlkem1024
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl KyberKeyPair {
     /// Returns the public key component of the Kyber key pair.
@@ -68,6 +78,16 @@ pub struct KyberEncapsulated {
     shared_secret: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    KyberEncapsulated { ciphertext as ciphertext, shared_secret as shared_secret },
+    crate::serialization::encapsulated_lengths!(
+        pqcrypto_mlkem::mlkem512,
+        pqcrypto_mlkem::mlkem768,
+        pqcrypto_mlkem::mlkem1024
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl KyberEncapsulated {
     /// Returns the ciphertext generated during encapsulation.
@@ -14,7 +14,10 @@ pub struct Kyber768;
 
 /// Kyber public key wrapper.
 #[derive(Clone)]
-pub struct KyberPublicKey(pub Vec<u8>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KyberPublicKey(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))] pub Vec<u8>,
+);
 
 /// Kyber secret key wrapper.
 #[derive(Clone)]
@@ -22,12 +25,26 @@ pub struct KyberSecretKey(pub Vec<u8>);
 
 /// Kyber ciphertext wrapper.
 #[derive(Clone)]
-pub struct KyberCiphertext(pub Vec<u8>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KyberCiphertext(
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::bytes"))] pub Vec<u8>,
+);
 
 /// Kyber shared secret wrapper.
 #[derive(Clone)]
 pub struct KyberSharedSecret(pub Vec<u8>);
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    KyberSecretKey { 0 as secret_key },
+    [[pqcrypto_mlkem::mlkem768::secret_key_bytes()]]
+);
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    KyberSharedSecret { 0 as shared_secret },
+    [[pqcrypto_mlkem::mlkem768::shared_secret_bytes()]]
+);
+
 impl AsRef<[u8]> for KyberPublicKey {
     fn as_ref(&self) -> &[u8] {
         &self.0
@@ -48,6 +48,37 @@ pub mod hash;
 pub mod utils;
 pub mod performance;
 pub mod blockchain;
+pub mod registry;
+pub mod container;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod multiformats;
+pub mod did;
+#[cfg(feature = "jose")]
+pub mod jose;
+#[cfg(feature = "vc")]
+pub mod vc;
+pub mod handshake;
+pub mod noise;
+pub mod messaging;
+pub mod stream;
+pub mod age;
+pub mod pake;
+pub mod multisig;
+pub mod sss;
+pub mod hbs;
+pub mod merkle;
+pub mod keystore;
+pub mod policy;
+pub mod keywrap;
+pub mod kms;
+pub mod audit;
+pub mod evm;
+pub mod hd;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+#[cfg(all(feature = "kyber", feature = "dilithium"))]
+pub mod openpgp;
 
 // The `js_bindings` module exposes a JavaScript‑friendly API on top of the
 // low‑level functions.  It is compiled unconditionally when building the
@@ -58,8 +89,8 @@ pub mod js_bindings;
 // `python-bindings` feature is enabled.  See `Cargo.toml` for more
 // details.  The module contains PyO3 wrappers that expose the
 // algorithms to Python as a native extension.
-// #[cfg(feature = "python-bindings")]
-// pub mod python_bindings;
+#[cfg(feature = "python-bindings")]
+pub mod python_bindings;
 
 #[cfg(feature = "kyber")]
 pub use kyber::*;
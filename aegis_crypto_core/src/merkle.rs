@@ -0,0 +1,455 @@
+//! Merkle tree commitments, inclusion and consistency proofs and signed
+//! tree heads.
+//!
+//! Trees follow RFC 9162 (Certificate Transparency 2.0): leaves hash as
+//! `H(0x00 ‖ data)`, interior nodes as `H(0x01 ‖ left ‖ right)`, and a
+//! tree of `n` leaves splits at the largest power of two below `n`.
+//! Inclusion and consistency proofs use the RFC 9162 algorithms. The hash
+//! is SHA3-256, BLAKE3 or Keccak-256, or SHA-256 for compatibility with
+//! Certificate Transparency logs.
+//!
+//! A [`MerkleLog`] is append-only. Its head can be signed with any
+//! registry signature algorithm to produce a [`SignedTreeHead`]:
+//!
+//! ```text
+//! sth     := "AEST" version:u8 hash:u8 algorithm:u16be tree_size:u64be
+//!            timestamp:u64be root[32] length:u32be signature
+//! message := "aegis-merkle-sth-v1" hash:u8 tree_size:u64be
+//!            timestamp:u64be root[32]
+//! ```
+
+use sha2::Sha256;
+use sha3::{ Digest, Keccak256, Sha3_256 };
+use subtle::ConstantTimeEq;
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+const STH_MAGIC: &[u8; 4] = b"AEST";
+const VERSION: u8 = 1;
+const STH_LABEL: &[u8] = b"aegis-merkle-sth-v1";
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub const HASH_LEN: usize = 32;
+
+pub type Hash = [u8; HASH_LEN];
+
+/// Merkle tree error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleError {
+    Malformed(String),
+    /// The leaf index is not below the tree size.
+    IndexOutOfRange {
+        index: u64,
+        tree_size: u64,
+    },
+    /// The requested tree sizes are not `0 < old <= new <= len`.
+    InvalidRange(String),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MerkleError::Malformed(msg) => write!(f, "Malformed Merkle data: {}", msg),
+            MerkleError::IndexOutOfRange { index, tree_size } => {
+                write!(f, "Leaf {} is outside a tree of {} leaves", index, tree_size)
+            }
+            MerkleError::InvalidRange(msg) => write!(f, "Invalid tree size: {}", msg),
+            MerkleError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MerkleError {}
+
+impl From<AlgorithmError> for MerkleError {
+    fn from(e: AlgorithmError) -> Self {
+        MerkleError::Algorithm(e)
+    }
+}
+
+/// The hash function of a tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MerkleHash {
+    Sha3_256 = 1,
+    Blake3 = 2,
+    Keccak256 = 3,
+    Sha256 = 4,
+}
+
+impl MerkleHash {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Result<Self, MerkleError> {
+        match code {
+            1 => Ok(MerkleHash::Sha3_256),
+            2 => Ok(MerkleHash::Blake3),
+            3 => Ok(MerkleHash::Keccak256),
+            4 => Ok(MerkleHash::Sha256),
+            _ => Err(MerkleError::Malformed(format!("unknown hash 0x{:02x}", code))),
+        }
+    }
+
+    fn digest(self, parts: &[&[u8]]) -> Hash {
+        match self {
+            MerkleHash::Sha3_256 => {
+                let mut hasher = Sha3_256::new();
+                parts.iter().for_each(|part| hasher.update(part));
+                hasher.finalize().into()
+            }
+            MerkleHash::Keccak256 => {
+                let mut hasher = Keccak256::new();
+                parts.iter().for_each(|part| hasher.update(part));
+                hasher.finalize().into()
+            }
+            MerkleHash::Sha256 => {
+                let mut hasher = Sha256::new();
+                parts.iter().for_each(|part| hasher.update(part));
+                hasher.finalize().into()
+            }
+            MerkleHash::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                parts.iter().for_each(|part| {
+                    hasher.update(part);
+                });
+                hasher.finalize().into()
+            }
+        }
+    }
+
+    /// Root of the empty tree: the hash of the empty string.
+    pub fn empty_root(self) -> Hash {
+        self.digest(&[])
+    }
+
+    pub fn leaf_hash(self, data: &[u8]) -> Hash {
+        self.digest(&[&[LEAF_PREFIX], data])
+    }
+
+    pub fn node_hash(self, left: &Hash, right: &Hash) -> Hash {
+        self.digest(&[&[NODE_PREFIX], left, right])
+    }
+}
+
+/// Largest power of two strictly below `n` (`n >= 2`).
+fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// An append-only log of leaf hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleLog {
+    hash: MerkleHash,
+    leaves: Vec<Hash>,
+}
+
+impl MerkleLog {
+    pub fn new(hash: MerkleHash) -> Self {
+        MerkleLog { hash, leaves: Vec::new() }
+    }
+
+    /// Commit to a batch of entries in one step.
+    pub fn from_leaves<I, T>(hash: MerkleHash, entries: I) -> Self where I: IntoIterator<Item = T>, T: AsRef<[u8]> {
+        let leaves = entries
+            .into_iter()
+            .map(|entry| hash.leaf_hash(entry.as_ref()))
+            .collect();
+        MerkleLog { hash, leaves }
+    }
+
+    pub fn hash(&self) -> MerkleHash {
+        self.hash
+    }
+
+    /// Append an entry and return its leaf index.
+    pub fn append(&mut self, entry: &[u8]) -> u64 {
+        self.leaves.push(self.hash.leaf_hash(entry));
+        (self.leaves.len() - 1) as u64
+    }
+
+    pub fn len(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn leaf_hash(&self, index: u64) -> Option<&Hash> {
+        self.leaves.get(index as usize)
+    }
+
+    pub fn root(&self) -> Hash {
+        self.subtree_root(&self.leaves)
+    }
+
+    /// Root of the tree as it was when it held `tree_size` leaves.
+    pub fn root_at(&self, tree_size: u64) -> Result<Hash, MerkleError> {
+        if tree_size > self.len() {
+            return Err(MerkleError::InvalidRange(format!("log has only {} leaves", self.len())));
+        }
+        Ok(self.subtree_root(&self.leaves[..tree_size as usize]))
+    }
+
+    fn subtree_root(&self, leaves: &[Hash]) -> Hash {
+        match leaves.len() {
+            0 => self.hash.empty_root(),
+            1 => leaves[0],
+            n => {
+                let (left, right) = leaves.split_at(split(n));
+                self.hash.node_hash(&self.subtree_root(left), &self.subtree_root(right))
+            }
+        }
+    }
+
+    /// Prove leaf `index` is in the tree of the first `tree_size` leaves.
+    pub fn inclusion_proof(&self, index: u64, tree_size: u64) -> Result<InclusionProof, MerkleError> {
+        if tree_size > self.len() {
+            return Err(MerkleError::InvalidRange(format!("log has only {} leaves", self.len())));
+        }
+        if index >= tree_size {
+            return Err(MerkleError::IndexOutOfRange { index, tree_size });
+        }
+        let mut path = Vec::new();
+        self.path(index as usize, &self.leaves[..tree_size as usize], &mut path);
+        Ok(InclusionProof { leaf_index: index, tree_size, path })
+    }
+
+    /// RFC 9162 Section 2.1.3.1 `PATH(m, D[n])`.
+    fn path(&self, m: usize, leaves: &[Hash], out: &mut Vec<Hash>) {
+        let n = leaves.len();
+        if n <= 1 {
+            return;
+        }
+        let k = split(n);
+        if m < k {
+            self.path(m, &leaves[..k], out);
+            out.push(self.subtree_root(&leaves[k..]));
+        } else {
+            self.path(m - k, &leaves[k..], out);
+            out.push(self.subtree_root(&leaves[..k]));
+        }
+    }
+
+    /// Prove the tree of `old_size` leaves is a prefix of the tree of
+    /// `new_size` leaves.
+    pub fn consistency_proof(&self, old_size: u64, new_size: u64) -> Result<ConsistencyProof, MerkleError> {
+        if old_size == 0 || old_size > new_size || new_size > self.len() {
+            return Err(MerkleError::InvalidRange(format!("{} to {} of {} leaves", old_size, new_size, self.len())));
+        }
+        let mut path = Vec::new();
+        self.subproof(old_size as usize, &self.leaves[..new_size as usize], true, &mut path);
+        Ok(ConsistencyProof { old_size, new_size, path })
+    }
+
+    /// RFC 9162 Section 2.1.4.1 `SUBPROOF(m, D[n], b)`.
+    fn subproof(&self, m: usize, leaves: &[Hash], complete: bool, out: &mut Vec<Hash>) {
+        let n = leaves.len();
+        if m == n {
+            if !complete {
+                out.push(self.subtree_root(leaves));
+            }
+            return;
+        }
+        let k = split(n);
+        if m <= k {
+            self.subproof(m, &leaves[..k], complete, out);
+            out.push(self.subtree_root(&leaves[k..]));
+        } else {
+            self.subproof(m - k, &leaves[k..], false, out);
+            out.push(self.subtree_root(&leaves[..k]));
+        }
+    }
+
+    /// Sign the current head with a registry signature key.
+    pub fn sign_tree_head(
+        &self,
+        algorithm: AlgorithmId,
+        secret_key: &[u8],
+        timestamp: u64
+    ) -> Result<SignedTreeHead, MerkleError> {
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        let mut head = SignedTreeHead {
+            hash: self.hash,
+            algorithm,
+            tree_size: self.len(),
+            timestamp,
+            root: self.root(),
+            signature: Vec::new(),
+        };
+        head.signature = registry::sign(algorithm, secret_key, &head.message())?;
+        Ok(head)
+    }
+}
+
+/// Proof that a leaf is included in a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub leaf_index: u64,
+    pub tree_size: u64,
+    pub path: Vec<Hash>,
+}
+
+impl InclusionProof {
+    /// Check that `entry` is leaf `leaf_index` of the tree with `root`.
+    pub fn verify(&self, hash: MerkleHash, entry: &[u8], root: &Hash) -> bool {
+        self.verify_leaf_hash(hash, &hash.leaf_hash(entry), root)
+    }
+
+    /// RFC 9162 Section 2.1.3.2.
+    pub fn verify_leaf_hash(&self, hash: MerkleHash, leaf_hash: &Hash, root: &Hash) -> bool {
+        if self.leaf_index >= self.tree_size {
+            return false;
+        }
+        let (mut f_n, mut s_n) = (self.leaf_index, self.tree_size - 1);
+        let mut r = *leaf_hash;
+        for p in &self.path {
+            if s_n == 0 {
+                return false;
+            }
+            if f_n & 1 == 1 || f_n == s_n {
+                r = hash.node_hash(p, &r);
+                while f_n & 1 == 0 && f_n != 0 {
+                    f_n >>= 1;
+                    s_n >>= 1;
+                }
+            } else {
+                r = hash.node_hash(&r, p);
+            }
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+        s_n == 0 && bool::from(r.ct_eq(root))
+    }
+}
+
+/// Proof that one tree is an append-only extension of another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyProof {
+    pub old_size: u64,
+    pub new_size: u64,
+    pub path: Vec<Hash>,
+}
+
+impl ConsistencyProof {
+    /// RFC 9162 Section 2.1.4.2.
+    pub fn verify(&self, hash: MerkleHash, old_root: &Hash, new_root: &Hash) -> bool {
+        if self.old_size == 0 || self.old_size > self.new_size {
+            return false;
+        }
+        if self.old_size == self.new_size {
+            return self.path.is_empty() && bool::from(old_root.ct_eq(new_root));
+        }
+        let mut path = self.path.iter();
+        let (mut f_n, mut s_n) = (self.old_size - 1, self.new_size - 1);
+        let first = if self.old_size.is_power_of_two() { Some(old_root) } else { path.next() };
+        let Some(&first) = first else {
+            return false;
+        };
+        while f_n & 1 == 1 {
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+        let (mut f_r, mut s_r) = (first, first);
+        for c in path {
+            if s_n == 0 {
+                return false;
+            }
+            if f_n & 1 == 1 || f_n == s_n {
+                f_r = hash.node_hash(c, &f_r);
+                s_r = hash.node_hash(c, &s_r);
+                while f_n & 1 == 0 && f_n != 0 {
+                    f_n >>= 1;
+                    s_n >>= 1;
+                }
+            } else {
+                s_r = hash.node_hash(&s_r, c);
+            }
+            f_n >>= 1;
+            s_n >>= 1;
+        }
+        s_n == 0 && bool::from(f_r.ct_eq(old_root)) && bool::from(s_r.ct_eq(new_root))
+    }
+}
+
+/// A log head signed by the log operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedTreeHead {
+    pub hash: MerkleHash,
+    pub algorithm: AlgorithmId,
+    pub tree_size: u64,
+    /// Caller-defined time of signing, e.g. Unix milliseconds.
+    pub timestamp: u64,
+    pub root: Hash,
+    pub signature: Vec<u8>,
+}
+
+impl SignedTreeHead {
+    fn message(&self) -> Vec<u8> {
+        let mut message = STH_LABEL.to_vec();
+        message.push(self.hash.code());
+        message.extend_from_slice(&self.tree_size.to_be_bytes());
+        message.extend_from_slice(&self.timestamp.to_be_bytes());
+        message.extend_from_slice(&self.root);
+        message
+    }
+
+    pub fn verify(&self, public_key: &[u8]) -> bool {
+        registry::verify(self.algorithm, public_key, &self.message(), &self.signature)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64 + self.signature.len());
+        out.extend_from_slice(STH_MAGIC);
+        out.push(VERSION);
+        out.push(self.hash.code());
+        out.extend_from_slice(&self.algorithm.code().to_be_bytes());
+        out.extend_from_slice(&self.tree_size.to_be_bytes());
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        out.extend_from_slice(&self.root);
+        out.extend_from_slice(&(self.signature.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MerkleError> {
+        const HEADER_LEN: usize = 4 + 1 + 1 + 2 + 8 + 8 + HASH_LEN + 4;
+        if data.len() < HEADER_LEN || &data[..4] != STH_MAGIC {
+            return Err(MerkleError::Malformed("not a signed tree head".into()));
+        }
+        if data[4] != VERSION {
+            return Err(MerkleError::Malformed(format!("unsupported version {}", data[4])));
+        }
+        let hash = MerkleHash::from_code(data[5])?;
+        let algorithm = AlgorithmId::from_code(u16::from_be_bytes([data[6], data[7]]))?;
+        let tree_size = u64::from_be_bytes(data[8..16].try_into().unwrap());
+        let timestamp = u64::from_be_bytes(data[16..24].try_into().unwrap());
+        let root = data[24..56].try_into().unwrap();
+        let length = u32::from_be_bytes(data[56..60].try_into().unwrap()) as usize;
+        if data.len() - HEADER_LEN != length {
+            return Err(MerkleError::Malformed("signature length mismatch".into()));
+        }
+        let signature = data[HEADER_LEN..].to_vec();
+        Ok(SignedTreeHead { hash, algorithm, tree_size, timestamp, root, signature })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_shape() {
+        let log = MerkleLog::from_leaves(MerkleHash::Sha3_256, [b"a", b"b", b"c"]);
+        let h = MerkleHash::Sha3_256;
+        let left = h.node_hash(&h.leaf_hash(b"a"), &h.leaf_hash(b"b"));
+        assert_eq!(log.root(), h.node_hash(&left, &h.leaf_hash(b"c")));
+        assert_eq!(MerkleLog::new(h).root(), h.empty_root());
+        assert_eq!((split(2), split(5), split(8), split(9)), (1, 4, 4, 8));
+    }
+}
@@ -0,0 +1,171 @@
+//! Asynchronous end-to-end encrypted messaging sessions.
+//!
+//! Sessions start with a PQXDH-style key agreement against a published
+//! prekey bundle ([`pqxdh`]), then continue with a Double Ratchet
+//! ([`ratchet`]):
+//!
+//! * The bundle holds an identity key (an ML-DSA signing key plus an X25519
+//!   key), a signed X25519 prekey, and signed ML-KEM-768 prekeys.
+//!   One-time KEM prekeys are consumed on first use; a signed last-resort
+//!   KEM prekey is used when they run out.
+//! * The initial secret combines three X25519 DHs with an ML-KEM
+//!   encapsulation, so it stays confidential unless both are broken.
+//! * Every reply performs an X25519 ratchet step. Every
+//!   `pq_ratchet_interval`-th step also mixes in a fresh ML-KEM
+//!   encapsulation (a sparse PQ ratchet), which restores post-quantum
+//!   security after a compromise without adding a KEM to every message.
+//! * Out-of-order messages are decrypted with stored skipped-message keys,
+//!   bounded per chain and in total.
+//!
+//! [`Session::to_bytes`] serializes the full ratchet state, which contains
+//! secret keys; store it encrypted.
+
+pub mod pqxdh;
+pub mod ratchet;
+
+pub use pqxdh::{ IdentityKeyPair, PrekeyBundle, PrekeyStore, PublicIdentity, SignedPrekey, PQXDH_KEM };
+pub use ratchet::{ Session, SessionConfig };
+
+use x25519_dalek::{ PublicKey, StaticSecret };
+
+use crate::registry::AlgorithmError;
+
+/// Messaging error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessagingError {
+    Malformed(String),
+    /// A prekey or identity signature did not verify.
+    BadSignature,
+    /// The initial message names a prekey this store does not hold.
+    UnknownPrekey(u32),
+    /// The initial message came from a different identity than the session.
+    IdentityMismatch,
+    /// More message keys would have to be skipped than the limit allows.
+    TooManySkipped,
+    /// The message key was already used or has been discarded.
+    DuplicateMessage,
+    /// The session has no sending chain yet.
+    NotReady,
+    Decrypt,
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for MessagingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MessagingError::Malformed(msg) => write!(f, "Malformed message: {}", msg),
+            MessagingError::BadSignature => write!(f, "Prekey signature is invalid"),
+            MessagingError::UnknownPrekey(id) => write!(f, "Unknown prekey {}", id),
+            MessagingError::IdentityMismatch => write!(f, "Message is from a different identity"),
+            MessagingError::TooManySkipped => write!(f, "Too many skipped messages"),
+            MessagingError::DuplicateMessage => write!(f, "Duplicate or expired message"),
+            MessagingError::NotReady => write!(f, "Session cannot send yet"),
+            MessagingError::Decrypt => write!(f, "Decryption failed"),
+            MessagingError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MessagingError {}
+
+impl From<AlgorithmError> for MessagingError {
+    fn from(e: AlgorithmError) -> Self {
+        MessagingError::Algorithm(e)
+    }
+}
+
+/// Generate an X25519 key pair as `(secret, public)`.
+pub(crate) fn generate_dh() -> ([u8; 32], [u8; 32]) {
+    let mut secret = [0u8; 32];
+    getrandom::fill(&mut secret).expect("RNG failed");
+    (secret, dh_public(&secret))
+}
+
+pub(crate) fn dh_public(secret: &[u8; 32]) -> [u8; 32] {
+    PublicKey::from(&StaticSecret::from(*secret)).to_bytes()
+}
+
+pub(crate) fn dh(secret: &[u8; 32], public: &[u8; 32]) -> [u8; 32] {
+    StaticSecret::from(*secret).diffie_hellman(&PublicKey::from(*public)).to_bytes()
+}
+
+pub(crate) fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+/// Append a `u32` length-prefixed byte string.
+pub(crate) fn put_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    put_u32(out, data.len() as u32);
+    out.extend_from_slice(data);
+}
+
+pub(crate) fn put_optional(out: &mut Vec<u8>, data: Option<&[u8]>) {
+    match data {
+        Some(data) => {
+            out.push(1);
+            put_bytes(out, data);
+        }
+        None => out.push(0),
+    }
+}
+
+/// Cursor over an encoded message or session.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], MessagingError> {
+        if self.data.len() < len {
+            return Err(MessagingError::Malformed("truncated".into()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn array<const N: usize>(&mut self) -> Result<[u8; N], MessagingError> {
+        Ok(self.take(N)?.try_into().expect("length checked"))
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, MessagingError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, MessagingError> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, MessagingError> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    pub(crate) fn bytes(&mut self) -> Result<&'a [u8], MessagingError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    pub(crate) fn optional(&mut self) -> Result<Option<&'a [u8]>, MessagingError> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.bytes()?)),
+            _ => Err(MessagingError::Malformed("bad optional flag".into())),
+        }
+    }
+
+    pub(crate) fn rest(self) -> &'a [u8] {
+        self.data
+    }
+
+    pub(crate) fn finish(self) -> Result<(), MessagingError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(MessagingError::Malformed("trailing data".into()))
+        }
+    }
+}
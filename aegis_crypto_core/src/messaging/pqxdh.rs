@@ -0,0 +1,474 @@
+//! PQXDH-style initial key agreement against published prekey bundles.
+//!
+//! The initiator computes
+//!
+//! ```text
+//! DH1 = DH(IK_A, SPK_B)   DH2 = DH(EK_A, IK_B)   DH3 = DH(EK_A, SPK_B)
+//! (CT, SS) = ML-KEM.Encaps(PQPK_B)
+//! SK = HKDF-SHA256(0^32, 0xFF^32 || DH1 || DH2 || DH3 || SS, INFO)
+//! ```
+//!
+//! and binds both identities into the associated data of every later
+//! message. `PQPK_B` is a one-time prekey when one is available and the
+//! last-resort prekey otherwise.
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use super::{ dh, generate_dh, put_bytes, put_u32, MessagingError, Reader };
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// KEM used for prekeys and the sparse PQ ratchet.
+pub const PQXDH_KEM: AlgorithmId = AlgorithmId::MlKem768;
+
+const KDF_INFO: &[u8] = b"Aegis_PQXDH_CURVE25519_SHA-256_ML-KEM-768";
+const PREKEY_CONTEXT: &[u8] = b"aegis-pqxdh-prekey";
+const BUNDLE_VERSION: u8 = 1;
+const INITIAL_VERSION: u8 = 1;
+
+const KIND_SIGNED_PREKEY: u8 = 1;
+const KIND_KEM_PREKEY: u8 = 2;
+
+/// Signed prekeys kept after rotation so in-flight bundles still work.
+const RETAINED_SIGNED_PREKEYS: usize = 2;
+
+/// A long-term identity: a signature key plus an X25519 key.
+pub struct IdentityKeyPair {
+    signing_algorithm: AlgorithmId,
+    signing_public: Vec<u8>,
+    signing_secret: Vec<u8>,
+    dh_secret: [u8; 32],
+    dh_public: [u8; 32],
+}
+
+impl IdentityKeyPair {
+    /// Generate a fresh identity signing with `signing_algorithm`.
+    pub fn generate(signing_algorithm: AlgorithmId) -> Result<Self, MessagingError> {
+        if signing_algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(signing_algorithm).into());
+        }
+        let (signing_public, signing_secret) = registry::keygen(signing_algorithm)?;
+        let (dh_secret, dh_public) = generate_dh();
+        Ok(IdentityKeyPair { signing_algorithm, signing_public, signing_secret, dh_secret, dh_public })
+    }
+
+    /// The public half, as published in bundles.
+    pub fn public(&self) -> PublicIdentity {
+        PublicIdentity {
+            signing_algorithm: self.signing_algorithm,
+            signing_key: self.signing_public.clone(),
+            dh_key: self.dh_public,
+        }
+    }
+
+    fn sign_prekey(&self, kind: u8, id: u32, public_key: Vec<u8>) -> Result<SignedPrekey, MessagingError> {
+        let message = prekey_message(kind, id, &public_key);
+        let signature = registry::sign(self.signing_algorithm, &self.signing_secret, &message)?;
+        Ok(SignedPrekey { id, public_key, signature })
+    }
+}
+
+impl Drop for IdentityKeyPair {
+    fn drop(&mut self) {
+        self.signing_secret.zeroize();
+        self.dh_secret.zeroize();
+    }
+}
+
+/// The public half of an [`IdentityKeyPair`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicIdentity {
+    pub signing_algorithm: AlgorithmId,
+    pub signing_key: Vec<u8>,
+    pub dh_key: [u8; 32],
+}
+
+impl PublicIdentity {
+    /// Canonical encoding, also used as the session associated data.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(2 + 4 + self.signing_key.len() + 32);
+        out.extend_from_slice(&self.signing_algorithm.code().to_be_bytes());
+        put_bytes(&mut out, &self.signing_key);
+        out.extend_from_slice(&self.dh_key);
+        out
+    }
+
+    pub(crate) fn decode(reader: &mut Reader<'_>) -> Result<Self, MessagingError> {
+        let signing_algorithm = AlgorithmId::from_code(reader.u16()?)?;
+        if signing_algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(signing_algorithm).into());
+        }
+        let signing_key = reader.bytes()?.to_vec();
+        let dh_key = reader.array()?;
+        Ok(PublicIdentity { signing_algorithm, signing_key, dh_key })
+    }
+
+    fn verify_prekey(&self, kind: u8, prekey: &SignedPrekey) -> Result<(), MessagingError> {
+        let message = prekey_message(kind, prekey.id, &prekey.public_key);
+        if registry::verify(self.signing_algorithm, &self.signing_key, &message, &prekey.signature) {
+            Ok(())
+        } else {
+            Err(MessagingError::BadSignature)
+        }
+    }
+}
+
+fn prekey_message(kind: u8, id: u32, public_key: &[u8]) -> Vec<u8> {
+    let mut message = PREKEY_CONTEXT.to_vec();
+    message.push(kind);
+    put_u32(&mut message, id);
+    message.extend_from_slice(public_key);
+    message
+}
+
+/// A prekey signed by its owner's identity key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedPrekey {
+    pub id: u32,
+    pub public_key: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+impl SignedPrekey {
+    fn encode(&self, out: &mut Vec<u8>) {
+        put_u32(out, self.id);
+        put_bytes(out, &self.public_key);
+        put_bytes(out, &self.signature);
+    }
+
+    fn decode(reader: &mut Reader<'_>) -> Result<Self, MessagingError> {
+        Ok(SignedPrekey {
+            id: reader.u32()?,
+            public_key: reader.bytes()?.to_vec(),
+            signature: reader.bytes()?.to_vec(),
+        })
+    }
+}
+
+/// Everything an initiator needs to start a session offline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrekeyBundle {
+    pub identity: PublicIdentity,
+    /// X25519 signed prekey.
+    pub signed_prekey: SignedPrekey,
+    /// ML-KEM prekey; one-time unless `kem_prekey_is_last_resort`.
+    pub kem_prekey: SignedPrekey,
+    pub kem_prekey_is_last_resort: bool,
+}
+
+impl PrekeyBundle {
+    /// Check both prekey signatures and key sizes.
+    pub fn verify(&self) -> Result<(), MessagingError> {
+        if self.signed_prekey.public_key.len() != 32 {
+            return Err(AlgorithmError::InvalidKey.into());
+        }
+        if PQXDH_KEM.public_key_len() != Some(self.kem_prekey.public_key.len()) {
+            return Err(AlgorithmError::InvalidKey.into());
+        }
+        self.identity.verify_prekey(KIND_SIGNED_PREKEY, &self.signed_prekey)?;
+        self.identity.verify_prekey(KIND_KEM_PREKEY, &self.kem_prekey)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![BUNDLE_VERSION];
+        out.extend_from_slice(&self.identity.encode());
+        self.signed_prekey.encode(&mut out);
+        self.kem_prekey.encode(&mut out);
+        out.push(self.kem_prekey_is_last_resort as u8);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MessagingError> {
+        let mut reader = Reader::new(bytes);
+        if reader.u8()? != BUNDLE_VERSION {
+            return Err(MessagingError::Malformed("unsupported bundle version".into()));
+        }
+        let bundle = PrekeyBundle {
+            identity: PublicIdentity::decode(&mut reader)?,
+            signed_prekey: SignedPrekey::decode(&mut reader)?,
+            kem_prekey: SignedPrekey::decode(&mut reader)?,
+            kem_prekey_is_last_resort: match reader.u8()? {
+                0 => false,
+                1 => true,
+                _ => return Err(MessagingError::Malformed("bad last-resort flag".into())),
+            },
+        };
+        reader.finish()?;
+        Ok(bundle)
+    }
+}
+
+struct StoredPrekey {
+    secret: Vec<u8>,
+    public: SignedPrekey,
+}
+
+impl Drop for StoredPrekey {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// The responder's prekey secrets.
+///
+/// One-time KEM prekeys move from unpublished to published when handed out
+/// in a bundle and are deleted when a session consumes them.
+pub struct PrekeyStore {
+    signed_prekeys: Vec<StoredPrekey>,
+    last_resort: StoredPrekey,
+    unpublished: Vec<StoredPrekey>,
+    published: Vec<StoredPrekey>,
+    next_id: u32,
+}
+
+impl PrekeyStore {
+    /// Create a store with a signed prekey, a last-resort KEM prekey and
+    /// `one_time` one-time KEM prekeys.
+    pub fn new(identity: &IdentityKeyPair, one_time: usize) -> Result<Self, MessagingError> {
+        let (secret, public) = generate_dh();
+        let signed = StoredPrekey {
+            secret: secret.to_vec(),
+            public: identity.sign_prekey(KIND_SIGNED_PREKEY, 1, public.to_vec())?,
+        };
+        let (public, secret) = registry::keygen(PQXDH_KEM)?;
+        let last_resort = StoredPrekey { secret, public: identity.sign_prekey(KIND_KEM_PREKEY, 2, public)? };
+        let mut store = PrekeyStore {
+            signed_prekeys: vec![signed],
+            last_resort,
+            unpublished: Vec::new(),
+            published: Vec::new(),
+            next_id: 3,
+        };
+        store.add_one_time_prekeys(identity, one_time)?;
+        Ok(store)
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1).max(1);
+        id
+    }
+
+    /// Generate `count` more one-time KEM prekeys.
+    pub fn add_one_time_prekeys(&mut self, identity: &IdentityKeyPair, count: usize) -> Result<(), MessagingError> {
+        for _ in 0..count {
+            let id = self.next_id();
+            let (public, secret) = registry::keygen(PQXDH_KEM)?;
+            self.unpublished.push(StoredPrekey { secret, public: identity.sign_prekey(KIND_KEM_PREKEY, id, public)? });
+        }
+        Ok(())
+    }
+
+    /// Replace the signed X25519 prekey, keeping the previous one so
+    /// sessions started from older bundles still complete.
+    pub fn rotate_signed_prekey(&mut self, identity: &IdentityKeyPair) -> Result<(), MessagingError> {
+        let id = self.next_id();
+        let (secret, public) = generate_dh();
+        let signed = StoredPrekey {
+            secret: secret.to_vec(),
+            public: identity.sign_prekey(KIND_SIGNED_PREKEY, id, public.to_vec())?,
+        };
+        self.signed_prekeys.insert(0, signed);
+        self.signed_prekeys.truncate(RETAINED_SIGNED_PREKEYS);
+        Ok(())
+    }
+
+    /// One-time prekeys not yet handed out.
+    pub fn remaining_one_time_prekeys(&self) -> usize {
+        self.unpublished.len()
+    }
+
+    /// Hand out a bundle, using the next one-time KEM prekey if any remain.
+    pub fn bundle(&mut self, identity: &IdentityKeyPair) -> PrekeyBundle {
+        let (kem_prekey, kem_prekey_is_last_resort) = match self.unpublished.pop() {
+            Some(prekey) => {
+                let public = prekey.public.clone();
+                self.published.push(prekey);
+                (public, false)
+            }
+            None => (self.last_resort.public.clone(), true),
+        };
+        PrekeyBundle {
+            identity: identity.public(),
+            signed_prekey: self.signed_prekeys[0].public.clone(),
+            kem_prekey,
+            kem_prekey_is_last_resort,
+        }
+    }
+
+    fn signed_prekey_secret(&self, id: u32) -> Result<[u8; 32], MessagingError> {
+        self.signed_prekeys
+            .iter()
+            .find(|prekey| prekey.public.id == id)
+            .map(|prekey| prekey.secret.as_slice().try_into().expect("32-byte X25519 secret"))
+            .ok_or(MessagingError::UnknownPrekey(id))
+    }
+
+    fn kem_prekey_secret(&self, id: u32) -> Result<&[u8], MessagingError> {
+        if self.last_resort.public.id == id {
+            return Ok(&self.last_resort.secret);
+        }
+        self.published
+            .iter()
+            .find(|prekey| prekey.public.id == id)
+            .map(|prekey| prekey.secret.as_slice())
+            .ok_or(MessagingError::UnknownPrekey(id))
+    }
+
+    /// Delete a one-time prekey once a session has been established with it.
+    pub(crate) fn consume(&mut self, kem_prekey_id: u32) {
+        self.published.retain(|prekey| prekey.public.id != kem_prekey_id);
+    }
+}
+
+/// Key agreement fields sent ahead of the first ratchet message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InitialHeader {
+    pub identity: PublicIdentity,
+    pub ephemeral: [u8; 32],
+    pub signed_prekey_id: u32,
+    pub kem_prekey_id: u32,
+    pub kem_ciphertext: Vec<u8>,
+}
+
+impl InitialHeader {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![INITIAL_VERSION];
+        out.extend_from_slice(&self.identity.encode());
+        out.extend_from_slice(&self.ephemeral);
+        put_u32(&mut out, self.signed_prekey_id);
+        put_u32(&mut out, self.kem_prekey_id);
+        put_bytes(&mut out, &self.kem_ciphertext);
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, MessagingError> {
+        let mut reader = Reader::new(bytes);
+        if reader.u8()? != INITIAL_VERSION {
+            return Err(MessagingError::Malformed("unsupported initial message version".into()));
+        }
+        let header = InitialHeader {
+            identity: PublicIdentity::decode(&mut reader)?,
+            ephemeral: reader.array()?,
+            signed_prekey_id: reader.u32()?,
+            kem_prekey_id: reader.u32()?,
+            kem_ciphertext: reader.bytes()?.to_vec(),
+        };
+        reader.finish()?;
+        Ok(header)
+    }
+}
+
+/// Output of the agreement: the root secret and the associated data binding
+/// both identities.
+pub(crate) struct Agreement {
+    pub secret: [u8; 32],
+    pub associated_data: Vec<u8>,
+}
+
+impl Drop for Agreement {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+fn derive_secret(dh1: &[u8; 32], dh2: &[u8; 32], dh3: &[u8; 32], kem_secret: &[u8]) -> [u8; 32] {
+    let mut ikm = vec![0xFF; 32];
+    for part in [&dh1[..], dh2, dh3, kem_secret] {
+        ikm.extend_from_slice(part);
+    }
+    let mut secret = [0u8; 32];
+    Hkdf::<Sha256>
+        ::new(Some(&[0u8; 32]), &ikm)
+        .expand(KDF_INFO, &mut secret)
+        .expect("32 is a valid HKDF-SHA256 output length");
+    ikm.zeroize();
+    secret
+}
+
+/// Initiator side: verify `bundle` and run the agreement against it.
+pub(crate) fn initiate(
+    identity: &IdentityKeyPair,
+    bundle: &PrekeyBundle
+) -> Result<(Agreement, InitialHeader), MessagingError> {
+    bundle.verify()?;
+    let signed_prekey: [u8; 32] = bundle.signed_prekey.public_key.as_slice().try_into().expect("length verified");
+    let (mut ephemeral_secret, ephemeral) = generate_dh();
+    let mut dh1 = dh(&identity.dh_secret, &signed_prekey);
+    let mut dh2 = dh(&ephemeral_secret, &bundle.identity.dh_key);
+    let mut dh3 = dh(&ephemeral_secret, &signed_prekey);
+    let (kem_ciphertext, mut kem_secret) = registry::encapsulate(PQXDH_KEM, &bundle.kem_prekey.public_key)?;
+    let secret = derive_secret(&dh1, &dh2, &dh3, &kem_secret);
+    for value in [&mut ephemeral_secret, &mut dh1, &mut dh2, &mut dh3] {
+        value.zeroize();
+    }
+    kem_secret.zeroize();
+
+    let own = identity.public();
+    let mut associated_data = own.encode();
+    associated_data.extend_from_slice(&bundle.identity.encode());
+    let header = InitialHeader {
+        identity: own,
+        ephemeral,
+        signed_prekey_id: bundle.signed_prekey.id,
+        kem_prekey_id: bundle.kem_prekey.id,
+        kem_ciphertext,
+    };
+    Ok((Agreement { secret, associated_data }, header))
+}
+
+/// Responder side: rerun the agreement from an initial header. Returns the
+/// signed prekey secret, which seeds the responder's first ratchet key.
+pub(crate) fn respond(
+    identity: &IdentityKeyPair,
+    store: &PrekeyStore,
+    header: &InitialHeader
+) -> Result<(Agreement, [u8; 32]), MessagingError> {
+    let signed_prekey_secret = store.signed_prekey_secret(header.signed_prekey_id)?;
+    let kem_prekey_secret = store.kem_prekey_secret(header.kem_prekey_id)?;
+    let mut dh1 = dh(&signed_prekey_secret, &header.identity.dh_key);
+    let mut dh2 = dh(&identity.dh_secret, &header.ephemeral);
+    let mut dh3 = dh(&signed_prekey_secret, &header.ephemeral);
+    let mut kem_secret = registry::decapsulate(PQXDH_KEM, kem_prekey_secret, &header.kem_ciphertext)?;
+    let secret = derive_secret(&dh1, &dh2, &dh3, &kem_secret);
+    for value in [&mut dh1, &mut dh2, &mut dh3] {
+        value.zeroize();
+    }
+    kem_secret.zeroize();
+
+    let mut associated_data = header.identity.encode();
+    associated_data.extend_from_slice(&identity.public().encode());
+    Ok((Agreement { secret, associated_data }, signed_prekey_secret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agreement_matches() {
+        let alice = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+        let bob = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+        let mut store = PrekeyStore::new(&bob, 1).unwrap();
+        let bundle = PrekeyBundle::from_bytes(&store.bundle(&bob).to_bytes()).unwrap();
+        assert!(!bundle.kem_prekey_is_last_resort);
+
+        let (sent, header) = initiate(&alice, &bundle).unwrap();
+        let header = InitialHeader::decode(&header.encode()).unwrap();
+        let (received, _) = respond(&bob, &store, &header).unwrap();
+        assert_eq!(sent.secret, received.secret);
+        assert_eq!(sent.associated_data, received.associated_data);
+    }
+
+    #[test]
+    fn test_forged_prekey_rejected() {
+        let bob = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+        let mallory = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+        let mut store = PrekeyStore::new(&bob, 0).unwrap();
+        let mut bundle = store.bundle(&bob);
+        assert!(bundle.kem_prekey_is_last_resort);
+        bundle.kem_prekey = PrekeyStore::new(&mallory, 0).unwrap().bundle(&mallory).kem_prekey;
+        assert_eq!(bundle.verify(), Err(MessagingError::BadSignature));
+    }
+}
@@ -0,0 +1,573 @@
+//! Double Ratchet with a sparse ML-KEM ratchet.
+//!
+//! Follows the Signal Double Ratchet specification: `KDF_RK` is
+//! HKDF-SHA256 salted with the root key, `KDF_CK` is HMAC-SHA256 with the
+//! constants `0x01` (message key) and `0x02` (next chain key), and each
+//! message key is expanded into an AES-256-GCM key and nonce. The header is
+//! authenticated as associated data but not encrypted.
+//!
+//! When a new sending chain is due for a PQ step, the sender publishes a
+//! fresh ML-KEM public key in its headers and, if it already knows the
+//! peer's current KEM key, encapsulates to it and mixes the shared secret
+//! into the root key alongside the X25519 output.
+
+use std::collections::VecDeque;
+
+use aes_gcm::{ aead::{ Aead, KeyInit, Payload }, Aes256Gcm, Nonce };
+use hkdf::Hkdf;
+use hmac::{ Hmac, Mac };
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use super::pqxdh::{ self, IdentityKeyPair, InitialHeader, PrekeyBundle, PrekeyStore, PublicIdentity, PQXDH_KEM };
+use super::{ dh, dh_public, generate_dh, put_bytes, put_optional, put_u32, MessagingError, Reader };
+use crate::registry;
+
+const MSG_INITIAL: u8 = 1;
+const MSG_RATCHET: u8 = 2;
+const STATE_VERSION: u8 = 1;
+
+const FLAG_KEM_PUBLIC: u8 = 0x01;
+const FLAG_KEM_CIPHERTEXT: u8 = 0x02;
+
+const ROOT_INFO: &[u8] = b"Aegis_Ratchet_Root";
+const MESSAGE_INFO: &[u8] = b"Aegis_Ratchet_Message";
+
+/// Ratchet tuning knobs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionConfig {
+    /// Most message keys skipped within one receiving chain.
+    pub max_skip: u32,
+    /// Most skipped message keys kept in total; the oldest are evicted.
+    pub max_stored_keys: usize,
+    /// Run a PQ ratchet step on every n-th sending chain; 0 disables it.
+    pub pq_ratchet_interval: u32,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig { max_skip: 1000, max_stored_keys: 2000, pq_ratchet_interval: 4 }
+    }
+}
+
+#[derive(Clone)]
+struct SkippedKey {
+    dh: [u8; 32],
+    n: u32,
+    key: [u8; 32],
+}
+
+#[derive(Clone, Default)]
+struct PqFields {
+    public_key: Option<Vec<u8>>,
+    ciphertext: Option<Vec<u8>>,
+}
+
+struct Header {
+    dh: [u8; 32],
+    pn: u32,
+    n: u32,
+    pq: PqFields,
+}
+
+impl Header {
+    fn encode(&self) -> Vec<u8> {
+        let mut out = self.dh.to_vec();
+        put_u32(&mut out, self.pn);
+        put_u32(&mut out, self.n);
+        let mut flags = 0;
+        if self.pq.public_key.is_some() {
+            flags |= FLAG_KEM_PUBLIC;
+        }
+        if self.pq.ciphertext.is_some() {
+            flags |= FLAG_KEM_CIPHERTEXT;
+        }
+        out.push(flags);
+        for field in [&self.pq.public_key, &self.pq.ciphertext].into_iter().flatten() {
+            put_bytes(&mut out, field);
+        }
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, MessagingError> {
+        let mut reader = Reader::new(bytes);
+        let dh = reader.array()?;
+        let pn = reader.u32()?;
+        let n = reader.u32()?;
+        let flags = reader.u8()?;
+        if flags & !(FLAG_KEM_PUBLIC | FLAG_KEM_CIPHERTEXT) != 0 {
+            return Err(MessagingError::Malformed("unknown header flags".into()));
+        }
+        let mut pq = PqFields::default();
+        if flags & FLAG_KEM_PUBLIC != 0 {
+            pq.public_key = Some(reader.bytes()?.to_vec());
+        }
+        if flags & FLAG_KEM_CIPHERTEXT != 0 {
+            pq.ciphertext = Some(reader.bytes()?.to_vec());
+        }
+        reader.finish()?;
+        Ok(Header { dh, pn, n, pq })
+    }
+}
+
+fn kdf_rk(root_key: &[u8; 32], input: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let mut okm = [0u8; 64];
+    Hkdf::<Sha256>
+        ::new(Some(root_key), input)
+        .expand(ROOT_INFO, &mut okm)
+        .expect("64 is a valid HKDF-SHA256 output length");
+    let root: [u8; 32] = okm[..32].try_into().unwrap();
+    let chain: [u8; 32] = okm[32..].try_into().unwrap();
+    okm.zeroize();
+    (root, chain)
+}
+
+fn kdf_ck(chain_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let step = |constant: u8| -> [u8; 32] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(chain_key).expect("HMAC accepts any key length");
+        mac.update(&[constant]);
+        mac.finalize().into_bytes().into()
+    };
+    (step(0x01), step(0x02))
+}
+
+fn message_cipher(message_key: &[u8; 32]) -> (Aes256Gcm, [u8; 12]) {
+    let mut okm = [0u8; 44];
+    Hkdf::<Sha256>
+        ::new(None, message_key)
+        .expand(MESSAGE_INFO, &mut okm)
+        .expect("44 is a valid HKDF-SHA256 output length");
+    let cipher = Aes256Gcm::new_from_slice(&okm[..32]).expect("32-byte key");
+    let nonce: [u8; 12] = okm[32..].try_into().unwrap();
+    okm.zeroize();
+    (cipher, nonce)
+}
+
+/// One end of a ratcheting session.
+#[derive(Clone)]
+pub struct Session {
+    config: SessionConfig,
+    associated_data: Vec<u8>,
+    remote_identity: PublicIdentity,
+    dh_secret: [u8; 32],
+    dh_public: [u8; 32],
+    remote_dh: Option<[u8; 32]>,
+    root_key: [u8; 32],
+    sending_chain: Option<[u8; 32]>,
+    receiving_chain: Option<[u8; 32]>,
+    ns: u32,
+    nr: u32,
+    pn: u32,
+    chains_sent: u32,
+    kem_secret: Option<Vec<u8>>,
+    remote_kem: Option<Vec<u8>>,
+    sending_pq: PqFields,
+    skipped: VecDeque<SkippedKey>,
+    pending_initial: Option<Vec<u8>>,
+}
+
+impl Session {
+    /// Start a session from `bundle` and encrypt the first message.
+    ///
+    /// Until the peer replies, every message carries the key agreement
+    /// fields so any of them can establish the session.
+    pub fn initiate(
+        identity: &IdentityKeyPair,
+        bundle: &PrekeyBundle,
+        plaintext: &[u8],
+        config: SessionConfig
+    ) -> Result<(Session, Vec<u8>), MessagingError> {
+        let (agreement, header) = pqxdh::initiate(identity, bundle)?;
+        let (dh_secret, dh_public) = generate_dh();
+        let remote_dh: [u8; 32] = bundle.signed_prekey.public_key.as_slice().try_into().expect("length verified");
+        let mut session = Session::new(config, &agreement, bundle.identity.clone(), dh_secret, dh_public);
+        session.remote_dh = Some(remote_dh);
+        session.pending_initial = Some(header.encode());
+        session.start_sending_chain()?;
+        let message = session.encrypt(plaintext)?;
+        Ok((session, message))
+    }
+
+    /// Accept an initial message, returning the session and the plaintext.
+    ///
+    /// A one-time KEM prekey is deleted from `store` only once the message
+    /// has decrypted. Later initial messages from the same peer (sent before
+    /// it saw a reply) belong to the returned session's [`Session::decrypt`].
+    pub fn respond(
+        identity: &IdentityKeyPair,
+        store: &mut PrekeyStore,
+        message: &[u8],
+        config: SessionConfig
+    ) -> Result<(Session, Vec<u8>), MessagingError> {
+        let (header, inner) = split_initial(message)?
+            .ok_or_else(|| MessagingError::Malformed("not an initial message".into()))?;
+        let (agreement, signed_prekey_secret) = pqxdh::respond(identity, store, &header)?;
+        let dh_public = dh_public(&signed_prekey_secret);
+        let mut session = Session::new(config, &agreement, header.identity.clone(), signed_prekey_secret, dh_public);
+        let plaintext = session.decrypt(inner)?;
+        store.consume(header.kem_prekey_id);
+        Ok((session, plaintext))
+    }
+
+    fn new(
+        config: SessionConfig,
+        agreement: &pqxdh::Agreement,
+        remote_identity: PublicIdentity,
+        dh_secret: [u8; 32],
+        dh_public: [u8; 32]
+    ) -> Self {
+        Session {
+            config,
+            associated_data: agreement.associated_data.clone(),
+            remote_identity,
+            dh_secret,
+            dh_public,
+            remote_dh: None,
+            root_key: agreement.secret,
+            sending_chain: None,
+            receiving_chain: None,
+            ns: 0,
+            nr: 0,
+            pn: 0,
+            chains_sent: 0,
+            kem_secret: None,
+            remote_kem: None,
+            sending_pq: PqFields::default(),
+            skipped: VecDeque::new(),
+            pending_initial: None,
+        }
+    }
+
+    /// The peer's identity, authenticated by the key agreement.
+    pub fn remote_identity(&self) -> &PublicIdentity {
+        &self.remote_identity
+    }
+
+    /// Skipped message keys currently held for late messages.
+    pub fn stored_skipped_keys(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// Encrypt the next message on the current sending chain.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, MessagingError> {
+        let chain_key = self.sending_chain.ok_or(MessagingError::NotReady)?;
+        let (mut message_key, next) = kdf_ck(&chain_key);
+        let header = Header { dh: self.dh_public, pn: self.pn, n: self.ns, pq: self.sending_pq.clone() }.encode();
+        let ciphertext = self.seal(&message_key, &header, plaintext);
+        message_key.zeroize();
+        self.sending_chain = Some(next);
+        self.ns = self.ns.checked_add(1).ok_or(MessagingError::NotReady)?;
+
+        let mut message = vec![MSG_RATCHET];
+        put_bytes(&mut message, &header);
+        message.extend_from_slice(&ciphertext);
+        Ok(match &self.pending_initial {
+            Some(initial) => {
+                let mut wrapped = vec![MSG_INITIAL];
+                put_bytes(&mut wrapped, initial);
+                wrapped.extend_from_slice(&message);
+                wrapped
+            }
+            None => message,
+        })
+    }
+
+    /// Decrypt a message. The session is left unchanged if it fails.
+    pub fn decrypt(&mut self, message: &[u8]) -> Result<Vec<u8>, MessagingError> {
+        let message = match split_initial(message)? {
+            Some((header, inner)) => {
+                if header.identity != self.remote_identity {
+                    return Err(MessagingError::IdentityMismatch);
+                }
+                inner
+            }
+            None => message,
+        };
+        let mut reader = Reader::new(message);
+        if reader.u8()? != MSG_RATCHET {
+            return Err(MessagingError::Malformed("unknown message type".into()));
+        }
+        let header_bytes = reader.bytes()?;
+        let header = Header::decode(header_bytes)?;
+        let ciphertext = reader.rest();
+
+        let mut next = self.clone();
+        let plaintext = next.decrypt_with_header(&header, header_bytes, ciphertext)?;
+        next.pending_initial = None;
+        *self = next;
+        Ok(plaintext)
+    }
+
+    fn decrypt_with_header(
+        &mut self,
+        header: &Header,
+        header_bytes: &[u8],
+        ciphertext: &[u8]
+    ) -> Result<Vec<u8>, MessagingError> {
+        if let Some(index) = self.skipped.iter().position(|k| k.dh == header.dh && k.n == header.n) {
+            let skipped = self.skipped.remove(index).expect("index in range");
+            return self.open(&skipped.key, header_bytes, ciphertext);
+        }
+        if self.remote_dh == Some(header.dh) {
+            if header.n < self.nr {
+                return Err(MessagingError::DuplicateMessage);
+            }
+        } else {
+            self.skip_message_keys(header.pn)?;
+            self.dh_ratchet(header)?;
+        }
+        self.skip_message_keys(header.n)?;
+        let chain_key = self.receiving_chain.ok_or(MessagingError::Decrypt)?;
+        let (mut message_key, next) = kdf_ck(&chain_key);
+        self.receiving_chain = Some(next);
+        self.nr += 1;
+        let plaintext = self.open(&message_key, header_bytes, ciphertext);
+        message_key.zeroize();
+        plaintext
+    }
+
+    fn skip_message_keys(&mut self, until: u32) -> Result<(), MessagingError> {
+        let (Some(mut chain_key), Some(remote_dh)) = (self.receiving_chain, self.remote_dh) else {
+            return Ok(());
+        };
+        if u64::from(until) > u64::from(self.nr) + u64::from(self.config.max_skip) {
+            return Err(MessagingError::TooManySkipped);
+        }
+        while self.nr < until {
+            let (key, next) = kdf_ck(&chain_key);
+            self.skipped.push_back(SkippedKey { dh: remote_dh, n: self.nr, key });
+            if self.skipped.len() > self.config.max_stored_keys {
+                self.skipped.pop_front();
+            }
+            chain_key = next;
+            self.nr += 1;
+        }
+        self.receiving_chain = Some(chain_key);
+        Ok(())
+    }
+
+    fn dh_ratchet(&mut self, header: &Header) -> Result<(), MessagingError> {
+        self.pn = self.ns;
+        self.ns = 0;
+        self.nr = 0;
+        self.remote_dh = Some(header.dh);
+
+        let mut input = dh(&self.dh_secret, &header.dh).to_vec();
+        if let Some(ciphertext) = &header.pq.ciphertext {
+            let secret = self.kem_secret
+                .as_ref()
+                .ok_or_else(|| MessagingError::Malformed("unexpected KEM ciphertext".into()))?;
+            let mut shared = registry::decapsulate(PQXDH_KEM, secret, ciphertext)?;
+            input.extend_from_slice(&shared);
+            shared.zeroize();
+        }
+        if let Some(public_key) = &header.pq.public_key {
+            if PQXDH_KEM.public_key_len() != Some(public_key.len()) {
+                return Err(MessagingError::Malformed("bad KEM public key".into()));
+            }
+            self.remote_kem = Some(public_key.clone());
+        }
+        let (root_key, chain_key) = kdf_rk(&self.root_key, &input);
+        input.zeroize();
+        self.root_key = root_key;
+        self.receiving_chain = Some(chain_key);
+
+        self.dh_secret.zeroize();
+        (self.dh_secret, self.dh_public) = generate_dh();
+        self.start_sending_chain()
+    }
+
+    fn start_sending_chain(&mut self) -> Result<(), MessagingError> {
+        let remote_dh = self.remote_dh.expect("remote ratchet key");
+        let mut input = dh(&self.dh_secret, &remote_dh).to_vec();
+        let interval = self.config.pq_ratchet_interval;
+        self.sending_pq = PqFields::default();
+        if interval > 0 && self.chains_sent.is_multiple_of(interval) {
+            if let Some(remote_kem) = &self.remote_kem {
+                let (ciphertext, mut shared) = registry::encapsulate(PQXDH_KEM, remote_kem)?;
+                input.extend_from_slice(&shared);
+                shared.zeroize();
+                self.sending_pq.ciphertext = Some(ciphertext);
+            }
+            let (public_key, secret_key) = registry::keygen(PQXDH_KEM)?;
+            if let Some(old) = self.kem_secret.as_mut() {
+                old.zeroize();
+            }
+            self.kem_secret = Some(secret_key);
+            self.sending_pq.public_key = Some(public_key);
+        }
+        self.chains_sent = self.chains_sent.wrapping_add(1);
+        let (root_key, chain_key) = kdf_rk(&self.root_key, &input);
+        input.zeroize();
+        self.root_key = root_key;
+        self.sending_chain = Some(chain_key);
+        Ok(())
+    }
+
+    fn seal(&self, message_key: &[u8; 32], header: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let (cipher, nonce) = message_cipher(message_key);
+        let aad = [self.associated_data.as_slice(), header].concat();
+        cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &aad })
+            .expect("AES-GCM encryption cannot fail")
+    }
+
+    fn open(&self, message_key: &[u8; 32], header: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, MessagingError> {
+        let (cipher, nonce) = message_cipher(message_key);
+        let aad = [self.associated_data.as_slice(), header].concat();
+        cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: ciphertext, aad: &aad })
+            .map_err(|_| MessagingError::Decrypt)
+    }
+
+    /// Serialize the whole session, including its secret keys.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = vec![STATE_VERSION];
+        put_u32(&mut out, self.config.max_skip);
+        put_u32(&mut out, self.config.max_stored_keys as u32);
+        put_u32(&mut out, self.config.pq_ratchet_interval);
+        put_bytes(&mut out, &self.associated_data);
+        put_bytes(&mut out, &self.remote_identity.encode());
+        out.extend_from_slice(&self.dh_secret);
+        put_optional(&mut out, self.remote_dh.as_ref().map(|k| &k[..]));
+        out.extend_from_slice(&self.root_key);
+        put_optional(&mut out, self.sending_chain.as_ref().map(|k| &k[..]));
+        put_optional(&mut out, self.receiving_chain.as_ref().map(|k| &k[..]));
+        for counter in [self.ns, self.nr, self.pn, self.chains_sent] {
+            put_u32(&mut out, counter);
+        }
+        put_optional(&mut out, self.kem_secret.as_deref());
+        put_optional(&mut out, self.remote_kem.as_deref());
+        put_optional(&mut out, self.sending_pq.public_key.as_deref());
+        put_optional(&mut out, self.sending_pq.ciphertext.as_deref());
+        put_optional(&mut out, self.pending_initial.as_deref());
+        put_u32(&mut out, self.skipped.len() as u32);
+        for skipped in &self.skipped {
+            out.extend_from_slice(&skipped.dh);
+            put_u32(&mut out, skipped.n);
+            out.extend_from_slice(&skipped.key);
+        }
+        out
+    }
+
+    /// Restore a session serialized with [`Session::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MessagingError> {
+        fn key(field: Option<&[u8]>) -> Result<Option<[u8; 32]>, MessagingError> {
+            field
+                .map(|k| k.try_into().map_err(|_| MessagingError::Malformed("bad key length".into())))
+                .transpose()
+        }
+        let mut reader = Reader::new(bytes);
+        if reader.u8()? != STATE_VERSION {
+            return Err(MessagingError::Malformed("unsupported session version".into()));
+        }
+        let config = SessionConfig {
+            max_skip: reader.u32()?,
+            max_stored_keys: reader.u32()? as usize,
+            pq_ratchet_interval: reader.u32()?,
+        };
+        let associated_data = reader.bytes()?.to_vec();
+        let mut identity = Reader::new(reader.bytes()?);
+        let remote_identity = PublicIdentity::decode(&mut identity)?;
+        identity.finish()?;
+        let dh_secret: [u8; 32] = reader.array()?;
+        let dh_public = dh_public(&dh_secret);
+        let remote_dh = key(reader.optional()?)?;
+        let root_key = reader.array()?;
+        let sending_chain = key(reader.optional()?)?;
+        let receiving_chain = key(reader.optional()?)?;
+        let (ns, nr, pn, chains_sent) = (reader.u32()?, reader.u32()?, reader.u32()?, reader.u32()?);
+        let kem_secret = reader.optional()?.map(<[u8]>::to_vec);
+        let remote_kem = reader.optional()?.map(<[u8]>::to_vec);
+        let sending_pq = PqFields {
+            public_key: reader.optional()?.map(<[u8]>::to_vec),
+            ciphertext: reader.optional()?.map(<[u8]>::to_vec),
+        };
+        let pending_initial = reader.optional()?.map(<[u8]>::to_vec);
+        let count = reader.u32()? as usize;
+        let mut skipped = VecDeque::with_capacity(count.min(config.max_stored_keys));
+        for _ in 0..count {
+            skipped.push_back(SkippedKey { dh: reader.array()?, n: reader.u32()?, key: reader.array()? });
+        }
+        reader.finish()?;
+        Ok(Session {
+            config,
+            associated_data,
+            remote_identity,
+            dh_secret,
+            dh_public,
+            remote_dh,
+            root_key,
+            sending_chain,
+            receiving_chain,
+            ns,
+            nr,
+            pn,
+            chains_sent,
+            kem_secret,
+            remote_kem,
+            sending_pq,
+            skipped,
+            pending_initial,
+        })
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.dh_secret.zeroize();
+        self.root_key.zeroize();
+        if let Some(key) = self.sending_chain.as_mut() {
+            key.zeroize();
+        }
+        if let Some(key) = self.receiving_chain.as_mut() {
+            key.zeroize();
+        }
+        if let Some(key) = self.kem_secret.as_mut() {
+            key.zeroize();
+        }
+        for skipped in self.skipped.iter_mut() {
+            skipped.key.zeroize();
+        }
+    }
+}
+
+/// Split an initial message into its agreement header and the ratchet
+/// message it wraps; `None` for plain ratchet messages.
+fn split_initial(message: &[u8]) -> Result<Option<(InitialHeader, &[u8])>, MessagingError> {
+    let mut reader = Reader::new(message);
+    match reader.u8()? {
+        MSG_INITIAL => {
+            let header = InitialHeader::decode(reader.bytes()?)?;
+            Ok(Some((header, reader.rest())))
+        }
+        MSG_RATCHET => Ok(None),
+        _ => Err(MessagingError::Malformed("unknown message type".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kdf_ck_separates_keys() {
+        let (message_key, chain_key) = kdf_ck(&[7u8; 32]);
+        assert_ne!(message_key, chain_key);
+        assert_eq!(kdf_ck(&[7u8; 32]), (message_key, chain_key));
+    }
+
+    #[test]
+    fn test_header_round_trip() {
+        let header = Header {
+            dh: [1u8; 32],
+            pn: 3,
+            n: 9,
+            pq: PqFields { public_key: Some(vec![2; 4]), ciphertext: None },
+        };
+        let decoded = Header::decode(&header.encode()).unwrap();
+        assert_eq!((decoded.dh, decoded.pn, decoded.n), (header.dh, 3, 9));
+        assert_eq!(decoded.pq.public_key, Some(vec![2; 4]));
+        assert!(decoded.pq.ciphertext.is_none());
+    }
+}
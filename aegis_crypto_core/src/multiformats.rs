@@ -0,0 +1,225 @@
+//! Multibase and multicodec encodings for post-quantum public keys.
+//!
+//! A multicodec public key is an unsigned-varint codec identifier followed
+//! by the raw key bytes; a multibase string is a one-character base prefix
+//! followed by the encoded data. Together they form the `publicKeyMultibase`
+//! values used by `did:key` (see [`crate::did`]).
+//!
+//! ML-KEM and ML-DSA use their (draft) entries in the multicodec table.
+//! Algorithms without an assigned code use the private-use range:
+//! `0x300000 + registry code`.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine as _ };
+
+use crate::registry::{ AlgorithmError, AlgorithmId };
+
+/// Start of the multicodec private-use range.
+pub const PRIVATE_USE_BASE: u64 = 0x30_0000;
+
+/// Multibase encodings supported for encoding and decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multibase {
+    /// `z`: base58 with the Bitcoin alphabet.
+    Base58Btc,
+    /// `u`: unpadded base64url.
+    Base64Url,
+    /// `f`: lowercase hexadecimal.
+    Base16,
+}
+
+impl Multibase {
+    pub fn prefix(self) -> char {
+        match self {
+            Multibase::Base58Btc => 'z',
+            Multibase::Base64Url => 'u',
+            Multibase::Base16 => 'f',
+        }
+    }
+}
+
+/// Multiformat error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultiformatError {
+    UnsupportedBase(char),
+    InvalidEncoding(String),
+    InvalidVarint,
+    UnknownCodec(u64),
+    InvalidKeyLength { expected: usize, actual: usize },
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for MultiformatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultiformatError::UnsupportedBase(prefix) => write!(f, "Unsupported multibase prefix '{}'", prefix),
+            MultiformatError::InvalidEncoding(msg) => write!(f, "Invalid multibase data: {}", msg),
+            MultiformatError::InvalidVarint => write!(f, "Invalid unsigned varint"),
+            MultiformatError::UnknownCodec(code) => write!(f, "Unknown multicodec 0x{:x}", code),
+            MultiformatError::InvalidKeyLength { expected, actual } =>
+                write!(f, "Invalid public key length: expected {}, got {}", expected, actual),
+            MultiformatError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MultiformatError {}
+
+impl From<AlgorithmError> for MultiformatError {
+    fn from(e: AlgorithmError) -> Self {
+        MultiformatError::Algorithm(e)
+    }
+}
+
+/// Encode `data` as a multibase string.
+pub fn multibase_encode(base: Multibase, data: &[u8]) -> String {
+    let body = match base {
+        Multibase::Base58Btc => bs58::encode(data).into_string(),
+        Multibase::Base64Url => URL_SAFE_NO_PAD.encode(data),
+        Multibase::Base16 => hex::encode(data),
+    };
+    let mut out = String::with_capacity(body.len() + 1);
+    out.push(base.prefix());
+    out.push_str(&body);
+    out
+}
+
+/// Decode a multibase string, returning the base it was encoded with.
+pub fn multibase_decode(encoded: &str) -> Result<(Multibase, Vec<u8>), MultiformatError> {
+    let mut chars = encoded.chars();
+    let prefix = chars.next().ok_or_else(|| MultiformatError::InvalidEncoding("empty string".into()))?;
+    let body = chars.as_str();
+    let invalid = |e: &dyn std::fmt::Display| MultiformatError::InvalidEncoding(e.to_string());
+    match prefix {
+        'z' => bs58
+            ::decode(body)
+            .into_vec()
+            .map(|data| (Multibase::Base58Btc, data))
+            .map_err(|e| invalid(&e)),
+        'u' => URL_SAFE_NO_PAD
+            .decode(body)
+            .map(|data| (Multibase::Base64Url, data))
+            .map_err(|e| invalid(&e)),
+        'f' | 'F' => hex
+            ::decode(body)
+            .map(|data| (Multibase::Base16, data))
+            .map_err(|e| invalid(&e)),
+        other => Err(MultiformatError::UnsupportedBase(other)),
+    }
+}
+
+/// Append `value` as an unsigned LEB128 varint.
+pub fn varint_encode(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Read an unsigned varint, returning the value and the number of bytes
+/// consumed. Non-minimal encodings are rejected, as required by the
+/// multiformats unsigned-varint specification.
+pub fn varint_decode(data: &[u8]) -> Result<(u64, usize), MultiformatError> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(9) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                return Err(MultiformatError::InvalidVarint);
+            }
+            return Ok((value, i + 1));
+        }
+    }
+    Err(MultiformatError::InvalidVarint)
+}
+
+/// The multicodec identifier for a public key of `algorithm`.
+pub fn public_key_codec(algorithm: AlgorithmId) -> u64 {
+    match algorithm {
+        AlgorithmId::MlKem512 => 0x120b,
+        AlgorithmId::MlKem768 => 0x120c,
+        AlgorithmId::MlKem1024 => 0x120d,
+        AlgorithmId::MlDsa44 => 0x1210,
+        AlgorithmId::MlDsa65 => 0x1211,
+        AlgorithmId::MlDsa87 => 0x1212,
+        other => PRIVATE_USE_BASE + (other.code() as u64),
+    }
+}
+
+/// The algorithm whose public keys carry multicodec `codec`.
+pub fn algorithm_for_codec(codec: u64) -> Result<AlgorithmId, MultiformatError> {
+    AlgorithmId::ALL
+        .iter()
+        .copied()
+        .find(|alg| public_key_codec(*alg) == codec)
+        .ok_or(MultiformatError::UnknownCodec(codec))
+}
+
+/// Prefix a raw public key with its multicodec identifier.
+pub fn encode_public_key(algorithm: AlgorithmId, public_key: &[u8]) -> Result<Vec<u8>, MultiformatError> {
+    check_key_length(algorithm, public_key)?;
+    let mut out = Vec::with_capacity(public_key.len() + 4);
+    varint_encode(public_key_codec(algorithm), &mut out);
+    out.extend_from_slice(public_key);
+    Ok(out)
+}
+
+/// Split a multicodec-prefixed public key into algorithm and raw key.
+pub fn decode_public_key(data: &[u8]) -> Result<(AlgorithmId, Vec<u8>), MultiformatError> {
+    let (codec, used) = varint_decode(data)?;
+    let algorithm = algorithm_for_codec(codec)?;
+    let public_key = &data[used..];
+    check_key_length(algorithm, public_key)?;
+    Ok((algorithm, public_key.to_vec()))
+}
+
+fn check_key_length(algorithm: AlgorithmId, public_key: &[u8]) -> Result<(), MultiformatError> {
+    match algorithm.public_key_len() {
+        Some(expected) if expected != public_key.len() => {
+            Err(MultiformatError::InvalidKeyLength { expected, actual: public_key.len() })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Encode a public key as a base58btc multibase string.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn public_key_to_multibase(algorithm_code: u16, public_key: &[u8]) -> Result<String, String> {
+    let algorithm = AlgorithmId::from_code(algorithm_code).map_err(|e| e.to_string())?;
+    encode_public_key(algorithm, public_key)
+        .map(|bytes| multibase_encode(Multibase::Base58Btc, &bytes))
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint_and_multibase() {
+        let mut buf = Vec::new();
+        varint_encode(0x1211, &mut buf);
+        assert_eq!(buf, vec![0x91, 0x24]);
+        assert_eq!(varint_decode(&buf).unwrap(), (0x1211, 2));
+        assert_eq!(varint_decode(&[0x80, 0x00]), Err(MultiformatError::InvalidVarint));
+        assert_eq!(varint_decode(&[0x80]), Err(MultiformatError::InvalidVarint));
+
+        assert_eq!(multibase_encode(Multibase::Base58Btc, b"hello world"), "zStV1DL6CwTryKyV");
+        assert_eq!(multibase_encode(Multibase::Base16, b"hi"), "f6869");
+        for base in [Multibase::Base58Btc, Multibase::Base64Url, Multibase::Base16] {
+            let encoded = multibase_encode(base, &[0, 0, 1, 2, 255]);
+            assert_eq!(multibase_decode(&encoded).unwrap(), (base, vec![0, 0, 1, 2, 255]));
+        }
+        assert_eq!(multibase_decode("Qabc"), Err(MultiformatError::UnsupportedBase('Q')));
+    }
+
+    #[test]
+    fn test_public_key_codecs_are_unique() {
+        for alg in AlgorithmId::ALL {
+            assert_eq!(algorithm_for_codec(public_key_codec(alg)).unwrap(), alg);
+        }
+        assert_eq!(public_key_codec(AlgorithmId::Falcon512), 0x30_0211);
+    }
+}
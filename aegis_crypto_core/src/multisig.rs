@@ -0,0 +1,420 @@
+//! k-of-n multi-signature policies over post-quantum signature keys.
+//!
+//! A [`MultisigPolicy`] names `n` public keys, possibly of different
+//! algorithms (ML-DSA, Falcon, SLH-DSA), and a threshold `k`. Each signer
+//! produces an ordinary detached signature over a canonical message that
+//! binds the policy hash, so a signature collected for one policy cannot
+//! be replayed under another. A [`SignatureCollector`] verifies partial
+//! signatures as they arrive and emits a [`MultisigBundle`] holding
+//! exactly `k` of them.
+//!
+//! ```text
+//! policy  := "AEGP" version:u8 threshold:u16be count:u16be key*
+//! key     := algorithm:u16be length:u32be public_key
+//! bundle  := "AEGM" version:u8 policy_hash[32] count:u16be entry*
+//! entry   := signer_index:u16be length:u32be signature
+//! message := "aegis-multisig-v1" policy_hash[32] payload
+//! ```
+//!
+//! Keys are stored sorted by [`container::key_id`], so the policy hash
+//! does not depend on the order keys were supplied in, and bundle entries
+//! refer to signers by their index in that order.
+
+use sha3::{ Digest, Sha3_256 };
+
+use crate::container::{ self, KEY_ID_LEN };
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+const POLICY_MAGIC: &[u8; 4] = b"AEGP";
+const BUNDLE_MAGIC: &[u8; 4] = b"AEGM";
+const VERSION: u8 = 1;
+const MESSAGE_LABEL: &[u8] = b"aegis-multisig-v1";
+const POLICY_HASH_LABEL: &[u8] = b"aegis-multisig-policy-v1";
+
+/// Upper bound on the number of keys in a policy.
+pub const MAX_SIGNERS: usize = 255;
+pub const POLICY_HASH_LEN: usize = 32;
+
+/// Multi-signature error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MultisigError {
+    Malformed(String),
+    InvalidPolicy(String),
+    /// The key is not a member of the policy.
+    UnknownSigner,
+    /// The same signer appears more than once in a bundle.
+    DuplicateSigner(usize),
+    /// The signature from the signer at this index does not verify.
+    BadSignature(usize),
+    /// The bundle was produced for a different policy.
+    PolicyMismatch,
+    ThresholdNotMet {
+        required: usize,
+        valid: usize,
+    },
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for MultisigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultisigError::Malformed(msg) => write!(f, "Malformed multisig data: {}", msg),
+            MultisigError::InvalidPolicy(msg) => write!(f, "Invalid multisig policy: {}", msg),
+            MultisigError::UnknownSigner => write!(f, "Signer is not part of the policy"),
+            MultisigError::DuplicateSigner(i) => write!(f, "Signer {} appears more than once", i),
+            MultisigError::BadSignature(i) => write!(f, "Signature from signer {} is invalid", i),
+            MultisigError::PolicyMismatch => write!(f, "Bundle does not match the policy"),
+            MultisigError::ThresholdNotMet { required, valid } => {
+                write!(f, "Threshold not met: {} of {} signatures", valid, required)
+            }
+            MultisigError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for MultisigError {}
+
+impl From<AlgorithmError> for MultisigError {
+    fn from(e: AlgorithmError) -> Self {
+        MultisigError::Algorithm(e)
+    }
+}
+
+/// A public key in a policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyKey {
+    pub algorithm: AlgorithmId,
+    pub public_key: Vec<u8>,
+}
+
+impl PolicyKey {
+    pub fn new(algorithm: AlgorithmId, public_key: &[u8]) -> Self {
+        PolicyKey { algorithm, public_key: public_key.to_vec() }
+    }
+
+    pub fn key_id(&self) -> [u8; KEY_ID_LEN] {
+        container::key_id(self.algorithm, &self.public_key)
+    }
+}
+
+/// A k-of-n signing policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigPolicy {
+    threshold: usize,
+    keys: Vec<PolicyKey>,
+    hash: [u8; POLICY_HASH_LEN],
+}
+
+impl MultisigPolicy {
+    /// Create a policy requiring `threshold` of `keys` to sign. Keys must
+    /// be distinct signature keys.
+    pub fn new(threshold: usize, keys: Vec<PolicyKey>) -> Result<Self, MultisigError> {
+        if keys.is_empty() || keys.len() > MAX_SIGNERS {
+            return Err(MultisigError::InvalidPolicy(format!("policy must have 1 to {} keys", MAX_SIGNERS)));
+        }
+        if threshold == 0 || threshold > keys.len() {
+            return Err(MultisigError::InvalidPolicy(format!("threshold must be 1 to {}", keys.len())));
+        }
+        for key in &keys {
+            if key.algorithm.kind() != AlgorithmKind::Signature {
+                return Err(AlgorithmError::Unsupported(key.algorithm).into());
+            }
+            if key.algorithm.public_key_len().is_some_and(|len| len != key.public_key.len()) {
+                return Err(AlgorithmError::InvalidKey.into());
+            }
+        }
+        let mut keys = keys;
+        keys.sort_by_cached_key(PolicyKey::key_id);
+        if keys.windows(2).any(|pair| pair[0].key_id() == pair[1].key_id()) {
+            return Err(MultisigError::InvalidPolicy("duplicate key".into()));
+        }
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(POLICY_HASH_LABEL);
+        hasher.update((threshold as u16).to_be_bytes());
+        hasher.update((keys.len() as u16).to_be_bytes());
+        for key in &keys {
+            hasher.update(key.algorithm.code().to_be_bytes());
+            hasher.update((key.public_key.len() as u32).to_be_bytes());
+            hasher.update(&key.public_key);
+        }
+        let hash = hasher.finalize().into();
+        Ok(MultisigPolicy { threshold, keys, hash })
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Keys in canonical order.
+    pub fn keys(&self) -> &[PolicyKey] {
+        &self.keys
+    }
+
+    /// SHA3-256 commitment to the threshold and key set.
+    pub fn policy_hash(&self) -> &[u8; POLICY_HASH_LEN] {
+        &self.hash
+    }
+
+    /// Index of a key in canonical order.
+    pub fn index_of(&self, algorithm: AlgorithmId, public_key: &[u8]) -> Option<usize> {
+        self.keys.iter().position(|key| key.algorithm == algorithm && key.public_key == public_key)
+    }
+
+    /// The bytes each signer signs for `payload`.
+    pub fn signing_message(&self, payload: &[u8]) -> Vec<u8> {
+        let mut message = Vec::with_capacity(MESSAGE_LABEL.len() + POLICY_HASH_LEN + payload.len());
+        message.extend_from_slice(MESSAGE_LABEL);
+        message.extend_from_slice(&self.hash);
+        message.extend_from_slice(payload);
+        message
+    }
+
+    /// Produce this signer's partial signature over `payload`.
+    pub fn sign(
+        &self,
+        algorithm: AlgorithmId,
+        public_key: &[u8],
+        secret_key: &[u8],
+        payload: &[u8]
+    ) -> Result<PartialSignature, MultisigError> {
+        let signer = self.index_of(algorithm, public_key).ok_or(MultisigError::UnknownSigner)?;
+        let signature = registry::sign(algorithm, secret_key, &self.signing_message(payload))?;
+        Ok(PartialSignature { signer, signature })
+    }
+
+    /// Check one partial signature.
+    pub fn verify_partial(&self, payload: &[u8], partial: &PartialSignature) -> Result<(), MultisigError> {
+        let key = self.keys.get(partial.signer).ok_or(MultisigError::UnknownSigner)?;
+        if registry::verify(key.algorithm, &key.public_key, &self.signing_message(payload), &partial.signature) {
+            Ok(())
+        } else {
+            Err(MultisigError::BadSignature(partial.signer))
+        }
+    }
+
+    /// Verify a bundle: it must name this policy, list each signer at most
+    /// once and carry at least `threshold` signatures, all of them valid.
+    pub fn verify(&self, payload: &[u8], bundle: &MultisigBundle) -> Result<(), MultisigError> {
+        if bundle.policy_hash != self.hash {
+            return Err(MultisigError::PolicyMismatch);
+        }
+        let mut seen = vec![false; self.keys.len()];
+        for partial in &bundle.signatures {
+            match seen.get_mut(partial.signer) {
+                None => {
+                    return Err(MultisigError::UnknownSigner);
+                }
+                Some(true) => {
+                    return Err(MultisigError::DuplicateSigner(partial.signer));
+                }
+                Some(flag) => {
+                    *flag = true;
+                }
+            }
+        }
+        if bundle.signatures.len() < self.threshold {
+            return Err(MultisigError::ThresholdNotMet {
+                required: self.threshold,
+                valid: bundle.signatures.len(),
+            });
+        }
+        bundle.signatures.iter().try_for_each(|partial| self.verify_partial(payload, partial))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(POLICY_MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&(self.threshold as u16).to_be_bytes());
+        out.extend_from_slice(&(self.keys.len() as u16).to_be_bytes());
+        for key in &self.keys {
+            out.extend_from_slice(&key.algorithm.code().to_be_bytes());
+            put_bytes(&mut out, &key.public_key);
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MultisigError> {
+        let mut reader = Reader::new(data, POLICY_MAGIC)?;
+        let threshold = reader.u16()? as usize;
+        let count = reader.u16()? as usize;
+        let mut keys = Vec::with_capacity(count.min(MAX_SIGNERS));
+        for _ in 0..count {
+            let algorithm = AlgorithmId::from_code(reader.u16()?)?;
+            keys.push(PolicyKey::new(algorithm, reader.bytes()?));
+        }
+        reader.finish()?;
+        let policy = MultisigPolicy::new(threshold, keys)?;
+        if policy.to_bytes() != data {
+            return Err(MultisigError::Malformed("keys are not in canonical order".into()));
+        }
+        Ok(policy)
+    }
+}
+
+/// One signer's contribution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialSignature {
+    /// Index of the signer in the policy's canonical key order.
+    pub signer: usize,
+    pub signature: Vec<u8>,
+}
+
+/// A threshold signature: `k` partial signatures under a policy hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigBundle {
+    pub policy_hash: [u8; POLICY_HASH_LEN],
+    /// Sorted by signer index.
+    pub signatures: Vec<PartialSignature>,
+}
+
+impl MultisigBundle {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(BUNDLE_MAGIC);
+        out.push(VERSION);
+        out.extend_from_slice(&self.policy_hash);
+        out.extend_from_slice(&(self.signatures.len() as u16).to_be_bytes());
+        for partial in &self.signatures {
+            out.extend_from_slice(&(partial.signer as u16).to_be_bytes());
+            put_bytes(&mut out, &partial.signature);
+        }
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, MultisigError> {
+        let mut reader = Reader::new(data, BUNDLE_MAGIC)?;
+        let policy_hash = reader.take(POLICY_HASH_LEN)?.try_into().expect("length checked");
+        let count = reader.u16()? as usize;
+        let mut signatures = Vec::with_capacity(count.min(MAX_SIGNERS));
+        for _ in 0..count {
+            let signer = reader.u16()? as usize;
+            signatures.push(PartialSignature { signer, signature: reader.bytes()?.to_vec() });
+        }
+        reader.finish()?;
+        Ok(MultisigBundle { policy_hash, signatures })
+    }
+}
+
+/// Gathers partial signatures over one payload until the threshold is met.
+pub struct SignatureCollector<'a> {
+    policy: &'a MultisigPolicy,
+    payload: Vec<u8>,
+    signatures: Vec<Option<Vec<u8>>>,
+}
+
+impl<'a> SignatureCollector<'a> {
+    pub fn new(policy: &'a MultisigPolicy, payload: &[u8]) -> Self {
+        SignatureCollector { policy, payload: payload.to_vec(), signatures: vec![None; policy.keys.len()] }
+    }
+
+    /// Verify and store a partial signature. Returns `false` if this signer
+    /// already contributed, in which case the new signature is ignored.
+    pub fn add(&mut self, partial: PartialSignature) -> Result<bool, MultisigError> {
+        self.policy.verify_partial(&self.payload, &partial)?;
+        let slot = &mut self.signatures[partial.signer];
+        if slot.is_some() {
+            return Ok(false);
+        }
+        *slot = Some(partial.signature);
+        Ok(true)
+    }
+
+    /// Number of distinct valid signers so far.
+    pub fn count(&self) -> usize {
+        self.signatures.iter().filter(|s| s.is_some()).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.count() >= self.policy.threshold
+    }
+
+    /// Emit a bundle with the first `threshold` signatures by signer index.
+    pub fn finish(self) -> Result<MultisigBundle, MultisigError> {
+        if !self.is_complete() {
+            return Err(MultisigError::ThresholdNotMet { required: self.policy.threshold, valid: self.count() });
+        }
+        let signatures = self.signatures
+            .into_iter()
+            .enumerate()
+            .filter_map(|(signer, signature)| signature.map(|signature| PartialSignature { signer, signature }))
+            .take(self.policy.threshold)
+            .collect();
+        Ok(MultisigBundle { policy_hash: self.policy.hash, signatures })
+    }
+}
+
+fn put_bytes(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], magic: &[u8; 4]) -> Result<Self, MultisigError> {
+        let mut reader = Reader { data };
+        if reader.take(4)? != magic {
+            return Err(MultisigError::Malformed("bad magic".into()));
+        }
+        if reader.take(1)?[0] != VERSION {
+            return Err(MultisigError::Malformed("unsupported version".into()));
+        }
+        Ok(reader)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], MultisigError> {
+        if self.data.len() < len {
+            return Err(MultisigError::Malformed("truncated".into()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, MultisigError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], MultisigError> {
+        let len = self.take(4)?;
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        self.take(len)
+    }
+
+    fn finish(&self) -> Result<(), MultisigError> {
+        if self.data.is_empty() { Ok(()) } else { Err(MultisigError::Malformed("trailing data".into())) }
+    }
+}
+
+#[cfg(all(test, feature = "dilithium", feature = "falcon"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_of_three_mixed_algorithms() {
+        let algorithms = [AlgorithmId::MlDsa44, AlgorithmId::MlDsa65, AlgorithmId::Falcon512];
+        let signers: Vec<_> = algorithms
+            .iter()
+            .map(|&alg| (alg, registry::keygen(alg).unwrap()))
+            .collect();
+        let keys = signers
+            .iter()
+            .map(|(alg, (pk, _))| PolicyKey::new(*alg, pk))
+            .collect();
+        let policy = MultisigPolicy::new(2, keys).unwrap();
+
+        let mut collector = SignatureCollector::new(&policy, b"transfer 10");
+        for (alg, (pk, sk)) in &signers[1..] {
+            assert!(collector.add(policy.sign(*alg, pk, sk, b"transfer 10").unwrap()).unwrap());
+        }
+        let bundle = collector.finish().unwrap();
+        assert_eq!(MultisigBundle::from_bytes(&bundle.to_bytes()).unwrap(), bundle);
+        assert_eq!(policy.verify(b"transfer 10", &bundle), Ok(()));
+        assert!(policy.verify(b"transfer 11", &bundle).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Noise cipher functions and `CipherState` (spec §4.2, §5.1).
+
+use aes_gcm::Aes256Gcm;
+use chacha20poly1305::{ aead::{ Aead, KeyInit, Payload }, ChaCha20Poly1305 };
+use zeroize::Zeroize;
+
+use super::NoiseError;
+
+/// Length of a cipher key.
+pub const KEY_LEN: usize = 32;
+/// Length of an AEAD authentication tag.
+pub const TAG_LEN: usize = 16;
+
+/// Noise cipher functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherChoice {
+    ChaChaPoly,
+    AesGcm,
+}
+
+impl CipherChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            CipherChoice::ChaChaPoly => "ChaChaPoly",
+            CipherChoice::AesGcm => "AESGCM",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ChaChaPoly" => Some(CipherChoice::ChaChaPoly),
+            "AESGCM" => Some(CipherChoice::AesGcm),
+            _ => None,
+        }
+    }
+
+    /// The 96-bit nonce: 32 zero bits then the counter, little-endian for
+    /// ChaChaPoly and big-endian for AESGCM.
+    fn nonce(self, n: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        match self {
+            CipherChoice::ChaChaPoly => nonce[4..].copy_from_slice(&n.to_le_bytes()),
+            CipherChoice::AesGcm => nonce[4..].copy_from_slice(&n.to_be_bytes()),
+        }
+        nonce
+    }
+
+    pub(crate) fn encrypt(self, k: &[u8; KEY_LEN], n: u64, ad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let nonce = self.nonce(n);
+        let payload = Payload { msg: plaintext, aad: ad };
+        match self {
+            CipherChoice::ChaChaPoly =>
+                ChaCha20Poly1305::new(k.into()).encrypt(&nonce.into(), payload),
+            CipherChoice::AesGcm => Aes256Gcm::new(k.into()).encrypt(&nonce.into(), payload),
+        }.expect("AEAD encryption of an in-memory buffer cannot fail")
+    }
+
+    pub(crate) fn decrypt(
+        self,
+        k: &[u8; KEY_LEN],
+        n: u64,
+        ad: &[u8],
+        ciphertext: &[u8]
+    ) -> Result<Vec<u8>, NoiseError> {
+        let nonce = self.nonce(n);
+        let payload = Payload { msg: ciphertext, aad: ad };
+        match self {
+            CipherChoice::ChaChaPoly =>
+                ChaCha20Poly1305::new(k.into()).decrypt(&nonce.into(), payload),
+            CipherChoice::AesGcm => Aes256Gcm::new(k.into()).decrypt(&nonce.into(), payload),
+        }.map_err(|_| NoiseError::Decrypt)
+    }
+}
+
+/// A cipher key and nonce counter.
+pub struct CipherState {
+    cipher: CipherChoice,
+    k: Option<[u8; KEY_LEN]>,
+    n: u64,
+}
+
+impl CipherState {
+    pub fn new(cipher: CipherChoice) -> Self {
+        CipherState { cipher, k: None, n: 0 }
+    }
+
+    /// Set the key (or clear it with `None`) and reset the nonce.
+    pub fn initialize_key(&mut self, key: Option<[u8; KEY_LEN]>) {
+        if let Some(k) = self.k.as_mut() {
+            k.zeroize();
+        }
+        self.k = key;
+        self.n = 0;
+    }
+
+    pub fn has_key(&self) -> bool {
+        self.k.is_some()
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.n = nonce;
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.n
+    }
+
+    /// Encrypt and advance the nonce. Without a key the plaintext is
+    /// returned unchanged.
+    pub fn encrypt_with_ad(&mut self, ad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let Some(k) = &self.k else {
+            return Ok(plaintext.to_vec());
+        };
+        // 2^64-1 is reserved for rekeying.
+        if self.n == u64::MAX {
+            return Err(NoiseError::NonceExhausted);
+        }
+        let ciphertext = self.cipher.encrypt(k, self.n, ad, plaintext);
+        self.n += 1;
+        Ok(ciphertext)
+    }
+
+    /// Decrypt and advance the nonce. The nonce is not advanced when
+    /// authentication fails.
+    pub fn decrypt_with_ad(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let Some(k) = &self.k else {
+            return Ok(ciphertext.to_vec());
+        };
+        if self.n == u64::MAX {
+            return Err(NoiseError::NonceExhausted);
+        }
+        let plaintext = self.cipher.decrypt(k, self.n, ad, ciphertext)?;
+        self.n += 1;
+        Ok(plaintext)
+    }
+
+    /// Replace the key with `ENCRYPT(k, 2^64-1, "", zeros)[..32]`.
+    pub fn rekey(&mut self) {
+        if let Some(k) = self.k.as_mut() {
+            let mut output = self.cipher.encrypt(k, u64::MAX, &[], &[0u8; KEY_LEN]);
+            k.copy_from_slice(&output[..KEY_LEN]);
+            output.zeroize();
+        }
+    }
+}
+
+impl Drop for CipherState {
+    fn drop(&mut self) {
+        self.initialize_key(None);
+    }
+}
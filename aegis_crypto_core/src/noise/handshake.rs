@@ -0,0 +1,348 @@
+//! `HandshakeState` (spec §5.3) and transport-phase state.
+
+use x25519_dalek::{ PublicKey, StaticSecret };
+
+use super::cipher::{ CipherState, TAG_LEN };
+use super::pattern::{ PatternKind, Token };
+use super::symmetric::SymmetricState;
+use super::{ Keypair, NoiseError, NoiseParams, MAX_MESSAGE_LEN };
+use crate::registry;
+
+/// Keys and prologue for a new handshake.
+#[derive(Default)]
+pub struct HandshakeOptions {
+    pub prologue: Vec<u8>,
+    /// Local static key pair (DH, or KEM for `pq` patterns).
+    pub s: Option<Keypair>,
+    /// Remote static public key, for patterns where it is known in advance.
+    pub rs: Option<Vec<u8>>,
+    /// Fixed ephemeral key pair. Only for test vectors; leave unset
+    /// otherwise so a fresh one is generated.
+    pub e: Option<Keypair>,
+    /// Fixed HFS KEM ephemeral key pair. Only for test vectors.
+    pub e1: Option<Keypair>,
+}
+
+/// A Noise handshake in progress.
+pub struct HandshakeState {
+    params: NoiseParams,
+    initiator: bool,
+    symmetric: SymmetricState,
+    s: Option<Keypair>,
+    e: Option<Keypair>,
+    e1: Option<Keypair>,
+    rs: Option<Vec<u8>>,
+    re: Option<Vec<u8>>,
+    re1: Option<Vec<u8>>,
+    message_index: usize,
+}
+
+fn dh(local: &Keypair, remote: &[u8]) -> Result<[u8; 32], NoiseError> {
+    let secret: [u8; 32] = local.secret_key().try_into().map_err(|_| NoiseError::MissingKey("DH secret"))?;
+    let remote: [u8; 32] = remote.try_into().map_err(|_| NoiseError::Malformed)?;
+    Ok(StaticSecret::from(secret).diffie_hellman(&PublicKey::from(remote)).to_bytes())
+}
+
+/// Cursor over an incoming handshake message.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], NoiseError> {
+        if self.0.len() < len {
+            return Err(NoiseError::Malformed);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+}
+
+impl HandshakeState {
+    /// `Initialize()`: mixes the protocol name, prologue and pre-message
+    /// public keys.
+    pub fn new(params: &NoiseParams, initiator: bool, options: HandshakeOptions) -> Result<Self, NoiseError> {
+        let mut symmetric = SymmetricState::initialize_symmetric(
+            params.name.as_bytes(),
+            params.cipher,
+            params.hash
+        );
+        symmetric.mix_hash(&options.prologue);
+        if params.pattern.responder_static_known {
+            let responder_static = if initiator {
+                options.rs.as_deref().ok_or(NoiseError::MissingKey("rs"))?
+            } else {
+                options.s.as_ref().map(|s| s.public.as_slice()).ok_or(NoiseError::MissingKey("s"))?
+            };
+            symmetric.mix_hash(responder_static);
+        }
+        Ok(HandshakeState {
+            params: params.clone(),
+            initiator,
+            symmetric,
+            s: options.s,
+            e: options.e,
+            e1: options.e1,
+            rs: options.rs,
+            re: None,
+            re1: None,
+            message_index: 0,
+        })
+    }
+
+    pub fn is_initiator(&self) -> bool {
+        self.initiator
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.message_index >= self.params.pattern.messages.len()
+    }
+
+    /// Whether the next handshake message is ours to write.
+    pub fn is_my_turn(&self) -> bool {
+        !self.is_finished() && self.message_index.is_multiple_of(2) == self.initiator
+    }
+
+    pub fn handshake_hash(&self) -> &[u8] {
+        self.symmetric.handshake_hash()
+    }
+
+    /// The peer's static public key, once known.
+    pub fn remote_static(&self) -> Option<&[u8]> {
+        self.rs.as_deref()
+    }
+
+    fn next_tokens(&self, writing: bool) -> Result<&'static [Token], NoiseError> {
+        if self.is_finished() {
+            return Err(NoiseError::HandshakeFinished);
+        }
+        if self.is_my_turn() != writing {
+            return Err(NoiseError::NotMyTurn);
+        }
+        Ok(self.params.pattern.messages[self.message_index])
+    }
+
+    fn new_ephemeral(&self) -> Result<Keypair, NoiseError> {
+        match self.params.pattern.kind {
+            PatternKind::Pq => Keypair::generate_kem(self.kem()?),
+            _ => Ok(Keypair::generate_dh()),
+        }
+    }
+
+    fn kem(&self) -> Result<registry::AlgorithmId, NoiseError> {
+        self.params.kem.ok_or(NoiseError::MissingKey("KEM"))
+    }
+
+    /// Public key length of `e` and `s` keys.
+    fn public_key_len(&self) -> Result<usize, NoiseError> {
+        match self.params.pattern.kind {
+            PatternKind::Pq => self.kem_public_key_len(),
+            _ => Ok(32),
+        }
+    }
+
+    fn kem_public_key_len(&self) -> Result<usize, NoiseError> {
+        self.kem()?.public_key_len().ok_or(NoiseError::MissingKey("KEM"))
+    }
+
+    fn kem_ciphertext_len(&self) -> Result<usize, NoiseError> {
+        self.kem()?.output_len().ok_or(NoiseError::MissingKey("KEM"))
+    }
+
+    fn tag_len(&self) -> usize {
+        if self.symmetric.has_key() { TAG_LEN } else { 0 }
+    }
+
+    /// The DH for `ee`, `es`, `se` or `ss`, from this party's point of view.
+    fn mix_dh(&mut self, token: Token) -> Result<(), NoiseError> {
+        let missing = |name| NoiseError::MissingKey(name);
+        let (local, remote) = match (token, self.initiator) {
+            (Token::Ee, _) => (self.e.as_ref().ok_or(missing("e"))?, self.re.as_deref().ok_or(missing("re"))?),
+            (Token::Ss, _) => (self.s.as_ref().ok_or(missing("s"))?, self.rs.as_deref().ok_or(missing("rs"))?),
+            (Token::Es, true) | (Token::Se, false) =>
+                (self.e.as_ref().ok_or(missing("e"))?, self.rs.as_deref().ok_or(missing("rs"))?),
+            (Token::Es, false) | (Token::Se, true) =>
+                (self.s.as_ref().ok_or(missing("s"))?, self.re.as_deref().ok_or(missing("re"))?),
+            _ => unreachable!("not a DH token"),
+        };
+        let shared = dh(local, remote)?;
+        self.symmetric.mix_key(&shared);
+        Ok(())
+    }
+
+    fn encapsulate(&self, remote: Option<&[u8]>, name: &'static str) -> Result<(Vec<u8>, Vec<u8>), NoiseError> {
+        let remote = remote.ok_or(NoiseError::MissingKey(name))?;
+        Ok(registry::encapsulate(self.kem()?, remote)?)
+    }
+
+    fn decapsulate(&self, local: Option<&Keypair>, name: &'static str, ciphertext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let local = local.ok_or(NoiseError::MissingKey(name))?;
+        Ok(registry::decapsulate(self.kem()?, local.secret_key(), ciphertext)?)
+    }
+
+    /// `WriteMessage(payload)`.
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let tokens = self.next_tokens(true)?;
+        let mut message = Vec::new();
+        for &token in tokens {
+            match token {
+                Token::E => {
+                    let e = match self.e.take() {
+                        Some(e) => e,
+                        None => self.new_ephemeral()?,
+                    };
+                    message.extend_from_slice(&e.public);
+                    self.symmetric.mix_hash(&e.public);
+                    self.e = Some(e);
+                }
+                Token::S => {
+                    let s = self.s.as_ref().ok_or(NoiseError::MissingKey("s"))?;
+                    let encrypted = self.symmetric.encrypt_and_hash(&s.public)?;
+                    message.extend_from_slice(&encrypted);
+                }
+                Token::Ee | Token::Es | Token::Se | Token::Ss => self.mix_dh(token)?,
+                Token::E1 => {
+                    let e1 = match self.e1.take() {
+                        Some(e1) => e1,
+                        None => Keypair::generate_kem(self.kem()?)?,
+                    };
+                    let encrypted = self.symmetric.encrypt_and_hash(&e1.public)?;
+                    message.extend_from_slice(&encrypted);
+                    self.e1 = Some(e1);
+                }
+                Token::Ekem1 | Token::Skem => {
+                    let (remote, name) = match token {
+                        Token::Ekem1 => (self.re1.as_deref(), "re1"),
+                        _ => (self.rs.as_deref(), "rs"),
+                    };
+                    let (ciphertext, shared_secret) = self.encapsulate(remote, name)?;
+                    let encrypted = self.symmetric.encrypt_and_hash(&ciphertext)?;
+                    message.extend_from_slice(&encrypted);
+                    self.symmetric.mix_key(&shared_secret);
+                }
+                Token::Ekem => {
+                    let (ciphertext, shared_secret) = self.encapsulate(self.re.as_deref(), "re")?;
+                    message.extend_from_slice(&ciphertext);
+                    self.symmetric.mix_hash(&ciphertext);
+                    self.symmetric.mix_key(&shared_secret);
+                }
+            }
+        }
+        message.extend_from_slice(&self.symmetric.encrypt_and_hash(payload)?);
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+        self.message_index += 1;
+        Ok(message)
+    }
+
+    /// `ReadMessage(message)`, returning the payload. A failed read leaves
+    /// the handshake unusable.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+        let tokens = self.next_tokens(false)?;
+        let mut reader = Reader(message);
+        for &token in tokens {
+            match token {
+                Token::E => {
+                    let re = reader.take(self.public_key_len()?)?;
+                    self.symmetric.mix_hash(re);
+                    self.re = Some(re.to_vec());
+                }
+                Token::S => {
+                    let len = self.public_key_len()? + self.tag_len();
+                    let rs = self.symmetric.decrypt_and_hash(reader.take(len)?)?;
+                    self.rs = Some(rs);
+                }
+                Token::Ee | Token::Es | Token::Se | Token::Ss => self.mix_dh(token)?,
+                Token::E1 => {
+                    let len = self.kem_public_key_len()? + self.tag_len();
+                    let re1 = self.symmetric.decrypt_and_hash(reader.take(len)?)?;
+                    self.re1 = Some(re1);
+                }
+                Token::Ekem1 | Token::Skem => {
+                    let len = self.kem_ciphertext_len()? + self.tag_len();
+                    let ciphertext = self.symmetric.decrypt_and_hash(reader.take(len)?)?;
+                    let shared_secret = match token {
+                        Token::Ekem1 => self.decapsulate(self.e1.as_ref(), "e1", &ciphertext)?,
+                        _ => self.decapsulate(self.s.as_ref(), "s", &ciphertext)?,
+                    };
+                    self.symmetric.mix_key(&shared_secret);
+                }
+                Token::Ekem => {
+                    let ciphertext = reader.take(self.kem_ciphertext_len()?)?;
+                    self.symmetric.mix_hash(ciphertext);
+                    let shared_secret = self.decapsulate(self.e.as_ref(), "e", ciphertext)?;
+                    self.symmetric.mix_key(&shared_secret);
+                }
+            }
+        }
+        let payload = self.symmetric.decrypt_and_hash(reader.0)?;
+        self.message_index += 1;
+        Ok(payload)
+    }
+
+    /// Finish the handshake with `Split()`.
+    pub fn into_transport_mode(self) -> Result<TransportState, NoiseError> {
+        if !self.is_finished() {
+            return Err(NoiseError::HandshakeNotFinished);
+        }
+        let (c1, c2) = self.symmetric.split();
+        let (send, recv) = if self.initiator { (c1, c2) } else { (c2, c1) };
+        Ok(TransportState {
+            send,
+            recv,
+            handshake_hash: self.symmetric.handshake_hash().to_vec(),
+            remote_static: self.rs.clone(),
+        })
+    }
+}
+
+/// Transport phase: one `CipherState` per direction.
+pub struct TransportState {
+    send: CipherState,
+    recv: CipherState,
+    handshake_hash: Vec<u8>,
+    remote_static: Option<Vec<u8>>,
+}
+
+impl TransportState {
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        if payload.len() + TAG_LEN > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+        self.send.encrypt_with_ad(&[], payload)
+    }
+
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        if message.len() > MAX_MESSAGE_LEN {
+            return Err(NoiseError::MessageTooLong);
+        }
+        self.recv.decrypt_with_ad(&[], message)
+    }
+
+    pub fn rekey_outgoing(&mut self) {
+        self.send.rekey();
+    }
+
+    pub fn rekey_incoming(&mut self) {
+        self.recv.rekey();
+    }
+
+    pub fn sending_nonce(&self) -> u64 {
+        self.send.nonce()
+    }
+
+    pub fn receiving_nonce(&self) -> u64 {
+        self.recv.nonce()
+    }
+
+    pub fn handshake_hash(&self) -> &[u8] {
+        &self.handshake_hash
+    }
+
+    pub fn remote_static(&self) -> Option<&[u8]> {
+        self.remote_static.as_deref()
+    }
+}
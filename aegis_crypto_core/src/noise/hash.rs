@@ -0,0 +1,88 @@
+//! Noise hash functions and HKDF (spec §4.3).
+
+use blake2::{ Blake2b512, Blake2s256 };
+use hmac::{ Mac, SimpleHmac };
+use sha2::{ Digest, Sha256, Sha512 };
+
+/// Noise hash functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashChoice {
+    Sha256,
+    Sha512,
+    Blake2s,
+    Blake2b,
+}
+
+impl HashChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            HashChoice::Sha256 => "SHA256",
+            HashChoice::Sha512 => "SHA512",
+            HashChoice::Blake2s => "BLAKE2s",
+            HashChoice::Blake2b => "BLAKE2b",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "SHA256" => Some(HashChoice::Sha256),
+            "SHA512" => Some(HashChoice::Sha512),
+            "BLAKE2s" => Some(HashChoice::Blake2s),
+            "BLAKE2b" => Some(HashChoice::Blake2b),
+            _ => None,
+        }
+    }
+
+    /// `HASHLEN`.
+    pub fn hash_len(self) -> usize {
+        match self {
+            HashChoice::Sha256 | HashChoice::Blake2s => 32,
+            HashChoice::Sha512 | HashChoice::Blake2b => 64,
+        }
+    }
+
+    /// Hash the concatenation of `parts`.
+    pub fn hash(self, parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+            let mut hasher = D::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            hasher.finalize().to_vec()
+        }
+        match self {
+            HashChoice::Sha256 => run::<Sha256>(parts),
+            HashChoice::Sha512 => run::<Sha512>(parts),
+            HashChoice::Blake2s => run::<Blake2s256>(parts),
+            HashChoice::Blake2b => run::<Blake2b512>(parts),
+        }
+    }
+
+    fn hmac(self, key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+        fn run<D: Digest + blake2::digest::core_api::BlockSizeUser>(key: &[u8], parts: &[&[u8]]) -> Vec<u8> {
+            let mut mac = <SimpleHmac<D> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+            for part in parts {
+                mac.update(part);
+            }
+            mac.finalize().into_bytes().to_vec()
+        }
+        match self {
+            HashChoice::Sha256 => run::<Sha256>(key, parts),
+            HashChoice::Sha512 => run::<Sha512>(key, parts),
+            HashChoice::Blake2s => run::<Blake2s256>(key, parts),
+            HashChoice::Blake2b => run::<Blake2b512>(key, parts),
+        }
+    }
+
+    /// Noise `HKDF(chaining_key, input_key_material, num_outputs)`,
+    /// returning `num_outputs` (2 or 3) outputs of `HASHLEN` bytes.
+    pub fn hkdf(self, chaining_key: &[u8], input_key_material: &[u8], num_outputs: usize) -> Vec<Vec<u8>> {
+        let temp_key = self.hmac(chaining_key, &[input_key_material]);
+        let mut outputs: Vec<Vec<u8>> = Vec::with_capacity(num_outputs);
+        for i in 1..=num_outputs as u8 {
+            let previous = outputs.last().map(Vec::as_slice).unwrap_or_default();
+            outputs.push(self.hmac(&temp_key, &[previous, &[i]]));
+        }
+        outputs
+    }
+}
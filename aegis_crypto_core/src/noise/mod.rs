@@ -0,0 +1,248 @@
+//! Noise Protocol Framework with post-quantum KEMs.
+//!
+//! Protocol names take the usual `Noise_<pattern>_<dh>_<cipher>_<hash>`
+//! form. Ciphers are `ChaChaPoly` and `AESGCM`; hashes are `SHA256`,
+//! `SHA512`, `BLAKE2s` and `BLAKE2b`. Supported patterns:
+//!
+//! * `NN`, `XX`, `IK` with `25519`, e.g. `Noise_XX_25519_ChaChaPoly_SHA256`.
+//! * Hybrid `NNhfs`, `XXhfs`, `IKhfs` (Noise HFS extension) with
+//!   `25519+MLKEM768` and the other ML-KEM sets: X25519 and an ML-KEM
+//!   ephemeral exchange are both mixed into the chaining key, e.g.
+//!   `Noise_XXhfs_25519+MLKEM768_ChaChaPoly_SHA256`.
+//! * PQNoise `pqXX`, `pqIK` with ML-KEM only; static keys are ML-KEM keys,
+//!   e.g. `Noise_pqIK_MLKEM768_AESGCM_SHA256`.
+//!
+//! [`CipherState`], [`SymmetricState`] and [`HandshakeState`] follow the
+//! operations of the Noise specification (revision 34). PSK modifiers,
+//! fallback and one-way patterns are not implemented.
+
+pub mod cipher;
+pub mod handshake;
+pub mod hash;
+pub mod pattern;
+pub mod symmetric;
+
+pub use cipher::{ CipherChoice, CipherState };
+pub use handshake::{ HandshakeOptions, HandshakeState, TransportState };
+pub use hash::HashChoice;
+pub use pattern::{ HandshakePattern, PatternKind, Token };
+pub use symmetric::SymmetricState;
+
+use x25519_dalek::{ PublicKey, StaticSecret };
+use zeroize::Zeroize;
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId };
+
+/// Maximum Noise message length.
+pub const MAX_MESSAGE_LEN: usize = 65_535;
+
+/// Noise error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoiseError {
+    InvalidProtocolName(String),
+    /// A key the pattern needs was not supplied.
+    MissingKey(&'static str),
+    NotMyTurn,
+    HandshakeFinished,
+    HandshakeNotFinished,
+    MessageTooLong,
+    Malformed,
+    Decrypt,
+    NonceExhausted,
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for NoiseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoiseError::InvalidProtocolName(name) => write!(f, "Unsupported Noise protocol: {}", name),
+            NoiseError::MissingKey(key) => write!(f, "Missing key: {}", key),
+            NoiseError::NotMyTurn => write!(f, "Not this party's turn"),
+            NoiseError::HandshakeFinished => write!(f, "Handshake already finished"),
+            NoiseError::HandshakeNotFinished => write!(f, "Handshake not finished"),
+            NoiseError::MessageTooLong => write!(f, "Noise message exceeds 65535 bytes"),
+            NoiseError::Malformed => write!(f, "Malformed Noise message"),
+            NoiseError::Decrypt => write!(f, "Decryption failed"),
+            NoiseError::NonceExhausted => write!(f, "Nonce exhausted"),
+            NoiseError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for NoiseError {}
+
+impl From<AlgorithmError> for NoiseError {
+    fn from(e: AlgorithmError) -> Self {
+        NoiseError::Algorithm(e)
+    }
+}
+
+/// Noise DH functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhChoice {
+    X25519,
+}
+
+impl DhChoice {
+    pub fn name(self) -> &'static str {
+        match self {
+            DhChoice::X25519 => "25519",
+        }
+    }
+}
+
+/// ML-KEM parameter sets by their Noise name, e.g. `MLKEM768`.
+fn kem_from_name(name: &str) -> Option<AlgorithmId> {
+    match name {
+        "MLKEM512" => Some(AlgorithmId::MlKem512),
+        "MLKEM768" => Some(AlgorithmId::MlKem768),
+        "MLKEM1024" => Some(AlgorithmId::MlKem1024),
+        _ => None,
+    }
+}
+
+/// A parsed protocol name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoiseParams {
+    pub name: String,
+    pub pattern: &'static HandshakePattern,
+    pub dh: Option<DhChoice>,
+    pub kem: Option<AlgorithmId>,
+    pub cipher: CipherChoice,
+    pub hash: HashChoice,
+}
+
+impl NoiseParams {
+    pub fn parse(name: &str) -> Result<Self, NoiseError> {
+        let invalid = || NoiseError::InvalidProtocolName(name.to_string());
+        let parts: Vec<&str> = name.split('_').collect();
+        let [prefix, pattern, functions, cipher, hash] = parts[..] else {
+            return Err(invalid());
+        };
+        if prefix != "Noise" {
+            return Err(invalid());
+        }
+        let pattern = HandshakePattern::from_name(pattern).ok_or_else(invalid)?;
+        let (dh, kem) = match (pattern.kind, functions.split_once('+')) {
+            (PatternKind::Dh, None) if functions == "25519" => (Some(DhChoice::X25519), None),
+            (PatternKind::Hfs, Some(("25519", kem))) => (Some(DhChoice::X25519), kem_from_name(kem)),
+            (PatternKind::Pq, None) => (None, kem_from_name(functions)),
+            _ => {
+                return Err(invalid());
+            }
+        };
+        if pattern.kind != PatternKind::Dh && kem.is_none() {
+            return Err(invalid());
+        }
+        Ok(NoiseParams {
+            name: name.to_string(),
+            pattern,
+            dh,
+            kem,
+            cipher: CipherChoice::from_name(cipher).ok_or_else(invalid)?,
+            hash: HashChoice::from_name(hash).ok_or_else(invalid)?,
+        })
+    }
+}
+
+impl std::str::FromStr for NoiseParams {
+    type Err = NoiseError;
+
+    fn from_str(name: &str) -> Result<Self, NoiseError> {
+        NoiseParams::parse(name)
+    }
+}
+
+/// A DH or KEM key pair.
+pub struct Keypair {
+    pub public: Vec<u8>,
+    secret: Vec<u8>,
+}
+
+impl Keypair {
+    pub fn generate_dh() -> Self {
+        let mut secret = [0u8; 32];
+        getrandom::fill(&mut secret).expect("RNG failed");
+        let keypair = Keypair::dh_from_secret(&secret);
+        secret.zeroize();
+        keypair
+    }
+
+    /// An X25519 key pair from its secret scalar.
+    pub fn dh_from_secret(secret: &[u8; 32]) -> Self {
+        let public = PublicKey::from(&StaticSecret::from(*secret)).to_bytes().to_vec();
+        Keypair { public, secret: secret.to_vec() }
+    }
+
+    pub fn generate_kem(kem: AlgorithmId) -> Result<Self, NoiseError> {
+        let (public, secret) = registry::keygen(kem)?;
+        Ok(Keypair { public, secret })
+    }
+
+    /// A KEM key pair from its encoded keys.
+    pub fn kem_from_bytes(public: &[u8], secret: &[u8]) -> Self {
+        Keypair { public: public.to_vec(), secret: secret.to_vec() }
+    }
+
+    pub fn secret_key(&self) -> &[u8] {
+        &self.secret
+    }
+}
+
+impl Drop for Keypair {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_protocol_names() {
+        let params = NoiseParams::parse("Noise_XXhfs_25519+MLKEM768_ChaChaPoly_BLAKE2s").unwrap();
+        assert_eq!(params.pattern.name, "XXhfs");
+        assert_eq!(params.dh, Some(DhChoice::X25519));
+        assert_eq!(params.kem, Some(AlgorithmId::MlKem768));
+        assert_eq!(params.hash, HashChoice::Blake2s);
+
+        let params = NoiseParams::parse("Noise_pqIK_MLKEM1024_AESGCM_SHA512").unwrap();
+        assert_eq!(params.dh, None);
+        assert_eq!(params.kem, Some(AlgorithmId::MlKem1024));
+
+        for bad in [
+            "Noise_XX_448_ChaChaPoly_SHA256",
+            "Noise_XX_25519+MLKEM768_ChaChaPoly_SHA256",
+            "Noise_pqXX_25519_ChaChaPoly_SHA256",
+            "Noise_XXhfs_25519_ChaChaPoly_SHA256",
+            "Noise_XX_25519_ChaChaPoly",
+            "Noise_KK_25519_ChaChaPoly_SHA256",
+        ] {
+            assert!(NoiseParams::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_cipher_state_rekey_and_nonce() {
+        let mut a = CipherState::new(CipherChoice::ChaChaPoly);
+        let mut b = CipherState::new(CipherChoice::ChaChaPoly);
+        assert_eq!(a.encrypt_with_ad(b"ad", b"clear").unwrap(), b"clear");
+        a.initialize_key(Some([7u8; 32]));
+        b.initialize_key(Some([7u8; 32]));
+
+        let ciphertext = a.encrypt_with_ad(b"ad", b"hello").unwrap();
+        assert_eq!(ciphertext.len(), 5 + cipher::TAG_LEN);
+        assert!(b.decrypt_with_ad(b"other", &ciphertext).is_err());
+        // A failed decryption does not consume the nonce.
+        assert_eq!(b.decrypt_with_ad(b"ad", &ciphertext).unwrap(), b"hello");
+
+        a.rekey();
+        b.rekey();
+        let ciphertext = a.encrypt_with_ad(&[], b"after rekey").unwrap();
+        assert_eq!(b.decrypt_with_ad(&[], &ciphertext).unwrap(), b"after rekey");
+
+        a.set_nonce(u64::MAX);
+        assert_eq!(a.encrypt_with_ad(&[], b"x"), Err(NoiseError::NonceExhausted));
+    }
+}
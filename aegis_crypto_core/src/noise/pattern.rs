@@ -0,0 +1,110 @@
+//! Handshake patterns.
+//!
+//! Classic DH patterns follow the Noise specification, the `hfs` variants
+//! follow the Noise HFS extension (an extra KEM ephemeral, `e1`/`ekem1`,
+//! mixed into a DH handshake), and the `pq` patterns follow PQNoise, where
+//! every DH is replaced by KEM encapsulation (`ekem`/`skem`).
+
+/// A pattern token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    E,
+    S,
+    Ee,
+    Es,
+    Se,
+    Ss,
+    /// HFS: KEM ephemeral public key, sent with `EncryptAndHash`.
+    E1,
+    /// HFS: encapsulation to the peer's `e1`, sent with `EncryptAndHash`.
+    Ekem1,
+    /// PQNoise: encapsulation to the peer's ephemeral KEM key, sent in the
+    /// clear and mixed with `MixHash`.
+    Ekem,
+    /// PQNoise: encapsulation to the peer's static KEM key, sent with
+    /// `EncryptAndHash`.
+    Skem,
+}
+
+/// Whether a pattern's key operations are DH, DH plus a KEM, or KEM only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternKind {
+    Dh,
+    Hfs,
+    Pq,
+}
+
+/// A handshake pattern: pre-messages and the message sequence. Even
+/// messages are sent by the initiator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakePattern {
+    pub name: &'static str,
+    pub kind: PatternKind,
+    /// Whether the responder's static key is a pre-message (`<- s`).
+    pub responder_static_known: bool,
+    pub messages: &'static [&'static [Token]],
+}
+
+use Token::*;
+
+const PATTERNS: &[HandshakePattern] = &[
+    HandshakePattern {
+        name: "NN",
+        kind: PatternKind::Dh,
+        responder_static_known: false,
+        messages: &[&[E], &[E, Ee]],
+    },
+    HandshakePattern {
+        name: "XX",
+        kind: PatternKind::Dh,
+        responder_static_known: false,
+        messages: &[&[E], &[E, Ee, S, Es], &[S, Se]],
+    },
+    HandshakePattern {
+        name: "IK",
+        kind: PatternKind::Dh,
+        responder_static_known: true,
+        messages: &[&[E, Es, S, Ss], &[E, Ee, Se]],
+    },
+    HandshakePattern {
+        name: "NNhfs",
+        kind: PatternKind::Hfs,
+        responder_static_known: false,
+        messages: &[&[E, E1], &[E, Ee, Ekem1]],
+    },
+    HandshakePattern {
+        name: "XXhfs",
+        kind: PatternKind::Hfs,
+        responder_static_known: false,
+        messages: &[&[E, E1], &[E, Ee, Ekem1, S, Es], &[S, Se]],
+    },
+    HandshakePattern {
+        name: "IKhfs",
+        kind: PatternKind::Hfs,
+        responder_static_known: true,
+        messages: &[&[E, Es, E1, S, Ss], &[E, Ee, Ekem1, Se]],
+    },
+    HandshakePattern {
+        name: "pqXX",
+        kind: PatternKind::Pq,
+        responder_static_known: false,
+        messages: &[&[E], &[Ekem, S], &[Skem, S], &[Skem]],
+    },
+    HandshakePattern {
+        name: "pqIK",
+        kind: PatternKind::Pq,
+        responder_static_known: true,
+        messages: &[&[Skem, E, S], &[Ekem, Skem]],
+    },
+];
+
+impl HandshakePattern {
+    pub fn from_name(name: &str) -> Option<&'static HandshakePattern> {
+        PATTERNS.iter().find(|pattern| pattern.name == name)
+    }
+
+    /// All supported patterns.
+    pub fn all() -> &'static [HandshakePattern] {
+        PATTERNS
+    }
+}
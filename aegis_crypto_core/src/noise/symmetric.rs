@@ -0,0 +1,100 @@
+//! `SymmetricState` (spec §5.2).
+
+use zeroize::Zeroize;
+
+use super::cipher::{ CipherChoice, CipherState, KEY_LEN };
+use super::hash::HashChoice;
+use super::NoiseError;
+
+/// Chaining key, handshake hash and the current handshake cipher.
+pub struct SymmetricState {
+    cipher_state: CipherState,
+    cipher: CipherChoice,
+    hash: HashChoice,
+    ck: Vec<u8>,
+    h: Vec<u8>,
+}
+
+fn truncate_key(output: &[u8]) -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&output[..KEY_LEN]);
+    key
+}
+
+impl SymmetricState {
+    /// `InitializeSymmetric(protocol_name)`.
+    pub fn initialize_symmetric(protocol_name: &[u8], cipher: CipherChoice, hash: HashChoice) -> Self {
+        let h = if protocol_name.len() <= hash.hash_len() {
+            let mut h = protocol_name.to_vec();
+            h.resize(hash.hash_len(), 0);
+            h
+        } else {
+            hash.hash(&[protocol_name])
+        };
+        SymmetricState { cipher_state: CipherState::new(cipher), cipher, hash, ck: h.clone(), h }
+    }
+
+    pub fn mix_key(&mut self, input_key_material: &[u8]) {
+        let mut outputs = self.hash.hkdf(&self.ck, input_key_material, 2);
+        self.ck.zeroize();
+        self.ck = std::mem::take(&mut outputs[0]);
+        self.cipher_state.initialize_key(Some(truncate_key(&outputs[1])));
+        outputs[1].zeroize();
+    }
+
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        self.h = self.hash.hash(&[&self.h, data]);
+    }
+
+    pub fn mix_key_and_hash(&mut self, input_key_material: &[u8]) {
+        let mut outputs = self.hash.hkdf(&self.ck, input_key_material, 3);
+        self.ck.zeroize();
+        self.ck = std::mem::take(&mut outputs[0]);
+        self.mix_hash(&outputs[1]);
+        self.cipher_state.initialize_key(Some(truncate_key(&outputs[2])));
+        for output in outputs.iter_mut() {
+            output.zeroize();
+        }
+    }
+
+    /// `GetHandshakeHash()`.
+    pub fn handshake_hash(&self) -> &[u8] {
+        &self.h
+    }
+
+    pub fn has_key(&self) -> bool {
+        self.cipher_state.has_key()
+    }
+
+    pub fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let ciphertext = self.cipher_state.encrypt_with_ad(&self.h, plaintext)?;
+        self.mix_hash(&ciphertext);
+        Ok(ciphertext)
+    }
+
+    pub fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, NoiseError> {
+        let plaintext = self.cipher_state.decrypt_with_ad(&self.h, ciphertext)?;
+        self.mix_hash(ciphertext);
+        Ok(plaintext)
+    }
+
+    /// `Split()`: the initiator-to-responder and responder-to-initiator
+    /// cipher states.
+    pub fn split(&self) -> (CipherState, CipherState) {
+        let mut outputs = self.hash.hkdf(&self.ck, &[], 2);
+        let mut c1 = CipherState::new(self.cipher);
+        let mut c2 = CipherState::new(self.cipher);
+        c1.initialize_key(Some(truncate_key(&outputs[0])));
+        c2.initialize_key(Some(truncate_key(&outputs[1])));
+        for output in outputs.iter_mut() {
+            output.zeroize();
+        }
+        (c1, c2)
+    }
+}
+
+impl Drop for SymmetricState {
+    fn drop(&mut self) {
+        self.ck.zeroize();
+    }
+}
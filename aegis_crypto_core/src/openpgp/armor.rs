@@ -0,0 +1,140 @@
+//! ASCII armor (RFC 9580, section 6).
+
+use base64::{ Engine as _, engine::general_purpose };
+use super::PgpError;
+
+/// The kind of data carried in an armored block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArmorKind {
+    PublicKey,
+    Message,
+    Signature,
+}
+
+impl ArmorKind {
+    fn label(self) -> &'static str {
+        match self {
+            ArmorKind::PublicKey => "PGP PUBLIC KEY BLOCK",
+            ArmorKind::Message => "PGP MESSAGE",
+            ArmorKind::Signature => "PGP SIGNATURE",
+        }
+    }
+
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "PGP PUBLIC KEY BLOCK" => Some(ArmorKind::PublicKey),
+            "PGP MESSAGE" => Some(ArmorKind::Message),
+            "PGP SIGNATURE" => Some(ArmorKind::Signature),
+            _ => None,
+        }
+    }
+}
+
+/// CRC-24 as defined in RFC 9580, section 6.1.1.
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0x00b7_04ce;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x0100_0000 != 0 {
+                crc ^= 0x0186_4cfb;
+            }
+        }
+    }
+    crc & 0x00ff_ffff
+}
+
+/// Armor binary data. No checksum line is emitted, as recommended for v6 data.
+pub fn armor(kind: ArmorKind, data: &[u8]) -> String {
+    let encoded = general_purpose::STANDARD.encode(data);
+    let mut out = format!("-----BEGIN {}-----\n\n", kind.label());
+    for line in encoded.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {}-----\n", kind.label()));
+    out
+}
+
+/// Remove ASCII armor, verifying the CRC-24 checksum when one is present.
+pub fn dearmor(text: &str) -> Result<(ArmorKind, Vec<u8>), PgpError> {
+    let mut lines = text.lines().map(str::trim_end);
+    let kind = loop {
+        let line = lines.next().ok_or_else(|| PgpError::Armor("missing BEGIN line".into()))?;
+        if let Some(label) = line.strip_prefix("-----BEGIN ").and_then(|l| l.strip_suffix("-----")) {
+            break ArmorKind::from_label(label).ok_or_else(||
+                PgpError::Armor(format!("unknown armor label: {}", label))
+            )?;
+        }
+    };
+
+    // Skip armor headers up to the first blank line.
+    for line in lines.by_ref() {
+        if line.is_empty() {
+            break;
+        }
+        if !line.contains(": ") {
+            return Err(PgpError::Armor("malformed armor header".into()));
+        }
+    }
+
+    let mut body = String::new();
+    let mut checksum = None;
+    let end = format!("-----END {}-----", kind.label());
+    let mut terminated = false;
+    for line in lines {
+        if line == end {
+            terminated = true;
+            break;
+        }
+        if let Some(crc) = line.strip_prefix('=') {
+            checksum = Some(crc.to_string());
+        } else {
+            body.push_str(line.trim());
+        }
+    }
+    if !terminated {
+        return Err(PgpError::Armor("missing END line".into()));
+    }
+
+    let data = general_purpose::STANDARD
+        .decode(body.as_bytes())
+        .map_err(|e| PgpError::Armor(format!("invalid base64: {}", e)))?;
+    if let Some(crc) = checksum {
+        let expected = general_purpose::STANDARD
+            .decode(crc.as_bytes())
+            .map_err(|e| PgpError::Armor(format!("invalid checksum: {}", e)))?;
+        let actual = crc24(&data).to_be_bytes();
+        if expected.as_slice() != &actual[1..] {
+            return Err(PgpError::Armor("checksum mismatch".into()));
+        }
+    }
+    Ok((kind, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc24_known_value() {
+        // Initial value of the register when no data has been processed.
+        assert_eq!(crc24(b""), 0x00b7_04ce);
+        assert_eq!(crc24(b"123456789"), 0x0021_cf02);
+    }
+
+    #[test]
+    fn test_armor_roundtrip_with_checksum() {
+        let data: Vec<u8> = (0..=255u8).collect();
+        let armored = armor(ArmorKind::Message, &data);
+        assert_eq!(dearmor(&armored).unwrap(), (ArmorKind::Message, data.clone()));
+
+        let crc = general_purpose::STANDARD.encode(&crc24(&data).to_be_bytes()[1..]);
+        let with_crc = armored.replace("-----END", &format!("={}\n-----END", crc));
+        assert_eq!(dearmor(&with_crc).unwrap().1, data);
+
+        let bad_crc = armored.replace("-----END", "=AAAA\n-----END");
+        assert!(dearmor(&bad_crc).is_err());
+    }
+}
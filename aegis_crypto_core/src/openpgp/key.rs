@@ -0,0 +1,588 @@
+//! v6 key material, signatures and transferable public keys.
+
+use ed25519_dalek::{ Signer as _, Verifier as _ };
+use sha2::Sha256;
+use sha3::{ Digest, Sha3_256 };
+use zeroize::Zeroize;
+
+use crate::{
+    dilithium65_keygen,
+    dilithium65_sign_detached,
+    dilithium65_verify_detached,
+    kyber768_keygen,
+};
+use super::packet::{
+    parse_packets,
+    parse_subpackets,
+    subpacket,
+    tag,
+    write_packets,
+    write_subpackets,
+    Packet,
+    Reader,
+    Subpacket,
+};
+use super::armor::{ armor, dearmor, ArmorKind };
+use super::{
+    random_bytes,
+    PgpError,
+    AEAD_GCM,
+    ALG_MLDSA65_ED25519,
+    ALG_MLKEM768_X25519,
+    HASH_SHA3_256,
+    SYM_AES256,
+};
+
+pub(crate) const ED25519_PUBLIC_LEN: usize = 32;
+pub(crate) const ED25519_SIG_LEN: usize = 64;
+pub(crate) const MLDSA65_PUBLIC_LEN: usize = 1952;
+pub(crate) const MLDSA65_SIG_LEN: usize = 3309;
+pub(crate) const X25519_LEN: usize = 32;
+pub(crate) const MLKEM768_PUBLIC_LEN: usize = 1184;
+pub(crate) const MLKEM768_CIPHERTEXT_LEN: usize = 1088;
+
+/// Salt length for SHA3-256 v6 signatures (RFC 9580, section 9.5).
+pub(crate) const SHA3_256_SALT_LEN: usize = 16;
+
+/// Signature types used by this implementation.
+pub mod sig_type {
+    pub const BINARY: u8 = 0x00;
+    pub const POSITIVE_CERTIFICATION: u8 = 0x13;
+    pub const SUBKEY_BINDING: u8 = 0x18;
+    pub const DIRECT_KEY: u8 = 0x1f;
+}
+
+/// Key flags (RFC 9580, section 5.2.3.29).
+const FLAG_CERTIFY: u8 = 0x01;
+const FLAG_SIGN: u8 = 0x02;
+const FLAG_ENCRYPT_COMMS: u8 = 0x04;
+const FLAG_ENCRYPT_STORAGE: u8 = 0x08;
+
+/// A v6 public key or public subkey packet body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicKey {
+    pub created: u32,
+    pub algorithm: u8,
+    pub material: Vec<u8>,
+}
+
+impl PublicKey {
+    /// Serialize the packet body.
+    pub fn body(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(10 + self.material.len());
+        out.push(6);
+        out.extend_from_slice(&self.created.to_be_bytes());
+        out.push(self.algorithm);
+        out.extend_from_slice(&(self.material.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.material);
+        out
+    }
+
+    /// Parse a v6 key packet body.
+    pub fn parse(body: &[u8]) -> Result<Self, PgpError> {
+        let mut r = Reader::new(body);
+        let version = r.u8()?;
+        if version != 6 {
+            return Err(PgpError::Unsupported(format!("key version {}", version)));
+        }
+        let created = r.u32()?;
+        let algorithm = r.u8()?;
+        let len = r.u32()? as usize;
+        let material = r.take(len)?.to_vec();
+        let expected = match algorithm {
+            ALG_MLDSA65_ED25519 => ED25519_PUBLIC_LEN + MLDSA65_PUBLIC_LEN,
+            ALG_MLKEM768_X25519 => X25519_LEN + MLKEM768_PUBLIC_LEN,
+            _ => {
+                return Err(PgpError::Unsupported(format!("public key algorithm {}", algorithm)));
+            }
+        };
+        if material.len() != expected || !r.rest().is_empty() {
+            return Err(PgpError::Malformed("bad key material length".into()));
+        }
+        Ok(PublicKey { created, algorithm, material })
+    }
+
+    /// The v6 fingerprint: SHA-256 over `0x9B || len || body`.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hash_key(&mut hasher, self);
+        hasher.finalize().into()
+    }
+
+    /// The key ID: the high-order 64 bits of the fingerprint.
+    pub fn key_id(&self) -> [u8; 8] {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&self.fingerprint()[..8]);
+        id
+    }
+
+    /// Verify a composite ML-DSA-65+Ed25519 signature over `digest`.
+    pub(crate) fn verify_digest(&self, digest: &[u8], material: &[u8]) -> bool {
+        if self.algorithm != ALG_MLDSA65_ED25519 ||
+            material.len() != ED25519_SIG_LEN + MLDSA65_SIG_LEN
+        {
+            return false;
+        }
+        let (ed_pk, mldsa_pk) = self.material.split_at(ED25519_PUBLIC_LEN);
+        let (ed_sig, mldsa_sig) = material.split_at(ED25519_SIG_LEN);
+        let ed_ok = ed25519_dalek::VerifyingKey
+            ::try_from(ed_pk)
+            .and_then(|vk| {
+                let sig = ed25519_dalek::Signature::from_slice(ed_sig)?;
+                vk.verify(digest, &sig)
+            })
+            .is_ok();
+        // Both components must verify; evaluate both to avoid an early-out.
+        let mldsa_ok = dilithium65_verify_detached(mldsa_pk, digest, mldsa_sig);
+        ed_ok & mldsa_ok
+    }
+}
+
+fn hash_key<D: Digest>(hasher: &mut D, key: &PublicKey) {
+    let body = key.body();
+    hasher.update([0x9b]);
+    hasher.update((body.len() as u32).to_be_bytes());
+    hasher.update(&body);
+}
+
+/// Composite ML-DSA-65+Ed25519 signing key (algorithm 30).
+pub struct SigningKey {
+    public: PublicKey,
+    ed25519: ed25519_dalek::SigningKey,
+    mldsa_secret: Vec<u8>,
+}
+
+impl SigningKey {
+    /// Generate a fresh composite signing key.
+    pub fn generate(created: u32) -> Self {
+        let mut seed = [0u8; 32];
+        random_bytes(&mut seed);
+        let ed25519 = ed25519_dalek::SigningKey::from_bytes(&seed);
+        seed.zeroize();
+        let mldsa = dilithium65_keygen();
+        let mut material = ed25519.verifying_key().to_bytes().to_vec();
+        material.extend_from_slice(&mldsa.public_key());
+        SigningKey {
+            public: PublicKey { created, algorithm: ALG_MLDSA65_ED25519, material },
+            ed25519,
+            mldsa_secret: mldsa.secret_key(),
+        }
+    }
+
+    pub fn public(&self) -> &PublicKey {
+        &self.public
+    }
+
+    /// Produce composite signature material (Ed25519 || ML-DSA-65) over `digest`.
+    pub(crate) fn sign_digest(&self, digest: &[u8]) -> Vec<u8> {
+        let mut out = self.ed25519.sign(digest).to_bytes().to_vec();
+        out.extend_from_slice(&dilithium65_sign_detached(&self.mldsa_secret, digest));
+        out
+    }
+}
+
+impl Drop for SigningKey {
+    fn drop(&mut self) {
+        self.mldsa_secret.zeroize();
+    }
+}
+
+/// Composite ML-KEM-768+X25519 decryption key (algorithm 35).
+pub struct DecryptionKey {
+    public: PublicKey,
+    pub(crate) x25519: x25519_dalek::StaticSecret,
+    pub(crate) mlkem_secret: Vec<u8>,
+}
+
+impl DecryptionKey {
+    /// Generate a fresh composite decryption key.
+    pub fn generate(created: u32) -> Self {
+        let mut seed = [0u8; 32];
+        random_bytes(&mut seed);
+        let x25519 = x25519_dalek::StaticSecret::from(seed);
+        seed.zeroize();
+        let mlkem = kyber768_keygen();
+        let mut material = x25519_dalek::PublicKey::from(&x25519).as_bytes().to_vec();
+        material.extend_from_slice(&mlkem.public_key());
+        DecryptionKey {
+            public: PublicKey { created, algorithm: ALG_MLKEM768_X25519, material },
+            x25519,
+            mlkem_secret: mlkem.secret_key(),
+        }
+    }
+
+    pub fn public(&self) -> &PublicKey {
+        &self.public
+    }
+}
+
+impl Drop for DecryptionKey {
+    fn drop(&mut self) {
+        self.mlkem_secret.zeroize();
+    }
+}
+
+/// A v6 signature packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignaturePacket {
+    pub sig_type: u8,
+    pub pk_algorithm: u8,
+    pub hash_algorithm: u8,
+    pub hashed: Vec<Subpacket>,
+    pub unhashed: Vec<Subpacket>,
+    pub left16: [u8; 2],
+    pub salt: Vec<u8>,
+    pub material: Vec<u8>,
+}
+
+impl SignaturePacket {
+    /// Create and sign a v6 signature. `data` is the signature-type specific
+    /// hash input (key material, user ID or document content).
+    pub(crate) fn create(
+        signer: &SigningKey,
+        sig_type: u8,
+        mut hashed: Vec<Subpacket>,
+        data: &[u8]
+    ) -> Self {
+        hashed.insert(
+            0,
+            Subpacket::new(subpacket::CREATION_TIME, true, now().to_be_bytes().to_vec())
+        );
+        let mut issuer = vec![6u8];
+        issuer.extend_from_slice(&signer.public().fingerprint());
+        hashed.push(Subpacket::new(subpacket::ISSUER_FINGERPRINT, false, issuer));
+
+        let mut salt = vec![0u8; SHA3_256_SALT_LEN];
+        random_bytes(&mut salt);
+        let mut sig = SignaturePacket {
+            sig_type,
+            pk_algorithm: ALG_MLDSA65_ED25519,
+            hash_algorithm: HASH_SHA3_256,
+            hashed,
+            unhashed: Vec::new(),
+            left16: [0; 2],
+            salt,
+            material: Vec::new(),
+        };
+        let digest = sig.digest(data);
+        sig.left16 = [digest[0], digest[1]];
+        sig.material = signer.sign_digest(&digest);
+        sig
+    }
+
+    /// Compute the v6 signature digest: `salt || data || trailer`.
+    pub(crate) fn digest(&self, data: &[u8]) -> [u8; 32] {
+        let hashed = write_subpackets(&self.hashed);
+        let mut header = vec![6, self.sig_type, self.pk_algorithm, self.hash_algorithm];
+        header.extend_from_slice(&(hashed.len() as u32).to_be_bytes());
+        header.extend_from_slice(&hashed);
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(&self.salt);
+        hasher.update(data);
+        hasher.update(&header);
+        hasher.update([0x06, 0xff]);
+        hasher.update((header.len() as u32).to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Verify this signature over `data` with `signer`.
+    pub fn verify(&self, signer: &PublicKey, data: &[u8]) -> bool {
+        if self.hash_algorithm != HASH_SHA3_256 || self.pk_algorithm != signer.algorithm {
+            return false;
+        }
+        let digest = self.digest(data);
+        digest[..2] == self.left16 && signer.verify_digest(&digest, &self.material)
+    }
+
+    /// The issuer fingerprint, if present in the hashed area.
+    pub fn issuer_fingerprint(&self) -> Option<[u8; 32]> {
+        self.hashed
+            .iter()
+            .find(|sp| sp.kind == subpacket::ISSUER_FINGERPRINT && sp.data.len() == 33)
+            .map(|sp| {
+                let mut fp = [0u8; 32];
+                fp.copy_from_slice(&sp.data[1..]);
+                fp
+            })
+    }
+
+    pub fn body(&self) -> Vec<u8> {
+        let hashed = write_subpackets(&self.hashed);
+        let unhashed = write_subpackets(&self.unhashed);
+        let mut out = vec![6, self.sig_type, self.pk_algorithm, self.hash_algorithm];
+        out.extend_from_slice(&(hashed.len() as u32).to_be_bytes());
+        out.extend_from_slice(&hashed);
+        out.extend_from_slice(&(unhashed.len() as u32).to_be_bytes());
+        out.extend_from_slice(&unhashed);
+        out.extend_from_slice(&self.left16);
+        out.push(self.salt.len() as u8);
+        out.extend_from_slice(&self.salt);
+        out.extend_from_slice(&self.material);
+        out
+    }
+
+    pub fn parse(body: &[u8]) -> Result<Self, PgpError> {
+        let mut r = Reader::new(body);
+        let version = r.u8()?;
+        if version != 6 {
+            return Err(PgpError::Unsupported(format!("signature version {}", version)));
+        }
+        let sig_type = r.u8()?;
+        let pk_algorithm = r.u8()?;
+        let hash_algorithm = r.u8()?;
+        let hashed_len = r.u32()? as usize;
+        let hashed = parse_subpackets(r.take(hashed_len)?)?;
+        let unhashed_len = r.u32()? as usize;
+        let unhashed = parse_subpackets(r.take(unhashed_len)?)?;
+        let left = r.take(2)?;
+        let salt_len = r.u8()? as usize;
+        let salt = r.take(salt_len)?.to_vec();
+        let material = r.rest().to_vec();
+        Ok(SignaturePacket {
+            sig_type,
+            pk_algorithm,
+            hash_algorithm,
+            hashed,
+            unhashed,
+            left16: [left[0], left[1]],
+            salt,
+            material,
+        })
+    }
+
+    pub fn to_packet(&self) -> Packet {
+        Packet::new(tag::SIGNATURE, self.body())
+    }
+}
+
+/// Hash input for a key signature over `primary` (and optionally a subkey).
+fn key_signature_data(primary: &PublicKey, subkey: Option<&PublicKey>) -> Vec<u8> {
+    let mut out = Vec::new();
+    for key in std::iter::once(primary).chain(subkey) {
+        let body = key.body();
+        out.push(0x9b);
+        out.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        out.extend_from_slice(&body);
+    }
+    out
+}
+
+/// Hash input for a certification over `primary` and `user_id`.
+fn certification_data(primary: &PublicKey, user_id: &str) -> Vec<u8> {
+    let mut out = key_signature_data(primary, None);
+    out.push(0xb4);
+    out.extend_from_slice(&(user_id.len() as u32).to_be_bytes());
+    out.extend_from_slice(user_id.as_bytes());
+    out
+}
+
+/// An OpenPGP transferable public key (RFC 9580, section 10.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferablePublicKey {
+    pub primary: PublicKey,
+    pub direct_signatures: Vec<SignaturePacket>,
+    pub user_ids: Vec<(String, Vec<SignaturePacket>)>,
+    pub subkeys: Vec<(PublicKey, Vec<SignaturePacket>)>,
+}
+
+impl TransferablePublicKey {
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.primary.fingerprint()
+    }
+
+    /// Verify every self-signature. Subkeys must carry a valid binding signature.
+    pub fn verify(&self) -> Result<(), PgpError> {
+        let direct = key_signature_data(&self.primary, None);
+        if self.direct_signatures.is_empty() {
+            return Err(PgpError::BadSignature);
+        }
+        for sig in &self.direct_signatures {
+            if sig.sig_type != sig_type::DIRECT_KEY || !sig.verify(&self.primary, &direct) {
+                return Err(PgpError::BadSignature);
+            }
+        }
+        for (uid, sigs) in &self.user_ids {
+            let data = certification_data(&self.primary, uid);
+            if !sigs.iter().any(|s| s.verify(&self.primary, &data)) {
+                return Err(PgpError::BadSignature);
+            }
+        }
+        for (subkey, sigs) in &self.subkeys {
+            let data = key_signature_data(&self.primary, Some(subkey));
+            let bound = sigs
+                .iter()
+                .any(|s| s.sig_type == sig_type::SUBKEY_BINDING && s.verify(&self.primary, &data));
+            if !bound {
+                return Err(PgpError::BadSignature);
+            }
+        }
+        Ok(())
+    }
+
+    /// The first bound ML-KEM-768+X25519 subkey, if any.
+    pub fn encryption_subkey(&self) -> Option<&PublicKey> {
+        self.subkeys
+            .iter()
+            .map(|(key, _)| key)
+            .find(|key| key.algorithm == ALG_MLKEM768_X25519)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut packets = vec![Packet::new(tag::PUBLIC_KEY, self.primary.body())];
+        packets.extend(self.direct_signatures.iter().map(SignaturePacket::to_packet));
+        for (uid, sigs) in &self.user_ids {
+            packets.push(Packet::new(tag::USER_ID, uid.as_bytes().to_vec()));
+            packets.extend(sigs.iter().map(SignaturePacket::to_packet));
+        }
+        for (subkey, sigs) in &self.subkeys {
+            packets.push(Packet::new(tag::PUBLIC_SUBKEY, subkey.body()));
+            packets.extend(sigs.iter().map(SignaturePacket::to_packet));
+        }
+        write_packets(&packets)
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, PgpError> {
+        let mut packets = parse_packets(data)?
+            .into_iter()
+            .filter(|p| p.tag != tag::TRUST && p.tag != tag::PADDING)
+            .peekable();
+        let first = packets.next().ok_or_else(|| PgpError::Malformed("empty key".into()))?;
+        if first.tag != tag::PUBLIC_KEY {
+            return Err(PgpError::Malformed("expected a public key packet".into()));
+        }
+        let mut tpk = TransferablePublicKey {
+            primary: PublicKey::parse(&first.body)?,
+            direct_signatures: Vec::new(),
+            user_ids: Vec::new(),
+            subkeys: Vec::new(),
+        };
+        while let Some(packet) = packets.next_if(|p| p.tag == tag::SIGNATURE) {
+            tpk.direct_signatures.push(SignaturePacket::parse(&packet.body)?);
+        }
+        for packet in packets.by_ref() {
+            match packet.tag {
+                tag::USER_ID => {
+                    let uid = String::from_utf8(packet.body).map_err(|_|
+                        PgpError::Malformed("user ID is not UTF-8".into())
+                    )?;
+                    tpk.user_ids.push((uid, Vec::new()));
+                }
+                tag::PUBLIC_SUBKEY => {
+                    tpk.subkeys.push((PublicKey::parse(&packet.body)?, Vec::new()));
+                }
+                tag::SIGNATURE => {
+                    let sig = SignaturePacket::parse(&packet.body)?;
+                    // Signatures attach to the most recent component.
+                    if let Some((_, sigs)) = tpk.subkeys.last_mut() {
+                        sigs.push(sig);
+                    } else if let Some((_, sigs)) = tpk.user_ids.last_mut() {
+                        sigs.push(sig);
+                    } else {
+                        return Err(PgpError::Malformed("unexpected signature".into()));
+                    }
+                }
+                other => {
+                    return Err(PgpError::Malformed(format!("unexpected packet tag {}", other)));
+                }
+            }
+        }
+        Ok(tpk)
+    }
+
+    pub fn to_armored(&self) -> String {
+        armor(ArmorKind::PublicKey, &self.to_bytes())
+    }
+
+    pub fn from_armored(text: &str) -> Result<Self, PgpError> {
+        match dearmor(text)? {
+            (ArmorKind::PublicKey, data) => Self::from_bytes(&data),
+            (kind, _) => Err(PgpError::Armor(format!("expected a public key, found {:?}", kind))),
+        }
+    }
+}
+
+/// Transferable public keys serialize as their binary packet sequence.
+#[cfg(feature = "serde")]
+impl serde::Serialize for TransferablePublicKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serialization::bytes::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TransferablePublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::serialization::bytes::deserialize(deserializer)?;
+        TransferablePublicKey::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+/// A secret OpenPGP key: an ML-DSA-65+Ed25519 primary key with an
+/// ML-KEM-768+X25519 encryption subkey.
+pub struct SecretKey {
+    pub signing: SigningKey,
+    pub decryption: DecryptionKey,
+    user_id: String,
+}
+
+impl SecretKey {
+    /// Generate a new key with the given user ID.
+    pub fn generate(user_id: &str) -> Self {
+        let created = now();
+        SecretKey {
+            signing: SigningKey::generate(created),
+            decryption: DecryptionKey::generate(created),
+            user_id: user_id.to_string(),
+        }
+    }
+
+    /// Build the self-signed transferable public key.
+    pub fn to_public(&self) -> TransferablePublicKey {
+        let primary = self.signing.public().clone();
+        let preferences = vec![
+            Subpacket::new(subpacket::KEY_FLAGS, true, vec![FLAG_CERTIFY | FLAG_SIGN]),
+            Subpacket::new(subpacket::FEATURES, false, vec![0x09]),
+            Subpacket::new(subpacket::PREFERRED_AEAD_CIPHERSUITES, false, vec![SYM_AES256, AEAD_GCM])
+        ];
+        let direct = SignaturePacket::create(
+            &self.signing,
+            sig_type::DIRECT_KEY,
+            preferences,
+            &key_signature_data(&primary, None)
+        );
+        let certification = SignaturePacket::create(
+            &self.signing,
+            sig_type::POSITIVE_CERTIFICATION,
+            Vec::new(),
+            &certification_data(&primary, &self.user_id)
+        );
+        let subkey = self.decryption.public().clone();
+        let binding = SignaturePacket::create(
+            &self.signing,
+            sig_type::SUBKEY_BINDING,
+            vec![
+                Subpacket::new(
+                    subpacket::KEY_FLAGS,
+                    true,
+                    vec![FLAG_ENCRYPT_COMMS | FLAG_ENCRYPT_STORAGE]
+                )
+            ],
+            &key_signature_data(&primary, Some(&subkey))
+        );
+        TransferablePublicKey {
+            primary,
+            direct_signatures: vec![direct],
+            user_ids: vec![(self.user_id.clone(), vec![certification])],
+            subkeys: vec![(subkey, vec![binding])],
+        }
+    }
+}
+
+pub(crate) fn now() -> u32 {
+    std::time::SystemTime
+        ::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as u32)
+        .unwrap_or(0)
+}
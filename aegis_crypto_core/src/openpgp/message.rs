@@ -0,0 +1,392 @@
+//! Signed and encrypted messages: literal data, one-pass signatures,
+//! v6 PKESK packets with the ML-KEM-768+X25519 key combiner, and
+//! version 2 SEIPD packets (AES-256-GCM).
+
+use aes_gcm::{ aead::{ Aead, KeyInit, Payload }, Aes256Gcm, Nonce };
+use aes_kw::KekAes256;
+use hkdf::Hkdf;
+use sha2::Sha256;
+use sha3::{ Digest, Sha3_256 };
+use zeroize::Zeroize;
+
+use crate::{ kyber768_decapsulate, kyber768_encapsulate };
+use super::armor::{ armor, dearmor, ArmorKind };
+use super::key::{
+    sig_type,
+    DecryptionKey,
+    PublicKey,
+    SignaturePacket,
+    SigningKey,
+    TransferablePublicKey,
+    MLKEM768_CIPHERTEXT_LEN,
+    SHA3_256_SALT_LEN,
+    X25519_LEN,
+};
+use super::packet::{ parse_packets, tag, write_packets, Packet, Reader };
+use super::{ random_bytes, PgpError, AEAD_GCM, ALG_MLKEM768_X25519, SYM_AES256 };
+
+/// Domain separator of the composite KEM combiner.
+const KDF_DOMAIN: &[u8] = b"OpenPGPCompositeKDFv1";
+
+/// Chunk size octet for SEIPDv2: chunks of 2^(10+6) = 64 KiB.
+const CHUNK_SIZE_OCTET: u8 = 10;
+const GCM_TAG_LEN: usize = 16;
+const GCM_IV_LEN: usize = 4;
+
+/// A literal data packet (RFC 9580, section 5.9).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralData {
+    pub filename: String,
+    pub date: u32,
+    pub data: Vec<u8>,
+}
+
+impl LiteralData {
+    pub fn new(filename: &str, data: &[u8]) -> Self {
+        LiteralData { filename: filename.to_string(), date: super::key::now(), data: data.to_vec() }
+    }
+
+    fn to_packet(&self) -> Packet {
+        let name = self.filename.as_bytes();
+        let name = &name[..name.len().min(255)];
+        let mut body = vec![b'b', name.len() as u8];
+        body.extend_from_slice(name);
+        body.extend_from_slice(&self.date.to_be_bytes());
+        body.extend_from_slice(&self.data);
+        Packet::new(tag::LITERAL_DATA, body)
+    }
+
+    fn parse(body: &[u8]) -> Result<Self, PgpError> {
+        let mut r = Reader::new(body);
+        let _format = r.u8()?;
+        let name_len = r.u8()? as usize;
+        let filename = String::from_utf8_lossy(r.take(name_len)?).into_owned();
+        let date = r.u32()?;
+        Ok(LiteralData { filename, date, data: r.rest().to_vec() })
+    }
+}
+
+/// A parsed, decrypted message.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub literal: LiteralData,
+    pub signatures: Vec<SignaturePacket>,
+}
+
+impl Message {
+    /// Verify that `signer`'s primary key produced at least one valid signature.
+    pub fn verify(&self, signer: &TransferablePublicKey) -> Result<(), PgpError> {
+        let valid = self.signatures
+            .iter()
+            .any(|sig| sig.sig_type == sig_type::BINARY && sig.verify(&signer.primary, &self.literal.data));
+        if valid { Ok(()) } else { Err(PgpError::BadSignature) }
+    }
+}
+
+fn one_pass_signature(sig: &SignaturePacket, signer: &PublicKey) -> Packet {
+    let mut body = vec![6, sig.sig_type, sig.hash_algorithm, sig.pk_algorithm];
+    body.push(sig.salt.len() as u8);
+    body.extend_from_slice(&sig.salt);
+    body.extend_from_slice(&signer.fingerprint());
+    body.push(1);
+    Packet::new(tag::ONE_PASS_SIGNATURE, body)
+}
+
+/// Produce a signed message: one-pass signature, literal data, signature.
+pub fn sign_message(signer: &SigningKey, literal: &LiteralData) -> Vec<u8> {
+    let sig = SignaturePacket::create(signer, sig_type::BINARY, Vec::new(), &literal.data);
+    debug_assert_eq!(sig.salt.len(), SHA3_256_SALT_LEN);
+    write_packets(&[one_pass_signature(&sig, signer.public()), literal.to_packet(), sig.to_packet()])
+}
+
+/// Parse a (decrypted) packet sequence into a literal and its signatures.
+pub fn read_message(data: &[u8]) -> Result<Message, PgpError> {
+    let mut literal = None;
+    let mut signatures = Vec::new();
+    for packet in parse_packets(data)? {
+        match packet.tag {
+            tag::LITERAL_DATA => {
+                if literal.is_some() {
+                    return Err(PgpError::Malformed("multiple literal data packets".into()));
+                }
+                literal = Some(LiteralData::parse(&packet.body)?);
+            }
+            tag::SIGNATURE => signatures.push(SignaturePacket::parse(&packet.body)?),
+            tag::ONE_PASS_SIGNATURE | tag::PADDING => {}
+            other => {
+                return Err(PgpError::Unsupported(format!("packet tag {} in message", other)));
+            }
+        }
+    }
+    let literal = literal.ok_or_else(|| PgpError::Malformed("no literal data".into()))?;
+    Ok(Message { literal, signatures })
+}
+
+/// Composite KEM combiner from draft-ietf-openpgp-pqc.
+fn combine_keys(
+    mlkem_share: &[u8],
+    ecdh_share: &[u8],
+    ecdh_ciphertext: &[u8],
+    ecdh_public: &[u8]
+) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(mlkem_share);
+    hasher.update(ecdh_share);
+    hasher.update(ecdh_ciphertext);
+    hasher.update(ecdh_public);
+    hasher.update([ALG_MLKEM768_X25519]);
+    hasher.update(KDF_DOMAIN);
+    hasher.update([KDF_DOMAIN.len() as u8]);
+    hasher.finalize().into()
+}
+
+/// Build a v6 PKESK packet wrapping `session_key` to `recipient`.
+fn encrypt_session_key(recipient: &PublicKey, session_key: &[u8]) -> Result<Packet, PgpError> {
+    if recipient.algorithm != ALG_MLKEM768_X25519 {
+        return Err(PgpError::Unsupported("recipient is not an ML-KEM-768+X25519 key".into()));
+    }
+    let (ecdh_public, mlkem_public) = recipient.material.split_at(X25519_LEN);
+    let ecdh_public: [u8; 32] = ecdh_public.try_into().expect("length checked at parse time");
+
+    let mut eph_seed = [0u8; 32];
+    random_bytes(&mut eph_seed);
+    let ephemeral = x25519_dalek::StaticSecret::from(eph_seed);
+    eph_seed.zeroize();
+    let ecdh_ciphertext = x25519_dalek::PublicKey::from(&ephemeral);
+    let ecdh_share = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(ecdh_public));
+
+    let encapsulated = kyber768_encapsulate(mlkem_public).map_err(|e|
+        PgpError::Crypto(format!("ML-KEM encapsulation failed: {}", e))
+    )?;
+    let mut mlkem_share = encapsulated.shared_secret();
+    let mut kek = combine_keys(
+        &mlkem_share,
+        ecdh_share.as_bytes(),
+        ecdh_ciphertext.as_bytes(),
+        &ecdh_public
+    );
+    mlkem_share.zeroize();
+    let wrapped = KekAes256::from(kek)
+        .wrap_vec(session_key)
+        .map_err(|e| PgpError::Crypto(format!("key wrap failed: {}", e)))?;
+    kek.zeroize();
+
+    let mut body = vec![6, 33, 6];
+    body.extend_from_slice(&recipient.fingerprint());
+    body.push(ALG_MLKEM768_X25519);
+    body.extend_from_slice(ecdh_ciphertext.as_bytes());
+    body.extend_from_slice(&encapsulated.ciphertext());
+    body.push(wrapped.len() as u8);
+    body.extend_from_slice(&wrapped);
+    Ok(Packet::new(tag::PKESK, body))
+}
+
+/// Try to recover the session key from a v6 PKESK body. Returns `Ok(None)`
+/// if the packet is addressed to a different key.
+fn decrypt_session_key(key: &DecryptionKey, body: &[u8]) -> Result<Option<Vec<u8>>, PgpError> {
+    let mut r = Reader::new(body);
+    if r.u8()? != 6 {
+        return Err(PgpError::Unsupported("PKESK version".into()));
+    }
+    let fp_len = r.u8()? as usize;
+    let fp = r.take(fp_len)?;
+    // A zero-length field denotes an anonymous recipient.
+    if fp_len != 0 && (fp_len != 33 || fp[1..] != key.public().fingerprint()) {
+        return Ok(None);
+    }
+    if r.u8()? != ALG_MLKEM768_X25519 {
+        return Ok(None);
+    }
+    let ecdh_ciphertext: [u8; 32] = r.take(X25519_LEN)?.try_into().expect("fixed length");
+    let mlkem_ciphertext = r.take(MLKEM768_CIPHERTEXT_LEN)?;
+    let wrapped_len = r.u8()? as usize;
+    let wrapped = r.take(wrapped_len)?;
+
+    let ecdh_share = key.x25519.diffie_hellman(&x25519_dalek::PublicKey::from(ecdh_ciphertext));
+    let mut mlkem_share = kyber768_decapsulate(&key.mlkem_secret, mlkem_ciphertext).map_err(|e|
+        PgpError::Crypto(format!("ML-KEM decapsulation failed: {}", e))
+    )?;
+    let mut kek = combine_keys(
+        &mlkem_share,
+        ecdh_share.as_bytes(),
+        &ecdh_ciphertext,
+        &key.public().material[..X25519_LEN]
+    );
+    mlkem_share.zeroize();
+    let unwrapped = KekAes256::from(kek).unwrap_vec(wrapped);
+    kek.zeroize();
+    // An unwrap failure for an anonymous recipient just means "not for us".
+    match unwrapped {
+        Ok(session_key) => Ok(Some(session_key)),
+        Err(_) if fp_len == 0 => Ok(None),
+        Err(_) => Err(PgpError::Crypto("session key unwrap failed".into())),
+    }
+}
+
+struct SeipdKeys {
+    cipher: Aes256Gcm,
+    iv: [u8; GCM_IV_LEN],
+    header: [u8; 5],
+}
+
+impl SeipdKeys {
+    fn derive(session_key: &[u8], salt: &[u8]) -> Result<Self, PgpError> {
+        let header = [0xc0 | tag::SEIPD, 2, SYM_AES256, AEAD_GCM, CHUNK_SIZE_OCTET];
+        let mut okm = [0u8; 32 + GCM_IV_LEN];
+        Hkdf::<Sha256>
+            ::new(Some(salt), session_key)
+            .expand(&header, &mut okm)
+            .map_err(|_| PgpError::Crypto("HKDF expansion failed".into()))?;
+        let cipher = Aes256Gcm::new_from_slice(&okm[..32]).expect("32-byte key");
+        let mut iv = [0u8; GCM_IV_LEN];
+        iv.copy_from_slice(&okm[32..]);
+        okm.zeroize();
+        Ok(SeipdKeys { cipher, iv, header })
+    }
+
+    fn nonce(&self, index: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[..GCM_IV_LEN].copy_from_slice(&self.iv);
+        nonce[GCM_IV_LEN..].copy_from_slice(&index.to_be_bytes());
+        nonce
+    }
+
+    fn final_aad(&self, total: u64) -> Vec<u8> {
+        let mut aad = self.header.to_vec();
+        aad.extend_from_slice(&total.to_be_bytes());
+        aad
+    }
+}
+
+fn chunk_size() -> usize {
+    1usize << ((CHUNK_SIZE_OCTET as usize) + 6)
+}
+
+fn seal_seipd(session_key: &[u8], plaintext: &[u8]) -> Result<Packet, PgpError> {
+    let mut salt = [0u8; 32];
+    random_bytes(&mut salt);
+    let keys = SeipdKeys::derive(session_key, &salt)?;
+    let mut body = keys.header[1..].to_vec();
+    body.extend_from_slice(&salt);
+
+    let mut index = 0u64;
+    for chunk in plaintext.chunks(chunk_size()) {
+        let nonce = keys.nonce(index);
+        let ct = keys.cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: chunk, aad: &keys.header })
+            .map_err(|_| PgpError::Crypto("AEAD encryption failed".into()))?;
+        body.extend_from_slice(&ct);
+        index += 1;
+    }
+    let nonce = keys.nonce(index);
+    let final_tag = keys.cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload {
+            msg: &[],
+            aad: &keys.final_aad(plaintext.len() as u64),
+        })
+        .map_err(|_| PgpError::Crypto("AEAD encryption failed".into()))?;
+    body.extend_from_slice(&final_tag);
+    Ok(Packet::new(tag::SEIPD, body))
+}
+
+fn open_seipd(session_key: &[u8], body: &[u8]) -> Result<Vec<u8>, PgpError> {
+    let mut r = Reader::new(body);
+    let header = r.take(4)?;
+    if header != [2, SYM_AES256, AEAD_GCM, CHUNK_SIZE_OCTET] {
+        return Err(PgpError::Unsupported("SEIPD parameters".into()));
+    }
+    let salt = r.take(32)?;
+    let keys = SeipdKeys::derive(session_key, salt)?;
+    let rest = r.rest();
+    if rest.len() < GCM_TAG_LEN {
+        return Err(PgpError::Malformed("SEIPD packet too short".into()));
+    }
+    let (chunks, final_tag) = rest.split_at(rest.len() - GCM_TAG_LEN);
+
+    let mut plaintext = Vec::with_capacity(chunks.len());
+    let mut index = 0u64;
+    for chunk in chunks.chunks(chunk_size() + GCM_TAG_LEN) {
+        let nonce = keys.nonce(index);
+        let pt = keys.cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: chunk, aad: &keys.header })
+            .map_err(|_| PgpError::Crypto("AEAD authentication failed".into()))?;
+        plaintext.extend_from_slice(&pt);
+        index += 1;
+    }
+    let nonce = keys.nonce(index);
+    keys.cipher
+        .decrypt(Nonce::from_slice(&nonce), Payload {
+            msg: final_tag,
+            aad: &keys.final_aad(plaintext.len() as u64),
+        })
+        .map_err(|_| PgpError::Crypto("final AEAD tag mismatch (truncated message?)".into()))?;
+    Ok(plaintext)
+}
+
+/// Encrypt a packet sequence to each recipient's ML-KEM-768+X25519 subkey.
+pub fn encrypt_packets(
+    recipients: &[&TransferablePublicKey],
+    packets: &[u8]
+) -> Result<Vec<u8>, PgpError> {
+    let mut session_key = [0u8; 32];
+    random_bytes(&mut session_key);
+    let mut out = Vec::new();
+    for recipient in recipients {
+        let subkey = recipient
+            .encryption_subkey()
+            .ok_or_else(|| PgpError::Unsupported("recipient has no ML-KEM subkey".into()))?;
+        out.push(encrypt_session_key(subkey, &session_key)?);
+    }
+    let seipd = seal_seipd(&session_key, packets);
+    session_key.zeroize();
+    out.push(seipd?);
+    Ok(write_packets(&out))
+}
+
+/// Decrypt an encrypted message and return the inner packet sequence.
+pub fn decrypt_packets(key: &DecryptionKey, data: &[u8]) -> Result<Vec<u8>, PgpError> {
+    let mut session_key = None;
+    let mut seipd = None;
+    for packet in parse_packets(data)? {
+        match packet.tag {
+            tag::PKESK if session_key.is_none() => {
+                session_key = decrypt_session_key(key, &packet.body)?;
+            }
+            tag::PKESK | tag::PADDING => {}
+            tag::SEIPD => {
+                seipd = Some(packet.body);
+            }
+            other => {
+                return Err(PgpError::Unsupported(format!("packet tag {} in message", other)));
+            }
+        }
+    }
+    let mut session_key = session_key.ok_or(PgpError::NoMatchingKey)?;
+    let seipd = seipd.ok_or_else(|| PgpError::Malformed("no encrypted data packet".into()))?;
+    let plaintext = open_seipd(&session_key, &seipd);
+    session_key.zeroize();
+    plaintext
+}
+
+/// Sign `literal` with `signer`, encrypt to `recipients`, and armor the result.
+pub fn encrypt_message(
+    signer: Option<&SigningKey>,
+    recipients: &[&TransferablePublicKey],
+    literal: &LiteralData
+) -> Result<String, PgpError> {
+    let inner = match signer {
+        Some(signer) => sign_message(signer, literal),
+        None => literal.to_packet().to_bytes(),
+    };
+    Ok(armor(ArmorKind::Message, &encrypt_packets(recipients, &inner)?))
+}
+
+/// Dearmor and decrypt a message. Signatures are returned unverified; call
+/// [`Message::verify`] with the expected signer.
+pub fn decrypt_message(key: &DecryptionKey, armored: &str) -> Result<Message, PgpError> {
+    let (kind, data) = dearmor(armored)?;
+    if kind != ArmorKind::Message {
+        return Err(PgpError::Armor(format!("expected a message, found {:?}", kind)));
+    }
+    read_message(&decrypt_packets(key, &data)?)
+}
@@ -0,0 +1,129 @@
+//! OpenPGP v6 support for post-quantum composite keys.
+//!
+//! Implements packet encoding and parsing for RFC 9580 v6 keys, signatures
+//! and PKESK packets using the composite algorithms of
+//! draft-ietf-openpgp-pqc:
+//!
+//! * ML-DSA-65+Ed25519 (algorithm 30) for primary keys and signatures.
+//! * ML-KEM-768+X25519 (algorithm 35) for encryption subkeys.
+//!
+//! Encrypted messages use version 2 SEIPD packets with AES-256-GCM.
+//! Only public keys are exported; secret key material stays in memory.
+
+pub mod armor;
+pub mod key;
+pub mod message;
+pub mod packet;
+
+pub use armor::{ armor, dearmor, ArmorKind };
+pub use key::{ DecryptionKey, PublicKey, SecretKey, SignaturePacket, SigningKey, TransferablePublicKey };
+pub use message::{
+    decrypt_message,
+    decrypt_packets,
+    encrypt_message,
+    encrypt_packets,
+    read_message,
+    sign_message,
+    LiteralData,
+    Message,
+};
+
+/// Public key algorithm: ML-DSA-65+Ed25519.
+pub const ALG_MLDSA65_ED25519: u8 = 30;
+/// Public key algorithm: ML-KEM-768+X25519.
+pub const ALG_MLKEM768_X25519: u8 = 35;
+/// Hash algorithm: SHA3-256.
+pub const HASH_SHA3_256: u8 = 12;
+/// Symmetric algorithm: AES-256.
+pub const SYM_AES256: u8 = 9;
+/// AEAD algorithm: GCM.
+pub const AEAD_GCM: u8 = 3;
+
+/// OpenPGP-specific error type.
+#[derive(Debug, Clone)]
+pub enum PgpError {
+    Malformed(String),
+    Unsupported(String),
+    Armor(String),
+    Crypto(String),
+    NoMatchingKey,
+    BadSignature,
+}
+
+impl std::fmt::Display for PgpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgpError::Malformed(msg) => write!(f, "Malformed packet: {}", msg),
+            PgpError::Unsupported(msg) => write!(f, "Unsupported: {}", msg),
+            PgpError::Armor(msg) => write!(f, "Armor error: {}", msg),
+            PgpError::Crypto(msg) => write!(f, "Cryptographic failure: {}", msg),
+            PgpError::NoMatchingKey => write!(f, "No PKESK packet matches the decryption key"),
+            PgpError::BadSignature => write!(f, "Bad signature"),
+        }
+    }
+}
+
+impl std::error::Error for PgpError {}
+
+pub(crate) fn random_bytes(buf: &mut [u8]) {
+    getrandom::fill(buf).expect("RNG failed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transferable_public_key_roundtrip() {
+        let key = SecretKey::generate("Alice <alice@example.org>");
+        let tpk = key.to_public();
+        tpk.verify().unwrap();
+
+        let parsed = TransferablePublicKey::from_armored(&tpk.to_armored()).unwrap();
+        assert_eq!(parsed, tpk);
+        parsed.verify().unwrap();
+        assert_eq!(parsed.user_ids[0].0, "Alice <alice@example.org>");
+        assert_eq!(parsed.encryption_subkey().unwrap().algorithm, ALG_MLKEM768_X25519);
+    }
+
+    #[test]
+    fn test_tampered_key_fails_verification() {
+        let mut tpk = SecretKey::generate("Bob").to_public();
+        tpk.user_ids[0].0 = "Mallory".to_string();
+        assert!(tpk.verify().is_err());
+    }
+
+    #[test]
+    fn test_sign_and_encrypt_literal_message() {
+        let alice = SecretKey::generate("Alice");
+        let bob = SecretKey::generate("Bob");
+        let literal = LiteralData::new("hello.txt", b"post-quantum hello");
+
+        let armored = encrypt_message(Some(&alice.signing), &[&bob.to_public()], &literal).unwrap();
+        assert!(armored.starts_with("-----BEGIN PGP MESSAGE-----"));
+
+        let message = decrypt_message(&bob.decryption, &armored).unwrap();
+        assert_eq!(message.literal, literal);
+        message.verify(&alice.to_public()).unwrap();
+        assert!(message.verify(&bob.to_public()).is_err());
+
+        assert!(matches!(decrypt_message(&alice.decryption, &armored), Err(PgpError::NoMatchingKey)));
+    }
+
+    #[test]
+    fn test_truncated_ciphertext_is_rejected() {
+        let bob = SecretKey::generate("Bob");
+        let literal = LiteralData::new("", &[7u8; 100_000]);
+        let mut data = encrypt_packets(&[&bob.to_public()], &sign_message(&bob.signing, &literal)).unwrap();
+        assert!(decrypt_packets(&bob.decryption, &data).is_ok());
+
+        // Keep only the first chunk and the final tag.
+        let packets = packet::parse_packets(&data).unwrap();
+        let body = &packets[1].body;
+        let mut truncated = body[..4 + 32 + 65536 + 16].to_vec();
+        truncated.extend_from_slice(&body[body.len() - 16..]);
+        let seipd = packet::Packet::new(packet::tag::SEIPD, truncated);
+        data = packet::write_packets(&[packets[0].clone(), seipd]);
+        assert!(decrypt_packets(&bob.decryption, &data).is_err());
+    }
+}
@@ -0,0 +1,228 @@
+//! OpenPGP packet framing (RFC 9580, section 4) and signature subpackets.
+
+use super::PgpError;
+
+/// Packet tags used by this implementation.
+pub mod tag {
+    pub const PKESK: u8 = 1;
+    pub const SIGNATURE: u8 = 2;
+    pub const ONE_PASS_SIGNATURE: u8 = 4;
+    pub const PUBLIC_KEY: u8 = 6;
+    pub const LITERAL_DATA: u8 = 11;
+    pub const TRUST: u8 = 12;
+    pub const USER_ID: u8 = 13;
+    pub const PUBLIC_SUBKEY: u8 = 14;
+    pub const SEIPD: u8 = 18;
+    pub const PADDING: u8 = 21;
+}
+
+/// Signature subpacket types used by this implementation.
+pub mod subpacket {
+    pub const CREATION_TIME: u8 = 2;
+    pub const KEY_EXPIRATION_TIME: u8 = 9;
+    pub const KEY_FLAGS: u8 = 27;
+    pub const FEATURES: u8 = 30;
+    pub const ISSUER_FINGERPRINT: u8 = 33;
+    pub const PREFERRED_AEAD_CIPHERSUITES: u8 = 39;
+}
+
+/// A raw OpenPGP packet: its tag and body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    pub tag: u8,
+    pub body: Vec<u8>,
+}
+
+impl Packet {
+    pub fn new(tag: u8, body: Vec<u8>) -> Self {
+        Packet { tag, body }
+    }
+
+    /// Serialize the packet with a new-format header.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.body.len() + 6);
+        out.push(0xc0 | self.tag);
+        write_length(&mut out, self.body.len());
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+/// Encode a new-format length (also used for subpacket lengths).
+pub fn write_length(out: &mut Vec<u8>, len: usize) {
+    if len < 192 {
+        out.push(len as u8);
+    } else if len < 8384 {
+        let v = len - 192;
+        out.push(((v >> 8) as u8) + 192);
+        out.push((v & 0xff) as u8);
+    } else {
+        out.push(0xff);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+/// Decode a new-format length, returning `(length, octets consumed)`.
+fn read_length(data: &[u8]) -> Result<(usize, usize), PgpError> {
+    let first = *data.first().ok_or_else(|| PgpError::Malformed("missing length".into()))?;
+    match first {
+        0..=191 => Ok((first as usize, 1)),
+        192..=223 => {
+            let second = *data
+                .get(1)
+                .ok_or_else(|| PgpError::Malformed("truncated length".into()))?;
+            Ok(((((first as usize) - 192) << 8) + (second as usize) + 192, 2))
+        }
+        255 => {
+            let bytes = data
+                .get(1..5)
+                .ok_or_else(|| PgpError::Malformed("truncated length".into()))?;
+            Ok((u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize, 5))
+        }
+        _ => Err(PgpError::Unsupported("partial body lengths".into())),
+    }
+}
+
+/// Split a byte stream into packets. Both new and legacy headers are accepted.
+pub fn parse_packets(mut data: &[u8]) -> Result<Vec<Packet>, PgpError> {
+    let mut packets = Vec::new();
+    while !data.is_empty() {
+        let header = data[0];
+        if header & 0x80 == 0 {
+            return Err(PgpError::Malformed("packet header bit 7 not set".into()));
+        }
+        let (tag, len, consumed) = if header & 0x40 != 0 {
+            let (len, n) = read_length(&data[1..])?;
+            (header & 0x3f, len, 1 + n)
+        } else {
+            let tag = (header >> 2) & 0x0f;
+            let (len, n) = match header & 0x03 {
+                0 => (*data.get(1).ok_or_else(|| PgpError::Malformed("truncated".into()))? as usize, 1),
+                1 => {
+                    let b = data.get(1..3).ok_or_else(|| PgpError::Malformed("truncated".into()))?;
+                    (u16::from_be_bytes([b[0], b[1]]) as usize, 2)
+                }
+                2 => {
+                    let b = data.get(1..5).ok_or_else(|| PgpError::Malformed("truncated".into()))?;
+                    (u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize, 4)
+                }
+                _ => {
+                    return Err(PgpError::Unsupported("indeterminate length packets".into()));
+                }
+            };
+            (tag, len, 1 + n)
+        };
+        let end = consumed
+            .checked_add(len)
+            .filter(|end| *end <= data.len())
+            .ok_or_else(|| PgpError::Malformed("packet body exceeds input".into()))?;
+        packets.push(Packet::new(tag, data[consumed..end].to_vec()));
+        data = &data[end..];
+    }
+    Ok(packets)
+}
+
+/// Serialize a sequence of packets.
+pub fn write_packets(packets: &[Packet]) -> Vec<u8> {
+    packets.iter().flat_map(|p| p.to_bytes()).collect()
+}
+
+/// A signature subpacket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subpacket {
+    pub kind: u8,
+    pub critical: bool,
+    pub data: Vec<u8>,
+}
+
+impl Subpacket {
+    pub fn new(kind: u8, critical: bool, data: Vec<u8>) -> Self {
+        Subpacket { kind, critical, data }
+    }
+}
+
+/// Serialize a subpacket area (without its 4-octet length prefix).
+pub fn write_subpackets(subpackets: &[Subpacket]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for sp in subpackets {
+        write_length(&mut out, sp.data.len() + 1);
+        out.push(if sp.critical { 0x80 | sp.kind } else { sp.kind });
+        out.extend_from_slice(&sp.data);
+    }
+    out
+}
+
+/// Parse a subpacket area.
+pub fn parse_subpackets(mut data: &[u8]) -> Result<Vec<Subpacket>, PgpError> {
+    let mut out = Vec::new();
+    while !data.is_empty() {
+        let (len, n) = read_length(data)?;
+        if len == 0 || n + len > data.len() {
+            return Err(PgpError::Malformed("bad subpacket length".into()));
+        }
+        let kind = data[n];
+        out.push(Subpacket::new(kind & 0x7f, kind & 0x80 != 0, data[n + 1..n + len].to_vec()));
+        data = &data[n + len..];
+    }
+    Ok(out)
+}
+
+/// Minimal cursor over a packet body.
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], PgpError> {
+        let end = self.pos
+            .checked_add(n)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| PgpError::Malformed("truncated packet body".into()))?;
+        let out = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, PgpError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32, PgpError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub fn rest(&mut self) -> &'a [u8] {
+        let out = &self.data[self.pos..];
+        self.pos = self.data.len();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_encodings_roundtrip() {
+        for len in [0usize, 191, 192, 8383, 8384, 100_000] {
+            let packet = Packet::new(tag::LITERAL_DATA, vec![0x42; len]);
+            let parsed = parse_packets(&packet.to_bytes()).unwrap();
+            assert_eq!(parsed, vec![packet]);
+        }
+    }
+
+    #[test]
+    fn test_legacy_header_parsing() {
+        // Legacy tag 13 (User ID), one-octet length.
+        let data = [0xb4, 0x03, b'a', b'b', b'c'];
+        let parsed = parse_packets(&data).unwrap();
+        assert_eq!(parsed[0].tag, tag::USER_ID);
+        assert_eq!(parsed[0].body, b"abc");
+    }
+}
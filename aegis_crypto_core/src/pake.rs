@@ -0,0 +1,446 @@
+//! Password-authenticated key exchange with post-quantum protection.
+//!
+//! Two parties that share only a low-entropy password, such as a pairing
+//! code, derive a strong session key. The protocol is CPace over
+//! ristretto255 combined with an ML-KEM exchange:
+//!
+//! ```text
+//! Initiator                                     Responder
+//! Ya = ya·G(pw), KEM pk               -->
+//!                                     <--  Yb = yb·G(pw), KEM ct, confirm_r
+//! confirm_i                           -->
+//! ```
+//!
+//! `G(pw)` is a ristretto255 point hashed from the password, the session
+//! id and both party identifiers, so only someone who knows the password
+//! can compute the Diffie-Hellman secret `ya·yb·G`. An attacker gets one
+//! password guess per protocol run and learns nothing from a failed run
+//! that would help an offline dictionary attack. The session key is
+//! derived from both the CPace secret and the ML-KEM shared secret, bound
+//! to the full transcript:
+//!
+//! - A recorded session stays confidential against a future quantum
+//!   adversary, because the key also depends on the ML-KEM secret.
+//! - An active attacker with a quantum computer could solve discrete logs
+//!   and test password guesses offline after one run. Password security
+//!   itself is therefore classical.
+//!
+//! Both sides send an HMAC key confirmation tag. The responder's tag
+//! arrives first, so the initiator detects a wrong password before it
+//! sends anything more.
+//!
+//! [`Pake`] is sans-IO: it only consumes and produces byte strings.
+//! [`loopback`] drives both sides in memory.
+
+use curve25519_dalek::ristretto::{ CompressedRistretto, RistrettoPoint };
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use hkdf::Hkdf;
+use hmac::{ Hmac, Mac };
+use sha2::{ Digest, Sha256, Sha512 };
+use zeroize::{ Zeroize, Zeroizing };
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+const VERSION: u8 = 1;
+
+const MSG_INITIATOR: u8 = 1;
+const MSG_RESPONDER: u8 = 2;
+const MSG_CONFIRM: u8 = 3;
+
+const PROTOCOL_LABEL: &[u8] = b"aegis-pake-v1";
+const GENERATOR_LABEL: &[u8] = b"CPaceRistretto255";
+
+const POINT_LEN: usize = 32;
+const TAG_LEN: usize = 32;
+/// Length of the derived session key.
+pub const KEY_LEN: usize = 32;
+
+/// PAKE error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PakeError {
+    Malformed(String),
+    /// A message arrived out of order, or a message was requested when it
+    /// was the peer's turn.
+    UnexpectedMessage,
+    UnsupportedAlgorithm(AlgorithmId),
+    /// The peer sent an invalid or identity group element.
+    InvalidPoint,
+    /// Key confirmation failed: the peer used a different password,
+    /// session id or identifiers, or the messages were modified.
+    BadConfirmation,
+    NotComplete,
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for PakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PakeError::Malformed(msg) => write!(f, "Malformed PAKE message: {}", msg),
+            PakeError::UnexpectedMessage => write!(f, "Unexpected PAKE message"),
+            PakeError::UnsupportedAlgorithm(alg) => write!(f, "Unsupported algorithm: {}", alg.name()),
+            PakeError::InvalidPoint => write!(f, "Invalid group element"),
+            PakeError::BadConfirmation => write!(f, "Key confirmation failed"),
+            PakeError::NotComplete => write!(f, "PAKE is not complete"),
+            PakeError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PakeError {}
+
+impl From<AlgorithmError> for PakeError {
+    fn from(e: AlgorithmError) -> Self {
+        PakeError::Algorithm(e)
+    }
+}
+
+/// PAKE parameters for one party. Both sides must use the same values.
+pub struct PakeConfig {
+    /// KEM for the post-quantum exchange.
+    pub kem: AlgorithmId,
+    /// Optional session identifier agreed out of band, such as a pairing
+    /// request id. Binding it prevents messages being replayed across runs.
+    pub session_id: Vec<u8>,
+    pub initiator_id: Vec<u8>,
+    pub responder_id: Vec<u8>,
+    password: Zeroizing<Vec<u8>>,
+}
+
+impl PakeConfig {
+    /// ML-KEM-768 with empty session id and identifiers.
+    pub fn new(password: &[u8]) -> Self {
+        PakeConfig {
+            kem: AlgorithmId::MlKem768,
+            session_id: Vec::new(),
+            initiator_id: Vec::new(),
+            responder_id: Vec::new(),
+            password: Zeroizing::new(password.to_vec()),
+        }
+    }
+
+    pub fn with_session_id(mut self, session_id: &[u8]) -> Self {
+        self.session_id = session_id.to_vec();
+        self
+    }
+
+    pub fn with_identities(mut self, initiator_id: &[u8], responder_id: &[u8]) -> Self {
+        self.initiator_id = initiator_id.to_vec();
+        self.responder_id = responder_id.to_vec();
+        self
+    }
+
+    /// The password-derived generator `G(pw)`.
+    fn generator(&self) -> RistrettoPoint {
+        let mut hasher = Sha512::new();
+        for field in [
+            GENERATOR_LABEL,
+            &self.password[..],
+            &self.initiator_id,
+            &self.responder_id,
+            &self.session_id,
+        ] {
+            hasher.update((field.len() as u32).to_be_bytes());
+            hasher.update(field);
+        }
+        let mut uniform: [u8; 64] = hasher.finalize().into();
+        let generator = RistrettoPoint::from_uniform_bytes(&uniform);
+        uniform.zeroize();
+        generator
+    }
+
+    /// Transcript hash over the configuration and both key shares.
+    fn transcript_hash(&self, initiator_message: &[u8], responder_share: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(PROTOCOL_LABEL);
+        for field in [&self.session_id, &self.initiator_id, &self.responder_id, initiator_message, responder_share] {
+            hasher.update((field.len() as u32).to_be_bytes());
+            hasher.update(field);
+        }
+        hasher.finalize().into()
+    }
+}
+
+/// The key both parties agree on.
+pub struct SessionKey {
+    key: [u8; KEY_LEN],
+    transcript_hash: [u8; 32],
+}
+
+impl SessionKey {
+    pub fn as_bytes(&self) -> &[u8; KEY_LEN] {
+        &self.key
+    }
+
+    /// Hash of the exchanged messages, identical on both sides. Useful as
+    /// a channel binding.
+    pub fn transcript_hash(&self) -> &[u8; 32] {
+        &self.transcript_hash
+    }
+}
+
+impl Drop for SessionKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+/// Keys derived from the CPace and KEM secrets.
+struct Confirmation {
+    initiator_key: Zeroizing<[u8; 32]>,
+    responder_key: Zeroizing<[u8; 32]>,
+    session: SessionKey,
+}
+
+impl Confirmation {
+    fn derive(dh: &RistrettoPoint, kem_secret: &[u8], transcript_hash: [u8; 32]) -> Self {
+        let mut ikm = Zeroizing::new(dh.compress().to_bytes().to_vec());
+        ikm.extend_from_slice(kem_secret);
+        let prk = Hkdf::<Sha256>::new(Some(&transcript_hash), &ikm);
+        let expand = |label: &str, out: &mut [u8]| {
+            let info = [PROTOCOL_LABEL, b" ", label.as_bytes()].concat();
+            prk.expand(&info, out).expect("32 is a valid HKDF-SHA256 output length");
+        };
+        let mut initiator_key = Zeroizing::new([0u8; 32]);
+        let mut responder_key = Zeroizing::new([0u8; 32]);
+        let mut key = [0u8; KEY_LEN];
+        expand("initiator confirm", &mut initiator_key[..]);
+        expand("responder confirm", &mut responder_key[..]);
+        expand("session key", &mut key);
+        Confirmation { initiator_key, responder_key, session: SessionKey { key, transcript_hash } }
+    }
+
+    fn tag(key: &[u8; 32], transcript_hash: &[u8; 32]) -> [u8; TAG_LEN] {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(transcript_hash);
+        mac.finalize().into_bytes().into()
+    }
+
+    fn verify(key: &[u8; 32], transcript_hash: &[u8; 32], tag: &[u8]) -> Result<(), PakeError> {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+        mac.update(transcript_hash);
+        mac.verify_slice(tag).map_err(|_| PakeError::BadConfirmation)
+    }
+}
+
+enum State {
+    InitiatorStart,
+    InitiatorAwaitResponse {
+        scalar: Zeroizing<Scalar>,
+        kem_secret_key: Zeroizing<Vec<u8>>,
+        message: Vec<u8>,
+    },
+    InitiatorSendConfirm(Confirmation),
+    ResponderAwaitInitiator,
+    ResponderSendResponse {
+        message: Vec<u8>,
+        confirmation: Confirmation,
+    },
+    ResponderAwaitConfirm(Confirmation),
+    Complete(SessionKey),
+    Failed,
+}
+
+/// One side of a PAKE run.
+///
+/// Drive it by calling [`write_message`](Self::write_message) whenever
+/// [`wants_write`](Self::wants_write) is true and passing every message
+/// received from the peer to [`read_message`](Self::read_message). Any
+/// error is fatal. After a failure, count it against the password
+/// (for example, by limiting attempts per pairing code) before retrying.
+pub struct Pake {
+    config: PakeConfig,
+    state: State,
+}
+
+impl Pake {
+    pub fn initiator(config: PakeConfig) -> Self {
+        Pake { config, state: State::InitiatorStart }
+    }
+
+    pub fn responder(config: PakeConfig) -> Self {
+        Pake { config, state: State::ResponderAwaitInitiator }
+    }
+
+    /// Whether the next step is to send a message.
+    pub fn wants_write(&self) -> bool {
+        matches!(
+            self.state,
+            State::InitiatorStart | State::InitiatorSendConfirm(_) | State::ResponderSendResponse { .. }
+        )
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self.state, State::Complete(_))
+    }
+
+    /// The agreed key, once the exchange is complete.
+    pub fn into_key(mut self) -> Result<SessionKey, PakeError> {
+        match std::mem::replace(&mut self.state, State::Failed) {
+            State::Complete(key) => Ok(key),
+            _ => Err(PakeError::NotComplete),
+        }
+    }
+
+    /// Produce the next outgoing message.
+    pub fn write_message(&mut self) -> Result<Vec<u8>, PakeError> {
+        let (message, next) = match std::mem::replace(&mut self.state, State::Failed) {
+            State::InitiatorStart => {
+                if self.config.kem.kind() != AlgorithmKind::Kem {
+                    return Err(PakeError::UnsupportedAlgorithm(self.config.kem));
+                }
+                let scalar = Zeroizing::new(random_scalar());
+                let share = (*scalar * self.config.generator()).compress();
+                let (public_key, secret_key) = registry::keygen(self.config.kem)?;
+                let mut message = vec![VERSION, MSG_INITIATOR];
+                message.extend_from_slice(&self.config.kem.code().to_be_bytes());
+                message.extend_from_slice(share.as_bytes());
+                message.extend_from_slice(&public_key);
+                let state = State::InitiatorAwaitResponse {
+                    scalar,
+                    kem_secret_key: Zeroizing::new(secret_key),
+                    message: message.clone(),
+                };
+                (message, state)
+            }
+            State::ResponderSendResponse { message, confirmation } => {
+                (message, State::ResponderAwaitConfirm(confirmation))
+            }
+            State::InitiatorSendConfirm(confirmation) => {
+                let hash = confirmation.session.transcript_hash;
+                let mut message = vec![VERSION, MSG_CONFIRM];
+                message.extend_from_slice(&Confirmation::tag(&confirmation.initiator_key, &hash));
+                (message, State::Complete(confirmation.session))
+            }
+            _ => {
+                return Err(PakeError::UnexpectedMessage);
+            }
+        };
+        self.state = next;
+        Ok(message)
+    }
+
+    /// Process a message received from the peer.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<(), PakeError> {
+        self.state = match std::mem::replace(&mut self.state, State::Failed) {
+            State::ResponderAwaitInitiator => self.read_initiator(message)?,
+            State::InitiatorAwaitResponse { scalar, kem_secret_key, message: sent } => {
+                let body = check_header(message, MSG_RESPONDER, POINT_LEN + TAG_LEN)?;
+                let (share, rest) = body.split_at(POINT_LEN);
+                let (tag, ciphertext) = rest.split_at(TAG_LEN);
+                let dh = *scalar * decode_point(share)?;
+                if dh.is_identity() {
+                    return Err(PakeError::InvalidPoint);
+                }
+                let kem_secret = Zeroizing::new(registry::decapsulate(self.config.kem, &kem_secret_key, ciphertext)?);
+                let responder_part = [share, ciphertext].concat();
+                let hash = self.config.transcript_hash(&sent, &responder_part);
+                let confirmation = Confirmation::derive(&dh, &kem_secret, hash);
+                Confirmation::verify(&confirmation.responder_key, &hash, tag)?;
+                State::InitiatorSendConfirm(confirmation)
+            }
+            State::ResponderAwaitConfirm(confirmation) => {
+                let tag = check_header(message, MSG_CONFIRM, TAG_LEN)?;
+                if tag.len() != TAG_LEN {
+                    return Err(PakeError::Malformed("trailing data".into()));
+                }
+                Confirmation::verify(&confirmation.initiator_key, &confirmation.session.transcript_hash, tag)?;
+                State::Complete(confirmation.session)
+            }
+            _ => {
+                return Err(PakeError::UnexpectedMessage);
+            }
+        };
+        Ok(())
+    }
+
+    fn read_initiator(&mut self, message: &[u8]) -> Result<State, PakeError> {
+        let body = check_header(message, MSG_INITIATOR, 2 + POINT_LEN)?;
+        let kem = AlgorithmId::from_code(u16::from_be_bytes([body[0], body[1]]))?;
+        if kem != self.config.kem {
+            return Err(PakeError::UnsupportedAlgorithm(kem));
+        }
+        let (share, public_key) = body[2..].split_at(POINT_LEN);
+        let peer_share = decode_point(share)?;
+        let (ciphertext, kem_secret) = registry::encapsulate(kem, public_key)?;
+        let kem_secret = Zeroizing::new(kem_secret);
+
+        let scalar = Zeroizing::new(random_scalar());
+        let own_share = (*scalar * self.config.generator()).compress();
+        let dh = *scalar * peer_share;
+        if dh.is_identity() {
+            return Err(PakeError::InvalidPoint);
+        }
+        let responder_part = [own_share.as_bytes(), &ciphertext[..]].concat();
+        let hash = self.config.transcript_hash(message, &responder_part);
+        let confirmation = Confirmation::derive(&dh, &kem_secret, hash);
+
+        let mut response = vec![VERSION, MSG_RESPONDER];
+        response.extend_from_slice(own_share.as_bytes());
+        response.extend_from_slice(&Confirmation::tag(&confirmation.responder_key, &hash));
+        response.extend_from_slice(&ciphertext);
+        Ok(State::ResponderSendResponse { message: response, confirmation })
+    }
+}
+
+fn check_header(message: &[u8], kind: u8, min_body: usize) -> Result<&[u8], PakeError> {
+    match message {
+        [VERSION, k, body @ ..] if *k == kind && body.len() >= min_body => Ok(body),
+        [VERSION, k, ..] if *k == kind => Err(PakeError::Malformed("message too short".into())),
+        [VERSION, ..] => Err(PakeError::UnexpectedMessage),
+        _ => Err(PakeError::Malformed("unsupported version".into())),
+    }
+}
+
+fn decode_point(bytes: &[u8]) -> Result<RistrettoPoint, PakeError> {
+    let point = CompressedRistretto::from_slice(bytes)
+        .ok()
+        .and_then(|c| c.decompress())
+        .ok_or(PakeError::InvalidPoint)?;
+    if point.is_identity() {
+        return Err(PakeError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+fn random_scalar() -> Scalar {
+    let mut wide = [0u8; 64];
+    getrandom::fill(&mut wide).expect("RNG failed");
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+    scalar
+}
+
+/// Run a complete exchange between two local parties, passing messages in
+/// memory, and return `(initiator, responder)` keys.
+pub fn loopback(initiator: PakeConfig, responder: PakeConfig) -> Result<(SessionKey, SessionKey), PakeError> {
+    let mut initiator = Pake::initiator(initiator);
+    let mut responder = Pake::responder(responder);
+    while !(initiator.is_complete() && responder.is_complete()) {
+        let (from, to) = if initiator.wants_write() {
+            (&mut initiator, &mut responder)
+        } else if responder.wants_write() {
+            (&mut responder, &mut initiator)
+        } else {
+            return Err(PakeError::UnexpectedMessage);
+        };
+        let message = from.write_message()?;
+        to.read_message(&message)?;
+    }
+    Ok((initiator.into_key()?, responder.into_key()?))
+}
+
+#[cfg(all(test, feature = "kyber"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_agrees_only_on_matching_password() {
+        let (a, b) = loopback(PakeConfig::new(b"482913"), PakeConfig::new(b"482913")).unwrap();
+        assert_eq!(a.as_bytes(), b.as_bytes());
+        assert_eq!(a.transcript_hash(), b.transcript_hash());
+
+        let result = loopback(PakeConfig::new(b"482913"), PakeConfig::new(b"482914"));
+        assert!(matches!(result, Err(PakeError::BadConfirmation)));
+    }
+}
@@ -0,0 +1,718 @@
+//! The C entry points. Each checks and copies its pointer arguments, then
+//! calls into [`Module`] under the module lock.
+
+#![allow(non_snake_case)]
+
+use std::os::raw::c_void;
+use std::panic::{ catch_unwind, AssertUnwindSafe };
+use std::sync::Mutex;
+
+use super::token::{ padded, Module, Template, Value };
+use super::types::*;
+use super::{ Attrs, Bytes, Len, LenOut, Mech, Param, CK_FUNCTION_LIST_3_2, FUNCTION_LIST_2_40, H, INTERFACES, O };
+
+static MODULE: Mutex<Option<Module>> = Mutex::new(None);
+
+const SLOT_ID: CK_SLOT_ID = 0;
+
+/// Run `f`, turning errors and panics into return values.
+fn guard(f: impl FnOnce() -> Result<(), CK_RV>) -> CK_RV {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => CKR_OK,
+        Ok(Err(rv)) => rv,
+        Err(_) => CKR_GENERAL_ERROR,
+    }
+}
+
+fn with_module<T>(f: impl FnOnce(&mut Module) -> Result<T, CK_RV>) -> Result<T, CK_RV> {
+    let mut module = MODULE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(module.as_mut().ok_or(CKR_CRYPTOKI_NOT_INITIALIZED)?)
+}
+
+fn check_slot(slot: CK_SLOT_ID) -> Result<(), CK_RV> {
+    if slot == SLOT_ID { Ok(()) } else { Err(CKR_SLOT_ID_INVALID) }
+}
+
+unsafe fn bytes<'a>(data: *const CK_BYTE, len: CK_ULONG) -> Result<&'a [u8], CK_RV> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    Ok(std::slice::from_raw_parts(data, len as usize))
+}
+
+unsafe fn write<T>(target: *mut T, value: T) -> Result<(), CK_RV> {
+    if target.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    target.write(value);
+    Ok(())
+}
+
+/// The usual output convention: a null buffer asks for the length, a
+/// short one fails with `CKR_BUFFER_TOO_SMALL`. Either way `*len` is set.
+unsafe fn write_list<T: Copy>(items: &[T], out: *mut T, len: *mut CK_ULONG) -> Result<(), CK_RV> {
+    if len.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    let capacity = *len as usize;
+    *len = items.len() as CK_ULONG;
+    if out.is_null() {
+        return Ok(());
+    }
+    if capacity < items.len() {
+        return Err(CKR_BUFFER_TOO_SMALL);
+    }
+    std::ptr::copy_nonoverlapping(items.as_ptr(), out, items.len());
+    Ok(())
+}
+
+/// For outputs produced by a one-shot operation: `Ok(false)` when the
+/// caller only asked for the length or its buffer is smaller than
+/// `max_len`, in which case the operation must not run yet.
+unsafe fn output_ready(out: *mut CK_BYTE, len: *mut CK_ULONG, max_len: usize) -> Result<bool, CK_RV> {
+    if len.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    let capacity = *len as usize;
+    *len = max_len as CK_ULONG;
+    if out.is_null() {
+        return Ok(false);
+    }
+    if capacity < max_len {
+        return Err(CKR_BUFFER_TOO_SMALL);
+    }
+    Ok(true)
+}
+
+unsafe fn template(attributes: *const CK_ATTRIBUTE, count: CK_ULONG) -> Result<Vec<(CK_ATTRIBUTE_TYPE, Vec<u8>)>, CK_RV> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    if attributes.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    std::slice::from_raw_parts(attributes, count as usize)
+        .iter()
+        .map(|a| Ok((a.type_, bytes(a.pValue as *const CK_BYTE, a.ulValueLen)?.to_vec())))
+        .collect()
+}
+
+/// The mechanism type, after checking its parameter. Signatures accept
+/// `CK_SIGN_ADDITIONAL_CONTEXT` with an empty context and any hedging
+/// other than deterministic; the other mechanisms take no parameter.
+unsafe fn mechanism(mechanism: *const CK_MECHANISM) -> Result<CK_MECHANISM_TYPE, CK_RV> {
+    if mechanism.is_null() {
+        return Err(CKR_ARGUMENTS_BAD);
+    }
+    let mechanism = &*mechanism;
+    if mechanism.pParameter.is_null() {
+        return Ok(mechanism.mechanism);
+    }
+    if mechanism.mechanism != CKM_ML_DSA && mechanism.mechanism != CKM_SLH_DSA {
+        return Err(CKR_MECHANISM_PARAM_INVALID);
+    }
+    if mechanism.ulParameterLen as usize != std::mem::size_of::<CK_SIGN_ADDITIONAL_CONTEXT>() {
+        return Err(CKR_MECHANISM_PARAM_INVALID);
+    }
+    let parameter = &*(mechanism.pParameter as *const CK_SIGN_ADDITIONAL_CONTEXT);
+    if parameter.ulContextLen != 0 || parameter.hedgeVariant == CKH_DETERMINISTIC_REQUIRED {
+        return Err(CKR_MECHANISM_PARAM_INVALID);
+    }
+    Ok(mechanism.mechanism)
+}
+
+pub(super) unsafe extern "C" fn C_Initialize(init_args: *mut c_void) -> CK_RV {
+    guard(|| {
+        if !init_args.is_null() {
+            let args = &*(init_args as *const CK_C_INITIALIZE_ARGS);
+            if !args.pReserved.is_null() {
+                return Err(CKR_ARGUMENTS_BAD);
+            }
+            let callbacks = [args.CreateMutex, args.DestroyMutex, args.LockMutex, args.UnlockMutex];
+            let supplied = callbacks.iter().filter(|f| !f.is_null()).count();
+            if supplied != 0 && supplied != callbacks.len() {
+                return Err(CKR_ARGUMENTS_BAD);
+            }
+            if supplied != 0 && args.flags & CKF_OS_LOCKING_OK == 0 {
+                return Err(CKR_CANT_LOCK);
+            }
+        }
+        let mut module = MODULE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if module.is_some() {
+            return Err(CKR_CRYPTOKI_ALREADY_INITIALIZED);
+        }
+        *module = Some(Module::new());
+        Ok(())
+    })
+}
+
+pub(super) unsafe extern "C" fn C_Finalize(reserved: *mut c_void) -> CK_RV {
+    guard(|| {
+        if !reserved.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let mut module = MODULE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        module.take().map(|_| ()).ok_or(CKR_CRYPTOKI_NOT_INITIALIZED)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetInfo(info: *mut CK_INFO) -> CK_RV {
+    guard(|| {
+        with_module(|_| Ok(()))?;
+        write(info, CK_INFO {
+            cryptokiVersion: CK_VERSION { major: 3, minor: 2 },
+            manufacturerID: padded("Synergy Network"),
+            flags: 0,
+            libraryDescription: padded("Aegis PQ Software Token"),
+            libraryVersion: CK_VERSION { major: 0, minor: 1 },
+        })
+    })
+}
+
+/// Return the PKCS#11 v2.40 function list. The table also carries the
+/// v3.0 and v3.2 functions; use `C_GetInterface` to get it with a v3
+/// version number.
+///
+/// # Safety
+///
+/// `function_list` must be null or valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn C_GetFunctionList(function_list: *mut *const CK_FUNCTION_LIST_3_2) -> CK_RV {
+    guard(|| write(function_list, &FUNCTION_LIST_2_40 as *const CK_FUNCTION_LIST_3_2))
+}
+
+/// List the "PKCS 11" interfaces for versions 2.40, 3.0 and 3.2.
+///
+/// # Safety
+///
+/// `count` must be null or valid for reads and writes, and a non-null
+/// `interfaces` must have room for `*count` entries.
+#[no_mangle]
+pub unsafe extern "C" fn C_GetInterfaceList(interfaces: *mut CK_INTERFACE, count: *mut CK_ULONG) -> CK_RV {
+    guard(|| {
+        let list: Vec<CK_INTERFACE> = INTERFACES.iter().map(|i| i.0).collect();
+        write_list(&list, interfaces, count)
+    })
+}
+
+/// Look up an interface by name and version. A null name or version
+/// selects the default, "PKCS 11" version 3.2.
+///
+/// # Safety
+///
+/// `name` must be null or NUL-terminated, `version` null or valid for a
+/// read, and `interface` null or valid for a pointer write.
+#[no_mangle]
+pub unsafe extern "C" fn C_GetInterface(
+    name: *mut CK_UTF8CHAR,
+    version: *mut CK_VERSION,
+    interface: *mut *mut CK_INTERFACE,
+    flags: CK_FLAGS
+) -> CK_RV {
+    guard(|| {
+        if !name.is_null() && std::ffi::CStr::from_ptr(name as *const _).to_bytes() != b"PKCS 11" {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let found = INTERFACES.iter()
+            .rev()
+            .find(|i| {
+                let list = &*(i.0.pFunctionList as *const CK_FUNCTION_LIST_3_2);
+                (version.is_null() || *version == list.version) && i.0.flags & flags == flags
+            })
+            .ok_or(CKR_ARGUMENTS_BAD)?;
+        write(interface, &found.0 as *const CK_INTERFACE as *mut CK_INTERFACE)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetSlotList(_token_present: CK_BBOOL, slots: *mut CK_SLOT_ID, count: *mut CK_ULONG) -> CK_RV {
+    guard(|| {
+        with_module(|_| Ok(()))?;
+        write_list(&[SLOT_ID], slots, count)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetSlotInfo(slot: CK_SLOT_ID, info: *mut CK_SLOT_INFO) -> CK_RV {
+    guard(|| {
+        with_module(|_| check_slot(slot))?;
+        let version = CK_VERSION { major: 0, minor: 1 };
+        write(info, CK_SLOT_INFO {
+            slotDescription: padded("Aegis Software Slot"),
+            manufacturerID: padded("Synergy Network"),
+            flags: CKF_TOKEN_PRESENT,
+            hardwareVersion: version,
+            firmwareVersion: version,
+        })
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetTokenInfo(slot: CK_SLOT_ID, info: *mut CK_TOKEN_INFO) -> CK_RV {
+    guard(|| {
+        let token_info = with_module(|m| {
+            check_slot(slot)?;
+            Ok(m.token_info())
+        })?;
+        write(info, token_info)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetMechanismList(
+    slot: CK_SLOT_ID,
+    mechanisms: *mut CK_MECHANISM_TYPE,
+    count: *mut CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let list = with_module(|m| {
+            check_slot(slot)?;
+            Ok(m.mechanism_list())
+        })?;
+        write_list(&list, mechanisms, count)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetMechanismInfo(
+    slot: CK_SLOT_ID,
+    mechanism: CK_MECHANISM_TYPE,
+    info: *mut CK_MECHANISM_INFO
+) -> CK_RV {
+    guard(|| {
+        let mechanism_info = with_module(|m| {
+            check_slot(slot)?;
+            m.mechanism_info(mechanism)
+        })?;
+        write(info, mechanism_info)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_InitToken(
+    slot: CK_SLOT_ID,
+    pin: *mut CK_UTF8CHAR,
+    pin_len: CK_ULONG,
+    label: *mut CK_UTF8CHAR
+) -> CK_RV {
+    guard(|| {
+        let pin = bytes(pin, pin_len)?;
+        let label = std::str::from_utf8(bytes(label, 32)?).map_err(|_| CKR_ARGUMENTS_BAD)?;
+        with_module(|m| {
+            check_slot(slot)?;
+            m.init_token(pin, label)
+        })
+    })
+}
+
+pub(super) unsafe extern "C" fn C_OpenSession(
+    slot: CK_SLOT_ID,
+    flags: CK_FLAGS,
+    _application: *mut c_void,
+    _notify: CK_NOTIFY,
+    session: *mut CK_SESSION_HANDLE
+) -> CK_RV {
+    guard(|| {
+        if session.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let handle = with_module(|m| {
+            check_slot(slot)?;
+            m.open_session(flags)
+        })?;
+        write(session, handle)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_CloseSession(session: CK_SESSION_HANDLE) -> CK_RV {
+    guard(|| with_module(|m| m.close_session(session)))
+}
+
+pub(super) unsafe extern "C" fn C_CloseAllSessions(slot: CK_SLOT_ID) -> CK_RV {
+    guard(|| {
+        with_module(|m| {
+            check_slot(slot)?;
+            m.close_all_sessions();
+            Ok(())
+        })
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GetSessionInfo(session: CK_SESSION_HANDLE, info: *mut CK_SESSION_INFO) -> CK_RV {
+    guard(|| {
+        let session_info = with_module(|m| m.session_info(session))?;
+        write(info, session_info)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_Login(
+    session: CK_SESSION_HANDLE,
+    user_type: CK_USER_TYPE,
+    pin: *mut CK_UTF8CHAR,
+    pin_len: CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let pin = bytes(pin, pin_len)?;
+        with_module(|m| m.login(session, user_type, pin))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_Logout(session: CK_SESSION_HANDLE) -> CK_RV {
+    guard(|| with_module(|m| m.logout(session)))
+}
+
+pub(super) unsafe extern "C" fn C_DestroyObject(session: CK_SESSION_HANDLE, object: CK_OBJECT_HANDLE) -> CK_RV {
+    guard(|| with_module(|m| m.destroy(session, object)))
+}
+
+/// Fill the caller's template. Every attribute is processed; the last
+/// error seen is returned.
+pub(super) unsafe extern "C" fn C_GetAttributeValue(
+    session: CK_SESSION_HANDLE,
+    object: CK_OBJECT_HANDLE,
+    attributes: *mut CK_ATTRIBUTE,
+    count: CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let values = with_module(|m| m.attribute_values(session, object))?;
+        if count != 0 && attributes.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let mut result = Ok(());
+        for attribute in (0..count as usize).map(|i| &mut *attributes.add(i)) {
+            let value = values.iter().find(|(t, _)| *t == attribute.type_).map(|(_, v)| v);
+            match value {
+                None => {
+                    attribute.ulValueLen = CK_UNAVAILABLE_INFORMATION;
+                    result = Err(CKR_ATTRIBUTE_TYPE_INVALID);
+                }
+                Some(Value::Sensitive) => {
+                    attribute.ulValueLen = CK_UNAVAILABLE_INFORMATION;
+                    result = Err(CKR_ATTRIBUTE_SENSITIVE);
+                }
+                Some(Value::Bytes(bytes)) if attribute.pValue.is_null() => {
+                    attribute.ulValueLen = bytes.len() as CK_ULONG;
+                }
+                Some(Value::Bytes(bytes)) if (attribute.ulValueLen as usize) < bytes.len() => {
+                    attribute.ulValueLen = CK_UNAVAILABLE_INFORMATION;
+                    result = Err(CKR_BUFFER_TOO_SMALL);
+                }
+                Some(Value::Bytes(bytes)) => {
+                    std::ptr::copy_nonoverlapping(bytes.as_ptr(), attribute.pValue as *mut u8, bytes.len());
+                    attribute.ulValueLen = bytes.len() as CK_ULONG;
+                }
+            }
+        }
+        result
+    })
+}
+
+pub(super) unsafe extern "C" fn C_FindObjectsInit(
+    session: CK_SESSION_HANDLE,
+    attributes: *mut CK_ATTRIBUTE,
+    count: CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let template: Vec<_> = template(attributes, count)?;
+        with_module(|m| m.find_init(session, &template))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_FindObjects(
+    session: CK_SESSION_HANDLE,
+    objects: *mut CK_OBJECT_HANDLE,
+    max_count: CK_ULONG,
+    count: *mut CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        if objects.is_null() || count.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let found = with_module(|m| m.find(session, max_count as usize))?;
+        std::ptr::copy_nonoverlapping(found.as_ptr(), objects, found.len());
+        write(count, found.len() as CK_ULONG)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_FindObjectsFinal(session: CK_SESSION_HANDLE) -> CK_RV {
+    guard(|| with_module(|m| m.find_final(session)))
+}
+
+pub(super) unsafe extern "C" fn C_GenerateKeyPair(
+    session: CK_SESSION_HANDLE,
+    mechanism_ptr: *mut CK_MECHANISM,
+    public_attributes: *mut CK_ATTRIBUTE,
+    public_count: CK_ULONG,
+    private_attributes: *mut CK_ATTRIBUTE,
+    private_count: CK_ULONG,
+    public_key: *mut CK_OBJECT_HANDLE,
+    private_key: *mut CK_OBJECT_HANDLE
+) -> CK_RV {
+    guard(|| {
+        if public_key.is_null() || private_key.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let mechanism = mechanism(mechanism_ptr)?;
+        let public_template: Vec<_> = template(public_attributes, public_count)?;
+        let private_template: Vec<_> = template(private_attributes, private_count)?;
+        let (public_handle, private_handle) = with_module(|m| {
+            m.generate_key_pair(session, mechanism, &public_template, &private_template)
+        })?;
+        write(public_key, public_handle)?;
+        write(private_key, private_handle)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_SignInit(
+    session: CK_SESSION_HANDLE,
+    mechanism_ptr: *mut CK_MECHANISM,
+    key: CK_OBJECT_HANDLE
+) -> CK_RV {
+    guard(|| {
+        let mechanism = mechanism(mechanism_ptr)?;
+        with_module(|m| m.sign_init(session, mechanism, key))
+    })
+}
+
+unsafe fn sign_into(
+    session: CK_SESSION_HANDLE,
+    data: Option<&[u8]>,
+    signature: *mut CK_BYTE,
+    signature_len: *mut CK_ULONG
+) -> Result<(), CK_RV> {
+    with_module(|m| {
+        if !output_ready(signature, signature_len, m.signature_len(session)?)? {
+            return Ok(());
+        }
+        if let Some(data) = data {
+            m.sign_update(session, data)?;
+        }
+        let produced = m.sign_final(session)?;
+        std::ptr::copy_nonoverlapping(produced.as_ptr(), signature, produced.len());
+        *signature_len = produced.len() as CK_ULONG;
+        Ok(())
+    })
+}
+
+pub(super) unsafe extern "C" fn C_Sign(
+    session: CK_SESSION_HANDLE,
+    data: *mut CK_BYTE,
+    data_len: CK_ULONG,
+    signature: *mut CK_BYTE,
+    signature_len: *mut CK_ULONG
+) -> CK_RV {
+    guard(|| sign_into(session, Some(bytes(data, data_len)?), signature, signature_len))
+}
+
+pub(super) unsafe extern "C" fn C_SignUpdate(session: CK_SESSION_HANDLE, part: *mut CK_BYTE, part_len: CK_ULONG) -> CK_RV {
+    guard(|| {
+        let part = bytes(part, part_len)?;
+        with_module(|m| m.sign_update(session, part))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_SignFinal(
+    session: CK_SESSION_HANDLE,
+    signature: *mut CK_BYTE,
+    signature_len: *mut CK_ULONG
+) -> CK_RV {
+    guard(|| sign_into(session, None, signature, signature_len))
+}
+
+pub(super) unsafe extern "C" fn C_VerifyInit(
+    session: CK_SESSION_HANDLE,
+    mechanism_ptr: *mut CK_MECHANISM,
+    key: CK_OBJECT_HANDLE
+) -> CK_RV {
+    guard(|| {
+        let mechanism = mechanism(mechanism_ptr)?;
+        with_module(|m| m.verify_init(session, mechanism, key))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_Verify(
+    session: CK_SESSION_HANDLE,
+    data: *mut CK_BYTE,
+    data_len: CK_ULONG,
+    signature: *mut CK_BYTE,
+    signature_len: CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let data = bytes(data, data_len)?;
+        let signature = bytes(signature, signature_len)?;
+        with_module(|m| {
+            m.verify_update(session, data)?;
+            m.verify_final(session, signature)
+        })
+    })
+}
+
+pub(super) unsafe extern "C" fn C_VerifyUpdate(session: CK_SESSION_HANDLE, part: *mut CK_BYTE, part_len: CK_ULONG) -> CK_RV {
+    guard(|| {
+        let part = bytes(part, part_len)?;
+        with_module(|m| m.verify_update(session, part))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_VerifyFinal(
+    session: CK_SESSION_HANDLE,
+    signature: *mut CK_BYTE,
+    signature_len: CK_ULONG
+) -> CK_RV {
+    guard(|| {
+        let signature = bytes(signature, signature_len)?;
+        with_module(|m| m.verify_final(session, signature))
+    })
+}
+
+pub(super) unsafe extern "C" fn C_SeedRandom(session: CK_SESSION_HANDLE, _seed: *mut CK_BYTE, _seed_len: CK_ULONG) -> CK_RV {
+    guard(|| {
+        with_module(|m| m.check_session(session))?;
+        Err(CKR_RANDOM_SEED_NOT_SUPPORTED)
+    })
+}
+
+pub(super) unsafe extern "C" fn C_GenerateRandom(session: CK_SESSION_HANDLE, data: *mut CK_BYTE, len: CK_ULONG) -> CK_RV {
+    guard(|| {
+        with_module(|m| m.check_session(session))?;
+        if len == 0 {
+            return Ok(());
+        }
+        if data.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        getrandom::fill(std::slice::from_raw_parts_mut(data, len as usize)).expect("RNG failed");
+        Ok(())
+    })
+}
+
+pub(super) unsafe extern "C" fn C_EncapsulateKey(
+    session: CK_SESSION_HANDLE,
+    mechanism_ptr: *mut CK_MECHANISM,
+    public_key: CK_OBJECT_HANDLE,
+    attributes: *mut CK_ATTRIBUTE,
+    count: CK_ULONG,
+    ciphertext: *mut CK_BYTE,
+    ciphertext_len: *mut CK_ULONG,
+    key: *mut CK_OBJECT_HANDLE
+) -> CK_RV {
+    guard(|| {
+        let mechanism = mechanism(mechanism_ptr)?;
+        let template: Vec<_> = template(attributes, count)?;
+        with_module(|m| {
+            let len = m.ciphertext_len(session, mechanism, public_key)?;
+            if !output_ready(ciphertext, ciphertext_len, len)? {
+                return Ok(());
+            }
+            if key.is_null() {
+                return Err(CKR_ARGUMENTS_BAD);
+            }
+            let (produced, handle) = m.encapsulate(session, mechanism, public_key, &template as &Template)?;
+            std::ptr::copy_nonoverlapping(produced.as_ptr(), ciphertext, produced.len());
+            *ciphertext_len = produced.len() as CK_ULONG;
+            *key = handle;
+            Ok(())
+        })
+    })
+}
+
+pub(super) unsafe extern "C" fn C_DecapsulateKey(
+    session: CK_SESSION_HANDLE,
+    mechanism_ptr: *mut CK_MECHANISM,
+    private_key: CK_OBJECT_HANDLE,
+    attributes: *mut CK_ATTRIBUTE,
+    count: CK_ULONG,
+    ciphertext: *mut CK_BYTE,
+    ciphertext_len: CK_ULONG,
+    key: *mut CK_OBJECT_HANDLE
+) -> CK_RV {
+    guard(|| {
+        if key.is_null() {
+            return Err(CKR_ARGUMENTS_BAD);
+        }
+        let mechanism = mechanism(mechanism_ptr)?;
+        let template: Vec<_> = template(attributes, count)?;
+        let ciphertext = bytes(ciphertext, ciphertext_len)?;
+        let handle = with_module(|m| m.decapsulate(session, mechanism, private_key, &template, ciphertext))?;
+        write(key, handle)
+    })
+}
+
+/// Entry points the token does not implement.
+macro_rules! not_supported {
+    ($($name:ident($($arg:ty),*);)*) => {
+        $(
+            pub(super) unsafe extern "C" fn $name($(_: $arg),*) -> CK_RV {
+                CKR_FUNCTION_NOT_SUPPORTED
+            }
+        )*
+    };
+}
+
+not_supported! {
+    C_InitPIN(H, Bytes, Len);
+    C_SetPIN(H, Bytes, Len, Bytes, Len);
+    C_GetOperationState(H, Bytes, LenOut);
+    C_SetOperationState(H, Bytes, Len, O, O);
+    C_CreateObject(H, Attrs, Len, *mut O);
+    C_CopyObject(H, O, Attrs, Len, *mut O);
+    C_GetObjectSize(H, O, LenOut);
+    C_SetAttributeValue(H, O, Attrs, Len);
+    C_EncryptInit(H, Mech, O);
+    C_Encrypt(H, Bytes, Len, Bytes, LenOut);
+    C_EncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_EncryptFinal(H, Bytes, LenOut);
+    C_DecryptInit(H, Mech, O);
+    C_Decrypt(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptFinal(H, Bytes, LenOut);
+    C_DigestInit(H, Mech);
+    C_Digest(H, Bytes, Len, Bytes, LenOut);
+    C_DigestUpdate(H, Bytes, Len);
+    C_DigestKey(H, O);
+    C_DigestFinal(H, Bytes, LenOut);
+    C_SignRecoverInit(H, Mech, O);
+    C_SignRecover(H, Bytes, Len, Bytes, LenOut);
+    C_VerifyRecoverInit(H, Mech, O);
+    C_VerifyRecover(H, Bytes, Len, Bytes, LenOut);
+    C_DigestEncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptDigestUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_SignEncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptVerifyUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_GenerateKey(H, Mech, Attrs, Len, *mut O);
+    C_WrapKey(H, Mech, O, O, Bytes, LenOut);
+    C_UnwrapKey(H, Mech, O, Bytes, Len, Attrs, Len, *mut O);
+    C_DeriveKey(H, Mech, O, Attrs, Len, *mut O);
+    C_GetFunctionStatus(H);
+    C_CancelFunction(H);
+    C_WaitForSlotEvent(CK_FLAGS, *mut CK_SLOT_ID, Param);
+    C_LoginUser(H, CK_USER_TYPE, Bytes, Len, Bytes, Len);
+    C_SessionCancel(H, CK_FLAGS);
+    C_MessageEncryptInit(H, Mech, O);
+    C_EncryptMessage(H, Param, Len, Bytes, Len, Bytes, Len, Bytes, LenOut);
+    C_EncryptMessageBegin(H, Param, Len, Bytes, Len);
+    C_EncryptMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut, CK_FLAGS);
+    C_MessageEncryptFinal(H);
+    C_MessageDecryptInit(H, Mech, O);
+    C_DecryptMessage(H, Param, Len, Bytes, Len, Bytes, Len, Bytes, LenOut);
+    C_DecryptMessageBegin(H, Param, Len, Bytes, Len);
+    C_DecryptMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut, CK_FLAGS);
+    C_MessageDecryptFinal(H);
+    C_MessageSignInit(H, Mech, O);
+    C_SignMessage(H, Param, Len, Bytes, Len, Bytes, LenOut);
+    C_SignMessageBegin(H, Param, Len);
+    C_SignMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut);
+    C_MessageSignFinal(H);
+    C_MessageVerifyInit(H, Mech, O);
+    C_VerifyMessage(H, Param, Len, Bytes, Len, Bytes, Len);
+    C_VerifyMessageBegin(H, Param, Len);
+    C_VerifyMessageNext(H, Param, Len, Bytes, Len, Bytes, Len);
+    C_MessageVerifyFinal(H);
+    C_VerifySignatureInit(H, Mech, O, Bytes, Len);
+    C_VerifySignature(H, Bytes, Len);
+    C_VerifySignatureUpdate(H, Bytes, Len);
+    C_VerifySignatureFinal(H);
+    C_GetSessionValidationFlags(H, CK_ULONG, *mut CK_FLAGS);
+    C_AsyncComplete(H, Bytes, Param);
+    C_AsyncGetID(H, Bytes, LenOut);
+    C_AsyncJoin(H, Bytes, Len, Bytes, Len);
+    C_WrapKeyAuthenticated(H, Mech, O, O, Bytes, Len, Bytes, LenOut);
+    C_UnwrapKeyAuthenticated(H, Mech, O, Bytes, Len, Attrs, Len, Bytes, Len, *mut O);
+}
@@ -0,0 +1,198 @@
+//! PKCS#11 software token over the [`keystore`](crate::keystore).
+//!
+//! Building with the `pkcs11` feature exports `C_GetFunctionList`,
+//! `C_GetInterfaceList` and `C_GetInterface` from the crate's `cdylib`, so
+//! any PKCS#11 application can load it as a module. The module has one
+//! slot holding one token. The token offers the v3.2 post-quantum
+//! mechanisms:
+//!
+//! | Mechanism | Key pair generation | Operations |
+//! |-----------|---------------------|------------|
+//! | ML-KEM | `CKM_ML_KEM_KEY_PAIR_GEN` | `C_EncapsulateKey`, `C_DecapsulateKey` |
+//! | ML-DSA | `CKM_ML_DSA_KEY_PAIR_GEN` | `C_Sign*`, `C_Verify*` |
+//! | SLH-DSA | `CKM_SLH_DSA_KEY_PAIR_GEN` | `C_Sign*`, `C_Verify*` |
+//!
+//! Key pairs live in the keystore and are addressed by object handles for
+//! the lifetime of the module. Private key values are always sensitive.
+//! Encapsulated secrets are session objects of type `CKK_GENERIC_SECRET`.
+//!
+//! By default the token is in memory and starts uninitialized:
+//! `C_InitToken` sets the user PIN. If `AEGIS_PKCS11_DIR` names a
+//! directory, the token is a [`DirectoryKeyStore`](crate::keystore::DirectoryKeyStore)
+//! there instead. The user PIN is then the keystore passphrase and keys
+//! persist across loads.
+//!
+//! Functions the token does not implement return
+//! `CKR_FUNCTION_NOT_SUPPORTED`.
+
+#![allow(non_snake_case)]
+
+mod api;
+mod token;
+mod types;
+
+pub use api::{ C_GetFunctionList, C_GetInterface, C_GetInterfaceList };
+pub use types::*;
+
+use std::os::raw::c_void;
+
+type H = CK_SESSION_HANDLE;
+type O = CK_OBJECT_HANDLE;
+type Bytes = *mut CK_BYTE;
+type Len = CK_ULONG;
+type LenOut = *mut CK_ULONG;
+type Mech = *mut CK_MECHANISM;
+type Attrs = *mut CK_ATTRIBUTE;
+type Param = *mut c_void;
+
+/// Define `CK_FUNCTION_LIST_3_2` with its fields in header order, and a
+/// constructor filling them from `api`.
+macro_rules! function_list {
+    ($($name:ident($($arg:ty),*);)*) => {
+        /// The v3.2 function table. The v2.40 and v3.0 tables are prefixes
+        /// of it.
+        #[repr(C)]
+        pub struct CK_FUNCTION_LIST_3_2 {
+            pub version: CK_VERSION,
+            $(pub $name: unsafe extern "C" fn($($arg),*) -> CK_RV,)*
+        }
+
+        const fn function_list(version: CK_VERSION) -> CK_FUNCTION_LIST_3_2 {
+            CK_FUNCTION_LIST_3_2 { version, $($name: api::$name,)* }
+        }
+    };
+}
+
+function_list! {
+    // v2.40
+    C_Initialize(Param);
+    C_Finalize(Param);
+    C_GetInfo(*mut CK_INFO);
+    C_GetFunctionList(*mut *const CK_FUNCTION_LIST_3_2);
+    C_GetSlotList(CK_BBOOL, *mut CK_SLOT_ID, LenOut);
+    C_GetSlotInfo(CK_SLOT_ID, *mut CK_SLOT_INFO);
+    C_GetTokenInfo(CK_SLOT_ID, *mut CK_TOKEN_INFO);
+    C_GetMechanismList(CK_SLOT_ID, *mut CK_MECHANISM_TYPE, LenOut);
+    C_GetMechanismInfo(CK_SLOT_ID, CK_MECHANISM_TYPE, *mut CK_MECHANISM_INFO);
+    C_InitToken(CK_SLOT_ID, Bytes, Len, Bytes);
+    C_InitPIN(H, Bytes, Len);
+    C_SetPIN(H, Bytes, Len, Bytes, Len);
+    C_OpenSession(CK_SLOT_ID, CK_FLAGS, Param, CK_NOTIFY, *mut H);
+    C_CloseSession(H);
+    C_CloseAllSessions(CK_SLOT_ID);
+    C_GetSessionInfo(H, *mut CK_SESSION_INFO);
+    C_GetOperationState(H, Bytes, LenOut);
+    C_SetOperationState(H, Bytes, Len, O, O);
+    C_Login(H, CK_USER_TYPE, Bytes, Len);
+    C_Logout(H);
+    C_CreateObject(H, Attrs, Len, *mut O);
+    C_CopyObject(H, O, Attrs, Len, *mut O);
+    C_DestroyObject(H, O);
+    C_GetObjectSize(H, O, LenOut);
+    C_GetAttributeValue(H, O, Attrs, Len);
+    C_SetAttributeValue(H, O, Attrs, Len);
+    C_FindObjectsInit(H, Attrs, Len);
+    C_FindObjects(H, *mut O, Len, LenOut);
+    C_FindObjectsFinal(H);
+    C_EncryptInit(H, Mech, O);
+    C_Encrypt(H, Bytes, Len, Bytes, LenOut);
+    C_EncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_EncryptFinal(H, Bytes, LenOut);
+    C_DecryptInit(H, Mech, O);
+    C_Decrypt(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptFinal(H, Bytes, LenOut);
+    C_DigestInit(H, Mech);
+    C_Digest(H, Bytes, Len, Bytes, LenOut);
+    C_DigestUpdate(H, Bytes, Len);
+    C_DigestKey(H, O);
+    C_DigestFinal(H, Bytes, LenOut);
+    C_SignInit(H, Mech, O);
+    C_Sign(H, Bytes, Len, Bytes, LenOut);
+    C_SignUpdate(H, Bytes, Len);
+    C_SignFinal(H, Bytes, LenOut);
+    C_SignRecoverInit(H, Mech, O);
+    C_SignRecover(H, Bytes, Len, Bytes, LenOut);
+    C_VerifyInit(H, Mech, O);
+    C_Verify(H, Bytes, Len, Bytes, Len);
+    C_VerifyUpdate(H, Bytes, Len);
+    C_VerifyFinal(H, Bytes, Len);
+    C_VerifyRecoverInit(H, Mech, O);
+    C_VerifyRecover(H, Bytes, Len, Bytes, LenOut);
+    C_DigestEncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptDigestUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_SignEncryptUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_DecryptVerifyUpdate(H, Bytes, Len, Bytes, LenOut);
+    C_GenerateKey(H, Mech, Attrs, Len, *mut O);
+    C_GenerateKeyPair(H, Mech, Attrs, Len, Attrs, Len, *mut O, *mut O);
+    C_WrapKey(H, Mech, O, O, Bytes, LenOut);
+    C_UnwrapKey(H, Mech, O, Bytes, Len, Attrs, Len, *mut O);
+    C_DeriveKey(H, Mech, O, Attrs, Len, *mut O);
+    C_SeedRandom(H, Bytes, Len);
+    C_GenerateRandom(H, Bytes, Len);
+    C_GetFunctionStatus(H);
+    C_CancelFunction(H);
+    C_WaitForSlotEvent(CK_FLAGS, *mut CK_SLOT_ID, Param);
+    // v3.0
+    C_GetInterfaceList(*mut CK_INTERFACE, LenOut);
+    C_GetInterface(*mut CK_UTF8CHAR, *mut CK_VERSION, *mut *mut CK_INTERFACE, CK_FLAGS);
+    C_LoginUser(H, CK_USER_TYPE, Bytes, Len, Bytes, Len);
+    C_SessionCancel(H, CK_FLAGS);
+    C_MessageEncryptInit(H, Mech, O);
+    C_EncryptMessage(H, Param, Len, Bytes, Len, Bytes, Len, Bytes, LenOut);
+    C_EncryptMessageBegin(H, Param, Len, Bytes, Len);
+    C_EncryptMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut, CK_FLAGS);
+    C_MessageEncryptFinal(H);
+    C_MessageDecryptInit(H, Mech, O);
+    C_DecryptMessage(H, Param, Len, Bytes, Len, Bytes, Len, Bytes, LenOut);
+    C_DecryptMessageBegin(H, Param, Len, Bytes, Len);
+    C_DecryptMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut, CK_FLAGS);
+    C_MessageDecryptFinal(H);
+    C_MessageSignInit(H, Mech, O);
+    C_SignMessage(H, Param, Len, Bytes, Len, Bytes, LenOut);
+    C_SignMessageBegin(H, Param, Len);
+    C_SignMessageNext(H, Param, Len, Bytes, Len, Bytes, LenOut);
+    C_MessageSignFinal(H);
+    C_MessageVerifyInit(H, Mech, O);
+    C_VerifyMessage(H, Param, Len, Bytes, Len, Bytes, Len);
+    C_VerifyMessageBegin(H, Param, Len);
+    C_VerifyMessageNext(H, Param, Len, Bytes, Len, Bytes, Len);
+    C_MessageVerifyFinal(H);
+    // v3.2
+    C_EncapsulateKey(H, Mech, O, Attrs, Len, Bytes, LenOut, *mut O);
+    C_DecapsulateKey(H, Mech, O, Attrs, Len, Bytes, Len, *mut O);
+    C_VerifySignatureInit(H, Mech, O, Bytes, Len);
+    C_VerifySignature(H, Bytes, Len);
+    C_VerifySignatureUpdate(H, Bytes, Len);
+    C_VerifySignatureFinal(H);
+    C_GetSessionValidationFlags(H, CK_ULONG, *mut CK_FLAGS);
+    C_AsyncComplete(H, Bytes, Param);
+    C_AsyncGetID(H, Bytes, LenOut);
+    C_AsyncJoin(H, Bytes, Len, Bytes, Len);
+    C_WrapKeyAuthenticated(H, Mech, O, O, Bytes, Len, Bytes, LenOut);
+    C_UnwrapKeyAuthenticated(H, Mech, O, Bytes, Len, Attrs, Len, Bytes, Len, *mut O);
+}
+
+static FUNCTION_LIST_2_40: CK_FUNCTION_LIST_3_2 = function_list(CK_VERSION { major: 2, minor: 40 });
+static FUNCTION_LIST_3_0: CK_FUNCTION_LIST_3_2 = function_list(CK_VERSION { major: 3, minor: 0 });
+static FUNCTION_LIST_3_2: CK_FUNCTION_LIST_3_2 = function_list(CK_VERSION { major: 3, minor: 2 });
+
+/// A `CK_INTERFACE` pointing at static data, so safe to share.
+struct Interface(CK_INTERFACE);
+
+unsafe impl Sync for Interface {}
+
+const fn interface(list: &'static CK_FUNCTION_LIST_3_2) -> Interface {
+    Interface(CK_INTERFACE {
+        pInterfaceName: c"PKCS 11".as_ptr() as *mut CK_UTF8CHAR,
+        pFunctionList: list as *const CK_FUNCTION_LIST_3_2 as *mut c_void,
+        flags: 0,
+    })
+}
+
+/// Oldest first; `C_GetInterface` prefers the newest match.
+static INTERFACES: [Interface; 3] = [
+    interface(&FUNCTION_LIST_2_40),
+    interface(&FUNCTION_LIST_3_0),
+    interface(&FUNCTION_LIST_3_2),
+];
@@ -0,0 +1,809 @@
+//! The software token behind the C entry points: login state, sessions,
+//! object handles and operations, with errors as PKCS#11 return values.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use sha3::{ Digest, Sha3_256 };
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use super::types::*;
+use crate::keystore::argon2::Argon2Params;
+use crate::keystore::{ DirectoryKeyStore, KeyId, KeyMetadata, KeyOptions, KeyStore, KeyStoreError, MemoryKeyStore };
+use crate::registry::{ self, AlgorithmId, AlgorithmKind };
+
+/// Environment variable naming the keystore directory. Without it the
+/// token keeps its keys in memory.
+pub const DIRECTORY_VARIABLE: &str = "AEGIS_PKCS11_DIR";
+
+pub const MIN_PIN_LEN: usize = 4;
+pub const MAX_PIN_LEN: usize = 255;
+const DEFAULT_LABEL: &str = "Aegis Software Token";
+
+/// Registry algorithms with a PKCS#11 key type and parameter set.
+const ALGORITHMS: [(AlgorithmId, CK_KEY_TYPE, CK_ULONG); 12] = [
+    (AlgorithmId::MlKem512, CKK_ML_KEM, CKP_ML_KEM_512),
+    (AlgorithmId::MlKem768, CKK_ML_KEM, CKP_ML_KEM_768),
+    (AlgorithmId::MlKem1024, CKK_ML_KEM, CKP_ML_KEM_1024),
+    (AlgorithmId::MlDsa44, CKK_ML_DSA, CKP_ML_DSA_44),
+    (AlgorithmId::MlDsa65, CKK_ML_DSA, CKP_ML_DSA_65),
+    (AlgorithmId::MlDsa87, CKK_ML_DSA, CKP_ML_DSA_87),
+    (AlgorithmId::SlhDsaSha2_128f, CKK_SLH_DSA, CKP_SLH_DSA_SHA2_128F),
+    (AlgorithmId::SlhDsaShake128f, CKK_SLH_DSA, CKP_SLH_DSA_SHAKE_128F),
+    (AlgorithmId::SlhDsaSha2_192f, CKK_SLH_DSA, CKP_SLH_DSA_SHA2_192F),
+    (AlgorithmId::SlhDsaShake192f, CKK_SLH_DSA, CKP_SLH_DSA_SHAKE_192F),
+    (AlgorithmId::SlhDsaSha2_256f, CKK_SLH_DSA, CKP_SLH_DSA_SHA2_256F),
+    (AlgorithmId::SlhDsaShake256f, CKK_SLH_DSA, CKP_SLH_DSA_SHAKE_256F),
+];
+
+/// Key pair generation and operation mechanisms of each key type.
+const MECHANISMS: [(CK_KEY_TYPE, CK_MECHANISM_TYPE, CK_MECHANISM_TYPE); 3] = [
+    (CKK_ML_KEM, CKM_ML_KEM_KEY_PAIR_GEN, CKM_ML_KEM),
+    (CKK_ML_DSA, CKM_ML_DSA_KEY_PAIR_GEN, CKM_ML_DSA),
+    (CKK_SLH_DSA, CKM_SLH_DSA_KEY_PAIR_GEN, CKM_SLH_DSA),
+];
+
+fn key_type(algorithm: AlgorithmId) -> Option<(CK_KEY_TYPE, CK_ULONG)> {
+    ALGORITHMS.iter()
+        .find(|(alg, _, _)| *alg == algorithm)
+        .map(|&(_, key_type, parameter_set)| (key_type, parameter_set))
+}
+
+fn algorithm(key_type: CK_KEY_TYPE, parameter_set: CK_ULONG) -> Option<AlgorithmId> {
+    ALGORITHMS.iter()
+        .find(|&&(_, t, p)| t == key_type && p == parameter_set)
+        .map(|&(alg, _, _)| alg)
+}
+
+fn mechanisms(key_type: CK_KEY_TYPE) -> (CK_MECHANISM_TYPE, CK_MECHANISM_TYPE) {
+    MECHANISMS.iter()
+        .find(|(t, _, _)| *t == key_type)
+        .map(|&(_, generate, operate)| (generate, operate))
+        .expect("every key type has mechanisms")
+}
+
+pub fn keystore_error(e: KeyStoreError) -> CK_RV {
+    match e {
+        KeyStoreError::NotFound(_) => CKR_OBJECT_HANDLE_INVALID,
+        KeyStoreError::Expired(_) | KeyStoreError::UsageNotPermitted(_) => CKR_KEY_FUNCTION_NOT_PERMITTED,
+        KeyStoreError::BadPassphrase => CKR_PIN_INCORRECT,
+        KeyStoreError::Malformed(_) | KeyStoreError::Io(_) => CKR_DEVICE_ERROR,
+        KeyStoreError::AlreadyExists(_) | KeyStoreError::Algorithm(_) => CKR_FUNCTION_FAILED,
+    }
+}
+
+/// Copy `text` into a blank-padded fixed-size field.
+pub fn padded<const N: usize>(text: &str) -> [CK_UTF8CHAR; N] {
+    let mut field = [b' '; N];
+    let len = text.len().min(N);
+    field[..len].copy_from_slice(&text.as_bytes()[..len]);
+    field
+}
+
+/// An attribute value, or a marker for one that may not be revealed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Bytes(Vec<u8>),
+    Sensitive,
+}
+
+fn ulong(value: CK_ULONG) -> Value {
+    Value::Bytes(value.to_ne_bytes().to_vec())
+}
+
+fn flag(value: bool) -> Value {
+    Value::Bytes(vec![if value { CK_TRUE } else { CK_FALSE }])
+}
+
+/// A caller-supplied attribute template.
+pub type Template = [(CK_ATTRIBUTE_TYPE, Vec<u8>)];
+
+fn template_value(template: &Template, type_: CK_ATTRIBUTE_TYPE) -> Option<&[u8]> {
+    template.iter().find(|(t, _)| *t == type_).map(|(_, value)| &value[..])
+}
+
+fn template_ulong(template: &Template, type_: CK_ATTRIBUTE_TYPE) -> Result<Option<CK_ULONG>, CK_RV> {
+    template_value(template, type_)
+        .map(|value| value.try_into().map(CK_ULONG::from_ne_bytes).map_err(|_| CKR_ATTRIBUTE_VALUE_INVALID))
+        .transpose()
+}
+
+fn template_flag(template: &Template, type_: CK_ATTRIBUTE_TYPE) -> Result<Option<bool>, CK_RV> {
+    match template_value(template, type_) {
+        None => Ok(None),
+        Some([value]) => Ok(Some(*value != CK_FALSE)),
+        Some(_) => Err(CKR_ATTRIBUTE_VALUE_INVALID),
+    }
+}
+
+/// Fail if the template sets `type_` to anything other than `expected`.
+fn template_expect(template: &Template, type_: CK_ATTRIBUTE_TYPE, expected: CK_ULONG) -> Result<(), CK_RV> {
+    match template_ulong(template, type_)? {
+        Some(value) if value != expected => Err(CKR_TEMPLATE_INCONSISTENT),
+        _ => Ok(()),
+    }
+}
+
+fn template_label(template: &Template) -> Result<Option<String>, CK_RV> {
+    template_value(template, CKA_LABEL)
+        .map(|value| String::from_utf8(value.to_vec()).map_err(|_| CKR_ATTRIBUTE_VALUE_INVALID))
+        .transpose()
+}
+
+enum Backing {
+    Memory,
+    Directory(PathBuf),
+}
+
+#[derive(Clone)]
+enum Object {
+    Public(KeyId),
+    Private(KeyId),
+    /// A shared secret from encapsulation, owned by a session.
+    Secret {
+        session: CK_SESSION_HANDLE,
+        value: Zeroizing<Vec<u8>>,
+        sensitive: bool,
+        label: Vec<u8>,
+    },
+}
+
+/// A signature operation: the key and the data received so far.
+struct Operation {
+    key: KeyId,
+    algorithm: AlgorithmId,
+    data: Vec<u8>,
+}
+
+struct Session {
+    read_write: bool,
+    found: Option<Vec<CK_OBJECT_HANDLE>>,
+    sign: Option<Operation>,
+    verify: Option<Operation>,
+}
+
+/// All state of the loaded module.
+pub struct Module {
+    backing: Backing,
+    /// In memory mode the store lives as long as the token; in directory
+    /// mode it is open only while the user is logged in.
+    store: Option<Box<dyn KeyStore + Send>>,
+    /// SHA3-256 of the PIN, in memory mode.
+    pin_hash: Option<Zeroizing<[u8; 32]>>,
+    label: String,
+    logged_in: bool,
+    sessions: HashMap<CK_SESSION_HANDLE, Session>,
+    next_session: CK_SESSION_HANDLE,
+    objects: HashMap<CK_OBJECT_HANDLE, Object>,
+    next_object: CK_OBJECT_HANDLE,
+}
+
+fn pin_hash(pin: &[u8]) -> Zeroizing<[u8; 32]> {
+    Zeroizing::new(Sha3_256::digest(pin).into())
+}
+
+impl Module {
+    pub fn new() -> Self {
+        let backing = match std::env::var_os(DIRECTORY_VARIABLE) {
+            Some(dir) => Backing::Directory(PathBuf::from(dir)),
+            None => Backing::Memory,
+        };
+        Module {
+            backing,
+            store: None,
+            pin_hash: None,
+            label: DEFAULT_LABEL.to_string(),
+            logged_in: false,
+            sessions: HashMap::new(),
+            next_session: 1,
+            objects: HashMap::new(),
+            next_object: 1,
+        }
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        match &self.backing {
+            Backing::Memory => self.pin_hash.is_some(),
+            Backing::Directory(dir) => DirectoryKeyStore::exists(dir),
+        }
+    }
+
+    pub fn token_info(&self) -> CK_TOKEN_INFO {
+        let mut flags = CKF_RNG | CKF_LOGIN_REQUIRED;
+        if self.is_initialized() {
+            flags |= CKF_TOKEN_INITIALIZED | CKF_USER_PIN_INITIALIZED;
+        }
+        let rw_sessions = self.sessions.values().filter(|s| s.read_write).count();
+        let version = CK_VERSION { major: 0, minor: 1 };
+        CK_TOKEN_INFO {
+            label: padded(&self.label),
+            manufacturerID: padded("Synergy Network"),
+            model: padded("Aegis Software"),
+            serialNumber: padded("0000000000000001"),
+            flags,
+            ulMaxSessionCount: 0,
+            ulSessionCount: self.sessions.len() as CK_ULONG,
+            ulMaxRwSessionCount: 0,
+            ulRwSessionCount: rw_sessions as CK_ULONG,
+            ulMaxPinLen: MAX_PIN_LEN as CK_ULONG,
+            ulMinPinLen: MIN_PIN_LEN as CK_ULONG,
+            ulTotalPublicMemory: CK_UNAVAILABLE_INFORMATION,
+            ulFreePublicMemory: CK_UNAVAILABLE_INFORMATION,
+            ulTotalPrivateMemory: CK_UNAVAILABLE_INFORMATION,
+            ulFreePrivateMemory: CK_UNAVAILABLE_INFORMATION,
+            hardwareVersion: version,
+            firmwareVersion: version,
+            utcTime: [b' '; 16],
+        }
+    }
+
+    /// Mechanisms whose algorithms are compiled in.
+    pub fn mechanism_list(&self) -> Vec<CK_MECHANISM_TYPE> {
+        MECHANISMS.iter()
+            .filter(|(t, _, _)| ALGORITHMS.iter().any(|(alg, kt, _)| kt == t && alg.is_available()))
+            .flat_map(|&(_, generate, operate)| [generate, operate])
+            .collect()
+    }
+
+    /// The key size range is the range of supported parameter sets.
+    pub fn mechanism_info(&self, mechanism: CK_MECHANISM_TYPE) -> Result<CK_MECHANISM_INFO, CK_RV> {
+        if !self.mechanism_list().contains(&mechanism) {
+            return Err(CKR_MECHANISM_INVALID);
+        }
+        let &(key_type, generate, _) = MECHANISMS.iter()
+            .find(|(_, g, o)| *g == mechanism || *o == mechanism)
+            .expect("listed mechanism");
+        let sets: Vec<CK_ULONG> = ALGORITHMS.iter()
+            .filter(|(alg, t, _)| *t == key_type && alg.is_available())
+            .map(|&(_, _, set)| set)
+            .collect();
+        let flags = if mechanism == generate {
+            CKF_GENERATE_KEY_PAIR
+        } else if key_type == CKK_ML_KEM {
+            CKF_ENCAPSULATE | CKF_DECAPSULATE
+        } else {
+            CKF_SIGN | CKF_VERIFY
+        };
+        Ok(CK_MECHANISM_INFO {
+            ulMinKeySize: sets.iter().copied().min().unwrap_or(0),
+            ulMaxKeySize: sets.iter().copied().max().unwrap_or(0),
+            flags,
+        })
+    }
+
+    /// Create the token, or re-create it destroying every key.
+    pub fn init_token(&mut self, pin: &[u8], label: &str) -> Result<(), CK_RV> {
+        if !self.sessions.is_empty() {
+            return Err(CKR_SESSION_EXISTS);
+        }
+        if !(MIN_PIN_LEN..=MAX_PIN_LEN).contains(&pin.len()) {
+            return Err(CKR_PIN_LEN_RANGE);
+        }
+        match &self.backing {
+            Backing::Memory => {
+                if let Some(hash) = &self.pin_hash {
+                    if !bool::from(hash.ct_eq(&pin_hash(pin)[..])) {
+                        return Err(CKR_PIN_INCORRECT);
+                    }
+                }
+                self.pin_hash = Some(pin_hash(pin));
+                self.store = Some(Box::new(MemoryKeyStore::new()));
+            }
+            Backing::Directory(dir) => {
+                if DirectoryKeyStore::exists(dir) {
+                    let mut store = DirectoryKeyStore::open(dir, pin).map_err(keystore_error)?;
+                    for key in store.list().map_err(keystore_error)? {
+                        store.delete(&key.id).map_err(keystore_error)?;
+                    }
+                } else {
+                    DirectoryKeyStore::create(dir, pin, Argon2Params::default()).map_err(keystore_error)?;
+                }
+                self.store = None;
+            }
+        }
+        self.objects.clear();
+        self.label = label.trim_end().to_string();
+        Ok(())
+    }
+
+    pub fn open_session(&mut self, flags: CK_FLAGS) -> Result<CK_SESSION_HANDLE, CK_RV> {
+        if flags & CKF_SERIAL_SESSION == 0 {
+            return Err(CKR_SESSION_PARALLEL_NOT_SUPPORTED);
+        }
+        let handle = self.next_session;
+        self.next_session += 1;
+        self.sessions.insert(handle, Session {
+            read_write: flags & CKF_RW_SESSION != 0,
+            found: None,
+            sign: None,
+            verify: None,
+        });
+        Ok(handle)
+    }
+
+    /// Close a session, dropping its secret objects. Closing the last
+    /// session logs the user out.
+    pub fn close_session(&mut self, handle: CK_SESSION_HANDLE) -> Result<(), CK_RV> {
+        self.sessions.remove(&handle).ok_or(CKR_SESSION_HANDLE_INVALID)?;
+        self.objects.retain(|_, object| !matches!(object, Object::Secret { session, .. } if *session == handle));
+        if self.sessions.is_empty() {
+            self.log_out();
+        }
+        Ok(())
+    }
+
+    pub fn close_all_sessions(&mut self) {
+        for handle in self.sessions.keys().copied().collect::<Vec<_>>() {
+            let _ = self.close_session(handle);
+        }
+    }
+
+    fn session(&mut self, handle: CK_SESSION_HANDLE) -> Result<&mut Session, CK_RV> {
+        self.sessions.get_mut(&handle).ok_or(CKR_SESSION_HANDLE_INVALID)
+    }
+
+    pub fn session_info(&mut self, handle: CK_SESSION_HANDLE) -> Result<CK_SESSION_INFO, CK_RV> {
+        let logged_in = self.logged_in;
+        let session = self.session(handle)?;
+        let state = match (session.read_write, logged_in) {
+            (false, false) => CKS_RO_PUBLIC_SESSION,
+            (false, true) => CKS_RO_USER_FUNCTIONS,
+            (true, false) => CKS_RW_PUBLIC_SESSION,
+            (true, true) => CKS_RW_USER_FUNCTIONS,
+        };
+        let mut flags = CKF_SERIAL_SESSION;
+        if session.read_write {
+            flags |= CKF_RW_SESSION;
+        }
+        Ok(CK_SESSION_INFO { slotID: 0, state, flags, ulDeviceError: 0 })
+    }
+
+    /// Log in as the normal user. The security officer role is not
+    /// supported: the token has a single PIN set by `C_InitToken`.
+    pub fn login(&mut self, handle: CK_SESSION_HANDLE, user_type: CK_USER_TYPE, pin: &[u8]) -> Result<(), CK_RV> {
+        self.session(handle)?;
+        if user_type != CKU_USER {
+            return Err(CKR_USER_TYPE_INVALID);
+        }
+        if self.logged_in {
+            return Err(CKR_USER_ALREADY_LOGGED_IN);
+        }
+        if !self.is_initialized() {
+            return Err(CKR_USER_PIN_NOT_INITIALIZED);
+        }
+        match &self.backing {
+            Backing::Memory => {
+                let hash = self.pin_hash.as_ref().expect("initialized");
+                if !bool::from(hash.ct_eq(&pin_hash(pin)[..])) {
+                    return Err(CKR_PIN_INCORRECT);
+                }
+            }
+            Backing::Directory(dir) => {
+                self.store = Some(Box::new(DirectoryKeyStore::open(dir, pin).map_err(keystore_error)?));
+            }
+        }
+        self.logged_in = true;
+        Ok(())
+    }
+
+    pub fn logout(&mut self, handle: CK_SESSION_HANDLE) -> Result<(), CK_RV> {
+        self.session(handle)?;
+        if !self.logged_in {
+            return Err(CKR_USER_NOT_LOGGED_IN);
+        }
+        self.log_out();
+        Ok(())
+    }
+
+    fn log_out(&mut self) {
+        self.logged_in = false;
+        for session in self.sessions.values_mut() {
+            session.sign = None;
+        }
+        if let Backing::Directory(_) = self.backing {
+            self.store = None;
+        }
+    }
+
+    fn require_user(&self) -> Result<(), CK_RV> {
+        if self.logged_in { Ok(()) } else { Err(CKR_USER_NOT_LOGGED_IN) }
+    }
+
+    fn store(&self) -> Result<&(dyn KeyStore + Send), CK_RV> {
+        self.store.as_deref().ok_or(CKR_USER_NOT_LOGGED_IN)
+    }
+
+    fn handle_for(&mut self, object: Object) -> CK_OBJECT_HANDLE {
+        let existing = self.objects.iter().find(|(_, o)| match (o, &object) {
+            (Object::Public(a), Object::Public(b)) | (Object::Private(a), Object::Private(b)) => a == b,
+            _ => false,
+        });
+        if let Some((&handle, _)) = existing {
+            return handle;
+        }
+        let handle = self.next_object;
+        self.next_object += 1;
+        self.objects.insert(handle, object);
+        handle
+    }
+
+    /// The object behind a handle, if the caller may currently see it.
+    fn object(&self, handle: CK_OBJECT_HANDLE) -> Result<Object, CK_RV> {
+        let object = self.objects.get(&handle).ok_or(CKR_OBJECT_HANDLE_INVALID)?;
+        let visible = match object {
+            Object::Public(id) => self.store.as_ref().is_some_and(|store| store.metadata(id).is_ok()),
+            Object::Private(id) => self.logged_in && self.store.as_ref().is_some_and(|store| store.metadata(id).is_ok()),
+            Object::Secret { .. } => true,
+        };
+        if visible { Ok(object.clone()) } else { Err(CKR_OBJECT_HANDLE_INVALID) }
+    }
+
+    /// Keys the token exposes: those with a PKCS#11 key type.
+    fn exposed_keys(&self) -> Result<Vec<KeyMetadata>, CK_RV> {
+        let Some(store) = &self.store else {
+            return Ok(Vec::new());
+        };
+        Ok(
+            store
+                .list()
+                .map_err(keystore_error)?
+                .into_iter()
+                .filter(|key| key_type(key.algorithm).is_some())
+                .collect()
+        )
+    }
+
+    /// Every attribute of an object.
+    fn attributes(&self, object: &Object) -> Result<Vec<(CK_ATTRIBUTE_TYPE, Value)>, CK_RV> {
+        let (id, private) = match object {
+            Object::Secret { value, sensitive, label, .. } => {
+                return Ok(vec![
+                    (CKA_CLASS, ulong(CKO_SECRET_KEY)),
+                    (CKA_KEY_TYPE, ulong(CKK_GENERIC_SECRET)),
+                    (CKA_TOKEN, flag(false)),
+                    (CKA_PRIVATE, flag(false)),
+                    (CKA_LABEL, Value::Bytes(label.clone())),
+                    (CKA_SENSITIVE, flag(*sensitive)),
+                    (CKA_EXTRACTABLE, flag(!*sensitive)),
+                    (CKA_VALUE_LEN, ulong(value.len() as CK_ULONG)),
+                    (CKA_VALUE, if *sensitive { Value::Sensitive } else { Value::Bytes(value.to_vec()) }),
+                    (CKA_DESTROYABLE, flag(true)),
+                ]);
+            }
+            Object::Public(id) => (id, false),
+            Object::Private(id) => (id, true),
+        };
+        let store = self.store()?;
+        let metadata = store.metadata(id).map_err(keystore_error)?;
+        let (key_type, parameter_set) = key_type(metadata.algorithm).ok_or(CKR_OBJECT_HANDLE_INVALID)?;
+        let (generate, _) = mechanisms(key_type);
+        let kem = metadata.algorithm.kind() == AlgorithmKind::Kem;
+        let label = metadata.labels.first().cloned().unwrap_or_default().into_bytes();
+        let mut attributes = vec![
+            (CKA_CLASS, ulong(if private { CKO_PRIVATE_KEY } else { CKO_PUBLIC_KEY })),
+            (CKA_KEY_TYPE, ulong(key_type)),
+            (CKA_PARAMETER_SET, ulong(parameter_set)),
+            (CKA_TOKEN, flag(true)),
+            (CKA_PRIVATE, flag(private)),
+            (CKA_MODIFIABLE, flag(false)),
+            (CKA_DESTROYABLE, flag(true)),
+            (CKA_LABEL, Value::Bytes(label)),
+            (CKA_ID, Value::Bytes(id.0.to_vec())),
+            (CKA_LOCAL, flag(true)),
+            (CKA_KEY_GEN_MECHANISM, ulong(generate)),
+        ];
+        if private {
+            attributes.extend([
+                (CKA_SENSITIVE, flag(true)),
+                (CKA_ALWAYS_SENSITIVE, flag(true)),
+                (CKA_EXTRACTABLE, flag(false)),
+                (CKA_NEVER_EXTRACTABLE, flag(true)),
+                (CKA_SIGN, flag(!kem)),
+                (CKA_DECAPSULATE, flag(kem)),
+                (CKA_VALUE, Value::Sensitive),
+            ]);
+        } else {
+            attributes.extend([
+                (CKA_VERIFY, flag(!kem)),
+                (CKA_ENCAPSULATE, flag(kem)),
+                (CKA_VALUE, Value::Bytes(store.public_key(id).map_err(keystore_error)?)),
+            ]);
+        }
+        Ok(attributes)
+    }
+
+    pub fn attribute_values(
+        &self,
+        session: CK_SESSION_HANDLE,
+        handle: CK_OBJECT_HANDLE
+    ) -> Result<Vec<(CK_ATTRIBUTE_TYPE, Value)>, CK_RV> {
+        self.sessions.get(&session).ok_or(CKR_SESSION_HANDLE_INVALID)?;
+        self.attributes(&self.object(handle)?)
+    }
+
+    pub fn find_init(&mut self, handle: CK_SESSION_HANDLE, template: &Template) -> Result<(), CK_RV> {
+        if self.session(handle)?.found.is_some() {
+            return Err(CKR_OPERATION_ACTIVE);
+        }
+        let mut candidates = Vec::new();
+        for key in self.exposed_keys()? {
+            candidates.push(self.handle_for(Object::Public(key.id)));
+            if self.logged_in {
+                candidates.push(self.handle_for(Object::Private(key.id)));
+            }
+        }
+        candidates.extend(self.objects.iter().filter(|(_, o)| matches!(o, Object::Secret { .. })).map(|(h, _)| *h));
+        candidates.sort();
+
+        let mut found = Vec::new();
+        for candidate in candidates {
+            let attributes = self.attributes(&self.object(candidate)?)?;
+            let matches = template.iter().all(|(type_, wanted)| {
+                attributes.iter().any(|(t, value)| t == type_ && *value == Value::Bytes(wanted.clone()))
+            });
+            if matches {
+                found.push(candidate);
+            }
+        }
+        found.reverse();
+        self.session(handle)?.found = Some(found);
+        Ok(())
+    }
+
+    pub fn find(&mut self, handle: CK_SESSION_HANDLE, max: usize) -> Result<Vec<CK_OBJECT_HANDLE>, CK_RV> {
+        let found = self.session(handle)?.found.as_mut().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        let count = max.min(found.len());
+        Ok((0..count).filter_map(|_| found.pop()).collect())
+    }
+
+    pub fn find_final(&mut self, handle: CK_SESSION_HANDLE) -> Result<(), CK_RV> {
+        self.session(handle)?.found.take().map(|_| ()).ok_or(CKR_OPERATION_NOT_INITIALIZED)
+    }
+
+    pub fn destroy(&mut self, session: CK_SESSION_HANDLE, handle: CK_OBJECT_HANDLE) -> Result<(), CK_RV> {
+        let read_write = self.session(session)?.read_write;
+        match self.object(handle)? {
+            Object::Secret { .. } => {
+                self.objects.remove(&handle);
+            }
+            Object::Public(id) | Object::Private(id) => {
+                self.require_user()?;
+                if !read_write {
+                    return Err(CKR_SESSION_READ_ONLY);
+                }
+                self.store.as_mut().ok_or(CKR_USER_NOT_LOGGED_IN)?.delete(&id).map_err(keystore_error)?;
+                self.objects.retain(|_, o| !matches!(o, Object::Public(k) | Object::Private(k) if *k == id));
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate a token key pair. The parameter set comes from
+    /// `CKA_PARAMETER_SET` and the keystore label from `CKA_LABEL`; the
+    /// key ID is always the keystore's.
+    pub fn generate_key_pair(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        public_template: &Template,
+        private_template: &Template
+    ) -> Result<(CK_OBJECT_HANDLE, CK_OBJECT_HANDLE), CK_RV> {
+        let read_write = self.session(session)?.read_write;
+        let &(key_type, _, _) = MECHANISMS.iter()
+            .find(|(_, generate, _)| *generate == mechanism)
+            .ok_or(CKR_MECHANISM_INVALID)?;
+        self.require_user()?;
+        if !read_write {
+            return Err(CKR_SESSION_READ_ONLY);
+        }
+        for (template, class) in [(public_template, CKO_PUBLIC_KEY), (private_template, CKO_PRIVATE_KEY)] {
+            template_expect(template, CKA_CLASS, class)?;
+            template_expect(template, CKA_KEY_TYPE, key_type)?;
+            if template_flag(template, CKA_TOKEN)? == Some(false) {
+                return Err(CKR_ATTRIBUTE_VALUE_INVALID);
+            }
+        }
+        let parameter_set = match template_ulong(public_template, CKA_PARAMETER_SET)? {
+            Some(set) => set,
+            None => template_ulong(private_template, CKA_PARAMETER_SET)?.ok_or(CKR_TEMPLATE_INCOMPLETE)?,
+        };
+        let algorithm = algorithm(key_type, parameter_set).ok_or(CKR_ATTRIBUTE_VALUE_INVALID)?;
+        if !algorithm.is_available() {
+            return Err(CKR_MECHANISM_INVALID);
+        }
+        let mut options = KeyOptions::new();
+        if let Some(label) = template_label(public_template)?.or(template_label(private_template)?) {
+            options = options.with_label(&label);
+        }
+        let store = self.store.as_mut().ok_or(CKR_USER_NOT_LOGGED_IN)?;
+        let key = store.generate(algorithm, options).map_err(keystore_error)?;
+        Ok((self.handle_for(Object::Public(key.id)), self.handle_for(Object::Private(key.id))))
+    }
+
+    /// The key ID and algorithm of a key object usable with `mechanism`.
+    fn operation_key(
+        &self,
+        handle: CK_OBJECT_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        private: bool
+    ) -> Result<(KeyId, AlgorithmId), CK_RV> {
+        let id = match (self.object(handle).map_err(|_| CKR_KEY_HANDLE_INVALID)?, private) {
+            (Object::Private(id), true) | (Object::Public(id), false) => id,
+            _ => {
+                return Err(CKR_KEY_TYPE_INCONSISTENT);
+            }
+        };
+        let algorithm = self.store()?.metadata(&id).map_err(keystore_error)?.algorithm;
+        let (key_type, _) = key_type(algorithm).ok_or(CKR_KEY_HANDLE_INVALID)?;
+        if !MECHANISMS.iter().any(|&(_, _, operate)| operate == mechanism) {
+            return Err(CKR_MECHANISM_INVALID);
+        }
+        if mechanisms(key_type).1 != mechanism {
+            return Err(CKR_KEY_TYPE_INCONSISTENT);
+        }
+        Ok((id, algorithm))
+    }
+
+    pub fn sign_init(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE
+    ) -> Result<(), CK_RV> {
+        if self.session(session)?.sign.is_some() {
+            return Err(CKR_OPERATION_ACTIVE);
+        }
+        self.require_user()?;
+        let (key, algorithm) = self.operation_key(key, mechanism, true)?;
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(CKR_KEY_FUNCTION_NOT_PERMITTED);
+        }
+        self.session(session)?.sign = Some(Operation { key, algorithm, data: Vec::new() });
+        Ok(())
+    }
+
+    pub fn sign_update(&mut self, session: CK_SESSION_HANDLE, data: &[u8]) -> Result<(), CK_RV> {
+        let operation = self.session(session)?.sign.as_mut().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        operation.data.extend_from_slice(data);
+        Ok(())
+    }
+
+    /// Maximum signature length of the active operation.
+    pub fn signature_len(&mut self, session: CK_SESSION_HANDLE) -> Result<usize, CK_RV> {
+        let operation = self.session(session)?.sign.as_ref().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        operation.algorithm.output_len().ok_or(CKR_GENERAL_ERROR)
+    }
+
+    /// Sign the collected data and end the operation.
+    pub fn sign_final(&mut self, session: CK_SESSION_HANDLE) -> Result<Vec<u8>, CK_RV> {
+        let operation = self.session(session)?.sign.take().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        self.store()?.sign(&operation.key, &operation.data).map_err(keystore_error)
+    }
+
+    pub fn verify_init(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE
+    ) -> Result<(), CK_RV> {
+        if self.session(session)?.verify.is_some() {
+            return Err(CKR_OPERATION_ACTIVE);
+        }
+        let (key, algorithm) = self.operation_key(key, mechanism, false)?;
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(CKR_KEY_FUNCTION_NOT_PERMITTED);
+        }
+        self.session(session)?.verify = Some(Operation { key, algorithm, data: Vec::new() });
+        Ok(())
+    }
+
+    pub fn verify_update(&mut self, session: CK_SESSION_HANDLE, data: &[u8]) -> Result<(), CK_RV> {
+        let operation = self.session(session)?.verify.as_mut().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        operation.data.extend_from_slice(data);
+        Ok(())
+    }
+
+    pub fn verify_final(&mut self, session: CK_SESSION_HANDLE, signature: &[u8]) -> Result<(), CK_RV> {
+        let operation = self.session(session)?.verify.take().ok_or(CKR_OPERATION_NOT_INITIALIZED)?;
+        if operation.algorithm.output_len().is_some_and(|max| signature.len() > max) {
+            return Err(CKR_SIGNATURE_LEN_RANGE);
+        }
+        let public_key = self.store()?.public_key(&operation.key).map_err(keystore_error)?;
+        if registry::verify(operation.algorithm, &public_key, &operation.data, signature) {
+            Ok(())
+        } else {
+            Err(CKR_SIGNATURE_INVALID)
+        }
+    }
+
+    /// Check a template for a shared-secret object and create it.
+    fn secret_object(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        template: &Template,
+        value: Zeroizing<Vec<u8>>
+    ) -> Result<CK_OBJECT_HANDLE, CK_RV> {
+        template_expect(template, CKA_CLASS, CKO_SECRET_KEY)?;
+        template_expect(template, CKA_KEY_TYPE, CKK_GENERIC_SECRET)?;
+        if template_flag(template, CKA_TOKEN)? == Some(true) {
+            return Err(CKR_ATTRIBUTE_VALUE_INVALID);
+        }
+        let sensitive = template_flag(template, CKA_SENSITIVE)?.unwrap_or(false);
+        let label = template_value(template, CKA_LABEL).unwrap_or_default().to_vec();
+        let handle = self.next_object;
+        self.next_object += 1;
+        self.objects.insert(handle, Object::Secret { session, value, sensitive, label });
+        Ok(handle)
+    }
+
+    /// Ciphertext length of an encapsulation to `key`.
+    pub fn ciphertext_len(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE
+    ) -> Result<usize, CK_RV> {
+        self.session(session)?;
+        let (_, algorithm) = self.operation_key(key, mechanism, false)?;
+        algorithm.output_len().ok_or(CKR_GENERAL_ERROR)
+    }
+
+    pub fn encapsulate(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE,
+        template: &Template
+    ) -> Result<(Vec<u8>, CK_OBJECT_HANDLE), CK_RV> {
+        self.session(session)?;
+        let (id, algorithm) = self.operation_key(key, mechanism, false)?;
+        let public_key = self.store()?.public_key(&id).map_err(keystore_error)?;
+        let (ciphertext, shared) = registry::encapsulate(algorithm, &public_key).map_err(|_| CKR_FUNCTION_FAILED)?;
+        let handle = self.secret_object(session, template, Zeroizing::new(shared))?;
+        Ok((ciphertext, handle))
+    }
+
+    pub fn decapsulate(
+        &mut self,
+        session: CK_SESSION_HANDLE,
+        mechanism: CK_MECHANISM_TYPE,
+        key: CK_OBJECT_HANDLE,
+        template: &Template,
+        ciphertext: &[u8]
+    ) -> Result<CK_OBJECT_HANDLE, CK_RV> {
+        self.session(session)?;
+        self.require_user()?;
+        let (id, _) = self.operation_key(key, mechanism, true)?;
+        let shared = self.store()?.decapsulate(&id, ciphertext).map_err(keystore_error)?;
+        self.secret_object(session, template, shared)
+    }
+
+    pub fn check_session(&self, session: CK_SESSION_HANDLE) -> Result<(), CK_RV> {
+        self.sessions.get(&session).map(|_| ()).ok_or(CKR_SESSION_HANDLE_INVALID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_token_pin() {
+        let mut module = Module { backing: Backing::Memory, ..Module::new() };
+        let session = module.open_session(CKF_SERIAL_SESSION).unwrap();
+        assert_eq!(module.init_token(b"1234", "wallet"), Err(CKR_SESSION_EXISTS));
+        module.close_session(session).unwrap();
+        assert_eq!(module.init_token(b"123", "wallet"), Err(CKR_PIN_LEN_RANGE));
+        module.init_token(b"1234", "wallet").unwrap();
+        assert_eq!(module.init_token(b"4321", "wallet"), Err(CKR_PIN_INCORRECT));
+        assert_eq!(&module.token_info().label[..7], b"wallet ");
+
+        let session = module.open_session(CKF_SERIAL_SESSION).unwrap();
+        assert_eq!(module.login(session, CKU_USER, b"4321"), Err(CKR_PIN_INCORRECT));
+        module.login(session, CKU_USER, b"1234").unwrap();
+        assert_eq!(module.session_info(session).unwrap().state, CKS_RO_USER_FUNCTIONS);
+        module.close_session(session).unwrap();
+        assert!(!module.logged_in);
+    }
+}
@@ -0,0 +1,265 @@
+//! PKCS#11 types and constants, as laid out in the OASIS v3.2 headers
+//! for Unix platforms.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::os::raw::{ c_ulong, c_void };
+
+pub type CK_BYTE = u8;
+pub type CK_BBOOL = u8;
+pub type CK_UTF8CHAR = u8;
+pub type CK_ULONG = c_ulong;
+pub type CK_RV = CK_ULONG;
+pub type CK_FLAGS = CK_ULONG;
+pub type CK_SLOT_ID = CK_ULONG;
+pub type CK_SESSION_HANDLE = CK_ULONG;
+pub type CK_OBJECT_HANDLE = CK_ULONG;
+pub type CK_OBJECT_CLASS = CK_ULONG;
+pub type CK_KEY_TYPE = CK_ULONG;
+pub type CK_MECHANISM_TYPE = CK_ULONG;
+pub type CK_ATTRIBUTE_TYPE = CK_ULONG;
+pub type CK_USER_TYPE = CK_ULONG;
+pub type CK_STATE = CK_ULONG;
+pub type CK_NOTIFICATION = CK_ULONG;
+pub type CK_NOTIFY = Option<
+    unsafe extern "C" fn(CK_SESSION_HANDLE, CK_NOTIFICATION, *mut c_void) -> CK_RV
+>;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CK_VERSION {
+    pub major: CK_BYTE,
+    pub minor: CK_BYTE,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_INFO {
+    pub cryptokiVersion: CK_VERSION,
+    pub manufacturerID: [CK_UTF8CHAR; 32],
+    pub flags: CK_FLAGS,
+    pub libraryDescription: [CK_UTF8CHAR; 32],
+    pub libraryVersion: CK_VERSION,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_SLOT_INFO {
+    pub slotDescription: [CK_UTF8CHAR; 64],
+    pub manufacturerID: [CK_UTF8CHAR; 32],
+    pub flags: CK_FLAGS,
+    pub hardwareVersion: CK_VERSION,
+    pub firmwareVersion: CK_VERSION,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_TOKEN_INFO {
+    pub label: [CK_UTF8CHAR; 32],
+    pub manufacturerID: [CK_UTF8CHAR; 32],
+    pub model: [CK_UTF8CHAR; 16],
+    pub serialNumber: [CK_UTF8CHAR; 16],
+    pub flags: CK_FLAGS,
+    pub ulMaxSessionCount: CK_ULONG,
+    pub ulSessionCount: CK_ULONG,
+    pub ulMaxRwSessionCount: CK_ULONG,
+    pub ulRwSessionCount: CK_ULONG,
+    pub ulMaxPinLen: CK_ULONG,
+    pub ulMinPinLen: CK_ULONG,
+    pub ulTotalPublicMemory: CK_ULONG,
+    pub ulFreePublicMemory: CK_ULONG,
+    pub ulTotalPrivateMemory: CK_ULONG,
+    pub ulFreePrivateMemory: CK_ULONG,
+    pub hardwareVersion: CK_VERSION,
+    pub firmwareVersion: CK_VERSION,
+    pub utcTime: [CK_UTF8CHAR; 16],
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_SESSION_INFO {
+    pub slotID: CK_SLOT_ID,
+    pub state: CK_STATE,
+    pub flags: CK_FLAGS,
+    pub ulDeviceError: CK_ULONG,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_ATTRIBUTE {
+    pub type_: CK_ATTRIBUTE_TYPE,
+    pub pValue: *mut c_void,
+    pub ulValueLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_MECHANISM {
+    pub mechanism: CK_MECHANISM_TYPE,
+    pub pParameter: *mut c_void,
+    pub ulParameterLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CK_MECHANISM_INFO {
+    pub ulMinKeySize: CK_ULONG,
+    pub ulMaxKeySize: CK_ULONG,
+    pub flags: CK_FLAGS,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_C_INITIALIZE_ARGS {
+    pub CreateMutex: *mut c_void,
+    pub DestroyMutex: *mut c_void,
+    pub LockMutex: *mut c_void,
+    pub UnlockMutex: *mut c_void,
+    pub flags: CK_FLAGS,
+    pub pReserved: *mut c_void,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_INTERFACE {
+    pub pInterfaceName: *mut CK_UTF8CHAR,
+    pub pFunctionList: *mut c_void,
+    pub flags: CK_FLAGS,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CK_SIGN_ADDITIONAL_CONTEXT {
+    pub hedgeVariant: CK_ULONG,
+    pub pContext: *mut CK_BYTE,
+    pub ulContextLen: CK_ULONG,
+}
+
+pub const CKH_HEDGE_PREFERRED: CK_ULONG = 0;
+pub const CKH_HEDGE_REQUIRED: CK_ULONG = 1;
+pub const CKH_DETERMINISTIC_REQUIRED: CK_ULONG = 2;
+
+pub const CK_INVALID_HANDLE: CK_ULONG = 0;
+pub const CK_UNAVAILABLE_INFORMATION: CK_ULONG = !0;
+pub const CK_TRUE: CK_BBOOL = 1;
+pub const CK_FALSE: CK_BBOOL = 0;
+
+// Return values.
+pub const CKR_OK: CK_RV = 0x000;
+pub const CKR_HOST_MEMORY: CK_RV = 0x002;
+pub const CKR_SLOT_ID_INVALID: CK_RV = 0x003;
+pub const CKR_GENERAL_ERROR: CK_RV = 0x005;
+pub const CKR_FUNCTION_FAILED: CK_RV = 0x006;
+pub const CKR_ARGUMENTS_BAD: CK_RV = 0x007;
+pub const CKR_CANT_LOCK: CK_RV = 0x00a;
+pub const CKR_ATTRIBUTE_SENSITIVE: CK_RV = 0x011;
+pub const CKR_ATTRIBUTE_TYPE_INVALID: CK_RV = 0x012;
+pub const CKR_ATTRIBUTE_VALUE_INVALID: CK_RV = 0x013;
+pub const CKR_DATA_LEN_RANGE: CK_RV = 0x021;
+pub const CKR_DEVICE_ERROR: CK_RV = 0x030;
+pub const CKR_FUNCTION_NOT_SUPPORTED: CK_RV = 0x054;
+pub const CKR_KEY_HANDLE_INVALID: CK_RV = 0x060;
+pub const CKR_KEY_TYPE_INCONSISTENT: CK_RV = 0x063;
+pub const CKR_KEY_FUNCTION_NOT_PERMITTED: CK_RV = 0x068;
+pub const CKR_MECHANISM_INVALID: CK_RV = 0x070;
+pub const CKR_MECHANISM_PARAM_INVALID: CK_RV = 0x071;
+pub const CKR_OBJECT_HANDLE_INVALID: CK_RV = 0x082;
+pub const CKR_OPERATION_ACTIVE: CK_RV = 0x090;
+pub const CKR_OPERATION_NOT_INITIALIZED: CK_RV = 0x091;
+pub const CKR_PIN_INCORRECT: CK_RV = 0x0a0;
+pub const CKR_PIN_LEN_RANGE: CK_RV = 0x0a2;
+pub const CKR_SESSION_HANDLE_INVALID: CK_RV = 0x0b3;
+pub const CKR_SESSION_PARALLEL_NOT_SUPPORTED: CK_RV = 0x0b4;
+pub const CKR_SESSION_READ_ONLY: CK_RV = 0x0b5;
+pub const CKR_SESSION_EXISTS: CK_RV = 0x0b6;
+pub const CKR_SIGNATURE_INVALID: CK_RV = 0x0c0;
+pub const CKR_SIGNATURE_LEN_RANGE: CK_RV = 0x0c1;
+pub const CKR_TEMPLATE_INCOMPLETE: CK_RV = 0x0d0;
+pub const CKR_TEMPLATE_INCONSISTENT: CK_RV = 0x0d1;
+pub const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x100;
+pub const CKR_USER_NOT_LOGGED_IN: CK_RV = 0x101;
+pub const CKR_USER_PIN_NOT_INITIALIZED: CK_RV = 0x102;
+pub const CKR_USER_TYPE_INVALID: CK_RV = 0x103;
+pub const CKR_RANDOM_SEED_NOT_SUPPORTED: CK_RV = 0x120;
+pub const CKR_BUFFER_TOO_SMALL: CK_RV = 0x150;
+pub const CKR_CRYPTOKI_NOT_INITIALIZED: CK_RV = 0x190;
+pub const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x191;
+
+// Flags.
+pub const CKF_TOKEN_PRESENT: CK_FLAGS = 0x0000_0001;
+pub const CKF_RNG: CK_FLAGS = 0x0000_0001;
+pub const CKF_LOGIN_REQUIRED: CK_FLAGS = 0x0000_0004;
+pub const CKF_USER_PIN_INITIALIZED: CK_FLAGS = 0x0000_0008;
+pub const CKF_TOKEN_INITIALIZED: CK_FLAGS = 0x0000_0400;
+pub const CKF_RW_SESSION: CK_FLAGS = 0x0000_0002;
+pub const CKF_SERIAL_SESSION: CK_FLAGS = 0x0000_0004;
+pub const CKF_OS_LOCKING_OK: CK_FLAGS = 0x0000_0002;
+pub const CKF_HW: CK_FLAGS = 0x0000_0001;
+pub const CKF_SIGN: CK_FLAGS = 0x0000_0800;
+pub const CKF_VERIFY: CK_FLAGS = 0x0000_2000;
+pub const CKF_GENERATE_KEY_PAIR: CK_FLAGS = 0x0001_0000;
+pub const CKF_ENCAPSULATE: CK_FLAGS = 0x1000_0000;
+pub const CKF_DECAPSULATE: CK_FLAGS = 0x2000_0000;
+
+// Users and session states.
+pub const CKU_SO: CK_USER_TYPE = 0;
+pub const CKU_USER: CK_USER_TYPE = 1;
+pub const CKS_RO_PUBLIC_SESSION: CK_STATE = 0;
+pub const CKS_RO_USER_FUNCTIONS: CK_STATE = 1;
+pub const CKS_RW_PUBLIC_SESSION: CK_STATE = 2;
+pub const CKS_RW_USER_FUNCTIONS: CK_STATE = 3;
+pub const CKS_RW_SO_FUNCTIONS: CK_STATE = 4;
+
+// Object classes and key types.
+pub const CKO_PUBLIC_KEY: CK_OBJECT_CLASS = 2;
+pub const CKO_PRIVATE_KEY: CK_OBJECT_CLASS = 3;
+pub const CKO_SECRET_KEY: CK_OBJECT_CLASS = 4;
+pub const CKK_GENERIC_SECRET: CK_KEY_TYPE = 0x10;
+pub const CKK_ML_KEM: CK_KEY_TYPE = 0x49;
+pub const CKK_ML_DSA: CK_KEY_TYPE = 0x4a;
+pub const CKK_SLH_DSA: CK_KEY_TYPE = 0x4b;
+
+// Attributes.
+pub const CKA_CLASS: CK_ATTRIBUTE_TYPE = 0x000;
+pub const CKA_TOKEN: CK_ATTRIBUTE_TYPE = 0x001;
+pub const CKA_PRIVATE: CK_ATTRIBUTE_TYPE = 0x002;
+pub const CKA_LABEL: CK_ATTRIBUTE_TYPE = 0x003;
+pub const CKA_VALUE: CK_ATTRIBUTE_TYPE = 0x011;
+pub const CKA_KEY_TYPE: CK_ATTRIBUTE_TYPE = 0x100;
+pub const CKA_ID: CK_ATTRIBUTE_TYPE = 0x102;
+pub const CKA_SENSITIVE: CK_ATTRIBUTE_TYPE = 0x103;
+pub const CKA_SIGN: CK_ATTRIBUTE_TYPE = 0x108;
+pub const CKA_VERIFY: CK_ATTRIBUTE_TYPE = 0x10a;
+pub const CKA_VALUE_LEN: CK_ATTRIBUTE_TYPE = 0x161;
+pub const CKA_EXTRACTABLE: CK_ATTRIBUTE_TYPE = 0x162;
+pub const CKA_LOCAL: CK_ATTRIBUTE_TYPE = 0x163;
+pub const CKA_NEVER_EXTRACTABLE: CK_ATTRIBUTE_TYPE = 0x164;
+pub const CKA_ALWAYS_SENSITIVE: CK_ATTRIBUTE_TYPE = 0x165;
+pub const CKA_KEY_GEN_MECHANISM: CK_ATTRIBUTE_TYPE = 0x166;
+pub const CKA_MODIFIABLE: CK_ATTRIBUTE_TYPE = 0x170;
+pub const CKA_DESTROYABLE: CK_ATTRIBUTE_TYPE = 0x172;
+pub const CKA_PARAMETER_SET: CK_ATTRIBUTE_TYPE = 0x61d;
+pub const CKA_ENCAPSULATE: CK_ATTRIBUTE_TYPE = 0x633;
+pub const CKA_DECAPSULATE: CK_ATTRIBUTE_TYPE = 0x634;
+
+// Mechanisms.
+pub const CKM_ML_KEM_KEY_PAIR_GEN: CK_MECHANISM_TYPE = 0x0f;
+pub const CKM_ML_KEM: CK_MECHANISM_TYPE = 0x17;
+pub const CKM_ML_DSA_KEY_PAIR_GEN: CK_MECHANISM_TYPE = 0x1c;
+pub const CKM_ML_DSA: CK_MECHANISM_TYPE = 0x1d;
+pub const CKM_SLH_DSA_KEY_PAIR_GEN: CK_MECHANISM_TYPE = 0x2d;
+pub const CKM_SLH_DSA: CK_MECHANISM_TYPE = 0x2e;
+
+// Parameter sets.
+pub const CKP_ML_KEM_512: CK_ULONG = 1;
+pub const CKP_ML_KEM_768: CK_ULONG = 2;
+pub const CKP_ML_KEM_1024: CK_ULONG = 3;
+pub const CKP_ML_DSA_44: CK_ULONG = 1;
+pub const CKP_ML_DSA_65: CK_ULONG = 2;
+pub const CKP_ML_DSA_87: CK_ULONG = 3;
+pub const CKP_SLH_DSA_SHA2_128F: CK_ULONG = 3;
+pub const CKP_SLH_DSA_SHAKE_128F: CK_ULONG = 4;
+pub const CKP_SLH_DSA_SHA2_192F: CK_ULONG = 7;
+pub const CKP_SLH_DSA_SHAKE_192F: CK_ULONG = 8;
+pub const CKP_SLH_DSA_SHA2_256F: CK_ULONG = 11;
+pub const CKP_SLH_DSA_SHAKE_256F: CK_ULONG = 12;
@@ -0,0 +1,397 @@
+//! Crypto-agility policy: which algorithms may be used for what, and until
+//! when.
+//!
+//! A [`CryptoPolicy`] lists the permitted algorithms for each [`Purpose`]
+//! in order of preference and carries a schedule of deprecation and expiry
+//! times. New data is produced with the preferred active algorithm
+//! ([`CryptoPolicy::select`]). Existing data is accepted while its
+//! algorithm is active or deprecated, and rejected once it has expired or
+//! was never permitted ([`CryptoPolicy::accept`]). Deprecation therefore
+//! stops new use without breaking data already in flight.
+//!
+//! Keys are rotated per group of [`KeyMetadata`] with the same labels:
+//! [`CryptoPolicy::rotation_due`] reports groups whose current key should
+//! be replaced and [`CryptoPolicy::rotate`] generates the replacements.
+//!
+//! Times are seconds since the Unix epoch and are passed in by the caller.
+
+use std::collections::{ BTreeMap, HashMap };
+
+use crate::container::{ self, Container, ContainerError };
+use crate::keystore::{ KeyId, KeyMetadata, KeyOptions, KeyStore, KeyStoreError };
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// Policy error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyError {
+    /// The algorithm is not listed for the purpose, is the wrong kind for
+    /// it or is below the minimum security level.
+    NotPermitted(Purpose, AlgorithmId),
+    Expired(AlgorithmId),
+    /// No permitted algorithm is active and compiled in.
+    NoAlgorithm(Purpose),
+    Container(ContainerError),
+    KeyStore(KeyStoreError),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for PolicyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::NotPermitted(purpose, alg) => {
+                write!(f, "{} is not permitted for {}", alg.name(), purpose.name())
+            }
+            PolicyError::Expired(alg) => write!(f, "{} has expired", alg.name()),
+            PolicyError::NoAlgorithm(purpose) => write!(f, "No active algorithm for {}", purpose.name()),
+            PolicyError::Container(e) => write!(f, "{}", e),
+            PolicyError::KeyStore(e) => write!(f, "{}", e),
+            PolicyError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<ContainerError> for PolicyError {
+    fn from(e: ContainerError) -> Self {
+        PolicyError::Container(e)
+    }
+}
+
+impl From<KeyStoreError> for PolicyError {
+    fn from(e: KeyStoreError) -> Self {
+        PolicyError::KeyStore(e)
+    }
+}
+
+impl From<AlgorithmError> for PolicyError {
+    fn from(e: AlgorithmError) -> Self {
+        PolicyError::Algorithm(e)
+    }
+}
+
+/// What an algorithm is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Purpose {
+    /// Sealing data to a recipient's KEM key.
+    Encrypt,
+    Sign,
+    /// Session key agreement, as in [`crate::handshake`].
+    KeyExchange,
+}
+
+impl Purpose {
+    pub fn name(self) -> &'static str {
+        match self {
+            Purpose::Encrypt => "encryption",
+            Purpose::Sign => "signing",
+            Purpose::KeyExchange => "key exchange",
+        }
+    }
+
+    /// The kind of algorithm that serves this purpose.
+    pub fn kind(self) -> AlgorithmKind {
+        match self {
+            Purpose::Sign => AlgorithmKind::Signature,
+            Purpose::Encrypt | Purpose::KeyExchange => AlgorithmKind::Kem,
+        }
+    }
+}
+
+/// Where an algorithm stands at a given time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlgorithmStatus {
+    /// May be used for new data.
+    Active,
+    /// Accepted for existing data, never selected for new data.
+    Deprecated,
+    /// Rejected.
+    Expired,
+}
+
+/// Why a key group needs a new key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationReason {
+    AlgorithmDeprecated,
+    AlgorithmExpired,
+    /// The key's algorithm is not permitted for the purpose at all.
+    AlgorithmNotPermitted,
+    /// The key is older than the policy's maximum key age.
+    MaxAgeExceeded,
+    /// The key's own expiry time has passed.
+    KeyExpired,
+}
+
+/// A key that should be, or has been, replaced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationEvent {
+    pub purpose: Purpose,
+    /// The newest key of the group.
+    pub key: KeyId,
+    pub labels: Vec<String>,
+    pub from: AlgorithmId,
+    /// The algorithm of the replacement.
+    pub to: AlgorithmId,
+    pub reason: RotationReason,
+    /// Set by [`CryptoPolicy::rotate`] once the replacement exists.
+    pub new_key: Option<KeyId>,
+}
+
+/// Permitted algorithms, their schedule and key lifetime rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CryptoPolicy {
+    allowed: HashMap<Purpose, Vec<AlgorithmId>>,
+    deprecated_at: BTreeMap<AlgorithmId, u64>,
+    expires_at: BTreeMap<AlgorithmId, u64>,
+    minimum_security_level: usize,
+    max_key_age: Option<u64>,
+}
+
+impl CryptoPolicy {
+    /// A policy that permits nothing.
+    pub fn new() -> Self {
+        CryptoPolicy::default()
+    }
+
+    /// ML-KEM-768 and ML-KEM-1024 for encryption and key exchange;
+    /// ML-DSA-65, ML-DSA-87, Falcon-512 and Falcon-1024 for signing.
+    pub fn recommended() -> Self {
+        let kems = [AlgorithmId::MlKem768, AlgorithmId::MlKem1024];
+        let signatures = [AlgorithmId::MlDsa65, AlgorithmId::MlDsa87, AlgorithmId::Falcon512, AlgorithmId::Falcon1024];
+        let mut policy = CryptoPolicy::new();
+        policy.allowed.insert(Purpose::Encrypt, kems.to_vec());
+        policy.allowed.insert(Purpose::KeyExchange, kems.to_vec());
+        policy.allowed.insert(Purpose::Sign, signatures.to_vec());
+        policy
+    }
+
+    /// Permit `algorithm` for `purpose`, after the algorithms already
+    /// permitted for it. Fails if the algorithm is the wrong kind.
+    pub fn allow(mut self, purpose: Purpose, algorithm: AlgorithmId) -> Result<Self, PolicyError> {
+        if algorithm.kind() != purpose.kind() {
+            return Err(PolicyError::NotPermitted(purpose, algorithm));
+        }
+        let list = self.allowed.entry(purpose).or_default();
+        if !list.contains(&algorithm) {
+            list.push(algorithm);
+        }
+        Ok(self)
+    }
+
+    /// Stop selecting `algorithm` for new data from time `at`.
+    pub fn deprecate(mut self, algorithm: AlgorithmId, at: u64) -> Self {
+        self.deprecated_at.insert(algorithm, at);
+        self
+    }
+
+    /// Reject `algorithm` entirely from time `at`.
+    pub fn expire(mut self, algorithm: AlgorithmId, at: u64) -> Self {
+        self.expires_at.insert(algorithm, at);
+        self
+    }
+
+    /// Refuse algorithms below `bits` (128, 192 or 256), as reported by
+    /// [`AlgorithmId::security_level`].
+    pub fn with_minimum_security_level(mut self, bits: usize) -> Self {
+        self.minimum_security_level = bits;
+        self
+    }
+
+    /// Rotate keys older than `seconds`.
+    pub fn with_max_key_age(mut self, seconds: u64) -> Self {
+        self.max_key_age = Some(seconds);
+        self
+    }
+
+    /// Permitted algorithms for `purpose`, most preferred first.
+    pub fn allowed(&self, purpose: Purpose) -> &[AlgorithmId] {
+        self.allowed.get(&purpose).map_or(&[], |list| &list[..])
+    }
+
+    fn permits(&self, purpose: Purpose, algorithm: AlgorithmId) -> bool {
+        self.allowed(purpose).contains(&algorithm) && algorithm.security_level() >= self.minimum_security_level
+    }
+
+    /// The status of `algorithm` for `purpose` at `now`, or `None` if the
+    /// policy does not permit it.
+    pub fn status(&self, purpose: Purpose, algorithm: AlgorithmId, now: u64) -> Option<AlgorithmStatus> {
+        if !self.permits(purpose, algorithm) {
+            return None;
+        }
+        let reached = |schedule: &BTreeMap<AlgorithmId, u64>| schedule.get(&algorithm).is_some_and(|&at| now >= at);
+        Some(if reached(&self.expires_at) {
+            AlgorithmStatus::Expired
+        } else if reached(&self.deprecated_at) {
+            AlgorithmStatus::Deprecated
+        } else {
+            AlgorithmStatus::Active
+        })
+    }
+
+    /// The preferred algorithm for new data: the first permitted one that
+    /// is active and compiled into this build.
+    pub fn select(&self, purpose: Purpose, now: u64) -> Result<AlgorithmId, PolicyError> {
+        self.allowed(purpose)
+            .iter()
+            .copied()
+            .find(|&alg| alg.is_available() && self.status(purpose, alg, now) == Some(AlgorithmStatus::Active))
+            .ok_or(PolicyError::NoAlgorithm(purpose))
+    }
+
+    /// Check `algorithm` before verifying or decrypting existing data.
+    /// Returns `Deprecated` for data the caller should re-protect.
+    pub fn accept(&self, purpose: Purpose, algorithm: AlgorithmId, now: u64) -> Result<AlgorithmStatus, PolicyError> {
+        match self.status(purpose, algorithm, now) {
+            None => Err(PolicyError::NotPermitted(purpose, algorithm)),
+            Some(AlgorithmStatus::Expired) => Err(PolicyError::Expired(algorithm)),
+            Some(status) => Ok(status),
+        }
+    }
+
+    /// Verify a detached signature if the policy accepts its algorithm.
+    pub fn verify(
+        &self,
+        algorithm: AlgorithmId,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+        now: u64
+    ) -> Result<bool, PolicyError> {
+        self.accept(Purpose::Sign, algorithm, now)?;
+        Ok(registry::verify(algorithm, public_key, message, signature))
+    }
+
+    /// Verify a signature container if the policy accepts its algorithm.
+    pub fn verify_container(
+        &self,
+        signature: &Container,
+        public_key: &[u8],
+        message: &[u8],
+        now: u64
+    ) -> Result<bool, PolicyError> {
+        self.accept(Purpose::Sign, signature.algorithm(), now)?;
+        Ok(signature.verify_signature(public_key, message)?)
+    }
+
+    /// Open a sealed envelope if the policy accepts its algorithm.
+    pub fn open_container(&self, envelope: &Container, secret_key: &[u8], now: u64) -> Result<Vec<u8>, PolicyError> {
+        self.accept(Purpose::Encrypt, envelope.algorithm(), now)?;
+        Ok(container::open(envelope, secret_key)?)
+    }
+
+    /// The key to use for `purpose` among `keys`: unexpired, with an
+    /// active algorithm, the most preferred algorithm first and the newest
+    /// key among equals.
+    pub fn select_key<'a>(&self, purpose: Purpose, keys: &'a [KeyMetadata], now: u64) -> Option<&'a KeyMetadata> {
+        let allowed = self.allowed(purpose);
+        keys.iter()
+            .filter(|key| !key.is_expired(now))
+            .filter(|key| self.status(purpose, key.algorithm, now) == Some(AlgorithmStatus::Active))
+            .min_by_key(|key| {
+                let rank = allowed.iter().position(|&alg| alg == key.algorithm);
+                (rank, std::cmp::Reverse(key.created_at))
+            })
+    }
+
+    /// Why `key` should be replaced, if it should.
+    fn rotation_reason(&self, purpose: Purpose, key: &KeyMetadata, now: u64) -> Option<RotationReason> {
+        match self.status(purpose, key.algorithm, now) {
+            None => Some(RotationReason::AlgorithmNotPermitted),
+            Some(AlgorithmStatus::Expired) => Some(RotationReason::AlgorithmExpired),
+            Some(AlgorithmStatus::Deprecated) => Some(RotationReason::AlgorithmDeprecated),
+            Some(AlgorithmStatus::Active) if key.is_expired(now) => Some(RotationReason::KeyExpired),
+            Some(AlgorithmStatus::Active) => {
+                let too_old = self.max_key_age.is_some_and(|age| now.saturating_sub(key.created_at) >= age);
+                too_old.then_some(RotationReason::MaxAgeExceeded)
+            }
+        }
+    }
+
+    /// Key groups that need a new key for `purpose`.
+    ///
+    /// Keys of the purpose's kind are grouped by their labels. A group is
+    /// due when none of its keys is in good standing; the event names the
+    /// group's newest key. Groups that were already rotated are therefore
+    /// not reported again.
+    pub fn rotation_due(
+        &self,
+        purpose: Purpose,
+        keys: &[KeyMetadata],
+        now: u64
+    ) -> Result<Vec<RotationEvent>, PolicyError> {
+        let mut groups: BTreeMap<Vec<String>, Vec<&KeyMetadata>> = BTreeMap::new();
+        for key in keys.iter().filter(|key| key.algorithm.kind() == purpose.kind()) {
+            let mut labels = key.labels.clone();
+            labels.sort();
+            groups.entry(labels).or_default().push(key);
+        }
+        let mut events = Vec::new();
+        for group in groups.values() {
+            if group.iter().any(|key| self.rotation_reason(purpose, key, now).is_none()) {
+                continue;
+            }
+            // Among keys created in the same second, prefer the one whose
+            // algorithm the policy still rates best.
+            let newest = group
+                .iter()
+                .max_by_key(|key| {
+                    let status = self.status(purpose, key.algorithm, now).map_or(3, |s| s as u8);
+                    (key.created_at, std::cmp::Reverse(status), key.id)
+                })
+                .expect("groups are never empty");
+            events.push(RotationEvent {
+                purpose,
+                key: newest.id,
+                labels: newest.labels.clone(),
+                from: newest.algorithm,
+                to: self.select(purpose, now)?,
+                reason: self.rotation_reason(purpose, newest, now).expect("checked above"),
+                new_key: None,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Generate a replacement for every key group that is due and return
+    /// the events with `new_key` set.
+    ///
+    /// The replacement gets the old key's labels and usage. Old keys are
+    /// kept so that data already sealed to them can still be opened;
+    /// delete them once that data has been re-protected.
+    pub fn rotate<S: KeyStore>(&self, store: &mut S, purpose: Purpose, now: u64) -> Result<Vec<RotationEvent>, PolicyError> {
+        let mut events = self.rotation_due(purpose, &store.list()?, now)?;
+        for event in &mut events {
+            let old = store.metadata(&event.key)?;
+            let mut options = KeyOptions::new().with_usage(old.usage);
+            for label in &old.labels {
+                options = options.with_label(label);
+            }
+            event.new_key = Some(store.generate(event.to, options)?.id);
+        }
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_and_selection() {
+        let policy = CryptoPolicy::recommended()
+            .deprecate(AlgorithmId::MlKem768, 100)
+            .expire(AlgorithmId::MlKem768, 200);
+        assert_eq!(policy.select(Purpose::Encrypt, 99), Ok(AlgorithmId::MlKem768));
+        assert_eq!(policy.select(Purpose::Encrypt, 100), Ok(AlgorithmId::MlKem1024));
+        assert_eq!(policy.accept(Purpose::Encrypt, AlgorithmId::MlKem768, 150), Ok(AlgorithmStatus::Deprecated));
+        assert_eq!(
+            policy.accept(Purpose::Encrypt, AlgorithmId::MlKem768, 200),
+            Err(PolicyError::Expired(AlgorithmId::MlKem768))
+        );
+        assert_eq!(
+            policy.accept(Purpose::Encrypt, AlgorithmId::MlKem512, 0),
+            Err(PolicyError::NotPermitted(Purpose::Encrypt, AlgorithmId::MlKem512))
+        );
+        assert!(CryptoPolicy::new().allow(Purpose::Sign, AlgorithmId::MlKem768).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+// src/python_bindings.rs
+//! Python bindings for Aegis Crypto Core (PyO3).
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{ PyBytes, PyDict };
+
+use crate::container;
+
+/// Seal `plaintext` to a KEM public key and return the encoded container.
+#[pyfunction]
+fn container_seal<'py>(
+    py: Python<'py>,
+    algorithm_code: u16,
+    recipient_public_key: &[u8],
+    plaintext: &[u8]
+) -> PyResult<Bound<'py, PyBytes>> {
+    container
+        ::container_seal(algorithm_code, recipient_public_key, plaintext)
+        .map(|bytes| PyBytes::new(py, &bytes))
+        .map_err(PyValueError::new_err)
+}
+
+/// Open an encoded sealed-envelope container.
+#[pyfunction]
+fn container_open<'py>(
+    py: Python<'py>,
+    secret_key: &[u8],
+    data: &[u8]
+) -> PyResult<Bound<'py, PyBytes>> {
+    container
+        ::container_open(secret_key, data)
+        .map(|bytes| PyBytes::new(py, &bytes))
+        .map_err(PyValueError::new_err)
+}
+
+/// Emit a key, ciphertext or signature container.
+#[pyfunction]
+fn container_encode<'py>(
+    py: Python<'py>,
+    kind: u8,
+    algorithm_code: u16,
+    key_id: &[u8],
+    payload: &[u8]
+) -> PyResult<Bound<'py, PyBytes>> {
+    container
+        ::container_encode(kind, algorithm_code, key_id, payload)
+        .map(|bytes| PyBytes::new(py, &bytes))
+        .map_err(PyValueError::new_err)
+}
+
+/// Parse an encoded container into a dict.
+#[pyfunction]
+fn container_decode<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyDict>> {
+    let parsed = container::container_decode(data).map_err(PyValueError::new_err)?;
+    let dict = PyDict::new(py);
+    dict.set_item("kind", parsed.kind_code())?;
+    dict.set_item("algorithm", parsed.algorithm_code())?;
+    dict.set_item("parameter_set", parsed.parameter_set())?;
+    dict.set_item("key_id", PyBytes::new(py, &parsed.key_id()))?;
+    dict.set_item("created", parsed.created())?;
+    dict.set_item("payload", PyBytes::new(py, parsed.payload_bytes()))?;
+    Ok(dict)
+}
+
+#[pymodule]
+fn aegis_crypto_core(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(container_seal, m)?)?;
+    m.add_function(wrap_pyfunction!(container_open, m)?)?;
+    m.add_function(wrap_pyfunction!(container_encode, m)?)?;
+    m.add_function(wrap_pyfunction!(container_decode, m)?)?;
+    Ok(())
+}
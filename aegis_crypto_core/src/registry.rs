@@ -0,0 +1,590 @@
+//! Algorithm registry: stable identifiers and runtime dispatch.
+//!
+//! Every algorithm and parameter set gets a stable 16-bit code. Codes are
+//! written into serialized artifacts (see [`crate::container`]) so that data
+//! stays decodable after the preferred algorithm changes. Codes are never
+//! reused; retired algorithms keep their identifier.
+
+/// Whether an algorithm is a KEM or a signature scheme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlgorithmKind {
+    Kem,
+    Signature,
+}
+
+/// Stable algorithm identifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u16)]
+pub enum AlgorithmId {
+    MlKem512 = 0x0101,
+    MlKem768 = 0x0102,
+    MlKem1024 = 0x0103,
+    Hqc128 = 0x0111,
+    Hqc192 = 0x0112,
+    Hqc256 = 0x0113,
+    MlDsa44 = 0x0201,
+    MlDsa65 = 0x0202,
+    MlDsa87 = 0x0203,
+    Falcon512 = 0x0211,
+    Falcon1024 = 0x0212,
+    SlhDsaSha2_128f = 0x0221,
+    SlhDsaSha2_192f = 0x0222,
+    SlhDsaSha2_256f = 0x0223,
+    SlhDsaShake128f = 0x0224,
+    SlhDsaShake192f = 0x0225,
+    SlhDsaShake256f = 0x0226,
+}
+
+/// Registry error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgorithmError {
+    UnknownAlgorithm(u16),
+    UnknownName(String),
+    /// The algorithm exists but its feature is not compiled in, or it is
+    /// the wrong kind for the requested operation.
+    Unsupported(AlgorithmId),
+    InvalidKey,
+    InvalidCiphertext,
+    OperationFailed(String),
+}
+
+impl std::fmt::Display for AlgorithmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlgorithmError::UnknownAlgorithm(code) => write!(f, "Unknown algorithm code 0x{:04x}", code),
+            AlgorithmError::UnknownName(name) => write!(f, "Unknown algorithm name: {}", name),
+            AlgorithmError::Unsupported(alg) => write!(f, "Unsupported operation for {}", alg.name()),
+            AlgorithmError::InvalidKey => write!(f, "Invalid key"),
+            AlgorithmError::InvalidCiphertext => write!(f, "Invalid ciphertext"),
+            AlgorithmError::OperationFailed(msg) => write!(f, "Operation failed: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for AlgorithmError {}
+
+impl AlgorithmId {
+    /// All registered algorithms, in code order.
+    pub const ALL: [AlgorithmId; 17] = [
+        AlgorithmId::MlKem512,
+        AlgorithmId::MlKem768,
+        AlgorithmId::MlKem1024,
+        AlgorithmId::Hqc128,
+        AlgorithmId::Hqc192,
+        AlgorithmId::Hqc256,
+        AlgorithmId::MlDsa44,
+        AlgorithmId::MlDsa65,
+        AlgorithmId::MlDsa87,
+        AlgorithmId::Falcon512,
+        AlgorithmId::Falcon1024,
+        AlgorithmId::SlhDsaSha2_128f,
+        AlgorithmId::SlhDsaSha2_192f,
+        AlgorithmId::SlhDsaSha2_256f,
+        AlgorithmId::SlhDsaShake128f,
+        AlgorithmId::SlhDsaShake192f,
+        AlgorithmId::SlhDsaShake256f,
+    ];
+
+    /// The stable numeric code.
+    pub fn code(self) -> u16 {
+        self as u16
+    }
+
+    pub fn from_code(code: u16) -> Result<Self, AlgorithmError> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|alg| alg.code() == code)
+            .ok_or(AlgorithmError::UnknownAlgorithm(code))
+    }
+
+    /// The parameter-set name, e.g. `ML-KEM-768`.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlgorithmId::MlKem512 => "ML-KEM-512",
+            AlgorithmId::MlKem768 => "ML-KEM-768",
+            AlgorithmId::MlKem1024 => "ML-KEM-1024",
+            AlgorithmId::Hqc128 => "HQC-128",
+            AlgorithmId::Hqc192 => "HQC-192",
+            AlgorithmId::Hqc256 => "HQC-256",
+            AlgorithmId::MlDsa44 => "ML-DSA-44",
+            AlgorithmId::MlDsa65 => "ML-DSA-65",
+            AlgorithmId::MlDsa87 => "ML-DSA-87",
+            AlgorithmId::Falcon512 => "Falcon-512",
+            AlgorithmId::Falcon1024 => "Falcon-1024",
+            AlgorithmId::SlhDsaSha2_128f => "SLH-DSA-SHA2-128f",
+            AlgorithmId::SlhDsaSha2_192f => "SLH-DSA-SHA2-192f",
+            AlgorithmId::SlhDsaSha2_256f => "SLH-DSA-SHA2-256f",
+            AlgorithmId::SlhDsaShake128f => "SLH-DSA-SHAKE-128f",
+            AlgorithmId::SlhDsaShake192f => "SLH-DSA-SHAKE-192f",
+            AlgorithmId::SlhDsaShake256f => "SLH-DSA-SHAKE-256f",
+        }
+    }
+
+    /// Look up an algorithm by parameter-set name (case-insensitive).
+    pub fn from_name(name: &str) -> Result<Self, AlgorithmError> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|alg| alg.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| AlgorithmError::UnknownName(name.to_string()))
+    }
+
+    pub fn kind(self) -> AlgorithmKind {
+        match self {
+            | AlgorithmId::MlKem512
+            | AlgorithmId::MlKem768
+            | AlgorithmId::MlKem1024
+            | AlgorithmId::Hqc128
+            | AlgorithmId::Hqc192
+            | AlgorithmId::Hqc256 => AlgorithmKind::Kem,
+            _ => AlgorithmKind::Signature,
+        }
+    }
+
+    /// Classical security level in bits of the NIST category.
+    pub fn security_level(self) -> usize {
+        match self {
+            | AlgorithmId::MlKem512
+            | AlgorithmId::Hqc128
+            | AlgorithmId::MlDsa44
+            | AlgorithmId::Falcon512
+            | AlgorithmId::SlhDsaSha2_128f
+            | AlgorithmId::SlhDsaShake128f => 128,
+            | AlgorithmId::MlKem768
+            | AlgorithmId::Hqc192
+            | AlgorithmId::MlDsa65
+            | AlgorithmId::SlhDsaSha2_192f
+            | AlgorithmId::SlhDsaShake192f => 192,
+            _ => 256,
+        }
+    }
+
+    /// Whether the backing implementation is compiled into this build.
+    pub fn is_available(self) -> bool {
+        match self {
+            AlgorithmId::MlKem512 | AlgorithmId::MlKem768 | AlgorithmId::MlKem1024 => {
+                cfg!(feature = "kyber")
+            }
+            AlgorithmId::Hqc128 | AlgorithmId::Hqc192 | AlgorithmId::Hqc256 => cfg!(feature = "hqc"),
+            AlgorithmId::MlDsa44 | AlgorithmId::MlDsa65 | AlgorithmId::MlDsa87 => {
+                cfg!(feature = "dilithium")
+            }
+            AlgorithmId::Falcon512 | AlgorithmId::Falcon1024 => cfg!(feature = "falcon"),
+            _ => cfg!(feature = "sphincsplus"),
+        }
+    }
+
+    /// Public key length in bytes, if the algorithm is available.
+    pub fn public_key_len(self) -> Option<usize> {
+        self.sizes().map(|s| s.0)
+    }
+
+    /// Secret key length in bytes, if the algorithm is available.
+    pub fn secret_key_len(self) -> Option<usize> {
+        self.sizes().map(|s| s.1)
+    }
+
+    /// KEM ciphertext length or maximum signature length in bytes.
+    pub fn output_len(self) -> Option<usize> {
+        self.sizes().map(|s| s.2)
+    }
+
+    /// Length of the seed taken by [`keygen_from_seed`], if the algorithm
+    /// is available and supports deterministic key generation.
+    pub fn seed_len(self) -> Option<usize> {
+        match self {
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem512 => Some(pqcrypto_mlkem::mlkem512::seed_bytes()),
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem768 => Some(pqcrypto_mlkem::mlkem768::seed_bytes()),
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem1024 => Some(pqcrypto_mlkem::mlkem1024::seed_bytes()),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa44 => Some(pqcrypto_mldsa::mldsa44::seed_bytes()),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa65 => Some(pqcrypto_mldsa::mldsa65::seed_bytes()),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa87 => Some(pqcrypto_mldsa::mldsa87::seed_bytes()),
+            #[cfg(feature = "falcon")]
+            AlgorithmId::Falcon512 => Some(pqcrypto_falcon::falcon512::seed_bytes()),
+            #[cfg(feature = "falcon")]
+            AlgorithmId::Falcon1024 => Some(pqcrypto_falcon::falcon1024::seed_bytes()),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    fn sizes(self) -> Option<(usize, usize, usize)> {
+        macro_rules! kem_sizes {
+            ($m:path) => {
+                {
+                    use $m as m;
+                    Some((m::public_key_bytes(), m::secret_key_bytes(), m::ciphertext_bytes()))
+                }
+            };
+        }
+        macro_rules! sig_sizes {
+            ($m:path) => {
+                {
+                    use $m as m;
+                    Some((m::public_key_bytes(), m::secret_key_bytes(), m::signature_bytes()))
+                }
+            };
+        }
+        match self {
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem512 => kem_sizes!(pqcrypto_mlkem::mlkem512),
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem768 => kem_sizes!(pqcrypto_mlkem::mlkem768),
+            #[cfg(feature = "kyber")]
+            AlgorithmId::MlKem1024 => kem_sizes!(pqcrypto_mlkem::mlkem1024),
+            #[cfg(feature = "hqc")]
+            AlgorithmId::Hqc128 => kem_sizes!(pqcrypto_hqc::hqc128),
+            #[cfg(feature = "hqc")]
+            AlgorithmId::Hqc192 => kem_sizes!(pqcrypto_hqc::hqc192),
+            #[cfg(feature = "hqc")]
+            AlgorithmId::Hqc256 => kem_sizes!(pqcrypto_hqc::hqc256),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa44 => sig_sizes!(pqcrypto_mldsa::mldsa44),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa65 => sig_sizes!(pqcrypto_mldsa::mldsa65),
+            #[cfg(feature = "dilithium")]
+            AlgorithmId::MlDsa87 => sig_sizes!(pqcrypto_mldsa::mldsa87),
+            #[cfg(feature = "falcon")]
+            AlgorithmId::Falcon512 => sig_sizes!(pqcrypto_falcon::falcon512),
+            #[cfg(feature = "falcon")]
+            AlgorithmId::Falcon1024 => sig_sizes!(pqcrypto_falcon::falcon1024),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaSha2_128f => sig_sizes!(pqcrypto_sphincsplus::sphincssha2128fsimple),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaSha2_192f => sig_sizes!(pqcrypto_sphincsplus::sphincssha2192fsimple),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaSha2_256f => sig_sizes!(pqcrypto_sphincsplus::sphincssha2256fsimple),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaShake128f => sig_sizes!(pqcrypto_sphincsplus::sphincsshake128fsimple),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaShake192f => sig_sizes!(pqcrypto_sphincsplus::sphincsshake192fsimple),
+            #[cfg(feature = "sphincsplus")]
+            AlgorithmId::SlhDsaShake256f => sig_sizes!(pqcrypto_sphincsplus::sphincsshake256fsimple),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AlgorithmId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Algorithms serialize by name in human-readable formats and by code
+/// otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for AlgorithmId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(self.name())
+        } else {
+            serializer.serialize_u16(self.code())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AlgorithmId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let name = <std::borrow::Cow<'de, str> as serde::Deserialize>::deserialize(deserializer)?;
+            AlgorithmId::from_name(&name).map_err(serde::de::Error::custom)
+        } else {
+            let code = <u16 as serde::Deserialize>::deserialize(deserializer)?;
+            AlgorithmId::from_code(code).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+fn check_len(
+    alg: AlgorithmId,
+    actual: usize,
+    expected: Option<usize>,
+    err: AlgorithmError
+) -> Result<(), AlgorithmError> {
+    match expected {
+        None => Err(AlgorithmError::Unsupported(alg)),
+        Some(len) if len == actual => Ok(()),
+        Some(_) => Err(err),
+    }
+}
+
+/// Generate a key pair, returning `(public_key, secret_key)`.
+pub fn keygen(alg: AlgorithmId) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+    macro_rules! pair {
+        ($kp:expr) => {
+            {
+                let kp = $kp;
+                Ok((kp.public_key(), kp.secret_key()))
+            }
+        };
+    }
+    match alg {
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem512 => pair!(crate::kyber512_keygen()),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem768 => pair!(crate::kyber768_keygen()),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem1024 => pair!(crate::kyber1024_keygen()),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc128 => pair!(crate::hqc128_keygen()),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc192 => pair!(crate::hqc192_keygen()),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc256 => pair!(crate::hqc256_keygen()),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa44 => pair!(crate::dilithium44_keygen()),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa65 => pair!(crate::dilithium65_keygen()),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa87 => pair!(crate::dilithium87_keygen()),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon512 => pair!(crate::falcon512_keygen()),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon1024 => pair!(crate::falcon1024_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_128f => pair!(crate::sphincsplus_sha2_128f_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_192f => pair!(crate::sphincsplus_sha2_192f_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_256f => pair!(crate::sphincsplus_sha2_256f_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake128f => pair!(crate::sphincsplus_shake_128f_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake192f => pair!(crate::sphincsplus_shake_192f_keygen()),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake256f => pair!(crate::sphincsplus_shake_256f_keygen()),
+        #[allow(unreachable_patterns)]
+        _ => Err(AlgorithmError::Unsupported(alg)),
+    }
+}
+
+/// Derive a key pair from a seed of [`AlgorithmId::seed_len`] bytes,
+/// returning `(public_key, secret_key)`. The same seed always gives the
+/// same key pair. For ML-DSA the seed is FIPS 204's `xi` and for ML-KEM
+/// it is FIPS 203's `d || z`.
+pub fn keygen_from_seed(alg: AlgorithmId, seed: &[u8]) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+    check_len(alg, seed.len(), alg.seed_len(), AlgorithmError::InvalidKey)?;
+    macro_rules! pair {
+        ($m:path, $kind:ident) => {
+            {
+                use $m as m;
+                use pqcrypto_traits::$kind::{ PublicKey as _, SecretKey as _ };
+                let (pk, sk) = m::keypair_from_seed(seed.try_into().expect("seed length checked"));
+                Ok((pk.as_bytes().to_vec(), sk.as_bytes().to_vec()))
+            }
+        };
+    }
+    match alg {
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem512 => pair!(pqcrypto_mlkem::mlkem512, kem),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem768 => pair!(pqcrypto_mlkem::mlkem768, kem),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem1024 => pair!(pqcrypto_mlkem::mlkem1024, kem),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa44 => pair!(pqcrypto_mldsa::mldsa44, sign),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa65 => pair!(pqcrypto_mldsa::mldsa65, sign),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa87 => pair!(pqcrypto_mldsa::mldsa87, sign),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon512 => pair!(pqcrypto_falcon::falcon512, sign),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon1024 => pair!(pqcrypto_falcon::falcon1024, sign),
+        #[allow(unreachable_patterns)]
+        _ => Err(AlgorithmError::Unsupported(alg)),
+    }
+}
+
+/// Encapsulate to a KEM public key, returning `(ciphertext, shared_secret)`.
+pub fn encapsulate(alg: AlgorithmId, public_key: &[u8]) -> Result<(Vec<u8>, Vec<u8>), AlgorithmError> {
+    if alg.kind() != AlgorithmKind::Kem {
+        return Err(AlgorithmError::Unsupported(alg));
+    }
+    check_len(alg, public_key.len(), alg.public_key_len(), AlgorithmError::InvalidKey)?;
+    macro_rules! encap {
+        ($f:path) => {
+            $f(public_key)
+                .map(|e| (e.ciphertext(), e.shared_secret()))
+                .map_err(|e| AlgorithmError::OperationFailed(e.to_string()))
+        };
+    }
+    match alg {
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem512 => encap!(crate::kyber512_encapsulate),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem768 => encap!(crate::kyber768_encapsulate),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem1024 => encap!(crate::kyber1024_encapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc128 => encap!(crate::hqc128_encapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc192 => encap!(crate::hqc192_encapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc256 => encap!(crate::hqc256_encapsulate),
+        _ => Err(AlgorithmError::Unsupported(alg)),
+    }
+}
+
+/// Decapsulate a KEM ciphertext, returning the shared secret.
+pub fn decapsulate(
+    alg: AlgorithmId,
+    secret_key: &[u8],
+    ciphertext: &[u8]
+) -> Result<Vec<u8>, AlgorithmError> {
+    if alg.kind() != AlgorithmKind::Kem {
+        return Err(AlgorithmError::Unsupported(alg));
+    }
+    check_len(alg, secret_key.len(), alg.secret_key_len(), AlgorithmError::InvalidKey)?;
+    check_len(alg, ciphertext.len(), alg.output_len(), AlgorithmError::InvalidCiphertext)?;
+    macro_rules! decap {
+        ($f:path) => {
+            $f(secret_key, ciphertext).map_err(|e| AlgorithmError::OperationFailed(e.to_string()))
+        };
+    }
+    match alg {
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem512 => decap!(crate::kyber512_decapsulate),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem768 => decap!(crate::kyber768_decapsulate),
+        #[cfg(feature = "kyber")]
+        AlgorithmId::MlKem1024 => decap!(crate::kyber1024_decapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc128 => decap!(crate::hqc128_decapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc192 => decap!(crate::hqc192_decapsulate),
+        #[cfg(feature = "hqc")]
+        AlgorithmId::Hqc256 => decap!(crate::hqc256_decapsulate),
+        _ => Err(AlgorithmError::Unsupported(alg)),
+    }
+}
+
+/// Produce a detached signature.
+pub fn sign(alg: AlgorithmId, secret_key: &[u8], message: &[u8]) -> Result<Vec<u8>, AlgorithmError> {
+    if alg.kind() != AlgorithmKind::Signature {
+        return Err(AlgorithmError::Unsupported(alg));
+    }
+    // The per-algorithm signing functions panic on malformed keys, so the
+    // length is checked here first.
+    check_len(alg, secret_key.len(), alg.secret_key_len(), AlgorithmError::InvalidKey)?;
+    match alg {
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa44 => Ok(crate::dilithium44_sign_detached(secret_key, message)),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa65 => Ok(crate::dilithium65_sign_detached(secret_key, message)),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa87 => Ok(crate::dilithium87_sign_detached(secret_key, message)),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon512 => Ok(crate::falcon512_sign(secret_key, message)),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon1024 => Ok(crate::falcon1024_sign(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_128f => Ok(crate::sphincsplus_sha2_128f_sign_detached(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_192f => Ok(crate::sphincsplus_sha2_192f_sign_detached(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_256f => Ok(crate::sphincsplus_sha2_256f_sign_detached(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake128f => Ok(crate::sphincsplus_shake_128f_sign_detached(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake192f => Ok(crate::sphincsplus_shake_192f_sign_detached(secret_key, message)),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake256f => Ok(crate::sphincsplus_shake_256f_sign_detached(secret_key, message)),
+        _ => Err(AlgorithmError::Unsupported(alg)),
+    }
+}
+
+/// Verify a detached signature. Returns `false` for unavailable algorithms.
+pub fn verify(alg: AlgorithmId, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    match alg {
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa44 => crate::dilithium44_verify_detached(public_key, message, signature),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa65 => crate::dilithium65_verify_detached(public_key, message, signature),
+        #[cfg(feature = "dilithium")]
+        AlgorithmId::MlDsa87 => crate::dilithium87_verify_detached(public_key, message, signature),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon512 => crate::falcon512_verify(public_key, message, signature),
+        #[cfg(feature = "falcon")]
+        AlgorithmId::Falcon1024 => crate::falcon1024_verify(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_128f => crate::sphincsplus_sha2_128f_verify_detached(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_192f => crate::sphincsplus_sha2_192f_verify_detached(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaSha2_256f => crate::sphincsplus_sha2_256f_verify_detached(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake128f => crate::sphincsplus_shake_128f_verify_detached(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake192f => crate::sphincsplus_shake_192f_verify_detached(public_key, message, signature),
+        #[cfg(feature = "sphincsplus")]
+        AlgorithmId::SlhDsaShake256f => crate::sphincsplus_shake_256f_verify_detached(public_key, message, signature),
+        _ => {
+            let _ = (public_key, message, signature);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_names_roundtrip() {
+        for alg in AlgorithmId::ALL {
+            assert_eq!(AlgorithmId::from_code(alg.code()).unwrap(), alg);
+            assert_eq!(AlgorithmId::from_name(alg.name()).unwrap(), alg);
+        }
+        assert_eq!(AlgorithmId::from_code(0xffff), Err(AlgorithmError::UnknownAlgorithm(0xffff)));
+    }
+
+    #[test]
+    #[cfg(all(feature = "kyber", feature = "dilithium", feature = "falcon"))]
+    fn test_dispatch_kem_and_signature() {
+        let (pk, sk) = keygen(AlgorithmId::MlKem1024).unwrap();
+        let (ct, ss) = encapsulate(AlgorithmId::MlKem1024, &pk).unwrap();
+        assert_eq!(ct.len(), AlgorithmId::MlKem1024.output_len().unwrap());
+        assert_eq!(decapsulate(AlgorithmId::MlKem1024, &sk, &ct).unwrap(), ss);
+
+        for alg in [AlgorithmId::MlDsa65, AlgorithmId::Falcon512] {
+            let (pk, sk) = keygen(alg).unwrap();
+            let sig = sign(alg, &sk, b"message").unwrap();
+            assert!(verify(alg, &pk, b"message", &sig));
+            assert!(!verify(alg, &pk, b"other", &sig));
+        }
+
+        assert_eq!(sign(AlgorithmId::MlDsa65, &[0u8; 3], b"m"), Err(AlgorithmError::InvalidKey));
+        assert!(encapsulate(AlgorithmId::MlDsa65, &pk).is_err());
+    }
+
+    #[test]
+    #[cfg(all(feature = "kyber", feature = "dilithium"))]
+    fn test_keygen_from_seed_is_deterministic() {
+        let seed = [3u8; 64];
+        let kem = keygen_from_seed(AlgorithmId::MlKem768, &seed).unwrap();
+        assert_eq!(keygen_from_seed(AlgorithmId::MlKem768, &seed).unwrap(), kem);
+        let (ct, ss) = encapsulate(AlgorithmId::MlKem768, &kem.0).unwrap();
+        assert_eq!(decapsulate(AlgorithmId::MlKem768, &kem.1, &ct).unwrap(), ss);
+
+        let (pk, sk) = keygen_from_seed(AlgorithmId::MlDsa44, &seed[..32]).unwrap();
+        assert!(verify(AlgorithmId::MlDsa44, &pk, b"m", &sign(AlgorithmId::MlDsa44, &sk, b"m").unwrap()));
+        assert_ne!(keygen_from_seed(AlgorithmId::MlDsa44, &[4u8; 32]).unwrap().0, pk);
+
+        assert_eq!(keygen_from_seed(AlgorithmId::MlDsa44, &seed), Err(AlgorithmError::InvalidKey));
+        assert_eq!(
+            keygen_from_seed(AlgorithmId::Hqc128, &seed),
+            Err(AlgorithmError::Unsupported(AlgorithmId::Hqc128))
+        );
+    }
+}
@@ -0,0 +1,304 @@
+//! Serde support for Aegis keys, ciphertexts, signatures and containers.
+//!
+//! Enabled by the `serde` feature. Byte strings are written as unpadded
+//! base64url in human-readable formats such as JSON, and as raw bytes in
+//! binary formats such as bincode or CBOR. Padded base64url input is also
+//! accepted when deserializing.
+//!
+//! Types that carry secret material (key pairs, encapsulation results and
+//! secret key containers) refuse to serialize on their own. Wrap them in
+//! [`ExposeSecrets`] to opt in explicitly:
+//!
+//! ```ignore
+//! let keypair = kyber768_keygen();
+//! assert!(serde_json::to_string(&keypair).is_err());
+//! let json = serde_json::to_string(&ExposeSecrets(&keypair))?;
+//! ```
+//!
+//! Deserialization is always available.
+
+use serde::{ Serialize, Serializer };
+
+/// `#[serde(with = "...")]` helper: base64url when human-readable, raw
+/// bytes otherwise.
+pub mod bytes {
+    use base64::{
+        alphabet,
+        engine::{ DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig },
+        Engine as _,
+    };
+    use serde::{ Deserializer, Serializer };
+
+    const ENCODE: GeneralPurpose = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    const DECODE: GeneralPurpose = GeneralPurpose::new(
+        &alphabet::URL_SAFE,
+        GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent)
+    );
+
+    pub fn serialize<S, T>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: AsRef<[u8]> + ?Sized
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&ENCODE.encode(bytes.as_ref()))
+        } else {
+            serializer.serialize_bytes(bytes.as_ref())
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where D: Deserializer<'de>
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(super::TextVisitor(|s: &str| DECODE.decode(s)))
+        } else {
+            deserializer.deserialize_byte_buf(super::BytesVisitor)
+        }
+    }
+}
+
+/// `#[serde(with = "...")]` helper: lowercase hex when human-readable, raw
+/// bytes otherwise.
+pub mod hex {
+    use serde::{ Deserializer, Serializer };
+
+    pub fn serialize<S, T>(bytes: &T, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer, T: AsRef<[u8]> + ?Sized
+    {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&::hex::encode(bytes.as_ref()))
+        } else {
+            serializer.serialize_bytes(bytes.as_ref())
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+        where D: Deserializer<'de>
+    {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(super::TextVisitor(|s: &str| ::hex::decode(s)))
+        } else {
+            deserializer.deserialize_byte_buf(super::BytesVisitor)
+        }
+    }
+}
+
+struct TextVisitor<F>(F);
+
+impl<'de, F, E> serde::de::Visitor<'de> for TextVisitor<F>
+    where F: FnOnce(&str) -> Result<Vec<u8>, E>, E: std::fmt::Display
+{
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("an encoded byte string")
+    }
+
+    fn visit_str<Er: serde::de::Error>(self, v: &str) -> Result<Vec<u8>, Er> {
+        (self.0)(v).map_err(Er::custom)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("a byte string")
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            out.push(byte);
+        }
+        Ok(out)
+    }
+}
+
+/// Serialization of a value including its secret material.
+///
+/// Implemented by every type whose plain `Serialize` impl refuses to run.
+pub trait SerializeSecret {
+    fn serialize_secret<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+}
+
+/// Explicit opt-in to serializing secret key material.
+pub struct ExposeSecrets<'a, T: ?Sized>(pub &'a T);
+
+impl<T: SerializeSecret + ?Sized> Serialize for ExposeSecrets<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize_secret(serializer)
+    }
+}
+
+/// Error returned when a secret-bearing type is serialized directly.
+pub(crate) fn refuse_secret<S: Serializer>(type_name: &str) -> Result<S::Ok, S::Error> {
+    Err(
+        <S::Error as serde::ser::Error>::custom(
+            format!("{} holds secret key material; serialize it through ExposeSecrets", type_name)
+        )
+    )
+}
+
+/// Check deserialized field lengths against the parameter sets a type can
+/// hold. Each entry of `allowed` lists the lengths of every field for one
+/// parameter set; the first field that fits none of the remaining sets is
+/// reported.
+pub(crate) fn check_lengths<E: serde::de::Error, const N: usize>(
+    names: [&str; N],
+    lengths: [usize; N],
+    allowed: &[[usize; N]]
+) -> Result<(), E> {
+    let mut candidates: Vec<&[usize; N]> = allowed.iter().collect();
+    for i in 0..N {
+        let matching: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|set| set[i] == lengths[i])
+            .collect();
+        if matching.is_empty() {
+            let mut expected: Vec<usize> = candidates.iter().map(|set| set[i]).collect();
+            expected.sort_unstable();
+            expected.dedup();
+            let expected: Vec<String> = expected.iter().map(usize::to_string).collect();
+            let expected = format!("{} of {} bytes", names[i], expected.join(" or "));
+            return Err(E::invalid_length(lengths[i], &expected.as_str()));
+        }
+        candidates = matching;
+    }
+    Ok(())
+}
+
+/// Implements `Serialize` (refusing), `SerializeSecret` and `Deserialize`
+/// for a struct of byte vectors. Each field is written under the given
+/// external name. Fields listed after a `;` are not key material and pass
+/// through with their own serde impls, after the byte fields.
+///
+/// The trailing array gives the byte field lengths of each supported
+/// parameter set, in field order; anything else fails to deserialize
+/// rather than reaching the backend with the wrong size.
+macro_rules! impl_secret_serde {
+    (
+        $ty:ident { $($field:tt as $name:ident),+ $(; $($plain:ident: $plain_ty:ty),+)? $(,)? },
+        $lengths:expr
+    ) => {
+        const _: () = {
+            #[derive(serde::Serialize)]
+            struct Borrowed<'a> {
+                $(
+                    #[serde(with = "crate::serialization::bytes")]
+                    $name: &'a [u8],
+                )+
+                $($(
+                    $plain: &'a $plain_ty,
+                )+)?
+            }
+
+            #[derive(serde::Deserialize)]
+            struct Owned {
+                $(
+                    #[serde(with = "crate::serialization::bytes")]
+                    $name: Vec<u8>,
+                )+
+                $($(
+                    $plain: $plain_ty,
+                )+)?
+            }
+
+            impl serde::Serialize for $ty {
+                fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+                    crate::serialization::refuse_secret::<S>(stringify!($ty))
+                }
+            }
+
+            impl crate::serialization::SerializeSecret for $ty {
+                fn serialize_secret<S: serde::Serializer>(
+                    &self,
+                    serializer: S
+                ) -> Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(
+                        &Borrowed { $($name: &self.$field,)+ $($($plain: &self.$plain,)+)? },
+                        serializer
+                    )
+                }
+            }
+
+            impl<'de> serde::Deserialize<'de> for $ty {
+                fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let owned = <Owned as serde::Deserialize>::deserialize(deserializer)?;
+                    crate::serialization::check_lengths::<D::Error, _>(
+                        [$(stringify!($name)),+],
+                        [$(owned.$name.len()),+],
+                        &$lengths
+                    )?;
+                    Ok($ty { $($field: owned.$name,)+ $($($plain: owned.$plain,)+)? })
+                }
+            }
+        };
+    };
+}
+
+pub(crate) use impl_secret_serde;
+
+/// `impl_secret_serde!` lengths of a key pair: public and secret key size
+/// of each listed `pqcrypto` parameter set module.
+macro_rules! key_lengths {
+    ($($m:path),+ $(,)?) => {
+        [$({
+            use $m as m;
+            [m::public_key_bytes(), m::secret_key_bytes()]
+        }),+]
+    };
+}
+
+/// `impl_secret_serde!` lengths of an encapsulation: ciphertext and shared
+/// secret size of each listed `pqcrypto` KEM module.
+macro_rules! encapsulated_lengths {
+    ($($m:path),+ $(,)?) => {
+        [$({
+            use $m as m;
+            [m::ciphertext_bytes(), m::shared_secret_bytes()]
+        }),+]
+    };
+}
+
+pub(crate) use { encapsulated_lengths, key_lengths };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Sample {
+        #[serde(with = "bytes")]
+        data: Vec<u8>,
+        #[serde(with = "hex")]
+        digest: Vec<u8>,
+    }
+
+    #[test]
+    fn test_human_readable_and_binary_encodings() {
+        let sample = Sample { data: vec![0xfb, 0xff, 0x01], digest: vec![0xab, 0xcd] };
+
+        let json = serde_json::to_string(&sample).unwrap();
+        assert_eq!(json, r#"{"data":"-_8B","digest":"abcd"}"#);
+        assert_eq!(serde_json::from_str::<Sample>(&json).unwrap(), sample);
+        let padded: Sample = serde_json::from_str(r#"{"data":"-_8BAg==","digest":"ABCD"}"#).unwrap();
+        assert_eq!(padded.data, vec![0xfb, 0xff, 0x01, 0x02]);
+        assert_eq!(padded.digest, sample.digest);
+
+        let binary = bincode::serialize(&sample).unwrap();
+        // Two u64 length prefixes plus the raw bytes.
+        assert_eq!(binary.len(), 8 + 3 + 8 + 2);
+        assert_eq!(bincode::deserialize::<Sample>(&binary).unwrap(), sample);
+    }
+}
@@ -5,6 +5,9 @@ use pqcrypto_sphincsplus::sphincssha2128fsimple::{
     keypair as keypairSha2128f,
     open as openSha2128f,
     SignedMessage as SignedMessageSha2128f,
+    DetachedSignature as DetachedSignatureSha2128f,
+    detached_sign as detachedSignSha2128f,
+    verify_detached_signature as verifyDetachedSha2128f,
 };
 use pqcrypto_sphincsplus::sphincssha2192fsimple::{
     PublicKey as PublicKeySha2192f,
@@ -13,6 +16,9 @@ use pqcrypto_sphincsplus::sphincssha2192fsimple::{
     keypair as keypairSha2192f,
     open as openSha2192f,
     SignedMessage as SignedMessageSha2192f,
+    DetachedSignature as DetachedSignatureSha2192f,
+    detached_sign as detachedSignSha2192f,
+    verify_detached_signature as verifyDetachedSha2192f,
 };
 use pqcrypto_sphincsplus::sphincssha2256fsimple::{
     PublicKey as PublicKeySha2256f,
@@ -21,6 +27,9 @@ use pqcrypto_sphincsplus::sphincssha2256fsimple::{
     keypair as keypairSha2256f,
     open as openSha2256f,
     SignedMessage as SignedMessageSha2256f,
+    DetachedSignature as DetachedSignatureSha2256f,
+    detached_sign as detachedSignSha2256f,
+    verify_detached_signature as verifyDetachedSha2256f,
 };
 use pqcrypto_sphincsplus::sphincsshake128fsimple::{
     PublicKey as PublicKeyShake128f,
@@ -29,6 +38,9 @@ use pqcrypto_sphincsplus::sphincsshake128fsimple::{
     keypair as keypairShake128f,
     open as openShake128f,
     SignedMessage as SignedMessageShake128f,
+    DetachedSignature as DetachedSignatureShake128f,
+    detached_sign as detachedSignShake128f,
+    verify_detached_signature as verifyDetachedShake128f,
 };
 use pqcrypto_sphincsplus::sphincsshake192fsimple::{
     PublicKey as PublicKeyShake192f,
@@ -37,6 +49,9 @@ use pqcrypto_sphincsplus::sphincsshake192fsimple::{
     keypair as keypairShake192f,
     open as openShake192f,
     SignedMessage as SignedMessageShake192f,
+    DetachedSignature as DetachedSignatureShake192f,
+    detached_sign as detachedSignShake192f,
+    verify_detached_signature as verifyDetachedShake192f,
 };
 use pqcrypto_sphincsplus::sphincsshake256fsimple::{
     PublicKey as PublicKeyShake256f,
@@ -45,8 +60,16 @@ use pqcrypto_sphincsplus::sphincsshake256fsimple::{
     keypair as keypairShake256f,
     open as openShake256f,
     SignedMessage as SignedMessageShake256f,
+    DetachedSignature as DetachedSignatureShake256f,
+    detached_sign as detachedSignShake256f,
+    verify_detached_signature as verifyDetachedShake256f,
+};
+use pqcrypto_traits::sign::{
+    PublicKey as _,
+    SecretKey as _,
+    SignedMessage as _,
+    DetachedSignature as _,
 };
-use pqcrypto_traits::sign::{ PublicKey as _, SecretKey as _, SignedMessage as _ };
 #[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
 
@@ -56,6 +79,19 @@ pub struct SphincsPlusKeyPair {
     sk: Vec<u8>,
 }
 
+#[cfg(feature = "serde")]
+crate::serialization::impl_secret_serde!(
+    SphincsPlusKeyPair { pk as public_key, sk as secret_key },
+    crate::serialization::key_lengths!(
+        pqcrypto_sphincsplus::sphincssha2128fsimple,
+        pqcrypto_sphincsplus::sphincssha2192fsimple,
+        pqcrypto_sphincsplus::sphincssha2256fsimple,
+        pqcrypto_sphincsplus::sphincsshake128fsimple,
+        pqcrypto_sphincsplus::sphincsshake192fsimple,
+        pqcrypto_sphincsplus::sphincsshake256fsimple
+    )
+);
+
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl SphincsPlusKeyPair {
     #[cfg_attr(feature = "wasm", wasm_bindgen(getter))]
@@ -103,6 +139,30 @@ pub fn sphincsplus_sha2_128f_verify(public_key: &[u8], signed_message: &[u8]) ->
     openSha2128f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_128f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeySha2128f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignSha2128f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_128f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeySha2128f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureSha2128f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedSha2128f(&sig, message, &pk).is_ok()
+}
+
 // SPHINCS+-SHA2-192f Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_sha2_192f_keygen() -> SphincsPlusKeyPair {
@@ -137,6 +197,30 @@ pub fn sphincsplus_sha2_192f_verify(public_key: &[u8], signed_message: &[u8]) ->
     openSha2192f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_192f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeySha2192f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignSha2192f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_192f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeySha2192f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureSha2192f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedSha2192f(&sig, message, &pk).is_ok()
+}
+
 // SPHINCS+-SHA2-256f Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_sha2_256f_keygen() -> SphincsPlusKeyPair {
@@ -171,6 +255,30 @@ pub fn sphincsplus_sha2_256f_verify(public_key: &[u8], signed_message: &[u8]) ->
     openSha2256f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_256f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeySha2256f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignSha2256f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_sha2_256f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeySha2256f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureSha2256f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedSha2256f(&sig, message, &pk).is_ok()
+}
+
 // SPHINCS+-SHAKE-128f Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_shake_128f_keygen() -> SphincsPlusKeyPair {
@@ -205,6 +313,30 @@ pub fn sphincsplus_shake_128f_verify(public_key: &[u8], signed_message: &[u8]) -
     openShake128f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_128f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeyShake128f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignShake128f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_128f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeyShake128f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureShake128f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedShake128f(&sig, message, &pk).is_ok()
+}
+
 // SPHINCS+-SHAKE-192f Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_shake_192f_keygen() -> SphincsPlusKeyPair {
@@ -239,6 +371,30 @@ pub fn sphincsplus_shake_192f_verify(public_key: &[u8], signed_message: &[u8]) -
     openShake192f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_192f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeyShake192f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignShake192f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_192f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeyShake192f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureShake192f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedShake192f(&sig, message, &pk).is_ok()
+}
+
 // SPHINCS+-SHAKE-256f Functions
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_shake_256f_keygen() -> SphincsPlusKeyPair {
@@ -273,6 +429,30 @@ pub fn sphincsplus_shake_256f_verify(public_key: &[u8], signed_message: &[u8]) -
     openShake256f(&signed_message, &pk).is_ok()
 }
 
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_256f_sign_detached(secret_key: &[u8], message: &[u8]) -> Vec<u8> {
+    let sk = SecretKeyShake256f::from_bytes(secret_key).expect("Invalid secret key");
+    let signature = detachedSignShake256f(message, &sk);
+    signature.as_bytes().to_vec()
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn sphincsplus_shake_256f_verify_detached(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let pk = match PublicKeyShake256f::from_bytes(public_key) {
+        Ok(pk) => pk,
+        Err(_) => {
+            return false;
+        }
+    };
+    let sig = match DetachedSignatureShake256f::from_bytes(signature) {
+        Ok(sig) => sig,
+        Err(_) => {
+            return false;
+        }
+    };
+    verifyDetachedShake256f(&sig, message, &pk).is_ok()
+}
+
 // Legacy functions (for backward compatibility - default to SPHINCS+-SHA2-128f)
 #[cfg_attr(feature = "wasm", wasm_bindgen)]
 pub fn sphincsplus_keygen() -> SphincsPlusKeyPair {
@@ -0,0 +1,417 @@
+//! Shamir secret sharing over GF(256) for secret keys and seeds.
+//!
+//! [`split`] cuts a secret of any length into `n` shares such that any
+//! `k` of them reconstruct it and fewer reveal nothing. Each byte of the
+//! secret is the constant term of its own random polynomial of degree
+//! `k - 1`. Arithmetic is in GF(2^8) modulo the AES polynomial and avoids
+//! secret-dependent table lookups and branches.
+//!
+//! Each share carries two kinds of protection:
+//!
+//! - An integrity tag, a truncated SHA3-256 over the encoded share, which
+//!   catches transcription and storage errors when a share is decoded.
+//! - A hash commitment to every share of the set (verifiable secret
+//!   sharing). Commitments are blinded with a per-share random nonce so
+//!   they leak nothing about low-entropy secrets. [`reconstruct`] checks
+//!   each share against the commitments, so a share altered after the
+//!   split is reported as [`SssError::CorruptShare`] instead of silently
+//!   producing a wrong secret.
+//!
+//! A holder who alters both their share and its copy of the commitments
+//! is still detected, because the shares then disagree about the set
+//! ([`SssError::InconsistentShares`]). Record
+//! [`Share::commitment_digest`] at split time to tell which side is
+//! honest.
+//!
+//! ```text
+//! share := "AESS" version:u8 threshold:u8 index:u8 set_id[16]
+//!          length:u16be value[length] blind[32]
+//!          count:u8 commitment[32]*count tag[16]
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine };
+use sha3::{ Digest, Sha3_256 };
+use subtle::ConstantTimeEq;
+use zeroize::{ Zeroize, Zeroizing };
+
+const MAGIC: &[u8; 4] = b"AESS";
+const VERSION: u8 = 1;
+const TEXT_PREFIX: &str = "aegis-share1-";
+const COMMITMENT_LABEL: &[u8] = b"aegis-sss-v1 commitment";
+const TAG_LABEL: &[u8] = b"aegis-sss-v1 tag";
+
+pub const SET_ID_LEN: usize = 16;
+const BLIND_LEN: usize = 32;
+const COMMITMENT_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+/// Longest secret that can be split.
+pub const MAX_SECRET_LEN: usize = u16::MAX as usize;
+
+/// Secret sharing error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SssError {
+    Malformed(String),
+    InvalidParameters(String),
+    /// The share's integrity tag does not match its contents.
+    BadTag,
+    /// The share at this index does not match its commitment.
+    CorruptShare(u8),
+    /// The shares come from different splits or disagree about the
+    /// commitments.
+    InconsistentShares,
+    NotEnoughShares {
+        required: usize,
+        got: usize,
+    },
+}
+
+impl fmt::Display for SssError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SssError::Malformed(msg) => write!(f, "Malformed share: {}", msg),
+            SssError::InvalidParameters(msg) => write!(f, "Invalid sharing parameters: {}", msg),
+            SssError::BadTag => write!(f, "Share integrity tag mismatch"),
+            SssError::CorruptShare(i) => write!(f, "Share {} does not match its commitment", i),
+            SssError::InconsistentShares => write!(f, "Shares do not belong to the same split"),
+            SssError::NotEnoughShares { required, got } => {
+                write!(f, "Need {} distinct shares, got {}", required, got)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SssError {}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, without
+/// data-dependent branches.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & (b & 1).wrapping_neg();
+        let carry = (a >> 7).wrapping_neg();
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse as `a^254`; maps 0 to 0.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// One share of a split secret.
+#[derive(Clone)]
+pub struct Share {
+    threshold: u8,
+    index: u8,
+    set_id: [u8; SET_ID_LEN],
+    value: Vec<u8>,
+    blind: [u8; BLIND_LEN],
+    commitments: Vec<[u8; COMMITMENT_LEN]>,
+}
+
+impl Share {
+    /// The share's x-coordinate, from 1 to the number of shares.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Number of shares in the split.
+    pub fn share_count(&self) -> u8 {
+        self.commitments.len() as u8
+    }
+
+    /// Random identifier shared by every share of one split.
+    pub fn set_id(&self) -> &[u8; SET_ID_LEN] {
+        &self.set_id
+    }
+
+    /// SHA3-256 over the split parameters and every share commitment.
+    /// Identical for all honest shares of a split.
+    pub fn commitment_digest(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(COMMITMENT_LABEL);
+        hasher.update(self.set_id);
+        hasher.update([self.threshold, self.commitments.len() as u8]);
+        hasher.update((self.value.len() as u16).to_be_bytes());
+        for commitment in &self.commitments {
+            hasher.update(commitment);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Whether the share matches its own commitment.
+    pub fn verify(&self) -> bool {
+        let expected = commitment(&self.set_id, self.index, &self.blind, &self.value);
+        let Some(stored) = self.commitments.get((self.index as usize).wrapping_sub(1)) else {
+            return false;
+        };
+        bool::from(expected.ct_eq(stored))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.encode_body();
+        let tag = tag(&out);
+        out.extend_from_slice(&tag);
+        out
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, SssError> {
+        if data.len() < TAG_LEN {
+            return Err(SssError::Malformed("truncated".into()));
+        }
+        let (body, stored_tag) = data.split_at(data.len() - TAG_LEN);
+        let mut reader = Reader { data: body };
+        if reader.take(4)? != MAGIC {
+            return Err(SssError::Malformed("bad magic".into()));
+        }
+        if reader.take(1)?[0] != VERSION {
+            return Err(SssError::Malformed("unsupported version".into()));
+        }
+        if !bool::from(tag(body).ct_eq(stored_tag)) {
+            return Err(SssError::BadTag);
+        }
+        let header = reader.take(2)?;
+        let (threshold, index) = (header[0], header[1]);
+        let set_id = reader.take(SET_ID_LEN)?.try_into().expect("length checked");
+        let length = reader.take(2)?;
+        let value = reader.take(u16::from_be_bytes([length[0], length[1]]) as usize)?.to_vec();
+        let blind = reader.take(BLIND_LEN)?.try_into().expect("length checked");
+        let count = reader.take(1)?[0] as usize;
+        let commitments = (0..count)
+            .map(|_| reader.take(COMMITMENT_LEN).map(|c| c.try_into().expect("length checked")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !reader.data.is_empty() {
+            return Err(SssError::Malformed("trailing data".into()));
+        }
+        if threshold == 0 || (threshold as usize) > count || index == 0 || (index as usize) > count {
+            return Err(SssError::Malformed("invalid threshold or index".into()));
+        }
+        Ok(Share { threshold, index, set_id, value, blind, commitments })
+    }
+
+    fn encode_body(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            26 + self.value.len() + BLIND_LEN + 1 + self.commitments.len() * COMMITMENT_LEN
+        );
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(self.threshold);
+        out.push(self.index);
+        out.extend_from_slice(&self.set_id);
+        out.extend_from_slice(&(self.value.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.value);
+        out.extend_from_slice(&self.blind);
+        out.push(self.commitments.len() as u8);
+        for commitment in &self.commitments {
+            out.extend_from_slice(commitment);
+        }
+        out
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.blind.zeroize();
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("share_count", &self.commitments.len())
+            .field("set_id", &hex::encode(self.set_id))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Text form: `aegis-share1-` followed by unpadded base64url.
+impl fmt::Display for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = self.to_bytes();
+        let result = write!(f, "{}{}", TEXT_PREFIX, URL_SAFE_NO_PAD.encode(&bytes));
+        bytes.zeroize();
+        result
+    }
+}
+
+impl FromStr for Share {
+    type Err = SssError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s
+            .trim()
+            .strip_prefix(TEXT_PREFIX)
+            .ok_or_else(|| SssError::Malformed("missing aegis-share1- prefix".into()))?;
+        let bytes = Zeroizing::new(
+            URL_SAFE_NO_PAD.decode(encoded).map_err(|_| SssError::Malformed("invalid base64".into()))?
+        );
+        Share::from_bytes(&bytes)
+    }
+}
+
+fn commitment(set_id: &[u8; SET_ID_LEN], index: u8, blind: &[u8; BLIND_LEN], value: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(COMMITMENT_LABEL);
+    hasher.update(set_id);
+    hasher.update([index]);
+    hasher.update(blind);
+    hasher.update(value);
+    hasher.finalize().into()
+}
+
+fn tag(body: &[u8]) -> [u8; TAG_LEN] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(TAG_LABEL);
+    hasher.update(body);
+    let digest = hasher.finalize();
+    digest[..TAG_LEN].try_into().expect("digest is 32 bytes")
+}
+
+/// Split `secret` into `count` shares, any `threshold` of which recover it.
+pub fn split(secret: &[u8], threshold: u8, count: u8) -> Result<Vec<Share>, SssError> {
+    if secret.is_empty() || secret.len() > MAX_SECRET_LEN {
+        return Err(SssError::InvalidParameters(format!("secret must be 1 to {} bytes", MAX_SECRET_LEN)));
+    }
+    if threshold == 0 || threshold > count {
+        return Err(SssError::InvalidParameters("threshold must be between 1 and the share count".into()));
+    }
+
+    // coefficients[j][b] is the x^(j+1) coefficient for secret byte b.
+    let mut coefficients = Zeroizing::new(vec![0u8; (threshold as usize - 1) * secret.len()]);
+    getrandom::fill(&mut coefficients).expect("RNG failed");
+    let mut set_id = [0u8; SET_ID_LEN];
+    getrandom::fill(&mut set_id).expect("RNG failed");
+
+    let mut shares: Vec<Share> = (1..=count)
+        .map(|x| {
+            // Horner's rule from the highest coefficient down to the secret.
+            let mut value = vec![0u8; secret.len()];
+            for row in coefficients.chunks(secret.len()).rev().chain(std::iter::once(secret)) {
+                for (acc, &c) in value.iter_mut().zip(row) {
+                    *acc = gf_mul(*acc, x) ^ c;
+                }
+            }
+            let mut blind = [0u8; BLIND_LEN];
+            getrandom::fill(&mut blind).expect("RNG failed");
+            Share { threshold, index: x, set_id, value, blind, commitments: Vec::new() }
+        })
+        .collect();
+
+    let commitments: Vec<[u8; COMMITMENT_LEN]> = shares
+        .iter()
+        .map(|s| commitment(&s.set_id, s.index, &s.blind, &s.value))
+        .collect();
+    for share in &mut shares {
+        share.commitments = commitments.clone();
+    }
+    Ok(shares)
+}
+
+/// Recover the secret from at least `threshold` shares of one split.
+///
+/// Every share is checked against the commitments before use. Repeated
+/// copies of the same share are accepted and counted once.
+pub fn reconstruct(shares: &[Share]) -> Result<Zeroizing<Vec<u8>>, SssError> {
+    let first = shares.first().ok_or(SssError::NotEnoughShares { required: 1, got: 0 })?;
+    let digest = first.commitment_digest();
+    let mut distinct: Vec<&Share> = Vec::with_capacity(shares.len());
+    for share in shares {
+        if share.commitment_digest() != digest || share.value.len() != first.value.len() {
+            return Err(SssError::InconsistentShares);
+        }
+        if !share.verify() {
+            return Err(SssError::CorruptShare(share.index));
+        }
+        if !distinct.iter().any(|s| s.index == share.index) {
+            distinct.push(share);
+        }
+    }
+    let required = first.threshold as usize;
+    if distinct.len() < required {
+        return Err(SssError::NotEnoughShares { required, got: distinct.len() });
+    }
+
+    // Lagrange interpolation at x = 0. Subtraction in GF(2^8) is XOR.
+    let points = &distinct[..required];
+    let mut secret = Zeroizing::new(vec![0u8; first.value.len()]);
+    for (i, share) in points.iter().enumerate() {
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, other) in points.iter().enumerate() {
+            if i != j {
+                numerator = gf_mul(numerator, other.index);
+                denominator = gf_mul(denominator, other.index ^ share.index);
+            }
+        }
+        let basis = gf_mul(numerator, gf_inv(denominator));
+        for (out, &y) in secret.iter_mut().zip(&share.value) {
+            *out ^= gf_mul(basis, y);
+        }
+    }
+    Ok(secret)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SssError> {
+        if self.data.len() < len {
+            return Err(SssError::Malformed("truncated".into()));
+        }
+        let (head, tail) = self.data.split_at(len);
+        self.data = tail;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_arithmetic() {
+        // FIPS 197 section 4.2: {57} * {83} = {c1}.
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_every_threshold_subset_recovers_seed() {
+        let seed: Vec<u8> = (0..64u8).collect();
+        let shares = split(&seed, 3, 5).unwrap();
+        for a in 0..5 {
+            for b in a + 1..5 {
+                for c in b + 1..5 {
+                    let subset = [shares[a].clone(), shares[b].clone(), shares[c].clone()];
+                    assert_eq!(*reconstruct(&subset).unwrap(), seed);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,563 @@
+//! Chunked streaming encryption keyed from a KEM encapsulation.
+//!
+//! The payload is split into fixed-size chunks sealed with
+//! ChaCha20-Poly1305 using the STREAM construction: chunk `i` uses the
+//! nonce `i` (11 bytes, big endian) followed by a flag byte that is `0x01`
+//! for the final chunk and `0x00` otherwise. Dropped, reordered or swapped
+//! chunks fail authentication, and a stream cut at a chunk boundary is
+//! rejected because its last chunk is not flagged as final.
+//!
+//! ```text
+//! magic "AEGS" | version | algorithm (u16) | chunk size (u32) | salt (16)
+//!   | KEM ciphertext length (u32) | KEM ciphertext | chunks...
+//! ```
+//!
+//! The payload key is HKDF-SHA256 over the KEM shared secret, salted with
+//! the header salt, with the whole header as info, so any header change
+//! also breaks decryption. Every chunk except the last carries exactly
+//! `chunk size` plaintext bytes; the last carries 1 to `chunk size` bytes,
+//! or 0 when the whole payload is empty.
+//!
+//! [`StreamEncryptor`] and [`StreamDecryptor`] take input in arbitrary
+//! pieces, which suits WASM callers; [`EncryptWriter`] and
+//! [`DecryptReader`] wrap them as `std::io` adapters.
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+use std::io::{ self, Read, Write };
+
+use chacha20poly1305::{ aead::{ Aead, KeyInit }, ChaCha20Poly1305, Nonce };
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::registry::{ self, AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// Stream magic bytes.
+pub const MAGIC: &[u8; 4] = b"AEGS";
+/// Current stream format version.
+pub const VERSION: u8 = 1;
+/// Default plaintext bytes per chunk.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+/// Largest accepted chunk size.
+pub const MAX_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+const TAG_LEN: usize = 16;
+const SALT_LEN: usize = 16;
+const FIXED_HEADER_LEN: usize = 4 + 1 + 2 + 4 + SALT_LEN + 4;
+const KEY_INFO: &[u8] = b"aegis-stream-v1 payload key";
+
+/// Streaming encryption error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamError {
+    Malformed(String),
+    UnsupportedVersion(u8),
+    InvalidChunkSize(usize),
+    /// The stream ended before its final chunk.
+    Truncated,
+    /// Chunk `n` failed authentication.
+    DecryptionFailed(u64),
+    /// Data follows the final chunk.
+    TrailingData,
+    /// Input was supplied after the stream was finished.
+    Finished,
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Malformed(msg) => write!(f, "Malformed stream: {}", msg),
+            StreamError::UnsupportedVersion(v) => write!(f, "Unsupported stream version {}", v),
+            StreamError::InvalidChunkSize(size) => write!(f, "Invalid chunk size {}", size),
+            StreamError::Truncated => write!(f, "Stream is truncated"),
+            StreamError::DecryptionFailed(n) => write!(f, "Chunk {} failed authentication", n),
+            StreamError::TrailingData => write!(f, "Data after the final chunk"),
+            StreamError::Finished => write!(f, "Stream is already finished"),
+            StreamError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<AlgorithmError> for StreamError {
+    fn from(e: AlgorithmError) -> Self {
+        StreamError::Algorithm(e)
+    }
+}
+
+impl From<StreamError> for io::Error {
+    fn from(e: StreamError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+fn check_chunk_size(chunk_size: usize) -> Result<(), StreamError> {
+    if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+        return Err(StreamError::InvalidChunkSize(chunk_size));
+    }
+    Ok(())
+}
+
+fn payload_cipher(shared_secret: &[u8], salt: &[u8], header: &[u8]) -> ChaCha20Poly1305 {
+    let mut key = [0u8; 32];
+    let mut info = KEY_INFO.to_vec();
+    info.extend_from_slice(header);
+    Hkdf::<Sha256>
+        ::new(Some(salt), shared_secret)
+        .expand(&info, &mut key)
+        .expect("32 is a valid HKDF-SHA256 output length");
+    let cipher = ChaCha20Poly1305::new_from_slice(&key).expect("32-byte key");
+    key.zeroize();
+    cipher
+}
+
+fn chunk_nonce(counter: u64, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[3..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+/// Chunk sealing shared by this format and the age payload.
+pub(crate) struct ChunkSealer {
+    cipher: ChaCha20Poly1305,
+    chunk_size: usize,
+    counter: u64,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl ChunkSealer {
+    pub(crate) fn new(cipher: ChaCha20Poly1305, chunk_size: usize) -> Self {
+        ChunkSealer { cipher, chunk_size, counter: 0, buffer: Vec::with_capacity(chunk_size), finished: false }
+    }
+
+    fn seal_chunk(&mut self, plaintext: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), StreamError> {
+        let nonce = chunk_nonce(self.counter, last);
+        let sealed = self.cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| StreamError::Malformed("chunk encryption failed".into()))?;
+        out.extend_from_slice(&sealed);
+        self.counter = self.counter.checked_add(1).ok_or(StreamError::Malformed("too many chunks".into()))?;
+        Ok(())
+    }
+
+    pub(crate) fn update(&mut self, plaintext: &[u8], out: &mut Vec<u8>) -> Result<(), StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+        self.buffer.extend_from_slice(plaintext);
+        // Hold back a full chunk until more input arrives, since it may be the last.
+        let mut start = 0;
+        while self.buffer.len() - start > self.chunk_size {
+            let chunk = self.buffer[start..start + self.chunk_size].to_vec();
+            self.seal_chunk(&chunk, false, out)?;
+            start += self.chunk_size;
+        }
+        self.buffer.drain(..start);
+        Ok(())
+    }
+
+    pub(crate) fn finalize(&mut self, out: &mut Vec<u8>) -> Result<(), StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+        let last = std::mem::take(&mut self.buffer);
+        self.seal_chunk(&last, true, out)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+/// Chunk opening shared by this format and the age payload.
+pub(crate) struct ChunkOpener {
+    cipher: ChaCha20Poly1305,
+    chunk_size: usize,
+    counter: u64,
+    buffer: Vec<u8>,
+    finished: bool,
+}
+
+impl ChunkOpener {
+    pub(crate) fn new(cipher: ChaCha20Poly1305, chunk_size: usize) -> Self {
+        ChunkOpener { cipher, chunk_size, counter: 0, buffer: Vec::new(), finished: false }
+    }
+
+    fn open_chunk(&mut self, sealed: &[u8], last: bool, out: &mut Vec<u8>) -> Result<(), StreamError> {
+        let cipher = &self.cipher;
+        let plaintext = match cipher.decrypt(Nonce::from_slice(&chunk_nonce(self.counter, last)), sealed) {
+            Ok(plaintext) => plaintext,
+            // A chunk that opens with the other flag is authentic but in the
+            // wrong place: a final chunk with data after it, or a stream cut
+            // short at a chunk boundary.
+            Err(_) if cipher.decrypt(Nonce::from_slice(&chunk_nonce(self.counter, !last)), sealed).is_ok() => {
+                return Err(if last { StreamError::Truncated } else { StreamError::TrailingData });
+            }
+            Err(_) => return Err(StreamError::DecryptionFailed(self.counter)),
+        };
+        out.extend_from_slice(&plaintext);
+        self.counter += 1;
+        Ok(())
+    }
+
+    pub(crate) fn update(&mut self, ciphertext: &[u8], out: &mut Vec<u8>) -> Result<(), StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+        self.buffer.extend_from_slice(ciphertext);
+        let sealed_len = self.chunk_size + TAG_LEN;
+        let mut start = 0;
+        while self.buffer.len() - start > sealed_len {
+            let chunk = self.buffer[start..start + sealed_len].to_vec();
+            self.open_chunk(&chunk, false, out)?;
+            start += sealed_len;
+        }
+        self.buffer.drain(..start);
+        Ok(())
+    }
+
+    pub(crate) fn finalize(&mut self, out: &mut Vec<u8>) -> Result<(), StreamError> {
+        if self.finished {
+            return Err(StreamError::Finished);
+        }
+        if self.buffer.len() < TAG_LEN {
+            return Err(StreamError::Truncated);
+        }
+        if self.buffer.len() == TAG_LEN && self.counter > 0 {
+            // Only an entirely empty payload ends in an empty chunk.
+            return Err(StreamError::Malformed("empty final chunk".into()));
+        }
+        let last = std::mem::take(&mut self.buffer);
+        self.open_chunk(&last, true, out)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+/// Incremental stream encryption.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct StreamEncryptor {
+    header: Option<Vec<u8>>,
+    chunks: ChunkSealer,
+}
+
+impl StreamEncryptor {
+    /// Start a stream to `public_key` with the default chunk size.
+    pub fn new(algorithm: AlgorithmId, public_key: &[u8]) -> Result<Self, StreamError> {
+        StreamEncryptor::with_chunk_size(algorithm, public_key, DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn with_chunk_size(
+        algorithm: AlgorithmId,
+        public_key: &[u8],
+        chunk_size: usize
+    ) -> Result<Self, StreamError> {
+        check_chunk_size(chunk_size)?;
+        if algorithm.kind() != AlgorithmKind::Kem {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        let (kem_ciphertext, mut shared_secret) = registry::encapsulate(algorithm, public_key)?;
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::fill(&mut salt).expect("RNG failed");
+
+        let mut header = Vec::with_capacity(FIXED_HEADER_LEN + kem_ciphertext.len());
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.extend_from_slice(&algorithm.code().to_be_bytes());
+        header.extend_from_slice(&(chunk_size as u32).to_be_bytes());
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&(kem_ciphertext.len() as u32).to_be_bytes());
+        header.extend_from_slice(&kem_ciphertext);
+        let cipher = payload_cipher(&shared_secret, &salt, &header);
+        shared_secret.zeroize();
+
+        Ok(StreamEncryptor { header: Some(header), chunks: ChunkSealer::new(cipher, chunk_size) })
+    }
+
+    /// Encrypt more plaintext, returning whatever output is ready. The
+    /// first call also returns the header.
+    pub fn update(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, StreamError> {
+        let mut out = self.header.take().unwrap_or_default();
+        self.chunks.update(plaintext, &mut out)?;
+        Ok(out)
+    }
+
+    /// Seal the final chunk and end the stream.
+    pub fn finalize(&mut self) -> Result<Vec<u8>, StreamError> {
+        let mut out = self.header.take().unwrap_or_default();
+        self.chunks.finalize(&mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl StreamEncryptor {
+    /// Start a stream to `public_key`; a `chunk_size` of 0 picks the default.
+    #[wasm_bindgen(constructor)]
+    pub fn create(
+        algorithm_code: u16,
+        public_key: &[u8],
+        chunk_size: usize
+    ) -> Result<StreamEncryptor, String> {
+        let algorithm = AlgorithmId::from_code(algorithm_code).map_err(|e| e.to_string())?;
+        let chunk_size = if chunk_size == 0 { DEFAULT_CHUNK_SIZE } else { chunk_size };
+        StreamEncryptor::with_chunk_size(algorithm, public_key, chunk_size).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_js(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        self.update(plaintext).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name = finish)]
+    pub fn finish_js(&mut self) -> Result<Vec<u8>, String> {
+        self.finalize().map_err(|e| e.to_string())
+    }
+}
+
+/// Incremental stream decryption.
+///
+/// Plaintext is released chunk by chunk as soon as it authenticates, so a
+/// caller must not act on it as a whole until [`StreamDecryptor::finalize`]
+/// has confirmed the stream was not truncated.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct StreamDecryptor {
+    secret_key: Vec<u8>,
+    header: Vec<u8>,
+    chunks: Option<ChunkOpener>,
+}
+
+impl StreamDecryptor {
+    /// Prepare to decrypt a stream sealed to the key pair of `secret_key`.
+    /// The algorithm is taken from the stream header.
+    pub fn new(secret_key: &[u8]) -> Self {
+        StreamDecryptor { secret_key: secret_key.to_vec(), header: Vec::new(), chunks: None }
+    }
+
+    /// Parse the header once enough bytes are buffered, returning the
+    /// header length.
+    fn read_header(&mut self) -> Result<Option<usize>, StreamError> {
+        let header = &self.header;
+        if header.len() < FIXED_HEADER_LEN {
+            return Ok(None);
+        }
+        if &header[..4] != MAGIC {
+            return Err(StreamError::Malformed("bad magic".into()));
+        }
+        if header[4] != VERSION {
+            return Err(StreamError::UnsupportedVersion(header[4]));
+        }
+        let algorithm = AlgorithmId::from_code(u16::from_be_bytes([header[5], header[6]]))?;
+        if algorithm.kind() != AlgorithmKind::Kem {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        let chunk_size = u32::from_be_bytes(header[7..11].try_into().unwrap()) as usize;
+        check_chunk_size(chunk_size)?;
+        let ct_len = u32::from_be_bytes(header[27..31].try_into().unwrap()) as usize;
+        if algorithm.output_len().is_some_and(|len| len != ct_len) {
+            return Err(AlgorithmError::InvalidCiphertext.into());
+        }
+        let header_len = FIXED_HEADER_LEN + ct_len;
+        if header.len() < header_len {
+            return Ok(None);
+        }
+        let header = &header[..header_len];
+        let mut shared_secret = registry::decapsulate(algorithm, &self.secret_key, &header[FIXED_HEADER_LEN..])?;
+        let cipher = payload_cipher(&shared_secret, &header[11..27], header);
+        shared_secret.zeroize();
+        self.chunks = Some(ChunkOpener::new(cipher, chunk_size));
+        Ok(Some(header_len))
+    }
+
+    /// Feed more ciphertext, returning any plaintext that authenticated.
+    pub fn update(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, StreamError> {
+        let mut out = Vec::new();
+        match self.chunks.as_mut() {
+            Some(chunks) => chunks.update(ciphertext, &mut out)?,
+            None => {
+                self.header.extend_from_slice(ciphertext);
+                if let Some(header_len) = self.read_header()? {
+                    let rest = self.header.split_off(header_len);
+                    self.chunks.as_mut().expect("header parsed").update(&rest, &mut out)?;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// Open the final chunk. Fails if the stream was truncated.
+    pub fn finalize(&mut self) -> Result<Vec<u8>, StreamError> {
+        let mut out = Vec::new();
+        self.chunks.as_mut().ok_or(StreamError::Truncated)?.finalize(&mut out)?;
+        Ok(out)
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[wasm_bindgen]
+impl StreamDecryptor {
+    #[wasm_bindgen(constructor)]
+    pub fn create(secret_key: &[u8]) -> StreamDecryptor {
+        StreamDecryptor::new(secret_key)
+    }
+
+    #[wasm_bindgen(js_name = push)]
+    pub fn push_js(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, String> {
+        self.update(ciphertext).map_err(|e| e.to_string())
+    }
+
+    #[wasm_bindgen(js_name = finish)]
+    pub fn finish_js(&mut self) -> Result<Vec<u8>, String> {
+        self.finalize().map_err(|e| e.to_string())
+    }
+}
+
+impl Drop for StreamDecryptor {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// Encrypt `plaintext` in one call.
+pub fn encrypt(algorithm: AlgorithmId, public_key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, StreamError> {
+    let mut encryptor = StreamEncryptor::new(algorithm, public_key)?;
+    let mut out = encryptor.update(plaintext)?;
+    out.extend_from_slice(&encryptor.finalize()?);
+    Ok(out)
+}
+
+/// Decrypt a complete stream in one call.
+pub fn decrypt(secret_key: &[u8], stream: &[u8]) -> Result<Vec<u8>, StreamError> {
+    let mut decryptor = StreamDecryptor::new(secret_key);
+    let mut out = decryptor.update(stream)?;
+    out.extend_from_slice(&decryptor.finalize()?);
+    Ok(out)
+}
+
+/// `std::io::Write` adapter that encrypts into an inner writer.
+///
+/// [`EncryptWriter::finish`] must be called to write the final chunk;
+/// dropping the writer leaves a stream that fails to decrypt as truncated.
+pub struct EncryptWriter<W: Write> {
+    inner: W,
+    encryptor: StreamEncryptor,
+}
+
+impl<W: Write> EncryptWriter<W> {
+    pub fn new(inner: W, algorithm: AlgorithmId, public_key: &[u8]) -> Result<Self, StreamError> {
+        Ok(EncryptWriter { inner, encryptor: StreamEncryptor::new(algorithm, public_key)? })
+    }
+
+    pub fn with_chunk_size(
+        inner: W,
+        algorithm: AlgorithmId,
+        public_key: &[u8],
+        chunk_size: usize
+    ) -> Result<Self, StreamError> {
+        Ok(EncryptWriter { inner, encryptor: StreamEncryptor::with_chunk_size(algorithm, public_key, chunk_size)? })
+    }
+
+    /// Write the final chunk and return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let tail = self.encryptor.finalize()?;
+        self.inner.write_all(&tail)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let out = self.encryptor.update(buf)?;
+        self.inner.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// `std::io::Read` adapter that decrypts from an inner reader.
+///
+/// Reads fail with `InvalidData` on tampering, and the final read fails if
+/// the stream was truncated instead of returning end of file.
+pub struct DecryptReader<R: Read> {
+    inner: R,
+    decryptor: StreamDecryptor,
+    plaintext: Vec<u8>,
+    position: usize,
+    done: bool,
+}
+
+impl<R: Read> DecryptReader<R> {
+    pub fn new(inner: R, secret_key: &[u8]) -> Self {
+        DecryptReader {
+            inner,
+            decryptor: StreamDecryptor::new(secret_key),
+            plaintext: Vec::new(),
+            position: 0,
+            done: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for DecryptReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut input = [0u8; 8192];
+        while self.position == self.plaintext.len() && !self.done {
+            let n = self.inner.read(&mut input)?;
+            self.plaintext = if n == 0 {
+                self.done = true;
+                self.decryptor.finalize()?
+            } else {
+                self.decryptor.update(&input[..n])?
+            };
+            self.position = 0;
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_chunk_boundaries() {
+        let (pk, sk) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+        for len in [0usize, 1, 63, 64, 65, 128, 200] {
+            let plaintext: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut encryptor = StreamEncryptor::with_chunk_size(AlgorithmId::MlKem768, &pk, 64).unwrap();
+            let mut stream = Vec::new();
+            for piece in plaintext.chunks(7) {
+                stream.extend_from_slice(&encryptor.update(piece).unwrap());
+            }
+            stream.extend_from_slice(&encryptor.finalize().unwrap());
+            let chunks = len.max(1).div_ceil(64);
+            assert_eq!(stream.len(), FIXED_HEADER_LEN + 1088 + len + chunks * TAG_LEN);
+            assert_eq!(decrypt(&sk, &stream).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_truncation_detected() {
+        let (pk, sk) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+        let mut encryptor = StreamEncryptor::with_chunk_size(AlgorithmId::MlKem768, &pk, 16).unwrap();
+        let mut stream = encryptor.update(&[5u8; 40]).unwrap();
+        let cut = stream.len();
+        stream.extend_from_slice(&encryptor.finalize().unwrap());
+        assert_eq!(decrypt(&sk, &stream[..cut]), Err(StreamError::Truncated));
+        assert_eq!(decrypt(&sk, &stream[..FIXED_HEADER_LEN]), Err(StreamError::Truncated));
+    }
+}
@@ -0,0 +1,121 @@
+//! JWT-VC: credentials and presentations secured as compact JWS tokens
+//! (VC-JOSE-COSE).
+//!
+//! The JWS payload is the credential or presentation itself. `typ` is
+//! `vc+jwt` or `vp+jwt`, and `kid` is the DID URL of the signing key,
+//! which is resolved and checked against the issuer or holder.
+
+use serde_json::{ json, Map, Value };
+
+use super::{ check_status, check_validity, issuer_id, Credential, DidSigner, VcError };
+use super::proof::{ resolve_key, ProofPurpose };
+use crate::did::VerificationMethod;
+use crate::jose;
+
+pub const VC_JWT_TYPE: &str = "vc+jwt";
+pub const VP_JWT_TYPE: &str = "vp+jwt";
+
+/// `id` prefix of an `EnvelopedVerifiableCredential` carrying a JWT-VC.
+pub const ENVELOPED_VC_PREFIX: &str = "data:application/vc+jwt,";
+
+fn sign(signer: &DidSigner, typ: &str, cty: &str, payload: &Value) -> Result<String, VcError> {
+    let mut header = Map::new();
+    header.insert("kid".into(), signer.did().verification_method_id().into());
+    header.insert("typ".into(), typ.into());
+    header.insert("cty".into(), cty.into());
+    let payload = serde_json::to_vec(payload).map_err(|e| VcError::Malformed(e.to_string()))?;
+    Ok(jose::sign_compact(signer.algorithm(), signer.secret_key(), header, &payload)?)
+}
+
+fn verify(token: &str, typ: &str, purpose: ProofPurpose) -> Result<(Value, VerificationMethod), VcError> {
+    let jws = jose::decode_compact(token)?;
+    if jws.typ() != Some(typ) {
+        return Err(VcError::Malformed(format!("expected typ {}", typ)));
+    }
+    let kid = jws.kid().ok_or_else(|| VcError::Malformed("missing kid".into()))?;
+    let (method, algorithm, public_key) = resolve_key(kid, purpose)?;
+    jws.verify(algorithm, &public_key)?;
+    let payload: Value = serde_json
+        ::from_slice(&jws.payload)
+        .map_err(|e| VcError::Malformed(e.to_string()))?;
+    if !payload.is_object() {
+        return Err(VcError::Malformed("payload is not a JSON object".into()));
+    }
+    Ok((payload, method))
+}
+
+/// Secure `credential` as a `vc+jwt` token signed by `issuer`.
+pub fn issue_jwt_credential(credential: &Credential, issuer: &DidSigner) -> Result<String, VcError> {
+    if credential.issuer != issuer.did().did() {
+        return Err(VcError::IssuerMismatch);
+    }
+    sign(issuer, VC_JWT_TYPE, "vc", &credential.to_value())
+}
+
+/// Verify a `vc+jwt` token, including its status, and return the
+/// credential.
+pub fn verify_jwt_credential(token: &str, status_lists: &[Value]) -> Result<Value, VcError> {
+    verify_jwt_credential_at(token, status_lists, super::now())
+}
+
+/// [`verify_jwt_credential`] at a given Unix time.
+pub fn verify_jwt_credential_at(token: &str, status_lists: &[Value], now: u64) -> Result<Value, VcError> {
+    let (credential, method) = verify(token, VC_JWT_TYPE, ProofPurpose::AssertionMethod)?;
+    if issuer_id(&credential) != Some(method.controller.as_str()) {
+        return Err(VcError::IssuerMismatch);
+    }
+    check_validity(&credential, now)?;
+    check_status(&credential, status_lists, now)?;
+    Ok(credential)
+}
+
+/// Wrap a `vc+jwt` token for inclusion in a presentation.
+pub fn enveloped_credential(token: &str) -> Value {
+    json!({
+        "@context": [super::CREDENTIALS_V2_CONTEXT],
+        "id": format!("{}{}", ENVELOPED_VC_PREFIX, token),
+        "type": "EnvelopedVerifiableCredential",
+    })
+}
+
+/// Create a `vp+jwt` presentation. `credentials` may mix Data Integrity
+/// secured credentials and [`enveloped_credential`]s. `nonce` and
+/// `audience` bind the token to the verifier's request.
+pub fn issue_jwt_presentation(
+    holder: &DidSigner,
+    credentials: &[Value],
+    nonce: &str,
+    audience: Option<&str>
+) -> Result<String, VcError> {
+    let mut presentation = super::presentation_value(holder, credentials);
+    presentation["nonce"] = nonce.into();
+    if let Some(audience) = audience {
+        presentation["aud"] = audience.into();
+    }
+    sign(holder, VP_JWT_TYPE, "vp", &presentation)
+}
+
+/// Verify a `vp+jwt` token and every credential it contains, returning
+/// the presentation.
+pub fn verify_jwt_presentation(
+    token: &str,
+    nonce: &str,
+    audience: Option<&str>,
+    status_lists: &[Value]
+) -> Result<Value, VcError> {
+    let (presentation, method) = verify(token, VP_JWT_TYPE, ProofPurpose::Authentication)?;
+    if presentation.get("holder").and_then(Value::as_str) != Some(method.controller.as_str()) {
+        return Err(VcError::IssuerMismatch);
+    }
+    if presentation.get("nonce").and_then(Value::as_str) != Some(nonce) {
+        return Err(VcError::InvalidProof("nonce mismatch".into()));
+    }
+    if presentation.get("aud").and_then(Value::as_str) != audience {
+        return Err(VcError::InvalidProof("audience mismatch".into()));
+    }
+    let now = super::now();
+    for credential in super::embedded_credentials(&presentation) {
+        super::verify_embedded_credential(credential, status_lists, now)?;
+    }
+    Ok(presentation)
+}
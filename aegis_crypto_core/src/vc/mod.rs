@@ -0,0 +1,466 @@
+//! W3C Verifiable Credentials 2.0 with post-quantum signatures.
+//!
+//! Two securing mechanisms are supported:
+//!
+//! * Data Integrity proofs with the ML-DSA and SLH-DSA JCS cryptosuites
+//!   ([`proof`]).
+//! * JWT-VC: compact JWS tokens as defined by VC-JOSE-COSE ([`jwt`]),
+//!   signed through [`crate::jose`].
+//!
+//! Issuers and holders are `did:key` identifiers. Revocation is checked
+//! against Bitstring Status List credentials the verifier already holds
+//! ([`status`]); nothing here performs network access.
+
+pub mod jcs;
+pub mod jwt;
+pub mod proof;
+pub mod status;
+
+pub use jwt::{
+    enveloped_credential,
+    issue_jwt_credential,
+    issue_jwt_presentation,
+    verify_jwt_credential,
+    verify_jwt_presentation,
+};
+pub use proof::{ add_proof, cryptosuite, verify_proof, ProofOptions, ProofPurpose };
+pub use status::{ check_status, status_list_credential, StatusEntry, StatusList };
+
+use serde_json::{ json, Value };
+use zeroize::Zeroize;
+
+use crate::did::{ DidError, DidKey };
+use crate::jose::JoseError;
+use crate::multiformats::MultiformatError;
+use crate::registry::{ AlgorithmError, AlgorithmId, AlgorithmKind };
+
+/// Base context of VC Data Model 2.0.
+pub const CREDENTIALS_V2_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+
+/// Verifiable credential error type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VcError {
+    Malformed(String),
+    UnsupportedCryptosuite(String),
+    InvalidProof(String),
+    /// The signing key's DID is not the credential issuer or holder.
+    IssuerMismatch,
+    NotYetValid,
+    Expired,
+    Status(String),
+    /// A status bit is set; carries the status purpose, e.g. `revocation`.
+    StatusSet(String),
+    Did(DidError),
+    Jose(JoseError),
+    Algorithm(AlgorithmError),
+}
+
+impl std::fmt::Display for VcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VcError::Malformed(msg) => write!(f, "Malformed credential: {}", msg),
+            VcError::UnsupportedCryptosuite(suite) => write!(f, "Unsupported cryptosuite: {}", suite),
+            VcError::InvalidProof(msg) => write!(f, "Invalid proof: {}", msg),
+            VcError::IssuerMismatch => write!(f, "Signing key does not belong to the issuer"),
+            VcError::NotYetValid => write!(f, "Credential is not yet valid"),
+            VcError::Expired => write!(f, "Credential has expired"),
+            VcError::Status(msg) => write!(f, "Status check failed: {}", msg),
+            VcError::StatusSet(purpose) => write!(f, "Credential status is set: {}", purpose),
+            VcError::Did(e) => write!(f, "{}", e),
+            VcError::Jose(e) => write!(f, "{}", e),
+            VcError::Algorithm(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for VcError {}
+
+impl From<DidError> for VcError {
+    fn from(e: DidError) -> Self {
+        VcError::Did(e)
+    }
+}
+
+impl From<MultiformatError> for VcError {
+    fn from(e: MultiformatError) -> Self {
+        VcError::Did(DidError::Multiformat(e))
+    }
+}
+
+impl From<JoseError> for VcError {
+    fn from(e: JoseError) -> Self {
+        VcError::Jose(e)
+    }
+}
+
+impl From<AlgorithmError> for VcError {
+    fn from(e: AlgorithmError) -> Self {
+        VcError::Algorithm(e)
+    }
+}
+
+/// A signature key pair bound to its `did:key` identifier, used by
+/// issuers and holders.
+pub struct DidSigner {
+    did: DidKey,
+    secret_key: Vec<u8>,
+}
+
+impl DidSigner {
+    pub fn new(algorithm: AlgorithmId, public_key: &[u8], secret_key: &[u8]) -> Result<Self, VcError> {
+        if algorithm.kind() != AlgorithmKind::Signature {
+            return Err(AlgorithmError::Unsupported(algorithm).into());
+        }
+        Ok(DidSigner { did: DidKey::new(algorithm, public_key)?, secret_key: secret_key.to_vec() })
+    }
+
+    pub fn did(&self) -> &DidKey {
+        &self.did
+    }
+
+    pub fn algorithm(&self) -> AlgorithmId {
+        self.did.algorithm()
+    }
+
+    pub(crate) fn secret_key(&self) -> &[u8] {
+        &self.secret_key
+    }
+}
+
+impl Drop for DidSigner {
+    fn drop(&mut self) {
+        self.secret_key.zeroize();
+    }
+}
+
+/// An unsecured credential.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Credential {
+    /// Extra JSON-LD contexts after the VC 2.0 base context.
+    pub contexts: Vec<String>,
+    pub id: Option<String>,
+    /// Types in addition to `VerifiableCredential`.
+    pub types: Vec<String>,
+    pub issuer: String,
+    pub valid_from: u64,
+    pub valid_until: Option<u64>,
+    pub credential_subject: Value,
+    pub credential_status: Vec<StatusEntry>,
+}
+
+impl Credential {
+    /// A credential valid from now, with no expiry.
+    pub fn new(issuer: &str, credential_subject: Value) -> Self {
+        Credential {
+            contexts: Vec::new(),
+            id: None,
+            types: Vec::new(),
+            issuer: issuer.to_string(),
+            valid_from: now(),
+            valid_until: None,
+            credential_subject,
+            credential_status: Vec::new(),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut context = vec![CREDENTIALS_V2_CONTEXT.to_string()];
+        context.extend(self.contexts.iter().cloned());
+        let mut types = vec!["VerifiableCredential".to_string()];
+        types.extend(self.types.iter().cloned());
+
+        let mut value =
+            json!({
+            "@context": context,
+            "type": types,
+            "issuer": self.issuer,
+            "validFrom": format_datetime(self.valid_from),
+            "credentialSubject": self.credential_subject,
+        });
+        if let Some(id) = &self.id {
+            value["id"] = id.clone().into();
+        }
+        if let Some(valid_until) = self.valid_until {
+            value["validUntil"] = format_datetime(valid_until).into();
+        }
+        match self.credential_status.as_slice() {
+            [] => {}
+            [entry] => {
+                value["credentialStatus"] = entry.to_value();
+            }
+            entries => {
+                value["credentialStatus"] = entries.iter().map(StatusEntry::to_value).collect();
+            }
+        }
+        value
+    }
+}
+
+/// Current Unix time in seconds.
+pub fn now() -> u64 {
+    std::time::SystemTime
+        ::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format Unix seconds as an RFC 3339 UTC timestamp.
+pub fn format_datetime(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+/// Parse an RFC 3339 timestamp into Unix seconds. Fractional seconds are
+/// truncated.
+pub fn parse_datetime(value: &str) -> Result<u64, VcError> {
+    let invalid = || VcError::Malformed(format!("invalid date-time: {}", value));
+    let b = value.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't') {
+        return Err(invalid());
+    }
+    if b[13] != b':' || b[16] != b':' {
+        return Err(invalid());
+    }
+    let num = |range: std::ops::Range<usize>| -> Result<i64, VcError> {
+        let s = value.get(range).ok_or_else(invalid)?;
+        if !s.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        s.parse().map_err(|_| invalid())
+    };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return Err(invalid());
+    }
+
+    let mut rest = &value[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        rest = &fraction[digits..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => {
+                    return Err(invalid());
+                }
+            };
+            let off = |range: std::ops::Range<usize>| rest[range].parse::<i64>().map_err(|_| invalid());
+            sign * (off(1..3)? * 3600 + off(4..6)? * 60)
+        }
+        _ => {
+            return Err(invalid());
+        }
+    };
+
+    let days = days_from_civil(year, month as u32, day as u32);
+    let secs = days * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    u64::try_from(secs).map_err(|_| invalid())
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = ((month as i64) + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + (day as i64) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// The issuer identifier: `issuer` may be a string or an object with `id`.
+pub(crate) fn issuer_id(credential: &Value) -> Option<&str> {
+    match credential.get("issuer")? {
+        Value::String(id) => Some(id),
+        other => other.get("id")?.as_str(),
+    }
+}
+
+fn has_type(value: &Value, wanted: &str) -> bool {
+    match value.get("type") {
+        Some(Value::String(t)) => t == wanted,
+        Some(Value::Array(types)) => types.iter().any(|t| t.as_str() == Some(wanted)),
+        _ => false,
+    }
+}
+
+pub(crate) fn check_validity(credential: &Value, now: u64) -> Result<(), VcError> {
+    if let Some(valid_from) = credential.get("validFrom").and_then(Value::as_str) {
+        if now < parse_datetime(valid_from)? {
+            return Err(VcError::NotYetValid);
+        }
+    }
+    if let Some(valid_until) = credential.get("validUntil").and_then(Value::as_str) {
+        if now > parse_datetime(valid_until)? {
+            return Err(VcError::Expired);
+        }
+    }
+    Ok(())
+}
+
+/// Verify a credential's proof, issuer binding and validity period, and
+/// return the issuer identifier.
+pub(crate) fn verify_issued(credential: &Value, now: u64) -> Result<String, VcError> {
+    if !has_type(credential, "VerifiableCredential") {
+        return Err(VcError::Malformed("missing VerifiableCredential type".into()));
+    }
+    let method = verify_proof(credential, ProofPurpose::AssertionMethod, None, None)?;
+    let issuer = issuer_id(credential).ok_or_else(|| VcError::Malformed("missing issuer".into()))?;
+    if issuer != method.controller {
+        return Err(VcError::IssuerMismatch);
+    }
+    check_validity(credential, now)?;
+    Ok(issuer.to_string())
+}
+
+/// Secure `credential` with a Data Integrity proof.
+pub fn issue_credential(credential: &Credential, issuer: &DidSigner) -> Result<Value, VcError> {
+    if credential.issuer != issuer.did().did() {
+        return Err(VcError::IssuerMismatch);
+    }
+    let options = ProofOptions {
+        purpose: ProofPurpose::AssertionMethod,
+        created: now(),
+        challenge: None,
+        domain: None,
+    };
+    add_proof(&credential.to_value(), issuer, &options)
+}
+
+/// Verify a Data Integrity secured credential, including its status.
+pub fn verify_credential(credential: &Value, status_lists: &[Value]) -> Result<(), VcError> {
+    verify_credential_at(credential, status_lists, now())
+}
+
+/// [`verify_credential`] at a given Unix time.
+pub fn verify_credential_at(credential: &Value, status_lists: &[Value], now: u64) -> Result<(), VcError> {
+    verify_issued(credential, now)?;
+    check_status(credential, status_lists, now)
+}
+
+/// Verify a credential embedded in a presentation: either Data Integrity
+/// secured or an `EnvelopedVerifiableCredential` carrying a JWT.
+pub(crate) fn verify_embedded_credential(
+    credential: &Value,
+    status_lists: &[Value],
+    now: u64
+) -> Result<(), VcError> {
+    if has_type(credential, "EnvelopedVerifiableCredential") {
+        let token = credential
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.strip_prefix(jwt::ENVELOPED_VC_PREFIX))
+            .ok_or_else(|| VcError::Malformed("unsupported enveloped credential".into()))?;
+        jwt::verify_jwt_credential_at(token, status_lists, now).map(|_| ())
+    } else {
+        verify_credential_at(credential, status_lists, now)
+    }
+}
+
+pub(crate) fn presentation_value(holder: &DidSigner, credentials: &[Value]) -> Value {
+    json!({
+        "@context": [CREDENTIALS_V2_CONTEXT],
+        "type": ["VerifiablePresentation"],
+        "holder": holder.did().did(),
+        "verifiableCredential": credentials,
+    })
+}
+
+pub(crate) fn embedded_credentials(presentation: &Value) -> Vec<&Value> {
+    match presentation.get("verifiableCredential") {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(item) => vec![item],
+        None => Vec::new(),
+    }
+}
+
+/// Create a presentation of `credentials`, authenticated by the holder
+/// with a proof bound to the verifier's `challenge` and optional `domain`.
+pub fn create_presentation(
+    holder: &DidSigner,
+    credentials: &[Value],
+    challenge: &str,
+    domain: Option<&str>
+) -> Result<Value, VcError> {
+    let options = ProofOptions {
+        purpose: ProofPurpose::Authentication,
+        created: now(),
+        challenge: Some(challenge.to_string()),
+        domain: domain.map(str::to_string),
+    };
+    add_proof(&presentation_value(holder, credentials), holder, &options)
+}
+
+/// Verify a presentation and every credential it contains.
+pub fn verify_presentation(
+    presentation: &Value,
+    challenge: &str,
+    domain: Option<&str>,
+    status_lists: &[Value]
+) -> Result<(), VcError> {
+    if !has_type(presentation, "VerifiablePresentation") {
+        return Err(VcError::Malformed("missing VerifiablePresentation type".into()));
+    }
+    if domain.is_none() && presentation.pointer("/proof/domain").is_some() {
+        return Err(VcError::InvalidProof("unexpected domain".into()));
+    }
+    let method = verify_proof(presentation, ProofPurpose::Authentication, Some(challenge), domain)?;
+    if presentation.get("holder").and_then(Value::as_str) != Some(method.controller.as_str()) {
+        return Err(VcError::IssuerMismatch);
+    }
+    let now = now();
+    for credential in embedded_credentials(presentation) {
+        verify_embedded_credential(credential, status_lists, now)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_datetime_roundtrip() {
+        assert_eq!(format_datetime(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_datetime(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_datetime(1_790_000_000), "2026-09-21T14:13:20Z");
+        for secs in [0, 951_782_400, 1_790_000_000, 4_102_444_799] {
+            assert_eq!(parse_datetime(&format_datetime(secs)).unwrap(), secs);
+        }
+        assert_eq!(parse_datetime("2026-09-21T16:13:20.123+02:00").unwrap(), 1_790_000_000);
+        assert!(parse_datetime("2026-09-21 14:13:20Z").is_err());
+        assert!(parse_datetime("2026-13-01T00:00:00Z").is_err());
+        assert!(parse_datetime("1969-12-31T23:59:59Z").is_err());
+    }
+}
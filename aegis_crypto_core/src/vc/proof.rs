@@ -0,0 +1,196 @@
+//! Data Integrity proofs with ML-DSA and SLH-DSA cryptosuites.
+//!
+//! The suites follow the JCS pattern of `eddsa-jcs-2022`:
+//!
+//! 1. The proof configuration (the proof without `proofValue`, carrying the
+//!    document's `@context`) and the unsecured document are canonicalized
+//!    with JCS.
+//! 2. The signed data is `SHA-256(config) || SHA-256(document)`.
+//! 3. `proofValue` is the signature as multibase base64url. PQ signatures
+//!    are too large for base58btc to be practical.
+
+use serde_json::{ Map, Value };
+use sha2::{ Digest, Sha256 };
+
+use super::{ format_datetime, jcs, DidSigner, VcError };
+use crate::did::{ self, VerificationMethod };
+use crate::multiformats::{ multibase_decode, multibase_encode, Multibase };
+use crate::registry::{ self, AlgorithmId };
+
+/// Why a proof was created; selects the DID verification relationship.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofPurpose {
+    /// Credentials and status lists (`assertionMethod`).
+    AssertionMethod,
+    /// Presentations (`authentication`).
+    Authentication,
+}
+
+impl ProofPurpose {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProofPurpose::AssertionMethod => "assertionMethod",
+            ProofPurpose::Authentication => "authentication",
+        }
+    }
+}
+
+/// Options for a new proof.
+#[derive(Debug, Clone)]
+pub struct ProofOptions {
+    pub purpose: ProofPurpose,
+    pub created: u64,
+    pub challenge: Option<String>,
+    pub domain: Option<String>,
+}
+
+const SUITES: [(AlgorithmId, &str); 9] = [
+    (AlgorithmId::MlDsa44, "mldsa44-jcs-2024"),
+    (AlgorithmId::MlDsa65, "mldsa65-jcs-2024"),
+    (AlgorithmId::MlDsa87, "mldsa87-jcs-2024"),
+    (AlgorithmId::SlhDsaSha2_128f, "slhdsa-sha2-128f-jcs-2024"),
+    (AlgorithmId::SlhDsaSha2_192f, "slhdsa-sha2-192f-jcs-2024"),
+    (AlgorithmId::SlhDsaSha2_256f, "slhdsa-sha2-256f-jcs-2024"),
+    (AlgorithmId::SlhDsaShake128f, "slhdsa-shake-128f-jcs-2024"),
+    (AlgorithmId::SlhDsaShake192f, "slhdsa-shake-192f-jcs-2024"),
+    (AlgorithmId::SlhDsaShake256f, "slhdsa-shake-256f-jcs-2024"),
+];
+
+/// The cryptosuite identifier for `algorithm`, if one is defined.
+pub fn cryptosuite(algorithm: AlgorithmId) -> Option<&'static str> {
+    SUITES.iter()
+        .find(|(alg, _)| *alg == algorithm)
+        .map(|(_, name)| *name)
+}
+
+pub fn cryptosuite_algorithm(name: &str) -> Option<AlgorithmId> {
+    SUITES.iter()
+        .find(|(_, suite)| *suite == name)
+        .map(|(alg, _)| *alg)
+}
+
+fn hash_data(proof_config: &Value, document: &Value) -> Vec<u8> {
+    let mut data = Sha256::digest(jcs::canonicalize(proof_config).as_bytes()).to_vec();
+    data.extend_from_slice(&Sha256::digest(jcs::canonicalize(document).as_bytes()));
+    data
+}
+
+fn object(value: &Value) -> Result<&Map<String, Value>, VcError> {
+    value.as_object().ok_or_else(|| VcError::Malformed("expected a JSON object".into()))
+}
+
+/// Attach a `DataIntegrityProof` to `document`. Any existing proof is
+/// replaced.
+pub fn add_proof(document: &Value, signer: &DidSigner, options: &ProofOptions) -> Result<Value, VcError> {
+    let suite = cryptosuite(signer.algorithm()).ok_or_else(||
+        VcError::UnsupportedCryptosuite(signer.algorithm().name().to_string())
+    )?;
+    let mut unsecured = object(document)?.clone();
+    unsecured.remove("proof");
+    let unsecured = Value::Object(unsecured);
+
+    let mut proof = Map::new();
+    proof.insert("type".into(), "DataIntegrityProof".into());
+    proof.insert("cryptosuite".into(), suite.into());
+    proof.insert("created".into(), format_datetime(options.created).into());
+    proof.insert("verificationMethod".into(), signer.did().verification_method_id().into());
+    proof.insert("proofPurpose".into(), options.purpose.as_str().into());
+    if let Some(challenge) = &options.challenge {
+        proof.insert("challenge".into(), challenge.clone().into());
+    }
+    if let Some(domain) = &options.domain {
+        proof.insert("domain".into(), domain.clone().into());
+    }
+
+    let mut config = proof.clone();
+    if let Some(context) = unsecured.get("@context") {
+        config.insert("@context".into(), context.clone());
+    }
+    let signature = registry::sign(
+        signer.algorithm(),
+        signer.secret_key(),
+        &hash_data(&Value::Object(config), &unsecured)
+    )?;
+    proof.insert("proofValue".into(), multibase_encode(Multibase::Base64Url, &signature).into());
+
+    let mut secured = object(&unsecured)?.clone();
+    secured.insert("proof".into(), Value::Object(proof));
+    Ok(Value::Object(secured))
+}
+
+/// Resolve a verification method and check that its DID authorizes it for
+/// `purpose`.
+pub(crate) fn resolve_key(
+    method_id: &str,
+    purpose: ProofPurpose
+) -> Result<(VerificationMethod, AlgorithmId, Vec<u8>), VcError> {
+    let method = did::resolve_verification_method(method_id)?;
+    let document = did::resolve(&method.controller)?;
+    let authorized = match purpose {
+        ProofPurpose::AssertionMethod => &document.assertion_method,
+        ProofPurpose::Authentication => &document.authentication,
+    };
+    if !authorized.iter().any(|id| id == method_id) {
+        return Err(VcError::InvalidProof(format!("key is not authorized for {}", purpose.as_str())));
+    }
+    let (algorithm, public_key) = method.public_key()?;
+    Ok((method, algorithm, public_key))
+}
+
+/// Verify the `DataIntegrityProof` on `document` and return the
+/// verification method that produced it.
+pub fn verify_proof(
+    document: &Value,
+    purpose: ProofPurpose,
+    challenge: Option<&str>,
+    domain: Option<&str>
+) -> Result<VerificationMethod, VcError> {
+    let mut unsecured = object(document)?.clone();
+    let proof = match unsecured.remove("proof") {
+        Some(Value::Object(proof)) => proof,
+        Some(_) => {
+            return Err(VcError::InvalidProof("multiple or malformed proofs".into()));
+        }
+        None => {
+            return Err(VcError::InvalidProof("missing proof".into()));
+        }
+    };
+    let field = |name: &str| proof.get(name).and_then(Value::as_str);
+    if field("type") != Some("DataIntegrityProof") {
+        return Err(VcError::InvalidProof("unsupported proof type".into()));
+    }
+    let suite = field("cryptosuite").unwrap_or_default();
+    let suite_algorithm = cryptosuite_algorithm(suite).ok_or_else(||
+        VcError::UnsupportedCryptosuite(suite.to_string())
+    )?;
+    if field("proofPurpose") != Some(purpose.as_str()) {
+        return Err(VcError::InvalidProof("unexpected proof purpose".into()));
+    }
+    if challenge.is_some() && field("challenge") != challenge {
+        return Err(VcError::InvalidProof("challenge mismatch".into()));
+    }
+    if domain.is_some() && field("domain") != domain {
+        return Err(VcError::InvalidProof("domain mismatch".into()));
+    }
+    let method_id = field("verificationMethod").ok_or_else(||
+        VcError::InvalidProof("missing verificationMethod".into())
+    )?;
+    let (method, algorithm, public_key) = resolve_key(method_id, purpose)?;
+    if algorithm != suite_algorithm {
+        return Err(VcError::InvalidProof("cryptosuite does not match the key algorithm".into()));
+    }
+    let (_, signature) = multibase_decode(field("proofValue").unwrap_or_default())?;
+
+    let mut config = proof.clone();
+    config.remove("proofValue");
+    match unsecured.get("@context") {
+        Some(context) => config.insert("@context".into(), context.clone()),
+        None => config.remove("@context"),
+    };
+    let unsecured = Value::Object(unsecured);
+    if registry::verify(algorithm, &public_key, &hash_data(&Value::Object(config), &unsecured), &signature) {
+        Ok(method)
+    } else {
+        Err(VcError::InvalidProof("bad signature".into()))
+    }
+}
@@ -0,0 +1,226 @@
+//! Bitstring Status List v1.0 revocation and suspension.
+//!
+//! A status list is a bitstring of at least 131,072 entries, GZIP
+//! compressed and published as multibase base64url inside a
+//! `BitstringStatusListCredential`. Index 0 is the most significant bit of
+//! the first byte.
+
+use base64::{ engine::general_purpose::URL_SAFE_NO_PAD, Engine as _ };
+use serde_json::{ json, Value };
+
+use super::{ Credential, VcError };
+
+/// Minimum number of entries in a status list (16 KiB uncompressed).
+pub const MIN_STATUS_LIST_LEN: usize = 131_072;
+
+const GZIP_HEADER: [u8; 10] = [0x1f, 0x8b, 0x08, 0, 0, 0, 0, 0, 0, 0xff];
+
+/// A decoded status bitstring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusList {
+    bits: Vec<u8>,
+}
+
+impl StatusList {
+    /// A list of `len` entries (rounded up to the minimum size), all clear.
+    pub fn new(len: usize) -> Self {
+        let len = len.max(MIN_STATUS_LIST_LEN);
+        StatusList { bits: vec![0u8; len.div_ceil(8)] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len() * 8
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.bits.get(index / 8).map(|byte| (byte >> (7 - (index % 8))) & 1 == 1)
+    }
+
+    pub fn set(&mut self, index: usize, value: bool) -> Result<(), VcError> {
+        let byte = self.bits
+            .get_mut(index / 8)
+            .ok_or_else(|| VcError::Status(format!("index {} out of range", index)))?;
+        let mask = 0x80u8 >> (index % 8);
+        if value {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+        Ok(())
+    }
+
+    /// The `encodedList` value: multibase base64url of the GZIP stream.
+    pub fn encode(&self) -> String {
+        let mut gzip = GZIP_HEADER.to_vec();
+        gzip.extend_from_slice(&miniz_oxide::deflate::compress_to_vec(&self.bits, 9));
+        gzip.extend_from_slice(&crc32fast::hash(&self.bits).to_le_bytes());
+        gzip.extend_from_slice(&(self.bits.len() as u32).to_le_bytes());
+        format!("u{}", URL_SAFE_NO_PAD.encode(gzip))
+    }
+
+    pub fn decode(encoded: &str) -> Result<Self, VcError> {
+        let invalid = |msg: &str| VcError::Status(format!("invalid encodedList: {}", msg));
+        let body = encoded.strip_prefix('u').ok_or_else(|| invalid("expected base64url multibase"))?;
+        let gzip = URL_SAFE_NO_PAD.decode(body).map_err(|_| invalid("bad base64url"))?;
+        if gzip.len() < 18 || gzip[..3] != GZIP_HEADER[..3] {
+            return Err(invalid("not a GZIP stream"));
+        }
+        let flags = gzip[3];
+        if flags & !0x01 != 0 {
+            // FTEXT is harmless; optional header fields are not expected here.
+            return Err(invalid("unsupported GZIP header flags"));
+        }
+        let (deflated, trailer) = gzip[10..].split_at(gzip.len() - 18);
+        let bits = miniz_oxide::inflate
+            ::decompress_to_vec_with_limit(deflated, MIN_STATUS_LIST_LEN * 64)
+            .map_err(|_| invalid("bad DEFLATE data"))?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != crc32fast::hash(&bits) || size != (bits.len() as u32) {
+            return Err(invalid("GZIP checksum mismatch"));
+        }
+        if bits.len() * 8 < MIN_STATUS_LIST_LEN {
+            return Err(invalid("list is shorter than the minimum length"));
+        }
+        Ok(StatusList { bits })
+    }
+}
+
+/// A `BitstringStatusListEntry` pointing into a status list credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    pub status_purpose: String,
+    pub status_list_index: usize,
+    pub status_list_credential: String,
+}
+
+impl StatusEntry {
+    pub fn revocation(status_list_credential: &str, index: usize) -> Self {
+        StatusEntry {
+            status_purpose: "revocation".into(),
+            status_list_index: index,
+            status_list_credential: status_list_credential.into(),
+        }
+    }
+
+    pub fn to_value(&self) -> Value {
+        json!({
+            "id": format!("{}#{}", self.status_list_credential, self.status_list_index),
+            "type": "BitstringStatusListEntry",
+            "statusPurpose": self.status_purpose,
+            "statusListIndex": self.status_list_index.to_string(),
+            "statusListCredential": self.status_list_credential,
+        })
+    }
+
+    fn from_value(value: &Value) -> Result<Option<Self>, VcError> {
+        if value.get("type").and_then(Value::as_str) != Some("BitstringStatusListEntry") {
+            return Ok(None);
+        }
+        let field = |name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_str)
+                .ok_or_else(|| VcError::Status(format!("status entry is missing {}", name)))
+        };
+        Ok(
+            Some(StatusEntry {
+                status_purpose: field("statusPurpose")?.to_string(),
+                status_list_index: field("statusListIndex")?
+                    .parse()
+                    .map_err(|_| VcError::Status("bad statusListIndex".into()))?,
+                status_list_credential: field("statusListCredential")?.to_string(),
+            })
+        )
+    }
+}
+
+/// An unsigned `BitstringStatusListCredential` for `list`. Sign it with
+/// [`issue_credential`](super::issue_credential) before publishing.
+pub fn status_list_credential(id: &str, issuer: &str, status_purpose: &str, list: &StatusList) -> Credential {
+    let mut credential = Credential::new(
+        issuer,
+        json!({
+            "id": format!("{}#list", id),
+            "type": "BitstringStatusList",
+            "statusPurpose": status_purpose,
+            "encodedList": list.encode(),
+        })
+    );
+    credential.id = Some(id.to_string());
+    credential.types.push("BitstringStatusListCredential".into());
+    credential
+}
+
+/// Check every `BitstringStatusListEntry` of `credential` against the
+/// locally available, signed status list credentials.
+pub fn check_status(credential: &Value, status_lists: &[Value], now: u64) -> Result<(), VcError> {
+    let entries = match credential.get("credentialStatus") {
+        None => {
+            return Ok(());
+        }
+        Some(Value::Array(items)) => items.clone(),
+        Some(item) => vec![item.clone()],
+    };
+    for entry in entries.iter().filter_map(|e| StatusEntry::from_value(e).transpose()) {
+        let entry = entry?;
+        let list_credential = status_lists
+            .iter()
+            .find(|list| list.get("id").and_then(Value::as_str) == Some(&entry.status_list_credential))
+            .ok_or_else(|| VcError::Status(format!("status list {} not available", entry.status_list_credential)))?;
+        let list_issuer = super::verify_issued(list_credential, now)?;
+        if Some(list_issuer.as_str()) != super::issuer_id(credential) {
+            return Err(VcError::Status("status list was not issued by the credential issuer".into()));
+        }
+
+        let subject = list_credential
+            .get("credentialSubject")
+            .ok_or_else(|| VcError::Status("status list has no subject".into()))?;
+        if subject.get("statusPurpose").and_then(Value::as_str) != Some(entry.status_purpose.as_str()) {
+            return Err(VcError::Status("status purpose mismatch".into()));
+        }
+        let encoded = subject
+            .get("encodedList")
+            .and_then(Value::as_str)
+            .ok_or_else(|| VcError::Status("status list has no encodedList".into()))?;
+        let list = StatusList::decode(encoded)?;
+        match list.get(entry.status_list_index) {
+            Some(false) => {}
+            Some(true) => {
+                return Err(VcError::StatusSet(entry.status_purpose));
+            }
+            None => {
+                return Err(VcError::Status("status index out of range".into()));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_list_encoding() {
+        let mut list = StatusList::new(10);
+        assert_eq!(list.len(), MIN_STATUS_LIST_LEN);
+        list.set(0, true).unwrap();
+        list.set(94_567, true).unwrap();
+        assert!(list.set(MIN_STATUS_LIST_LEN, true).is_err());
+
+        let encoded = list.encode();
+        // A mostly empty list compresses to well under a kilobyte.
+        assert!(encoded.len() < 1000);
+        let decoded = StatusList::decode(&encoded).unwrap();
+        assert_eq!(decoded, list);
+        assert_eq!(decoded.get(0), Some(true));
+        assert_eq!(decoded.get(1), Some(false));
+        assert_eq!(decoded.get(94_567), Some(true));
+        assert_eq!(decoded.bits[0], 0x80);
+    }
+}
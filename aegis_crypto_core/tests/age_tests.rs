@@ -0,0 +1,128 @@
+//! Tests for age v1 file encryption, including interoperability with the
+//! reference `age` crate.
+
+use std::io::{ Read, Write };
+
+use age::secrecy::ExposeSecret;
+use aegis_crypto_core::age::{
+    self as aegis_age,
+    AgeError,
+    DecryptReader,
+    EncryptWriter,
+    HybridIdentity,
+    HybridRecipient,
+    MlKemIdentity,
+    MlKemRecipient,
+    X25519Identity,
+    X25519Recipient,
+};
+
+#[test]
+fn test_reference_age_decrypts_our_files() {
+    let reference = age::x25519::Identity::generate();
+    let recipient: X25519Recipient = reference.to_public().to_string().parse().unwrap();
+    let pq = MlKemIdentity::generate().unwrap();
+
+    let plaintext: Vec<u8> = (0..150_000u32).map(|i| (i % 253) as u8).collect();
+    let file = aegis_age::encrypt(&[&pq.to_public(), &recipient], &plaintext).unwrap();
+    // The reference implementation skips the unknown ML-KEM stanza.
+    assert_eq!(age::decrypt(&reference, &file).unwrap(), plaintext);
+    assert_eq!(aegis_age::decrypt(&[&pq], &file).unwrap(), plaintext);
+}
+
+#[test]
+fn test_we_decrypt_reference_age_files() {
+    let reference = age::x25519::Identity::generate();
+    let identity: X25519Identity = reference.to_string().expose_secret().parse().unwrap();
+    assert_eq!(identity.to_public().to_string(), reference.to_public().to_string());
+
+    for len in [0usize, 1, 64 * 1024, 64 * 1024 + 1] {
+        let plaintext = vec![0x5au8; len];
+        let file = age::encrypt(&reference.to_public(), &plaintext).unwrap();
+        assert_eq!(aegis_age::decrypt(&[&identity], &file).unwrap(), plaintext);
+    }
+}
+
+#[test]
+fn test_post_quantum_recipients_round_trip() {
+    let mlkem = MlKemIdentity::generate().unwrap();
+    let hybrid = HybridIdentity::generate().unwrap();
+    let mlkem_recipient: MlKemRecipient = mlkem.to_public().to_string().parse().unwrap();
+    let hybrid_recipient: HybridRecipient = hybrid.to_public().to_string().parse().unwrap();
+    assert!(mlkem_recipient.to_string().starts_with("age1aegismlkem1"));
+    assert!(hybrid_recipient.to_string().starts_with("age1aegishybrid1"));
+
+    let file = aegis_age::encrypt(&[&mlkem_recipient, &hybrid_recipient], b"quantum-safe").unwrap();
+    assert_eq!(aegis_age::decrypt(&[&mlkem], &file).unwrap(), b"quantum-safe");
+    assert_eq!(aegis_age::decrypt(&[&hybrid], &file).unwrap(), b"quantum-safe");
+}
+
+#[test]
+fn test_identity_file_and_recipient_parsing() {
+    let x25519 = X25519Identity::generate();
+    let hybrid = HybridIdentity::generate().unwrap();
+    let contents = format!(
+        "# created: today\n# public key: {}\n{}\n\n{}\n",
+        x25519.to_public(),
+        x25519,
+        hybrid
+    );
+    let identities = aegis_age::parse_identity_file(&contents).unwrap();
+    assert_eq!(identities.len(), 2);
+    let identities: Vec<&dyn aegis_age::Identity> = identities.iter().map(|i| i.as_ref()).collect();
+
+    let recipient = aegis_age::parse_recipient(&hybrid.to_public().to_string()).unwrap();
+    let file = aegis_age::encrypt(&[recipient.as_ref()], b"from a file").unwrap();
+    assert_eq!(aegis_age::decrypt(&identities, &file).unwrap(), b"from a file");
+
+    assert!(aegis_age::parse_identity_file("# nothing here\n").is_err());
+    assert!(aegis_age::parse_identity_file("AGE-PLUGIN-OTHER-1QQQQ\n").is_err());
+    assert!(aegis_age::parse_recipient("age1notarecipient").is_err());
+}
+
+#[test]
+fn test_io_adapters_round_trip() {
+    let identity = HybridIdentity::generate().unwrap();
+    let plaintext: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+    let mut writer = EncryptWriter::new(Vec::new(), &[&identity.to_public()]).unwrap();
+    for piece in plaintext.chunks(10_000) {
+        writer.write_all(piece).unwrap();
+    }
+    let file = writer.finish().unwrap();
+
+    let mut reader = DecryptReader::new(file.as_slice(), &[&identity]).unwrap();
+    let mut decrypted = Vec::new();
+    reader.read_to_end(&mut decrypted).unwrap();
+    assert_eq!(decrypted, plaintext);
+}
+
+#[test]
+fn test_tampering_is_detected() {
+    let identity = MlKemIdentity::generate().unwrap();
+    let file = aegis_age::encrypt(&[&identity.to_public()], b"integrity").unwrap();
+    let mac_line = file.windows(4).position(|w| w == b"--- ").unwrap();
+
+    let mut bad_header = file.clone();
+    bad_header[mac_line - 2] ^= 0x01;
+    assert!(aegis_age::decrypt(&[&identity], &bad_header).is_err());
+
+    let mut bad_mac = file.clone();
+    let mac_byte = mac_line + 5;
+    bad_mac[mac_byte] = if bad_mac[mac_byte] == b'A' { b'B' } else { b'A' };
+    assert_eq!(aegis_age::decrypt(&[&identity], &bad_mac), Err(AgeError::HeaderMac));
+
+    let mut bad_payload = file.clone();
+    *bad_payload.last_mut().unwrap() ^= 0x01;
+    assert!(matches!(aegis_age::decrypt(&[&identity], &bad_payload), Err(AgeError::Stream(_))));
+
+    let truncated = &file[..file.len() - 20];
+    assert!(aegis_age::decrypt(&[&identity], truncated).is_err());
+}
+
+#[test]
+fn test_rejects_unsupported_version() {
+    let file = b"age-encryption.org/v2\n-> X25519 abc\n\n--- AAAA\n";
+    assert!(matches!(aegis_age::decrypt(&[], file), Err(AgeError::UnsupportedVersion(_))));
+    assert!(aegis_age::encrypt(&[], b"x").is_err());
+}
@@ -0,0 +1,142 @@
+//! Tests for the hash-chained audit log, its checkpoints and proofs.
+
+use aegis_crypto_core::audit::{ verify, AuditError, AuditLog, AuditProof, CheckpointKey };
+use aegis_crypto_core::keystore::{ KeyOptions, MemoryKeyStore };
+use aegis_crypto_core::kms::{ EncryptionContext, Kms, LocalKms };
+use aegis_crypto_core::registry::AlgorithmId;
+
+fn temp_file(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("aegis-audit-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn key_pair() -> (Vec<u8>, Vec<u8>) {
+    aegis_crypto_core::registry::keygen(AlgorithmId::MlDsa44).unwrap()
+}
+
+/// Size of a record entry holding a 4-byte event.
+const ENTRY: usize = 5 + 48 + 4;
+
+/// Offset of record `i` in a log written without intermediate checkpoints.
+fn record_at(i: usize) -> usize {
+    5 + i * ENTRY
+}
+
+#[test]
+fn test_file_log_persists_and_checkpoints() {
+    let path = temp_file("persist");
+    let (pk, sk) = key_pair();
+    let key = || CheckpointKey::new(AlgorithmId::MlDsa44, &pk, &sk).unwrap();
+
+    let mut log = AuditLog::create(&path, key(), 3).unwrap();
+    for i in 0..4 {
+        assert_eq!(log.append(format!("login {}", i).as_bytes()).unwrap(), i);
+    }
+    assert_eq!(log.checkpoints().len(), 1);
+    assert_eq!(log.checkpoints()[0].size, 3);
+    assert!(AuditLog::create(&path, key(), 3).is_err());
+    drop(log);
+
+    let mut log = AuditLog::open(&path, key(), 3).unwrap();
+    assert_eq!(log.len(), 4);
+    log.append(b"logout").unwrap();
+    log.append(b"login 5").unwrap();
+    let data = std::fs::read(&path).unwrap();
+    assert_eq!(data, log.to_bytes());
+
+    let verified = verify(&data, &pk, Some(&log.checkpoints()[0])).unwrap();
+    assert_eq!(verified.records.len(), 6);
+    assert_eq!(verified.checkpoints.len(), 2);
+    assert!(verified.unsigned_records().is_empty());
+    assert_eq!(verified.records[4].event, b"logout");
+
+    let (other_pk, _) = key_pair();
+    assert_eq!(verify(&data, &other_pk, None), Err(AuditError::BadSignature { size: 3 }));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_tampering_is_detected() {
+    let (pk, sk) = key_pair();
+    let mut log = AuditLog::new(CheckpointKey::new(AlgorithmId::MlDsa44, &pk, &sk).unwrap(), 0);
+    for i in 0..5 {
+        log.append(format!("ev{:02}", i).as_bytes()).unwrap();
+    }
+    let trusted = log.checkpoint().unwrap();
+    let data = log.to_bytes();
+    assert!(verify(&data, &pk, Some(&trusted)).is_ok());
+
+    // Modification: change the event of record 2.
+    let mut modified = data.clone();
+    modified[record_at(2) + 5 + 48] ^= 1;
+    assert_eq!(verify(&modified, &pk, None), Err(AuditError::ChainBroken { sequence: 3 }));
+
+    // Modifying the last record is caught by the checkpoint.
+    let mut modified = data.clone();
+    modified[record_at(4) + 5 + 48] ^= 1;
+    assert_eq!(verify(&modified, &pk, None), Err(AuditError::CheckpointMismatch { size: 5 }));
+
+    // Insertion: repeat record 1.
+    let mut inserted = data[..record_at(2)].to_vec();
+    inserted.extend_from_slice(&data[record_at(1)..]);
+    assert_eq!(verify(&inserted, &pk, None), Err(AuditError::ChainBroken { sequence: 2 }));
+
+    // Deletion: drop record 1.
+    let mut deleted = data[..record_at(1)].to_vec();
+    deleted.extend_from_slice(&data[record_at(2)..]);
+    assert_eq!(verify(&deleted, &pk, None), Err(AuditError::ChainBroken { sequence: 1 }));
+
+    // Truncation: a clean cut is only visible against a trusted checkpoint.
+    let truncated = &data[..record_at(3)];
+    assert!(verify(truncated, &pk, None).is_ok());
+    assert_eq!(verify(truncated, &pk, Some(&trusted)), Err(AuditError::Truncated { expected: 5, found: 3 }));
+    assert_eq!(verify(&data[..record_at(3) - 1], &pk, None), Err(AuditError::Truncated { expected: 3, found: 2 }));
+}
+
+#[test]
+fn test_proofs_for_auditors() {
+    let (pk, sk) = key_pair();
+    let mut log = AuditLog::new(CheckpointKey::new(AlgorithmId::MlDsa44, &pk, &sk).unwrap(), 4);
+    for i in 0..6 {
+        log.append(format!("ev{:02}", i).as_bytes()).unwrap();
+    }
+    assert_eq!(log.proof(4, 6), Err(AuditError::NotCheckpointed));
+
+    let proof = log.proof(1, 3).unwrap();
+    assert_eq!(proof.records.len(), 3);
+    assert_eq!(proof.checkpoint.size, 4);
+    let proof = AuditProof::from_bytes(&proof.to_bytes()).unwrap();
+    assert_eq!(proof.verify(&pk), Ok(()));
+
+    let mut forged = proof.clone();
+    forged.records[0].event = b"ev99".to_vec();
+    assert_eq!(forged.verify(&pk), Err(AuditError::ChainBroken { sequence: 2 }));
+    let mut forged = proof.clone();
+    forged.records.pop();
+    assert_eq!(forged.verify(&pk), Err(AuditError::CheckpointMismatch { size: 4 }));
+    let (other_pk, _) = key_pair();
+    assert_eq!(proof.verify(&other_pk), Err(AuditError::BadSignature { size: 4 }));
+
+    log.checkpoint().unwrap();
+    assert_eq!(log.proof(4, 6).unwrap().verify(&pk), Ok(()));
+}
+
+#[test]
+fn test_kms_calls_are_logged() {
+    let key = CheckpointKey::generate(AlgorithmId::MlDsa44).unwrap();
+    let pk = key.public_key().to_vec();
+    let mut kms = LocalKms::with_audit(MemoryKeyStore::new(), AuditLog::new(key, 0));
+    let id = kms.create_key(AlgorithmId::MlKem768, KeyOptions::new()).unwrap().id;
+    let ciphertext = kms.encrypt(&id, b"secret", &EncryptionContext::new()).unwrap();
+    kms.decrypt(&ciphertext, &EncryptionContext::new()).unwrap();
+
+    let (_, mut log) = kms.into_parts();
+    log.checkpoint().unwrap();
+    let verified = verify(&log.to_bytes(), &pk, None).unwrap();
+    let events: Vec<_> = verified.records.iter().map(|r| String::from_utf8(r.event.clone()).unwrap()).collect();
+    assert_eq!(events.len(), 3);
+    assert!(events[0].starts_with("CreateKey keys="));
+    assert!(events[2].starts_with(&format!("Decrypt keys={} ", id)));
+    assert!(events[2].ends_with("result=ok"));
+}
@@ -0,0 +1,147 @@
+//! Compiles the generated Solidity with solc and runs it in revm.
+//!
+//! Ignored by default because they need solc; CI installs it and runs them
+//! with `cargo test --test evm_solc_tests -- --ignored`. They use the
+//! compiler named by `SOLC`, or the one on `PATH`, and fail without either.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+use aegis_crypto_core::evm::abi::{ self, encode_verify_call, Token };
+use aegis_crypto_core::evm::erc4337::{ UserOperation, ENTRY_POINT_V07 };
+use aegis_crypto_core::evm::{ parse_address, solidity };
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+use revm::primitives::{ Address, Bytes, ExecutionResult, Output, TxKind };
+use revm::{ Evm, InMemoryDB };
+
+const VALIDATE_USER_OP: &str =
+    "validateUserOp((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes),bytes32,uint256)";
+
+/// The compiler to use.
+fn solc() -> PathBuf {
+    let path: PathBuf = std::env::var_os("SOLC").unwrap_or_else(|| "solc".into()).into();
+    let found = Command::new(&path).arg("--version").output().is_ok_and(|output| output.status.success());
+    assert!(found, "{} not found; set SOLC or put solc on PATH", path.display());
+    path
+}
+
+/// Creation bytecode of `contract`, compiled from both generated sources.
+fn compile(solc: &PathBuf, contract: &str) -> Vec<u8> {
+    let dir = std::env::temp_dir().join(format!("aegis-solc-{}-{}", std::process::id(), contract));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("Falcon512.sol"), solidity::falcon512_verifier()).unwrap();
+    std::fs::write(dir.join("Falcon512Account.sol"), solidity::falcon512_account()).unwrap();
+    let output = Command::new(solc)
+        .current_dir(&dir)
+        .args(["--optimize", "--via-ir", "--combined-json", "bin", "Falcon512.sol", "Falcon512Account.sol"])
+        .output()
+        .expect("failed to run solc");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success(), "solc failed:\n{}", String::from_utf8_lossy(&output.stderr));
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let (_, artifact) = json["contracts"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .find(|(name, _)| name.ends_with(&format!(":{}", contract)))
+        .unwrap_or_else(|| panic!("{} missing from solc output", contract));
+    hex::decode(artifact["bin"].as_str().unwrap()).unwrap()
+}
+
+fn transact(evm: &mut Evm<'_, (), InMemoryDB>, caller: Address, to: TxKind, data: Vec<u8>) -> ExecutionResult {
+    let tx = evm.tx_mut();
+    tx.caller = caller;
+    tx.transact_to = to;
+    tx.data = Bytes::from(data);
+    tx.gas_limit = 1_000_000_000;
+    evm.transact_commit().unwrap()
+}
+
+fn deploy(evm: &mut Evm<'_, (), InMemoryDB>, code: Vec<u8>) -> Address {
+    match transact(evm, Address::repeat_byte(0x01), TxKind::Create, code) {
+        ExecutionResult::Success { output: Output::Create(_, Some(address)), .. } => address,
+        other => panic!("deployment failed: {:?}", other),
+    }
+}
+
+fn call(evm: &mut Evm<'_, (), InMemoryDB>, caller: Address, to: Address, data: Vec<u8>) -> Vec<u8> {
+    match transact(evm, caller, TxKind::Call(to), data) {
+        ExecutionResult::Success { output: Output::Call(bytes), .. } => bytes.to_vec(),
+        other => panic!("call failed: {:?}", other),
+    }
+}
+
+#[test]
+#[ignore = "requires solc"]
+fn test_verifier_contract() {
+    let solc = solc();
+    let mut evm = Evm::builder().with_db(InMemoryDB::default()).build();
+    let verifier = deploy(&mut evm, compile(&solc, "Falcon512Verifier"));
+
+    let (pk, sk) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let message = b"verified on chain";
+    let signature = registry::sign(AlgorithmId::Falcon512, &sk, message).unwrap();
+    let caller = Address::repeat_byte(0x02);
+    let result = call(&mut evm, caller, verifier, encode_verify_call(&pk, message, &signature));
+    assert_eq!(result, abi::uint(1));
+
+    let mut tampered = signature.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 0x01;
+    let result = call(&mut evm, caller, verifier, encode_verify_call(&pk, message, &tampered));
+    assert_eq!(result, abi::uint(0));
+    let result = call(&mut evm, caller, verifier, encode_verify_call(&pk, b"another message", &signature));
+    assert_eq!(result, abi::uint(0));
+}
+
+#[test]
+#[ignore = "requires solc"]
+fn test_account_validates_user_operations() {
+    let solc = solc();
+    let entry_point = parse_address(ENTRY_POINT_V07).unwrap();
+    let (pk, sk) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let mut evm = Evm::builder().with_db(InMemoryDB::default()).build();
+    let mut code = compile(&solc, "Falcon512Account");
+    code.extend_from_slice(&abi::encode(&[Token::Word(abi::address(&entry_point)), Token::Bytes(&pk)]));
+    let account = deploy(&mut evm, code);
+
+    let mut op = UserOperation {
+        sender: account.into_array(),
+        nonce: abi::uint(0),
+        call_data: vec![0xb6, 0x1d, 0x27, 0xf6],
+        verification_gas_limit: 6_000_000,
+        call_gas_limit: 100_000,
+        ..Default::default()
+    };
+    // The EVM's default chain id is 1.
+    op.sign(&entry_point, 1, AlgorithmId::Falcon512, &sk).unwrap();
+    let validate = |op: &UserOperation| {
+        let mut calldata = abi::selector(VALIDATE_USER_OP).to_vec();
+        calldata.extend_from_slice(&abi::uint(0x60));
+        calldata.extend_from_slice(&op.hash(&entry_point, 1));
+        calldata.extend_from_slice(&abi::uint(0));
+        calldata.extend_from_slice(&abi::encode(&[
+            Token::Word(abi::address(&op.sender)),
+            Token::Word(op.nonce),
+            Token::Bytes(&op.init_code),
+            Token::Bytes(&op.call_data),
+            Token::Word(op.account_gas_limits()),
+            Token::Word(abi::uint(op.pre_verification_gas)),
+            Token::Word(op.gas_fees()),
+            Token::Bytes(&op.paymaster_and_data),
+            Token::Bytes(&op.signature),
+        ]));
+        calldata
+    };
+    let caller = Address::from(entry_point);
+    assert_eq!(call(&mut evm, caller, account, validate(&op)), abi::uint(0));
+
+    let mut tampered = op.clone();
+    tampered.signature[10] ^= 0x01;
+    assert_eq!(call(&mut evm, caller, account, validate(&tampered)), abi::uint(1));
+
+    // Only the EntryPoint may call it.
+    let result = transact(&mut evm, Address::repeat_byte(0x02), TxKind::Call(account), validate(&op));
+    assert!(matches!(result, ExecutionResult::Revert { .. }), "{:?}", result);
+}
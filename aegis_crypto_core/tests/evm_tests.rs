@@ -0,0 +1,123 @@
+//! Tests for the EVM calldata encoding, Falcon-512 reference verifier,
+//! generated Solidity and ERC-4337 signing.
+
+use aegis_crypto_core::evm::abi::{ self, decode_verify_call, encode_verify_call, selector };
+use aegis_crypto_core::evm::erc4337::{ UserOperation, ENTRY_POINT_V07 };
+use aegis_crypto_core::evm::gas::estimate_verify_gas;
+use aegis_crypto_core::evm::{ falcon, parse_address, solidity };
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+use sha3::digest::{ ExtendableOutput, Update, XofReader };
+
+#[test]
+fn test_falcon_reference_matches_implementation() {
+    for len in [0usize, 1, 135, 136, 137, 300] {
+        let input: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        let mut shake = sha3::Shake256::default();
+        shake.update(&input);
+        let mut expected = vec![0u8; 500];
+        shake.finalize_xof().read(&mut expected);
+        assert_eq!(falcon::shake256(&input, 500), expected, "input length {}", len);
+    }
+
+    let (pk, sk) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    for i in 0..10 {
+        let message = vec![i as u8; i * 29];
+        let signature = registry::sign(AlgorithmId::Falcon512, &sk, &message).unwrap();
+        assert!(falcon::verify(&pk, &message, &signature), "signature {}", i);
+        assert!(!falcon::verify(&pk, b"another message", &signature));
+
+        let mut tampered = signature.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0x01;
+        assert!(!falcon::verify(&pk, &message, &tampered));
+    }
+    let (other_pk, _) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let signature = registry::sign(AlgorithmId::Falcon512, &sk, b"message").unwrap();
+    assert!(!falcon::verify(&other_pk, b"message", &signature));
+    assert!(!falcon::verify(&pk[..896], b"message", &signature));
+}
+
+#[test]
+fn test_verify_calldata_layout() {
+    assert_eq!(hex::encode(selector("transfer(address,uint256)")), "a9059cbb");
+
+    let (pk, sk) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let signature = registry::sign(AlgorithmId::Falcon512, &sk, b"hello").unwrap();
+    let calldata = encode_verify_call(&pk, b"hello", &signature);
+    assert_eq!(calldata[..4], selector(abi::VERIFY_SIGNATURE));
+    assert_eq!((calldata.len() - 4) % 32, 0);
+    let args = &calldata[4..];
+    assert_eq!(args[..32], abi::uint(0x60));
+    assert_eq!(args[0x60..0x80], abi::uint(897));
+    assert_eq!(&args[0x80..0x80 + 897], &pk[..]);
+
+    let (decoded_pk, message, decoded_signature) = decode_verify_call(&calldata).unwrap();
+    assert_eq!((decoded_pk, message, decoded_signature), (pk, b"hello".to_vec(), signature));
+
+    let mut bad_padding = calldata.clone();
+    let end = bad_padding.len() - 1;
+    bad_padding[end] = 1;
+    assert!(decode_verify_call(&bad_padding).is_err());
+    assert!(decode_verify_call(&calldata[..calldata.len() - 32]).is_err());
+}
+
+#[test]
+fn test_gas_estimates() {
+    let falcon = estimate_verify_gas(AlgorithmId::Falcon512, 32).unwrap();
+    let longer = estimate_verify_gas(AlgorithmId::Falcon512, 1024).unwrap();
+    assert!(falcon.total().unwrap() < longer.total().unwrap());
+
+    let ml_dsa = estimate_verify_gas(AlgorithmId::MlDsa65, 32).unwrap();
+    assert!(ml_dsa.execution.is_none());
+    assert!(ml_dsa.calldata > falcon.calldata);
+    assert!(estimate_verify_gas(AlgorithmId::MlKem768, 32).is_err());
+}
+
+#[test]
+fn test_user_operation_signing() {
+    let entry_point = parse_address(ENTRY_POINT_V07).unwrap();
+    let (pk, sk) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let mut op = UserOperation {
+        sender: [0x11; 20],
+        nonce: abi::uint(7),
+        call_data: vec![0xb6, 0x1d, 0x27, 0xf6],
+        verification_gas_limit: 6_000_000,
+        call_gas_limit: 100_000,
+        pre_verification_gas: 50_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        max_fee_per_gas: 30_000_000_000,
+        ..Default::default()
+    };
+    let hash = op.hash(&entry_point, 1);
+    op.sign(&entry_point, 1, AlgorithmId::Falcon512, &sk).unwrap();
+    assert_eq!(op.hash(&entry_point, 1), hash);
+    assert!(op.verify(&entry_point, 1, AlgorithmId::Falcon512, &pk));
+    // What the generated account contract checks.
+    assert!(falcon::verify(&pk, &hash, &op.signature));
+
+    assert!(!op.verify(&entry_point, 10, AlgorithmId::Falcon512, &pk));
+    assert!(!op.verify(&[0u8; 20], 1, AlgorithmId::Falcon512, &pk));
+    let mut changed = op.clone();
+    changed.call_gas_limit += 1;
+    assert!(!changed.verify(&entry_point, 1, AlgorithmId::Falcon512, &pk));
+
+    let (ml_dsa_pk, ml_dsa_sk) = registry::keygen(AlgorithmId::MlDsa44).unwrap();
+    op.sign(&entry_point, 1, AlgorithmId::MlDsa44, &ml_dsa_sk).unwrap();
+    assert!(op.verify(&entry_point, 1, AlgorithmId::MlDsa44, &ml_dsa_pk));
+}
+
+#[test]
+fn test_generated_solidity_embeds_reference_tables() {
+    let source = solidity::falcon512_verifier();
+    assert!(source.contains("library Falcon512"));
+    assert!(source.contains("function verify(bytes calldata publicKey, bytes calldata message, bytes calldata signature)"));
+    assert!(source.contains(&format!("N_INVERSE = {};", falcon::n_inverse())));
+    let zetas: String = falcon::zetas().iter().map(|z| format!("{:04x}", z)).collect();
+    assert!(source.contains(&format!("hex\"{}\"", zetas)));
+    assert!(source.contains("hex\"00000000000000010000000000008082"));
+    assert!(!source.contains("{{"));
+
+    let account = solidity::falcon512_account();
+    assert!(account.contains("import \"./Falcon512.sol\";"));
+    assert!(account.contains("function validateUserOp(PackedUserOperation calldata userOp"));
+}
@@ -0,0 +1,120 @@
+//! Tests for the post-quantum authenticated key-exchange handshake.
+
+use aegis_crypto_core::handshake::{
+    loopback,
+    Handshake,
+    HandshakeConfig,
+    HandshakeError,
+    Identity,
+    Role,
+};
+use aegis_crypto_core::registry::AlgorithmId;
+
+fn config(algorithm: AlgorithmId) -> HandshakeConfig {
+    HandshakeConfig::new(Identity::generate(algorithm).unwrap())
+}
+
+#[test]
+fn test_manual_sans_io_exchange() {
+    let mut initiator = Handshake::initiator(config(AlgorithmId::MlDsa44));
+    let mut responder = Handshake::responder(config(AlgorithmId::MlDsa87));
+    assert!(initiator.wants_write());
+    assert!(!responder.wants_write());
+    assert_eq!(responder.write_message(), Err(HandshakeError::UnexpectedMessage));
+
+    // The rejected call above is fatal for the responder; start a new one.
+    let mut responder = Handshake::responder(config(AlgorithmId::MlDsa87));
+    let hello = initiator.write_message().unwrap();
+    responder.read_message(&hello).unwrap();
+    let response = responder.write_message().unwrap();
+    initiator.read_message(&response).unwrap();
+    let auth = initiator.write_message().unwrap();
+    assert!(initiator.is_complete());
+    assert!(!responder.is_complete());
+    responder.read_message(&auth).unwrap();
+
+    let mut client = initiator.into_session().unwrap();
+    let mut server = responder.into_session().unwrap();
+    assert_eq!(client.role(), Role::Initiator);
+    assert_eq!(server.peer().algorithm, AlgorithmId::MlDsa44);
+    assert_eq!(client.peer().algorithm, AlgorithmId::MlDsa87);
+
+    for i in 0..3u8 {
+        let record = server.seal(&[i; 100]).unwrap();
+        assert_eq!(client.open(&record).unwrap(), vec![i; 100]);
+    }
+    // Records are bound to their sequence number, so a dropped record
+    // makes the next one fail.
+    client.seal(b"dropped").unwrap();
+    let record = client.seal(b"two").unwrap();
+    assert_eq!(server.open(&record), Err(HandshakeError::DecryptionFailed));
+}
+
+#[test]
+fn test_expected_peer_is_enforced() {
+    let server_identity = Identity::generate(AlgorithmId::MlDsa65).unwrap();
+    let impostor = Identity::generate(AlgorithmId::MlDsa65).unwrap();
+
+    let mut pinned = config(AlgorithmId::MlDsa65);
+    pinned.expected_peer = Some(server_identity.to_peer());
+    assert!(loopback(pinned, HandshakeConfig::new(server_identity)).is_ok());
+
+    let mut pinned = config(AlgorithmId::MlDsa65);
+    pinned.expected_peer = Some(Identity::generate(AlgorithmId::MlDsa65).unwrap().to_peer());
+    assert_eq!(loopback(pinned, HandshakeConfig::new(impostor)).err(), Some(HandshakeError::UnknownPeer));
+}
+
+#[test]
+fn test_tampering_is_detected() {
+    let run = |flip: usize, message_index: usize| -> Result<(), HandshakeError> {
+        let mut initiator = Handshake::initiator(config(AlgorithmId::MlDsa44));
+        let mut responder = Handshake::responder(config(AlgorithmId::MlDsa44));
+        let mut messages = 0;
+        loop {
+            let (from, to) = if initiator.wants_write() {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let mut message = from.write_message().unwrap();
+            if messages == message_index {
+                let index = flip % message.len();
+                message[index] ^= 0x01;
+            }
+            messages += 1;
+            to.read_message(&message)?;
+            if initiator.is_complete() && responder.is_complete() {
+                return Ok(());
+            }
+        }
+    };
+    // Flip a bit in the version byte, the KEM public key, the response
+    // nonce and the encrypted authentication of each side.
+    assert!(run(0, 0).is_err());
+    assert!(run(100, 0).is_err());
+    assert!(run(5, 1).is_err());
+    assert!(run(5000, 1).is_err());
+    assert!(run(50, 2).is_err());
+    assert!(run(0, 3).is_ok());
+}
+
+#[test]
+fn test_kem_and_signature_negotiation() {
+    let mut initiator = config(AlgorithmId::Falcon512);
+    initiator.kem = AlgorithmId::MlKem1024;
+    let mut responder = config(AlgorithmId::SlhDsaSha2_128f);
+    responder.kem = AlgorithmId::MlKem1024;
+    let (client, server) = loopback(initiator, responder).unwrap();
+    assert_eq!(client.peer().algorithm, AlgorithmId::SlhDsaSha2_128f);
+    assert_eq!(server.peer().algorithm, AlgorithmId::Falcon512);
+
+    let initiator = config(AlgorithmId::MlDsa44);
+    let mut responder = config(AlgorithmId::MlDsa44);
+    responder.kem = AlgorithmId::MlKem512;
+    assert_eq!(
+        loopback(initiator, responder).err(),
+        Some(HandshakeError::UnsupportedAlgorithm(AlgorithmId::MlKem768))
+    );
+
+    assert!(Identity::generate(AlgorithmId::MlKem768).is_err());
+}
@@ -0,0 +1,121 @@
+//! Tests for LMS/HSS and XMSS^MT signing and the state handling around them.
+
+use aegis_crypto_core::hbs::lms::{ self, HssPrivateKey, LMOTS_SHA256_N32_W4, LMS_SHA256_M32_H5 };
+use aegis_crypto_core::hbs::xmss::{ self, XmssPrivateKey, XMSSMT_SHA2_20_4_192 };
+use aegis_crypto_core::hbs::{ FileStateStore, HbsError, MemoryStateStore, StateStore, StatefulSigner };
+use zeroize::Zeroizing;
+
+/// A store whose writes start failing after `allowed` successes.
+struct FlakyStore {
+    inner: MemoryStateStore,
+    allowed: usize,
+}
+
+impl StateStore for FlakyStore {
+    fn store(&mut self, state: &[u8]) -> Result<(), HbsError> {
+        if self.allowed == 0 {
+            return Err(HbsError::State("disk full".into()));
+        }
+        self.allowed -= 1;
+        self.inner.store(state)
+    }
+
+    fn load(&mut self) -> Result<Option<Zeroizing<Vec<u8>>>, HbsError> {
+        self.inner.load()
+    }
+}
+
+fn small_hss(levels: usize) -> HssPrivateKey {
+    HssPrivateKey::generate(&vec![(LMS_SHA256_M32_H5, LMOTS_SHA256_N32_W4); levels]).unwrap()
+}
+
+#[test]
+fn test_hss_crosses_tree_boundary() {
+    let mut signer = StatefulSigner::new(small_hss(2), MemoryStateStore::new()).unwrap();
+    let public_key = signer.public_key();
+    assert_eq!(signer.remaining(), 1024);
+
+    let mut signatures = Vec::new();
+    for i in 0..34u32 {
+        let message = i.to_be_bytes();
+        signatures.push(signer.sign(&message).unwrap());
+    }
+    for (i, signature) in signatures.iter().enumerate() {
+        assert!(lms::verify_hss(&public_key, &(i as u32).to_be_bytes(), signature));
+    }
+    // Indices 31 and 32 sit in different bottom-level trees.
+    assert_ne!(signatures[31][4..60], signatures[32][4..60]);
+    assert!(!lms::verify_hss(&public_key, &0u32.to_be_bytes(), &signatures[1]));
+    assert!(!lms::verify_hss(&public_key, &0u32.to_be_bytes(), &signatures[0][..signatures[0].len() - 1]));
+}
+
+#[test]
+fn test_exhaustion() {
+    let mut signer = StatefulSigner::new(small_hss(1), MemoryStateStore::new()).unwrap();
+    for _ in 0..32 {
+        signer.sign(b"log entry").unwrap();
+    }
+    assert_eq!(signer.remaining(), 0);
+    assert_eq!(signer.sign(b"log entry"), Err(HbsError::Exhausted));
+    assert_eq!(signer.reserve(5), Ok(()));
+
+    let store = signer.into_store();
+    let mut reloaded: StatefulSigner<HssPrivateKey, _> = StatefulSigner::load(store).unwrap();
+    assert_eq!(reloaded.sign(b"log entry"), Err(HbsError::Exhausted));
+}
+
+#[test]
+fn test_file_store_never_reuses_indices() {
+    let path = std::env::temp_dir().join(format!("aegis-hbs-{}.state", std::process::id()));
+    let mut signer = StatefulSigner::new(small_hss(1), FileStateStore::new(&path)).unwrap();
+    let public_key = signer.public_key();
+    signer.sign(b"first").unwrap();
+    signer.reserve(10).unwrap();
+    signer.sign(b"second").unwrap();
+    assert_eq!(signer.next_index(), 2);
+    drop(signer);
+
+    // The reserved but unused indices 2..11 are skipped after a restart.
+    let mut signer: StatefulSigner<HssPrivateKey, _> = StatefulSigner::load(FileStateStore::new(&path)).unwrap();
+    assert_eq!(signer.next_index(), 11);
+    assert_eq!(signer.public_key(), public_key);
+    let signature = signer.sign(b"third").unwrap();
+    assert_eq!(&signature[4..8], &11u32.to_be_bytes());
+    assert!(lms::verify_hss(&public_key, b"third", &signature));
+
+    let stored = std::fs::read(&path).unwrap();
+    assert_eq!(&stored[stored.len() - 8..], &12u64.to_be_bytes());
+    std::fs::remove_file(&path).unwrap();
+
+    let missing = StatefulSigner::<HssPrivateKey, _>::load(FileStateStore::new(&path));
+    assert!(matches!(missing, Err(HbsError::State(_))));
+}
+
+#[test]
+fn test_store_failure_produces_no_signature() {
+    let store = FlakyStore { inner: MemoryStateStore::new(), allowed: 2 };
+    let mut signer = StatefulSigner::new(small_hss(1), store).unwrap();
+    signer.sign(b"ok").unwrap();
+    assert!(matches!(signer.sign(b"lost"), Err(HbsError::State(_))));
+    assert_eq!(signer.next_index(), 1);
+}
+
+#[test]
+fn test_xmss_mt_state_round_trip() {
+    let key = XmssPrivateKey::generate_mt(XMSSMT_SHA2_20_4_192).unwrap();
+    let mut signer = StatefulSigner::new(key, MemoryStateStore::new()).unwrap();
+    let public_key = signer.public_key();
+    assert_eq!(public_key.len(), 4 + 2 * 24);
+    let first = signer.sign(b"package index").unwrap();
+
+    let mut signer: StatefulSigner<XmssPrivateKey, _> = StatefulSigner::load(signer.into_store()).unwrap();
+    let second = signer.sign(b"package index").unwrap();
+    assert_eq!(first.len(), 3 + 24 + 4 * (51 + 5) * 24);
+    assert_eq!(&second[..3], &[0, 0, 1]);
+    assert!(xmss::verify_mt(&public_key, b"package index", &first));
+    assert!(xmss::verify_mt(&public_key, b"package index", &second));
+
+    let mut tampered = second.clone();
+    tampered[100] ^= 0x01;
+    assert!(!xmss::verify_mt(&public_key, b"package index", &tampered));
+}
@@ -0,0 +1,150 @@
+//! Tests for BIP-39 mnemonics and hardened derivation of post-quantum keys.
+
+use aegis_crypto_core::hd::{ DerivationPath, ExtendedKey, HdError, Mnemonic, HARDENED };
+use aegis_crypto_core::registry::{ self, AlgorithmError, AlgorithmId };
+use sha2::{ Digest, Sha256 };
+
+const ZERO_PHRASE: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+#[test]
+fn test_bip39_vector() {
+    let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+    assert_eq!(mnemonic.phrase().as_str(), ZERO_PHRASE);
+    assert_eq!(
+        hex::encode(&mnemonic.to_seed("TREZOR")[..]),
+        "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+    );
+    assert_eq!(Mnemonic::parse(&format!("  {}  ", ZERO_PHRASE.to_uppercase())).unwrap(), mnemonic);
+
+    for words in [12, 15, 18, 21, 24] {
+        let mnemonic = Mnemonic::generate(words).unwrap();
+        assert_eq!(mnemonic.word_count(), words);
+        assert_eq!(Mnemonic::parse(&mnemonic.phrase()).unwrap(), mnemonic);
+    }
+    assert_eq!(Mnemonic::generate(13), Err(HdError::InvalidWordCount(13)));
+}
+
+#[test]
+fn test_restore_from_phrase() {
+    let mnemonic = Mnemonic::generate(24).unwrap();
+    let path = DerivationPath::bip44(0, 0, 0).unwrap();
+    let node = ExtendedKey::from_mnemonic(&mnemonic, "").derive(&path).unwrap();
+    assert_eq!(node.path(), &path);
+
+    let restored = ExtendedKey::from_mnemonic(&Mnemonic::parse(&mnemonic.phrase()).unwrap(), "")
+        .derive(&"m/44'/0'/0'/0'".parse().unwrap())
+        .unwrap();
+    for alg in [AlgorithmId::MlDsa87, AlgorithmId::MlKem768, AlgorithmId::Falcon512] {
+        assert_eq!(node.keypair(alg).unwrap(), restored.keypair(alg).unwrap(), "{}", alg.name());
+    }
+
+    // Step-by-step derivation reaches the same node.
+    let stepped = [44, 0, 0, 0]
+        .iter()
+        .try_fold(ExtendedKey::from_mnemonic(&mnemonic, ""), |node, &i| node.child(i | HARDENED))
+        .unwrap();
+    assert_eq!(stepped.keypair(AlgorithmId::MlDsa65).unwrap(), restored.keypair(AlgorithmId::MlDsa65).unwrap());
+
+    // A different passphrase gives a different wallet.
+    let other = ExtendedKey::from_mnemonic(&mnemonic, "extra").derive(&path).unwrap();
+    assert_ne!(other.keypair(AlgorithmId::MlDsa87).unwrap().0, node.keypair(AlgorithmId::MlDsa87).unwrap().0);
+}
+
+#[test]
+fn test_paths_and_algorithms_are_separated() {
+    let root = ExtendedKey::from_mnemonic(&Mnemonic::parse(ZERO_PHRASE).unwrap(), "");
+    let a = root.derive(&DerivationPath::bip44(0, 0, 0).unwrap()).unwrap();
+    let b = root.derive(&DerivationPath::bip44(0, 0, 1).unwrap()).unwrap();
+    let c = root.derive(&DerivationPath::bip44(60, 0, 0).unwrap()).unwrap();
+    let seeds: Vec<_> = [&a, &b, &c]
+        .iter()
+        .map(|node| node.algorithm_seed(AlgorithmId::MlDsa44).unwrap())
+        .collect();
+    assert_ne!(seeds[0], seeds[1]);
+    assert_ne!(seeds[0], seeds[2]);
+
+    let dsa = a.algorithm_seed(AlgorithmId::MlDsa44).unwrap();
+    let dsa65 = a.algorithm_seed(AlgorithmId::MlDsa65).unwrap();
+    assert_ne!(dsa, dsa65);
+    assert_eq!(a.algorithm_seed(AlgorithmId::MlKem768).unwrap().len(), 64);
+    assert_eq!(a.algorithm_seed(AlgorithmId::Falcon1024).unwrap().len(), 48);
+    assert!(matches!(a.keypair(AlgorithmId::Hqc128), Err(HdError::Algorithm(AlgorithmError::Unsupported(_)))));
+}
+
+#[test]
+fn test_derived_keys_work() {
+    let node = ExtendedKey::master(&[7u8; 32]).unwrap().derive(&"m/44'/60'/0'/0'".parse().unwrap()).unwrap();
+    let message = b"derived key";
+    for alg in [AlgorithmId::MlDsa44, AlgorithmId::MlDsa87, AlgorithmId::Falcon512, AlgorithmId::Falcon1024] {
+        let (public_key, secret_key) = node.keypair(alg).unwrap();
+        let signature = registry::sign(alg, &secret_key, message).unwrap();
+        assert!(registry::verify(alg, &public_key, message, &signature), "{}", alg.name());
+    }
+    for alg in [AlgorithmId::MlKem512, AlgorithmId::MlKem768, AlgorithmId::MlKem1024] {
+        let (public_key, secret_key) = node.keypair(alg).unwrap();
+        let (ciphertext, shared_secret) = registry::encapsulate(alg, &public_key).unwrap();
+        assert_eq!(registry::decapsulate(alg, &secret_key, &ciphertext).unwrap(), shared_secret, "{}", alg.name());
+    }
+}
+
+/// Count 0 of the NIST KAT responses (the outputs PQClean's META.yml
+/// `nistkat-sha256` covers). The seed is the first output of the KAT DRBG
+/// seeded with that entry's `seed`; the digests are SHA-256 of its pk and sk.
+#[test]
+fn test_seeded_keygen_matches_nist_kat() {
+    const XI: &str = "7c9935a0b07694aa0c6d10e4db6b1add2fd81a25ccb148032dcd739936737f2d";
+    const FALCON_SEED: &str =
+        "7c9935a0b07694aa0c6d10e4db6b1add2fd81a25ccb148032dcd739936737f2db505d7cfad1b497499323c8686325e47";
+    let vectors = [
+        (
+            AlgorithmId::MlDsa44,
+            XI,
+            "4b498bd0d7e0e8436bf30db37cd27b448c0e8cf1482fc9dc6e0cae496be62441",
+            "cff36f6545a148897e244806ae7aafa964807e30217046f672f86020208353dc",
+        ),
+        (
+            AlgorithmId::MlDsa65,
+            XI,
+            "af4ff33e95424383ef62326bff2d309e353a60653c3f2a24e89821f7cdf442b0",
+            "e7e04cf8ea0475f8004a4289fe56e41894d2359c15ff4f35d6663774ef52fd42",
+        ),
+        (
+            AlgorithmId::MlDsa87,
+            XI,
+            "0eb2575900d62454bf746e96780c07c00082958185fc3d37a77e30ac9175741a",
+            "2a7ab6461383de094b204cf7e8c5b79217b5611a1c133be468a4c5071873743e",
+        ),
+        (
+            AlgorithmId::Falcon512,
+            FALCON_SEED,
+            "e74cb05c857846d8c768a2d3a2461d589482e770461fdbbe9e2e9dc6415e2daf",
+            "0dfaaa352b95059488f1fdad55848bb814f18bca1de5194e5f9994c40a8730b6",
+        ),
+        (
+            AlgorithmId::Falcon1024,
+            FALCON_SEED,
+            "f392083048bb1bc08d77ef3782dd114154480adebbf5ee0c3853816cba0a7341",
+            "5b93be961746f89a4c1d339ba7b6c6c82baab915f36d51e2afe9d655cf435464",
+        ),
+    ];
+    for (alg, seed, pk_digest, sk_digest) in vectors {
+        let (public_key, secret_key) = registry::keygen_from_seed(alg, &hex::decode(seed).unwrap()).unwrap();
+        assert_eq!(hex::encode(Sha256::digest(&public_key)), pk_digest, "{} pk", alg.name());
+        assert_eq!(hex::encode(Sha256::digest(&secret_key)), sk_digest, "{} sk", alg.name());
+    }
+}
+
+#[test]
+fn test_rejects_bad_input() {
+    let mut words: Vec<&str> = ZERO_PHRASE.split(' ').collect();
+    words[11] = "abandon";
+    assert!(matches!(Mnemonic::parse(&words.join(" ")), Err(HdError::InvalidMnemonic(_))));
+    words[11] = "notaword";
+    assert!(matches!(Mnemonic::parse(&words.join(" ")), Err(HdError::InvalidMnemonic(_))));
+
+    assert_eq!("m/44'/0'/0".parse::<DerivationPath>(), Err(HdError::NotHardened(0)));
+    assert!(DerivationPath::new(&[HARDENED]).is_err());
+    assert_eq!(ExtendedKey::master(&[0u8; 15]).unwrap_err(), HdError::InvalidSeedLength(15));
+    assert_eq!(ExtendedKey::master(&[0u8; 16]).unwrap().child(5).unwrap_err(), HdError::NotHardened(5));
+}
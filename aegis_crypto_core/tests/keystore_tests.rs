@@ -0,0 +1,123 @@
+//! Tests for the keystore trait and its memory and directory backends.
+
+use aegis_crypto_core::keystore::argon2::Argon2Params;
+use aegis_crypto_core::keystore::{
+    DirectoryKeyStore,
+    KeyId,
+    KeyOptions,
+    KeyStore,
+    KeyStoreError,
+    KeyUsage,
+    MemoryKeyStore,
+};
+use aegis_crypto_core::registry::{ self, AlgorithmError, AlgorithmId };
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("aegis-keystore-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn fast_params() -> Argon2Params {
+    Argon2Params::new(64, 1, 1).unwrap()
+}
+
+/// Exercise the operations every backend must support.
+fn exercise(store: &mut impl KeyStore) {
+    let signer = store.generate(AlgorithmId::MlDsa44, KeyOptions::new().with_label("wallet")).unwrap();
+    let kem = store.generate(AlgorithmId::MlKem768, KeyOptions::new()).unwrap();
+    assert_eq!(signer.usage, KeyUsage::SIGN);
+    assert!(signer.has_label("wallet"));
+
+    let public_key = store.public_key(&signer.id).unwrap();
+    let signature = store.sign(&signer.id, b"transfer").unwrap();
+    assert!(registry::verify(AlgorithmId::MlDsa44, &public_key, b"transfer", &signature));
+
+    let (ciphertext, shared) = registry::encapsulate(AlgorithmId::MlKem768, &store.public_key(&kem.id).unwrap()).unwrap();
+    assert_eq!(*store.decapsulate(&kem.id, &ciphertext).unwrap(), shared);
+    assert_eq!(store.sign(&kem.id, b"transfer"), Err(KeyStoreError::UsageNotPermitted(kem.id)));
+
+    let mut listed: Vec<KeyId> = store.list().unwrap().iter().map(|m| m.id).collect();
+    let mut expected = vec![signer.id, kem.id];
+    listed.sort();
+    expected.sort();
+    assert_eq!(listed, expected);
+
+    store.delete(&kem.id).unwrap();
+    assert_eq!(store.metadata(&kem.id), Err(KeyStoreError::NotFound(kem.id)));
+    assert_eq!(store.delete(&kem.id), Err(KeyStoreError::NotFound(kem.id)));
+    assert_eq!(store.list().unwrap(), vec![signer]);
+}
+
+#[test]
+fn test_memory_backend() {
+    exercise(&mut MemoryKeyStore::new());
+}
+
+#[test]
+fn test_directory_backend() {
+    let dir = temp_dir("ops");
+    exercise(&mut DirectoryKeyStore::create(&dir, b"correct horse", fast_params()).unwrap());
+    let leftovers: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_import_checks_and_policies() {
+    let mut store = MemoryKeyStore::new();
+    let (public_key, secret_key) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let (other_public, _) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    assert_eq!(
+        store.import(AlgorithmId::Falcon512, &other_public, &secret_key, KeyOptions::new()),
+        Err(KeyStoreError::Algorithm(AlgorithmError::InvalidKey))
+    );
+
+    let expired = KeyOptions::new().with_expiry(1);
+    let imported = store.import(AlgorithmId::Falcon512, &public_key, &secret_key, expired).unwrap();
+    assert_eq!(store.sign(&imported.id, b"late"), Err(KeyStoreError::Expired(imported.id)));
+    assert_eq!(
+        store.import(AlgorithmId::Falcon512, &public_key, &secret_key, KeyOptions::new()),
+        Err(KeyStoreError::AlreadyExists(imported.id))
+    );
+
+    let kem_for_signing = KeyOptions::new().with_usage(KeyUsage::SIGN);
+    assert!(matches!(store.generate(AlgorithmId::MlKem512, kem_for_signing), Err(KeyStoreError::Algorithm(_))));
+
+    let id: KeyId = imported.id.to_string().parse().unwrap();
+    assert_eq!(id, imported.id);
+    assert!("abcd".parse::<KeyId>().is_err());
+}
+
+#[test]
+fn test_directory_persistence_and_passphrase() {
+    let dir = temp_dir("persist");
+    let (public_key, secret_key) = registry::keygen(AlgorithmId::MlDsa65).unwrap();
+    let id = {
+        let mut store = DirectoryKeyStore::create(&dir, b"hunter2", fast_params()).unwrap();
+        let options = KeyOptions::new().with_label("node").with_expiry(u64::MAX);
+        store.import(AlgorithmId::MlDsa65, &public_key, &secret_key, options).unwrap().id
+    };
+    assert!(DirectoryKeyStore::create(&dir, b"hunter2", fast_params()).is_err());
+    assert_eq!(DirectoryKeyStore::open(&dir, b"hunter3").err(), Some(KeyStoreError::BadPassphrase));
+
+    let store = DirectoryKeyStore::open(&dir, b"hunter2").unwrap();
+    let metadata = store.metadata(&id).unwrap();
+    assert_eq!((metadata.labels.clone(), metadata.expires_at), (vec!["node".to_string()], Some(u64::MAX)));
+    let signature = store.sign(&id, b"block 7").unwrap();
+    assert!(registry::verify(AlgorithmId::MlDsa65, &public_key, b"block 7", &signature));
+
+    // The secret key is not stored in the clear, and edits are detected.
+    let key_path = dir.join(format!("{}.key", id));
+    let mut file = std::fs::read(&key_path).unwrap();
+    assert!(!file.windows(32).any(|w| w == &secret_key[..32]));
+    let last = file.len() - 1;
+    file[last] ^= 0x01;
+    std::fs::write(&key_path, &file).unwrap();
+    assert!(matches!(store.sign(&id, b"block 8"), Err(KeyStoreError::Malformed(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,82 @@
+//! Tests for wrapping a data key to several KEM recipients.
+
+use aegis_crypto_core::keystore::{ KeyOptions, KeyStore, MemoryKeyStore };
+use aegis_crypto_core::keywrap::{ unwrap_key, unwrap_key_with, wrap_key, KeyWrapError, PublicKeyRef, WrappedKeyBundle };
+use aegis_crypto_core::registry::{ self, AlgorithmError, AlgorithmId };
+
+#[test]
+fn test_every_recipient_can_unwrap() {
+    let algorithms = [AlgorithmId::MlKem768, AlgorithmId::MlKem1024, AlgorithmId::Hqc128];
+    let keys: Vec<_> = algorithms.iter().map(|&alg| (alg, registry::keygen(alg).unwrap())).collect();
+    let recipients: Vec<_> = keys.iter().map(|(alg, (pk, _))| PublicKeyRef::new(*alg, pk)).collect();
+
+    let dek = [0x42u8; 32];
+    let bundle = wrap_key(&dek, &recipients).unwrap();
+    assert_eq!(bundle.recipients().len(), 3);
+    let bundle = WrappedKeyBundle::from_bytes(&bundle.to_bytes()).unwrap();
+    for ((_, (_, sk)), recipient) in keys.iter().zip(&recipients) {
+        assert!(bundle.contains(&recipient.key_id()));
+        assert_eq!(*unwrap_key(sk, &bundle).unwrap(), dek);
+    }
+
+    let (_, outsider) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+    assert_eq!(unwrap_key(&outsider, &bundle), Err(KeyWrapError::NotRecipient));
+}
+
+#[test]
+fn test_keystore_recipients_added_and_removed() {
+    let mut store = MemoryKeyStore::new();
+    let owner = store.generate(AlgorithmId::MlKem768, KeyOptions::new()).unwrap();
+    let owner_pk = store.public_key(&owner.id).unwrap();
+    let (colleague_pk, colleague_sk) = registry::keygen(AlgorithmId::MlKem512).unwrap();
+
+    // A 20-byte key exercises the padded key wrap.
+    let dek = b"per-object data key!";
+    let mut bundle = wrap_key(dek, &[PublicKeyRef::new(AlgorithmId::MlKem768, &owner_pk)]).unwrap();
+    assert_eq!(unwrap_key(&colleague_sk, &bundle), Err(KeyWrapError::NotRecipient));
+
+    // Share with a colleague later, starting from the owner's copy.
+    let recovered = unwrap_key_with(&store, &bundle).unwrap();
+    let colleague = PublicKeyRef::new(AlgorithmId::MlKem512, &colleague_pk);
+    bundle.add_recipient(&recovered, colleague).unwrap();
+    bundle.add_recipient(&recovered, colleague).unwrap();
+    assert_eq!(bundle.recipients().len(), 2);
+    assert_eq!(&unwrap_key(&colleague_sk, &bundle).unwrap()[..], dek);
+
+    assert!(bundle.remove_recipient(&owner.id));
+    assert!(!bundle.remove_recipient(&owner.id));
+    assert_eq!(unwrap_key_with(&store, &bundle), Err(KeyWrapError::NotRecipient));
+    assert_eq!(&unwrap_key(&colleague_sk, &bundle).unwrap()[..], dek);
+}
+
+#[test]
+fn test_invalid_inputs_and_tampering() {
+    let (pk, sk) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+    let recipient = PublicKeyRef::new(AlgorithmId::MlKem768, &pk);
+    assert_eq!(wrap_key(&[], &[recipient]), Err(KeyWrapError::KeyLength(0)));
+    assert_eq!(wrap_key(&[1; 32], &[]), Err(KeyWrapError::NoRecipients));
+    let (signer_pk, _) = registry::keygen(AlgorithmId::MlDsa44).unwrap();
+    assert_eq!(
+        wrap_key(&[1; 32], &[PublicKeyRef::new(AlgorithmId::MlDsa44, &signer_pk)]),
+        Err(KeyWrapError::Algorithm(AlgorithmError::Unsupported(AlgorithmId::MlDsa44)))
+    );
+
+    let mut bundle = wrap_key(&[1; 32], &[recipient]).unwrap();
+    assert_eq!(bundle.add_recipient(&[1; 16], recipient), Err(KeyWrapError::KeyLength(16)));
+
+    let mut bytes = bundle.to_bytes();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    let tampered = WrappedKeyBundle::from_bytes(&bytes).unwrap();
+    assert_eq!(unwrap_key(&sk, &tampered), Err(KeyWrapError::NotRecipient));
+
+    let mut store = MemoryKeyStore::new();
+    let id = store.import(AlgorithmId::MlKem768, &pk, &sk, KeyOptions::new()).unwrap().id;
+    assert_eq!(id, recipient.key_id());
+    assert_eq!(unwrap_key_with(&store, &tampered), Err(KeyWrapError::UnwrapFailed));
+
+    bytes.push(0);
+    assert!(matches!(WrappedKeyBundle::from_bytes(&bytes), Err(KeyWrapError::Malformed(_))));
+    bytes[4] = 2;
+    assert!(matches!(WrappedKeyBundle::from_bytes(&bytes), Err(KeyWrapError::Malformed(_))));
+}
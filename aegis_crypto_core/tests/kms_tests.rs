@@ -0,0 +1,103 @@
+//! Tests for the KMS service layer over an in-memory keystore.
+
+use aegis_crypto_core::keystore::{ KeyOptions, KeyStoreError, KeyUsage, MemoryKeyStore };
+use aegis_crypto_core::kms::{
+    decrypt_envelope,
+    encrypt_envelope,
+    AuditRecord,
+    AuditSink,
+    EncryptionContext,
+    Kms,
+    KmsError,
+    KmsOperation,
+    LocalKms,
+};
+use aegis_crypto_core::registry::AlgorithmId;
+
+fn context(pairs: &[(&str, &str)]) -> EncryptionContext {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_data_keys_and_envelopes() {
+    let mut kms = LocalKms::new(MemoryKeyStore::new());
+    let key = kms.create_key(AlgorithmId::MlKem768, KeyOptions::new()).unwrap().id;
+    let bucket = context(&[("bucket", "invoices"), ("object", "2024/03.pdf")]);
+
+    let data_key = kms.generate_data_key(&key, 32, &bucket).unwrap();
+    assert_eq!(data_key.plaintext.len(), 32);
+    assert_eq!(*kms.decrypt(&data_key.ciphertext, &bucket).unwrap(), *data_key.plaintext);
+    assert_eq!(kms.generate_data_key(&key, 0, &bucket).err(), Some(KmsError::DataKeyLength(0)));
+
+    let envelope = encrypt_envelope(&mut kms, &key, b"quarterly numbers", &bucket).unwrap();
+    assert_eq!(decrypt_envelope(&mut kms, &envelope, &bucket).unwrap(), b"quarterly numbers");
+    let other = context(&[("bucket", "invoices"), ("object", "2024/04.pdf")]);
+    assert_eq!(decrypt_envelope(&mut kms, &envelope, &other), Err(KmsError::DecryptionFailed));
+}
+
+#[test]
+fn test_context_binding_and_re_encrypt() {
+    let mut kms = LocalKms::new(MemoryKeyStore::new());
+    let old = kms.create_key(AlgorithmId::MlKem768, KeyOptions::new()).unwrap().id;
+    let new = kms.create_key(AlgorithmId::Hqc128, KeyOptions::new()).unwrap().id;
+    let table = context(&[("table", "users")]);
+
+    let ciphertext = kms.encrypt(&old, b"row secret", &table).unwrap();
+    assert_eq!(kms.decrypt(&ciphertext, &EncryptionContext::new()).err(), Some(KmsError::DecryptionFailed));
+    let mut tampered = ciphertext.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert_eq!(kms.decrypt(&tampered, &table).err(), Some(KmsError::DecryptionFailed));
+    assert!(matches!(kms.decrypt(&ciphertext[..20], &table), Err(KmsError::Malformed(_))));
+
+    let moved = context(&[("table", "users_v2")]);
+    let rewrapped = kms.re_encrypt(&ciphertext, &table, &new, &moved).unwrap();
+    assert_eq!(&kms.decrypt(&rewrapped, &moved).unwrap()[..], b"row secret");
+    assert_eq!(kms.decrypt(&rewrapped, &table).err(), Some(KmsError::DecryptionFailed));
+}
+
+#[test]
+fn test_sign_verify_and_usage() {
+    let mut kms = LocalKms::new(MemoryKeyStore::new());
+    let signer = kms.create_key(AlgorithmId::MlDsa65, KeyOptions::new()).unwrap().id;
+    let decrypt_only = kms
+        .create_key(AlgorithmId::MlKem512, KeyOptions::new().with_usage(KeyUsage::DECAPSULATE))
+        .unwrap().id;
+
+    let signature = kms.sign(&signer, b"release v1").unwrap();
+    assert_eq!(kms.verify(&signer, b"release v1", &signature), Ok(true));
+    assert_eq!(kms.verify(&signer, b"release v2", &signature), Ok(false));
+
+    assert_eq!(
+        kms.encrypt(&signer, b"x", &EncryptionContext::new()),
+        Err(KmsError::KeyStore(KeyStoreError::UsageNotPermitted(signer)))
+    );
+    assert!(kms.sign(&decrypt_only, b"x").is_err());
+}
+
+#[test]
+fn test_audit_trail() {
+    let mut kms = LocalKms::new(MemoryKeyStore::new());
+    let key = kms.create_key(AlgorithmId::MlKem768, KeyOptions::new()).unwrap().id;
+    let tenant = context(&[("tenant", "acme")]);
+    let ciphertext = kms.encrypt(&key, b"secret", &tenant).unwrap();
+    kms.decrypt(&ciphertext, &EncryptionContext::new()).unwrap_err();
+
+    let trail = kms.audit();
+    let operations: Vec<_> = trail.iter().map(|r| r.operation).collect();
+    assert_eq!(operations, [KmsOperation::CreateKey, KmsOperation::Encrypt, KmsOperation::Decrypt]);
+    assert!(trail.iter().all(|r| r.keys == [key]));
+    assert_eq!(trail[1].context, tenant);
+    assert_eq!(trail[1].error, None);
+    assert_eq!(trail[2].error, Some(KmsError::DecryptionFailed));
+
+    // An operation that cannot be audited does not happen.
+    struct Full;
+    impl AuditSink for Full {
+        fn record(&mut self, _: AuditRecord) -> Result<(), KmsError> {
+            Err(KmsError::Audit("disk full".into()))
+        }
+    }
+    let (store, _) = kms.into_parts();
+    let mut kms = LocalKms::with_audit(store, Full);
+    assert_eq!(kms.decrypt(&ciphertext, &tenant), Err(KmsError::Audit("disk full".into())));
+}
@@ -0,0 +1,99 @@
+//! Tests for Merkle logs, RFC 9162 proofs and signed tree heads.
+
+use aegis_crypto_core::merkle::{ MerkleError, MerkleHash, MerkleLog, SignedTreeHead };
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+
+/// The leaves of the RFC 6962 reference test tree.
+fn reference_leaves() -> Vec<Vec<u8>> {
+    vec![
+        vec![],
+        vec![0x00],
+        vec![0x10],
+        vec![0x20, 0x21],
+        vec![0x30, 0x31],
+        vec![0x40, 0x41, 0x42, 0x43],
+        (0x50..0x58).collect(),
+        (0x60..0x70).collect()
+    ]
+}
+
+#[test]
+fn test_reference_roots() {
+    let log = MerkleLog::from_leaves(MerkleHash::Sha256, reference_leaves());
+    assert_eq!(
+        hex::encode(log.root_at(1).unwrap()),
+        "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d"
+    );
+    assert_eq!(hex::encode(log.root()), "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328");
+    assert_eq!(
+        hex::encode(MerkleLog::new(MerkleHash::Sha256).root()),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+
+#[test]
+fn test_all_proofs_verify() {
+    for hash in [MerkleHash::Sha3_256, MerkleHash::Blake3, MerkleHash::Keccak256] {
+        let mut log = MerkleLog::new(hash);
+        for i in 0..20u32 {
+            log.append(&i.to_be_bytes());
+        }
+        for size in 1..=20 {
+            let root = log.root_at(size).unwrap();
+            for index in 0..size {
+                let proof = log.inclusion_proof(index, size).unwrap();
+                assert!(proof.verify(hash, &(index as u32).to_be_bytes(), &root));
+                assert!(!proof.verify(hash, b"other", &root));
+            }
+            for old in 1..=size {
+                let proof = log.consistency_proof(old, size).unwrap();
+                assert!(proof.verify(hash, &log.root_at(old).unwrap(), &root));
+                if old < size {
+                    assert!(!proof.verify(hash, &root, &root));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tampered_proofs_rejected() {
+    let log = MerkleLog::from_leaves(MerkleHash::Blake3, (0..13u8).map(|i| [i]));
+    let root = log.root();
+    let mut proof = log.inclusion_proof(6, 13).unwrap();
+    proof.leaf_index = 7;
+    assert!(!proof.verify(MerkleHash::Blake3, &[6], &root));
+    proof.leaf_index = 6;
+    proof.path.push(root);
+    assert!(!proof.verify(MerkleHash::Blake3, &[6], &root));
+    proof.path.truncate(proof.path.len() - 2);
+    assert!(!proof.verify(MerkleHash::Blake3, &[6], &root));
+
+    let mut consistency = log.consistency_proof(5, 13).unwrap();
+    consistency.path[0][0] ^= 1;
+    assert!(!consistency.verify(MerkleHash::Blake3, &log.root_at(5).unwrap(), &root));
+
+    assert_eq!(log.inclusion_proof(13, 13), Err(MerkleError::IndexOutOfRange { index: 13, tree_size: 13 }));
+    assert!(matches!(log.consistency_proof(0, 13), Err(MerkleError::InvalidRange(_))));
+    assert!(matches!(log.consistency_proof(5, 14), Err(MerkleError::InvalidRange(_))));
+}
+
+#[test]
+fn test_signed_tree_head() {
+    let (public_key, secret_key) = registry::keygen(AlgorithmId::MlDsa44).unwrap();
+    let mut log = MerkleLog::new(MerkleHash::Keccak256);
+    log.append(b"tx-1");
+    log.append(b"tx-2");
+    let head = log.sign_tree_head(AlgorithmId::MlDsa44, &secret_key, 1_700_000_000_000).unwrap();
+    assert!(head.verify(&public_key));
+
+    let decoded = SignedTreeHead::from_bytes(&head.to_bytes()).unwrap();
+    assert_eq!(decoded, head);
+    let mut moved = decoded.clone();
+    moved.tree_size = 3;
+    assert!(!moved.verify(&public_key));
+    assert!(SignedTreeHead::from_bytes(&head.to_bytes()[..60]).is_err());
+
+    let (_, kem_secret) = registry::keygen(AlgorithmId::MlKem512).unwrap();
+    assert!(matches!(log.sign_tree_head(AlgorithmId::MlKem512, &kem_secret, 0), Err(MerkleError::Algorithm(_))));
+}
@@ -0,0 +1,139 @@
+//! Tests for PQXDH session setup and the double ratchet.
+
+use aegis_crypto_core::messaging::{
+    IdentityKeyPair,
+    MessagingError,
+    PrekeyStore,
+    Session,
+    SessionConfig,
+};
+use aegis_crypto_core::registry::AlgorithmId;
+
+struct Pair {
+    alice: Session,
+    bob: Session,
+}
+
+fn establish(config: SessionConfig) -> Pair {
+    let alice_id = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+    let bob_id = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+    let mut store = PrekeyStore::new(&bob_id, 2).unwrap();
+    let bundle = store.bundle(&bob_id);
+
+    let (alice, first) = Session::initiate(&alice_id, &bundle, b"hello bob", config).unwrap();
+    let (bob, plaintext) = Session::respond(&bob_id, &mut store, &first, config).unwrap();
+    assert_eq!(plaintext, b"hello bob");
+    assert_eq!(bob.remote_identity(), &alice_id.public());
+    assert_eq!(alice.remote_identity(), &bob_id.public());
+    Pair { alice, bob }
+}
+
+#[test]
+fn test_conversation() {
+    let Pair { mut alice, mut bob } = establish(SessionConfig::default());
+    for round in 0..6u8 {
+        let message = bob.encrypt(&[round; 10]).unwrap();
+        assert_eq!(alice.decrypt(&message).unwrap(), vec![round; 10]);
+        let message = alice.encrypt(&[round; 3]).unwrap();
+        let again = alice.encrypt(b"twice").unwrap();
+        assert_eq!(bob.decrypt(&message).unwrap(), vec![round; 3]);
+        assert_eq!(bob.decrypt(&again).unwrap(), b"twice");
+    }
+}
+
+#[test]
+fn test_initial_messages_until_reply() {
+    let alice_id = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+    let bob_id = IdentityKeyPair::generate(AlgorithmId::MlDsa44).unwrap();
+    let mut store = PrekeyStore::new(&bob_id, 1).unwrap();
+    let bundle = store.bundle(&bob_id);
+    assert_eq!(store.remaining_one_time_prekeys(), 0);
+
+    let config = SessionConfig::default();
+    let (mut alice, first) = Session::initiate(&alice_id, &bundle, b"one", config).unwrap();
+    let second = alice.encrypt(b"two").unwrap();
+
+    // The second message also carries the agreement, so it can set up the
+    // session on its own; the one-time prekey is then consumed.
+    let (mut bob, plaintext) = Session::respond(&bob_id, &mut store, &second, config).unwrap();
+    assert_eq!(plaintext, b"two");
+    assert_eq!(bob.decrypt(&first).unwrap(), b"one");
+    assert_eq!(
+        Session::respond(&bob_id, &mut store, &first, config).err(),
+        Some(MessagingError::UnknownPrekey(bundle.kem_prekey.id))
+    );
+
+    // The next bundle falls back to the last-resort prekey.
+    assert!(store.bundle(&bob_id).kem_prekey_is_last_resort);
+}
+
+#[test]
+fn test_out_of_order_and_skip_limit() {
+    let config = SessionConfig { max_skip: 5, max_stored_keys: 3, pq_ratchet_interval: 0 };
+    let Pair { mut alice, mut bob } = establish(config);
+    let messages: Vec<_> = (0..5u8).map(|i| bob.encrypt(&[i]).unwrap()).collect();
+
+    assert_eq!(alice.decrypt(&messages[4]).unwrap(), [4]);
+    // Four keys were skipped but only three are kept; the oldest is gone.
+    assert_eq!(alice.stored_skipped_keys(), 3);
+    assert_eq!(alice.decrypt(&messages[2]).unwrap(), [2]);
+    assert_eq!(alice.decrypt(&messages[0]), Err(MessagingError::DuplicateMessage));
+    assert_eq!(alice.decrypt(&messages[2]), Err(MessagingError::DuplicateMessage));
+
+    let skipped: Vec<_> = (0..7).map(|_| bob.encrypt(b"x").unwrap()).collect();
+    assert_eq!(alice.decrypt(&skipped[6]), Err(MessagingError::TooManySkipped));
+    assert_eq!(alice.decrypt(&skipped[5]).unwrap(), b"x");
+}
+
+#[test]
+fn test_sparse_pq_ratchet() {
+    let config = SessionConfig { pq_ratchet_interval: 1, ..Default::default() };
+    let Pair { mut alice, mut bob } = establish(config);
+    let mut sizes = Vec::new();
+    for _ in 0..3 {
+        let message = bob.encrypt(b"pq").unwrap();
+        sizes.push(message.len());
+        assert_eq!(alice.decrypt(&message).unwrap(), b"pq");
+        let message = alice.encrypt(b"pq").unwrap();
+        assert_eq!(bob.decrypt(&message).unwrap(), b"pq");
+    }
+    // Every chain carries an ML-KEM-768 public key and ciphertext.
+    assert!(sizes.iter().all(|&len| len > 1184 + 1088));
+
+    let Pair { mut alice, mut bob } = establish(SessionConfig { pq_ratchet_interval: 0, ..config });
+    let message = bob.encrypt(b"classic").unwrap();
+    assert!(message.len() < 200);
+    assert_eq!(alice.decrypt(&message).unwrap(), b"classic");
+}
+
+#[test]
+fn test_serialization_round_trip() {
+    let Pair { mut alice, mut bob } = establish(SessionConfig::default());
+    let late = bob.encrypt(b"late").unwrap();
+    let now = bob.encrypt(b"now").unwrap();
+    assert_eq!(alice.decrypt(&now).unwrap(), b"now");
+
+    let mut restored = Session::from_bytes(&alice.to_bytes()).unwrap();
+    assert_eq!(restored.to_bytes(), alice.to_bytes());
+    assert_eq!(restored.decrypt(&late).unwrap(), b"late");
+    let reply = restored.encrypt(b"reply").unwrap();
+    assert_eq!(bob.decrypt(&reply).unwrap(), b"reply");
+
+    let bytes = alice.to_bytes();
+    assert!(Session::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_tampering_leaves_session_intact() {
+    let Pair { mut alice, mut bob } = establish(SessionConfig::default());
+    let message = bob.encrypt(b"authentic").unwrap();
+    let mut tampered = message.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert_eq!(alice.decrypt(&tampered), Err(MessagingError::Decrypt));
+    assert_eq!(alice.decrypt(&message).unwrap(), b"authentic");
+
+    // A different peer's session cannot read the message.
+    let Pair { alice: mut other, .. } = establish(SessionConfig::default());
+    assert!(other.decrypt(&bob.encrypt(b"private").unwrap()).is_err());
+}
@@ -0,0 +1,132 @@
+//! Tests for k-of-n multi-signature policies and bundles.
+
+use aegis_crypto_core::multisig::{
+    MultisigBundle,
+    MultisigError,
+    MultisigPolicy,
+    PartialSignature,
+    PolicyKey,
+    SignatureCollector,
+};
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+
+struct Signer {
+    algorithm: AlgorithmId,
+    public_key: Vec<u8>,
+    secret_key: Vec<u8>,
+}
+
+fn signers(algorithms: &[AlgorithmId]) -> Vec<Signer> {
+    algorithms
+        .iter()
+        .map(|&algorithm| {
+            let (public_key, secret_key) = registry::keygen(algorithm).unwrap();
+            Signer { algorithm, public_key, secret_key }
+        })
+        .collect()
+}
+
+fn policy(threshold: usize, signers: &[Signer]) -> MultisigPolicy {
+    let keys = signers
+        .iter()
+        .map(|s| PolicyKey::new(s.algorithm, &s.public_key))
+        .collect();
+    MultisigPolicy::new(threshold, keys).unwrap()
+}
+
+fn sign(policy: &MultisigPolicy, signer: &Signer, payload: &[u8]) -> PartialSignature {
+    policy.sign(signer.algorithm, &signer.public_key, &signer.secret_key, payload).unwrap()
+}
+
+#[test]
+fn test_policy_is_order_independent_and_round_trips() {
+    let s = signers(&[AlgorithmId::MlDsa44, AlgorithmId::Falcon512, AlgorithmId::MlDsa65]);
+    let forward = policy(2, &s);
+    let reversed: Vec<Signer> = s.into_iter().rev().collect();
+    let backward = policy(2, &reversed);
+    assert_eq!(forward.policy_hash(), backward.policy_hash());
+
+    let bytes = forward.to_bytes();
+    assert_eq!(MultisigPolicy::from_bytes(&bytes).unwrap(), forward);
+    assert!(MultisigPolicy::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert_ne!(policy(3, &reversed).policy_hash(), forward.policy_hash());
+}
+
+#[test]
+fn test_invalid_policies_rejected() {
+    let s = signers(&[AlgorithmId::MlDsa44, AlgorithmId::MlDsa44]);
+    let keys: Vec<PolicyKey> = s
+        .iter()
+        .map(|s| PolicyKey::new(s.algorithm, &s.public_key))
+        .collect();
+    assert!(matches!(MultisigPolicy::new(0, keys.clone()), Err(MultisigError::InvalidPolicy(_))));
+    assert!(matches!(MultisigPolicy::new(3, keys.clone()), Err(MultisigError::InvalidPolicy(_))));
+
+    let duplicated = vec![keys[0].clone(), keys[0].clone()];
+    assert!(matches!(MultisigPolicy::new(1, duplicated), Err(MultisigError::InvalidPolicy(_))));
+
+    let (kem_pk, _) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+    let with_kem = vec![keys[0].clone(), PolicyKey::new(AlgorithmId::MlKem768, &kem_pk)];
+    assert!(matches!(MultisigPolicy::new(1, with_kem), Err(MultisigError::Algorithm(_))));
+}
+
+#[test]
+fn test_collector_deduplicates_and_enforces_threshold() {
+    let s = signers(&[AlgorithmId::MlDsa44, AlgorithmId::MlDsa44, AlgorithmId::Falcon512]);
+    let policy = policy(2, &s);
+    let payload = b"rotate treasury key";
+
+    let mut collector = SignatureCollector::new(&policy, payload);
+    assert!(collector.add(sign(&policy, &s[0], payload)).unwrap());
+    // A second signature from the same signer does not count twice.
+    assert!(!collector.add(sign(&policy, &s[0], payload)).unwrap());
+    assert_eq!(collector.count(), 1);
+    assert!(!collector.is_complete());
+
+    let wrong_payload = sign(&policy, &s[2], b"something else");
+    assert!(matches!(collector.add(wrong_payload), Err(MultisigError::BadSignature(_))));
+
+    let outsider = &signers(&[AlgorithmId::MlDsa44])[0];
+    assert_eq!(
+        policy.sign(outsider.algorithm, &outsider.public_key, &outsider.secret_key, payload),
+        Err(MultisigError::UnknownSigner)
+    );
+
+    collector.add(sign(&policy, &s[2], payload)).unwrap();
+    let bundle = collector.finish().unwrap();
+    assert_eq!(bundle.signatures.len(), 2);
+    assert_eq!(policy.verify(payload, &bundle), Ok(()));
+}
+
+#[test]
+fn test_verify_rejects_forged_bundles() {
+    let s = signers(&[AlgorithmId::MlDsa65, AlgorithmId::MlDsa65, AlgorithmId::MlDsa65]);
+    let policy = policy(2, &s);
+    let payload = b"upgrade contract";
+    let first = sign(&policy, &s[0], payload);
+
+    // The same signer listed twice does not meet a 2-of-3 threshold.
+    let duplicated = MultisigBundle {
+        policy_hash: *policy.policy_hash(),
+        signatures: vec![first.clone(), first.clone()],
+    };
+    assert_eq!(policy.verify(payload, &duplicated), Err(MultisigError::DuplicateSigner(first.signer)));
+
+    let short = MultisigBundle { policy_hash: *policy.policy_hash(), signatures: vec![first.clone()] };
+    assert_eq!(policy.verify(payload, &short), Err(MultisigError::ThresholdNotMet { required: 2, valid: 1 }));
+
+    // Signatures are bound to the policy hash.
+    let other = self::policy(2, &s[..2]);
+    let mut collector = SignatureCollector::new(&other, payload);
+    for signer in &s[..2] {
+        collector.add(sign(&other, signer, payload)).unwrap();
+    }
+    let foreign = collector.finish().unwrap();
+    assert_eq!(policy.verify(payload, &foreign), Err(MultisigError::PolicyMismatch));
+    let relabelled = MultisigBundle { policy_hash: *policy.policy_hash(), ..foreign };
+    assert!(matches!(policy.verify(payload, &relabelled), Err(MultisigError::BadSignature(_))));
+
+    let mut bytes = short.to_bytes();
+    bytes.push(0);
+    assert!(matches!(MultisigBundle::from_bytes(&bytes), Err(MultisigError::Malformed(_))));
+}
@@ -0,0 +1,212 @@
+//! Tests for the Noise Protocol Framework implementation.
+//!
+//! `vectors/noise_cacophony.json` holds the `NN`, `XX` and `IK` X25519
+//! vectors from the cacophony test suite, in cacophony's JSON format. The
+//! hybrid and PQNoise patterns use ML-KEM encapsulation, whose randomness
+//! cannot be fixed, so they are checked with interactive round trips.
+
+use aegis_crypto_core::noise::{
+    HandshakeOptions,
+    HandshakeState,
+    Keypair,
+    NoiseError,
+    NoiseParams,
+    PatternKind,
+    TransportState,
+};
+use serde_json::Value;
+
+const CACOPHONY: &str = include_str!("vectors/noise_cacophony.json");
+
+fn hex_field(vector: &Value, name: &str) -> Option<Vec<u8>> {
+    vector
+        .get(name)
+        .and_then(Value::as_str)
+        .map(|s| hex::decode(s).unwrap())
+}
+
+fn dh_keypair(vector: &Value, name: &str) -> Option<Keypair> {
+    hex_field(vector, name).map(|secret| Keypair::dh_from_secret(&secret.try_into().unwrap()))
+}
+
+fn options(vector: &Value, side: &str) -> HandshakeOptions {
+    HandshakeOptions {
+        prologue: hex_field(vector, &format!("{}_prologue", side)).unwrap_or_default(),
+        s: dh_keypair(vector, &format!("{}_static", side)),
+        rs: hex_field(vector, &format!("{}_remote_static", side)),
+        e: dh_keypair(vector, &format!("{}_ephemeral", side)),
+        e1: None,
+    }
+}
+
+#[test]
+fn test_cacophony_vectors() {
+    let vectors: Value = serde_json::from_str(CACOPHONY).unwrap();
+    let vectors = vectors["vectors"].as_array().unwrap();
+    assert_eq!(vectors.len(), 24);
+
+    for vector in vectors {
+        let name = vector["protocol_name"].as_str().unwrap();
+        let params = NoiseParams::parse(name).unwrap();
+        let mut initiator = HandshakeState::new(&params, true, options(vector, "init")).unwrap();
+        let mut responder = HandshakeState::new(&params, false, options(vector, "resp")).unwrap();
+
+        let messages = vector["messages"].as_array().unwrap();
+        let mut index = 0;
+        while !initiator.is_finished() {
+            let payload = hex_field(&messages[index], "payload").unwrap();
+            let expected = hex_field(&messages[index], "ciphertext").unwrap();
+            let (writer, reader) = if initiator.is_my_turn() {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let ciphertext = writer.write_message(&payload).unwrap();
+            assert_eq!(ciphertext, expected, "{} message {}", name, index);
+            assert_eq!(reader.read_message(&ciphertext).unwrap(), payload);
+            index += 1;
+        }
+        let expected_hash = hex_field(vector, "handshake_hash").unwrap();
+        assert_eq!(initiator.handshake_hash(), expected_hash.as_slice(), "{}", name);
+
+        let mut initiator = initiator.into_transport_mode().unwrap();
+        let mut responder = responder.into_transport_mode().unwrap();
+        for (i, message) in messages.iter().enumerate().skip(index) {
+            let payload = hex_field(message, "payload").unwrap();
+            let (writer, reader) = if i % 2 == 0 {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let ciphertext = writer.write_message(&payload).unwrap();
+            assert_eq!(ciphertext, hex_field(message, "ciphertext").unwrap(), "{} message {}", name, i);
+            assert_eq!(reader.read_message(&ciphertext).unwrap(), payload);
+        }
+    }
+}
+
+/// Run a full handshake with fresh keys and return both transports.
+fn round_trip(name: &str) -> (TransportState, TransportState) {
+    let params = NoiseParams::parse(name).unwrap();
+    let new_static = || match params.pattern.kind {
+        PatternKind::Pq => Keypair::generate_kem(params.kem.unwrap()).unwrap(),
+        _ => Keypair::generate_dh(),
+    };
+    let initiator_static = new_static();
+    let responder_static = new_static();
+    let responder_public = responder_static.public.clone();
+    let initiator_public = initiator_static.public.clone();
+
+    let mut initiator = HandshakeState::new(&params, true, HandshakeOptions {
+        prologue: b"aegis".to_vec(),
+        s: Some(initiator_static),
+        rs: params.pattern.responder_static_known.then(|| responder_public.clone()),
+        ..Default::default()
+    }).unwrap();
+    let mut responder = HandshakeState::new(&params, false, HandshakeOptions {
+        prologue: b"aegis".to_vec(),
+        s: Some(responder_static),
+        ..Default::default()
+    }).unwrap();
+
+    let mut turn = 0u8;
+    while !initiator.is_finished() {
+        let (writer, reader) = if initiator.is_my_turn() {
+            (&mut initiator, &mut responder)
+        } else {
+            (&mut responder, &mut initiator)
+        };
+        assert_eq!(reader.write_message(b""), Err(NoiseError::NotMyTurn));
+        let message = writer.write_message(&[turn; 3]).unwrap();
+        assert_eq!(reader.read_message(&message).unwrap(), vec![turn; 3]);
+        turn += 1;
+    }
+    assert!(responder.is_finished());
+    assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+    assert_eq!(initiator.remote_static(), Some(responder_public.as_slice()));
+    assert_eq!(responder.remote_static(), Some(initiator_public.as_slice()));
+    (initiator.into_transport_mode().unwrap(), responder.into_transport_mode().unwrap())
+}
+
+#[test]
+fn test_hybrid_and_pq_patterns() {
+    for name in [
+        "Noise_XXhfs_25519+MLKEM768_ChaChaPoly_BLAKE2s",
+        "Noise_IKhfs_25519+MLKEM1024_AESGCM_SHA512",
+        "Noise_pqXX_MLKEM768_ChaChaPoly_SHA256",
+        "Noise_pqIK_MLKEM512_AESGCM_BLAKE2b",
+    ] {
+        let (mut initiator, mut responder) = round_trip(name);
+        let message = initiator.write_message(b"over the tunnel").unwrap();
+        assert_eq!(responder.read_message(&message).unwrap(), b"over the tunnel");
+        let message = responder.write_message(b"and back").unwrap();
+        assert_eq!(initiator.read_message(&message).unwrap(), b"and back");
+    }
+}
+
+#[test]
+fn test_nnhfs_message_sizes() {
+    let params = NoiseParams::parse("Noise_NNhfs_25519+MLKEM768_ChaChaPoly_SHA256").unwrap();
+    let mut initiator = HandshakeState::new(&params, true, HandshakeOptions::default()).unwrap();
+    let mut responder = HandshakeState::new(&params, false, HandshakeOptions::default()).unwrap();
+
+    let first = initiator.write_message(b"").unwrap();
+    // X25519 ephemeral plus the ML-KEM-768 ephemeral, unencrypted so far.
+    assert_eq!(first.len(), 32 + 1184);
+    responder.read_message(&first).unwrap();
+    let second = responder.write_message(b"hi").unwrap();
+    // X25519 ephemeral, encrypted ML-KEM-768 ciphertext and the payload.
+    assert_eq!(second.len(), 32 + 1088 + 16 + 2 + 16);
+    assert_eq!(initiator.read_message(&second).unwrap(), b"hi");
+    assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+
+    let mut initiator = initiator.into_transport_mode().unwrap();
+    let mut responder = responder.into_transport_mode().unwrap();
+    assert_eq!(initiator.remote_static(), None);
+    let message = responder.write_message(b"ok").unwrap();
+    assert_eq!(initiator.read_message(&message).unwrap(), b"ok");
+}
+
+#[test]
+fn test_tampered_pq_handshake_fails() {
+    let params = NoiseParams::parse("Noise_pqIK_MLKEM768_ChaChaPoly_SHA256").unwrap();
+    let responder_static = Keypair::generate_kem(params.kem.unwrap()).unwrap();
+    let mut initiator = HandshakeState::new(&params, true, HandshakeOptions {
+        s: Some(Keypair::generate_kem(params.kem.unwrap()).unwrap()),
+        rs: Some(responder_static.public.clone()),
+        ..Default::default()
+    }).unwrap();
+    let mut responder = HandshakeState::new(&params, false, HandshakeOptions {
+        s: Some(responder_static),
+        ..Default::default()
+    }).unwrap();
+
+    let mut message = initiator.write_message(b"secret").unwrap();
+    let last = message.len() - 1;
+    message[last] ^= 1;
+    assert_eq!(responder.read_message(&message), Err(NoiseError::Decrypt));
+
+    // A responder with a different static key cannot decrypt the first message.
+    let mut initiator = HandshakeState::new(&params, true, HandshakeOptions {
+        s: Some(Keypair::generate_kem(params.kem.unwrap()).unwrap()),
+        rs: Some(Keypair::generate_kem(params.kem.unwrap()).unwrap().public.clone()),
+        ..Default::default()
+    }).unwrap();
+    let mut responder = HandshakeState::new(&params, false, HandshakeOptions {
+        s: Some(Keypair::generate_kem(params.kem.unwrap()).unwrap()),
+        ..Default::default()
+    }).unwrap();
+    let message = initiator.write_message(b"secret").unwrap();
+    assert!(responder.read_message(&message).is_err());
+}
+
+#[test]
+fn test_transport_rekey() {
+    let (mut initiator, mut responder) = round_trip("Noise_XX_25519_ChaChaPoly_SHA256");
+    initiator.rekey_outgoing();
+    responder.rekey_incoming();
+    let message = initiator.write_message(b"rekeyed").unwrap();
+    assert_eq!(responder.read_message(&message).unwrap(), b"rekeyed");
+    assert_eq!(initiator.sending_nonce(), 1);
+    assert!(initiator.write_message(&vec![0u8; 65_535]).is_err());
+}
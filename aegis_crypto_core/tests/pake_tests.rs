@@ -0,0 +1,94 @@
+//! Tests for the password-authenticated key exchange, run against an
+//! in-process peer.
+
+use aegis_crypto_core::pake::{ self, Pake, PakeConfig, PakeError };
+use aegis_crypto_core::registry::AlgorithmId;
+
+fn pairing(code: &[u8]) -> PakeConfig {
+    PakeConfig::new(code).with_session_id(b"pairing-7").with_identities(b"phone", b"thermostat")
+}
+
+#[test]
+fn test_sans_io_exchange() {
+    let mut phone = Pake::initiator(pairing(b"123456"));
+    let mut device = Pake::responder(pairing(b"123456"));
+    assert!(phone.wants_write() && !device.wants_write());
+
+    let first = phone.write_message().unwrap();
+    device.read_message(&first).unwrap();
+    let second = device.write_message().unwrap();
+    phone.read_message(&second).unwrap();
+    let third = phone.write_message().unwrap();
+    assert!(phone.is_complete() && !device.is_complete());
+    device.read_message(&third).unwrap();
+
+    let (a, b) = (phone.into_key().unwrap(), device.into_key().unwrap());
+    assert_eq!(a.as_bytes(), b.as_bytes());
+    assert_eq!(a.transcript_hash(), b.transcript_hash());
+}
+
+#[test]
+fn test_keys_are_fresh_per_run() {
+    let (a, _) = pake::loopback(pairing(b"123456"), pairing(b"123456")).unwrap();
+    let (b, _) = pake::loopback(pairing(b"123456"), pairing(b"123456")).unwrap();
+    assert_ne!(a.as_bytes(), b.as_bytes());
+}
+
+#[test]
+fn test_mismatched_inputs_fail_confirmation() {
+    let wrong_code = pake::loopback(pairing(b"123456"), pairing(b"654321"));
+    assert!(matches!(wrong_code, Err(PakeError::BadConfirmation)));
+
+    let other_session = PakeConfig::new(b"123456").with_session_id(b"pairing-8").with_identities(b"phone", b"thermostat");
+    let result = pake::loopback(pairing(b"123456"), other_session);
+    assert!(matches!(result, Err(PakeError::BadConfirmation)));
+
+    let other_peer = PakeConfig::new(b"123456").with_session_id(b"pairing-7").with_identities(b"laptop", b"thermostat");
+    let result = pake::loopback(pairing(b"123456"), other_peer);
+    assert!(matches!(result, Err(PakeError::BadConfirmation)));
+}
+
+#[test]
+fn test_wrong_password_detected_by_initiator_first() {
+    let mut phone = Pake::initiator(pairing(b"123456"));
+    let mut device = Pake::responder(pairing(b"000000"));
+    device.read_message(&phone.write_message().unwrap()).unwrap();
+    let response = device.write_message().unwrap();
+    assert_eq!(phone.read_message(&response), Err(PakeError::BadConfirmation));
+    // A failed run cannot be continued.
+    assert_eq!(phone.write_message(), Err(PakeError::UnexpectedMessage));
+}
+
+#[test]
+fn test_tampered_confirmation_rejected() {
+    let mut phone = Pake::initiator(pairing(b"123456"));
+    let mut device = Pake::responder(pairing(b"123456"));
+    device.read_message(&phone.write_message().unwrap()).unwrap();
+    phone.read_message(&device.write_message().unwrap()).unwrap();
+    let mut confirm = phone.write_message().unwrap();
+    confirm[5] ^= 0x01;
+    assert_eq!(device.read_message(&confirm), Err(PakeError::BadConfirmation));
+    assert!(matches!(device.into_key(), Err(PakeError::NotComplete)));
+}
+
+#[test]
+fn test_invalid_shares_and_ordering() {
+    let mut phone = Pake::initiator(pairing(b"123456"));
+    let first = phone.write_message().unwrap();
+
+    // An identity element share would fix the shared secret.
+    let mut identity_share = first.clone();
+    identity_share[4..36].fill(0);
+    let mut device = Pake::responder(pairing(b"123456"));
+    assert_eq!(device.read_message(&identity_share), Err(PakeError::InvalidPoint));
+
+    let mut device = Pake::responder(pairing(b"123456"));
+    assert_eq!(device.write_message(), Err(PakeError::UnexpectedMessage));
+    let mut device = Pake::responder(pairing(b"123456"));
+    assert!(matches!(device.read_message(&first[..10]), Err(PakeError::Malformed(_))));
+
+    let mut config = pairing(b"123456");
+    config.kem = AlgorithmId::MlKem1024;
+    let mut device = Pake::responder(config);
+    assert_eq!(device.read_message(&first), Err(PakeError::UnsupportedAlgorithm(AlgorithmId::MlKem768)));
+}
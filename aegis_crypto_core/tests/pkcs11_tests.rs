@@ -0,0 +1,396 @@
+//! Tests for the PKCS#11 software token, driven through its function list
+//! the way a PKCS#11 application would.
+
+#![cfg(feature = "pkcs11")]
+
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::{ Mutex, MutexGuard };
+
+use aegis_crypto_core::keystore::argon2::Argon2Params;
+use aegis_crypto_core::keystore::{ DirectoryKeyStore, KeyOptions, KeyStore };
+use aegis_crypto_core::pkcs11::*;
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+
+/// The module is process-wide state, so tests take turns.
+static LOCK: Mutex<()> = Mutex::new(());
+
+const PIN: &[u8] = b"123456";
+
+macro_rules! call {
+    ($list:expr, $name:ident($($arg:expr),*)) => {
+        unsafe { ($list.$name)($($arg),*) }
+    };
+}
+
+fn functions() -> (MutexGuard<'static, ()>, &'static CK_FUNCTION_LIST_3_2) {
+    let guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut list = ptr::null();
+    assert_eq!(unsafe { C_GetFunctionList(&mut list) }, CKR_OK);
+    let list = unsafe { &*list };
+    // A test that failed part-way may have left the module initialized.
+    call!(list, C_Finalize(ptr::null_mut()));
+    (guard, list)
+}
+
+fn attribute<T>(type_: CK_ATTRIBUTE_TYPE, value: &T) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE { type_, pValue: value as *const T as *mut c_void, ulValueLen: std::mem::size_of::<T>() as CK_ULONG }
+}
+
+fn bytes_attribute(type_: CK_ATTRIBUTE_TYPE, value: &[u8]) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE { type_, pValue: value.as_ptr() as *mut c_void, ulValueLen: value.len() as CK_ULONG }
+}
+
+fn mechanism(mechanism: CK_MECHANISM_TYPE) -> CK_MECHANISM {
+    CK_MECHANISM { mechanism, pParameter: ptr::null_mut(), ulParameterLen: 0 }
+}
+
+/// Initialize the module and an in-memory token, and log in on a new
+/// read-write session.
+fn logged_in(f: &CK_FUNCTION_LIST_3_2) -> CK_SESSION_HANDLE {
+    assert_eq!(call!(f, C_Initialize(ptr::null_mut())), CKR_OK);
+    let mut label = [b' '; 32];
+    label[..4].copy_from_slice(b"test");
+    assert_eq!(call!(f, C_InitToken(0, PIN.as_ptr() as *mut _, PIN.len() as CK_ULONG, label.as_mut_ptr())), CKR_OK);
+    let mut session = 0;
+    let flags = CKF_SERIAL_SESSION | CKF_RW_SESSION;
+    assert_eq!(call!(f, C_OpenSession(0, flags, ptr::null_mut(), None, &mut session)), CKR_OK);
+    assert_eq!(call!(f, C_Login(session, CKU_USER, PIN.as_ptr() as *mut _, PIN.len() as CK_ULONG)), CKR_OK);
+    session
+}
+
+fn generate(
+    f: &CK_FUNCTION_LIST_3_2,
+    session: CK_SESSION_HANDLE,
+    generate: CK_MECHANISM_TYPE,
+    parameter_set: CK_ULONG
+) -> (CK_OBJECT_HANDLE, CK_OBJECT_HANDLE) {
+    let mut mechanism = mechanism(generate);
+    let mut public_template = [attribute(CKA_PARAMETER_SET, &parameter_set), bytes_attribute(CKA_LABEL, b"pkcs11")];
+    let (mut public_key, mut private_key) = (0, 0);
+    let rv = call!(f, C_GenerateKeyPair(
+        session,
+        &mut mechanism,
+        public_template.as_mut_ptr(),
+        public_template.len() as CK_ULONG,
+        ptr::null_mut(),
+        0,
+        &mut public_key,
+        &mut private_key
+    ));
+    assert_eq!(rv, CKR_OK);
+    (public_key, private_key)
+}
+
+fn value(
+    f: &CK_FUNCTION_LIST_3_2,
+    session: CK_SESSION_HANDLE,
+    object: CK_OBJECT_HANDLE,
+    type_: CK_ATTRIBUTE_TYPE
+) -> Result<Vec<u8>, CK_RV> {
+    let mut template = [CK_ATTRIBUTE { type_, pValue: ptr::null_mut(), ulValueLen: 0 }];
+    let rv = call!(f, C_GetAttributeValue(session, object, template.as_mut_ptr(), 1));
+    if rv != CKR_OK {
+        return Err(rv);
+    }
+    let mut value = vec![0u8; template[0].ulValueLen as usize];
+    template[0].pValue = value.as_mut_ptr() as *mut c_void;
+    match call!(f, C_GetAttributeValue(session, object, template.as_mut_ptr(), 1)) {
+        CKR_OK => Ok(value),
+        rv => Err(rv),
+    }
+}
+
+fn sign(f: &CK_FUNCTION_LIST_3_2, session: CK_SESSION_HANDLE, mechanism: &mut CK_MECHANISM, key: CK_OBJECT_HANDLE, data: &[u8]) -> Vec<u8> {
+    assert_eq!(call!(f, C_SignInit(session, mechanism, key)), CKR_OK);
+    let data_ptr = data.as_ptr() as *mut CK_BYTE;
+    let mut len = 0;
+    assert_eq!(call!(f, C_Sign(session, data_ptr, data.len() as CK_ULONG, ptr::null_mut(), &mut len)), CKR_OK);
+    let mut signature = vec![0u8; len as usize];
+    let mut short = len - 1;
+    let rv = call!(f, C_Sign(session, data_ptr, data.len() as CK_ULONG, signature.as_mut_ptr(), &mut short));
+    assert_eq!(rv, CKR_BUFFER_TOO_SMALL);
+    let rv = call!(f, C_Sign(session, data_ptr, data.len() as CK_ULONG, signature.as_mut_ptr(), &mut len));
+    assert_eq!(rv, CKR_OK);
+    signature.truncate(len as usize);
+    signature
+}
+
+fn verify(f: &CK_FUNCTION_LIST_3_2, session: CK_SESSION_HANDLE, operate: CK_MECHANISM_TYPE, key: CK_OBJECT_HANDLE, data: &[u8], signature: &[u8]) -> CK_RV {
+    let mut mechanism = mechanism(operate);
+    assert_eq!(call!(f, C_VerifyInit(session, &mut mechanism, key)), CKR_OK);
+    call!(f, C_Verify(
+        session,
+        data.as_ptr() as *mut _,
+        data.len() as CK_ULONG,
+        signature.as_ptr() as *mut _,
+        signature.len() as CK_ULONG
+    ))
+}
+
+#[test]
+fn test_sign_and_verify() {
+    let (_guard, f) = functions();
+    let session = logged_in(f);
+    let cases = [
+        (CKM_ML_DSA_KEY_PAIR_GEN, CKM_ML_DSA, CKP_ML_DSA_65, AlgorithmId::MlDsa65),
+        (CKM_SLH_DSA_KEY_PAIR_GEN, CKM_SLH_DSA, CKP_SLH_DSA_SHA2_128F, AlgorithmId::SlhDsaSha2_128f),
+    ];
+    for (generate_mechanism, operate, parameter_set, algorithm) in cases {
+        let (public_key, private_key) = generate(f, session, generate_mechanism, parameter_set);
+        let public_value = value(f, session, public_key, CKA_VALUE).unwrap();
+        assert_eq!(Some(public_value.len()), algorithm.public_key_len());
+        assert_eq!(value(f, session, private_key, CKA_VALUE), Err(CKR_ATTRIBUTE_SENSITIVE));
+        assert_eq!(value(f, session, public_key, CKA_ID), value(f, session, private_key, CKA_ID));
+
+        let signature = sign(f, session, &mut mechanism(operate), private_key, b"transfer 10 SNRG");
+        assert!(registry::verify(algorithm, &public_value, b"transfer 10 SNRG", &signature));
+        assert_eq!(verify(f, session, operate, public_key, b"transfer 10 SNRG", &signature), CKR_OK);
+        assert_eq!(verify(f, session, operate, public_key, b"transfer 99 SNRG", &signature), CKR_SIGNATURE_INVALID);
+
+        // Multi-part signing covers the same bytes.
+        let mut mechanism = mechanism(operate);
+        assert_eq!(call!(f, C_SignInit(session, &mut mechanism, private_key)), CKR_OK);
+        assert_eq!(call!(f, C_SignUpdate(session, b"transfer ".as_ptr() as *mut _, 9)), CKR_OK);
+        assert_eq!(call!(f, C_SignUpdate(session, b"10 SNRG".as_ptr() as *mut _, 7)), CKR_OK);
+        let mut len = signature.len() as CK_ULONG;
+        let mut multi_part = vec![0u8; len as usize];
+        assert_eq!(call!(f, C_SignFinal(session, multi_part.as_mut_ptr(), &mut len)), CKR_OK);
+        assert_eq!(verify(f, session, operate, public_key, b"transfer 10 SNRG", &multi_part[..len as usize]), CKR_OK);
+    }
+
+    // An empty context is accepted; contexts and deterministic signing are not.
+    let (_, private_key) = generate(f, session, CKM_ML_DSA_KEY_PAIR_GEN, CKP_ML_DSA_44);
+    let mut context = CK_SIGN_ADDITIONAL_CONTEXT { hedgeVariant: CKH_HEDGE_PREFERRED, pContext: ptr::null_mut(), ulContextLen: 0 };
+    let mut with_context = CK_MECHANISM {
+        mechanism: CKM_ML_DSA,
+        pParameter: &mut context as *mut _ as *mut c_void,
+        ulParameterLen: std::mem::size_of::<CK_SIGN_ADDITIONAL_CONTEXT>() as CK_ULONG,
+    };
+    sign(f, session, &mut with_context, private_key, b"message");
+    let mut deterministic = CK_SIGN_ADDITIONAL_CONTEXT { hedgeVariant: CKH_DETERMINISTIC_REQUIRED, ..context };
+    with_context.pParameter = &mut deterministic as *mut _ as *mut c_void;
+    assert_eq!(call!(f, C_SignInit(session, &mut with_context, private_key)), CKR_MECHANISM_PARAM_INVALID);
+
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+}
+
+#[test]
+fn test_encapsulate_and_decapsulate() {
+    let (_guard, f) = functions();
+    let session = logged_in(f);
+    let (public_key, private_key) = generate(f, session, CKM_ML_KEM_KEY_PAIR_GEN, CKP_ML_KEM_768);
+    assert_eq!(value(f, session, public_key, CKA_ENCAPSULATE), Ok(vec![CK_TRUE]));
+
+    let mut mechanism = mechanism(CKM_ML_KEM);
+    let class = CKO_SECRET_KEY;
+    let key_type = CKK_GENERIC_SECRET;
+    let mut template = [attribute(CKA_CLASS, &class), attribute(CKA_KEY_TYPE, &key_type)];
+    let mut len = 0;
+    let mut sender = 0;
+    let rv = call!(f, C_EncapsulateKey(
+        session,
+        &mut mechanism,
+        public_key,
+        template.as_mut_ptr(),
+        2,
+        ptr::null_mut(),
+        &mut len,
+        &mut sender
+    ));
+    assert_eq!(rv, CKR_OK);
+    assert_eq!(len as usize, AlgorithmId::MlKem768.output_len().unwrap());
+    let mut ciphertext = vec![0u8; len as usize];
+    let rv = call!(f, C_EncapsulateKey(
+        session,
+        &mut mechanism,
+        public_key,
+        template.as_mut_ptr(),
+        2,
+        ciphertext.as_mut_ptr(),
+        &mut len,
+        &mut sender
+    ));
+    assert_eq!(rv, CKR_OK);
+
+    let mut receiver = 0;
+    let rv = call!(f, C_DecapsulateKey(
+        session,
+        &mut mechanism,
+        private_key,
+        template.as_mut_ptr(),
+        2,
+        ciphertext.as_mut_ptr(),
+        len,
+        &mut receiver
+    ));
+    assert_eq!(rv, CKR_OK);
+    let shared = value(f, session, sender, CKA_VALUE).unwrap();
+    assert_eq!(shared.len(), 32);
+    assert_eq!(value(f, session, receiver, CKA_VALUE).unwrap(), shared);
+
+    // ML-KEM keys cannot sign.
+    assert_eq!(call!(f, C_SignInit(session, &mut mechanism, private_key)), CKR_KEY_FUNCTION_NOT_PERMITTED);
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+}
+
+#[test]
+fn test_sessions_login_and_objects() {
+    let (_guard, f) = functions();
+    let mut info = unsafe { std::mem::zeroed::<CK_INFO>() };
+    assert_eq!(call!(f, C_GetInfo(&mut info)), CKR_CRYPTOKI_NOT_INITIALIZED);
+    assert_eq!(call!(f, C_Initialize(ptr::null_mut())), CKR_OK);
+    assert_eq!(call!(f, C_Initialize(ptr::null_mut())), CKR_CRYPTOKI_ALREADY_INITIALIZED);
+    assert_eq!(call!(f, C_GetInfo(&mut info)), CKR_OK);
+    assert_eq!(info.cryptokiVersion, CK_VERSION { major: 3, minor: 2 });
+
+    let mut count = 0;
+    assert_eq!(call!(f, C_GetMechanismList(0, ptr::null_mut(), &mut count)), CKR_OK);
+    let mut mechanisms = vec![0; count as usize];
+    assert_eq!(call!(f, C_GetMechanismList(0, mechanisms.as_mut_ptr(), &mut count)), CKR_OK);
+    for expected in [CKM_ML_KEM, CKM_ML_DSA, CKM_SLH_DSA] {
+        assert!(mechanisms.contains(&expected));
+    }
+
+    // The token has no PIN until it is initialized.
+    let mut session = 0;
+    assert_eq!(call!(f, C_OpenSession(0, CKF_SERIAL_SESSION, ptr::null_mut(), None, &mut session)), CKR_OK);
+    assert_eq!(call!(f, C_Login(session, CKU_USER, PIN.as_ptr() as *mut _, 6)), CKR_USER_PIN_NOT_INITIALIZED);
+    assert_eq!(call!(f, C_CloseSession(session)), CKR_OK);
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+
+    let session = logged_in(f);
+    let (public_key, _) = generate(f, session, CKM_ML_DSA_KEY_PAIR_GEN, CKP_ML_DSA_44);
+    assert_eq!(call!(f, C_Logout(session)), CKR_OK);
+    assert_eq!(call!(f, C_Login(session, CKU_USER, b"654321".as_ptr() as *mut _, 6)), CKR_PIN_INCORRECT);
+    assert_eq!(call!(f, C_Login(session, CKU_SO, PIN.as_ptr() as *mut _, 6)), CKR_USER_TYPE_INVALID);
+
+    // Logged out, only public objects are visible and nothing can be generated.
+    let mut mechanism = mechanism(CKM_ML_DSA_KEY_PAIR_GEN);
+    let (mut a, mut b) = (0, 0);
+    let rv = call!(f, C_GenerateKeyPair(session, &mut mechanism, ptr::null_mut(), 0, ptr::null_mut(), 0, &mut a, &mut b));
+    assert_eq!(rv, CKR_USER_NOT_LOGGED_IN);
+    assert_eq!(call!(f, C_FindObjectsInit(session, ptr::null_mut(), 0)), CKR_OK);
+    let mut found = [0; 8];
+    assert_eq!(call!(f, C_FindObjects(session, found.as_mut_ptr(), 8, &mut count)), CKR_OK);
+    assert_eq!(call!(f, C_FindObjectsFinal(session)), CKR_OK);
+    assert_eq!(&found[..count as usize], &[public_key]);
+
+    assert_eq!(call!(f, C_Login(session, CKU_USER, PIN.as_ptr() as *mut _, 6)), CKR_OK);
+    let class = CKO_PRIVATE_KEY;
+    let mut template = [attribute(CKA_CLASS, &class), bytes_attribute(CKA_LABEL, b"pkcs11")];
+    assert_eq!(call!(f, C_FindObjectsInit(session, template.as_mut_ptr(), 2)), CKR_OK);
+    assert_eq!(call!(f, C_FindObjects(session, found.as_mut_ptr(), 8, &mut count)), CKR_OK);
+    assert_eq!(call!(f, C_FindObjectsFinal(session)), CKR_OK);
+    assert_eq!(count, 1);
+    assert_eq!(value(f, session, found[0], CKA_CLASS), Ok(CKO_PRIVATE_KEY.to_ne_bytes().to_vec()));
+
+    assert_eq!(call!(f, C_DestroyObject(session, found[0])), CKR_OK);
+    assert_eq!(value(f, session, public_key, CKA_VALUE), Err(CKR_OBJECT_HANDLE_INVALID));
+    assert_eq!(call!(f, C_CloseAllSessions(0)), CKR_OK);
+    let mut session_info = unsafe { std::mem::zeroed::<CK_SESSION_INFO>() };
+    assert_eq!(call!(f, C_GetSessionInfo(session, &mut session_info)), CKR_SESSION_HANDLE_INVALID);
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+}
+
+#[test]
+fn test_interfaces() {
+    let (_guard, _) = functions();
+    let mut interface = ptr::null_mut();
+    assert_eq!(unsafe { C_GetInterface(ptr::null_mut(), ptr::null_mut(), &mut interface, 0) }, CKR_OK);
+    let list = unsafe { &*((*interface).pFunctionList as *const CK_FUNCTION_LIST_3_2) };
+    assert_eq!(list.version, CK_VERSION { major: 3, minor: 2 });
+
+    let mut version = CK_VERSION { major: 3, minor: 0 };
+    let name = c"PKCS 11".as_ptr() as *mut CK_UTF8CHAR;
+    assert_eq!(unsafe { C_GetInterface(name, &mut version, &mut interface, 0) }, CKR_OK);
+    let list = unsafe { &*((*interface).pFunctionList as *const CK_FUNCTION_LIST_3_2) };
+    assert_eq!(list.version, version);
+    let name = c"Vendor".as_ptr() as *mut CK_UTF8CHAR;
+    assert_eq!(unsafe { C_GetInterface(name, ptr::null_mut(), &mut interface, 0) }, CKR_ARGUMENTS_BAD);
+
+    let mut count = 0;
+    assert_eq!(unsafe { C_GetInterfaceList(ptr::null_mut(), &mut count) }, CKR_OK);
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_directory_keystore_token() {
+    let (_guard, f) = functions();
+    let dir = std::env::temp_dir().join(format!("aegis-pkcs11-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let mut store = DirectoryKeyStore::create(&dir, b"correct horse", Argon2Params::new(64, 1, 1).unwrap()).unwrap();
+    let key = store.generate(AlgorithmId::MlDsa44, KeyOptions::new().with_label("wallet")).unwrap();
+    drop(store);
+
+    std::env::set_var("AEGIS_PKCS11_DIR", &dir);
+    let rv = call!(f, C_Initialize(ptr::null_mut()));
+    std::env::remove_var("AEGIS_PKCS11_DIR");
+    assert_eq!(rv, CKR_OK);
+    let mut token = unsafe { std::mem::zeroed::<CK_TOKEN_INFO>() };
+    assert_eq!(call!(f, C_GetTokenInfo(0, &mut token)), CKR_OK);
+    assert_ne!(token.flags & CKF_TOKEN_INITIALIZED, 0);
+
+    let mut session = 0;
+    assert_eq!(call!(f, C_OpenSession(0, CKF_SERIAL_SESSION, ptr::null_mut(), None, &mut session)), CKR_OK);
+    assert_eq!(call!(f, C_Login(session, CKU_USER, b"wrong horse".as_ptr() as *mut _, 11)), CKR_PIN_INCORRECT);
+    assert_eq!(call!(f, C_Login(session, CKU_USER, b"correct horse".as_ptr() as *mut _, 13)), CKR_OK);
+
+    let mut template = [bytes_attribute(CKA_ID, &key.id.0)];
+    let mut found = [0; 4];
+    let mut count = 0;
+    assert_eq!(call!(f, C_FindObjectsInit(session, template.as_mut_ptr(), 1)), CKR_OK);
+    assert_eq!(call!(f, C_FindObjects(session, found.as_mut_ptr(), 4, &mut count)), CKR_OK);
+    assert_eq!(call!(f, C_FindObjectsFinal(session)), CKR_OK);
+    assert_eq!(count, 2);
+    assert_eq!(value(f, session, found[0], CKA_LABEL), Ok(b"wallet".to_vec()));
+
+    let (public_key, private_key) = match value(f, session, found[0], CKA_CLASS).unwrap() {
+        class if class == CKO_PUBLIC_KEY.to_ne_bytes() => (found[0], found[1]),
+        _ => (found[1], found[0]),
+    };
+    let signature = sign(f, session, &mut mechanism(CKM_ML_DSA), private_key, b"persisted");
+    assert_eq!(verify(f, session, CKM_ML_DSA, public_key, b"persisted", &signature), CKR_OK);
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Load the built shared library and use it through `C_GetInterface`, as
+/// a PKCS#11 application would.
+#[cfg(unix)]
+#[test]
+fn test_load_with_dlopen() {
+    use std::env::consts::{ DLL_PREFIX, DLL_SUFFIX };
+    use std::ffi::CString;
+
+    type GetInterface = unsafe extern "C" fn(*mut CK_UTF8CHAR, *mut CK_VERSION, *mut *mut CK_INTERFACE, CK_FLAGS) -> CK_RV;
+
+    let _guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    // `cargo test` builds the cdylib next to the test binaries in
+    // target/<profile>/deps, with the same features; only `cargo build`
+    // copies it up a level.
+    let exe = std::env::current_exe().unwrap();
+    let path = exe.parent().unwrap().join(format!("{}aegis_crypto_core{}", DLL_PREFIX, DLL_SUFFIX));
+    assert!(path.is_file(), "{} not built", path.display());
+
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let library = unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+    assert!(!library.is_null());
+    let symbol = unsafe { libc::dlsym(library, c"C_GetInterface".as_ptr()) };
+    assert!(!symbol.is_null());
+    let get_interface: GetInterface = unsafe { std::mem::transmute(symbol) };
+
+    let mut interface = ptr::null_mut();
+    assert_eq!(unsafe { get_interface(ptr::null_mut(), ptr::null_mut(), &mut interface, 0) }, CKR_OK);
+    let f = unsafe { &*((*interface).pFunctionList as *const CK_FUNCTION_LIST_3_2) };
+    assert_eq!(f.version, CK_VERSION { major: 3, minor: 2 });
+
+    // The library has its own module state, separate from this binary's.
+    let session = logged_in(f);
+    let (public_key, private_key) = generate(f, session, CKM_ML_DSA_KEY_PAIR_GEN, CKP_ML_DSA_87);
+    let signature = sign(f, session, &mut mechanism(CKM_ML_DSA), private_key, b"loaded");
+    assert_eq!(verify(f, session, CKM_ML_DSA, public_key, b"loaded", &signature), CKR_OK);
+    assert_eq!(call!(f, C_Finalize(ptr::null_mut())), CKR_OK);
+    // The library stays loaded: unloading Rust code with live thread-local
+    // state is not supported on every platform.
+}
@@ -0,0 +1,100 @@
+//! Tests for the crypto-agility policy engine.
+
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use aegis_crypto_core::container;
+use aegis_crypto_core::keystore::{ KeyOptions, KeyStore, MemoryKeyStore };
+use aegis_crypto_core::policy::{ AlgorithmStatus, CryptoPolicy, PolicyError, Purpose, RotationReason };
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+#[test]
+fn test_migration_keeps_data_in_flight_readable() {
+    let t = now();
+    let policy = CryptoPolicy::recommended().deprecate(AlgorithmId::MlKem768, t + 10).expire(AlgorithmId::MlKem768, t + 20);
+
+    let (old_public, old_secret) = registry::keygen(policy.select(Purpose::Encrypt, t).unwrap()).unwrap();
+    let envelope = container::seal(AlgorithmId::MlKem768, &old_public, b"in flight").unwrap();
+
+    // After deprecation new data moves to ML-KEM-1024, old data still opens.
+    assert_eq!(policy.select(Purpose::Encrypt, t + 10), Ok(AlgorithmId::MlKem1024));
+    assert_eq!(policy.open_container(&envelope, &old_secret, t + 10).unwrap(), b"in flight");
+
+    // After expiry it is refused before any decryption happens.
+    assert_eq!(policy.open_container(&envelope, &old_secret, t + 20), Err(PolicyError::Expired(AlgorithmId::MlKem768)));
+}
+
+#[test]
+fn test_verification_only_accepts_permitted_algorithms() {
+    let t = now();
+    let policy = CryptoPolicy::new().allow(Purpose::Sign, AlgorithmId::MlDsa65).unwrap();
+    let (public_key, secret_key) = registry::keygen(AlgorithmId::Falcon512).unwrap();
+    let signature = registry::sign(AlgorithmId::Falcon512, &secret_key, b"tx").unwrap();
+    assert_eq!(
+        policy.verify(AlgorithmId::Falcon512, &public_key, b"tx", &signature, t),
+        Err(PolicyError::NotPermitted(Purpose::Sign, AlgorithmId::Falcon512))
+    );
+
+    let policy = policy.allow(Purpose::Sign, AlgorithmId::Falcon512).unwrap().deprecate(AlgorithmId::Falcon512, t);
+    assert_eq!(policy.verify(AlgorithmId::Falcon512, &public_key, b"tx", &signature, t), Ok(true));
+    assert_eq!(policy.accept(Purpose::Sign, AlgorithmId::Falcon512, t), Ok(AlgorithmStatus::Deprecated));
+    assert_eq!(policy.select(Purpose::Sign, t), Ok(AlgorithmId::MlDsa65));
+
+    let container = container::Container::signature(AlgorithmId::Falcon512, &[], &signature);
+    assert_eq!(policy.verify_container(&container, &public_key, b"tx", t), Ok(true));
+
+    let strict = CryptoPolicy::recommended().with_minimum_security_level(192);
+    assert_eq!(strict.select(Purpose::Sign, t), Ok(AlgorithmId::MlDsa65));
+    assert_eq!(strict.status(Purpose::Sign, AlgorithmId::Falcon512, t), None);
+}
+
+#[test]
+fn test_rotation_from_falcon_to_ml_dsa() {
+    let t = now();
+    let mut store = MemoryKeyStore::new();
+    let wallet = store.generate(AlgorithmId::Falcon512, KeyOptions::new().with_label("wallet")).unwrap();
+    let node = store.generate(AlgorithmId::MlDsa65, KeyOptions::new().with_label("node")).unwrap();
+    store.generate(AlgorithmId::MlKem768, KeyOptions::new().with_label("wallet")).unwrap();
+
+    let policy = CryptoPolicy::recommended();
+    assert_eq!(policy.rotation_due(Purpose::Sign, &store.list().unwrap(), t), Ok(vec![]));
+
+    let policy = policy.deprecate(AlgorithmId::Falcon512, t);
+    let events = policy.rotate(&mut store, Purpose::Sign, t).unwrap();
+    assert_eq!(events.len(), 1);
+    let event = &events[0];
+    assert_eq!((event.key, event.from, event.to), (wallet.id, AlgorithmId::Falcon512, AlgorithmId::MlDsa65));
+    assert_eq!(event.reason, RotationReason::AlgorithmDeprecated);
+
+    let replacement = store.metadata(&event.new_key.unwrap()).unwrap();
+    assert!(replacement.has_label("wallet"));
+    assert_eq!(store.metadata(&wallet.id).unwrap(), wallet);
+
+    // The group now has a good key, so nothing is due and the new key is
+    // the one selected for signing.
+    let keys = store.list().unwrap();
+    assert_eq!(policy.rotate(&mut store, Purpose::Sign, t), Ok(vec![]));
+    let wallet_keys: Vec<_> = keys.iter().filter(|key| key.has_label("wallet")).cloned().collect();
+    assert_eq!(policy.select_key(Purpose::Sign, &wallet_keys, t), Some(&replacement));
+
+    // Key age applies to every group.
+    let aged = policy.with_max_key_age(3600);
+    let due = aged.rotation_due(Purpose::Sign, &keys, t + 3700).unwrap();
+    assert_eq!(due.len(), 2);
+    assert!(due.iter().all(|event| event.reason == RotationReason::MaxAgeExceeded));
+    assert!(due.iter().any(|event| event.key == node.id));
+}
+
+#[test]
+fn test_no_active_algorithm() {
+    let t = now();
+    let policy = CryptoPolicy::new()
+        .allow(Purpose::KeyExchange, AlgorithmId::MlKem768)
+        .unwrap()
+        .expire(AlgorithmId::MlKem768, t);
+    assert_eq!(policy.select(Purpose::KeyExchange, t), Err(PolicyError::NoAlgorithm(Purpose::KeyExchange)));
+    assert_eq!(policy.select(Purpose::Sign, t), Err(PolicyError::NoAlgorithm(Purpose::Sign)));
+}
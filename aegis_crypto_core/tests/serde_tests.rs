@@ -0,0 +1,147 @@
+//! Tests for serde support of keys, ciphertexts, signatures and containers.
+//!
+//! Requires the `serde` feature.
+
+#![cfg(feature = "serde")]
+
+use aegis_crypto_core::{
+    blockchain::{ generate_blockchain_keypair, BlockchainKeyPair, BlockchainTransaction },
+    container::{ self, Container },
+    dilithium_keygen,
+    kyber::traits::KyberPublicKey,
+    kyber768_encapsulate,
+    kyber512_keygen,
+    kyber768_keygen,
+    registry::AlgorithmId,
+    serialization::ExposeSecrets,
+    DilithiumKeyPair,
+    KyberEncapsulated,
+    KyberKeyPair,
+};
+
+#[test]
+fn test_keypair_requires_explicit_opt_in() {
+    let keypair = kyber768_keygen();
+    assert!(serde_json::to_string(&keypair).is_err());
+    assert!(bincode::serialize(&keypair).is_err());
+
+    let json = serde_json::to_string(&ExposeSecrets(&keypair)).unwrap();
+    assert!(json.starts_with(r#"{"public_key":""#));
+    let restored: KyberKeyPair = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.public_key(), keypair.public_key());
+    assert_eq!(restored.secret_key(), keypair.secret_key());
+
+    let signing = dilithium_keygen();
+    let binary = bincode::serialize(&ExposeSecrets(&signing)).unwrap();
+    let restored: DilithiumKeyPair = bincode::deserialize(&binary).unwrap();
+    assert_eq!(restored.secret_key(), signing.secret_key());
+}
+
+#[test]
+fn test_public_types_use_base64url_and_raw_bytes() {
+    let keypair = kyber768_keygen();
+    let public_key = KyberPublicKey(keypair.public_key());
+
+    let json = serde_json::to_string(&public_key).unwrap();
+    assert!(!json.contains('+') && !json.contains('/') && !json.contains('='));
+    // 1184 bytes in unpadded base64url plus the quotes.
+    assert_eq!(json.len(), 1579 + 2);
+    let restored: KyberPublicKey = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.0, public_key.0);
+
+    let binary = bincode::serialize(&public_key).unwrap();
+    assert_eq!(binary.len(), 8 + 1184);
+
+    let encapsulated = kyber768_encapsulate(&keypair.public_key()).unwrap();
+    assert!(serde_json::to_string(&encapsulated).is_err());
+    let json = serde_json::to_string(&ExposeSecrets(&encapsulated)).unwrap();
+    let restored: KyberEncapsulated = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.ciphertext(), encapsulated.ciphertext());
+}
+
+#[test]
+fn test_containers_and_transactions_roundtrip() {
+    let keypair = kyber768_keygen();
+    let sealed = container::seal(AlgorithmId::MlKem768, &keypair.public_key(), b"envelope").unwrap();
+    let json = serde_json::to_string(&sealed).unwrap();
+    let restored: Container = serde_json::from_str(&json).unwrap();
+    assert_eq!(container::open(&restored, &keypair.secret_key()).unwrap(), b"envelope");
+
+    let secret = Container::secret_key(
+        AlgorithmId::MlKem768,
+        &keypair.public_key(),
+        &keypair.secret_key()
+    );
+    assert!(serde_json::to_string(&secret).is_err());
+    let binary = bincode::serialize(&ExposeSecrets(&secret)).unwrap();
+    assert_eq!(bincode::deserialize::<Container>(&binary).unwrap(), secret);
+
+    assert_eq!(serde_json::to_string(&AlgorithmId::MlDsa65).unwrap(), r#""ML-DSA-65""#);
+    assert_eq!(bincode::serialize(&AlgorithmId::MlDsa65).unwrap(), vec![0x02, 0x02]);
+
+    let tx = BlockchainTransaction {
+        chain_id: 1,
+        nonce: 0,
+        from_address: "0xaa".to_string(),
+        to_address: "0xbb".to_string(),
+        amount: 5,
+        fee: 1,
+        encrypted_data: vec![1, 2, 3],
+        signature: vec![0xff; 4],
+        timestamp: 7,
+    };
+    let json = serde_json::to_string(&tx).unwrap();
+    assert!(json.contains(r#""signature":"_____w""#));
+    let restored: BlockchainTransaction = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.encrypted_data, tx.encrypted_data);
+
+    let wallet = generate_blockchain_keypair();
+    assert!(serde_json::to_string(&wallet).is_err());
+    let json = serde_json::to_string(&ExposeSecrets(&wallet)).unwrap();
+    assert!(json.ends_with(&format!(r#","address":"{}"}}"#, wallet.address)));
+    let restored: BlockchainKeyPair = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.signature_secret_key, wallet.signature_secret_key);
+    assert_eq!(restored.address, wallet.address);
+
+    let mut short = wallet.clone();
+    short.signature_secret_key.pop();
+    let json = serde_json::to_string(&ExposeSecrets(&short)).unwrap();
+    let error = serde_json::from_str::<BlockchainKeyPair>(&json).unwrap_err().to_string();
+    assert!(error.contains("signature_secret_key of 4896 bytes"), "{}", error);
+}
+
+#[test]
+fn test_wrong_key_lengths_are_rejected() {
+    // A secret key of one parameter set with the public key of another.
+    let mixed = serde_json::json!({
+        "public_key": base64url(&kyber512_keygen().public_key()),
+        "secret_key": base64url(&kyber768_keygen().secret_key()),
+    });
+    let error = serde_json::from_value::<KyberKeyPair>(mixed).err().unwrap().to_string();
+    assert!(error.contains("invalid length 2400, expected secret_key of 1632 bytes"), "{}", error);
+
+    let encapsulated = kyber768_encapsulate(&kyber768_keygen().public_key()).unwrap();
+    let mut value = serde_json::to_value(ExposeSecrets(&encapsulated)).unwrap();
+    value["shared_secret"] = base64url(&[0u8; 16]).into();
+    assert!(serde_json::from_value::<KyberEncapsulated>(value).is_err());
+}
+
+fn base64url(bytes: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[test]
+fn test_did_document_json_matches_serde() {
+    use aegis_crypto_core::did::{ DidDocument, DidKey };
+
+    let keypair = dilithium_keygen();
+    let did = DidKey::new(AlgorithmId::MlDsa87, &keypair.public_key()).unwrap();
+    let document = did.document();
+    let parsed: DidDocument = serde_json::from_str(&document.to_json()).unwrap();
+    assert_eq!(parsed, document);
+
+    let value = serde_json::to_value(&document).unwrap();
+    assert_eq!(value["verificationMethod"][0]["type"], "Multikey");
+    assert!(value.get("keyAgreement").is_none());
+}
@@ -0,0 +1,85 @@
+//! Tests for Shamir secret sharing, share encoding and commitment checks.
+
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+use aegis_crypto_core::sss::{ self, Share, SssError };
+use sha3::{ Digest, Sha3_256 };
+
+/// Re-seal an edited share encoding with a valid integrity tag, as an
+/// attacker who knows the format could.
+fn retag(mut bytes: Vec<u8>) -> Vec<u8> {
+    bytes.truncate(bytes.len() - 16);
+    let mut hasher = Sha3_256::new();
+    hasher.update(b"aegis-sss-v1 tag");
+    hasher.update(&bytes);
+    let tag = hasher.finalize();
+    bytes.extend_from_slice(&tag[..16]);
+    bytes
+}
+
+#[test]
+fn test_split_secret_key_and_recover() {
+    let (_, secret_key) = registry::keygen(AlgorithmId::MlDsa65).unwrap();
+    let shares = sss::split(&secret_key, 3, 5).unwrap();
+    assert_eq!(shares.len(), 5);
+    assert!(shares.iter().all(|s| s.threshold() == 3 && s.share_count() == 5 && s.verify()));
+
+    let recovered = sss::reconstruct(&[shares[4].clone(), shares[0].clone(), shares[2].clone()]).unwrap();
+    assert_eq!(*recovered, secret_key);
+    let all = sss::reconstruct(&shares).unwrap();
+    assert_eq!(*all, secret_key);
+}
+
+#[test]
+fn test_threshold_and_duplicates() {
+    let seed = [0x42u8; 64];
+    let shares = sss::split(&seed, 3, 5).unwrap();
+    let result = sss::reconstruct(&[shares[0].clone(), shares[1].clone(), shares[1].clone()]);
+    assert_eq!(result.unwrap_err(), SssError::NotEnoughShares { required: 3, got: 2 });
+
+    let single = sss::split(&seed, 1, 1).unwrap();
+    assert_eq!(*sss::reconstruct(&single).unwrap(), seed);
+
+    assert!(matches!(sss::split(&seed, 0, 3), Err(SssError::InvalidParameters(_))));
+    assert!(matches!(sss::split(&seed, 4, 3), Err(SssError::InvalidParameters(_))));
+    assert!(matches!(sss::split(&[], 2, 3), Err(SssError::InvalidParameters(_))));
+}
+
+#[test]
+fn test_encoding_round_trip_and_integrity_tag() {
+    let shares = sss::split(b"custody root seed", 2, 3).unwrap();
+    let text = shares[1].to_string();
+    assert!(text.starts_with("aegis-share1-"));
+    let decoded: Share = text.parse().unwrap();
+    assert_eq!(decoded.to_bytes(), shares[1].to_bytes());
+    assert_eq!(decoded.commitment_digest(), shares[0].commitment_digest());
+
+    let mut bytes = shares[1].to_bytes();
+    bytes[30] ^= 0x01;
+    assert!(matches!(Share::from_bytes(&bytes), Err(SssError::BadTag)));
+    assert!(matches!(Share::from_bytes(&bytes[..10]), Err(SssError::Malformed(_))));
+    assert!("aegis-share2-AAAA".parse::<Share>().is_err());
+
+    let recovered = sss::reconstruct(&[decoded, shares[2].clone()]).unwrap();
+    assert_eq!(&recovered[..], b"custody root seed");
+}
+
+#[test]
+fn test_corrupted_share_detected_by_commitment() {
+    let shares = sss::split(&[7u8; 32], 2, 3).unwrap();
+    // Flip a byte of the share value (offset 4 + 1 + 2 + 16 + 2) and fix
+    // up the integrity tag: only the commitment can catch this.
+    let mut bytes = shares[0].to_bytes();
+    bytes[25] ^= 0x80;
+    let forged = Share::from_bytes(&retag(bytes)).unwrap();
+    assert!(!forged.verify());
+    let result = sss::reconstruct(&[forged, shares[1].clone()]);
+    assert_eq!(result.unwrap_err(), SssError::CorruptShare(1));
+}
+
+#[test]
+fn test_shares_from_different_splits_rejected() {
+    let first = sss::split(&[1u8; 32], 2, 3).unwrap();
+    let second = sss::split(&[1u8; 32], 2, 3).unwrap();
+    let result = sss::reconstruct(&[first[0].clone(), second[1].clone()]);
+    assert_eq!(result.unwrap_err(), SssError::InconsistentShares);
+}
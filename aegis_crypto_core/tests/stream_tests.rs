@@ -0,0 +1,99 @@
+//! Tests for chunked streaming encryption and its `std::io` adapters.
+
+use std::io::{ Cursor, Read, Write };
+
+use aegis_crypto_core::registry::{ self, AlgorithmId };
+use aegis_crypto_core::stream::{ self, DecryptReader, EncryptWriter, StreamDecryptor, StreamEncryptor, StreamError };
+
+const CHUNK: usize = 32;
+const SEALED: usize = CHUNK + 16;
+
+/// Encrypt `len` bytes with small chunks, returning the stream, the offset
+/// of the first chunk, the plaintext and the secret key.
+fn sealed_stream(len: usize) -> (Vec<u8>, usize, Vec<u8>, Vec<u8>) {
+    let (pk, sk) = registry::keygen(AlgorithmId::MlKem768).unwrap();
+    let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+    let mut encryptor = StreamEncryptor::with_chunk_size(AlgorithmId::MlKem768, &pk, CHUNK).unwrap();
+    let mut out = encryptor.update(&plaintext).unwrap();
+    out.extend_from_slice(&encryptor.finalize().unwrap());
+    let header_len = out.len() - len - len.max(1).div_ceil(CHUNK) * 16;
+    (out, header_len, plaintext, sk)
+}
+
+#[test]
+fn test_io_adapters_round_trip() {
+    let (pk, sk) = registry::keygen(AlgorithmId::MlKem1024).unwrap();
+    let plaintext: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+
+    let mut writer = EncryptWriter::new(Vec::new(), AlgorithmId::MlKem1024, &pk).unwrap();
+    for piece in plaintext.chunks(10_000) {
+        writer.write_all(piece).unwrap();
+    }
+    let ciphertext = writer.finish().unwrap();
+
+    let mut reader = DecryptReader::new(Cursor::new(&ciphertext), &sk);
+    let mut decrypted = Vec::new();
+    reader.read_to_end(&mut decrypted).unwrap();
+    assert_eq!(decrypted, plaintext);
+    assert_eq!(stream::decrypt(&sk, &ciphertext).unwrap(), plaintext);
+}
+
+#[test]
+fn test_reader_reports_truncation() {
+    let (ciphertext, header_len, _, sk) = sealed_stream(3 * CHUNK);
+    let cut = &ciphertext[..header_len + 2 * SEALED];
+    let mut reader = DecryptReader::new(Cursor::new(cut), &sk);
+    let mut decrypted = Vec::new();
+    let err = reader.read_to_end(&mut decrypted).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(decrypted.len(), CHUNK);
+}
+
+#[test]
+fn test_reordered_and_swapped_chunks_rejected() {
+    let (ciphertext, header_len, _, sk) = sealed_stream(3 * CHUNK + 5);
+    let chunk = |i: usize| header_len + i * SEALED..header_len + (i + 1) * SEALED;
+
+    let mut swapped = ciphertext.clone();
+    swapped[chunk(0)].copy_from_slice(&ciphertext[chunk(1)]);
+    swapped[chunk(1)].copy_from_slice(&ciphertext[chunk(0)]);
+    assert_eq!(stream::decrypt(&sk, &swapped), Err(StreamError::DecryptionFailed(0)));
+
+    let mut dropped = ciphertext[..chunk(1).start].to_vec();
+    dropped.extend_from_slice(&ciphertext[chunk(2).start..]);
+    assert_eq!(stream::decrypt(&sk, &dropped), Err(StreamError::DecryptionFailed(1)));
+
+    // A chunk spliced in from another stream under a different key.
+    let (other, other_header, _, _) = sealed_stream(3 * CHUNK + 5);
+    let mut spliced = ciphertext.clone();
+    spliced[chunk(2)].copy_from_slice(&other[other_header + 2 * SEALED..other_header + 3 * SEALED]);
+    assert_eq!(stream::decrypt(&sk, &spliced), Err(StreamError::DecryptionFailed(2)));
+}
+
+#[test]
+fn test_header_and_trailing_data_bound() {
+    let (ciphertext, header_len, plaintext, sk) = sealed_stream(50);
+
+    // Changing the advertised chunk size changes the key.
+    let mut header = ciphertext.clone();
+    header[10] ^= 1;
+    assert!(stream::decrypt(&sk, &header).is_err());
+
+    let mut extended = ciphertext.clone();
+    extended.extend_from_slice(&[0u8; SEALED]);
+    assert!(stream::decrypt(&sk, &extended).is_err());
+    let (full, _, _, full_sk) = sealed_stream(2 * CHUNK);
+    let mut extended = full.clone();
+    extended.extend_from_slice(&full[full.len() - SEALED..]);
+    assert_eq!(stream::decrypt(&full_sk, &extended), Err(StreamError::TrailingData));
+
+    let mut decryptor = StreamDecryptor::new(&sk);
+    let mut out = Vec::new();
+    for byte in &ciphertext {
+        out.extend_from_slice(&decryptor.update(std::slice::from_ref(byte)).unwrap());
+    }
+    out.extend_from_slice(&decryptor.finalize().unwrap());
+    assert_eq!(out, plaintext);
+    assert_eq!(decryptor.update(b"more"), Err(StreamError::Finished));
+    assert!(header_len > 1088);
+}
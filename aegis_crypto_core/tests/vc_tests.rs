@@ -0,0 +1,162 @@
+//! Tests for W3C verifiable credentials with post-quantum proofs.
+//!
+//! Requires the `vc` feature.
+
+#![cfg(feature = "vc")]
+
+use aegis_crypto_core::{
+    dilithium44_keygen,
+    dilithium65_keygen,
+    registry::AlgorithmId,
+    sphincsplus_sha2_128f_keygen,
+    vc::{ self, Credential, DidSigner, StatusEntry, StatusList, VcError },
+};
+use serde_json::json;
+
+fn mldsa65_signer() -> DidSigner {
+    let keypair = dilithium65_keygen();
+    DidSigner::new(AlgorithmId::MlDsa65, &keypair.public_key(), &keypair.secret_key()).unwrap()
+}
+
+fn degree(issuer: &DidSigner) -> Credential {
+    let mut credential = Credential::new(
+        &issuer.did().did(),
+        json!({ "id": "did:example:alice", "degree": { "name": "BSc Cryptography" } })
+    );
+    credential.types.push("UniversityDegreeCredential".into());
+    credential
+}
+
+#[test]
+fn test_issue_and_verify_credential() {
+    let issuer = mldsa65_signer();
+    let credential = vc::issue_credential(&degree(&issuer), &issuer).unwrap();
+    assert_eq!(credential["proof"]["cryptosuite"], "mldsa65-jcs-2024");
+    assert_eq!(credential["proof"]["proofPurpose"], "assertionMethod");
+    vc::verify_credential(&credential, &[]).unwrap();
+
+    // Any change to the signed document invalidates the proof.
+    let mut tampered = credential.clone();
+    tampered["credentialSubject"]["degree"]["name"] = "PhD Cryptography".into();
+    assert!(matches!(vc::verify_credential(&tampered, &[]), Err(VcError::InvalidProof(_))));
+
+    // A proof by one DID cannot vouch for a credential claiming another issuer.
+    let other = mldsa65_signer();
+    assert_eq!(vc::issue_credential(&degree(&other), &issuer), Err(VcError::IssuerMismatch));
+}
+
+#[test]
+fn test_validity_period() {
+    let issuer = mldsa65_signer();
+    let mut unsigned = degree(&issuer);
+    unsigned.valid_from = 1_700_000_000;
+    unsigned.valid_until = Some(1_800_000_000);
+    let credential = vc::issue_credential(&unsigned, &issuer).unwrap();
+
+    vc::verify_credential_at(&credential, &[], 1_750_000_000).unwrap();
+    assert_eq!(vc::verify_credential_at(&credential, &[], 1_600_000_000), Err(VcError::NotYetValid));
+    assert_eq!(vc::verify_credential_at(&credential, &[], 1_900_000_000), Err(VcError::Expired));
+}
+
+#[test]
+fn test_revocation_with_status_list() {
+    let issuer = mldsa65_signer();
+    let list_id = "https://issuer.example/status/1";
+    let mut unsigned = degree(&issuer);
+    unsigned.credential_status.push(StatusEntry::revocation(list_id, 42));
+    let credential = vc::issue_credential(&unsigned, &issuer).unwrap();
+
+    let mut list = StatusList::new(0);
+    let publish = |list: &StatusList| {
+        let status = vc::status_list_credential(list_id, &issuer.did().did(), "revocation", list);
+        vc::issue_credential(&status, &issuer).unwrap()
+    };
+
+    vc::verify_credential(&credential, &[publish(&list)]).unwrap();
+    assert!(matches!(vc::verify_credential(&credential, &[]), Err(VcError::Status(_))));
+
+    list.set(42, true).unwrap();
+    assert_eq!(
+        vc::verify_credential(&credential, &[publish(&list)]),
+        Err(VcError::StatusSet("revocation".into()))
+    );
+
+    // A status list signed by someone other than the issuer is not trusted.
+    let other = mldsa65_signer();
+    let forged = vc::status_list_credential(list_id, &other.did().did(), "revocation", &StatusList::new(0));
+    let forged = vc::issue_credential(&forged, &other).unwrap();
+    assert!(matches!(vc::verify_credential(&credential, &[forged]), Err(VcError::Status(_))));
+}
+
+#[test]
+fn test_presentation_with_challenge() {
+    let issuer = mldsa65_signer();
+    let holder = mldsa65_signer();
+    let credential = vc::issue_credential(&degree(&issuer), &issuer).unwrap();
+
+    let presentation = vc::create_presentation(
+        &holder,
+        &[credential],
+        "nonce-123",
+        Some("verifier.example")
+    ).unwrap();
+    vc::verify_presentation(&presentation, "nonce-123", Some("verifier.example"), &[]).unwrap();
+    assert!(vc::verify_presentation(&presentation, "nonce-456", Some("verifier.example"), &[]).is_err());
+    assert!(vc::verify_presentation(&presentation, "nonce-123", Some("other.example"), &[]).is_err());
+    assert!(vc::verify_presentation(&presentation, "nonce-123", None, &[]).is_err());
+
+    let mut swapped = presentation.clone();
+    swapped["holder"] = issuer.did().did().into();
+    assert!(vc::verify_presentation(&swapped, "nonce-123", Some("verifier.example"), &[]).is_err());
+}
+
+#[test]
+fn test_jwt_credential_and_presentation() {
+    let keypair = dilithium44_keygen();
+    let issuer = DidSigner::new(AlgorithmId::MlDsa44, &keypair.public_key(), &keypair.secret_key()).unwrap();
+    let holder = mldsa65_signer();
+
+    let token = vc::issue_jwt_credential(&degree(&issuer), &issuer).unwrap();
+    let credential = vc::verify_jwt_credential(&token, &[]).unwrap();
+    assert_eq!(credential["credentialSubject"]["id"], "did:example:alice");
+
+    let header = aegis_crypto_core::jose::decode_compact(&token).unwrap();
+    assert_eq!(header.typ(), Some("vc+jwt"));
+    assert_eq!(header.header["alg"], "ML-DSA-44");
+
+    let mut parts: Vec<&str> = token.split('.').collect();
+    parts[2] = "AAAA";
+    assert!(vc::verify_jwt_credential(&parts.join("."), &[]).is_err());
+
+    let presentation = vc::issue_jwt_presentation(
+        &holder,
+        &[vc::enveloped_credential(&token)],
+        "n-1",
+        Some("verifier.example")
+    ).unwrap();
+    let verified = vc::verify_jwt_presentation(&presentation, "n-1", Some("verifier.example"), &[]).unwrap();
+    assert_eq!(verified["holder"], holder.did().did());
+    assert!(vc::verify_jwt_presentation(&presentation, "n-2", Some("verifier.example"), &[]).is_err());
+
+    // Enveloped JWT credentials are also accepted in Data Integrity presentations.
+    let presentation = vc::create_presentation(&holder, &[vc::enveloped_credential(&token)], "c", None).unwrap();
+    vc::verify_presentation(&presentation, "c", None, &[]).unwrap();
+}
+
+#[test]
+fn test_slh_dsa_cryptosuite() {
+    let keypair = sphincsplus_sha2_128f_keygen();
+    let issuer = DidSigner::new(
+        AlgorithmId::SlhDsaSha2_128f,
+        &keypair.public_key(),
+        &keypair.secret_key()
+    ).unwrap();
+    let credential = vc::issue_credential(&degree(&issuer), &issuer).unwrap();
+    assert_eq!(credential["proof"]["cryptosuite"], "slhdsa-sha2-128f-jcs-2024");
+    vc::verify_credential(&credential, &[]).unwrap();
+
+    // The cryptosuite must match the key algorithm.
+    let mut relabeled = credential.clone();
+    relabeled["proof"]["cryptosuite"] = "slhdsa-shake-128f-jcs-2024".into();
+    assert!(vc::verify_credential(&relabeled, &[]).is_err());
+}
@@ -41,8 +41,8 @@
 
 /* see api.h */
 int
-PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair(
-    uint8_t *pk, uint8_t *sk) {
+PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair_derand(
+    uint8_t *pk, uint8_t *sk, const uint8_t *seed) {
     union {
         uint8_t b[FALCON_KEYGEN_TEMP_10];
         uint64_t dummy_u64;
@@ -50,16 +50,14 @@ PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair(
     } tmp;
     int8_t f[1024], g[1024], F[1024];
     uint16_t h[1024];
-    unsigned char seed[48];
     inner_shake256_context rng;
     size_t u, v;
 
     /*
      * Generate key pair.
      */
-    randombytes(seed, sizeof seed);
     inner_shake256_init(&rng);
-    inner_shake256_inject(&rng, seed, sizeof seed);
+    inner_shake256_inject(&rng, seed, 48);
     inner_shake256_flip(&rng);
     PQCLEAN_FALCON1024_CLEAN_keygen(&rng, f, g, F, NULL, h, 10, tmp.b);
     inner_shake256_ctx_release(&rng);
@@ -108,6 +106,16 @@ PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair(
     return 0;
 }
 
+/* see api.h */
+int
+PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair(
+    uint8_t *pk, uint8_t *sk) {
+    unsigned char seed[48];
+
+    randombytes(seed, sizeof seed);
+    return PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair_derand(pk, sk, seed);
+}
+
 /*
  * Compute the signature. nonce[] receives the nonce and must have length
  * NONCELEN bytes. sigbuf[] receives the signature value (without nonce
@@ -21,6 +21,13 @@
 int PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair(
     uint8_t *pk, uint8_t *sk);
 
+/*
+ * Generate a key pair deterministically from a 48-byte seed. Key sizes
+ * and return value are as for PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair().
+ */
+int PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair_derand(
+    uint8_t *pk, uint8_t *sk, const uint8_t *seed);
+
 /*
  * Compute a signature on a provided message (m, mlen), with a given
  * private key (sk). Signature is written in sig[], with length written
@@ -38,8 +38,8 @@
 
 /* see api.h */
 int
-PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair(
-    uint8_t *pk, uint8_t *sk) {
+PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair_derand(
+    uint8_t *pk, uint8_t *sk, const uint8_t *seed) {
     union {
         uint8_t b[28 * FALCON_N];
         uint64_t dummy_u64;
@@ -47,16 +47,14 @@ PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair(
     } tmp;
     int8_t f[FALCON_N], g[FALCON_N], F[FALCON_N];
     uint16_t h[FALCON_N];
-    unsigned char seed[48];
     inner_shake256_context rng;
     size_t u, v;
 
     /*
      * Generate key pair.
      */
-    randombytes(seed, sizeof seed);
     inner_shake256_init(&rng);
-    inner_shake256_inject(&rng, seed, sizeof seed);
+    inner_shake256_inject(&rng, seed, 48);
     inner_shake256_flip(&rng);
     PQCLEAN_FALCONPADDED512_AARCH64_keygen(&rng, f, g, F, NULL, h, FALCON_LOGN, tmp.b);
     inner_shake256_ctx_release(&rng);
@@ -105,6 +103,16 @@ PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair(
     return 0;
 }
 
+/* see api.h */
+int
+PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair(
+    uint8_t *pk, uint8_t *sk) {
+    unsigned char seed[48];
+
+    randombytes(seed, sizeof seed);
+    return PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair_derand(pk, sk, seed);
+}
+
 /*
  * Compute the signature. nonce[] receives the nonce and must have length
  * NONCELEN bytes. sigbuf[] receives the signature value (without nonce
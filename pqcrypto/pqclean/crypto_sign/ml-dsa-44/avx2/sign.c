@@ -32,18 +32,19 @@ static inline void polyvec_matrix_expand_row(polyvecl **row, polyvecl buf[2], co
 }
 
 /*************************************************
-* Name:        PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair
+* Name:        PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair_derand
 *
-* Description: Generates public and private key.
+* Description: Generates public and private key from a seed.
 *
 * Arguments:   - uint8_t *pk: pointer to output public key (allocated
 *                             array of PQCLEAN_MLDSA44_AVX2_CRYPTO_PUBLICKEYBYTES bytes)
 *              - uint8_t *sk: pointer to output private key (allocated
 *                             array of PQCLEAN_MLDSA44_AVX2_CRYPTO_SECRETKEYBYTES bytes)
+*              - const uint8_t *seed: pointer to input seed (SEEDBYTES bytes)
 *
 * Returns 0 (success)
 **************************************************/
-int PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair(uint8_t *pk, uint8_t *sk) {
+int PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair_derand(uint8_t *pk, uint8_t *sk, const uint8_t *seed) {
     unsigned int i;
     uint8_t seedbuf[2 * SEEDBYTES + CRHBYTES];
     const uint8_t *rho, *rhoprime, *key;
@@ -52,8 +53,8 @@ int PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair(uint8_t *pk, uint8_t *sk) {
     polyveck s2;
     poly t1, t0;
 
-    /* Get randomness for rho, rhoprime and key */
-    randombytes(seedbuf, SEEDBYTES);
+    /* Expand the seed into rho, rhoprime and key */
+    memcpy(seedbuf, seed, SEEDBYTES);
     seedbuf[SEEDBYTES + 0] = K;
     seedbuf[SEEDBYTES + 1] = L;
     shake256(seedbuf, 2 * SEEDBYTES + CRHBYTES, seedbuf, SEEDBYTES + 2);
@@ -105,6 +106,24 @@ int PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair(uint8_t *pk, uint8_t *sk) {
     return 0;
 }
 
+/*************************************************
+* Name:        PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair
+*
+* Description: Generates public and private key.
+*
+* Arguments:   - uint8_t *pk: pointer to output public key (allocated
+*                             array of PQCLEAN_MLDSA44_AVX2_CRYPTO_PUBLICKEYBYTES bytes)
+*              - uint8_t *sk: pointer to output private key (allocated
+*                             array of PQCLEAN_MLDSA44_AVX2_CRYPTO_SECRETKEYBYTES bytes)
+*
+* Returns 0 (success)
+**************************************************/
+int PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair(uint8_t *pk, uint8_t *sk) {
+    uint8_t seed[SEEDBYTES];
+    randombytes(seed, SEEDBYTES);
+    return PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair_derand(pk, sk, seed);
+}
+
 /*************************************************
 * Name:        crypto_sign_signature
 *
@@ -17,6 +17,8 @@
 
 int PQCLEAN_MLDSA65_AARCH64_crypto_sign_keypair(uint8_t *pk, uint8_t *sk);
 
+int PQCLEAN_MLDSA65_AARCH64_crypto_sign_keypair_derand(uint8_t *pk, uint8_t *sk, const uint8_t *seed);
+
 int PQCLEAN_MLDSA65_AARCH64_crypto_sign_signature_ctx(
     uint8_t *sig, size_t *siglen,
     const uint8_t *m, size_t mlen,
@@ -202,6 +202,49 @@ pub fn keypair() -> (PublicKey, SecretKey) {
     gen_keypair!(PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair)
 }
 
+/// Get the number of bytes of the seed taken by [`keypair_from_seed`]
+pub const fn seed_bytes() -> usize {
+    48
+}
+
+macro_rules! gen_keypair_from_seed {
+    ($variant:ident, $seed:ident) => {{
+        let mut pk = PublicKey::new();
+        let mut sk = SecretKey::new();
+        assert_eq!(
+            unsafe { ffi::$variant(pk.0.as_mut_ptr(), sk.0.as_mut_ptr(), $seed.as_ptr()) },
+            0
+        );
+        (pk, sk)
+    }};
+}
+
+/// Deterministically generate a falcon-padded-1024 keypair from a 48-byte seed
+pub fn keypair_from_seed(seed: &[u8; 48]) -> (PublicKey, SecretKey) {
+    #[cfg(all(enable_x86_avx2, feature = "avx2"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return gen_keypair_from_seed!(
+                PQCLEAN_FALCONPADDED1024_AVX2_crypto_sign_keypair_derand,
+                seed
+            );
+        }
+    }
+    #[cfg(all(enable_aarch64_neon, feature = "neon"))]
+    {
+        if true {
+            return gen_keypair_from_seed!(
+                PQCLEAN_FALCONPADDED1024_AARCH64_crypto_sign_keypair_derand,
+                seed
+            );
+        }
+    }
+    gen_keypair_from_seed!(
+        PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair_derand,
+        seed
+    )
+}
+
 macro_rules! gen_signature {
     ($variant:ident, $msg:ident, $sk:ident) => {{
         let max_len = $msg.len() + signature_bytes();
@@ -410,4 +453,27 @@ mod test {
         assert!(verify_detached_signature(&sig, &message, &pk).is_ok());
         assert!(!verify_detached_signature(&sig, &message[..message.len() - 1], &pk).is_ok());
     }
+
+    #[test]
+    pub fn test_keypair_from_seed() {
+        let seed = [7u8; 48];
+        let (pk, sk) = keypair_from_seed(&seed);
+        let (pk2, sk2) = keypair_from_seed(&seed);
+        assert!(pk == pk2 && sk == sk2);
+        assert!(keypair_from_seed(&[8u8; 48]).0 != pk);
+
+        // Every implementation derives the same keys.
+        let (mut clean_pk, mut clean_sk) = (PublicKey::new(), SecretKey::new());
+        assert_eq!(
+            unsafe {
+                ffi::PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair_derand(
+                    clean_pk.0.as_mut_ptr(),
+                    clean_sk.0.as_mut_ptr(),
+                    seed.as_ptr(),
+                )
+            },
+            0
+        );
+        assert!(clean_pk == pk && clean_sk == sk);
+    }
 }
@@ -90,6 +90,11 @@ pub const PQCLEAN_FALCONPADDED1024_AARCH64_CRYPTO_BYTES: usize = 1280;
 #[link(name = "falcon-512_clean")]
 extern "C" {
     pub fn PQCLEAN_FALCON512_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_FALCON512_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_FALCON512_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -127,6 +132,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCON512_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_FALCON512_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCON512_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -167,6 +178,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCON512_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_FALCON512_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCON512_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -204,6 +221,11 @@ extern "C" {
 #[link(name = "falcon-padded-512_clean")]
 extern "C" {
     pub fn PQCLEAN_FALCONPADDED512_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_FALCONPADDED512_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_FALCONPADDED512_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -241,6 +263,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCONPADDED512_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_FALCONPADDED512_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCONPADDED512_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -281,6 +309,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCONPADDED512_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -318,6 +352,11 @@ extern "C" {
 #[link(name = "falcon-1024_clean")]
 extern "C" {
     pub fn PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_FALCON1024_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_FALCON1024_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -355,6 +394,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCON1024_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_FALCON1024_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCON1024_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -395,6 +440,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCON1024_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_FALCON1024_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCON1024_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -432,6 +483,11 @@ extern "C" {
 #[link(name = "falcon-padded-1024_clean")]
 extern "C" {
     pub fn PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_FALCONPADDED1024_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -469,6 +525,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCONPADDED1024_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_FALCONPADDED1024_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_FALCONPADDED1024_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -509,6 +571,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCONPADDED1024_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_FALCONPADDED1024_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_FALCONPADDED1024_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
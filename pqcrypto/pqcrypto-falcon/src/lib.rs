@@ -29,28 +29,34 @@ pub mod ffi;
 
 pub use crate::falcon1024::{
     detached_sign as falcon1024_detached_sign, keypair as falcon1024_keypair,
-    open as falcon1024_open, public_key_bytes as falcon1024_public_key_bytes,
-    secret_key_bytes as falcon1024_secret_key_bytes, sign as falcon1024_sign,
-    signature_bytes as falcon1024_signature_bytes,
+    keypair_from_seed as falcon1024_keypair_from_seed, open as falcon1024_open,
+    public_key_bytes as falcon1024_public_key_bytes,
+    secret_key_bytes as falcon1024_secret_key_bytes, seed_bytes as falcon1024_seed_bytes,
+    sign as falcon1024_sign, signature_bytes as falcon1024_signature_bytes,
     verify_detached_signature as falcon1024_verify_detached_signature,
 };
 pub use crate::falcon512::{
-    detached_sign as falcon512_detached_sign, keypair as falcon512_keypair, open as falcon512_open,
+    detached_sign as falcon512_detached_sign, keypair as falcon512_keypair,
+    keypair_from_seed as falcon512_keypair_from_seed, open as falcon512_open,
     public_key_bytes as falcon512_public_key_bytes, secret_key_bytes as falcon512_secret_key_bytes,
-    sign as falcon512_sign, signature_bytes as falcon512_signature_bytes,
+    seed_bytes as falcon512_seed_bytes, sign as falcon512_sign,
+    signature_bytes as falcon512_signature_bytes,
     verify_detached_signature as falcon512_verify_detached_signature,
 };
 pub use crate::falconpadded1024::{
     detached_sign as falconpadded1024_detached_sign, keypair as falconpadded1024_keypair,
-    open as falconpadded1024_open, public_key_bytes as falconpadded1024_public_key_bytes,
-    secret_key_bytes as falconpadded1024_secret_key_bytes, sign as falconpadded1024_sign,
+    keypair_from_seed as falconpadded1024_keypair_from_seed, open as falconpadded1024_open,
+    public_key_bytes as falconpadded1024_public_key_bytes,
+    secret_key_bytes as falconpadded1024_secret_key_bytes,
+    seed_bytes as falconpadded1024_seed_bytes, sign as falconpadded1024_sign,
     signature_bytes as falconpadded1024_signature_bytes,
     verify_detached_signature as falconpadded1024_verify_detached_signature,
 };
 pub use crate::falconpadded512::{
     detached_sign as falconpadded512_detached_sign, keypair as falconpadded512_keypair,
-    open as falconpadded512_open, public_key_bytes as falconpadded512_public_key_bytes,
-    secret_key_bytes as falconpadded512_secret_key_bytes, sign as falconpadded512_sign,
-    signature_bytes as falconpadded512_signature_bytes,
+    keypair_from_seed as falconpadded512_keypair_from_seed, open as falconpadded512_open,
+    public_key_bytes as falconpadded512_public_key_bytes,
+    secret_key_bytes as falconpadded512_secret_key_bytes, seed_bytes as falconpadded512_seed_bytes,
+    sign as falconpadded512_sign, signature_bytes as falconpadded512_signature_bytes,
     verify_detached_signature as falconpadded512_verify_detached_signature,
 };
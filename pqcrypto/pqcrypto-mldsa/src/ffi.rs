@@ -71,6 +71,11 @@ pub const PQCLEAN_MLDSA87_AARCH64_CRYPTO_BYTES: usize = 4627;
 #[link(name = "ml-dsa-44_clean")]
 extern "C" {
     pub fn PQCLEAN_MLDSA44_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLDSA44_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLDSA44_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -144,6 +149,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLDSA44_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA44_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -224,6 +235,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA44_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLDSA44_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA44_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -301,6 +318,11 @@ extern "C" {
 #[link(name = "ml-dsa-65_clean")]
 extern "C" {
     pub fn PQCLEAN_MLDSA65_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLDSA65_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLDSA65_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -374,6 +396,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA65_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLDSA65_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA65_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -454,6 +482,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA65_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLDSA65_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA65_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -531,6 +565,11 @@ extern "C" {
 #[link(name = "ml-dsa-87_clean")]
 extern "C" {
     pub fn PQCLEAN_MLDSA87_CLEAN_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLDSA87_CLEAN_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLDSA87_CLEAN_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -604,6 +643,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA87_AVX2_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLDSA87_AVX2_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLDSA87_AVX2_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -684,6 +729,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA87_AARCH64_crypto_sign_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLDSA87_AARCH64_crypto_sign_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLDSA87_AARCH64_crypto_sign(
         sm: *mut u8,
         smlen: *mut usize,
@@ -86,6 +86,11 @@ pub const PQCLEAN_MLKEM1024_AARCH64_CRYPTO_BYTES: usize = 32;
 #[link(name = "ml-kem-512_clean")]
 extern "C" {
     pub fn PQCLEAN_MLKEM512_CLEAN_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLKEM512_CLEAN_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLKEM512_CLEAN_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int;
     pub fn PQCLEAN_MLKEM512_CLEAN_crypto_kem_dec(
         ss: *mut u8,
@@ -100,6 +105,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM512_AVX2_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLKEM512_AVX2_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM512_AVX2_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int;
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM512_AVX2_crypto_kem_dec(ss: *mut u8, ct: *const u8, sk: *const u8)
@@ -112,6 +123,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM512_AARCH64_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLKEM512_AARCH64_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM512_AARCH64_crypto_kem_enc(
         ct: *mut u8,
         ss: *mut u8,
@@ -128,6 +145,11 @@ extern "C" {
 #[link(name = "ml-kem-768_clean")]
 extern "C" {
     pub fn PQCLEAN_MLKEM768_CLEAN_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLKEM768_CLEAN_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLKEM768_CLEAN_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int;
     pub fn PQCLEAN_MLKEM768_CLEAN_crypto_kem_dec(
         ss: *mut u8,
@@ -142,6 +164,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM768_AVX2_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLKEM768_AVX2_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM768_AVX2_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int;
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM768_AVX2_crypto_kem_dec(ss: *mut u8, ct: *const u8, sk: *const u8)
@@ -154,6 +182,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM768_AARCH64_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLKEM768_AARCH64_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM768_AARCH64_crypto_kem_enc(
         ct: *mut u8,
         ss: *mut u8,
@@ -170,6 +204,11 @@ extern "C" {
 #[link(name = "ml-kem-1024_clean")]
 extern "C" {
     pub fn PQCLEAN_MLKEM1024_CLEAN_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
+    pub fn PQCLEAN_MLKEM1024_CLEAN_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
     pub fn PQCLEAN_MLKEM1024_CLEAN_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8)
         -> c_int;
     pub fn PQCLEAN_MLKEM1024_CLEAN_crypto_kem_dec(
@@ -185,6 +224,12 @@ extern "C" {
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM1024_AVX2_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_x86_avx2)]
+    pub fn PQCLEAN_MLKEM1024_AVX2_crypto_kem_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM1024_AVX2_crypto_kem_enc(ct: *mut u8, ss: *mut u8, pk: *const u8) -> c_int;
     #[cfg(enable_x86_avx2)]
     pub fn PQCLEAN_MLKEM1024_AVX2_crypto_kem_dec(
@@ -200,6 +245,12 @@ extern "C" {
     #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM1024_AARCH64_crypto_kem_keypair(pk: *mut u8, sk: *mut u8) -> c_int;
     #[cfg(enable_aarch64_neon)]
+    pub fn PQCLEAN_MLKEM1024_AARCH64_keypair_derand(
+        pk: *mut u8,
+        sk: *mut u8,
+        coins: *const u8,
+    ) -> c_int;
+    #[cfg(enable_aarch64_neon)]
     pub fn PQCLEAN_MLKEM1024_AARCH64_crypto_kem_enc(
         ct: *mut u8,
         ss: *mut u8,
@@ -28,18 +28,21 @@ pub mod mlkem768;
 pub use crate::mlkem1024::{
     ciphertext_bytes as mlkem1024_ciphertext_bytes, decapsulate as mlkem1024_decapsulate,
     encapsulate as mlkem1024_encapsulate, keypair as mlkem1024_keypair,
+    keypair_from_seed as mlkem1024_keypair_from_seed,
     public_key_bytes as mlkem1024_public_key_bytes, secret_key_bytes as mlkem1024_secret_key_bytes,
-    shared_secret_bytes as mlkem1024_shared_secret_bytes,
+    seed_bytes as mlkem1024_seed_bytes, shared_secret_bytes as mlkem1024_shared_secret_bytes,
 };
 pub use crate::mlkem512::{
     ciphertext_bytes as mlkem512_ciphertext_bytes, decapsulate as mlkem512_decapsulate,
     encapsulate as mlkem512_encapsulate, keypair as mlkem512_keypair,
-    public_key_bytes as mlkem512_public_key_bytes, secret_key_bytes as mlkem512_secret_key_bytes,
+    keypair_from_seed as mlkem512_keypair_from_seed, public_key_bytes as mlkem512_public_key_bytes,
+    secret_key_bytes as mlkem512_secret_key_bytes, seed_bytes as mlkem512_seed_bytes,
     shared_secret_bytes as mlkem512_shared_secret_bytes,
 };
 pub use crate::mlkem768::{
     ciphertext_bytes as mlkem768_ciphertext_bytes, decapsulate as mlkem768_decapsulate,
     encapsulate as mlkem768_encapsulate, keypair as mlkem768_keypair,
-    public_key_bytes as mlkem768_public_key_bytes, secret_key_bytes as mlkem768_secret_key_bytes,
+    keypair_from_seed as mlkem768_keypair_from_seed, public_key_bytes as mlkem768_public_key_bytes,
+    secret_key_bytes as mlkem768_secret_key_bytes, seed_bytes as mlkem768_seed_bytes,
     shared_secret_bytes as mlkem768_shared_secret_bytes,
 };
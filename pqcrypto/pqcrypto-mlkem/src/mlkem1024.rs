@@ -148,6 +148,40 @@ pub fn keypair() -> (PublicKey, SecretKey) {
     gen_keypair!(PQCLEAN_MLKEM1024_CLEAN_crypto_kem_keypair)
 }
 
+/// Get the number of bytes of the seed taken by [`keypair_from_seed`]
+pub const fn seed_bytes() -> usize {
+    64
+}
+
+macro_rules! gen_keypair_from_seed {
+    ($variant:ident, $seed:ident) => {{
+        let mut pk = PublicKey::new();
+        let mut sk = SecretKey::new();
+        assert_eq!(
+            unsafe { ffi::$variant(pk.0.as_mut_ptr(), sk.0.as_mut_ptr(), $seed.as_ptr()) },
+            0
+        );
+        (pk, sk)
+    }};
+}
+
+/// Deterministically generate a ml-kem-1024 keypair from the seeds `d || z` of FIPS 203
+pub fn keypair_from_seed(seed: &[u8; 64]) -> (PublicKey, SecretKey) {
+    #[cfg(all(enable_x86_avx2, feature = "avx2"))]
+    {
+        if std::is_x86_feature_detected!("avx2") {
+            return gen_keypair_from_seed!(PQCLEAN_MLKEM1024_AVX2_crypto_kem_keypair_derand, seed);
+        }
+    }
+    #[cfg(all(enable_aarch64_neon, feature = "neon"))]
+    {
+        if true {
+            return gen_keypair_from_seed!(PQCLEAN_MLKEM1024_AARCH64_keypair_derand, seed);
+        }
+    }
+    gen_keypair_from_seed!(PQCLEAN_MLKEM1024_CLEAN_crypto_kem_keypair_derand, seed)
+}
+
 macro_rules! encap {
     ($variant:ident, $pk:ident) => {{
         let mut ss = SharedSecret::new();
@@ -216,4 +250,27 @@ mod test {
         let ss2 = decapsulate(&ct, &sk);
         assert_eq!(&ss1.0[..], &ss2.0[..], "Difference in shared secrets!");
     }
+
+    #[test]
+    pub fn test_keypair_from_seed() {
+        let seed = [7u8; 64];
+        let (pk, sk) = keypair_from_seed(&seed);
+        let (pk2, sk2) = keypair_from_seed(&seed);
+        assert!(pk == pk2 && sk == sk2);
+        assert!(keypair_from_seed(&[8u8; 64]).0 != pk);
+
+        // Every implementation derives the same keys.
+        let (mut clean_pk, mut clean_sk) = (PublicKey::new(), SecretKey::new());
+        assert_eq!(
+            unsafe {
+                ffi::PQCLEAN_MLKEM1024_CLEAN_crypto_kem_keypair_derand(
+                    clean_pk.0.as_mut_ptr(),
+                    clean_sk.0.as_mut_ptr(),
+                    seed.as_ptr(),
+                )
+            },
+            0
+        );
+        assert!(clean_pk == pk && clean_sk == sk);
+    }
 }